@@ -0,0 +1,17 @@
+use std::process::Command;
+
+fn main() {
+    // printed by `rock version --verbose`, missing when built outside of a git repo
+    println!("cargo:rerun-if-changed=../.git/HEAD");
+    println!("cargo:rerun-if-changed=../.git/refs/heads");
+
+    let output = Command::new("git")
+        .args(["rev-parse", "--short=10", "HEAD"])
+        .output();
+    if let Ok(output) = output {
+        if output.status.success() {
+            let hash = String::from_utf8_lossy(&output.stdout);
+            println!("cargo:rustc-env=ROCK_COMMIT_HASH={}", hash.trim());
+        }
+    }
+}
@@ -1,17 +1,113 @@
-pub const RESET: &str = "\x1B[0m";
-pub const BLACK: &str = "\x1B[0;30m";
-pub const BLACK_BOLD: &str = "\x1B[1;30m";
-pub const RED: &str = "\x1B[0;31m";
-pub const RED_BOLD: &str = "\x1B[1;31m";
-pub const GREEN: &str = "\x1B[0;32m";
-pub const GREEN_BOLD: &str = "\x1B[1;32m";
-pub const YELLOW: &str = "\x1B[0;33m";
-pub const YELLOW_BOLD: &str = "\x1B[1;33m";
-pub const BLUE: &str = "\x1B[0;34m";
-pub const BLUE_BOLD: &str = "\x1B[1;34m";
-pub const MAGENTA: &str = "\x1B[0;35m";
-pub const MAGENTA_BOLD: &str = "\x1B[1;35m";
-pub const CYAN: &str = "\x1B[0;36m";
-pub const CYAN_BOLD: &str = "\x1B[1;36m";
-pub const WHITE: &str = "\x1B[0;37m";
-pub const WHITE_BOLD: &str = "\x1B[1;37m";
+use std::fmt;
+use std::io::IsTerminal;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+#[derive(Copy, Clone, PartialEq)]
+pub enum ColorMode {
+    Auto,
+    Always,
+    Never,
+}
+
+impl ColorMode {
+    pub fn from_str(string: &str) -> Option<ColorMode> {
+        match string {
+            "auto" => Some(ColorMode::Auto),
+            "always" => Some(ColorMode::Always),
+            "never" => Some(ColorMode::Never),
+            _ => None,
+        }
+    }
+}
+
+static COLOR_ENABLED: AtomicBool = AtomicBool::new(false);
+
+pub fn init(mode: ColorMode) {
+    let enabled = match mode {
+        ColorMode::Auto => color_detect() && enable_virtual_terminal(),
+        ColorMode::Always => {
+            enable_virtual_terminal();
+            true
+        }
+        ColorMode::Never => false,
+    };
+    COLOR_ENABLED.store(enabled, Ordering::Relaxed);
+}
+
+// https://no-color.org, any non-empty value disables colors
+fn color_detect() -> bool {
+    if std::env::var_os("NO_COLOR").is_some_and(|value| !value.is_empty()) {
+        return false;
+    }
+    if std::env::var_os("TERM").is_some_and(|term| term == "dumb") {
+        return false;
+    }
+    std::io::stdout().is_terminal() && std::io::stderr().is_terminal()
+}
+
+// windows consoles only interpret escape codes with VT processing enabled
+#[cfg(windows)]
+#[allow(unsafe_code)]
+fn enable_virtual_terminal() -> bool {
+    type Handle = *mut std::ffi::c_void;
+    const STD_OUTPUT_HANDLE: u32 = -11_i32 as u32;
+    const STD_ERROR_HANDLE: u32 = -12_i32 as u32;
+    const ENABLE_VIRTUAL_TERMINAL_PROCESSING: u32 = 0x0004;
+
+    #[link(name = "kernel32")]
+    extern "system" {
+        fn GetStdHandle(std_handle: u32) -> Handle;
+        fn GetConsoleMode(console: Handle, mode: *mut u32) -> i32;
+        fn SetConsoleMode(console: Handle, mode: u32) -> i32;
+    }
+
+    [STD_OUTPUT_HANDLE, STD_ERROR_HANDLE]
+        .into_iter()
+        .all(|std_handle| unsafe {
+            let handle = GetStdHandle(std_handle);
+            let mut mode = 0;
+            if GetConsoleMode(handle, &mut mode) == 0 {
+                return false;
+            }
+            mode & ENABLE_VIRTUAL_TERMINAL_PROCESSING != 0
+                || SetConsoleMode(handle, mode | ENABLE_VIRTUAL_TERMINAL_PROCESSING) != 0
+        })
+}
+
+#[cfg(not(windows))]
+fn enable_virtual_terminal() -> bool {
+    true
+}
+
+/// escape code which is only written when colors are enabled
+#[derive(Copy, Clone)]
+pub struct Ansi(&'static str);
+
+impl fmt::Display for Ansi {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if COLOR_ENABLED.load(Ordering::Relaxed) {
+            f.write_str(self.0)
+        } else {
+            Ok(())
+        }
+    }
+}
+
+pub const RESET: Ansi = Ansi("\x1B[0m");
+pub const BLACK: Ansi = Ansi("\x1B[0;30m");
+pub const BLACK_BOLD: Ansi = Ansi("\x1B[1;30m");
+pub const RED: Ansi = Ansi("\x1B[0;31m");
+pub const RED_BOLD: Ansi = Ansi("\x1B[1;31m");
+pub const GREEN: Ansi = Ansi("\x1B[0;32m");
+pub const GREEN_BOLD: Ansi = Ansi("\x1B[1;32m");
+pub const YELLOW: Ansi = Ansi("\x1B[0;33m");
+pub const YELLOW_BOLD: Ansi = Ansi("\x1B[1;33m");
+pub const BLUE: Ansi = Ansi("\x1B[0;34m");
+pub const BLUE_BOLD: Ansi = Ansi("\x1B[1;34m");
+pub const MAGENTA: Ansi = Ansi("\x1B[0;35m");
+pub const MAGENTA_BOLD: Ansi = Ansi("\x1B[1;35m");
+pub const CYAN: Ansi = Ansi("\x1B[0;36m");
+pub const CYAN_BOLD: Ansi = Ansi("\x1B[1;36m");
+pub const WHITE: Ansi = Ansi("\x1B[0;37m");
+pub const WHITE_BOLD: Ansi = Ansi("\x1B[1;37m");
+pub const CLEAR: Ansi = Ansi("\x1B[2J\x1B[H");
@@ -0,0 +1,183 @@
+use super::DocFormat;
+use rock_core::hir_lower::{DocSpan, ItemDocs, ModuleDocs, PackageDocs};
+use rock_core::session::ModuleID;
+
+/// file name and content of each generated page, `index` page comes first
+pub fn render(docs: &PackageDocs, format: DocFormat) -> Vec<(String, String)> {
+    let mut pages = Vec::with_capacity(docs.modules.len() + 1);
+    pages.push((page_name("index", format), index_page(docs, format)));
+    for module in docs.modules.iter() {
+        let page = match format {
+            DocFormat::Markdown => module_markdown(docs, module),
+            DocFormat::Html => module_html(docs, module),
+        };
+        pages.push((module_page_name(module, format), page));
+    }
+    pages
+}
+
+fn index_page(docs: &PackageDocs, format: DocFormat) -> String {
+    let mut page = String::new();
+    match format {
+        DocFormat::Markdown => {
+            page.push_str(&format!("# {}\n\n", docs.name));
+            for module in docs.modules.iter() {
+                let name = module_page_name(module, format);
+                page.push_str(&format!("- [{}]({name})\n", module.path));
+            }
+        }
+        DocFormat::Html => {
+            html_begin(&mut page, &docs.name);
+            page.push_str(&format!("<h1>{}</h1>\n<ul>\n", html_escape(&docs.name)));
+            for module in docs.modules.iter() {
+                let name = module_page_name(module, format);
+                let path = html_escape(&module.path);
+                page.push_str(&format!("<li><a href=\"{name}\">{path}</a></li>\n"));
+            }
+            page.push_str("</ul>\n");
+            html_end(&mut page);
+        }
+    }
+    page
+}
+
+// signatures are html `<pre>` blocks in both formats, markdown code blocks cannot contain links
+fn module_markdown(docs: &PackageDocs, module: &ModuleDocs) -> String {
+    let mut page = format!("# {}\n\n[{}](index.md)\n", module.path, docs.name);
+    for item in module.items.iter() {
+        item_markdown(&mut page, docs, item, "##", None);
+        for method in item.methods.iter() {
+            item_markdown(&mut page, docs, method, "###", Some(&item.name));
+        }
+    }
+    page
+}
+
+fn item_markdown(
+    page: &mut String,
+    docs: &PackageDocs,
+    item: &ItemDocs,
+    heading: &str,
+    owner: Option<&str>,
+) {
+    let anchor = item_anchor(item, owner);
+    let kind = item.kind.as_str();
+    page.push_str(&format!("\n<a id=\"{anchor}\"></a>\n"));
+    page.push_str(&format!("{heading} {kind} `{anchor}`\n\n"));
+    page.push_str(&signature_html(docs, &item.signature, DocFormat::Markdown));
+    if !item.docs.is_empty() {
+        page.push_str(&format!("\n{}\n", item.docs));
+    }
+}
+
+fn module_html(docs: &PackageDocs, module: &ModuleDocs) -> String {
+    let mut page = String::new();
+    html_begin(&mut page, &module.path);
+    page.push_str(&format!(
+        "<h1>{}</h1>\n<p><a href=\"index.html\">{}</a></p>\n",
+        html_escape(&module.path),
+        html_escape(&docs.name),
+    ));
+    for item in module.items.iter() {
+        item_html(&mut page, docs, item, "h2", None);
+        for method in item.methods.iter() {
+            item_html(&mut page, docs, method, "h3", Some(&item.name));
+        }
+    }
+    html_end(&mut page);
+    page
+}
+
+fn item_html(
+    page: &mut String,
+    docs: &PackageDocs,
+    item: &ItemDocs,
+    heading: &str,
+    owner: Option<&str>,
+) {
+    let anchor = item_anchor(item, owner);
+    let kind = item.kind.as_str();
+    page.push_str(&format!(
+        "<{heading} id=\"{anchor}\">{kind} <code>{anchor}</code></{heading}>\n"
+    ));
+    page.push_str(&signature_html(docs, &item.signature, DocFormat::Html));
+    // blank lines separate paragraphs, same as in markdown
+    for paragraph in item
+        .docs
+        .split("\n\n")
+        .filter(|text| !text.trim().is_empty())
+    {
+        page.push_str(&format!("<p>{}</p>\n", html_escape(paragraph)));
+    }
+}
+
+fn signature_html(docs: &PackageDocs, signature: &[DocSpan], format: DocFormat) -> String {
+    let mut html = String::from("<pre><code>");
+    for span in signature.iter() {
+        let text = html_escape(&span.text);
+        match &span.link {
+            Some(link) => {
+                let page = link_page_name(docs, link.module_id, format);
+                html.push_str(&format!("<a href=\"{page}#{}\">{text}</a>", link.name));
+            }
+            None => html.push_str(&text),
+        }
+    }
+    html.push_str("</code></pre>\n");
+    html
+}
+
+// methods are prefixed by their type, item names are unique within a module
+fn item_anchor(item: &ItemDocs, owner: Option<&str>) -> String {
+    match owner {
+        Some(owner) => format!("{owner}.{}", item.name),
+        None => item.name.clone(),
+    }
+}
+
+fn link_page_name(docs: &PackageDocs, module_id: ModuleID, format: DocFormat) -> String {
+    match docs
+        .modules
+        .iter()
+        .find(|module| module.module_id == module_id)
+    {
+        Some(module) => module_page_name(module, format),
+        None => page_name("index", format),
+    }
+}
+
+// pages are placed in a single directory, `dir/module` becomes `dir.module`
+fn module_page_name(module: &ModuleDocs, format: DocFormat) -> String {
+    page_name(&module.path.replace('/', "."), format)
+}
+
+fn page_name(name: &str, format: DocFormat) -> String {
+    match format {
+        DocFormat::Markdown => format!("{name}.md"),
+        DocFormat::Html => format!("{name}.html"),
+    }
+}
+
+fn html_begin(page: &mut String, title: &str) {
+    page.push_str("<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n");
+    page.push_str(&format!("<title>{}</title>\n", html_escape(title)));
+    page.push_str("</head>\n<body>\n");
+}
+
+fn html_end(page: &mut String) {
+    page.push_str("</body>\n</html>\n");
+}
+
+fn html_escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
@@ -1,31 +1,55 @@
-use super::{Command, CommandBuild, CommandNew, CommandRun};
+use super::doc;
+use super::spec::{CommandSpec, GLOBAL_OPTIONS};
+use super::{
+    Command, CommandBuild, CommandCheck, CommandClean, CommandDoc, CommandExplain, CommandFmt,
+    CommandGraph, CommandHelp, CommandInit, CommandNew, CommandRun, CommandTest, CommandVersion,
+    DocFormat, Emit, GraphFormat,
+};
 use crate::ansi;
-use crate::error_format;
-use rock_core::ast_parse;
+use crate::error_format::{self, MessageFormat};
+use crate::report::{self, ExitCode, Summary};
+use rock_core::build_layout;
 #[cfg(not(target_os = "linux"))]
 use rock_core::codegen;
-use rock_core::error::{DiagnosticCollection, ErrorComp, ResultComp, WarningComp};
+use rock_core::driver::{Compilation, CompileOptions, Compiled, EmitKind, Stage, Timing, Timings};
+use rock_core::error::{DiagnosticCollection, ErrorComp, LintConfig, ResultComp, WarningComp};
+use rock_core::format;
 use rock_core::fs_env;
-use rock_core::hir_lower;
+use rock_core::hir_lower::{self, ModuleGraph, PackageDocs};
 use rock_core::intern::InternPool;
 use rock_core::package;
 use rock_core::package::manifest::{BuildManifest, Manifest, PackageKind, PackageManifest};
-use rock_core::package::semver::Semver;
-use rock_core::session::Session;
+use rock_core::package::semver::{Semver, SemverReq};
+use rock_core::session::{ModuleID, Session};
+use rock_core::target::TargetTriple;
+use rock_core::text;
+use rock_core::vfs::RealFs;
 use std::collections::BTreeMap;
+use std::path::PathBuf;
+use std::time::{Duration, Instant, SystemTime};
 
 pub fn command(command: Command) -> Result<(), ErrorComp> {
     match command {
         Command::New(data) => new(data),
-        Command::Check => check(),
+        Command::Init(data) => init(data),
+        Command::Check(data) => check(data),
         Command::Build(data) => build(data),
         Command::Run(data) => run(data),
-        Command::Help => {
-            help();
+        Command::Test(data) => test(data),
+        Command::Clean(data) => clean(data),
+        Command::Fmt(data) => fmt(data),
+        Command::Graph(data) => graph(data),
+        Command::Doc(data) => doc(data),
+        Command::Explain(data) => {
+            explain(data);
             Ok(())
         }
-        Command::Version => {
-            version();
+        Command::Help(data) => {
+            help(data);
+            Ok(())
+        }
+        Command::Version(data) => {
+            version(data);
             Ok(())
         }
     }
@@ -34,57 +58,104 @@ pub fn command(command: Command) -> Result<(), ErrorComp> {
 pub fn new(data: CommandNew) -> Result<(), ErrorComp> {
     let cwd = fs_env::dir_get_current_working()?;
     let root_dir = cwd.join(&data.name);
-    let src_dir = root_dir.join("src");
 
-    package_name_check(&data.name)?;
+    package::package_name_check(&data.name)?;
     fs_env::dir_create(&root_dir, true)?;
-    fs_env::dir_create(&src_dir, true)?;
+    package_create(&root_dir, &data.name, data.kind, data.no_git)?;
+
+    let g = ansi::GREEN_BOLD;
+    let r = ansi::RESET;
+    println!(
+        "  {g}Created{r} {} `{}` package\n",
+        data.kind.as_str_full(),
+        data.name,
+    );
+    Ok(())
+}
+
+pub fn init(data: CommandInit) -> Result<(), ErrorComp> {
+    let root_dir = fs_env::dir_get_current_working()?;
+    let name = fs_env::filename_stem(&root_dir)?.to_string();
+
+    let manifest_path = root_dir.join("Rock.toml");
+    if manifest_path.exists() {
+        return Err(ErrorComp::message(format!(
+            "cannot initialize package, manifest already exists\npath: `{}`",
+            manifest_path.to_string_lossy()
+        )));
+    }
+    package::package_name_check(&name)?;
+    package_create(&root_dir, &name, data.kind, data.no_git)?;
+
+    let g = ansi::GREEN_BOLD;
+    let r = ansi::RESET;
+    println!(
+        "  {g}Initialized{r} {} `{}` package\n",
+        data.kind.as_str_full(),
+        name,
+    );
+    Ok(())
+}
+
+// existing files are kept when initializing inside non-empty directory
+fn package_create(
+    root_dir: &PathBuf,
+    name: &str,
+    kind: PackageKind,
+    no_git: bool,
+) -> Result<(), ErrorComp> {
+    let src_dir = root_dir.join("src");
+    fs_env::dir_create(&src_dir, false)?;
 
     const IMPORT_CORE_IO: &str = "import core/libc as io;\n\n";
-    match data.kind {
+    match kind {
         PackageKind::Bin => {
             let bin_content = format!(
                 "{IMPORT_CORE_IO}proc main() -> s32 {{\n    io.printf(c\"Bin `{}` works\\n\");\n    return 0;\n}}\n",
-                data.name
+                name
             );
-            fs_env::file_create_or_rewrite(&src_dir.join("main.rock"), &bin_content)?
+            file_create_if_missing(&src_dir.join("main.rock"), &bin_content)?;
         }
         PackageKind::Lib => {
             let lib_content = format!(
                 "{IMPORT_CORE_IO}proc test() {{\n    io.printf(c\"Lib `{}` works\\n\");\n}}\n",
-                data.name
+                name
             );
-            fs_env::file_create_or_rewrite(&src_dir.join("test.rock"), &lib_content)?;
+            file_create_if_missing(&src_dir.join("lib.rock"), &lib_content)?;
         }
     }
 
     {
         let package = PackageManifest {
-            name: data.name.clone(),
-            kind: data.kind,
+            name: name.to_string(),
+            kind,
             version: Semver::new(0, 1, 0),
             authors: None,
             repository: None,
             description: None,
         };
 
-        let build = match data.kind {
+        let build = match kind {
             PackageKind::Bin => BuildManifest {
-                bin_name: Some(data.name.clone()),
+                bin_name: Some(name.to_string()),
+                opt_level: None,
                 nodefaultlib: None,
                 lib_paths: None,
                 links: None,
+                unstable_generics: None,
             },
             PackageKind::Lib => BuildManifest {
                 bin_name: None,
+                opt_level: None,
                 nodefaultlib: None,
                 lib_paths: None,
                 links: None,
+                unstable_generics: None,
             },
         };
 
         let mut dependencies = BTreeMap::new();
-        dependencies.insert("core".to_string(), rock_core::VERSION);
+        dependencies.insert("core".to_string(), SemverReq::caret(rock_core::VERSION));
 
         let manifest = Manifest {
             package,
@@ -92,127 +163,721 @@ pub fn new(data: CommandNew) -> Result<(), ErrorComp> {
             dependencies,
         };
 
+        // generated manifest must be readable by `Session`
+        let manifest_path = root_dir.join("Rock.toml");
         let manifest_text = package::manifest_serialize(&manifest)?;
-        fs_env::file_create_or_rewrite(&root_dir.join("Rock.toml"), &manifest_text)?;
+        package::manifest_deserialize(manifest_text.clone(), &manifest_path)?;
+        fs_env::file_create_or_rewrite(&manifest_path, &manifest_text)?;
     }
 
-    if !data.no_git {
-        fs_env::file_create_or_rewrite(&root_dir.join(".gitignore"), "build/\n")?;
-        fs_env::file_create_or_rewrite(&root_dir.join("README.md"), &format!("# {}\n", data.name))?;
-        fs_env::dir_set_current_working(&root_dir)?;
+    if !no_git {
+        file_create_if_missing(&root_dir.join(".gitignore"), "build/\n")?;
+        file_create_if_missing(&root_dir.join("README.md"), &format!("# {}\n", name))?;
 
-        std::process::Command::new("git")
-            .arg("init")
-            .stdout(std::process::Stdio::null())
-            .status()
-            .map_err(|io_error| {
-                ErrorComp::message(format!(
-                    "failed to initialize git repository\nreason: {}",
-                    io_error
-                ))
-            })?;
+        if !root_dir.join(".git").exists() {
+            std::process::Command::new("git")
+                .arg("init")
+                .current_dir(root_dir)
+                .stdout(std::process::Stdio::null())
+                .status()
+                .map_err(|io_error| {
+                    ErrorComp::message(format!(
+                        "failed to initialize git repository\nreason: {}",
+                        io_error
+                    ))
+                })?;
+        }
     }
+    Ok(())
+}
 
-    let g = ansi::GREEN_BOLD;
+fn file_create_if_missing(path: &PathBuf, text: &str) -> Result<(), ErrorComp> {
+    if path.exists() {
+        return Ok(());
+    }
+    fs_env::file_create_or_rewrite(path, text)
+}
+
+fn check(data: CommandCheck) -> Result<(), ErrorComp> {
+    if data.watch {
+        return check_watch(data);
+    }
+    let start = Instant::now();
+    let (session, intern_name) = Session::new(false, data.target, &RealFs)?;
+    let timings = session_timings(data.timings, start);
+    let result = check_impl(&session, intern_name, &data, timings);
+    let diagnostics = DiagnosticCollection::from_result(result);
+    let summary = Summary::new(Some(&session), &diagnostics, start.elapsed());
+    error_format::print_errors(Some(&session), diagnostics, data.message_format);
+    report::print_summary(&summary, "Checked", data.message_format);
+    summary.exit_code.exit_on_failure();
+    Ok(())
+}
+
+fn check_impl(
+    session: &Session,
+    intern_name: InternPool,
+    data: &CommandCheck,
+    timings: Option<Timings>,
+) -> Result<Vec<WarningComp>, DiagnosticCollection> {
+    let mut options = compile_options(Stage::Check, data.target, &data.lint_config);
+    options.error_limit = data.error_limit;
+    if let Some(emit) = &data.emit {
+        options.emit.set(emit.kind);
+    }
+    let mut compilation = compile(session, intern_name, &options, timings);
+    let result = emit_output(&data.emit, std::mem::take(&mut compilation.emitted));
+    let (_, warnings) = compilation.into_result()?;
+    let (_, warnings) = ResultComp::from_error(result).into_result(warnings)?;
+    Ok(warnings)
+}
+
+fn compile<'ast, 'hir, 'intern: 'hir>(
+    session: &Session,
+    intern_name: InternPool<'intern>,
+    options: &CompileOptions,
+    timings: Option<Timings>,
+) -> Compilation<'ast, 'hir, 'intern> {
+    let mut compilation = rock_core::compile(session, intern_name, options);
+    if let Some(mut timings) = timings {
+        timings.extend(std::mem::take(&mut compilation.timings));
+        print_timings(&timings);
+    }
+    compilation
+}
+
+// `Session` is created before the driver runs, its load time is recorded first
+fn session_timings(enabled: bool, start: Instant) -> Option<Timings> {
+    if !enabled {
+        return None;
+    }
+    let mut timings = Timings::new();
+    timings.stage("session load", start.elapsed(), None);
+    Some(timings)
+}
+
+const TIMINGS_FILE_COUNT: usize = 10;
+
+// printed to stderr, stdout can hold json diagnostics or `--emit` output
+fn print_timings(timings: &Timings) {
+    let c = ansi::CYAN_BOLD;
     let r = ansi::RESET;
-    println!(
-        "  {g}Created{r} {} `{}` package\n",
-        data.kind.as_str_full(),
-        data.name,
+    let name_width = timings
+        .stages
+        .iter()
+        .map(|timing| timing.name.len())
+        .max()
+        .unwrap_or(0)
+        .max("total".len());
+
+    eprintln!("  {c}Timings{r}");
+    for timing in timings.stages.iter() {
+        eprintln!(
+            "    {:<name_width$}  {:>10}  {:>10}",
+            timing.name,
+            format_duration(timing.duration),
+            timing.mem_usage.map(format_mem_usage).unwrap_or_default(),
+        );
+    }
+    eprintln!(
+        "    {:<name_width$}  {:>10}",
+        "total",
+        format_duration(timings.total())
     );
+
+    let mut files: Vec<&Timing> = timings.files.iter().collect();
+    files.sort_by_key(|timing| std::cmp::Reverse(timing.duration));
+    if !files.is_empty() {
+        eprintln!("  {c}Slowest files{r}");
+    }
+    for timing in files.iter().take(TIMINGS_FILE_COUNT) {
+        eprintln!(
+            "    {:>10}  {}",
+            format_duration(timing.duration),
+            timing.name
+        );
+    }
+    eprintln!();
+
+    fn format_duration(duration: Duration) -> String {
+        format!("{:.2} ms", duration.as_secs_f64() * 1000.0)
+    }
+    fn format_mem_usage(bytes: usize) -> String {
+        format!("{:.1} KiB", bytes as f64 / 1024.0)
+    }
+}
+
+fn compile_options(stage: Stage, target: TargetTriple, lint_config: &LintConfig) -> CompileOptions {
+    let mut options = CompileOptions::new(stage, target);
+    options.lint_config = lint_config.clone();
+    options
+}
+
+// `--emit` output is written for each stage that succeeded
+fn emit_output(emit: &Option<Emit>, emitted: Vec<(EmitKind, String)>) -> Result<(), ErrorComp> {
+    let emit = match emit {
+        Some(emit) => emit,
+        None => return Ok(()),
+    };
+    for (kind, text) in emitted {
+        if kind != emit.kind {
+            continue;
+        }
+        match &emit.path {
+            Some(path) => fs_env::file_create_or_rewrite(path, &text)?,
+            None => print!("{text}"),
+        }
+    }
     Ok(())
 }
 
-fn package_name_check(name: &str) -> Result<(), ErrorComp> {
-    let mut chars = name.chars();
-    if let Some(c) = chars.next() {
-        if !(c == '_' || c.is_ascii_alphabetic()) {
-            return Err(ErrorComp::message(format!(
-                "package name must be a valid identifier, first `{}` is not allowed",
-                c
-            )));
+const WATCH_POLL: Duration = Duration::from_millis(250);
+const WATCH_DEBOUNCE: Duration = Duration::from_millis(100);
+
+//@polling file modification times, std has no file system events
+// session is created from scratch on every change, no incremental reuse yet
+fn check_watch(data: CommandCheck) -> Result<(), ErrorComp> {
+    let root_dir = fs_env::dir_get_current_working()?;
+    let mut files = watch_files(&root_dir);
+
+    loop {
+        check_watch_run(&data);
+
+        loop {
+            std::thread::sleep(WATCH_POLL);
+            let mut changed = watch_files(&root_dir);
+            if changed == files {
+                continue;
+            }
+            // wait for rapid edits to settle
+            loop {
+                std::thread::sleep(WATCH_DEBOUNCE);
+                let settled = watch_files(&root_dir);
+                if settled == changed {
+                    break;
+                }
+                changed = settled;
+            }
+            files = changed;
+            break;
         }
     }
-    for c in chars {
-        if !(c == '_' || c.is_ascii_alphanumeric()) {
-            return Err(ErrorComp::message(format!(
-                "package name must be a valid identifier, inner `{}` is not allowed",
-                c
-            )));
+}
+
+fn check_watch_run(data: &CommandCheck) {
+    if data.message_format == MessageFormat::Human {
+        print!("{}", ansi::CLEAR);
+        let _ = std::io::Write::flush(&mut std::io::stdout());
+    }
+    let start = Instant::now();
+
+    let (session, diagnostics) = match Session::new(false, data.target, &RealFs) {
+        Ok((session, intern_name)) => {
+            let timings = session_timings(data.timings, start);
+            let result = check_impl(&session, intern_name, data, timings);
+            (Some(session), DiagnosticCollection::from_result(result))
         }
+        Err(error) => (None, DiagnosticCollection::new().join_errors(vec![error])),
+    };
+    let summary = Summary::new(session.as_ref(), &diagnostics, start.elapsed());
+    error_format::print_errors(session.as_ref(), diagnostics, data.message_format);
+    report::print_summary(&summary, "Checked", data.message_format);
+
+    if data.message_format == MessageFormat::Human {
+        let c = ansi::CYAN_BOLD;
+        let r = ansi::RESET;
+        eprintln!("  {c}Watching{r} for changes in `src`, press Ctrl+C to stop");
     }
-    Ok(())
 }
 
-fn check() -> Result<(), ErrorComp> {
-    let (session, intern_name) = Session::new(false, None)?;
-    let result = check_impl(&session, intern_name);
-    error_format::print_errors(Some(&session), DiagnosticCollection::from_result(result));
-    return Ok(());
+// sorted source paths with their modification time
+fn watch_files(root_dir: &PathBuf) -> Vec<(PathBuf, SystemTime)> {
+    let mut files = Vec::new();
+    watch_file(root_dir.join("Rock.toml"), &mut files);
+    watch_dir(&root_dir.join("src"), &mut files);
+    files.sort_by(|a, b| a.0.cmp(&b.0));
+    return files;
 
-    fn check_impl(
-        session: &Session,
-        intern_name: InternPool,
-    ) -> Result<Vec<WarningComp>, DiagnosticCollection> {
-        let (ast, warnings) = ast_parse::parse(session, intern_name).into_result(vec![])?;
-        let (_, warnings) = hir_lower::check(ast, session).into_result(warnings)?;
-        Ok(warnings)
+    fn watch_dir(dir: &PathBuf, files: &mut Vec<(PathBuf, SystemTime)>) {
+        let entries = match std::fs::read_dir(dir) {
+            Ok(entries) => entries,
+            Err(_) => return,
+        };
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if path.is_dir() {
+                watch_dir(&path, files);
+            } else if fs_env::file_extension(&path) == Some("rock") {
+                watch_file(path, files);
+            }
+        }
+    }
+
+    fn watch_file(path: PathBuf, files: &mut Vec<(PathBuf, SystemTime)>) {
+        if let Ok(modified) = std::fs::metadata(&path).and_then(|meta| meta.modified()) {
+            files.push((path, modified));
+        }
     }
 }
 
 fn build(data: CommandBuild) -> Result<(), ErrorComp> {
-    let (session, intern_name) = Session::new(true, None)?;
-    let result = build_impl(&session, intern_name, data);
-    error_format::print_errors(Some(&session), DiagnosticCollection::from_result(result));
+    let start = Instant::now();
+    let (session, intern_name) = Session::new(true, data.target, &RealFs)?;
+    let timings = session_timings(data.timings, start);
+    let message_format = data.message_format;
+    let result = build_impl(&session, intern_name, data, timings);
+    let diagnostics = DiagnosticCollection::from_result(result);
+    let summary = Summary::new(Some(&session), &diagnostics, start.elapsed());
+    error_format::print_errors(Some(&session), diagnostics, message_format);
+    report::print_summary(&summary, "Built", message_format);
+    summary.exit_code.exit_on_failure();
     return Ok(());
 
     fn build_impl(
         session: &Session,
         intern_name: InternPool,
         data: CommandBuild,
+        timings: Option<Timings>,
     ) -> Result<Vec<WarningComp>, DiagnosticCollection> {
-        let (ast, warnings) = ast_parse::parse(session, intern_name).into_result(vec![])?;
-        let (hir, warnings) = hir_lower::check(ast, session).into_result(warnings)?;
-        let diagnostics = DiagnosticCollection::new().join_warnings(warnings);
-        error_format::print_errors(Some(session), diagnostics);
-
-        #[cfg(not(target_os = "linux"))]
-        {
-            let result = codegen::codegen(hir, session, data.kind, data.emit_llvm, None);
-            let (_, warnings) = ResultComp::from_error(result).into_result(vec![])?;
-            return Ok(warnings);
+        let mut options = compile_options(Stage::Build, data.target, &data.lint_config);
+        options.build_kind = data.kind;
+        options.error_limit = data.error_limit;
+        options.emit_llvm = data.emit_llvm;
+        if let Some(emit) = &data.emit {
+            options.emit.set(emit.kind);
         }
-        Ok(vec![])
+        let mut compilation = compile(session, intern_name, &options, timings);
+        let result = emit_output(&data.emit, std::mem::take(&mut compilation.emitted));
+        let (_, warnings) = compilation.into_result()?;
+        let (_, warnings) = ResultComp::from_error(result).into_result(warnings)?;
+        Ok(warnings)
     }
 }
 
 fn run(data: CommandRun) -> Result<(), ErrorComp> {
-    let (session, intern_name) = Session::new(true, None)?;
-    let result = run_impl(&session, intern_name, data);
-    error_format::print_errors(Some(&session), DiagnosticCollection::from_result(result));
-    return Ok(());
+    check_target_runnable(data.target)?;
+    let start = Instant::now();
+    let (session, intern_name) = Session::new(true, data.target, &RealFs)?;
+    let timings = session_timings(data.timings, start);
+    let message_format = data.message_format;
+    let result = run_impl(&session, intern_name, data, timings);
 
-    fn run_impl(
-        session: &Session,
-        intern_name: InternPool,
-        data: CommandRun,
-    ) -> Result<Vec<WarningComp>, DiagnosticCollection> {
-        let (ast, warnings) = ast_parse::parse(session, intern_name).into_result(vec![])?;
-        let (hir, warnings) = hir_lower::check(ast, session).into_result(warnings)?;
-        let diagnostics = DiagnosticCollection::new().join_warnings(warnings);
-        error_format::print_errors(Some(session), diagnostics);
+    match result {
+        Ok(Some(exit_code)) => std::process::exit(exit_code),
+        Ok(None) => Ok(()),
+        Err(diagnostics) => {
+            let exit_code = ExitCode::from_diagnostics(&diagnostics);
+            error_format::print_errors(Some(&session), diagnostics, message_format);
+            exit_code.exit_on_failure();
+            Ok(())
+        }
+    }
+}
+
+fn run_impl(
+    session: &Session,
+    intern_name: InternPool,
+    data: CommandRun,
+    timings: Option<Timings>,
+) -> Result<Option<i32>, DiagnosticCollection> {
+    let mut options = compile_options(Stage::Build, data.target, &data.lint_config);
+    options.build_kind = data.kind;
+    options.error_limit = data.error_limit;
+    options.emit_llvm = data.emit_llvm;
+    let (compiled, warnings) = compile(session, intern_name, &options, timings).into_result()?;
+    let diagnostics = DiagnosticCollection::new().join_warnings(warnings);
+    error_format::print_errors(Some(session), diagnostics, data.message_format);
 
+    match compiled {
         #[cfg(not(target_os = "linux"))]
-        {
-            let result = codegen::codegen(hir, session, data.kind, data.emit_llvm, Some(data.args));
-            let (_, warnings) = ResultComp::from_error(result).into_result(vec![])?;
-            return Ok(warnings);
+        Compiled::Built(built) => match built.executable_path {
+            Some(executable_path) => {
+                let result = codegen::run_executable(&executable_path, data.args);
+                let (exit_code, _) = ResultComp::from_error(result).into_result(vec![])?;
+                Ok(Some(exit_code))
+            }
+            None => Ok(None),
+        },
+        _ => Ok(None),
+    }
+}
+
+fn test(data: CommandTest) -> Result<(), ErrorComp> {
+    check_target_runnable(data.target)?;
+    let start = Instant::now();
+    let (session, intern_name) = Session::new(true, data.target, &RealFs)?;
+    let timings = session_timings(data.timings, start);
+    let message_format = data.message_format;
+    let result = test_impl(&session, intern_name, data, timings);
+
+    match result {
+        Ok(true) => Ok(()),
+        Ok(false) => std::process::exit(ExitCode::Errors as i32),
+        Err(diagnostics) => {
+            let exit_code = ExitCode::from_diagnostics(&diagnostics);
+            error_format::print_errors(Some(&session), diagnostics, message_format);
+            exit_code.exit_on_failure();
+            Ok(())
+        }
+    }
+}
+
+// returns false if any of the tests failed
+fn test_impl(
+    session: &Session,
+    intern_name: InternPool,
+    data: CommandTest,
+    timings: Option<Timings>,
+) -> Result<bool, DiagnosticCollection> {
+    let mut options = compile_options(Stage::Build, data.target, &data.lint_config);
+    options.build_kind = data.kind;
+    options.error_limit = data.error_limit;
+    options.tests = true;
+    let (compiled, warnings) = compile(session, intern_name, &options, timings).into_result()?;
+    let diagnostics = DiagnosticCollection::new().join_warnings(warnings);
+    error_format::print_errors(Some(session), diagnostics, data.message_format);
+
+    let built = match compiled {
+        Compiled::Built(built) => built,
+        _ => unreachable!(),
+    };
+    let mut tests = Vec::with_capacity(built.test_names.len());
+    for (idx, name) in built.test_names.iter().enumerate() {
+        let selected = match &data.filter {
+            Some(filter) => name.contains(filter.as_str()),
+            None => true,
+        };
+        if selected {
+            tests.push((idx, name.clone()));
+        }
+    }
+
+    let c = ansi::CYAN_BOLD;
+    let r = ansi::RESET;
+    let filtered_count = built.test_names.len() - tests.len();
+    println!(
+        "  {c}Running{r} {} test{}, {} filtered out",
+        tests.len(),
+        if tests.len() == 1 { "" } else { "s" },
+        filtered_count,
+    );
+
+    #[cfg(not(target_os = "linux"))]
+    if let Some(executable_path) = &built.executable_path {
+        let g = ansi::GREEN_BOLD;
+        let red = ansi::RED_BOLD;
+
+        let mut failed = Vec::new();
+        for (idx, name) in tests.iter() {
+            let args = vec![idx.to_string()];
+            let result = codegen::run_executable_captured(executable_path, args);
+            let ((exit_code, output), _) = ResultComp::from_error(result).into_result(vec![])?;
+
+            if exit_code == 0 {
+                println!("  test {name} ... {g}ok{r}");
+            } else {
+                println!("  test {name} ... {red}FAILED{r}");
+                failed.push((name, exit_code, output));
+            }
+        }
+
+        for (name, exit_code, output) in failed.iter() {
+            println!("\n  {red}---- {name} ----{r} exit code: {exit_code}");
+            for line in output.lines() {
+                println!("  {line}");
+            }
+        }
+
+        let passed_count = tests.len() - failed.len();
+        let status = if failed.is_empty() { g } else { red };
+        println!(
+            "\n  {status}Finished{r} {} passed, {} failed",
+            passed_count,
+            failed.len()
+        );
+        return Ok(failed.is_empty());
+    }
+    Ok(true)
+}
+
+// executables built for other targets can only be built, not run
+fn check_target_runnable(target: TargetTriple) -> Result<(), ErrorComp> {
+    let host = TargetTriple::host();
+    if target == host {
+        return Ok(());
+    }
+    Err(ErrorComp::message(format!(
+        "cannot run executable built for `{}` on host `{}`\nuse `rock build --target {}` instead",
+        target.as_str(),
+        host.as_str(),
+        target.as_str()
+    )))
+}
+
+// build directory is located by the manifest, same as for `build`
+fn clean(data: CommandClean) -> Result<(), ErrorComp> {
+    let root_dir = fs_env::dir_get_current_working()?;
+    let manifest_path = root_dir.join("Rock.toml");
+    if !manifest_path.exists() {
+        return Err(ErrorComp::message(format!(
+            "could not find manifest `Rock.toml` in current directory\npath: `{}`",
+            manifest_path.to_string_lossy()
+        )));
+    }
+    build_layout::clean(&root_dir, data.kind, TargetTriple::host())?;
+
+    let g = ansi::GREEN_BOLD;
+    let r = ansi::RESET;
+    match data.kind {
+        Some(kind) => println!("  {g}Cleaned{r} `build/{}` directory\n", kind.as_str()),
+        None => println!("  {g}Cleaned{r} `build` directory\n"),
+    }
+    Ok(())
+}
+
+// only root package modules are formatted
+fn fmt(data: CommandFmt) -> Result<(), ErrorComp> {
+    let (session, _) = Session::new(false, TargetTriple::host(), &RealFs)?;
+    let mut diagnostics = DiagnosticCollection::new();
+
+    for module_id in session.module_ids() {
+        let module = session.module(module_id);
+        if module.package_id != Session::ROOT_ID {
+            continue;
+        }
+        let formatted = match format::format(&module.source, module_id) {
+            Ok(formatted) => formatted,
+            Err(errors) => {
+                diagnostics = diagnostics.join_errors(errors);
+                continue;
+            }
+        };
+        if formatted == module.source {
+            continue;
+        }
+        if data.check {
+            diagnostics.error(ErrorComp::message(format!(
+                "module `{}` is not formatted",
+                module.path.to_string_lossy()
+            )));
+        } else {
+            let mut formatted = formatted;
+            if module.bom {
+                formatted.insert(0, text::BOM);
+            }
+            fs_env::file_create_or_rewrite(&module.path, &formatted)?;
+        }
+    }
+
+    let failed = !diagnostics.errors().is_empty();
+    error_format::print_errors(Some(&session), diagnostics, MessageFormat::Human);
+    if data.check && failed {
+        std::process::exit(ExitCode::Errors as i32);
+    }
+    Ok(())
+}
+
+fn graph(data: CommandGraph) -> Result<(), ErrorComp> {
+    let (session, intern_name) = Session::new(false, TargetTriple::host(), &RealFs)?;
+    let result = graph_impl(&session, intern_name, &data);
+    error_format::print_errors(
+        Some(&session),
+        DiagnosticCollection::from_result(result),
+        MessageFormat::Human,
+    );
+    Ok(())
+}
+
+fn graph_impl(
+    session: &Session,
+    intern_name: InternPool,
+    data: &CommandGraph,
+) -> Result<Vec<WarningComp>, DiagnosticCollection> {
+    let options = CompileOptions::new(Stage::Parse, TargetTriple::host());
+    let (compiled, warnings) = rock_core::compile(session, intern_name, &options).into_result()?;
+    let ast = match compiled {
+        Compiled::Parsed(ast) => *ast,
+        _ => unreachable!(),
+    };
+    let (graph, warnings) = hir_lower::module_graph(ast, session).into_result(warnings)?;
+    match data.format {
+        GraphFormat::Dot => print!("{}", graph_dot(session, &graph)),
+        GraphFormat::Json => println!("{}", graph_json(session, &graph)),
+    }
+    Ok(warnings)
+}
+
+// modules are grouped by package, import edges in a cycle are red
+fn graph_dot(session: &Session, graph: &ModuleGraph) -> String {
+    let mut dot = String::from("digraph modules {\n");
+    for package_id in session.package_ids() {
+        let package = session.package(package_id);
+        let package_name = &package.manifest().package.name;
+        dot.push_str(&format!("    subgraph cluster_{} {{\n", package_id.index()));
+        dot.push_str(&format!("        label = \"{package_name}\";\n"));
+        for module_id in session.module_ids() {
+            if session.module(module_id).package_id == package_id {
+                let idx = module_id.index();
+                let name = hir_lower::module_name(session, module_id);
+                dot.push_str(&format!("        m{idx} [label = \"{name}\"];\n"));
+            }
+        }
+        dot.push_str("    }\n");
+    }
+    for (origin_id, target_id) in graph_edges(graph) {
+        let (origin, target) = (origin_id.index(), target_id.index());
+        if graph.in_cycle(origin_id, target_id) {
+            dot.push_str(&format!("    m{origin} -> m{target} [color = red];\n"));
+        } else {
+            dot.push_str(&format!("    m{origin} -> m{target};\n"));
+        }
+    }
+    dot.push_str("}\n");
+    dot
+}
+
+// {"packages":[{"name","dependencies":[name]}],"modules":[{"id","name","package","path"}],
+//  "imports":[{"from","to","cycle"}],"cycles":[[id]]}
+fn graph_json(session: &Session, graph: &ModuleGraph) -> String {
+    let mut json = String::from("{\"packages\":[");
+    for package_id in session.package_ids() {
+        let package = session.package(package_id);
+        if package_id.index() != 0 {
+            json.push(',');
+        }
+        json.push_str("{\"name\":");
+        error_format::json_string(&mut json, &package.manifest().package.name);
+        json.push_str(",\"dependencies\":[");
+        for (idx, dependency_id) in package.dependency_ids().into_iter().enumerate() {
+            if idx != 0 {
+                json.push(',');
+            }
+            let name = &session.package(dependency_id).manifest().package.name;
+            error_format::json_string(&mut json, name);
+        }
+        json.push_str("]}");
+    }
+
+    json.push_str("],\"modules\":[");
+    for module_id in session.module_ids() {
+        let module = session.module(module_id);
+        if module_id.index() != 0 {
+            json.push(',');
+        }
+        json.push_str(&format!("{{\"id\":{},\"name\":", module_id.index()));
+        error_format::json_string(&mut json, &hir_lower::module_name(session, module_id));
+        json.push_str(",\"package\":");
+        let package = session.package(module.package_id);
+        error_format::json_string(&mut json, &package.manifest().package.name);
+        json.push_str(",\"path\":");
+        error_format::json_string(&mut json, &module.path.to_string_lossy());
+        json.push('}');
+    }
+
+    json.push_str("],\"imports\":[");
+    for (idx, (origin_id, target_id)) in graph_edges(graph).into_iter().enumerate() {
+        if idx != 0 {
+            json.push(',');
+        }
+        json.push_str(&format!(
+            "{{\"from\":{},\"to\":{},\"cycle\":{}}}",
+            origin_id.index(),
+            target_id.index(),
+            graph.in_cycle(origin_id, target_id)
+        ));
+    }
+
+    json.push_str("],\"cycles\":[");
+    for (idx, cycle) in graph.cycles.iter().enumerate() {
+        if idx != 0 {
+            json.push(',');
         }
-        Ok(vec![])
+        let ids: Vec<String> = cycle.iter().map(|id| id.index().to_string()).collect();
+        json.push_str(&format!("[{}]", ids.join(",")));
+    }
+    json.push_str("]}");
+    json
+}
+
+// same module imported more than once is a single edge
+fn doc(data: CommandDoc) -> Result<(), ErrorComp> {
+    let (session, intern_name) = Session::new(false, TargetTriple::host(), &RealFs)?;
+    let result = doc_impl(&session, intern_name, &data);
+    let failed = result.is_err();
+    error_format::print_errors(
+        Some(&session),
+        DiagnosticCollection::from_result(result),
+        MessageFormat::Human,
+    );
+    if failed {
+        std::process::exit(1);
+    }
+    Ok(())
+}
+
+fn doc_impl(
+    session: &Session,
+    intern_name: InternPool,
+    data: &CommandDoc,
+) -> Result<Vec<WarningComp>, DiagnosticCollection> {
+    let options = CompileOptions::new(Stage::Parse, TargetTriple::host());
+    let (compiled, warnings) = rock_core::compile(session, intern_name, &options).into_result()?;
+    let ast = match compiled {
+        Compiled::Parsed(ast) => *ast,
+        _ => unreachable!(),
+    };
+    let (docs, warnings) = hir_lower::package_docs(ast, session).into_result(warnings)?;
+
+    let result = doc_write(session, &docs, data.format);
+    let (_, warnings) = ResultComp::from_error(result).into_result(warnings)?;
+
+    let g = ansi::GREEN_BOLD;
+    let r = ansi::RESET;
+    println!("  {g}Documented{r} `{}` in `build/doc`\n", docs.name);
+    Ok(warnings)
+}
+
+fn doc_write(session: &Session, docs: &PackageDocs, format: DocFormat) -> Result<(), ErrorComp> {
+    let root_dir = &session.package(Session::ROOT_ID).root_dir;
+    let doc_dir = build_layout::create_doc_dir(root_dir)?;
+    for (name, page) in doc::render(docs, format) {
+        fs_env::file_create_or_rewrite(&doc_dir.join(name), &page)?;
     }
+    Ok(())
+}
+
+fn graph_edges(graph: &ModuleGraph) -> Vec<(ModuleID, ModuleID)> {
+    let mut edges: Vec<(ModuleID, ModuleID)> = graph
+        .imports
+        .iter()
+        .map(|import| (import.origin_id, import.target_id))
+        .collect();
+    edges.sort_by_key(|(origin_id, target_id)| (origin_id.index(), target_id.index()));
+    edges.dedup();
+    edges
 }
 
-fn help() {
+fn explain(data: CommandExplain) {
+    let g = ansi::GREEN_BOLD;
+    let r = ansi::RESET;
+    let mut lines = data.code.explain().lines();
+    let summary = lines.next().unwrap_or("");
+    println!("  {g}{}:{r} {summary}", data.code.as_str());
+    for line in lines {
+        if line.is_empty() {
+            println!();
+        } else {
+            println!("  {line}");
+        }
+    }
+    println!();
+}
+
+fn help(data: CommandHelp) {
+    if let Some(spec) = data.command {
+        help_command(spec);
+        return;
+    }
     let g = ansi::GREEN_BOLD;
     let c = ansi::CYAN_BOLD;
     let r = ansi::RESET;
@@ -223,32 +888,126 @@ Usage:
   {c}rock <command> [options]
 
 {g}Commands:
-  {c}n, new <name>  {r}Create new package
-  {c}c, check       {r}Check the program
-  {c}b, build       {r}Build the program
-  {c}r, run         {r}Build and run the program
-  {c}h, help        {r}Print help information
-  {c}v, version     {r}Print compiler version
+  {c}n, new <name>     {r}Create new package
+  {c}i, init           {r}Create package in current directory
+  {c}c, check          {r}Check the program
+  {c}b, build          {r}Build the program
+  {c}r, run            {r}Build and run the program
+  {c}t, test [name]    {r}Build and run `#[test]` procedures
+  {c}clean             {r}Remove the build directory
+  {c}f, fmt            {r}Format the source files
+  {c}graph             {r}Print the module import graph
+  {c}d, doc            {r}Generate documentation of public items
+  {c}e, explain <code> {r}Explain an error code
+  {c}h, help [command] {r}Print help information
+  {c}v, version        {r}Print compiler version
 
 {g}Options:
-  {c}new
+  {c}all commands
+    {c}--color <when>  {r}Colored output: auto, always, never
+    {c}--help          {r}Print help of the command
+
+  {c}new, init
     {c}--lib        {r}Create {} package
     {c}--bin        {r}Create {} package
     {c}--no-git     {r}Create package without git repo
 
-  {c}build, run
+  {c}check
+    {c}--watch      {r}Check again when source files change
+
+  {c}check, build
+    {c}--emit <ast|hir> [path]  {r}Print parsed or lowered program, to stdout or file
+
+  {c}check, build, run, test
+    {c}--message-format <fmt>  {r}Diagnostic format: human, json
+    {c}--allow <codes>         {r}Do not report these warnings
+    {c}--warn <codes>          {r}Report these as warnings
+    {c}--deny <codes>          {r}Report these warnings as errors
+    {c}--error-limit <n>       {r}Stop after reporting n errors
+    {c}--target <triple>       {r}Target to compile for, defaults to host
+    {c}--timings               {r}Print time and memory used by each stage
+
+  {c}build, run, test
     {c}--debug      {r}Build in debug mode
     {c}--release    {r}Build in release mode
     {c}--emit-llvm  {r}Save llvm module to file
 
   {c}run
     {c}-- [args]    {r}Pass command line arguments
+
+  {c}clean
+    {c}--debug      {r}Remove only debug build artifacts
+    {c}--release    {r}Remove only release build artifacts
+
+  {c}fmt
+    {c}--check      {r}Report unformatted files without changing them
+
+  {c}graph
+    {c}--format <dot|json>  {r}Output format, defaults to dot
+
+  {c}doc
+    {c}--format <markdown|html>  {r}Output format, defaults to markdown
+
+  {c}version
+    {c}--verbose    {r}Print version, commit and target
+
+{g}Exit codes:
+  {c}0  {r}Success
+  {c}1  {r}Errors were reported
+  {c}2  {r}Internal compiler error
 "#,
     PackageKind::Lib.as_str_full(),
     PackageKind::Bin.as_str_full());
 }
 
-fn version() {
+fn help_command(spec: &CommandSpec) {
+    let g = ansi::GREEN_BOLD;
+    let c = ansi::CYAN_BOLD;
+    let r = ansi::RESET;
+
+    let options = spec.options.iter().chain(GLOBAL_OPTIONS.iter());
+    let width = options
+        .clone()
+        .map(|option| option.usage().len())
+        .max()
+        .unwrap_or(0);
+    let name = match spec.alias {
+        Some(alias) => format!("{alias}, {}", spec.name),
+        None => spec.name.to_string(),
+    };
+    let args = if spec.args.is_empty() {
+        String::new()
+    } else {
+        format!(" {}", spec.args)
+    };
+
+    println!("\n  {c}{name}{r} - {}\n", spec.about);
+    println!("{g}Usage:");
+    println!("  {c}rock {}{args} [options]{r}\n", spec.name);
+    println!("{g}Options:");
+    for option in options {
+        let usage = option.usage();
+        println!("  {c}{usage:<width$}  {r}{}", option.about);
+    }
+    println!();
+}
+
+// `--verbose` output is `key: value` lines for tools and bug reports
+fn version(data: CommandVersion) {
+    if data.verbose {
+        let commit = option_env!("ROCK_COMMIT_HASH").unwrap_or("unknown");
+        let build = if cfg!(debug_assertions) {
+            "debug"
+        } else {
+            "release"
+        };
+        println!("rock {}", rock_core::VERSION);
+        println!("version: {}", rock_core::VERSION);
+        println!("commit: {commit}");
+        println!("target: {}", TargetTriple::host().as_str());
+        println!("build: {build}");
+        return;
+    }
     let g = ansi::GREEN_BOLD;
     let r = ansi::RESET;
     println!("  {g}Rock version:{r} {}\n", rock_core::VERSION);
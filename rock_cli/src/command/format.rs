@@ -24,6 +24,9 @@ pub fn parse() -> ResultComp<CommandFormat> {
 fn parse_name(p: &mut FormatParser, diagnostics: &mut DiagnosticCollection) -> String {
     if let Some(name) = p.eat_arg() {
         name
+    } else if p.at_option() {
+        // `rock --help` or `rock --version`, checked with other options
+        String::new()
     } else {
         diagnostics.error(ErrorComp::message(
             "command name is missing, use `rock help` to learn the usage",
@@ -93,6 +96,12 @@ impl FormatParser {
         }
     }
 
+    fn at_option(&self) -> bool {
+        self.args
+            .get(self.cursor)
+            .is_some_and(|arg| arg.starts_with("--") && arg.len() > 2)
+    }
+
     fn eat_option(&mut self) -> Option<String> {
         let arg = self.args.get(self.cursor)?;
         let option = arg.strip_prefix("--")?;
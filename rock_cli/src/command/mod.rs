@@ -1,20 +1,34 @@
+mod doc;
 mod execute;
 mod format;
 mod parse;
+mod spec;
 
-use crate::error_format;
-#[cfg(not(target_os = "linux"))]
-use rock_core::codegen::BuildKind;
-use rock_core::error::{DiagnosticCollection, ResultComp, WarningComp};
+use crate::ansi::{self, ColorMode};
+use crate::error_format::{self, MessageFormat};
+use crate::report::ExitCode;
+use rock_core::build_layout::BuildKind;
+use rock_core::driver::EmitKind;
+use rock_core::error::{DiagnosticCollection, ErrorCode, LintConfig, ResultComp, WarningComp};
 use rock_core::package::manifest::PackageKind;
+use rock_core::target::TargetTriple;
+use spec::CommandSpec;
+use std::path::PathBuf;
 
 enum Command {
     New(CommandNew),
-    Check,
+    Init(CommandInit),
+    Check(CommandCheck),
     Build(CommandBuild),
     Run(CommandRun),
-    Help,
-    Version,
+    Test(CommandTest),
+    Clean(CommandClean),
+    Fmt(CommandFmt),
+    Graph(CommandGraph),
+    Doc(CommandDoc),
+    Explain(CommandExplain),
+    Help(CommandHelp),
+    Version(CommandVersion),
 }
 
 struct CommandNew {
@@ -23,30 +37,131 @@ struct CommandNew {
     no_git: bool,
 }
 
+struct CommandInit {
+    kind: PackageKind,
+    no_git: bool,
+}
+
+struct CommandCheck {
+    watch: bool,
+    target: TargetTriple,
+    emit: Option<Emit>,
+    message_format: MessageFormat,
+    lint_config: LintConfig,
+    error_limit: Option<u32>,
+    timings: bool,
+}
+
 struct CommandBuild {
-    #[cfg(not(target_os = "linux"))]
     kind: BuildKind,
+    target: TargetTriple,
+    emit: Option<Emit>,
     emit_llvm: bool,
+    message_format: MessageFormat,
+    lint_config: LintConfig,
+    error_limit: Option<u32>,
+    timings: bool,
 }
 
 struct CommandRun {
-    #[cfg(not(target_os = "linux"))]
     kind: BuildKind,
+    target: TargetTriple,
     emit_llvm: bool,
+    message_format: MessageFormat,
+    lint_config: LintConfig,
+    error_limit: Option<u32>,
+    timings: bool,
     args: Vec<String>,
 }
 
+struct CommandTest {
+    kind: BuildKind,
+    target: TargetTriple,
+    filter: Option<String>,
+    message_format: MessageFormat,
+    lint_config: LintConfig,
+    error_limit: Option<u32>,
+    timings: bool,
+}
+
+/// `--emit ast|hir [path]`, printed to stdout when path is not specified
+struct Emit {
+    kind: EmitKind,
+    path: Option<PathBuf>,
+}
+
+struct CommandClean {
+    kind: Option<BuildKind>,
+}
+
+struct CommandFmt {
+    check: bool,
+}
+
+struct CommandGraph {
+    format: GraphFormat,
+}
+
+/// `--format dot|json` of the module graph, printed to stdout
+#[derive(Copy, Clone)]
+enum GraphFormat {
+    Dot,
+    Json,
+}
+
+struct CommandDoc {
+    format: DocFormat,
+}
+
+/// `--format markdown|html` of the generated pages, written to `build/doc`
+#[derive(Copy, Clone)]
+enum DocFormat {
+    Markdown,
+    Html,
+}
+
+struct CommandExplain {
+    code: ErrorCode,
+}
+
+struct CommandHelp {
+    command: Option<&'static CommandSpec>,
+}
+
+struct CommandVersion {
+    verbose: bool,
+}
+
+impl Command {
+    fn message_format(&self) -> MessageFormat {
+        match self {
+            Command::Check(data) => data.message_format,
+            Command::Build(data) => data.message_format,
+            Command::Run(data) => data.message_format,
+            Command::Test(data) => data.message_format,
+            _ => MessageFormat::Human,
+        }
+    }
+}
+
 pub fn run() {
-    let result = run_impl();
-    error_format::print_errors(None, DiagnosticCollection::from_result(result));
+    ansi::init(ColorMode::Auto);
+    let mut message_format = MessageFormat::Human;
+    let result = run_impl(&mut message_format);
+    let diagnostics = DiagnosticCollection::from_result(result);
+    let exit_code = ExitCode::from_diagnostics(&diagnostics);
+    error_format::print_errors(None, diagnostics, message_format);
+    exit_code.exit_on_failure();
 }
 
-//@feedback print after check / build / run, possibly with timer
-fn run_impl() -> Result<Vec<WarningComp>, DiagnosticCollection> {
-    let (format, warnings) = format::parse().into_result(vec![])?;
+fn run_impl(message_format: &mut MessageFormat) -> Result<Vec<WarningComp>, DiagnosticCollection> {
+    let (mut format, warnings) = format::parse().into_result(vec![])?;
+    let (color_mode, warnings) = parse::color_mode(&mut format).into_result(warnings)?;
+    ansi::init(color_mode);
     let (command, warnings) = parse::command(format).into_result(warnings)?;
+    *message_format = command.message_format();
     let diagnostics = DiagnosticCollection::new().join_warnings(warnings);
-    error_format::print_errors(None, diagnostics);
+    error_format::print_errors(None, diagnostics, *message_format);
 
     let result = execute::command(command);
     let ((), warnings) = ResultComp::from_error(result).into_result(vec![])?;
@@ -1,32 +1,120 @@
 use super::format::CommandFormat;
-use super::{Command, CommandBuild, CommandNew, CommandRun};
-#[cfg(not(target_os = "linux"))]
-use rock_core::codegen::BuildKind;
-use rock_core::error::{DiagnosticCollection, ErrorComp, ResultComp, WarningComp};
+use super::spec::{CommandSpec, COMMANDS};
+use super::{
+    Command, CommandBuild, CommandCheck, CommandClean, CommandDoc, CommandExplain, CommandFmt,
+    CommandGraph, CommandHelp, CommandInit, CommandNew, CommandRun, CommandTest, CommandVersion,
+    DocFormat, Emit, EmitKind, GraphFormat,
+};
+use crate::ansi::ColorMode;
+use crate::error_format::MessageFormat;
+use rock_core::build_layout::BuildKind;
+use rock_core::error::{
+    DiagnosticCollection, ErrorCode, ErrorComp, LintConfig, LintLevel, ResultComp, WarningCode,
+    WarningComp,
+};
 use rock_core::package::manifest::PackageKind;
+use rock_core::target::TargetTriple;
+use rock_core::text;
+use std::path::PathBuf;
 
 pub fn command(format: CommandFormat) -> ResultComp<Command> {
-    match format.name.as_str() {
-        "n" | "new" => parse_new(format),
-        "c" | "check" => parse_simple_command(&format, "check", Command::Check),
-        "b" | "build" => parse_build(format),
-        "r" | "run" => parse_run(format),
-        "h" | "help" => parse_simple_command(&format, "help", Command::Help),
-        "v" | "version" => parse_simple_command(&format, "version", Command::Version),
-        _ => {
-            let error = ErrorComp::message(format!(
-                "`{}` command does not exist, use `rock help` to learn the usage",
-                format.name
-            ));
-            ResultComp::from_error(Err(error))
-        }
+    if format.name.is_empty() {
+        return parse_no_command(format);
+    }
+    let spec = match CommandSpec::find(&format.name) {
+        Some(spec) => spec,
+        None => return ResultComp::from_error(Err(error_unknown_command(&format.name))),
+    };
+    if format.options.contains_key("help") {
+        let data = CommandHelp {
+            command: Some(spec),
+        };
+        return ResultComp::Ok((Command::Help(data), vec![]));
+    }
+
+    match spec.name {
+        "new" => parse_new(format, spec),
+        "init" => parse_init(format, spec),
+        "check" => parse_check(format, spec),
+        "build" => parse_build(format, spec),
+        "run" => parse_run(format, spec),
+        "test" => parse_test(format, spec),
+        "clean" => parse_clean(format, spec),
+        "fmt" => parse_fmt(format, spec),
+        "graph" => parse_graph(format, spec),
+        "doc" => parse_doc(format, spec),
+        "explain" => parse_explain(format, spec),
+        "help" => parse_help(format, spec),
+        "version" => parse_version(format, spec),
+        _ => unreachable!(),
     }
 }
 
-fn parse_new(format: CommandFormat) -> ResultComp<Command> {
+// `rock --help` and `rock --version [--verbose]` don't require a command name
+fn parse_no_command(mut format: CommandFormat) -> ResultComp<Command> {
+    let name = if format.options.contains_key("version") {
+        "version"
+    } else if format.options.contains_key("help") {
+        "help"
+    } else {
+        let error =
+            ErrorComp::message("command name is missing, use `rock help` to learn the usage");
+        return ResultComp::from_error(Err(error));
+    };
+    let spec = CommandSpec::find(name).unwrap();
+    format.options.remove(name);
+
+    match spec.name {
+        "help" => parse_help(format, spec),
+        _ => parse_version(format, spec),
+    }
+}
+
+fn error_unknown_command(name: &str) -> ErrorComp {
+    let names = COMMANDS.iter().map(|spec| spec.name);
+    match text::similar_name(name, names) {
+        Some(similar) => ErrorComp::message(format!(
+            "`{name}` command does not exist\ndid you mean `{similar}`?"
+        )),
+        None => ErrorComp::message(format!(
+            "`{name}` command does not exist, use `rock help` to learn the usage"
+        )),
+    }
+}
+
+// `--color` is accepted by every command,
+// its removed before command specific options are checked
+pub fn color_mode(format: &mut CommandFormat) -> ResultComp<ColorMode> {
+    let mut diagnostics = DiagnosticCollection::new();
+    let name = "color";
+    let default = ColorMode::Auto;
+
+    let mode = match format.options.remove(name) {
+        None => default,
+        Some(args) if args.len() != 1 => {
+            diagnostics.error(ErrorComp::message(format!(
+                "option `--{name}` expects one argument: `auto`, `always` or `never`"
+            )));
+            default
+        }
+        Some(args) => match ColorMode::from_str(&args[0]) {
+            Some(mode) => mode,
+            None => {
+                diagnostics.error(ErrorComp::message(format!(
+                    "unknown color mode `{}`, expected `auto`, `always` or `never`",
+                    args[0]
+                )));
+                default
+            }
+        },
+    };
+    ResultComp::new(mode, diagnostics)
+}
+
+fn parse_new(format: CommandFormat, spec: &CommandSpec) -> ResultComp<Command> {
     let mut diagnostics = DiagnosticCollection::new();
-    check_command_args(&format, &mut diagnostics, "new", true, false);
-    check_expected_option_set(&format, &mut diagnostics, &["lib", "bin", "no-git"]);
+    check_command_args(&format, &mut diagnostics, spec, true, false);
+    check_expected_option_set(&format, &mut diagnostics, spec);
 
     let name = parse_package_name(&format, &mut diagnostics);
     let kind = parse_package_kind(&format, &mut diagnostics, PackageKind::Bin);
@@ -36,71 +124,219 @@ fn parse_new(format: CommandFormat) -> ResultComp<Command> {
     ResultComp::new(Command::New(data), diagnostics)
 }
 
-fn parse_build(format: CommandFormat) -> ResultComp<Command> {
+fn parse_init(format: CommandFormat, spec: &CommandSpec) -> ResultComp<Command> {
     let mut diagnostics = DiagnosticCollection::new();
-    check_command_args(&format, &mut diagnostics, "build", false, false);
-    check_expected_option_set(
-        &format,
-        &mut diagnostics,
-        &["debug", "release", "emit-llvm"],
-    );
-
-    #[cfg(not(target_os = "linux"))]
-    let kind = parse_build_kind(&format, &mut diagnostics, BuildKind::Debug);
+    check_command_args(&format, &mut diagnostics, spec, false, false);
+    check_expected_option_set(&format, &mut diagnostics, spec);
+
+    let kind = parse_package_kind(&format, &mut diagnostics, PackageKind::Bin);
+    let no_git = parse_bool_flag(&format, &mut diagnostics, "no-git", false);
+
+    let data = CommandInit { kind, no_git };
+    ResultComp::new(Command::Init(data), diagnostics)
+}
+
+fn parse_check(format: CommandFormat, spec: &CommandSpec) -> ResultComp<Command> {
+    let mut diagnostics = DiagnosticCollection::new();
+    check_command_args(&format, &mut diagnostics, spec, false, false);
+    check_expected_option_set(&format, &mut diagnostics, spec);
+
+    let watch = parse_bool_flag(&format, &mut diagnostics, "watch", false);
+    let target = parse_target(&format, &mut diagnostics);
+    let emit = parse_emit(&format, &mut diagnostics);
+    let message_format = parse_message_format(&format, &mut diagnostics, MessageFormat::Human);
+    let lint_config = parse_lint_config(&format, &mut diagnostics);
+    let error_limit = parse_error_limit(&format, &mut diagnostics);
+    let timings = parse_bool_flag(&format, &mut diagnostics, "timings", false);
+
+    let data = CommandCheck {
+        watch,
+        target,
+        emit,
+        message_format,
+        lint_config,
+        error_limit,
+        timings,
+    };
+    ResultComp::new(Command::Check(data), diagnostics)
+}
+
+fn parse_build(format: CommandFormat, spec: &CommandSpec) -> ResultComp<Command> {
+    let mut diagnostics = DiagnosticCollection::new();
+    check_command_args(&format, &mut diagnostics, spec, false, false);
+    check_expected_option_set(&format, &mut diagnostics, spec);
+
+    let kind = parse_build_kind(&format, &mut diagnostics).unwrap_or(BuildKind::Debug);
+    let target = parse_target(&format, &mut diagnostics);
+    let emit = parse_emit(&format, &mut diagnostics);
     let emit_llvm = parse_bool_flag(&format, &mut diagnostics, "emit-llvm", false);
+    let message_format = parse_message_format(&format, &mut diagnostics, MessageFormat::Human);
+    let lint_config = parse_lint_config(&format, &mut diagnostics);
+    let error_limit = parse_error_limit(&format, &mut diagnostics);
+    let timings = parse_bool_flag(&format, &mut diagnostics, "timings", false);
 
     let data = CommandBuild {
-        #[cfg(not(target_os = "linux"))]
         kind,
+        target,
+        emit,
         emit_llvm,
+        message_format,
+        lint_config,
+        error_limit,
+        timings,
     };
     ResultComp::new(Command::Build(data), diagnostics)
 }
 
-fn parse_run(format: CommandFormat) -> ResultComp<Command> {
+fn parse_run(format: CommandFormat, spec: &CommandSpec) -> ResultComp<Command> {
     let mut diagnostics: DiagnosticCollection = DiagnosticCollection::new();
-    check_command_args(&format, &mut diagnostics, "run", false, true);
-    check_expected_option_set(
-        &format,
-        &mut diagnostics,
-        &["debug", "release", "emit-llvm"],
-    );
-
-    #[cfg(not(target_os = "linux"))]
-    let kind = parse_build_kind(&format, &mut diagnostics, BuildKind::Debug);
+    check_command_args(&format, &mut diagnostics, spec, false, true);
+    check_expected_option_set(&format, &mut diagnostics, spec);
+
+    let kind = parse_build_kind(&format, &mut diagnostics).unwrap_or(BuildKind::Debug);
+    let target = parse_target(&format, &mut diagnostics);
     let emit_llvm = parse_bool_flag(&format, &mut diagnostics, "emit-llvm", false);
+    let message_format = parse_message_format(&format, &mut diagnostics, MessageFormat::Human);
+    let lint_config = parse_lint_config(&format, &mut diagnostics);
+    let error_limit = parse_error_limit(&format, &mut diagnostics);
+    let timings = parse_bool_flag(&format, &mut diagnostics, "timings", false);
 
     let data = CommandRun {
-        #[cfg(not(target_os = "linux"))]
         kind,
+        target,
         emit_llvm,
+        message_format,
+        lint_config,
+        error_limit,
+        timings,
         args: format.trail_args,
     };
     ResultComp::new(Command::Run(data), diagnostics)
 }
 
-fn parse_simple_command(
-    format: &CommandFormat,
-    cmd_name: &str,
-    command: Command,
-) -> ResultComp<Command> {
-    if !format.args.is_empty() || !format.options.is_empty() || !format.trail_args.is_empty() {
-        let warning = WarningComp::message(format!(
-            "`{cmd_name}` command does not take any options or arguments",
+fn parse_test(format: CommandFormat, spec: &CommandSpec) -> ResultComp<Command> {
+    let mut diagnostics = DiagnosticCollection::new();
+    check_command_args(&format, &mut diagnostics, spec, true, false);
+    check_expected_option_set(&format, &mut diagnostics, spec);
+
+    let kind = parse_build_kind(&format, &mut diagnostics).unwrap_or(BuildKind::Debug);
+    let target = parse_target(&format, &mut diagnostics);
+    let filter = parse_test_filter(&format, &mut diagnostics);
+    let message_format = parse_message_format(&format, &mut diagnostics, MessageFormat::Human);
+    let lint_config = parse_lint_config(&format, &mut diagnostics);
+    let error_limit = parse_error_limit(&format, &mut diagnostics);
+    let timings = parse_bool_flag(&format, &mut diagnostics, "timings", false);
+
+    let data = CommandTest {
+        kind,
+        target,
+        filter,
+        message_format,
+        lint_config,
+        error_limit,
+        timings,
+    };
+    ResultComp::new(Command::Test(data), diagnostics)
+}
+
+fn parse_clean(format: CommandFormat, spec: &CommandSpec) -> ResultComp<Command> {
+    let mut diagnostics = DiagnosticCollection::new();
+    check_command_args(&format, &mut diagnostics, spec, false, false);
+    check_expected_option_set(&format, &mut diagnostics, spec);
+
+    let kind = parse_build_kind(&format, &mut diagnostics);
+
+    let data = CommandClean { kind };
+    ResultComp::new(Command::Clean(data), diagnostics)
+}
+
+fn parse_fmt(format: CommandFormat, spec: &CommandSpec) -> ResultComp<Command> {
+    let mut diagnostics = DiagnosticCollection::new();
+    check_command_args(&format, &mut diagnostics, spec, false, false);
+    check_expected_option_set(&format, &mut diagnostics, spec);
+
+    let check = parse_bool_flag(&format, &mut diagnostics, "check", false);
+
+    let data = CommandFmt { check };
+    ResultComp::new(Command::Fmt(data), diagnostics)
+}
+
+fn parse_graph(format: CommandFormat, spec: &CommandSpec) -> ResultComp<Command> {
+    let mut diagnostics = DiagnosticCollection::new();
+    check_command_args(&format, &mut diagnostics, spec, false, false);
+    check_expected_option_set(&format, &mut diagnostics, spec);
+
+    let format = parse_graph_format(&format, &mut diagnostics);
+
+    let data = CommandGraph { format };
+    ResultComp::new(Command::Graph(data), diagnostics)
+}
+
+fn parse_doc(format: CommandFormat, spec: &CommandSpec) -> ResultComp<Command> {
+    let mut diagnostics = DiagnosticCollection::new();
+    check_command_args(&format, &mut diagnostics, spec, false, false);
+    check_expected_option_set(&format, &mut diagnostics, spec);
+
+    let format = parse_doc_format(&format, &mut diagnostics);
+
+    let data = CommandDoc { format };
+    ResultComp::new(Command::Doc(data), diagnostics)
+}
+
+fn parse_explain(format: CommandFormat, spec: &CommandSpec) -> ResultComp<Command> {
+    let mut diagnostics = DiagnosticCollection::new();
+    check_command_args(&format, &mut diagnostics, spec, true, false);
+    check_expected_option_set(&format, &mut diagnostics, spec);
+
+    let code = parse_error_code(&format, &mut diagnostics);
+    let data = CommandExplain { code };
+    ResultComp::new(Command::Explain(data), diagnostics)
+}
+
+fn parse_help(format: CommandFormat, spec: &CommandSpec) -> ResultComp<Command> {
+    let mut diagnostics = DiagnosticCollection::new();
+    check_command_args(&format, &mut diagnostics, spec, true, false);
+    check_expected_option_set(&format, &mut diagnostics, spec);
+
+    if format.args.len() > 1 {
+        diagnostics.warning(WarningComp::message(
+            "`help` command expects one command name, other arguments will be ignored",
         ));
-        ResultComp::Ok((command, vec![warning]))
-    } else {
-        ResultComp::Ok((command, vec![]))
     }
+    let command = match format.args.first() {
+        Some(name) => match CommandSpec::find(name) {
+            Some(spec) => Some(spec),
+            None => {
+                diagnostics.error(error_unknown_command(name));
+                None
+            }
+        },
+        None => None,
+    };
+
+    let data = CommandHelp { command };
+    ResultComp::new(Command::Help(data), diagnostics)
+}
+
+fn parse_version(format: CommandFormat, spec: &CommandSpec) -> ResultComp<Command> {
+    let mut diagnostics = DiagnosticCollection::new();
+    check_command_args(&format, &mut diagnostics, spec, false, false);
+    check_expected_option_set(&format, &mut diagnostics, spec);
+
+    let verbose = parse_bool_flag(&format, &mut diagnostics, "verbose", false);
+
+    let data = CommandVersion { verbose };
+    ResultComp::new(Command::Version(data), diagnostics)
 }
 
 fn check_command_args(
     format: &CommandFormat,
     diagnostics: &mut DiagnosticCollection,
-    cmd_name: &str,
+    spec: &CommandSpec,
     has_args: bool,
     has_trail_args: bool,
 ) {
+    let cmd_name = spec.name;
     if !has_args && !format.args.is_empty() {
         diagnostics.warning(WarningComp::message(format!(
             "`{cmd_name}` command does not take any arguments"
@@ -113,17 +349,27 @@ fn check_command_args(
     }
 }
 
+// unknown options are ignored, closest option of the command is suggested
 fn check_expected_option_set(
     format: &CommandFormat,
     diagnostics: &mut DiagnosticCollection,
-    expected: &[&str],
+    spec: &CommandSpec,
 ) {
     for (option, _) in format.options.iter() {
-        if !expected.contains(&option.as_str()) {
-            diagnostics.warning(WarningComp::message(format!(
-                "option `--{option}` is not recognized and will be ignored"
-            )));
+        if spec.option(option).is_some() {
+            continue;
         }
+        let names = spec.options.iter().map(|option| option.name);
+        let message = match text::similar_name(option, names) {
+            Some(similar) => format!(
+                "option `--{option}` is not recognized and will be ignored\ndid you mean `--{similar}`?"
+            ),
+            None => format!(
+                "option `--{option}` is not recognized and will be ignored, use `rock help {}` to learn the usage",
+                spec.name
+            ),
+        };
+        diagnostics.warning(WarningComp::message(message));
     }
 }
 
@@ -168,18 +414,148 @@ fn parse_package_name(format: &CommandFormat, diagnostics: &mut DiagnosticCollec
         arg.clone()
     } else {
         diagnostics.error(ErrorComp::message(
-            "missing new package name, use `rock help` to learn the usage",
+            "missing new package name, use `rock help new` to learn the usage",
         ));
         "error".into()
     }
 }
 
-#[cfg(not(target_os = "linux"))]
+fn parse_error_code(format: &CommandFormat, diagnostics: &mut DiagnosticCollection) -> ErrorCode {
+    let default = ErrorCode::Internal;
+    let arg = match format.args.first() {
+        Some(arg) => arg,
+        None => {
+            diagnostics.error(ErrorComp::message(
+                "missing error code, for example: `rock explain E0001`",
+            ));
+            return default;
+        }
+    };
+    if format.args.len() > 1 {
+        diagnostics.warning(WarningComp::message(
+            "`explain` command expects one error code, other arguments will be ignored",
+        ));
+    }
+    match ErrorCode::from_code(arg) {
+        Some(code) => code,
+        None => {
+            let first = ErrorCode::ALL[0].as_str();
+            let last = ErrorCode::ALL[ErrorCode::ALL.len() - 1].as_str();
+            diagnostics.error(ErrorComp::message(format!(
+                "unknown error code `{arg}`, expected range is {first}..={last}"
+            )));
+            default
+        }
+    }
+}
+
+fn parse_test_filter(
+    format: &CommandFormat,
+    diagnostics: &mut DiagnosticCollection,
+) -> Option<String> {
+    if format.args.len() > 1 {
+        diagnostics.warning(WarningComp::message(
+            "`test` command expects one filter argument, other arguments will be ignored",
+        ));
+    }
+    format.args.first().cloned()
+}
+
+fn parse_message_format(
+    format: &CommandFormat,
+    diagnostics: &mut DiagnosticCollection,
+    default: MessageFormat,
+) -> MessageFormat {
+    let name = "message-format";
+    let args = match format.options.get(name) {
+        Some(args) => args,
+        None => return default,
+    };
+
+    if args.len() != 1 {
+        diagnostics.error(ErrorComp::message(format!(
+            "option `--{name}` expects one argument: `human` or `json`"
+        )));
+        return default;
+    }
+
+    match MessageFormat::from_str(&args[0]) {
+        Some(message_format) => message_format,
+        None => {
+            diagnostics.error(ErrorComp::message(format!(
+                "unknown message format `{}`, expected `human` or `json`",
+                args[0]
+            )));
+            default
+        }
+    }
+}
+
+// levels are applied in order: allow, warn, deny
+// `all` can be used to set the level of every warning code
+fn parse_lint_config(format: &CommandFormat, diagnostics: &mut DiagnosticCollection) -> LintConfig {
+    let mut lint_config = LintConfig::new();
+    let levels = [
+        ("allow", LintLevel::Allow),
+        ("warn", LintLevel::Warn),
+        ("deny", LintLevel::Deny),
+    ];
+
+    for (name, level) in levels {
+        let args = match format.options.get(name) {
+            Some(args) => args,
+            None => continue,
+        };
+        if args.is_empty() {
+            diagnostics.error(ErrorComp::message(format!(
+                "option `--{name}` expects a list of warning codes"
+            )));
+            continue;
+        }
+        for arg in args {
+            if arg == "all" {
+                lint_config.set_all(level);
+            } else if let Some(code) = WarningCode::from_name(arg) {
+                lint_config.set(code, level);
+            } else {
+                diagnostics.warning(WarningComp::message(format!(
+                    "unknown warning code `{arg}` in option `--{name}` will be ignored"
+                )));
+            }
+        }
+    }
+    lint_config
+}
+
+fn parse_error_limit(
+    format: &CommandFormat,
+    diagnostics: &mut DiagnosticCollection,
+) -> Option<u32> {
+    let name = "error-limit";
+    let args = format.options.get(name)?;
+
+    if args.len() != 1 {
+        diagnostics.error(ErrorComp::message(format!(
+            "option `--{name}` expects one argument: number of errors"
+        )));
+        return None;
+    }
+    match args[0].parse::<u32>() {
+        Ok(limit) if limit > 0 => Some(limit),
+        _ => {
+            diagnostics.error(ErrorComp::message(format!(
+                "invalid error limit `{}`, expected a positive number",
+                args[0]
+            )));
+            None
+        }
+    }
+}
+
 fn parse_build_kind(
     format: &CommandFormat,
     diagnostics: &mut DiagnosticCollection,
-    default: BuildKind,
-) -> BuildKind {
+) -> Option<BuildKind> {
     let debug_str = BuildKind::Debug.as_str();
     let release_str = BuildKind::Release.as_str();
 
@@ -190,15 +566,127 @@ fn parse_build_kind(
         diagnostics.error(ErrorComp::message(format!(
             "conflicting options `--{debug_str}` and `--{release_str}` cannot be used together"
         )));
-        return default;
+        return None;
     }
 
     if debug {
-        BuildKind::Debug
+        Some(BuildKind::Debug)
     } else if release {
-        BuildKind::Release
+        Some(BuildKind::Release)
     } else {
-        default
+        None
+    }
+}
+
+fn parse_target(format: &CommandFormat, diagnostics: &mut DiagnosticCollection) -> TargetTriple {
+    let name = "target";
+    let args = match format.options.get(name) {
+        Some(args) => args,
+        None => return TargetTriple::host(),
+    };
+
+    if args.len() != 1 {
+        diagnostics.error(ErrorComp::message(format!(
+            "option `--{name}` expects one argument: target triple"
+        )));
+        return TargetTriple::host();
+    }
+    match TargetTriple::from_name(&args[0]) {
+        Some(target) => target,
+        None => {
+            let mut supported = String::new();
+            for target in TargetTriple::ALL {
+                supported.push_str("\n  ");
+                supported.push_str(target.as_str());
+            }
+            diagnostics.error(ErrorComp::message(format!(
+                "unknown target `{}`, supported targets:{supported}",
+                args[0]
+            )));
+            TargetTriple::host()
+        }
+    }
+}
+
+fn parse_emit(format: &CommandFormat, diagnostics: &mut DiagnosticCollection) -> Option<Emit> {
+    let name = "emit";
+    let args = format.options.get(name)?;
+
+    if args.is_empty() || args.len() > 2 {
+        diagnostics.error(ErrorComp::message(format!(
+            "option `--{name}` expects `ast` or `hir` and an optional output path"
+        )));
+        return None;
+    }
+    let kind = match args[0].as_str() {
+        "ast" => EmitKind::Ast,
+        "hir" => EmitKind::Hir,
+        _ => {
+            diagnostics.error(ErrorComp::message(format!(
+                "invalid emit kind `{}`, expected `ast` or `hir`",
+                args[0]
+            )));
+            return None;
+        }
+    };
+    let path = args.get(1).map(PathBuf::from);
+    Some(Emit { kind, path })
+}
+
+fn parse_graph_format(
+    format: &CommandFormat,
+    diagnostics: &mut DiagnosticCollection,
+) -> GraphFormat {
+    let name = "format";
+    let default = GraphFormat::Dot;
+    let args = match format.options.get(name) {
+        Some(args) => args,
+        None => return default,
+    };
+
+    if args.len() != 1 {
+        diagnostics.error(ErrorComp::message(format!(
+            "option `--{name}` expects one argument: `dot` or `json`"
+        )));
+        return default;
+    }
+    match args[0].as_str() {
+        "dot" => GraphFormat::Dot,
+        "json" => GraphFormat::Json,
+        _ => {
+            diagnostics.error(ErrorComp::message(format!(
+                "unknown graph format `{}`, expected `dot` or `json`",
+                args[0]
+            )));
+            default
+        }
+    }
+}
+
+fn parse_doc_format(format: &CommandFormat, diagnostics: &mut DiagnosticCollection) -> DocFormat {
+    let name = "format";
+    let default = DocFormat::Markdown;
+    let args = match format.options.get(name) {
+        Some(args) => args,
+        None => return default,
+    };
+
+    if args.len() != 1 {
+        diagnostics.error(ErrorComp::message(format!(
+            "option `--{name}` expects one argument: `markdown` or `html`"
+        )));
+        return default;
+    }
+    match args[0].as_str() {
+        "markdown" => DocFormat::Markdown,
+        "html" => DocFormat::Html,
+        _ => {
+            diagnostics.error(ErrorComp::message(format!(
+                "unknown doc format `{}`, expected `markdown` or `html`",
+                args[0]
+            )));
+            default
+        }
     }
 }
 
@@ -0,0 +1,237 @@
+/// command line interface of a command,
+/// used to validate options and print `rock help <command>`
+pub struct CommandSpec {
+    pub name: &'static str,
+    pub alias: Option<&'static str>,
+    pub args: &'static str,
+    pub about: &'static str,
+    pub options: &'static [OptionSpec],
+}
+
+pub struct OptionSpec {
+    pub name: &'static str,
+    pub value: Option<&'static str>,
+    pub about: &'static str,
+}
+
+/// accepted by every command, not listed in command options
+pub const GLOBAL_OPTIONS: &[OptionSpec] = &[
+    option(
+        "color",
+        Some("<when>"),
+        "Colored output: auto, always, never",
+    ),
+    option("help", None, "Print help of the command"),
+];
+
+pub const COMMANDS: &[CommandSpec] = &[
+    CommandSpec {
+        name: "new",
+        alias: Some("n"),
+        args: "<name>",
+        about: "Create new package",
+        options: &[LIB, BIN, NO_GIT],
+    },
+    CommandSpec {
+        name: "init",
+        alias: Some("i"),
+        args: "",
+        about: "Create package in current directory",
+        options: &[LIB, BIN, NO_GIT],
+    },
+    CommandSpec {
+        name: "check",
+        alias: Some("c"),
+        args: "",
+        about: "Check the program",
+        options: &[
+            option("watch", None, "Check again when source files change"),
+            EMIT,
+            MESSAGE_FORMAT,
+            ALLOW,
+            WARN,
+            DENY,
+            ERROR_LIMIT,
+            TARGET,
+            TIMINGS,
+        ],
+    },
+    CommandSpec {
+        name: "build",
+        alias: Some("b"),
+        args: "",
+        about: "Build the program",
+        options: &[
+            DEBUG,
+            RELEASE,
+            EMIT,
+            EMIT_LLVM,
+            MESSAGE_FORMAT,
+            ALLOW,
+            WARN,
+            DENY,
+            ERROR_LIMIT,
+            TARGET,
+            TIMINGS,
+        ],
+    },
+    CommandSpec {
+        name: "run",
+        alias: Some("r"),
+        args: "[-- args]",
+        about: "Build and run the program",
+        options: &[
+            DEBUG,
+            RELEASE,
+            EMIT_LLVM,
+            MESSAGE_FORMAT,
+            ALLOW,
+            WARN,
+            DENY,
+            ERROR_LIMIT,
+            TARGET,
+            TIMINGS,
+        ],
+    },
+    CommandSpec {
+        name: "test",
+        alias: Some("t"),
+        args: "[name]",
+        about: "Build and run `#[test]` procedures",
+        options: &[
+            DEBUG,
+            RELEASE,
+            MESSAGE_FORMAT,
+            ALLOW,
+            WARN,
+            DENY,
+            ERROR_LIMIT,
+            TARGET,
+            TIMINGS,
+        ],
+    },
+    CommandSpec {
+        name: "clean",
+        alias: None,
+        args: "",
+        about: "Remove the build directory",
+        options: &[
+            option("debug", None, "Remove only debug build artifacts"),
+            option("release", None, "Remove only release build artifacts"),
+        ],
+    },
+    CommandSpec {
+        name: "fmt",
+        alias: Some("f"),
+        args: "",
+        about: "Format the source files",
+        options: &[option(
+            "check",
+            None,
+            "Report unformatted files without changing them",
+        )],
+    },
+    CommandSpec {
+        name: "graph",
+        alias: None,
+        args: "",
+        about: "Print the module import graph",
+        options: &[option(
+            "format",
+            Some("<dot|json>"),
+            "Output format, defaults to dot",
+        )],
+    },
+    CommandSpec {
+        name: "doc",
+        alias: Some("d"),
+        args: "",
+        about: "Generate documentation of public items",
+        options: &[option(
+            "format",
+            Some("<markdown|html>"),
+            "Output format, defaults to markdown",
+        )],
+    },
+    CommandSpec {
+        name: "explain",
+        alias: Some("e"),
+        args: "<code>",
+        about: "Explain an error code",
+        options: &[],
+    },
+    CommandSpec {
+        name: "help",
+        alias: Some("h"),
+        args: "[command]",
+        about: "Print help information",
+        options: &[],
+    },
+    CommandSpec {
+        name: "version",
+        alias: Some("v"),
+        args: "",
+        about: "Print compiler version",
+        options: &[option("verbose", None, "Print version, commit and target")],
+    },
+];
+
+const LIB: OptionSpec = option("lib", None, "Create library package");
+const BIN: OptionSpec = option("bin", None, "Create executable package");
+const NO_GIT: OptionSpec = option("no-git", None, "Create package without git repo");
+const DEBUG: OptionSpec = option("debug", None, "Build in debug mode");
+const RELEASE: OptionSpec = option("release", None, "Build in release mode");
+const EMIT_LLVM: OptionSpec = option("emit-llvm", None, "Save llvm module to file");
+const ALLOW: OptionSpec = option("allow", Some("<codes>"), "Do not report these warnings");
+const WARN: OptionSpec = option("warn", Some("<codes>"), "Report these as warnings");
+const DENY: OptionSpec = option("deny", Some("<codes>"), "Report these warnings as errors");
+const ERROR_LIMIT: OptionSpec = option("error-limit", Some("<n>"), "Stop after reporting n errors");
+const TIMINGS: OptionSpec = option("timings", None, "Print time and memory used by each stage");
+const EMIT: OptionSpec = option(
+    "emit",
+    Some("<ast|hir> [path]"),
+    "Print parsed or lowered program, to stdout or file",
+);
+const MESSAGE_FORMAT: OptionSpec = option(
+    "message-format",
+    Some("<fmt>"),
+    "Diagnostic format: human, json",
+);
+const TARGET: OptionSpec = option(
+    "target",
+    Some("<triple>"),
+    "Target to compile for, defaults to host",
+);
+
+const fn option(
+    name: &'static str,
+    value: Option<&'static str>,
+    about: &'static str,
+) -> OptionSpec {
+    OptionSpec { name, value, about }
+}
+
+impl CommandSpec {
+    pub fn find(name: &str) -> Option<&'static CommandSpec> {
+        COMMANDS
+            .iter()
+            .find(|spec| spec.name == name || spec.alias == Some(name))
+    }
+
+    pub fn option(&self, name: &str) -> Option<&'static OptionSpec> {
+        self.options
+            .iter()
+            .chain(GLOBAL_OPTIONS.iter())
+            .find(|option| option.name == name)
+    }
+}
+
+impl OptionSpec {
+    /// `--name <value>` as shown in help
+    pub fn usage(&self) -> String {
+        match self.value {
+            Some(value) => format!("--{} {value}", self.name),
+            None => format!("--{}", self.name),
+        }
+    }
+}
@@ -2,12 +2,39 @@ use crate::ansi;
 use rock_core::error::{
     Diagnostic, DiagnosticCollection, DiagnosticContext, DiagnosticKind, DiagnosticSeverity,
 };
-use rock_core::session::{RockModule, Session};
+use rock_core::session::Session;
 use rock_core::text::{self, TextLocation, TextRange};
-use std::io::{BufWriter, Stderr, Write};
+use std::io::{BufWriter, Stderr, Stdout, Write};
 use std::path::Path;
 
-pub fn print_errors(session: Option<&Session>, diagnostics: DiagnosticCollection) {
+#[derive(Copy, Clone, PartialEq)]
+pub enum MessageFormat {
+    Human,
+    Json,
+}
+
+impl MessageFormat {
+    pub fn from_str(string: &str) -> Option<MessageFormat> {
+        match string {
+            "human" => Some(MessageFormat::Human),
+            "json" => Some(MessageFormat::Json),
+            _ => None,
+        }
+    }
+}
+
+pub fn print_errors(
+    session: Option<&Session>,
+    diagnostics: DiagnosticCollection,
+    format: MessageFormat,
+) {
+    match format {
+        MessageFormat::Human => print_errors_human(session, diagnostics),
+        MessageFormat::Json => print_errors_json(session, diagnostics),
+    }
+}
+
+fn print_errors_human(session: Option<&Session>, diagnostics: DiagnosticCollection) {
     let mut handle = BufWriter::new(std::io::stderr());
     let mut state = StateFmt::new();
 
@@ -33,36 +60,38 @@ pub fn print_errors(session: Option<&Session>, diagnostics: DiagnosticCollection
 }
 
 struct StateFmt<'src> {
-    line_num_offset: usize,
+    line_num_width: usize,
     context_fmts: Vec<ContextFmt<'src>>,
 }
 
 struct ContextFmt<'src> {
-    module: &'src RockModule,
+    source: &'src str,
+    line_ranges: &'src [TextRange],
     path: &'src Path,
     message: &'src str,
     range: TextRange,
     location: TextLocation,
-    line_range: TextRange,
-    line_num: String,
+    line_start: usize,
+    line_end: usize,
     severity: DiagnosticSeverity,
 }
 
 impl<'src> StateFmt<'src> {
     fn new() -> StateFmt<'src> {
         StateFmt {
-            line_num_offset: 0,
+            line_num_width: 0,
             context_fmts: Vec::with_capacity(8),
         }
     }
 
     fn reset(&mut self) {
-        self.line_num_offset = 0;
+        self.line_num_width = 0;
         self.context_fmts.clear();
     }
 
     fn push(&mut self, fmt: ContextFmt<'src>) {
-        self.line_num_offset = self.line_num_offset.max(fmt.line_num.len());
+        let line_num = fmt.line_end + 1;
+        self.line_num_width = self.line_num_width.max(line_num.to_string().len());
         self.context_fmts.push(fmt);
     }
 }
@@ -79,22 +108,52 @@ impl<'src> ContextFmt<'src> {
             .strip_prefix(session.cwd())
             .unwrap_or_else(|_| &module.path);
 
-        let range = context.source().range();
-        let location = text::find_text_location(&module.source, range.start(), &module.line_ranges);
-        let line_num = location.line().to_string();
-        let line_range = module.line_ranges[location.line_index()];
+        ContextFmt::from_source(
+            &module.source,
+            &module.line_ranges,
+            path,
+            context.message(),
+            context.source().range(),
+            severity,
+        )
+    }
+
+    fn from_source(
+        source: &'src str,
+        line_ranges: &'src [TextRange],
+        path: &'src Path,
+        message: &'src str,
+        range: TextRange,
+        severity: DiagnosticSeverity,
+    ) -> ContextFmt<'src> {
+        let location = text::find_text_location(source, range.start(), line_ranges);
+        let line_start = location.line_index();
+        // last line is the one containing the last character of the range
+        let line_end = if range.end() > range.start() {
+            let last = range.end() - 1.into();
+            text::find_text_location(source, last, line_ranges).line_index()
+        } else {
+            line_start
+        };
 
         ContextFmt {
-            module,
+            source,
+            line_ranges,
             path,
-            message: context.message(),
+            message,
             range,
             location,
-            line_range,
-            line_num,
+            line_start,
+            line_end,
             severity,
         }
     }
+
+    fn line_str(&self, line_idx: usize) -> String {
+        let line_range = self.line_ranges[line_idx];
+        let line_str = &self.source[line_range.as_usize()];
+        line_str.trim_end().replace('\t', TAB_REPLACE_STR)
+    }
 }
 
 fn print_diagnostic<'src>(
@@ -105,9 +164,13 @@ fn print_diagnostic<'src>(
     handle: &mut BufWriter<Stderr>,
 ) {
     let message = diagnostic.message().as_str();
+    let code = match diagnostic.code() {
+        Some(code) => format!("[{}]", code.as_str()),
+        None => String::new(),
+    };
     let _ = writeln!(
         handle,
-        "{}{}: {}{message}{}",
+        "{}{}{code}: {}{message}{}",
         severity_color(severity),
         severity_name(severity),
         ansi::WHITE_BOLD,
@@ -139,49 +202,93 @@ fn print_diagnostic<'src>(
         }
     };
 
-    let line_pad = " ".repeat(state.line_num_offset);
     for (idx, fmt) in state.context_fmts.iter().enumerate() {
         let last = idx + 1 == state.context_fmts.len();
-        let line_num_pad = " ".repeat(state.line_num_offset - fmt.line_num.len());
-        print_context(handle, fmt, last, &line_pad, &line_num_pad);
+        print_context(handle, fmt, last, state.line_num_width);
     }
     let _ = write!(handle, "\n");
 }
 
-fn print_context(
-    handle: &mut BufWriter<Stderr>,
-    fmt: &ContextFmt,
-    last: bool,
-    line_pad: &str,
-    line_num_pad: &str,
-) {
-    let prefix_range = TextRange::new(fmt.line_range.start(), fmt.range.start());
-    let source_range = TextRange::new(
-        fmt.range.start(),
-        (fmt.line_range.end() - 1.into()).min(fmt.range.end()),
-    );
-
-    let line_str = &fmt.module.source[fmt.line_range.as_usize()];
-    let prefix_str = &fmt.module.source[prefix_range.as_usize()];
-    let source_str = &fmt.module.source[source_range.as_usize()];
-
-    let line = line_str.trim_end().replace('\t', TAB_REPLACE_STR);
-    let marker_pad = " ".repeat(normalized_tab_len(prefix_str));
-    let marker = severity_marker(fmt.severity).repeat(normalized_tab_len(source_str));
-    let message = fmt.message;
+// spans covering more lines are compressed,
+// only first and last lines are displayed
+const SPAN_MAX_LINES: usize = 6;
+const SPAN_EDGE_LINES: usize = 2;
 
+fn print_context(handle: &mut impl Write, fmt: &ContextFmt, last: bool, line_num_width: usize) {
     let c = ansi::CYAN;
     let r = ansi::RESET;
+    let s = severity_color(fmt.severity);
+    let line_pad = " ".repeat(line_num_width);
+    let marker = severity_marker(fmt.severity);
+    let message = fmt.message;
     let box_char = if last { '└' } else { '├' };
 
+    let _ = writeln!(handle, "{line_pad} {c}│");
+
+    let first_range = fmt.line_ranges[fmt.line_start];
+    let prefix_range = TextRange::new(first_range.start(), fmt.range.start());
+    let marker_pad = normalized_tab_len(&fmt.source[prefix_range.as_usize()]);
+
+    if fmt.line_start == fmt.line_end {
+        let source_range = TextRange::new(
+            fmt.range.start(),
+            (first_range.end() - 1.into()).min(fmt.range.end()),
+        );
+        let source_str = &fmt.source[source_range.as_usize()];
+        let marker_pad = " ".repeat(marker_pad);
+        let marker = marker.repeat(normalized_tab_len(source_str));
+
+        let _ = writeln!(
+            handle,
+            "{:<line_num_width$} │{r} {}",
+            fmt.line_start + 1,
+            fmt.line_str(fmt.line_start)
+        );
+        let _ = writeln!(handle, "{line_pad} {c}│ {marker_pad}{s}{marker} {message}");
+    } else {
+        let last_range = fmt.line_ranges[fmt.line_end];
+        let suffix_range = TextRange::new(last_range.start(), fmt.range.end());
+        let end_pad = normalized_tab_len(&fmt.source[suffix_range.as_usize()]).saturating_sub(1);
+        let compress = fmt.line_end - fmt.line_start + 1 > SPAN_MAX_LINES;
+
+        let _ = writeln!(
+            handle,
+            "{:<line_num_width$} │{r}   {}",
+            fmt.line_start + 1,
+            fmt.line_str(fmt.line_start)
+        );
+        let _ = writeln!(
+            handle,
+            "{c}{line_pad} │ {s}┌{}{marker}",
+            "─".repeat(marker_pad + 1)
+        );
+        for line_idx in fmt.line_start + 1..=fmt.line_end {
+            if compress
+                && line_idx > fmt.line_start + SPAN_EDGE_LINES
+                && line_idx + SPAN_EDGE_LINES <= fmt.line_end
+            {
+                if line_idx == fmt.line_start + SPAN_EDGE_LINES + 1 {
+                    let _ = writeln!(handle, "{c}{line_pad} ┆ {s}│");
+                }
+                continue;
+            }
+            let _ = writeln!(
+                handle,
+                "{c}{:<line_num_width$} │ {s}│{r} {}",
+                line_idx + 1,
+                fmt.line_str(line_idx)
+            );
+        }
+        let _ = writeln!(
+            handle,
+            "{c}{line_pad} │ {s}└{}{marker} {message}",
+            "─".repeat(end_pad + 1)
+        );
+    }
+
     let _ = writeln!(
         handle,
-        r#"{line_pad} {c}│
-{}{line_num_pad} │{r} {line}
-{line_pad} {c}│ {marker_pad}{}{marker} {message}
-{line_pad} {c}{box_char}─ {}:{:?}{r}"#,
-        fmt.line_num,
-        severity_color(fmt.severity),
+        "{line_pad} {c}{box_char}─ {}:{:?}{r}",
         fmt.path.to_string_lossy(),
         fmt.location,
     );
@@ -212,10 +319,189 @@ const fn severity_marker(severity: DiagnosticSeverity) -> &'static str {
     }
 }
 
-const fn severity_color(severity: DiagnosticSeverity) -> &'static str {
+const fn severity_color(severity: DiagnosticSeverity) -> ansi::Ansi {
     match severity {
         DiagnosticSeverity::Info => ansi::GREEN_BOLD,
         DiagnosticSeverity::Error => ansi::RED_BOLD,
         DiagnosticSeverity::Warning => ansi::YELLOW_BOLD,
     }
 }
+
+// json output: one diagnostic object per line on stdout
+// {"severity","code","message","contexts":[{"severity","message","path","range":[start,end],"line","col"}]}
+// first context is the main one, others are related info
+fn print_errors_json(session: Option<&Session>, diagnostics: DiagnosticCollection) {
+    let mut handle = BufWriter::new(std::io::stdout());
+
+    for warning in diagnostics.warnings() {
+        print_diagnostic_json(
+            session,
+            warning.diagnostic(),
+            DiagnosticSeverity::Warning,
+            &mut handle,
+        );
+    }
+    for error in diagnostics.errors() {
+        print_diagnostic_json(
+            session,
+            error.diagnostic(),
+            DiagnosticSeverity::Error,
+            &mut handle,
+        );
+    }
+    let _ = handle.flush();
+}
+
+fn print_diagnostic_json(
+    session: Option<&Session>,
+    diagnostic: &Diagnostic,
+    severity: DiagnosticSeverity,
+    handle: &mut BufWriter<Stdout>,
+) {
+    let mut json = String::with_capacity(256);
+    json.push_str("{\"severity\":");
+    json_string(&mut json, severity_name(severity));
+    json.push_str(",\"code\":");
+    match diagnostic.code() {
+        Some(code) => json_string(&mut json, code.as_str()),
+        None => json.push_str("null"),
+    }
+    json.push_str(",\"message\":");
+    json_string(&mut json, diagnostic.message().as_str());
+    json.push_str(",\"contexts\":[");
+
+    match diagnostic.kind() {
+        DiagnosticKind::Message => {}
+        DiagnosticKind::Context { main, info } => {
+            let session = session.expect("session context");
+            json_context(&mut json, session, main, severity);
+            if let Some(info) = info {
+                json.push(',');
+                json_context(&mut json, session, info, DiagnosticSeverity::Info);
+            }
+        }
+        DiagnosticKind::ContextVec { main, info_vec } => {
+            let session = session.expect("session context");
+            json_context(&mut json, session, main, severity);
+            for info in info_vec {
+                json.push(',');
+                json_context(&mut json, session, info, DiagnosticSeverity::Info);
+            }
+        }
+    }
+
+    json.push_str("]}");
+    let _ = writeln!(handle, "{json}");
+}
+
+fn json_context(
+    json: &mut String,
+    session: &Session,
+    context: &DiagnosticContext,
+    severity: DiagnosticSeverity,
+) {
+    let module = session.module(context.source().module_id());
+    let range = context.source().range();
+    let location = text::find_text_location(&module.source, range.start(), &module.line_ranges);
+    let start: u32 = range.start().into();
+    let end: u32 = range.end().into();
+
+    json.push_str("{\"severity\":");
+    json_string(json, severity_name(severity));
+    json.push_str(",\"message\":");
+    json_string(json, context.message());
+    json.push_str(",\"path\":");
+    json_string(json, &module.path.to_string_lossy());
+    json.push_str(&format!(
+        ",\"range\":[{start},{end}],\"line\":{},\"col\":{}}}",
+        location.line(),
+        location.col()
+    ));
+}
+
+pub fn json_string(json: &mut String, string: &str) {
+    json.push('"');
+    for c in string.chars() {
+        match c {
+            '"' => json.push_str("\\\""),
+            '\\' => json.push_str("\\\\"),
+            '\n' => json.push_str("\\n"),
+            '\r' => json.push_str("\\r"),
+            '\t' => json.push_str("\\t"),
+            c if (c as u32) < 0x20 => json.push_str(&format!("\\u{:04x}", c as u32)),
+            c => json.push(c),
+        }
+    }
+    json.push('"');
+}
+
+#[cfg(test)]
+fn render_context(source: &str, range: (u32, u32), message: &str) -> String {
+    let line_ranges = text::find_line_ranges(source);
+    let range = TextRange::new(range.0.into(), range.1.into());
+    let path = Path::new("src/main.rock");
+    let fmt = ContextFmt::from_source(
+        source,
+        &line_ranges,
+        path,
+        message,
+        range,
+        DiagnosticSeverity::Error,
+    );
+
+    let mut output = Vec::new();
+    let line_num_width = (fmt.line_end + 1).to_string().len();
+    print_context(&mut output, &fmt, true, line_num_width);
+    // colors are disabled until `ansi::init` is called
+    String::from_utf8(output).unwrap()
+}
+
+#[test]
+fn test_render_single_line() {
+    let source = "proc main() {\n    let x = 5;\n}\n";
+    let output = render_context(source, (22, 23), "unused");
+    let expected = r#"  │
+2 │     let x = 5;
+  │         ^ unused
+  └─ src/main.rock:2:9
+"#;
+    assert_eq!(output, expected);
+}
+
+#[test]
+fn test_render_multi_line() {
+    let source = "proc main() {\n    let x = foo(\n        1,\n        2);\n}\n";
+    let output = render_context(source, (26, 53), "mismatch");
+    let expected = r#"  │
+2 │       let x = foo(
+  │ ┌─────────────^
+3 │ │         1,
+4 │ │         2);
+  │ └───────────^ mismatch
+  └─ src/main.rock:2:13
+"#;
+    assert_eq!(output, expected);
+}
+
+#[test]
+fn test_render_multi_line_compressed() {
+    let mut source = String::from("proc main() {\n");
+    for idx in 0..10 {
+        source.push_str(&format!("    a{idx};\n"));
+    }
+    source.push_str("}\n");
+    let end = source.len() as u32 - 1;
+    let output = render_context(&source, (12, end), "long");
+    let expected = r#"   │
+1  │   proc main() {
+   │ ┌─────────────^
+2  │ │     a0;
+3  │ │     a1;
+   ┆ │
+11 │ │     a9;
+12 │ │ }
+   │ └─^ long
+   └─ src/main.rock:1:13
+"#;
+    assert_eq!(output, expected);
+}
@@ -1,9 +1,10 @@
-#![forbid(unsafe_code)]
+#![deny(unsafe_code)]
 
 #[allow(dead_code)]
 mod ansi;
 mod command;
 mod error_format;
+mod report;
 
 pub fn main() {
     command::run();
@@ -0,0 +1,104 @@
+use crate::ansi;
+use crate::error_format::MessageFormat;
+use rock_core::error::{DiagnosticCode, DiagnosticCollection, ErrorCode};
+use rock_core::session::Session;
+use std::time::Duration;
+
+/// process exit codes, distinct so CI scripts can tell failure modes apart
+#[derive(Copy, Clone, PartialEq)]
+pub enum ExitCode {
+    Success = 0,
+    Errors = 1,
+    Internal = 2,
+}
+
+/// diagnostic counts of a single `check` or `build` run
+pub struct Summary {
+    pub errors: usize,
+    pub warnings: usize,
+    pub files: usize,
+    pub elapsed: Duration,
+    pub exit_code: ExitCode,
+}
+
+impl ExitCode {
+    pub fn from_diagnostics(diagnostics: &DiagnosticCollection) -> ExitCode {
+        let internal = DiagnosticCode::Error(ErrorCode::Internal);
+        if diagnostics
+            .errors()
+            .iter()
+            .any(|error| error.diagnostic().code() == Some(internal))
+        {
+            ExitCode::Internal
+        } else if diagnostics.has_errors() {
+            ExitCode::Errors
+        } else {
+            ExitCode::Success
+        }
+    }
+
+    /// exits the process unless `self` is `Success`
+    pub fn exit_on_failure(self) {
+        if self != ExitCode::Success {
+            std::process::exit(self as i32);
+        }
+    }
+}
+
+impl Summary {
+    /// `files` counts modules of all packages in the session,
+    /// `elapsed` includes session load
+    pub fn new(
+        session: Option<&Session>,
+        diagnostics: &DiagnosticCollection,
+        elapsed: Duration,
+    ) -> Summary {
+        Summary {
+            errors: diagnostics.errors().len(),
+            warnings: diagnostics.warnings().len(),
+            files: session.map_or(0, |session| session.module_ids().count()),
+            elapsed,
+            exit_code: ExitCode::from_diagnostics(diagnostics),
+        }
+    }
+}
+
+// printed to stderr, stdout can hold json diagnostics or `--emit` output
+pub fn print_summary(summary: &Summary, verb: &str, format: MessageFormat) {
+    if format != MessageFormat::Human {
+        return;
+    }
+    let color = if summary.errors == 0 {
+        ansi::GREEN_BOLD
+    } else {
+        ansi::RED_BOLD
+    };
+    let r = ansi::RESET;
+    eprintln!(
+        "  {color}{verb}{r} {}, {} in {}, {:.2} ms",
+        plural(summary.errors, "error"),
+        plural(summary.warnings, "warning"),
+        plural(summary.files, "file"),
+        summary.elapsed.as_secs_f64() * 1000.0,
+    );
+}
+
+fn plural(count: usize, noun: &str) -> String {
+    if count == 1 {
+        format!("{count} {noun}")
+    } else {
+        format!("{count} {noun}s")
+    }
+}
+
+#[test]
+fn test_exit_code() {
+    use rock_core::error::{ice::Ice, ErrorComp};
+
+    let empty = DiagnosticCollection::new();
+    assert!(ExitCode::from_diagnostics(&empty) == ExitCode::Success);
+    let errors = DiagnosticCollection::new().join_errors(vec![ErrorComp::message("error")]);
+    assert!(ExitCode::from_diagnostics(&errors) == ExitCode::Errors);
+    let internal = errors.join_errors(vec![Ice::new("bug").into_error()]);
+    assert!(ExitCode::from_diagnostics(&internal) == ExitCode::Internal);
+}
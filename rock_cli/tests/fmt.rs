@@ -0,0 +1,44 @@
+//! `rock fmt --check` exit status
+//!
+//! the `rock` executable is run inside a temporary library package without dependencies,
+//! `--check` must fail on unformatted modules without rewriting them
+
+use std::path::Path;
+use std::process::Command;
+
+const MANIFEST: &str = "[package]\nname = \"fmt_check\"\nkind = \"lib\"\nversion = \"0.1.0\"\n\n[build]\n\n[dependencies]\n";
+const UNFORMATTED: &str = "pub proc add( a:s32,b:s32 )->s32 { return a+b; }\n";
+const FORMATTED: &str = "pub proc add(a: s32, b: s32) -> s32 { return a + b; }\n";
+
+#[test]
+fn fmt_check() {
+    let root_dir = std::env::temp_dir().join(format!("rock_fmt_check_{}", std::process::id()));
+    let module_path = root_dir.join("src").join("lib.rock");
+    std::fs::create_dir_all(root_dir.join("src")).unwrap();
+    std::fs::write(root_dir.join("Rock.toml"), MANIFEST).unwrap();
+    std::fs::write(&module_path, UNFORMATTED).unwrap();
+
+    let check = rock_fmt(&root_dir, true);
+    let source = std::fs::read_to_string(&module_path).unwrap();
+    assert!(check == Some(1), "unformatted module must fail the check");
+    assert!(source == UNFORMATTED, "check must not rewrite the module");
+
+    let format = rock_fmt(&root_dir, false);
+    let source = std::fs::read_to_string(&module_path).unwrap();
+    assert!(format == Some(0));
+    assert!(source == FORMATTED);
+
+    let check = rock_fmt(&root_dir, true);
+    assert!(check == Some(0), "formatted module must pass the check");
+    std::fs::remove_dir_all(&root_dir).unwrap();
+}
+
+fn rock_fmt(root_dir: &Path, check: bool) -> Option<i32> {
+    let mut command = Command::new(env!("CARGO_BIN_EXE_rock"));
+    command.arg("fmt").current_dir(root_dir);
+    if check {
+        command.arg("--check");
+    }
+    let output = command.output().expect("failed to run `rock`");
+    output.status.code()
+}
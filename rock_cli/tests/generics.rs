@@ -0,0 +1,36 @@
+//! `rock check` of recursive generic procedures
+//!
+//! the `rock` executable is run inside a temporary library package with generics enabled,
+//! recursion with a growing type must be reported instead of creating instances until the limit
+
+use std::process::Command;
+
+const MANIFEST: &str = "[package]\nname = \"generic_recursion\"\nkind = \"lib\"\nversion = \"0.1.0\"\n\n[build]\nunstable_generics = true\n\n[dependencies]\n";
+const SOURCE: &str = "\
+proc grow[T](x: T) -> s32 { return grow([x, x]); }
+proc settle[T](x: T, n: s32) -> s32 { if n == 0 { return 0; } return settle([1, 2], n - 1); }
+pub proc check() -> s32 { return grow(1) + settle(1, 3); }
+";
+
+#[test]
+fn generic_recursion() {
+    let root_dir =
+        std::env::temp_dir().join(format!("rock_generic_recursion_{}", std::process::id()));
+    std::fs::create_dir_all(root_dir.join("src")).unwrap();
+    std::fs::write(root_dir.join("Rock.toml"), MANIFEST).unwrap();
+    std::fs::write(root_dir.join("src").join("lib.rock"), SOURCE).unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_rock"))
+        .args(["check", "--color", "never"])
+        .current_dir(&root_dir)
+        .output()
+        .expect("failed to run `rock`");
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let message =
+        "error[E0066]: generic procedure `grow` is instantiated with a type nested too deeply";
+    assert!(output.status.code() == Some(1));
+    assert!(stderr.contains(message) || stdout.contains(message));
+    assert!(stderr.matches("error[").count() + stdout.matches("error[").count() == 1);
+    std::fs::remove_dir_all(&root_dir).unwrap();
+}
@@ -0,0 +1,45 @@
+//! `rock run` of enums with variant fields
+//!
+//! the `rock` executable builds and runs a temporary binary package,
+//! exit code of the program is computed from the variants it creates and their fields
+//! codegen is not available on linux, the test only runs on other hosts
+#![cfg(not(target_os = "linux"))]
+
+use std::process::Command;
+
+const MANIFEST: &str = "[package]\nname = \"variants\"\nkind = \"bin\"\nversion = \"0.1.0\"\n\n[build]\n\n[dependencies]\n";
+const SOURCE: &str = "\
+enum Shape { Circle(f32) = 0, Rect(s32, s32) = 1, Point = 2 }
+const POINT: Shape = Shape.Point;
+proc make(n: s32) -> Shape {
+    if n == 0 { return Shape.Circle(1.0); }
+    if n == 1 { return Shape.Rect(2, 3); }
+    return POINT;
+}
+proc kind(shape: Shape) -> s32 {
+    return match shape { .Circle(_) -> 1, .Rect(_, _) -> 10, .Point -> 100, };
+}
+proc area(shape: Shape) -> s32 {
+    return match shape { .Circle(radius) -> radius as s32, .Rect(w, h) -> w * h, .Point -> 0, };
+}
+proc main() -> s32 {
+    let kinds = kind(make(0)) + kind(make(1)) + kind(make(2));
+    return kinds + area(make(0)) + area(make(1)) + area(make(2));
+}
+";
+
+#[test]
+fn variant_fields() {
+    let root_dir = std::env::temp_dir().join(format!("rock_variants_{}", std::process::id()));
+    std::fs::create_dir_all(root_dir.join("src")).unwrap();
+    std::fs::write(root_dir.join("Rock.toml"), MANIFEST).unwrap();
+    std::fs::write(root_dir.join("src").join("main.rock"), SOURCE).unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_rock"))
+        .args(["run", "--color", "never"])
+        .current_dir(&root_dir)
+        .output()
+        .expect("failed to run `rock`");
+    assert!(output.status.code() == Some(118));
+    std::fs::remove_dir_all(&root_dir).unwrap();
+}
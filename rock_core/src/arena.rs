@@ -41,21 +41,42 @@ impl<'arena> Arena<'arena> {
         unsafe { std::str::from_utf8_unchecked(bytes) }
     }
 
+    // memory is retained for reuse, only the largest block is kept.
+    // arena is consumed, references from previous lifetime can no longer be created.
+    pub fn reset<'new>(self) -> Arena<'new> {
+        let mut arena = std::mem::ManuallyDrop::new(self);
+        for block in arena.full_blocks.drain(..) {
+            dealloc(block);
+        }
+        Arena {
+            offset: 0,
+            block: arena.block,
+            full_blocks: std::mem::take(&mut arena.full_blocks),
+            phantom: PhantomData,
+        }
+    }
+
     fn offset_raw<T: Copy>(&mut self, size: usize) -> *mut T {
-        if self.offset + size > self.block.1.size() {
-            self.grow();
+        let align = std::mem::align_of::<T>();
+        let mut offset = self.offset.next_multiple_of(align);
+
+        if offset + size > self.block.1.size() {
+            self.grow(size);
+            offset = 0;
         }
         unsafe {
-            let offset = self.block.0.add(self.offset) as *mut T;
-            self.offset += size;
-            offset
+            let ptr = self.block.0.add(offset) as *mut T;
+            self.offset = offset + size;
+            ptr
         }
     }
 
-    fn grow(&mut self) {
-        self.full_blocks.push(self.block);
+    fn grow(&mut self, min_size: usize) {
+        let size = (self.block.1.size() * 2).min(MAX_PAGE_SIZE);
+        let size = size.max(min_size.next_multiple_of(PAGE_SIZE));
+        let block = std::mem::replace(&mut self.block, alloc_zeroed(size));
+        self.full_blocks.push(block);
         self.offset = 0;
-        self.block = alloc_zeroed((self.block.1.size() * 2).min(MAX_PAGE_SIZE));
     }
 
     pub fn mem_usage(&self) -> usize {
@@ -64,6 +85,11 @@ impl<'arena> Arena<'arena> {
     }
 }
 
+// blocks are uniquely owned by the arena,
+// allocation requires `&mut` access, shared access is read only
+unsafe impl<'arena> Send for Arena<'arena> {}
+unsafe impl<'arena> Sync for Arena<'arena> {}
+
 impl<'arena> Drop for Arena<'arena> {
     fn drop(&mut self) {
         for block in &self.full_blocks {
@@ -1,12 +1,15 @@
+pub mod print;
+pub mod query;
+pub mod visit;
+
 use crate::arena::Arena;
 use crate::intern::{InternID, InternPool};
 use crate::text::TextRange;
 
 pub struct Ast<'ast, 'intern> {
-    pub arena: Arena<'ast>,
+    pub arenas: Vec<Arena<'ast>>,
     pub intern_name: InternPool<'intern>,
     pub intern_string: InternPool<'intern>,
-    pub string_is_cstr: Vec<bool>,
     pub modules: Vec<Module<'ast>>,
 }
 
@@ -23,6 +26,7 @@ pub enum Item<'ast> {
     Const(&'ast ConstItem<'ast>),
     Global(&'ast GlobalItem<'ast>),
     Import(&'ast ImportItem<'ast>),
+    Impl(&'ast ImplItem<'ast>),
 }
 
 #[derive(Default)]
@@ -36,9 +40,11 @@ pub struct ItemCount {
 
 #[derive(Copy, Clone)]
 pub struct ProcItem<'ast> {
-    pub attrs: &'ast [Attribute],
+    pub docs: &'ast [TextRange],
+    pub attrs: &'ast [Attribute<'ast>],
     pub vis: Vis,
     pub name: Name,
+    pub poly_params: Option<&'ast [Name]>,
     pub params: &'ast [ProcParam<'ast>],
     pub is_variadic: bool,
     pub return_ty: Option<Type<'ast>>,
@@ -54,7 +60,8 @@ pub struct ProcParam<'ast> {
 
 #[derive(Copy, Clone)]
 pub struct EnumItem<'ast> {
-    pub attrs: &'ast [Attribute],
+    pub docs: &'ast [TextRange],
+    pub attrs: &'ast [Attribute<'ast>],
     pub vis: Vis,
     pub name: Name,
     pub basic: Option<BasicType>,
@@ -65,12 +72,14 @@ pub struct EnumItem<'ast> {
 #[derive(Copy, Clone)]
 pub struct EnumVariant<'ast> {
     pub name: Name,
+    pub fields: &'ast [Type<'ast>],
     pub value: ConstExpr<'ast>,
 }
 
 #[derive(Copy, Clone)]
 pub struct StructItem<'ast> {
-    pub attrs: &'ast [Attribute],
+    pub docs: &'ast [TextRange],
+    pub attrs: &'ast [Attribute<'ast>],
     pub vis: Vis,
     pub name: Name,
     pub fields: &'ast [StructField<'ast>],
@@ -81,11 +90,13 @@ pub struct StructField<'ast> {
     pub vis: Vis,
     pub name: Name,
     pub ty: Type<'ast>,
+    pub default: Option<ConstExpr<'ast>>,
 }
 
 #[derive(Copy, Clone)]
 pub struct ConstItem<'ast> {
-    pub attrs: &'ast [Attribute],
+    pub docs: &'ast [TextRange],
+    pub attrs: &'ast [Attribute<'ast>],
     pub vis: Vis,
     pub name: Name,
     pub ty: Type<'ast>,
@@ -94,7 +105,8 @@ pub struct ConstItem<'ast> {
 
 #[derive(Copy, Clone)]
 pub struct GlobalItem<'ast> {
-    pub attrs: &'ast [Attribute],
+    pub docs: &'ast [TextRange],
+    pub attrs: &'ast [Attribute<'ast>],
     pub vis: Vis,
     pub mutt: Mut,
     pub name: Name,
@@ -104,11 +116,20 @@ pub struct GlobalItem<'ast> {
 
 #[derive(Copy, Clone)]
 pub struct ImportItem<'ast> {
-    pub attrs: &'ast [Attribute],
+    pub attrs: &'ast [Attribute<'ast>],
     pub package: Option<Name>,
     pub import_path: &'ast [Name],
     pub alias: Option<Name>,
     pub symbols: &'ast [ImportSymbol],
+    /// range of `*` in `import foo.*;`
+    pub glob: Option<TextRange>,
+}
+
+#[derive(Copy, Clone)]
+pub struct ImplItem<'ast> {
+    pub attrs: &'ast [Attribute<'ast>],
+    pub path: &'ast Path<'ast>,
+    pub procs: &'ast [&'ast ProcItem<'ast>],
 }
 
 #[derive(Copy, Clone)]
@@ -136,9 +157,12 @@ pub struct Name {
 }
 
 #[derive(Copy, Clone)]
-pub struct Attribute {
+pub struct Attribute<'ast> {
     pub kind: AttributeKind,
     pub range: TextRange,
+    pub args: &'ast [Name],
+    /// string argument: `#[deprecated("message")]`, interned in `intern_string`
+    pub string: Option<InternID>,
 }
 
 #[allow(non_camel_case_types)]
@@ -148,6 +172,12 @@ pub enum AttributeKind {
     Builtin,
     Inline,
     Thread_Local,
+    Link_Name,
+    Link,
+    Call_Conv,
+    Allow,
+    No_Return,
+    Deprecated,
     Unknown,
 }
 
@@ -237,6 +267,7 @@ pub enum LoopKind<'ast> {
 #[derive(Copy, Clone)]
 pub struct Local<'ast> {
     pub mutt: Mut,
+    pub mutt_range: TextRange,
     pub name: Name,
     pub kind: LocalKind<'ast>,
 }
@@ -269,21 +300,23 @@ pub struct Expr<'ast> {
 pub enum ExprKind<'ast> {
     LitNull,
     LitBool     { val: bool },
-    LitInt      { val: u64 },
-    LitFloat    { val: f64 },
+    LitInt      { val: u64, ty: Option<BasicType> },
+    LitFloat    { val: f64, ty: Option<BasicType> },
     LitChar     { val: char },
-    LitString   { id: InternID, c_string: bool },
+    LitString   { id: InternID, kind: StringKind },
     If          { if_: &'ast If<'ast> },
     Block       { block: &'ast Block<'ast> },
     Match       { match_: &'ast Match<'ast> },
     Field       { target: &'ast Expr<'ast>, name: Name },
     Index       { target: &'ast Expr<'ast>, index: &'ast Expr<'ast> },
-    Slice       { target: &'ast Expr<'ast>, mutt: Mut, slice_range: &'ast SliceRange<'ast> },
+    Slice       { target: &'ast Expr<'ast>, mutt: Mut, range: &'ast RangeExpr<'ast> },
     Call        { target: &'ast Expr<'ast>, input: &'ast &'ast [&'ast Expr<'ast>] },
     Cast        { target: &'ast Expr<'ast>, into: &'ast Type<'ast> },
     Sizeof      { ty: &'ast Type<'ast> },
+    Format      { format: &'ast Format<'ast> },
     Item        { path: &'ast Path<'ast> },
     Variant     { name: Name },
+    Wildcard,
     StructInit  { struct_init: &'ast StructInit<'ast> },
     ArrayInit   { input: &'ast [&'ast Expr<'ast>] },
     ArrayRepeat { expr: &'ast Expr<'ast>, len: ConstExpr<'ast> },
@@ -291,6 +324,7 @@ pub enum ExprKind<'ast> {
     Address     { mutt: Mut, rhs: &'ast Expr<'ast> },
     Unary       { op: UnOp, op_range: TextRange, rhs: &'ast Expr<'ast> },
     Binary      { op: BinOp, op_range: TextRange, bin: &'ast BinExpr<'ast> },
+    Range       { range: &'ast RangeExpr<'ast> },
 }
 
 #[derive(Copy, Clone)]
@@ -316,10 +350,33 @@ pub struct Match<'ast> {
 
 #[derive(Copy, Clone)]
 pub struct MatchArm<'ast> {
-    pub pat: ConstExpr<'ast>,
+    pub pats: &'ast [MatchPat<'ast>],
     pub expr: &'ast Expr<'ast>,
 }
 
+/// alternative of the arm pattern, alternatives are separated by `|`
+#[derive(Copy, Clone)]
+pub enum MatchPat<'ast> {
+    Value(ConstExpr<'ast>),
+    Range {
+        start: ConstExpr<'ast>,
+        end: ConstExpr<'ast>,
+        inclusive: bool,
+    },
+}
+
+#[derive(Copy, Clone)]
+pub struct Format<'ast> {
+    pub kind: FormatKind,
+    pub input: &'ast [&'ast Expr<'ast>],
+}
+
+#[derive(Copy, Clone, PartialEq)]
+pub enum FormatKind {
+    Print,
+    Format,
+}
+
 #[derive(Copy, Clone)]
 pub struct StructInit<'ast> {
     pub path: Option<&'ast Path<'ast>>,
@@ -332,20 +389,16 @@ pub struct FieldInit<'ast> {
     pub expr: &'ast Expr<'ast>,
 }
 
-//@this syntax doesnt allow expressions that might be Range or RangeInc 07.05.24
-// eg:
-// let array = [1, 2, 3];
-// let range = 0..<10;
-// let slice: []s32 = array[range];
-
+/// `..`, `lower..`, `..<upper` or `lower..=upper`,
+/// parsed with lower precedence than any binary operator
 #[derive(Copy, Clone)]
-pub struct SliceRange<'ast> {
+pub struct RangeExpr<'ast> {
     pub lower: Option<&'ast Expr<'ast>>,
-    pub upper: SliceRangeEnd<'ast>,
+    pub upper: RangeEnd<'ast>,
 }
 
 #[derive(Copy, Clone)]
-pub enum SliceRangeEnd<'ast> {
+pub enum RangeEnd<'ast> {
     Unbounded,
     Exclusive(&'ast Expr<'ast>),
     Inclusive(&'ast Expr<'ast>),
@@ -357,6 +410,14 @@ pub struct BinExpr<'ast> {
     pub rhs: &'ast Expr<'ast>,
 }
 
+/// `"..."` is a `[]u8` slice, `c"..."` a null terminated `&u8`, `b"..."` a `[N]u8` array
+#[derive(Copy, Clone, PartialEq, Hash)]
+pub enum StringKind {
+    Regular,
+    CString,
+    Bytes,
+}
+
 #[derive(Copy, Clone, PartialEq, Hash)]
 pub enum BasicType {
     S8,
@@ -406,8 +467,6 @@ pub enum BinOp {
     GreaterEq,
     LogicAnd,
     LogicOr,
-    Range,
-    RangeInc,
 }
 
 #[derive(Copy, Clone)]
@@ -424,6 +483,17 @@ size_assert!(24, Type);
 size_assert!(24, Stmt);
 size_assert!(32, Expr);
 
+impl<'ast> MatchPat<'ast> {
+    pub fn range(&self) -> TextRange {
+        match self {
+            MatchPat::Value(value) => value.0.range,
+            MatchPat::Range { start, end, .. } => {
+                TextRange::new(start.0.range.start(), end.0.range.end())
+            }
+        }
+    }
+}
+
 impl AttributeKind {
     pub fn as_str(self) -> &'static str {
         match self {
@@ -431,6 +501,12 @@ impl AttributeKind {
             AttributeKind::Builtin => "builtin",
             AttributeKind::Inline => "inline",
             AttributeKind::Thread_Local => "thread_local",
+            AttributeKind::Link_Name => "link_name",
+            AttributeKind::Link => "link",
+            AttributeKind::Call_Conv => "call_conv",
+            AttributeKind::Allow => "allow",
+            AttributeKind::No_Return => "no_return",
+            AttributeKind::Deprecated => "deprecated",
             AttributeKind::Unknown => "unknown",
         }
     }
@@ -441,11 +517,44 @@ impl AttributeKind {
             "builtin" => AttributeKind::Builtin,
             "inline" => AttributeKind::Inline,
             "thread_local" => AttributeKind::Thread_Local,
+            "link_name" => AttributeKind::Link_Name,
+            "link" => AttributeKind::Link,
+            "call_conv" => AttributeKind::Call_Conv,
+            "allow" => AttributeKind::Allow,
+            "no_return" => AttributeKind::No_Return,
+            "deprecated" => AttributeKind::Deprecated,
             _ => AttributeKind::Unknown,
         }
     }
 }
 
+impl FormatKind {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            FormatKind::Print => "print",
+            FormatKind::Format => "format",
+        }
+    }
+
+    pub fn from_str(string: &str) -> Option<FormatKind> {
+        match string {
+            "print" => Some(FormatKind::Print),
+            "format" => Some(FormatKind::Format),
+            _ => None,
+        }
+    }
+}
+
+impl StringKind {
+    pub fn prefix(self) -> &'static str {
+        match self {
+            StringKind::Regular => "",
+            StringKind::CString => "c",
+            StringKind::Bytes => "b",
+        }
+    }
+}
+
 impl BasicType {
     pub fn as_str(self) -> &'static str {
         match self {
@@ -502,8 +611,6 @@ impl BinOp {
             BinOp::GreaterEq => ">=",
             BinOp::LogicAnd => "&&",
             BinOp::LogicOr => "||",
-            BinOp::Range => "..<",
-            BinOp::RangeInc => "..=",
         }
     }
 }
@@ -0,0 +1,605 @@
+use super::*;
+use crate::session::{ModuleID, Session};
+use std::fmt::Write;
+
+// ast is printed as rock-like pseudo source:
+// binary expressions are parenthesized to show the parsed precedence
+
+/// readable text form of parsed modules, used by `--emit ast`
+pub fn ast_print(ast: &Ast, session: &Session) -> String {
+    let mut p = Printer {
+        ast,
+        buf: String::with_capacity(4096),
+        indent: 0,
+    };
+
+    for (idx, module) in ast.modules.iter().enumerate() {
+        let path = &session.module(ModuleID::new(idx)).path;
+        let path = path.strip_prefix(session.cwd()).unwrap_or(path);
+        let _ = writeln!(&mut p.buf, "// module `{}`\n", path.to_string_lossy());
+        for item in module.items {
+            p.item(*item);
+        }
+    }
+    p.buf
+}
+
+struct Printer<'p, 'ast, 'intern> {
+    ast: &'p Ast<'ast, 'intern>,
+    buf: String,
+    indent: u32,
+}
+
+impl<'p, 'ast, 'intern> Printer<'p, 'ast, 'intern> {
+    fn item(&mut self, item: Item) {
+        match item {
+            Item::Proc(item) => self.proc_item(item),
+            Item::Enum(item) => {
+                self.attrs(item.attrs);
+                self.vis(item.vis);
+                self.buf.push_str("enum ");
+                self.name(item.name);
+                if let Some(basic) = item.basic {
+                    self.buf.push(' ');
+                    self.buf.push_str(basic.as_str());
+                }
+                self.buf.push_str(" {\n");
+                for variant in item.variants {
+                    self.tab(1);
+                    self.name(variant.name);
+                    if !variant.fields.is_empty() {
+                        self.buf.push('(');
+                        self.list(variant.fields, |p, ty| p.ty(*ty));
+                        self.buf.push(')');
+                    }
+                    self.buf.push_str(" = ");
+                    self.expr(variant.value.0);
+                    self.buf.push_str(",\n");
+                }
+                self.buf.push_str("}\n\n");
+            }
+            Item::Struct(item) => {
+                self.attrs(item.attrs);
+                self.vis(item.vis);
+                self.buf.push_str("struct ");
+                self.name(item.name);
+                self.buf.push_str(" {\n");
+                for field in item.fields {
+                    self.tab(1);
+                    self.vis(field.vis);
+                    self.name(field.name);
+                    self.buf.push_str(": ");
+                    self.ty(field.ty);
+                    if let Some(default) = field.default {
+                        self.buf.push_str(" = ");
+                        self.expr(default.0);
+                    }
+                    self.buf.push_str(",\n");
+                }
+                self.buf.push_str("}\n\n");
+            }
+            Item::Const(item) => {
+                self.attrs(item.attrs);
+                self.vis(item.vis);
+                self.buf.push_str("const ");
+                self.name(item.name);
+                self.buf.push_str(": ");
+                self.ty(item.ty);
+                self.buf.push_str(" = ");
+                self.expr(item.value.0);
+                self.buf.push_str(";\n\n");
+            }
+            Item::Global(item) => {
+                self.attrs(item.attrs);
+                self.vis(item.vis);
+                self.buf.push_str("global ");
+                self.mutt(item.mutt);
+                self.name(item.name);
+                self.buf.push_str(": ");
+                self.ty(item.ty);
+                self.buf.push_str(" = ");
+                self.expr(item.value.0);
+                self.buf.push_str(";\n\n");
+            }
+            Item::Import(item) => {
+                self.attrs(item.attrs);
+                self.buf.push_str("import ");
+                if let Some(package) = item.package {
+                    self.name(package);
+                    self.buf.push(':');
+                }
+                for (idx, name) in item.import_path.iter().enumerate() {
+                    if idx != 0 {
+                        self.buf.push('/');
+                    }
+                    self.name(*name);
+                }
+                if let Some(alias) = item.alias {
+                    self.buf.push_str(" as ");
+                    self.name(alias);
+                }
+                if !item.symbols.is_empty() {
+                    self.buf.push_str(".{ ");
+                    self.list(item.symbols, |p, symbol| {
+                        p.name(symbol.name);
+                        if let Some(alias) = symbol.alias {
+                            p.buf.push_str(" as ");
+                            p.name(alias);
+                        }
+                    });
+                    self.buf.push_str(" }");
+                }
+                if item.glob.is_some() {
+                    self.buf.push_str(".*");
+                }
+                self.buf.push_str(";\n\n");
+            }
+            Item::Impl(item) => {
+                self.attrs(item.attrs);
+                self.buf.push_str("impl ");
+                self.path(item.path);
+                self.buf.push_str(" {\n");
+                self.indent += 1;
+                for proc in item.procs {
+                    self.proc_item(proc);
+                }
+                self.indent -= 1;
+                self.buf.push_str("}\n\n");
+            }
+        }
+    }
+
+    fn proc_item(&mut self, item: &ProcItem) {
+        self.attrs(item.attrs);
+        self.tab(self.indent);
+        self.vis(item.vis);
+        self.buf.push_str("proc ");
+        self.name(item.name);
+        self.poly_params(item.poly_params);
+        self.buf.push('(');
+        self.list(item.params, |p, param| {
+            p.mutt(param.mutt);
+            p.name(param.name);
+            p.buf.push_str(": ");
+            p.ty(param.ty);
+        });
+        if item.is_variadic {
+            if !item.params.is_empty() {
+                self.buf.push_str(", ");
+            }
+            self.buf.push_str("..");
+        }
+        self.buf.push(')');
+        if let Some(return_ty) = item.return_ty {
+            self.buf.push_str(" -> ");
+            self.ty(return_ty);
+        }
+
+        match item.block {
+            Some(block) => {
+                self.buf.push(' ');
+                self.block(block);
+                self.buf.push_str("\n\n");
+            }
+            None => self.buf.push_str(";\n\n"),
+        }
+    }
+
+    fn block(&mut self, block: Block) {
+        if block.stmts.is_empty() {
+            self.buf.push_str("{}");
+            return;
+        }
+        self.buf.push_str("{\n");
+        self.indent += 1;
+        for stmt in block.stmts {
+            self.tab(self.indent);
+            self.stmt(*stmt);
+            self.buf.push('\n');
+        }
+        self.indent -= 1;
+        self.tab(self.indent);
+        self.buf.push('}');
+    }
+
+    fn stmt(&mut self, stmt: Stmt) {
+        match stmt.kind {
+            StmtKind::Break => self.buf.push_str("break;"),
+            StmtKind::Continue => self.buf.push_str("continue;"),
+            StmtKind::Return(expr) => {
+                self.buf.push_str("return");
+                if let Some(expr) = expr {
+                    self.buf.push(' ');
+                    self.expr(expr);
+                }
+                self.buf.push(';');
+            }
+            StmtKind::Defer(block) => {
+                self.buf.push_str("defer ");
+                self.block(*block);
+            }
+            StmtKind::Loop(loop_) => {
+                self.buf.push_str("for ");
+                match loop_.kind {
+                    LoopKind::Loop => {}
+                    LoopKind::While { cond } => {
+                        self.expr(cond);
+                        self.buf.push(' ');
+                    }
+                    LoopKind::ForLoop {
+                        local,
+                        cond,
+                        assign,
+                    } => {
+                        self.local(local);
+                        self.buf.push(' ');
+                        self.expr(cond);
+                        self.buf.push_str("; ");
+                        self.assign(assign);
+                        self.buf.push(' ');
+                    }
+                }
+                self.block(loop_.block);
+            }
+            StmtKind::Local(local) => self.local(local),
+            StmtKind::Assign(assign) => {
+                self.assign(assign);
+                self.buf.push(';');
+            }
+            StmtKind::ExprSemi(expr) => {
+                self.expr(expr);
+                self.buf.push(';');
+            }
+            StmtKind::ExprTail(expr) => {
+                self.buf.push_str("-> ");
+                self.expr(expr);
+                self.buf.push(';');
+            }
+        }
+    }
+
+    fn local(&mut self, local: &Local) {
+        match local.mutt {
+            Mut::Mutable => self.buf.push_str("mut "),
+            Mut::Immutable => self.buf.push_str("let "),
+        }
+        self.name(local.name);
+        match local.kind {
+            LocalKind::Decl(ty) => {
+                self.buf.push_str(": ");
+                self.ty(ty);
+            }
+            LocalKind::Init(ty, value) => {
+                if let Some(ty) = ty {
+                    self.buf.push_str(": ");
+                    self.ty(ty);
+                }
+                self.buf.push_str(" = ");
+                self.expr(value);
+            }
+        }
+        self.buf.push(';');
+    }
+
+    fn assign(&mut self, assign: &Assign) {
+        self.expr(assign.lhs);
+        match assign.op {
+            AssignOp::Assign => self.buf.push_str(" = "),
+            AssignOp::Bin(op) => {
+                let _ = write!(&mut self.buf, " {}= ", op.as_str());
+            }
+        }
+        self.expr(assign.rhs);
+    }
+
+    fn expr(&mut self, expr: &Expr) {
+        match expr.kind {
+            ExprKind::LitNull => self.buf.push_str("null"),
+            ExprKind::LitBool { val } => {
+                let _ = write!(&mut self.buf, "{val}");
+            }
+            ExprKind::LitInt { val, ty } => {
+                let _ = write!(&mut self.buf, "{val}");
+                if let Some(ty) = ty {
+                    self.buf.push_str(ty.as_str());
+                }
+            }
+            ExprKind::LitFloat { val, ty } => {
+                let _ = write!(&mut self.buf, "{val:?}");
+                if let Some(ty) = ty {
+                    self.buf.push_str(ty.as_str());
+                }
+            }
+            ExprKind::LitChar { val } => {
+                let _ = write!(&mut self.buf, "`{}`", val.escape_default());
+            }
+            ExprKind::LitString { id, kind } => {
+                let string = self.ast.intern_string.get_str(id);
+                let prefix = kind.prefix();
+                let _ = write!(&mut self.buf, "{prefix}\"{}\"", string.escape_default());
+            }
+            ExprKind::If { if_ } => {
+                self.buf.push_str("if ");
+                self.branch(if_.entry);
+                for branch in if_.branches {
+                    self.buf.push_str(" else if ");
+                    self.branch(*branch);
+                }
+                if let Some(block) = if_.else_block {
+                    self.buf.push_str(" else ");
+                    self.block(block);
+                }
+            }
+            ExprKind::Block { block } => self.block(*block),
+            ExprKind::Match { match_ } => {
+                self.buf.push_str("match ");
+                self.expr(match_.on_expr);
+                self.buf.push_str(" {\n");
+                self.indent += 1;
+                for arm in match_.arms {
+                    self.tab(self.indent);
+                    for (idx, pat) in arm.pats.iter().enumerate() {
+                        if idx != 0 {
+                            self.buf.push_str(" | ");
+                        }
+                        match *pat {
+                            MatchPat::Value(value) => self.expr(value.0),
+                            MatchPat::Range {
+                                start,
+                                end,
+                                inclusive,
+                            } => {
+                                self.expr(start.0);
+                                self.buf.push_str(if inclusive { "..=" } else { "..<" });
+                                self.expr(end.0);
+                            }
+                        }
+                    }
+                    self.buf.push_str(" -> ");
+                    self.expr(arm.expr);
+                    self.buf.push_str(",\n");
+                }
+                if let Some(fallback) = match_.fallback {
+                    self.tab(self.indent);
+                    self.buf.push_str("_ -> ");
+                    self.expr(fallback);
+                    self.buf.push_str(",\n");
+                }
+                self.indent -= 1;
+                self.tab(self.indent);
+                self.buf.push('}');
+            }
+            ExprKind::Field { target, name } => {
+                self.expr(target);
+                self.buf.push('.');
+                self.name(name);
+            }
+            ExprKind::Index { target, index } => {
+                self.expr(target);
+                self.buf.push('[');
+                self.expr(index);
+                self.buf.push(']');
+            }
+            ExprKind::Slice {
+                target,
+                mutt,
+                range,
+            } => {
+                self.expr(target);
+                self.buf.push('[');
+                self.mutt(mutt);
+                self.range(range);
+                self.buf.push(']');
+            }
+            ExprKind::Call { target, input } => {
+                self.expr(target);
+                self.input(input);
+            }
+            ExprKind::Cast { target, into } => {
+                self.buf.push('(');
+                self.expr(target);
+                self.buf.push_str(" as ");
+                self.ty(*into);
+                self.buf.push(')');
+            }
+            ExprKind::Sizeof { ty } => {
+                self.buf.push_str("sizeof(");
+                self.ty(*ty);
+                self.buf.push(')');
+            }
+            ExprKind::Format { format } => {
+                self.buf.push('#');
+                self.buf.push_str(format.kind.as_str());
+                self.input(format.input);
+            }
+            ExprKind::Item { path } => self.path(path),
+            ExprKind::Variant { name } => {
+                self.buf.push('.');
+                self.name(name);
+            }
+            ExprKind::Wildcard => self.buf.push('_'),
+            ExprKind::StructInit { struct_init } => {
+                if let Some(path) = struct_init.path {
+                    self.path(path);
+                }
+                self.buf.push_str(".{ ");
+                self.list(struct_init.input, |p, init| {
+                    p.name(init.name);
+                    p.buf.push_str(": ");
+                    p.expr(init.expr);
+                });
+                self.buf.push_str(" }");
+            }
+            ExprKind::ArrayInit { input } => {
+                self.buf.push('[');
+                self.list(input, |p, expr| p.expr(expr));
+                self.buf.push(']');
+            }
+            ExprKind::ArrayRepeat { expr, len } => {
+                self.buf.push('[');
+                self.expr(expr);
+                self.buf.push_str("; ");
+                self.expr(len.0);
+                self.buf.push(']');
+            }
+            ExprKind::Deref { rhs } => {
+                self.buf.push('*');
+                self.expr(rhs);
+            }
+            ExprKind::Address { mutt, rhs } => {
+                self.buf.push('&');
+                self.mutt(mutt);
+                self.expr(rhs);
+            }
+            ExprKind::Unary { op, rhs, .. } => {
+                self.buf.push_str(op.as_str());
+                self.expr(rhs);
+            }
+            ExprKind::Binary { op, bin, .. } => {
+                self.buf.push('(');
+                self.expr(bin.lhs);
+                let _ = write!(&mut self.buf, " {} ", op.as_str());
+                self.expr(bin.rhs);
+                self.buf.push(')');
+            }
+            ExprKind::Range { range } => {
+                self.buf.push('(');
+                self.range(range);
+                self.buf.push(')');
+            }
+        }
+    }
+
+    fn range(&mut self, range: &RangeExpr) {
+        if let Some(lower) = range.lower {
+            self.expr(lower);
+        }
+        match range.upper {
+            RangeEnd::Unbounded => self.buf.push_str(".."),
+            RangeEnd::Exclusive(upper) => {
+                self.buf.push_str("..<");
+                self.expr(upper);
+            }
+            RangeEnd::Inclusive(upper) => {
+                self.buf.push_str("..=");
+                self.expr(upper);
+            }
+        }
+    }
+
+    fn branch(&mut self, branch: Branch) {
+        self.expr(branch.cond);
+        self.buf.push(' ');
+        self.block(branch.block);
+    }
+
+    fn input(&mut self, input: &[&Expr]) {
+        self.buf.push('(');
+        self.list(input, |p, expr| p.expr(expr));
+        self.buf.push(')');
+    }
+
+    fn ty(&mut self, ty: Type) {
+        match ty.kind {
+            TypeKind::Basic(basic) => self.buf.push_str(basic.as_str()),
+            TypeKind::Custom(path) => self.path(path),
+            TypeKind::Reference(ref_ty, mutt) => {
+                self.buf.push('&');
+                self.mutt(mutt);
+                self.ty(*ref_ty);
+            }
+            TypeKind::Procedure(proc_ty) => {
+                self.buf.push_str("proc(");
+                self.list(proc_ty.params, |p, ty| p.ty(*ty));
+                if proc_ty.is_variadic {
+                    if !proc_ty.params.is_empty() {
+                        self.buf.push_str(", ");
+                    }
+                    self.buf.push_str("..");
+                }
+                self.buf.push(')');
+                if let Some(return_ty) = proc_ty.return_ty {
+                    self.buf.push_str(" -> ");
+                    self.ty(return_ty);
+                }
+            }
+            TypeKind::ArraySlice(slice) => {
+                self.buf.push('[');
+                if slice.mutt == Mut::Mutable {
+                    self.buf.push_str("mut");
+                }
+                self.buf.push(']');
+                self.ty(slice.elem_ty);
+            }
+            TypeKind::ArrayStatic(array) => {
+                self.buf.push('[');
+                self.expr(array.len.0);
+                self.buf.push(']');
+                self.ty(array.elem_ty);
+            }
+        }
+    }
+
+    fn attrs(&mut self, attrs: &[Attribute]) {
+        for attr in attrs {
+            self.tab(self.indent);
+            self.buf.push_str("#[");
+            self.buf.push_str(attr.kind.as_str());
+            if !attr.args.is_empty() {
+                self.buf.push('(');
+                self.list(attr.args, |p, name| p.name(*name));
+                self.buf.push(')');
+            }
+            if let Some(id) = attr.string {
+                let string = self.ast.intern_string.get_str(id);
+                let _ = write!(&mut self.buf, "(\"{}\")", string.escape_default());
+            }
+            self.buf.push_str("]\n");
+        }
+    }
+
+    fn poly_params(&mut self, poly_params: Option<&[Name]>) {
+        if let Some(poly_params) = poly_params {
+            self.buf.push('(');
+            self.list(poly_params, |p, name| p.name(*name));
+            self.buf.push(')');
+        }
+    }
+
+    fn path(&mut self, path: &Path) {
+        for (idx, name) in path.names.iter().enumerate() {
+            if idx != 0 {
+                self.buf.push('.');
+            }
+            self.name(*name);
+        }
+    }
+
+    fn list<T>(&mut self, items: &[T], mut print: impl FnMut(&mut Self, &T)) {
+        for (idx, item) in items.iter().enumerate() {
+            if idx != 0 {
+                self.buf.push_str(", ");
+            }
+            print(self, item);
+        }
+    }
+
+    fn name(&mut self, name: Name) {
+        self.buf.push_str(self.ast.intern_name.get_str(name.id));
+    }
+    fn vis(&mut self, vis: Vis) {
+        if vis == Vis::Public {
+            self.buf.push_str("pub ");
+        }
+    }
+    fn mutt(&mut self, mutt: Mut) {
+        if mutt == Mut::Mutable {
+            self.buf.push_str("mut ");
+        }
+    }
+    fn tab(&mut self, indent: u32) {
+        for _ in 0..indent {
+            self.buf.push_str("    ");
+        }
+    }
+}
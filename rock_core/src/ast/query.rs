@@ -0,0 +1,68 @@
+use super::visit::{self, Visitor};
+use super::*;
+use crate::text::TextOffset;
+
+/// call expression which input list contains the query offset
+#[derive(Copy, Clone)]
+pub struct CallAt {
+    pub range: TextRange,
+    pub active_input: usize,
+}
+
+/// finds the innermost call around `offset`, only procedure bodies are searched
+pub fn call_at<'ast>(module: Module<'ast>, offset: TextOffset) -> Option<CallAt> {
+    let mut finder = CallFinder {
+        offset,
+        found: None,
+    };
+    finder.visit_module(module);
+    finder.found
+}
+
+struct CallFinder {
+    offset: TextOffset,
+    found: Option<CallAt>,
+}
+
+impl<'ast> Visitor<'ast> for CallFinder {
+    fn visit_proc(&mut self, proc_item: &'ast ProcItem<'ast>) {
+        if let Some(block) = proc_item.block {
+            self.visit_block(block);
+        }
+    }
+    fn visit_enum(&mut self, _: &'ast EnumItem<'ast>) {}
+    fn visit_struct(&mut self, _: &'ast StructItem<'ast>) {}
+    fn visit_const(&mut self, _: &'ast ConstItem<'ast>) {}
+    fn visit_global(&mut self, _: &'ast GlobalItem<'ast>) {}
+
+    fn visit_block(&mut self, block: Block<'ast>) {
+        if block.range.contains_inclusive(self.offset) {
+            visit::walk_block(self, block);
+        }
+    }
+    fn visit_stmt(&mut self, stmt: Stmt<'ast>) {
+        if stmt.range.contains_inclusive(self.offset) {
+            visit::walk_stmt(self, stmt);
+        }
+    }
+    fn visit_expr(&mut self, expr: &'ast Expr<'ast>) {
+        if !expr.range.contains_inclusive(self.offset) {
+            return;
+        }
+        if let ExprKind::Call { target, input } = expr.kind {
+            // offset must be between the parentheses
+            let offset = self.offset;
+            if offset > target.range.end() && offset < expr.range.end() {
+                let active_input = input
+                    .iter()
+                    .take_while(|input| input.range.end() < offset)
+                    .count();
+                self.found = Some(CallAt {
+                    range: expr.range,
+                    active_input,
+                });
+            }
+        }
+        visit::walk_expr(self, expr);
+    }
+}
@@ -0,0 +1,300 @@
+use super::*;
+
+/// depth-first traversal of the ast in source order
+///
+/// each `visit_*` method walks into the children of the node by default,
+/// overriding it without calling the matching `walk_*` skips the subtree.
+/// ast nodes are immutable arena references, visitors keep their own mutable state.
+pub trait Visitor<'ast>: Sized {
+    fn visit_module(&mut self, module: Module<'ast>) {
+        walk_module(self, module);
+    }
+    fn visit_item(&mut self, item: Item<'ast>) {
+        walk_item(self, item);
+    }
+    fn visit_proc(&mut self, proc_item: &'ast ProcItem<'ast>) {
+        walk_proc(self, proc_item);
+    }
+    fn visit_enum(&mut self, enum_item: &'ast EnumItem<'ast>) {
+        walk_enum(self, enum_item);
+    }
+    fn visit_struct(&mut self, struct_item: &'ast StructItem<'ast>) {
+        walk_struct(self, struct_item);
+    }
+    fn visit_const(&mut self, const_item: &'ast ConstItem<'ast>) {
+        walk_const(self, const_item);
+    }
+    fn visit_global(&mut self, global_item: &'ast GlobalItem<'ast>) {
+        walk_global(self, global_item);
+    }
+    fn visit_import(&mut self, _import_item: &'ast ImportItem<'ast>) {}
+    fn visit_impl(&mut self, impl_item: &'ast ImplItem<'ast>) {
+        walk_impl(self, impl_item);
+    }
+    fn visit_path(&mut self, _path: &'ast Path<'ast>) {}
+    fn visit_type(&mut self, ty: Type<'ast>) {
+        walk_type(self, ty);
+    }
+    fn visit_block(&mut self, block: Block<'ast>) {
+        walk_block(self, block);
+    }
+    fn visit_stmt(&mut self, stmt: Stmt<'ast>) {
+        walk_stmt(self, stmt);
+    }
+    fn visit_local(&mut self, local: &'ast Local<'ast>) {
+        walk_local(self, local);
+    }
+    fn visit_assign(&mut self, assign: &'ast Assign<'ast>) {
+        walk_assign(self, assign);
+    }
+    fn visit_const_expr(&mut self, expr: ConstExpr<'ast>) {
+        self.visit_expr(expr.0);
+    }
+    fn visit_expr(&mut self, expr: &'ast Expr<'ast>) {
+        walk_expr(self, expr);
+    }
+}
+
+pub fn walk_module<'ast, V: Visitor<'ast>>(v: &mut V, module: Module<'ast>) {
+    for item in module.items {
+        v.visit_item(*item);
+    }
+}
+
+pub fn walk_item<'ast, V: Visitor<'ast>>(v: &mut V, item: Item<'ast>) {
+    match item {
+        Item::Proc(proc_item) => v.visit_proc(proc_item),
+        Item::Enum(enum_item) => v.visit_enum(enum_item),
+        Item::Struct(struct_item) => v.visit_struct(struct_item),
+        Item::Const(const_item) => v.visit_const(const_item),
+        Item::Global(global_item) => v.visit_global(global_item),
+        Item::Import(import_item) => v.visit_import(import_item),
+        Item::Impl(impl_item) => v.visit_impl(impl_item),
+    }
+}
+
+pub fn walk_proc<'ast, V: Visitor<'ast>>(v: &mut V, proc_item: &'ast ProcItem<'ast>) {
+    for param in proc_item.params {
+        v.visit_type(param.ty);
+    }
+    if let Some(return_ty) = proc_item.return_ty {
+        v.visit_type(return_ty);
+    }
+    if let Some(block) = proc_item.block {
+        v.visit_block(block);
+    }
+}
+
+pub fn walk_enum<'ast, V: Visitor<'ast>>(v: &mut V, enum_item: &'ast EnumItem<'ast>) {
+    for variant in enum_item.variants {
+        for field_ty in variant.fields {
+            v.visit_type(*field_ty);
+        }
+        v.visit_const_expr(variant.value);
+    }
+}
+
+pub fn walk_struct<'ast, V: Visitor<'ast>>(v: &mut V, struct_item: &'ast StructItem<'ast>) {
+    for field in struct_item.fields {
+        v.visit_type(field.ty);
+        if let Some(default) = field.default {
+            v.visit_const_expr(default);
+        }
+    }
+}
+
+pub fn walk_const<'ast, V: Visitor<'ast>>(v: &mut V, const_item: &'ast ConstItem<'ast>) {
+    v.visit_type(const_item.ty);
+    v.visit_const_expr(const_item.value);
+}
+
+pub fn walk_global<'ast, V: Visitor<'ast>>(v: &mut V, global_item: &'ast GlobalItem<'ast>) {
+    v.visit_type(global_item.ty);
+    v.visit_const_expr(global_item.value);
+}
+
+pub fn walk_impl<'ast, V: Visitor<'ast>>(v: &mut V, impl_item: &'ast ImplItem<'ast>) {
+    v.visit_path(impl_item.path);
+    for proc_item in impl_item.procs {
+        v.visit_proc(proc_item);
+    }
+}
+
+pub fn walk_type<'ast, V: Visitor<'ast>>(v: &mut V, ty: Type<'ast>) {
+    match ty.kind {
+        TypeKind::Basic(_) => {}
+        TypeKind::Custom(path) => v.visit_path(path),
+        TypeKind::Reference(ref_ty, _) => v.visit_type(*ref_ty),
+        TypeKind::Procedure(proc_ty) => {
+            for param in proc_ty.params {
+                v.visit_type(*param);
+            }
+            if let Some(return_ty) = proc_ty.return_ty {
+                v.visit_type(return_ty);
+            }
+        }
+        TypeKind::ArraySlice(slice) => v.visit_type(slice.elem_ty),
+        TypeKind::ArrayStatic(array) => {
+            v.visit_const_expr(array.len);
+            v.visit_type(array.elem_ty);
+        }
+    }
+}
+
+pub fn walk_block<'ast, V: Visitor<'ast>>(v: &mut V, block: Block<'ast>) {
+    for stmt in block.stmts {
+        v.visit_stmt(*stmt);
+    }
+}
+
+pub fn walk_stmt<'ast, V: Visitor<'ast>>(v: &mut V, stmt: Stmt<'ast>) {
+    match stmt.kind {
+        StmtKind::Break | StmtKind::Continue => {}
+        StmtKind::Return(expr) => {
+            if let Some(expr) = expr {
+                v.visit_expr(expr);
+            }
+        }
+        StmtKind::Defer(block) => v.visit_block(*block),
+        StmtKind::Loop(loop_) => {
+            match loop_.kind {
+                LoopKind::Loop => {}
+                LoopKind::While { cond } => v.visit_expr(cond),
+                LoopKind::ForLoop {
+                    local,
+                    cond,
+                    assign,
+                } => {
+                    v.visit_local(local);
+                    v.visit_expr(cond);
+                    v.visit_assign(assign);
+                }
+            }
+            v.visit_block(loop_.block);
+        }
+        StmtKind::Local(local) => v.visit_local(local),
+        StmtKind::Assign(assign) => v.visit_assign(assign),
+        StmtKind::ExprSemi(expr) => v.visit_expr(expr),
+        StmtKind::ExprTail(expr) => v.visit_expr(expr),
+    }
+}
+
+pub fn walk_local<'ast, V: Visitor<'ast>>(v: &mut V, local: &'ast Local<'ast>) {
+    match local.kind {
+        LocalKind::Decl(ty) => v.visit_type(ty),
+        LocalKind::Init(ty, value) => {
+            if let Some(ty) = ty {
+                v.visit_type(ty);
+            }
+            v.visit_expr(value);
+        }
+    }
+}
+
+pub fn walk_assign<'ast, V: Visitor<'ast>>(v: &mut V, assign: &'ast Assign<'ast>) {
+    v.visit_expr(assign.lhs);
+    v.visit_expr(assign.rhs);
+}
+
+pub fn walk_expr<'ast, V: Visitor<'ast>>(v: &mut V, expr: &'ast Expr<'ast>) {
+    match expr.kind {
+        ExprKind::LitNull
+        | ExprKind::LitBool { .. }
+        | ExprKind::LitInt { .. }
+        | ExprKind::LitFloat { .. }
+        | ExprKind::LitChar { .. }
+        | ExprKind::LitString { .. }
+        | ExprKind::Variant { .. }
+        | ExprKind::Wildcard => {}
+        ExprKind::If { if_ } => {
+            for branch in std::iter::once(&if_.entry).chain(if_.branches) {
+                v.visit_expr(branch.cond);
+                v.visit_block(branch.block);
+            }
+            if let Some(else_block) = if_.else_block {
+                v.visit_block(else_block);
+            }
+        }
+        ExprKind::Block { block } => v.visit_block(*block),
+        ExprKind::Match { match_ } => {
+            v.visit_expr(match_.on_expr);
+            for arm in match_.arms {
+                for pat in arm.pats {
+                    match *pat {
+                        MatchPat::Value(value) => v.visit_const_expr(value),
+                        MatchPat::Range { start, end, .. } => {
+                            v.visit_const_expr(start);
+                            v.visit_const_expr(end);
+                        }
+                    }
+                }
+                v.visit_expr(arm.expr);
+            }
+            if let Some(fallback) = match_.fallback {
+                v.visit_expr(fallback);
+            }
+        }
+        ExprKind::Field { target, .. } => v.visit_expr(target),
+        ExprKind::Index { target, index } => {
+            v.visit_expr(target);
+            v.visit_expr(index);
+        }
+        ExprKind::Slice { target, range, .. } => {
+            v.visit_expr(target);
+            walk_range(v, range);
+        }
+        ExprKind::Call { target, input } => {
+            v.visit_expr(target);
+            for input in input.iter() {
+                v.visit_expr(input);
+            }
+        }
+        ExprKind::Cast { target, into } => {
+            v.visit_expr(target);
+            v.visit_type(*into);
+        }
+        ExprKind::Sizeof { ty } => v.visit_type(*ty),
+        ExprKind::Format { format } => {
+            for input in format.input {
+                v.visit_expr(input);
+            }
+        }
+        ExprKind::Item { path } => v.visit_path(path),
+        ExprKind::StructInit { struct_init } => {
+            if let Some(path) = struct_init.path {
+                v.visit_path(path);
+            }
+            for input in struct_init.input {
+                v.visit_expr(input.expr);
+            }
+        }
+        ExprKind::ArrayInit { input } => {
+            for input in input {
+                v.visit_expr(input);
+            }
+        }
+        ExprKind::ArrayRepeat { expr, len } => {
+            v.visit_expr(expr);
+            v.visit_const_expr(len);
+        }
+        ExprKind::Deref { rhs } => v.visit_expr(rhs),
+        ExprKind::Address { rhs, .. } => v.visit_expr(rhs),
+        ExprKind::Unary { rhs, .. } => v.visit_expr(rhs),
+        ExprKind::Binary { bin, .. } => {
+            v.visit_expr(bin.lhs);
+            v.visit_expr(bin.rhs);
+        }
+        ExprKind::Range { range } => walk_range(v, range),
+    }
+}
+
+fn walk_range<'ast, V: Visitor<'ast>>(v: &mut V, range: &'ast RangeExpr<'ast>) {
+    if let Some(lower) = range.lower {
+        v.visit_expr(lower);
+    }
+    match range.upper {
+        RangeEnd::Unbounded => {}
+        RangeEnd::Exclusive(upper) => v.visit_expr(upper),
+        RangeEnd::Inclusive(upper) => v.visit_expr(upper),
+    }
+}
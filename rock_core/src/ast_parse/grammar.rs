@@ -1,7 +1,8 @@
 use super::parser::Parser;
 use crate::ast::*;
-use crate::error::{ErrorComp, SourceRange};
-use crate::session::ModuleID;
+use crate::error::{ErrorCode, ErrorComp, SourceRange};
+use crate::intern::InternID;
+use crate::lexer::{self, NumberLit};
 use crate::text::TextRange;
 use crate::token::{Token, T};
 
@@ -21,55 +22,95 @@ macro_rules! comma_separated_list {
     }};
 }
 
-pub fn module<'ast>(
-    mut p: Parser<'ast, '_, '_, '_>,
-    module_id: ModuleID,
-) -> Result<Module<'ast>, ErrorComp> {
+pub fn module<'ast>(mut p: Parser<'ast, '_, '_, '_>) -> Module<'ast> {
     let offset = p.state.items.start();
     while !p.at(T![eof]) {
+        let start_cursor = p.position();
+        let snapshot = p.state.buffer_snapshot();
+
         match item(&mut p) {
             Ok(item) => p.state.items.add(item),
             Err(error) => {
-                if p.at(T![eof]) {
-                    p.cursor -= 1;
+                p.error(error);
+                p.state.buffer_reset(snapshot);
+                if p.position() == start_cursor {
+                    p.bump();
                 }
-                let range = p.peek_range();
-                return Err(ErrorComp::new_detailed(
-                    error,
-                    "unexpected token",
-                    SourceRange::new(module_id, range),
-                    None,
-                ));
+                recover_to_item(&mut p);
             }
         }
     }
     let items = p.state.items.take(offset, &mut p.state.arena);
 
-    Ok(Module { items })
+    Module { items }
+}
+
+/// skips tokens until start of the next item
+fn recover_to_item(p: &mut Parser) {
+    while !p.at(T![eof]) && !at_item_start(p) {
+        p.bump();
+    }
+}
+
+/// skips tokens until end of the statement, stops before `}` of the enclosing block
+fn recover_to_stmt(p: &mut Parser) {
+    let mut depth: u32 = 0;
+    while !p.at(T![eof]) && !at_item_start(p) {
+        match p.peek() {
+            T!['{'] => depth += 1,
+            T!['}'] => {
+                if depth == 0 {
+                    return;
+                }
+                depth -= 1;
+            }
+            T![;] => {
+                if depth == 0 {
+                    p.bump();
+                    return;
+                }
+            }
+            _ => {}
+        }
+        p.bump();
+    }
+}
+
+fn at_item_start(p: &Parser) -> bool {
+    match p.peek() {
+        T![#] => p.at_next(T!['[']),
+        T![enum] | T![struct] | T![const] | T![global] | T![import] | T![impl] => true,
+        T![proc] => !p.at_next(T!['(']),
+        _ => false,
+    }
 }
 
 fn item<'ast>(p: &mut Parser<'ast, '_, '_, '_>) -> Result<Item<'ast>, String> {
+    let docs = p.doc_comments();
     let attrs = attribute_list(p)?;
-    let vis = vis(p); //@not allowing vis with `import` is not enforced right now
+    let vis = vis(p); //@not allowing vis with `import` or `impl` is not enforced right now
 
     match p.peek() {
-        T![proc] => Ok(Item::Proc(proc_item(p, attrs, vis)?)),
-        T![enum] => Ok(Item::Enum(enum_item(p, attrs, vis)?)),
-        T![struct] => Ok(Item::Struct(struct_item(p, attrs, vis)?)),
-        T![const] => Ok(Item::Const(const_item(p, attrs, vis)?)),
-        T![global] => Ok(Item::Global(global_item(p, attrs, vis)?)),
+        T![proc] => Ok(Item::Proc(proc_item(p, docs, attrs, vis)?)),
+        T![enum] => Ok(Item::Enum(enum_item(p, docs, attrs, vis)?)),
+        T![struct] => Ok(Item::Struct(struct_item(p, docs, attrs, vis)?)),
+        T![const] => Ok(Item::Const(const_item(p, docs, attrs, vis)?)),
+        T![global] => Ok(Item::Global(global_item(p, docs, attrs, vis)?)),
         T![import] => Ok(Item::Import(import_item(p, attrs, vis)?)),
+        T![impl] => Ok(Item::Impl(impl_item(p, attrs)?)),
         _ => Err("expected item".into()),
     }
 }
 
 fn proc_item<'ast>(
     p: &mut Parser<'ast, '_, '_, '_>,
-    attrs: &'ast [Attribute],
+    docs: &'ast [TextRange],
+    attrs: &'ast [Attribute<'ast>],
     vis: Vis,
 ) -> Result<&'ast ProcItem<'ast>, String> {
     p.bump();
     let name = name(p)?;
+    let poly_params = poly_params(p)?;
 
     let offset = p.state.proc_params.start();
     let mut is_variadic = false;
@@ -98,9 +139,11 @@ fn proc_item<'ast>(
     };
 
     Ok(p.state.arena.alloc(ProcItem {
+        docs,
         attrs,
         vis,
         name,
+        poly_params,
         params,
         is_variadic,
         return_ty,
@@ -108,6 +151,15 @@ fn proc_item<'ast>(
     }))
 }
 
+fn poly_params<'ast>(p: &mut Parser<'ast, '_, '_, '_>) -> Result<Option<&'ast [Name]>, String> {
+    if p.at(T!['[']) {
+        let names = comma_separated_list!(p, name, names, T!['['], T![']']);
+        Ok(Some(names))
+    } else {
+        Ok(None)
+    }
+}
+
 fn proc_param<'ast>(p: &mut Parser<'ast, '_, '_, '_>) -> Result<ProcParam<'ast>, String> {
     let mutt = mutt(p);
     let name = name(p)?;
@@ -119,7 +171,8 @@ fn proc_param<'ast>(p: &mut Parser<'ast, '_, '_, '_>) -> Result<ProcParam<'ast>,
 
 fn enum_item<'ast>(
     p: &mut Parser<'ast, '_, '_, '_>,
-    attrs: &'ast [Attribute],
+    docs: &'ast [TextRange],
+    attrs: &'ast [Attribute<'ast>],
     vis: Vis,
 ) -> Result<&'ast EnumItem<'ast>, String> {
     p.bump();
@@ -131,6 +184,7 @@ fn enum_item<'ast>(
     let variants = comma_separated_list!(p, enum_variant, enum_variants, T!['{'], T!['}']);
 
     Ok(p.state.arena.alloc(EnumItem {
+        docs,
         attrs,
         vis,
         name,
@@ -141,15 +195,25 @@ fn enum_item<'ast>(
 
 fn enum_variant<'ast>(p: &mut Parser<'ast, '_, '_, '_>) -> Result<EnumVariant<'ast>, String> {
     let name = name(p)?;
+    let fields = if p.at(T!['(']) {
+        comma_separated_list!(p, ty, types, T!['('], T![')'])
+    } else {
+        &[]
+    };
     p.expect(T![=])?;
     let value = ConstExpr(expr(p)?);
 
-    Ok(EnumVariant { name, value })
+    Ok(EnumVariant {
+        name,
+        fields,
+        value,
+    })
 }
 
 fn struct_item<'ast>(
     p: &mut Parser<'ast, '_, '_, '_>,
-    attrs: &'ast [Attribute],
+    docs: &'ast [TextRange],
+    attrs: &'ast [Attribute<'ast>],
     vis: Vis,
 ) -> Result<&'ast StructItem<'ast>, String> {
     p.bump();
@@ -157,6 +221,7 @@ fn struct_item<'ast>(
     let fields = comma_separated_list!(p, struct_field, struct_fields, T!['{'], T!['}']);
 
     Ok(p.state.arena.alloc(StructItem {
+        docs,
         attrs,
         vis,
         name,
@@ -169,13 +234,24 @@ fn struct_field<'ast>(p: &mut Parser<'ast, '_, '_, '_>) -> Result<StructField<'a
     let name = name(p)?;
     p.expect(T![:])?;
     let ty = ty(p)?;
+    let default = if p.eat(T![=]) {
+        Some(ConstExpr(expr(p)?))
+    } else {
+        None
+    };
 
-    Ok(StructField { vis, name, ty })
+    Ok(StructField {
+        vis,
+        name,
+        ty,
+        default,
+    })
 }
 
 fn const_item<'ast>(
     p: &mut Parser<'ast, '_, '_, '_>,
-    attrs: &'ast [Attribute],
+    docs: &'ast [TextRange],
+    attrs: &'ast [Attribute<'ast>],
     vis: Vis,
 ) -> Result<&'ast ConstItem<'ast>, String> {
     p.bump();
@@ -187,6 +263,7 @@ fn const_item<'ast>(
     p.expect(T![;])?;
 
     Ok(p.state.arena.alloc(ConstItem {
+        docs,
         attrs,
         vis,
         name,
@@ -197,7 +274,8 @@ fn const_item<'ast>(
 
 fn global_item<'ast>(
     p: &mut Parser<'ast, '_, '_, '_>,
-    attrs: &'ast [Attribute],
+    docs: &'ast [TextRange],
+    attrs: &'ast [Attribute<'ast>],
     vis: Vis,
 ) -> Result<&'ast GlobalItem<'ast>, String> {
     p.bump();
@@ -210,6 +288,7 @@ fn global_item<'ast>(
     p.expect(T![;])?;
 
     Ok(p.state.arena.alloc(GlobalItem {
+        docs,
         attrs,
         vis,
         mutt,
@@ -219,9 +298,34 @@ fn global_item<'ast>(
     }))
 }
 
+fn impl_item<'ast>(
+    p: &mut Parser<'ast, '_, '_, '_>,
+    attrs: &'ast [Attribute<'ast>],
+) -> Result<&'ast ImplItem<'ast>, String> {
+    p.bump();
+    let path = path(p)?;
+
+    let offset = p.state.impl_procs.start();
+    p.expect(T!['{'])?;
+    while !p.at(T!['}']) && !p.at(T![eof]) {
+        let docs = p.doc_comments();
+        let attrs = attribute_list(p)?;
+        let vis = vis(p);
+        if !p.at(T![proc]) {
+            return Err("expected procedure".into());
+        }
+        let proc = proc_item(p, docs, attrs, vis)?;
+        p.state.impl_procs.add(proc);
+    }
+    p.expect(T!['}'])?;
+    let procs = p.state.impl_procs.take(offset, &mut p.state.arena);
+
+    Ok(p.state.arena.alloc(ImplItem { attrs, path, procs }))
+}
+
 fn import_item<'ast>(
     p: &mut Parser<'ast, '_, '_, '_>,
-    attrs: &'ast [Attribute],
+    attrs: &'ast [Attribute<'ast>],
     vis: Vis,
 ) -> Result<&'ast ImportItem<'ast>, String> {
     p.bump();
@@ -244,13 +348,20 @@ fn import_item<'ast>(
     let import_path = p.state.names.take(offset, &mut p.state.arena);
     let alias = if p.eat(T![as]) { Some(name(p)?) } else { None };
 
-    let symbols = if p.eat(T![.]) {
-        let symbols = comma_separated_list!(p, import_symbol, import_symbols, T!['{'], T!['}']);
-        p.eat(T![;]);
-        symbols
+    let (symbols, glob) = if p.eat(T![.]) {
+        if p.at(T![*]) {
+            let range = p.peek_range();
+            p.bump();
+            p.expect(T![;])?;
+            (&[][..], Some(range))
+        } else {
+            let symbols = comma_separated_list!(p, import_symbol, import_symbols, T!['{'], T!['}']);
+            p.eat(T![;]);
+            (symbols, None)
+        }
     } else {
         p.expect(T![;])?;
-        &[]
+        (&[][..], None)
     };
 
     Ok(p.state.arena.alloc(ImportItem {
@@ -259,6 +370,7 @@ fn import_item<'ast>(
         import_path,
         alias,
         symbols,
+        glob,
     }))
 }
 
@@ -289,12 +401,14 @@ fn name(p: &mut Parser) -> Result<Name, String> {
     let range = p.peek_range();
     p.expect(T![ident])?;
     let string = &p.source[range.as_usize()];
-    let id = p.state.intern_name.intern(string);
+    let id = p.intern.intern_name.get_id(string).expect("interned name");
 
     Ok(Name { range, id })
 }
 
-fn attribute_list<'ast>(p: &mut Parser<'ast, '_, '_, '_>) -> Result<&'ast [Attribute], String> {
+fn attribute_list<'ast>(
+    p: &mut Parser<'ast, '_, '_, '_>,
+) -> Result<&'ast [Attribute<'ast>], String> {
     let offset = p.state.attrs.start();
 
     while p.at(T![#]) {
@@ -306,11 +420,14 @@ fn attribute_list<'ast>(p: &mut Parser<'ast, '_, '_, '_>) -> Result<&'ast [Attri
         p.expect(T![ident])?;
         let string = &p.source[range.as_usize()];
         let kind = AttributeKind::from_str(string);
+        let (args, string) = attribute_args(p)?;
 
         p.expect(T![']'])?;
         let attr = Attribute {
             kind,
             range: p.make_range(start),
+            args,
+            string,
         };
         p.state.attrs.add(attr);
     }
@@ -318,6 +435,30 @@ fn attribute_list<'ast>(p: &mut Parser<'ast, '_, '_, '_>) -> Result<&'ast [Attri
     Ok(p.state.attrs.take(offset, &mut p.state.arena))
 }
 
+// arguments are either a list of names or a single string
+fn attribute_args<'ast>(
+    p: &mut Parser<'ast, '_, '_, '_>,
+) -> Result<(&'ast [Name], Option<InternID>), String> {
+    if !p.eat(T!['(']) {
+        return Ok((&[], None));
+    }
+    if p.eat(T![string_lit]) {
+        let (id, _) = p.get_string_lit();
+        p.expect(T![')'])?;
+        return Ok((&[], Some(id)));
+    }
+    let offset = p.state.names.start();
+    while !p.at(T![')']) && !p.at(T![eof]) {
+        let name = name(p)?;
+        p.state.names.add(name);
+        if !p.at(T![')']) {
+            p.expect(T![,])?;
+        }
+    }
+    p.expect(T![')'])?;
+    Ok((p.state.names.take(offset, &mut p.state.arena), None))
+}
+
 fn path<'ast>(p: &mut Parser<'ast, '_, '_, '_>) -> Result<&'ast Path<'ast>, String> {
     let offset = p.state.names.start();
     let first = name(p)?;
@@ -337,6 +478,13 @@ fn path<'ast>(p: &mut Parser<'ast, '_, '_, '_>) -> Result<&'ast Path<'ast>, Stri
 }
 
 fn ty<'ast>(p: &mut Parser<'ast, '_, '_, '_>) -> Result<Type<'ast>, String> {
+    p.enter_nested("type")?;
+    let result = ty_impl(p);
+    p.exit_nested();
+    result
+}
+
+fn ty_impl<'ast>(p: &mut Parser<'ast, '_, '_, '_>) -> Result<Type<'ast>, String> {
     let start = p.start_range();
 
     if let Some(basic) = p.peek().as_basic_type() {
@@ -442,8 +590,10 @@ fn stmt<'ast>(p: &mut Parser<'ast, '_, '_, '_>) -> Result<Stmt<'ast>, String> {
                 let start = p.start_range();
                 let offset = p.state.stmts.start();
 
-                let stmt = stmt(p)?;
-                p.state.stmts.add(stmt);
+                p.enter_nested("block")?;
+                let stmt = stmt(p);
+                p.exit_nested();
+                p.state.stmts.add(stmt?);
                 let stmts = p.state.stmts.take(offset, &mut p.state.arena);
 
                 Block {
@@ -536,6 +686,7 @@ fn local<'ast>(p: &mut Parser<'ast, '_, '_, '_>) -> Result<&'ast Local<'ast>, St
         T![let] => Mut::Immutable,
         _ => return Err("expected `let` or `mut`".into()),
     };
+    let mutt_range = p.peek_range();
     p.bump();
 
     let name = name(p)?;
@@ -554,20 +705,76 @@ fn local<'ast>(p: &mut Parser<'ast, '_, '_, '_>) -> Result<&'ast Local<'ast>, St
     };
     p.expect(T![;])?;
 
-    Ok(p.state.arena.alloc(Local { mutt, name, kind }))
+    Ok(p.state.arena.alloc(Local {
+        mutt,
+        mutt_range,
+        name,
+        kind,
+    }))
 }
 
 fn expr<'ast>(p: &mut Parser<'ast, '_, '_, '_>) -> Result<&'ast Expr<'ast>, String> {
-    sub_expr(p, 0)
+    let start = p.start_range();
+    let lower = if at_range_op(p) {
+        None
+    } else {
+        let lower = sub_expr(p, 0)?;
+        if !at_range_op(p) {
+            return Ok(lower);
+        }
+        Some(lower)
+    };
+
+    let upper = match p.peek() {
+        T![..] => {
+            p.bump();
+            RangeEnd::Unbounded
+        }
+        T!["..<"] => {
+            p.bump();
+            RangeEnd::Exclusive(sub_expr(p, 0)?)
+        }
+        T!["..="] => {
+            p.bump();
+            RangeEnd::Inclusive(sub_expr(p, 0)?)
+        }
+        _ => unreachable!(),
+    };
+
+    let range = p.state.arena.alloc(RangeExpr { lower, upper });
+    Ok(p.state.arena.alloc(Expr {
+        kind: ExprKind::Range { range },
+        range: p.make_range(start),
+    }))
+}
+
+fn at_range_op(p: &Parser<'_, '_, '_, '_>) -> bool {
+    p.at(T![..]) || p.at(T!["..<"]) || p.at(T!["..="])
 }
 
 fn sub_expr<'ast>(
     p: &mut Parser<'ast, '_, '_, '_>,
     min_prec: u32,
+) -> Result<&'ast Expr<'ast>, String> {
+    sub_expr_impl(p, min_prec, false)
+}
+
+/// match pattern operand, `|` separates patterns
+fn pat_operand<'ast>(p: &mut Parser<'ast, '_, '_, '_>) -> Result<&'ast Expr<'ast>, String> {
+    sub_expr_impl(p, 0, true)
+}
+
+fn sub_expr_impl<'ast>(
+    p: &mut Parser<'ast, '_, '_, '_>,
+    min_prec: u32,
+    in_pat: bool,
 ) -> Result<&'ast Expr<'ast>, String> {
     let mut expr_lhs = primary_expr(p)?;
+    // operator chains are folded without recursion,
+    // each fold nests the tree deeper and counts towards the nesting limit
+    let mut chain = 0;
 
-    loop {
+    let result = loop {
         let prec: u32;
         let op: BinOp;
         let op_range: TextRange;
@@ -576,28 +783,48 @@ fn sub_expr<'ast>(
             op = bin_op;
             prec = bin_op.prec();
             if prec < min_prec {
-                break;
+                break Ok(expr_lhs);
+            }
+            if in_pat && op == BinOp::BitOr {
+                break Ok(expr_lhs);
             }
+            if let Err(error) = p.enter_nested("expression") {
+                break Err(error);
+            }
+            chain += 1;
             op_range = p.peek_range();
             p.bump();
         } else {
-            break;
+            break Ok(expr_lhs);
         }
 
         let lhs = expr_lhs;
-        let rhs = sub_expr(p, prec + 1)?;
+        let rhs = match sub_expr_impl(p, prec + 1, in_pat) {
+            Ok(rhs) => rhs,
+            Err(error) => break Err(error),
+        };
         let bin = p.state.arena.alloc(BinExpr { lhs, rhs });
 
         expr_lhs = p.state.arena.alloc(Expr {
             kind: ExprKind::Binary { op, op_range, bin },
             range: TextRange::new(lhs.range.start(), rhs.range.end()),
         });
-    }
+    };
 
-    Ok(expr_lhs)
+    for _ in 0..chain {
+        p.exit_nested();
+    }
+    result
 }
 
 fn primary_expr<'ast>(p: &mut Parser<'ast, '_, '_, '_>) -> Result<&'ast Expr<'ast>, String> {
+    p.enter_nested("expression")?;
+    let result = primary_expr_impl(p);
+    p.exit_nested();
+    result
+}
+
+fn primary_expr_impl<'ast>(p: &mut Parser<'ast, '_, '_, '_>) -> Result<&'ast Expr<'ast>, String> {
     let start = p.start_range();
 
     if p.eat(T!['(']) {
@@ -651,41 +878,25 @@ fn primary_expr<'ast>(p: &mut Parser<'ast, '_, '_, '_>) -> Result<&'ast Expr<'as
             p.bump();
             ExprKind::LitBool { val: false }
         }
-        T![int_lit] => {
+        T![int_lit] | T![float_lit] => {
             let range = p.peek_range();
+            let is_float = p.at(T![float_lit]);
             p.bump();
             let string = &p.source[range.as_usize()];
 
-            let val = match string.parse::<u64>() {
-                Ok(value) => value,
-                Err(error) => {
+            match lexer::parse_number_lit(string, is_float) {
+                Ok(NumberLit::Int { val, ty }) => ExprKind::LitInt { val, ty },
+                Ok(NumberLit::Float { val, ty }) => ExprKind::LitFloat { val, ty },
+                Err(message) => {
                     p.state.errors.push(ErrorComp::new(
-                        format!("parse int error: {}", error),
+                        ErrorCode::InvalidNumberLiteral,
+                        message,
                         SourceRange::new(p.module_id, range),
                         None,
                     ));
-                    0
+                    ExprKind::LitInt { val: 0, ty: None }
                 }
-            };
-            ExprKind::LitInt { val }
-        }
-        T![float_lit] => {
-            let range = p.peek_range();
-            p.bump();
-            let string = &p.source[range.as_usize()];
-
-            let val = match string.parse::<f64>() {
-                Ok(value) => value,
-                Err(error) => {
-                    p.state.errors.push(ErrorComp::new(
-                        format!("parse float error: {}", error),
-                        SourceRange::new(p.module_id, range),
-                        None,
-                    ));
-                    0.0
-                }
-            };
-            ExprKind::LitFloat { val }
+            }
         }
         T![char_lit] => {
             p.bump();
@@ -694,8 +905,8 @@ fn primary_expr<'ast>(p: &mut Parser<'ast, '_, '_, '_>) -> Result<&'ast Expr<'as
         }
         T![string_lit] => {
             p.bump();
-            let (id, c_string) = p.get_string_lit();
-            ExprKind::LitString { id, c_string }
+            let (id, kind) = p.get_string_lit();
+            ExprKind::LitString { id, kind }
         }
         T![if] => ExprKind::If { if_: if_(p)? },
         T!['{'] => {
@@ -712,6 +923,22 @@ fn primary_expr<'ast>(p: &mut Parser<'ast, '_, '_, '_>) -> Result<&'ast Expr<'as
             let ty_ref = p.state.arena.alloc(ty);
             ExprKind::Sizeof { ty: ty_ref }
         }
+        T![#] => {
+            p.bump();
+            let range = p.peek_range();
+            let kind = match p.peek() {
+                T![ident] => FormatKind::from_str(&p.source[range.as_usize()]),
+                _ => None,
+            };
+            let kind = match kind {
+                Some(kind) => kind,
+                None => return Err("expected `print` or `format` directive".into()),
+            };
+            p.bump();
+            let input = comma_separated_list!(p, expr, exprs, T!['('], T![')']);
+            let format = p.state.arena.alloc(Format { kind, input });
+            ExprKind::Format { format }
+        }
         T![.] => {
             p.bump();
 
@@ -725,6 +952,10 @@ fn primary_expr<'ast>(p: &mut Parser<'ast, '_, '_, '_>) -> Result<&'ast Expr<'as
                 ExprKind::Variant { name }
             }
         }
+        T![_] => {
+            p.bump();
+            ExprKind::Wildcard
+        }
         T![ident] => {
             let path = path(p)?;
 
@@ -852,90 +1083,18 @@ fn index_or_slice_expr<'ast>(
     target: &'ast Expr<'ast>,
     mutt: Mut,
 ) -> Result<ExprKind<'ast>, String> {
-    let range = match p.peek() {
-        T![..] => {
-            p.bump();
-            Some(SliceRange {
-                lower: None,
-                upper: SliceRangeEnd::Unbounded,
-            })
-        }
-        T!["..<"] => {
-            p.bump();
-            Some(SliceRange {
-                lower: None,
-                upper: SliceRangeEnd::Exclusive(expr(p)?),
-            })
-        }
-        T!["..="] => {
-            p.bump();
-            Some(SliceRange {
-                lower: None,
-                upper: SliceRangeEnd::Inclusive(expr(p)?),
-            })
-        }
-        _ => None,
-    };
-
-    let kind = if let Some(range) = range {
-        ExprKind::Slice {
+    let index = expr(p)?;
+    match index.kind {
+        ExprKind::Range { range } => Ok(ExprKind::Slice {
             target,
             mutt,
-            slice_range: p.state.arena.alloc(range),
+            range,
+        }),
+        _ if mutt == Mut::Mutable => {
+            Err("expected `..<`, `..=` or `..` in slice expression".into())
         }
-    } else {
-        let expr = expr(p)?;
-        if p.eat(T![..]) {
-            let range = SliceRange {
-                lower: Some(expr),
-                upper: SliceRangeEnd::Unbounded,
-            };
-            ExprKind::Slice {
-                target,
-                mutt,
-                slice_range: p.state.arena.alloc(range),
-            }
-        } else {
-            if let Some(slice_range) = expr_into_slice_range(p, expr) {
-                ExprKind::Slice {
-                    target,
-                    mutt,
-                    slice_range,
-                }
-            } else if mutt == Mut::Mutable {
-                return Err("expected `..<`, `..=` or `..` in slice expression".into());
-            } else {
-                ExprKind::Index {
-                    target,
-                    index: expr,
-                }
-            }
-        }
-    };
-
-    Ok(kind)
-}
-
-//@this is bad for grammar, change slice range parsing to avoid this
-fn expr_into_slice_range<'ast>(
-    p: &mut Parser<'ast, '_, '_, '_>,
-    expr: &'ast Expr<'ast>,
-) -> Option<&'ast SliceRange<'ast>> {
-    let range = match expr.kind {
-        ExprKind::Binary { op, op_range, bin } => match op {
-            BinOp::Range => SliceRange {
-                lower: Some(bin.lhs),
-                upper: SliceRangeEnd::Exclusive(bin.rhs),
-            },
-            BinOp::RangeInc => SliceRange {
-                lower: Some(bin.lhs),
-                upper: SliceRangeEnd::Inclusive(bin.rhs),
-            },
-            _ => return None,
-        },
-        _ => return None,
-    };
-    Some(p.state.arena.alloc(range))
+        _ => Ok(ExprKind::Index { target, index }),
+    }
 }
 
 fn if_<'ast>(p: &mut Parser<'ast, '_, '_, '_>) -> Result<&'ast If<'ast>, String> {
@@ -969,13 +1128,33 @@ fn if_<'ast>(p: &mut Parser<'ast, '_, '_, '_>) -> Result<&'ast If<'ast>, String>
 }
 
 fn block<'ast>(p: &mut Parser<'ast, '_, '_, '_>) -> Result<Block<'ast>, String> {
+    p.enter_nested("block")?;
+    let result = block_impl(p);
+    p.exit_nested();
+    result
+}
+
+fn block_impl<'ast>(p: &mut Parser<'ast, '_, '_, '_>) -> Result<Block<'ast>, String> {
     let start = p.start_range();
     let offset = p.state.stmts.start();
 
     p.expect(T!['{'])?;
-    while !p.at(T!['}']) && !p.at(T![eof]) {
-        let stmt = stmt(p)?;
-        p.state.stmts.add(stmt);
+    // stop at the next item in case of missing `}`
+    while !p.at(T!['}']) && !p.at(T![eof]) && !at_item_start(p) {
+        let start_cursor = p.position();
+        let snapshot = p.state.buffer_snapshot();
+
+        match stmt(p) {
+            Ok(stmt) => p.state.stmts.add(stmt),
+            Err(error) => {
+                p.error(error);
+                p.state.buffer_reset(snapshot);
+                if p.position() == start_cursor && !p.at(T!['}']) {
+                    p.bump();
+                }
+                recover_to_stmt(p);
+            }
+        }
     }
     p.expect(T!['}'])?;
 
@@ -1001,10 +1180,10 @@ fn match_<'ast>(p: &mut Parser<'ast, '_, '_, '_>) -> Result<&'ast Match<'ast>, S
             let expr = expr(p)?;
             fallback = Some(expr);
         } else {
-            let pat = ConstExpr(expr(p)?);
+            let pats = match_pats(p)?;
             p.expect(T![->])?;
             let expr = expr(p)?;
-            let arm = MatchArm { pat, expr };
+            let arm = MatchArm { pats, expr };
             p.state.match_arms.add(arm);
         }
 
@@ -1025,6 +1204,31 @@ fn match_<'ast>(p: &mut Parser<'ast, '_, '_, '_>) -> Result<&'ast Match<'ast>, S
     Ok(match_)
 }
 
+fn match_pats<'ast>(p: &mut Parser<'ast, '_, '_, '_>) -> Result<&'ast [MatchPat<'ast>], String> {
+    let offset = p.state.match_pats.start();
+    loop {
+        let start = ConstExpr(pat_operand(p)?);
+        let pat = if p.at(T!["..<"]) || p.at(T!["..="]) {
+            let inclusive = p.at(T!["..="]);
+            p.bump();
+            let end = ConstExpr(pat_operand(p)?);
+            MatchPat::Range {
+                start,
+                end,
+                inclusive,
+            }
+        } else {
+            MatchPat::Value(start)
+        };
+        p.state.match_pats.add(pat);
+
+        if !p.eat(T![|]) {
+            break;
+        }
+    }
+    Ok(p.state.match_pats.take(offset, &mut p.state.arena))
+}
+
 fn field_init_list<'ast>(
     p: &mut Parser<'ast, '_, '_, '_>,
 ) -> Result<&'ast [FieldInit<'ast>], String> {
@@ -1069,7 +1273,6 @@ fn field_init<'ast>(p: &mut Parser<'ast, '_, '_, '_>) -> Result<FieldInit<'ast>,
 impl BinOp {
     pub fn prec(&self) -> u32 {
         match self {
-            BinOp::Range | BinOp::RangeInc => 1,
             BinOp::LogicOr => 2,
             BinOp::LogicAnd => 3,
             BinOp::IsEq
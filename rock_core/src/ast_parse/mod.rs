@@ -2,35 +2,145 @@ mod grammar;
 mod parser;
 
 use crate::ast::*;
-use crate::error::ResultComp;
+use crate::error::{DiagnosticCollection, PartialComp};
 use crate::intern::InternPool;
 use crate::lexer;
 use crate::session::Session;
-use crate::timer::Timer;
+use crate::timer::{Timer, Timings};
+use crate::token::token_list::TokenList;
+use crate::token::Token;
 
+pub use crate::ast::print::ast_print;
+
+/// default depth of nested expressions, types and blocks,
+/// parser threads must have enough stack space for it
+pub const NESTING_LIMIT: u32 = 256;
+
+// modules are lexed and parsed in parallel, interning is done in between
+// in module order, to keep intern ids and error order deterministic
+// items with syntax errors are skipped, modules with lexer errors have no items
 pub fn parse<'ast, 'intern>(
     session: &Session,
     intern_name: InternPool<'intern>,
-) -> ResultComp<Ast<'ast, 'intern>> {
-    let t_total = Timer::new();
-    let mut state = parser::ParseState::new(intern_name);
+    nesting_limit: u32,
+    timings: &mut Timings,
+) -> PartialComp<Ast<'ast, 'intern>> {
+    let mut timer = Timer::new();
+    let module_ids = session.module_ids().collect();
+
+    let lexed = parallel_chunks(module_ids, |module_ids| {
+        let mut lexed = Vec::with_capacity(module_ids.len());
+        for module_id in module_ids {
+            let module = session.module(module_id);
+            let file_timer = Timer::new();
+            let (tokens, errors) = lexer::lex(&module.source, module_id, false);
+            lexed.push((module_id, tokens, errors, file_timer.elapsed()));
+        }
+        lexed
+    });
+    let lexed: Vec<_> = lexed.into_iter().flatten().collect();
+    timer.lap(timings, "lex", None);
+
+    let mut intern = parser::ParseIntern {
+        intern_name,
+        intern_string: InternPool::new(),
+    };
+    for (module_id, tokens, _, _) in lexed.iter() {
+        let module = session.module(*module_id);
+        intern_tokens(&mut intern, &module.source, tokens);
+    }
+    timer.lap(timings, "intern", None);
+
+    let intern_ref = &intern;
+    let parsed = parallel_chunks(lexed, |lexed| {
+        let mut state = parser::ParseState::new(nesting_limit);
+        let mut modules = Vec::with_capacity(lexed.len());
+
+        for (module_id, tokens, errors, lex_time) in lexed {
+            if !errors.is_empty() {
+                modules.push((module_id, Module { items: &[] }, errors, lex_time));
+                continue;
+            }
+            let file_timer = Timer::new();
+            let module = session.module(module_id);
+            let parser =
+                parser::Parser::new(&tokens, module_id, &module.source, &mut state, intern_ref);
+            let module = grammar::module(parser);
+            let file_time = lex_time + file_timer.elapsed();
+            modules.push((module_id, module, std::mem::take(&mut state.errors), file_time));
+        }
+        (state.arena, modules)
+    });
 
-    for module_id in session.module_ids() {
-        let module = session.module(module_id);
+    let mut arenas = Vec::with_capacity(parsed.len());
+    let mut modules = Vec::with_capacity(session.module_ids().count());
+    let mut errors = Vec::new();
+    for (arena, parsed_modules) in parsed {
+        arenas.push(arena);
+        for (module_id, module, module_errors, file_time) in parsed_modules {
+            let path = session.module(module_id).path.to_string_lossy().to_string();
+            timings.file(path, file_time);
+            modules.push(module);
+            errors.extend(module_errors);
+        }
+    }
+    let mem_usage = arenas.iter().map(|arena| arena.mem_usage()).sum();
+    timer.lap(timings, "parse", Some(mem_usage));
 
-        let (tokens, errors) = lexer::lex(&module.source, module_id, false);
-        if !errors.is_empty() {
-            state.errors.extend(errors);
-            continue;
+    let ast = Ast {
+        arenas,
+        intern_name: intern.intern_name,
+        intern_string: intern.intern_string,
+        modules,
+    };
+    PartialComp::new(ast, DiagnosticCollection::new().join_errors(errors))
+}
+
+fn intern_tokens(intern: &mut parser::ParseIntern, source: &str, tokens: &TokenList) {
+    for idx in 0..tokens.token_count() {
+        if tokens.token(idx) == Token::Ident {
+            let range = tokens.token_range(idx);
+            intern.intern_name.intern(&source[range.as_usize()]);
         }
-        let parser = parser::Parser::new(tokens, module_id, &module.source, &mut state);
+    }
+    for idx in 0..tokens.string_count() {
+        let (string, _) = tokens.string(idx);
+        intern.intern_string.intern(string);
+    }
+}
+
+/// splits work into contiguous chunks, one per thread
+/// results are returned in the same order as the input
+fn parallel_chunks<T: Send, R: Send>(items: Vec<T>, work: impl Fn(Vec<T>) -> R + Sync) -> Vec<R> {
+    let thread_count = std::thread::available_parallelism()
+        .map(|count| count.get())
+        .unwrap_or(1)
+        .min(items.len())
+        .max(1);
+    if thread_count == 1 {
+        return vec![work(items)];
+    }
 
-        match grammar::module(parser, module_id) {
-            Ok(module) => state.modules.push(module),
-            Err(error) => state.errors.push(error),
+    let chunk_size = items.len().div_ceil(thread_count);
+    let mut chunks = Vec::with_capacity(thread_count);
+    let mut items = items.into_iter();
+    loop {
+        let chunk: Vec<T> = items.by_ref().take(chunk_size).collect();
+        if chunk.is_empty() {
+            break;
         }
+        chunks.push(chunk);
     }
 
-    t_total.stop("ast parse (old) total");
-    state.result()
+    let work = &work;
+    std::thread::scope(|scope| {
+        let handles: Vec<_> = chunks
+            .into_iter()
+            .map(|chunk| scope.spawn(move || work(chunk)))
+            .collect();
+        handles
+            .into_iter()
+            .map(|handle| handle.join().expect("parser thread panicked"))
+            .collect()
+    })
 }
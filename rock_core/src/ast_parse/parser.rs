@@ -1,80 +1,128 @@
 use crate::arena::Arena;
 use crate::ast::*;
-use crate::error::{DiagnosticCollection, ErrorComp, ResultComp};
+use crate::error::{DiagnosticEdit, DiagnosticFix, ErrorCode, ErrorComp, SourceRange};
 use crate::intern::{InternID, InternPool};
 use crate::session::ModuleID;
-use crate::temp_buffer::TempBuffer;
+use crate::temp_buffer::{BufferOffset, TempBuffer};
 use crate::text::{TextOffset, TextRange};
+use crate::token::token_cursor::TokenCursor;
 use crate::token::token_list::TokenList;
-use crate::token::Token;
+use crate::token::{Token, T};
 
 pub struct Parser<'ast, 'intern, 'src, 'state> {
-    pub cursor: usize,
-    tokens: TokenList,
+    tokens: TokenCursor<'src>,
     char_id: u32,
     string_id: u32,
     pub module_id: ModuleID,
     pub source: &'src str,
-    pub state: &'state mut ParseState<'ast, 'intern>,
+    pub state: &'state mut ParseState<'ast>,
+    pub intern: &'state ParseIntern<'intern>,
+    expected: Option<Token>,
+    depth: u32,
 }
 
-pub struct ParseState<'ast, 'intern> {
-    pub arena: Arena<'ast>,
+/// intern pools filled before parsing, shared between parser threads
+pub struct ParseIntern<'intern> {
     pub intern_name: InternPool<'intern>,
     pub intern_string: InternPool<'intern>,
-    pub string_is_cstr: Vec<bool>,
-    pub modules: Vec<Module<'ast>>,
+}
+
+/// state owned by each parser thread
+pub struct ParseState<'ast> {
+    pub nesting_limit: u32,
+    pub arena: Arena<'ast>,
     pub errors: Vec<ErrorComp>,
     pub items: TempBuffer<Item<'ast>>,
-    pub attrs: TempBuffer<Attribute>,
+    pub attrs: TempBuffer<Attribute<'ast>>,
     pub proc_params: TempBuffer<ProcParam<'ast>>,
     pub enum_variants: TempBuffer<EnumVariant<'ast>>,
     pub struct_fields: TempBuffer<StructField<'ast>>,
+    pub impl_procs: TempBuffer<&'ast ProcItem<'ast>>,
     pub import_symbols: TempBuffer<ImportSymbol>,
     pub names: TempBuffer<Name>,
     pub types: TempBuffer<Type<'ast>>,
     pub stmts: TempBuffer<Stmt<'ast>>,
     pub branches: TempBuffer<Branch<'ast>>,
     pub match_arms: TempBuffer<MatchArm<'ast>>,
+    pub match_pats: TempBuffer<MatchPat<'ast>>,
     pub exprs: TempBuffer<&'ast Expr<'ast>>,
     pub field_inits: TempBuffer<FieldInit<'ast>>,
 }
 
+/// temp buffer offsets, used to discard partially parsed nodes during error recovery
+pub struct BufferSnapshot<'ast> {
+    items: BufferOffset<Item<'ast>>,
+    attrs: BufferOffset<Attribute<'ast>>,
+    proc_params: BufferOffset<ProcParam<'ast>>,
+    enum_variants: BufferOffset<EnumVariant<'ast>>,
+    struct_fields: BufferOffset<StructField<'ast>>,
+    impl_procs: BufferOffset<&'ast ProcItem<'ast>>,
+    import_symbols: BufferOffset<ImportSymbol>,
+    names: BufferOffset<Name>,
+    types: BufferOffset<Type<'ast>>,
+    stmts: BufferOffset<Stmt<'ast>>,
+    branches: BufferOffset<Branch<'ast>>,
+    match_arms: BufferOffset<MatchArm<'ast>>,
+    match_pats: BufferOffset<MatchPat<'ast>>,
+    exprs: BufferOffset<&'ast Expr<'ast>>,
+    field_inits: BufferOffset<FieldInit<'ast>>,
+}
+
 impl<'ast, 'intern, 'src, 'state> Parser<'ast, 'intern, 'src, 'state> {
     pub fn new(
-        tokens: TokenList,
+        tokens: &'src TokenList,
         module_id: ModuleID,
         source: &'src str,
-        state: &'state mut ParseState<'ast, 'intern>,
+        state: &'state mut ParseState<'ast>,
+        intern: &'state ParseIntern<'intern>,
     ) -> Self {
         Self {
-            cursor: 0,
-            tokens,
+            tokens: TokenCursor::new(tokens),
             char_id: 0,
             string_id: 0,
             module_id,
             source,
             state,
+            intern,
+            expected: None,
+            depth: 0,
         }
     }
 
+    /// index of the current token, used to detect progress during error recovery
+    pub fn position(&self) -> usize {
+        self.tokens.position()
+    }
+
     pub fn start_range(&self) -> TextOffset {
-        self.tokens.token_range(self.cursor).start()
+        self.tokens.peek_range().start()
     }
 
     /// `start` offset must be result of `start_range()` call  
     /// and at least one token must be consumed in between
     pub fn make_range(&self, start: TextOffset) -> TextRange {
-        let end = self.tokens.token_range(self.cursor - 1).end();
+        let end = self.tokens.prev_range().end();
         TextRange::new(start, end)
     }
 
+    /// doc comments between previous and current token
+    pub fn doc_comments(&mut self) -> &'ast [TextRange] {
+        let start = if self.position() == 0 {
+            0.into()
+        } else {
+            self.tokens.prev_range().end()
+        };
+        let range = TextRange::new(start, self.start_range());
+        let docs = self.tokens.tokens().doc_comments_in(range);
+        self.state.arena.alloc_slice(docs)
+    }
+
     pub fn peek_range(&self) -> TextRange {
-        self.tokens.token_range(self.cursor)
+        self.tokens.peek_range()
     }
 
     pub fn at(&self, t: Token) -> bool {
-        self.peek() == t
+        self.tokens.at(t)
     }
 
     // would be good to remove the need for forward peeking @14.04.24
@@ -84,96 +132,152 @@ impl<'ast, 'intern, 'src, 'state> Parser<'ast, 'intern, 'src, 'state> {
     }
 
     pub fn at_prev(&self, t: Token) -> bool {
-        self.tokens.token(self.cursor - 1) == t
+        self.tokens.at_prev(t)
     }
 
     pub fn peek(&self) -> Token {
-        self.tokens.token(self.cursor)
+        self.tokens.peek()
     }
 
     // would be good to remove the need for forward peeking @14.04.24
     // used in path -> import -> struct_init parsing with `.{`
     pub fn peek_next(&self) -> Token {
-        self.tokens.token(self.cursor + 1)
+        self.tokens.peek_nth(1)
     }
 
     pub fn eat(&mut self, t: Token) -> bool {
-        if self.at(t) {
-            self.bump();
-            return true;
-        }
-        false
+        self.tokens.eat(t)
     }
 
     pub fn bump(&mut self) {
-        self.cursor += 1;
+        self.tokens.bump();
+    }
+
+    /// reports syntax error at the current token, `eof` errors point to the last token
+    pub fn error(&mut self, msg: String) {
+        let range = if self.at(T![eof]) && self.position() > 0 {
+            self.tokens.prev_range()
+        } else {
+            self.peek_range()
+        };
+        let mut error = ErrorComp::new_detailed(
+            ErrorCode::SyntaxError,
+            msg,
+            "unexpected token",
+            SourceRange::new(self.module_id, range),
+            None,
+        );
+        // missing `;` is inserted right after the previous token
+        if self.expected.take() == Some(T![;]) && self.position() > 0 {
+            let offset = self.tokens.prev_range().end();
+            let edit = DiagnosticEdit::insert(self.module_id, offset, ";");
+            error = error.with_fix(DiagnosticFix::new("insert missing `;`", vec![edit]));
+        }
+        self.state.errors.push(error);
+    }
+
+    /// expressions, types and blocks are parsed recursively,
+    /// nesting is limited to report an error instead of overflowing the stack
+    pub fn enter_nested(&mut self, what: &str) -> Result<(), String> {
+        if self.depth >= self.state.nesting_limit {
+            return Err(format!(
+                "{what} is nested too deeply\nnesting limit is {}",
+                self.state.nesting_limit
+            ));
+        }
+        self.depth += 1;
+        Ok(())
+    }
+
+    pub fn exit_nested(&mut self) {
+        self.depth -= 1;
     }
 
     pub fn expect(&mut self, t: Token) -> Result<(), String> {
         if self.eat(t) {
             return Ok(());
         }
+        self.expected = Some(t);
         Err(format!("expected `{}`", t.as_str()))
     }
 
     pub fn get_char_lit(&mut self) -> char {
-        let value = self.tokens.char(self.char_id as usize);
+        let value = self.tokens.tokens().char(self.char_id as usize);
         self.char_id += 1;
         value
     }
 
-    pub fn get_string_lit(&mut self) -> (InternID, bool) {
-        let (string, c_string) = self.tokens.string(self.string_id as usize);
-        let id = self.state.intern_string.intern(string);
+    pub fn get_string_lit(&mut self) -> (InternID, StringKind) {
+        let (string, kind) = self.tokens.tokens().string(self.string_id as usize);
+        let id = self
+            .intern
+            .intern_string
+            .get_id(string)
+            .expect("interned string");
+        self.string_id += 1;
+        (id, kind)
+    }
+}
 
-        if id.index() >= self.state.string_is_cstr.len() {
-            self.state.string_is_cstr.push(c_string);
-        } else if c_string {
-            self.state.string_is_cstr[id.index()] = true;
+impl<'ast> ParseState<'ast> {
+    pub fn buffer_snapshot(&self) -> BufferSnapshot<'ast> {
+        BufferSnapshot {
+            items: self.items.start(),
+            attrs: self.attrs.start(),
+            proc_params: self.proc_params.start(),
+            enum_variants: self.enum_variants.start(),
+            struct_fields: self.struct_fields.start(),
+            impl_procs: self.impl_procs.start(),
+            import_symbols: self.import_symbols.start(),
+            names: self.names.start(),
+            types: self.types.start(),
+            stmts: self.stmts.start(),
+            branches: self.branches.start(),
+            match_arms: self.match_arms.start(),
+            match_pats: self.match_pats.start(),
+            exprs: self.exprs.start(),
+            field_inits: self.field_inits.start(),
         }
+    }
 
-        self.string_id += 1;
-        (id, c_string)
+    pub fn buffer_reset(&mut self, snapshot: BufferSnapshot<'ast>) {
+        self.items.reset(snapshot.items);
+        self.attrs.reset(snapshot.attrs);
+        self.proc_params.reset(snapshot.proc_params);
+        self.enum_variants.reset(snapshot.enum_variants);
+        self.struct_fields.reset(snapshot.struct_fields);
+        self.impl_procs.reset(snapshot.impl_procs);
+        self.import_symbols.reset(snapshot.import_symbols);
+        self.names.reset(snapshot.names);
+        self.types.reset(snapshot.types);
+        self.stmts.reset(snapshot.stmts);
+        self.branches.reset(snapshot.branches);
+        self.match_arms.reset(snapshot.match_arms);
+        self.match_pats.reset(snapshot.match_pats);
+        self.exprs.reset(snapshot.exprs);
+        self.field_inits.reset(snapshot.field_inits);
     }
-}
 
-impl<'ast, 'intern> ParseState<'ast, 'intern> {
-    pub fn new(intern_name: InternPool<'intern>) -> ParseState<'ast, 'intern> {
+    pub fn new(nesting_limit: u32) -> ParseState<'ast> {
         ParseState {
+            nesting_limit,
             arena: Arena::new(),
-            intern_name,
-            intern_string: InternPool::new(),
-            string_is_cstr: Vec::with_capacity(1024),
-            modules: Vec::new(),
             errors: Vec::new(),
             items: TempBuffer::new(128),
             attrs: TempBuffer::new(32),
             proc_params: TempBuffer::new(32),
             enum_variants: TempBuffer::new(32),
             struct_fields: TempBuffer::new(32),
+            impl_procs: TempBuffer::new(32),
             import_symbols: TempBuffer::new(32),
             names: TempBuffer::new(32),
             types: TempBuffer::new(32),
             stmts: TempBuffer::new(32),
             branches: TempBuffer::new(32),
             match_arms: TempBuffer::new(32),
+            match_pats: TempBuffer::new(32),
             exprs: TempBuffer::new(32),
             field_inits: TempBuffer::new(32),
         }
     }
-
-    pub fn result(self) -> ResultComp<Ast<'ast, 'intern>> {
-        let ast = Ast {
-            arena: self.arena,
-            intern_name: self.intern_name,
-            intern_string: self.intern_string,
-            string_is_cstr: self.string_is_cstr,
-            modules: self.modules,
-        };
-        if self.errors.is_empty() {
-            ResultComp::Ok((ast, vec![]))
-        } else {
-            ResultComp::Err(DiagnosticCollection::new().join_errors(self.errors))
-        }
-    }
 }
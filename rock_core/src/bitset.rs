@@ -3,6 +3,7 @@ pub struct BitSet(u32);
 
 impl BitSet {
     pub const EMPTY: BitSet = BitSet(0);
+    pub const FULL: BitSet = BitSet(u32::MAX);
 
     pub const fn new(flags: &[u32]) -> BitSet {
         let mut bitset = BitSet::EMPTY;
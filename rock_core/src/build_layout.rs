@@ -0,0 +1,250 @@
+use crate::error::ErrorComp;
+use crate::fs_env;
+use crate::package;
+use crate::package::manifest::OptLevel;
+use crate::package::semver::Semver;
+use crate::session::Session;
+use crate::target::TargetTriple;
+use crate::vfs;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+
+#[derive(Copy, Clone, PartialEq, Serialize, Deserialize)]
+pub enum BuildKind {
+    #[serde(rename = "debug")]
+    Debug,
+    #[serde(rename = "release")]
+    Release,
+}
+
+impl BuildKind {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            BuildKind::Debug => "debug",
+            BuildKind::Release => "release",
+        }
+    }
+    pub fn default_opt_level(self) -> OptLevel {
+        match self {
+            BuildKind::Debug => OptLevel::None,
+            BuildKind::Release => OptLevel::Default,
+        }
+    }
+}
+
+/// output paths of the package build:
+/// `build/<kind>/` executable, llvm ir and build metadata
+/// `build/<kind>/obj/<package>/` object files of each package
+/// cross builds are placed in `build/<target>/<kind>/`
+pub struct BuildLayout {
+    kind: BuildKind,
+    target: TargetTriple,
+    build_dir: PathBuf,
+    profile_dir: PathBuf,
+}
+
+/// inputs and outputs of the last build, stored in `build/<kind>/build.toml`
+/// used to remove stale artifacts and to detect when relinking is required
+#[derive(PartialEq, Serialize, Deserialize)]
+pub struct BuildMetadata {
+    pub version: Semver,           // toolchain version
+    pub kind: BuildKind,           // build kind
+    pub target: String,            // target triple
+    pub executable: PathBuf,       // linked executable
+    pub objects: Vec<PathBuf>,     // linked object files
+    pub links: Vec<String>,        // libraries requested by manifests
+    pub sources: Vec<SourceInput>, // manifests and source files with their content hash
+}
+
+#[derive(PartialEq, Serialize, Deserialize)]
+pub struct SourceInput {
+    pub path: PathBuf,
+    pub hash: String,
+}
+
+const BUILD_DIR: &str = "build";
+const OBJ_DIR: &str = "obj";
+const METADATA_FILE: &str = "build.toml";
+const DOC_DIR: &str = "doc";
+const INDEX_FILE: &str = "index.toml";
+
+impl BuildLayout {
+    pub fn new(root_dir: &PathBuf, kind: BuildKind, target: TargetTriple) -> BuildLayout {
+        let build_dir = root_dir.join(BUILD_DIR);
+        let profile_dir = if target == TargetTriple::host() {
+            build_dir.join(kind.as_str())
+        } else {
+            build_dir.join(target.as_str()).join(kind.as_str())
+        };
+        BuildLayout {
+            kind,
+            target,
+            build_dir,
+            profile_dir,
+        }
+    }
+
+    pub fn kind(&self) -> BuildKind {
+        self.kind
+    }
+    pub fn target(&self) -> TargetTriple {
+        self.target
+    }
+    pub fn build_dir(&self) -> &PathBuf {
+        &self.build_dir
+    }
+    pub fn profile_dir(&self) -> &PathBuf {
+        &self.profile_dir
+    }
+    pub fn obj_dir(&self, package_name: &str) -> PathBuf {
+        self.profile_dir.join(OBJ_DIR).join(package_name)
+    }
+    pub fn object_path(&self, package_name: &str, name: &str) -> PathBuf {
+        let mut path = self.obj_dir(package_name).join(name);
+        path.set_extension(self.target.obj_extension());
+        path
+    }
+    pub fn executable_path(&self, bin_name: &str) -> PathBuf {
+        let mut path = self.profile_dir.join(bin_name);
+        if let Some(extension) = self.target.exe_extension() {
+            path.set_extension(extension);
+        }
+        path
+    }
+    pub fn llvm_ir_path(&self, bin_name: &str) -> PathBuf {
+        self.profile_dir.join(format!("{bin_name}.ll"))
+    }
+    pub fn metadata_path(&self) -> PathBuf {
+        self.profile_dir.join(METADATA_FILE)
+    }
+
+    /// create `build/<kind>/obj/<package>` directories for every package in the session
+    pub fn create_dirs(&self, session: &Session) -> Result<(), ErrorComp> {
+        fs_env::dir_create(&self.build_dir, false)?;
+        fs_env::dir_create(&self.profile_dir, false)?;
+        fs_env::dir_create(&self.profile_dir.join(OBJ_DIR), false)?;
+
+        for package_id in session.package_ids() {
+            let manifest = session.package(package_id).manifest();
+            fs_env::dir_create(&self.obj_dir(&manifest.package.name), false)?;
+        }
+        Ok(())
+    }
+
+    /// metadata of the last build, `None` if missing or written by different toolchain
+    pub fn read_metadata(&self) -> Option<BuildMetadata> {
+        let text = std::fs::read_to_string(self.metadata_path()).ok()?;
+        let metadata: BuildMetadata = basic_toml::from_str(&text).ok()?;
+        if metadata.version != crate::VERSION
+            || metadata.kind != self.kind
+            || metadata.target != self.target.as_str()
+        {
+            return None;
+        }
+        Some(metadata)
+    }
+
+    pub fn write_metadata(&self, metadata: &BuildMetadata) -> Result<(), ErrorComp> {
+        let text = basic_toml::to_string(metadata).map_err(|error| {
+            ErrorComp::message(format!(
+                "failed to serialize build metadata\nreason: {}",
+                error
+            ))
+        })?;
+        fs_env::file_create_or_rewrite(&self.metadata_path(), &text)
+    }
+
+    /// remove object files of the last build which are not produced anymore,
+    /// only files inside of `build/<kind>/` are ever removed
+    pub fn remove_stale(
+        &self,
+        last: &BuildMetadata,
+        current: &BuildMetadata,
+    ) -> Result<(), ErrorComp> {
+        for path in last.objects.iter() {
+            if !current.objects.contains(path) && path.starts_with(&self.profile_dir) {
+                fs_env::file_remove(path, false)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl BuildMetadata {
+    pub fn new(
+        session: &Session,
+        kind: BuildKind,
+        executable: PathBuf,
+        objects: Vec<PathBuf>,
+    ) -> BuildMetadata {
+        let mut links = Vec::new();
+        for package_id in session.package_ids() {
+            let manifest = session.package(package_id).manifest();
+            if let Some(package_links) = &manifest.build.links {
+                links.extend(package_links.iter().cloned());
+            }
+        }
+
+        // manifests are inputs too: opt level, libraries and link paths
+        let mut sources = Vec::new();
+        for package_id in session.package_ids() {
+            let package = session.package(package_id);
+            let manifest_text = package::manifest_serialize(package.manifest()).unwrap_or_default();
+            sources.push(SourceInput {
+                path: package.root_dir.join("Rock.toml"),
+                hash: format!("{:016x}", vfs::content_hash(&manifest_text)),
+            });
+        }
+        for module_id in session.module_ids() {
+            let module = session.module(module_id);
+            sources.push(SourceInput {
+                path: module.path.clone(),
+                hash: format!("{:016x}", vfs::content_hash(&module.source)),
+            });
+        }
+        sources.sort_by(|a, b| a.path.cmp(&b.path));
+
+        BuildMetadata {
+            version: crate::VERSION,
+            kind,
+            target: session.target().as_str().to_string(),
+            executable,
+            objects,
+            links,
+            sources,
+        }
+    }
+
+    /// linking can be skipped when all inputs and outputs match the last build
+    pub fn is_up_to_date(&self, last: &BuildMetadata) -> bool {
+        self == last && self.executable.exists() && self.objects.iter().all(|path| path.exists())
+    }
+}
+
+/// remove `build/` directory, or only the profile directory when kind is specified
+pub fn clean(
+    root_dir: &PathBuf,
+    kind: Option<BuildKind>,
+    target: TargetTriple,
+) -> Result<(), ErrorComp> {
+    let path = match kind {
+        Some(kind) => BuildLayout::new(root_dir, kind, target).profile_dir,
+        None => root_dir.join(BUILD_DIR),
+    };
+    fs_env::dir_remove_all(&path, false)
+}
+
+/// `build/index.toml` symbol index, shared by all build kinds and targets
+pub fn index_path(root_dir: &PathBuf) -> PathBuf {
+    root_dir.join(BUILD_DIR).join(INDEX_FILE)
+}
+
+/// create empty `build/doc/` directory for `rock doc`, pages of previous runs are removed
+pub fn create_doc_dir(root_dir: &PathBuf) -> Result<PathBuf, ErrorComp> {
+    let build_dir = root_dir.join(BUILD_DIR);
+    let doc_dir = build_dir.join(DOC_DIR);
+    fs_env::dir_create(&build_dir, false)?;
+    fs_env::dir_remove_all(&doc_dir, false)?;
+    fs_env::dir_create(&doc_dir, false)?;
+    Ok(doc_dir)
+}
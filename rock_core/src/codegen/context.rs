@@ -2,6 +2,7 @@ use super::emit_stmt::{TailAllocaID, TailAllocaStatus};
 use crate::ast;
 use crate::hir;
 use crate::intern::InternID;
+use crate::target::TargetTriple;
 use inkwell::basic_block::BasicBlock;
 use inkwell::builder;
 use inkwell::context;
@@ -18,9 +19,11 @@ pub struct Codegen<'ctx> {
     target_machine: targets::TargetMachine,
     pub string_lits: Vec<values::GlobalValue<'ctx>>,
     pub structs: Vec<types::StructType<'ctx>>,
+    /// tagged layout of enums with variant fields, `None` when enum is lowered to its tag
+    pub enums: Vec<Option<types::StructType<'ctx>>>,
     pub consts: Vec<values::BasicValueEnum<'ctx>>,
     pub globals: Vec<values::GlobalValue<'ctx>>,
-    pub function_values: Vec<values::FunctionValue<'ctx>>,
+    pub function_values: Vec<Option<values::FunctionValue<'ctx>>>,
     pub hir: hir::Hir<'ctx>,
     pub c_functions: HashMap<InternID, values::FunctionValue<'ctx>>,
     pub ptr_type: types::PointerType<'ctx>,
@@ -34,14 +37,12 @@ pub struct ProcCodegen<'ctx> {
     pub param_vars: Vec<values::PointerValue<'ctx>>,
     pub local_vars: Vec<values::PointerValue<'ctx>>,
     pub block_info: Vec<BlockInfo<'ctx>>,
-    pub defer_blocks: Vec<hir::Block<'ctx>>,
     pub next_loop_info: Option<LoopInfo<'ctx>>,
     pub tail_alloca: Vec<TailAllocaStatus<'ctx>>,
 }
 
 #[derive(Copy, Clone)]
 pub struct BlockInfo<'ctx> {
-    defer_count: u32,
     loop_info: Option<LoopInfo<'ctx>>,
 }
 
@@ -52,24 +53,37 @@ pub struct LoopInfo<'ctx> {
 }
 
 impl<'ctx> Codegen<'ctx> {
-    pub fn new(hir: hir::Hir<'ctx>, context: &'ctx context::Context) -> Codegen<'ctx> {
+    pub fn new(
+        hir: hir::Hir<'ctx>,
+        context: &'ctx context::Context,
+        opt_level: inkwell::OptimizationLevel,
+        target: TargetTriple,
+    ) -> Codegen<'ctx> {
         let module = context.create_module("rock_module");
         let builder = context.create_builder();
 
-        targets::Target::initialize_x86(&targets::InitializationConfig::default());
-        let target = targets::Target::from_name("x86-64").unwrap();
-        let target_machine = target
+        targets::Target::initialize_all(&targets::InitializationConfig::default());
+        let triple = targets::TargetTriple::create(target.as_str());
+        let (cpu, features) = if target == TargetTriple::host() {
+            let cpu = targets::TargetMachine::get_host_cpu_name();
+            let features = targets::TargetMachine::get_host_cpu_features();
+            (cpu.to_string(), features.to_string())
+        } else {
+            ("generic".to_string(), String::new())
+        };
+        let target_machine = targets::Target::from_triple(&triple)
+            .unwrap()
             .create_target_machine(
-                &targets::TargetMachine::get_default_triple(),
-                "x86-64",
-                targets::TargetMachine::get_host_cpu_features()
-                    .to_str()
-                    .expect("utf-8"),
-                inkwell::OptimizationLevel::None,
+                &triple,
+                &cpu,
+                &features,
+                opt_level,
                 targets::RelocMode::Default,
                 targets::CodeModel::Default,
             )
             .unwrap();
+        module.set_triple(&triple);
+        module.set_data_layout(&target_machine.get_target_data().get_data_layout());
 
         let ptr_type = context.ptr_type(0.into());
         let ptr_sized_int_type =
@@ -81,8 +95,9 @@ impl<'ctx> Codegen<'ctx> {
             module,
             builder,
             target_machine,
-            string_lits: Vec::with_capacity(hir.intern_string.get_all_strings().len()),
+            string_lits: Vec::with_capacity(hir.string_lits.lits().len()),
             structs: Vec::with_capacity(hir.structs.len()),
+            enums: Vec::with_capacity(hir.enums.len()),
             consts: Vec::with_capacity(hir.consts.len()),
             globals: Vec::with_capacity(hir.globals.len()),
             function_values: Vec::with_capacity(hir.procs.len()),
@@ -116,13 +131,15 @@ impl<'ctx> Codegen<'ctx> {
         }
     }
 
+    /// generic procedures are not generated, only their instances
+    pub fn function_value(&self, proc_id: hir::ProcID) -> values::FunctionValue<'ctx> {
+        self.function_values[proc_id.index()].expect("function value")
+    }
+
     pub fn array_static_len(&self, len: hir::ArrayStaticLen) -> u64 {
-        match len {
-            hir::ArrayStaticLen::Immediate(len) => len.expect("array len is known"),
-            hir::ArrayStaticLen::ConstEval(eval_id) => match self.hir.const_eval_value(eval_id) {
-                hir::ConstValue::Int { val, neg, ty } => val,
-                _ => panic!("array len must be int"),
-            },
+        match self.hir.array_static_len(len) {
+            hir::ConstValue::Int { val, .. } => val,
+            _ => panic!("array len must be int"),
         }
     }
 
@@ -149,15 +166,19 @@ impl<'ctx> Codegen<'ctx> {
                 ast::BasicType::Void => self.context.void_type().into(),
                 ast::BasicType::Never => self.context.void_type().into(), // only expected as procedure return type
             },
-            hir::Type::Enum(enum_id) => {
-                let basic = self.hir.enum_data(enum_id).basic;
-                self.basic_type_into_int(basic).into()
-            }
+            hir::Type::Enum(enum_id) => match self.enums[enum_id.index()] {
+                Some(enum_ty) => enum_ty.into(),
+                None => {
+                    let basic = self.hir.enum_data(enum_id).basic;
+                    self.basic_type_into_int(basic).into()
+                }
+            },
             hir::Type::Struct(struct_id) => self.struct_type(struct_id).into(),
             hir::Type::Reference(_, _) => self.ptr_type.into(),
             hir::Type::Procedure(_) => self.ptr_type.into(),
             hir::Type::ArraySlice(_) => self.slice_type.into(),
             hir::Type::ArrayStatic(array) => self.array_type(array).into(),
+            hir::Type::PolyProc(_, _) => unreachable!(),
         }
     }
 
@@ -165,6 +186,23 @@ impl<'ctx> Codegen<'ctx> {
         self.structs[struct_id.index()]
     }
 
+    /// variant is accessed as a struct with the tag followed by its fields,
+    /// matching the variant layout computed in `hir_lower`
+    pub fn variant_type(
+        &self,
+        enum_id: hir::EnumID,
+        variant_id: hir::EnumVariantID,
+    ) -> types::StructType<'ctx> {
+        let data = self.hir.enum_data(enum_id);
+        let variant = data.variant(variant_id);
+        let mut field_types = Vec::with_capacity(variant.fields.len() + 1);
+        field_types.push(self.basic_type_into_int(data.basic).into());
+        for field_ty in variant.fields {
+            field_types.push(self.type_into_basic(*field_ty));
+        }
+        self.context.struct_type(&field_types, false)
+    }
+
     pub fn array_type(&self, array: &hir::ArrayStatic) -> types::ArrayType<'ctx> {
         // @should use LLVMArrayType2 which takes u64, what not exposed 03.05.24
         //  by inkwell even for llvm 17 (LLVMArrayType was deprecated in this version)
@@ -266,6 +304,13 @@ impl<'ctx> Codegen<'ctx> {
     pub fn build_ret(&self, value: Option<values::BasicValueEnum<'ctx>>) {
         if let Some(value) = value {
             self.builder.build_return(Some(&value)).unwrap();
+            return;
+        }
+        // `void` main is declared with `s32` return type
+        let function = self.get_insert_bb().get_parent().expect("insert bb parent");
+        if let Some(return_ty) = function.get_type().get_return_type() {
+            let exit_code = return_ty.const_zero();
+            self.builder.build_return(Some(&exit_code)).unwrap();
         } else {
             self.builder.build_return(None).unwrap();
         }
@@ -306,52 +351,24 @@ impl<'ctx> ProcCodegen<'ctx> {
 
     pub fn enter_block(&mut self) {
         self.block_info.push(BlockInfo {
-            defer_count: 0,
             loop_info: self.next_loop_info,
         });
         self.next_loop_info = None;
     }
 
     pub fn exit_block(&mut self) {
-        let last_count = self.block_info.last().unwrap().defer_count;
-        for _ in 0..last_count {
-            assert!(self.defer_blocks.pop().is_some());
-        }
         assert!(self.block_info.pop().is_some());
     }
 
-    pub fn push_defer_block(&mut self, block: hir::Block<'ctx>) {
-        self.block_info.last_mut().unwrap().defer_count += 1;
-        self.defer_blocks.push(block);
-    }
-
-    pub fn last_loop_info(&self) -> (LoopInfo<'ctx>, Vec<hir::Block<'ctx>>) {
-        let mut defer_count = 0;
+    pub fn last_loop_info(&self) -> LoopInfo<'ctx> {
         for info in self.block_info.iter().rev() {
-            defer_count += info.defer_count;
-
             if let Some(loop_info) = info.loop_info {
-                let total_count = self.defer_blocks.len();
-                let range = total_count - defer_count as usize..total_count;
-                let defer_blocks = self.defer_blocks[range].to_vec();
-                return (loop_info, defer_blocks);
+                return loop_info;
             }
         }
         unreachable!("last loop must exist")
     }
 
-    pub fn last_defer_blocks(&self) -> Vec<hir::Block<'ctx>> {
-        let total_count = self.defer_blocks.len();
-        let defer_count = self.block_info.last().unwrap().defer_count;
-        let range = total_count - defer_count as usize..total_count;
-        self.defer_blocks[range].to_vec()
-    }
-
-    //@lifetime problems, need to clone like this (since codegen_expr can mutate this vec) 05.05.24
-    pub fn all_defer_blocks(&self) -> Vec<hir::Block<'ctx>> {
-        self.defer_blocks.clone()
-    }
-
     pub fn push_tail_alloca(&mut self) -> TailAllocaID {
         let id = TailAllocaID::new(self.tail_alloca.len());
         self.tail_alloca.push(TailAllocaStatus::NoValue);
@@ -128,16 +128,25 @@ pub fn codegen_expr<'ctx>(
         Expr::ConstVar { const_id } => Some(codegen_const_var(cg, const_id)),
         Expr::GlobalVar { global_id } => Some(codegen_global_var(cg, expect_ptr, global_id)),
         Expr::CallDirect { proc_id, input } => codegen_call_direct(cg, proc_cg, proc_id, input),
+        Expr::CallPoly { poly_call, .. } => {
+            codegen_call_direct(cg, proc_cg, poly_call.instance, poly_call.input)
+        }
         Expr::CallIndirect { target, indirect } => {
             codegen_call_indirect(cg, proc_cg, target, indirect)
         }
+        Expr::Format { format } => codegen_format(cg, proc_cg, format),
         Expr::StructInit { struct_id, input } => {
             codegen_struct_init(cg, proc_cg, struct_id, input, expect_ptr, kind)
         }
+        Expr::VariantInit { variant_init } => {
+            codegen_variant_init(cg, proc_cg, variant_init, expect_ptr, kind)
+        }
         Expr::ArrayInit { array_init } => {
             codegen_array_init(cg, proc_cg, array_init, expect_ptr, kind)
         }
-        Expr::ArrayRepeat { array_repeat } => Some(codegen_array_repeat(cg, proc_cg, array_repeat)),
+        Expr::ArrayRepeat { array_repeat } => {
+            codegen_array_repeat(cg, proc_cg, array_repeat, expect_ptr, kind)
+        }
         Expr::Deref { rhs, ptr_ty } => Some(codegen_deref(cg, proc_cg, expect_ptr, rhs, *ptr_ty)),
         Expr::Address { rhs } => Some(codegen_address(cg, proc_cg, rhs)),
         Expr::Unary { op, rhs } => Some(codegen_unary(cg, proc_cg, op, rhs)),
@@ -181,30 +190,38 @@ pub fn codegen_const_value<'ctx>(
             let unsigned = type_is_unsigned_int(ty);
 
             if neg {
-                let negative = -(val as i64);
+                let negative = (val as i64).wrapping_neg();
                 int_type.const_int(negative as u64, !unsigned).into()
             } else {
                 int_type.const_int(val, !unsigned).into()
             }
         }
-        hir::ConstValue::IntS(val) => todo!("codegen: ConstValue::IntSigned"),
-        hir::ConstValue::IntU(val) => todo!("codegen: ConstValue::IntUnsigned"),
+        hir::ConstValue::IntS(val) => cg.context.i64_type().const_int(val as u64, true).into(),
+        hir::ConstValue::IntU(val) => cg.context.i64_type().const_int(val, false).into(),
         hir::ConstValue::Float { val, ty } => {
             let ty = ty.expect("const float type");
             cg.basic_type_into_float(ty).const_float(val).into()
         }
         hir::ConstValue::Char { val } => cg.context.i32_type().const_int(val as u64, false).into(),
-        hir::ConstValue::String { id, c_string } => codegen_lit_string(cg, id, c_string),
+        hir::ConstValue::String { id, kind } => codegen_lit_string(cg, id, kind),
         hir::ConstValue::Procedure { proc_id } => {
-            let function = cg.function_values[proc_id.index()];
+            let function = cg.function_value(proc_id);
             function.as_global_value().as_pointer_value().into()
         }
         hir::ConstValue::EnumVariant {
             enum_id,
             variant_id,
         } => {
-            let variant = cg.hir.enum_data(enum_id).variant(variant_id);
-            codegen_const_value(cg, cg.hir.const_eval_value(variant.value))
+            let tag = codegen_variant_tag(cg, enum_id, variant_id);
+            match cg.enums[enum_id.index()] {
+                // variant without fields, payload is zeroed
+                Some(enum_ty) => {
+                    let payload_ty = enum_ty.get_field_type_at_index(1).unwrap();
+                    let values = [tag.into(), payload_ty.const_zero()];
+                    enum_ty.const_named_struct(&values).into()
+                }
+                None => tag.into(),
+            }
         }
         hir::ConstValue::Struct { struct_ } => {
             use llvm_sys::core::LLVMConstNamedStruct;
@@ -257,22 +274,40 @@ pub fn codegen_const_value<'ctx>(
     }
 }
 
+fn codegen_variant_tag<'ctx>(
+    cg: &Codegen<'ctx>,
+    enum_id: hir::EnumID,
+    variant_id: hir::EnumVariantID,
+) -> values::IntValue<'ctx> {
+    let variant = cg.hir.enum_data(enum_id).variant(variant_id);
+    codegen_const_value(cg, cg.hir.const_eval_value(variant.value)).into_int_value()
+}
+
 fn codegen_lit_string<'ctx>(
     cg: &Codegen<'ctx>,
     id: InternID,
-    c_string: bool,
+    kind: ast::StringKind,
 ) -> values::BasicValueEnum<'ctx> {
-    let global_ptr = cg.string_lits[id.index()].as_pointer_value();
-
-    if c_string {
-        global_ptr.into()
-    } else {
+    // `[N]u8` value, bytes literals are not stored in the string literal table
+    if kind == ast::StringKind::Bytes {
         let string = cg.hir.intern_string.get_str(id);
-        let bytes_len = cg.ptr_sized_int_type.const_int(string.len() as u64, false);
-        let slice_value = cg
-            .context
-            .const_struct(&[global_ptr.into(), bytes_len.into()], false);
-        slice_value.into()
+        return cg.context.const_string(string.as_bytes(), false).into();
+    }
+    let lits = &cg.hir.string_lits;
+    let lit_id = lits.lit_id(id).expect("string literal in table");
+    let global_ptr = cg.string_lits[lit_id.index()].as_pointer_value();
+
+    match kind {
+        ast::StringKind::Regular => {
+            let len = lits.get(lit_id).len;
+            let bytes_len = cg.ptr_sized_int_type.const_int(len, false);
+            let slice_value = cg
+                .context
+                .const_struct(&[global_ptr.into(), bytes_len.into()], false);
+            slice_value.into()
+        }
+        ast::StringKind::CString => global_ptr.into(),
+        ast::StringKind::Bytes => unreachable!(),
     }
 }
 
@@ -283,25 +318,56 @@ fn codegen_if<'ctx>(
     if_: &'ctx hir::If<'ctx>,
     kind: BlockKind<'ctx>,
 ) {
+    // branches with constant `false` condition are skipped,
+    // first constant `true` condition replaces the `else` block
+    let mut else_block = if_.else_block;
+    let mut live_branches = Vec::with_capacity(if_.branches.len() + 1);
+    for branch in std::iter::once(&if_.entry).chain(if_.branches) {
+        match branch.cond_value {
+            Some(true) => {
+                else_block = Some(branch.block);
+                break;
+            }
+            Some(false) => {}
+            None => live_branches.push(branch),
+        }
+    }
+
+    let (entry, branches) = match live_branches.split_first() {
+        Some((entry, branches)) => (*entry, branches),
+        None => {
+            if let Some(block) = else_block {
+                let body_bb = cg.append_bb(proc_cg, "if_body");
+                let exit_bb = cg.append_bb(proc_cg, "if_exit");
+                cg.build_br(body_bb);
+                cg.position_at_end(body_bb);
+                codegen_block(cg, proc_cg, block, kind);
+                cg.build_br_no_term(exit_bb);
+                cg.position_at_end(exit_bb);
+            }
+            return;
+        }
+    };
+
     let mut body_bb = cg.append_bb(proc_cg, "if_body");
     let exit_bb = cg.append_bb(proc_cg, "if_exit");
 
-    let mut next_bb = if !if_.branches.is_empty() || if_.else_block.is_some() {
+    let mut next_bb = if !branches.is_empty() || else_block.is_some() {
         cg.insert_bb(body_bb, "if_next")
     } else {
         exit_bb
     };
 
-    let cond = codegen_expr_value(cg, proc_cg, if_.entry.cond);
+    let cond = codegen_expr_value(cg, proc_cg, entry.cond);
     cg.build_cond_br(cond, body_bb, next_bb);
 
     cg.position_at_end(body_bb);
-    codegen_block(cg, proc_cg, if_.entry.block, kind);
+    codegen_block(cg, proc_cg, entry.block, kind);
     cg.build_br_no_term(exit_bb);
 
-    for (idx, branch) in if_.branches.iter().enumerate() {
-        let last = idx + 1 == if_.branches.len();
-        let create_next = !last || if_.else_block.is_some();
+    for (idx, branch) in branches.iter().enumerate() {
+        let last = idx + 1 == branches.len();
+        let create_next = !last || else_block.is_some();
 
         body_bb = cg.insert_bb(next_bb, "if_body");
         cg.position_at_end(next_bb);
@@ -319,7 +385,7 @@ fn codegen_if<'ctx>(
         cg.build_br_no_term(exit_bb);
     }
 
-    if let Some(block) = if_.else_block {
+    if let Some(block) = else_block {
         cg.position_at_end(next_bb);
         codegen_block(cg, proc_cg, block, kind);
         cg.build_br_no_term(exit_bb);
@@ -335,18 +401,52 @@ fn codegen_match<'ctx>(
 ) {
     let insert_bb = cg.get_insert_bb();
     let on_value = codegen_expr_value(cg, proc_cg, match_.on_expr);
+    // enums with variant fields are matched on their tag,
+    // value is stored to load the fields bound by match arms
+    let (on_value, on_ptr) = if on_value.is_struct_value() {
+        let on_ptr = cg.entry_insert_alloca(proc_cg, on_value.get_type(), "match_on");
+        cg.builder.build_store(on_ptr, on_value).unwrap();
+        let tag = cg
+            .builder
+            .build_extract_value(on_value.into_struct_value(), 0, "match_tag")
+            .unwrap();
+        (tag, Some(on_ptr))
+    } else {
+        (on_value, None)
+    };
     let exit_bb = cg.append_bb(proc_cg, "match_exit");
 
     let mut cases = Vec::with_capacity(match_.arms.len());
+    let mut ranges = Vec::new();
     for arm in match_.arms {
         if arm.unreachable {
             continue;
         }
-        let value = codegen_const_value(cg, cg.hir.const_value(arm.pat));
         let case_bb = cg.append_bb(proc_cg, "match_case");
-        cases.push((value.into_int_value(), case_bb));
+        for pat in arm.pats {
+            match *pat {
+                hir::MatchPat::Value(value) => {
+                    let value = match cg.hir.const_value(value) {
+                        hir::ConstValue::EnumVariant {
+                            enum_id,
+                            variant_id,
+                        } => codegen_variant_tag(cg, enum_id, variant_id),
+                        value => codegen_const_value(cg, value).into_int_value(),
+                    };
+                    cases.push((value, case_bb));
+                }
+                hir::MatchPat::Range {
+                    start,
+                    end,
+                    inclusive,
+                } => ranges.push((start, end, inclusive, case_bb)),
+            }
+        }
 
         cg.position_at_end(case_bb);
+        if let Some(on_ptr) = on_ptr {
+            codegen_match_binds(cg, proc_cg, arm, on_ptr);
+        }
         codegen_block(cg, proc_cg, arm.block, kind);
         cg.build_br_no_term(exit_bb);
     }
@@ -357,17 +457,91 @@ fn codegen_match<'ctx>(
         codegen_block(cg, proc_cg, fallback, kind);
         cg.build_br_no_term(exit_bb);
         fallback_bb
+    } else if match_.exhaustive {
+        // unreachable default lets llvm drop the range check of the jump table
+        let unreachable_bb = cg.append_bb(proc_cg, "match_unreachable");
+        cg.position_at_end(unreachable_bb);
+        cg.builder.build_unreachable().unwrap();
+        unreachable_bb
     } else {
         exit_bb
     };
 
+    // ranges are checked in source order when no value case matched
+    let on_value = on_value.into_int_value();
+    let mut switch_else = else_block;
+    for (start, end, inclusive, case_bb) in ranges.into_iter().rev() {
+        let range_bb = cg.append_bb(proc_cg, "match_range");
+        cg.position_at_end(range_bb);
+        let start = codegen_const_value(cg, cg.hir.const_value(start)).into_int_value();
+        let end = codegen_const_value(cg, cg.hir.const_value(end)).into_int_value();
+        let offset = cg
+            .builder
+            .build_int_sub(on_value, start, "range_offset")
+            .unwrap();
+        let width = cg.builder.build_int_sub(end, start, "range_width").unwrap();
+        let predicate = if inclusive {
+            inkwell::IntPredicate::ULE
+        } else {
+            inkwell::IntPredicate::ULT
+        };
+        let in_range = cg
+            .builder
+            .build_int_compare(predicate, offset, width, "in_range")
+            .unwrap();
+        cg.build_cond_br(in_range.into(), case_bb, switch_else);
+        switch_else = range_bb;
+    }
+
     cg.position_at_end(insert_bb);
     cg.builder
-        .build_switch(on_value.into_int_value(), else_block, &cases)
+        .build_switch(on_value, switch_else, &cases)
         .unwrap();
     cg.position_at_end(exit_bb);
 }
 
+// arm with binds has a single variant pattern
+fn codegen_match_binds<'ctx>(
+    cg: &Codegen<'ctx>,
+    proc_cg: &mut ProcCodegen<'ctx>,
+    arm: &hir::MatchArm<'ctx>,
+    on_ptr: values::PointerValue<'ctx>,
+) {
+    if arm.binds.iter().all(Option::is_none) {
+        return;
+    }
+    let (enum_id, variant_id) = match arm.pats {
+        [hir::MatchPat::Value(value)] => match cg.hir.const_value(*value) {
+            hir::ConstValue::EnumVariant {
+                enum_id,
+                variant_id,
+            } => (enum_id, variant_id),
+            _ => unreachable!(),
+        },
+        _ => unreachable!(),
+    };
+    let variant = cg.hir.enum_data(enum_id).variant(variant_id);
+    let variant_ty = cg.variant_type(enum_id, variant_id);
+
+    for (idx, bind) in arm.binds.iter().enumerate() {
+        let local_id = match *bind {
+            Some(local_id) => local_id,
+            None => continue,
+        };
+        let field_ptr = cg
+            .builder
+            .build_struct_gep(variant_ty, on_ptr, idx as u32 + 1, "variant_field_ptr")
+            .unwrap();
+        let field_ty = cg.type_into_basic(variant.fields[idx]);
+        let field = cg
+            .builder
+            .build_load(field_ty, field_ptr, "variant_field")
+            .unwrap();
+        let local_ptr = proc_cg.local_vars[local_id.index()];
+        cg.builder.build_store(local_ptr, field).unwrap();
+    }
+}
+
 fn codegen_struct_field<'ctx>(
     cg: &Codegen<'ctx>,
     proc_cg: &mut ProcCodegen<'ctx>,
@@ -505,8 +679,7 @@ fn codegen_panic_conditional<'ctx>(
 }
 
 //@fix how bounds check is done 31.05.24
-// possible feature is to not bounds check indexing with constant values
-// that are proven to be in bounds of static array type, store flag in hir
+// constant indices in bounds of static arrays are not checked, see `IndexAccess::bounds_check`
 #[allow(unsafe_code)]
 fn codegen_index<'ctx>(
     cg: &Codegen<'ctx>,
@@ -564,8 +737,7 @@ fn codegen_index<'ctx>(
                 &[messsage_ptr.into(), index.into(), len.into()],
             );
 
-            //@i64 mul is probably wrong when dealing with non 64bit targets 07.05.24
-            let elem_size = cg.context.i64_type().const_int(elem_size, false);
+            let elem_size = cg.ptr_sized_int_type.const_int(elem_size, false);
             let byte_offset =
                 codegen_bin_op(cg, ast::BinOp::Mul, index.into(), elem_size.into(), false)
                     .into_int_value();
@@ -576,25 +748,27 @@ fn codegen_index<'ctx>(
             }
         }
         hir::IndexKind::Array { array } => unsafe {
-            let len = cg.array_static_len(array.len);
-            let len = cg.ptr_sized_int_type.const_int(len, false);
-
-            let panic_cond = cg
-                .builder
-                .build_int_compare(inkwell::IntPredicate::UGE, index, len, "bounds_check")
-                .unwrap();
-            let message = "thread `name` panicked at src/some_file.rock:xx:xx\nreason: index `%llu` out of bounds, array len = `%llu`\n\n";
-            let messsage_ptr = cg
-                .builder
-                .build_global_string_ptr(message, "panic_index_out_of_bounds")
-                .unwrap()
-                .as_pointer_value();
-            codegen_panic_conditional(
-                cg,
-                proc_cg,
-                panic_cond,
-                &[messsage_ptr.into(), index.into(), len.into()],
-            );
+            if access.bounds_check {
+                let len = cg.array_static_len(array.len);
+                let len = cg.ptr_sized_int_type.const_int(len, false);
+
+                let panic_cond = cg
+                    .builder
+                    .build_int_compare(inkwell::IntPredicate::UGE, index, len, "bounds_check")
+                    .unwrap();
+                let message = "thread `name` panicked at src/some_file.rock:xx:xx\nreason: index `%llu` out of bounds, array len = `%llu`\n\n";
+                let messsage_ptr = cg
+                    .builder
+                    .build_global_string_ptr(message, "panic_index_out_of_bounds")
+                    .unwrap()
+                    .as_pointer_value();
+                codegen_panic_conditional(
+                    cg,
+                    proc_cg,
+                    panic_cond,
+                    &[messsage_ptr.into(), index.into(), len.into()],
+                );
+            }
 
             cg.builder
                 .build_in_bounds_gep(
@@ -838,7 +1012,7 @@ fn codegen_cast<'ctx>(
     let into = cg.type_into_basic(*into);
     let op = match kind {
         hir::CastKind::Error => panic!("codegen unexpected hir::CastKind::Error"),
-        hir::CastKind::NoOp => return target,
+        hir::CastKind::NoOp | hir::CastKind::Integer_NoOp => return target,
         hir::CastKind::Integer_Trunc => values::InstructionOpcode::Trunc,
         hir::CastKind::Sint_Sign_Extend => values::InstructionOpcode::SExt,
         hir::CastKind::Uint_Zero_Extend => values::InstructionOpcode::ZExt,
@@ -848,6 +1022,8 @@ fn codegen_cast<'ctx>(
         hir::CastKind::Uint_to_Float => values::InstructionOpcode::UIToFP,
         hir::CastKind::Float_Trunc => values::InstructionOpcode::FPTrunc,
         hir::CastKind::Float_Extend => values::InstructionOpcode::FPExt,
+        hir::CastKind::Pointer_to_Int => values::InstructionOpcode::PtrToInt,
+        hir::CastKind::Int_to_Pointer => values::InstructionOpcode::IntToPtr,
     };
     cg.builder.build_cast(op, target, into, "cast_val").unwrap()
 }
@@ -927,11 +1103,12 @@ fn codegen_call_direct<'ctx>(
         input_values.push(value.into());
     }
 
-    let function = cg.function_values[proc_id.index()];
+    let function = cg.function_value(proc_id);
     let call_val = cg
         .builder
         .build_direct_call(function, &input_values, "call_val")
         .unwrap();
+    call_val.set_call_convention(function.get_call_conventions());
     call_val.try_as_basic_value().left()
 }
 
@@ -957,6 +1134,221 @@ fn codegen_call_indirect<'ctx>(
     call_val.try_as_basic_value().left()
 }
 
+fn codegen_format<'ctx>(
+    cg: &Codegen<'ctx>,
+    proc_cg: &mut ProcCodegen<'ctx>,
+    format: &'ctx hir::Format,
+) -> Option<values::BasicValueEnum<'ctx>> {
+    let i32_type = cg.context.i32_type();
+    let i64_type = cg.context.i64_type();
+    let f64_type = cg.context.f64_type();
+
+    let buffer = if let Some(buffer) = format.buffer {
+        let slice = codegen_expr_value(cg, proc_cg, buffer).into_struct_value();
+        let ptr = cg
+            .builder
+            .build_extract_value(slice, 0, "buffer_ptr")
+            .unwrap()
+            .into_pointer_value();
+        let len = cg
+            .builder
+            .build_extract_value(slice, 1, "buffer_len")
+            .unwrap()
+            .into_int_value();
+        Some((ptr, len))
+    } else {
+        None
+    };
+
+    let format_ptr = cg
+        .builder
+        .build_global_string_ptr(format.c_format, "format_string")
+        .unwrap()
+        .as_pointer_value();
+
+    let mut args: Vec<values::BasicMetadataValueEnum> = Vec::with_capacity(format.args.len() + 3);
+    if let Some((ptr, len)) = buffer {
+        args.push(ptr.into());
+        args.push(len.into());
+    }
+    args.push(format_ptr.into());
+
+    // arguments are promoted to match the printf conversion specifiers
+    for arg in format.args {
+        let value = codegen_expr_value(cg, proc_cg, arg.expr);
+        match arg.kind {
+            hir::FormatArgKind::Sint | hir::FormatArgKind::Uint => {
+                let value = value.into_int_value();
+                let value = if value.get_type().get_bit_width() < 64 {
+                    let op = if matches!(arg.kind, hir::FormatArgKind::Sint) {
+                        values::InstructionOpcode::SExt
+                    } else {
+                        values::InstructionOpcode::ZExt
+                    };
+                    cg.builder
+                        .build_cast(op, value, i64_type, "format_int")
+                        .unwrap()
+                } else {
+                    value.into()
+                };
+                args.push(value.into());
+            }
+            hir::FormatArgKind::Float => {
+                let value = value.into_float_value();
+                let value = if value.get_type() != f64_type {
+                    let op = values::InstructionOpcode::FPExt;
+                    cg.builder
+                        .build_cast(op, value, f64_type, "format_float")
+                        .unwrap()
+                } else {
+                    value.into()
+                };
+                args.push(value.into());
+            }
+            hir::FormatArgKind::Bool => {
+                let true_ptr = cg
+                    .builder
+                    .build_global_string_ptr("true", "format_true")
+                    .unwrap()
+                    .as_pointer_value();
+                let false_ptr = cg
+                    .builder
+                    .build_global_string_ptr("false", "format_false")
+                    .unwrap()
+                    .as_pointer_value();
+                let value = cg
+                    .builder
+                    .build_select(value.into_int_value(), true_ptr, false_ptr, "format_bool")
+                    .unwrap();
+                args.push(value.into());
+            }
+            hir::FormatArgKind::Slice => {
+                let slice = value.into_struct_value();
+                let ptr = cg
+                    .builder
+                    .build_extract_value(slice, 0, "slice_ptr")
+                    .unwrap();
+                let len = cg
+                    .builder
+                    .build_extract_value(slice, 1, "slice_len")
+                    .unwrap();
+                //@slices longer than i32::MAX are truncated 16.10.26
+                let op = values::InstructionOpcode::Trunc;
+                let len = cg
+                    .builder
+                    .build_cast(op, len, i32_type, "format_len")
+                    .unwrap();
+                args.push(len.into());
+                args.push(ptr.into());
+            }
+            hir::FormatArgKind::CString => args.push(value.into()),
+        }
+    }
+
+    let (ptr, len) = match buffer {
+        Some(buffer) => buffer,
+        None => {
+            let printf_ty = i32_type.fn_type(&[cg.ptr_type.into()], true);
+            let c_printf = c_function_get_or_declare(cg, "printf", printf_ty);
+            cg.builder.build_call(c_printf, &args, "").unwrap();
+            return None;
+        }
+    };
+
+    let snprintf_ty = i32_type.fn_type(
+        &[
+            cg.ptr_type.into(),
+            cg.ptr_sized_int_type.into(),
+            cg.ptr_type.into(),
+        ],
+        true,
+    );
+    let c_snprintf = c_function_get_or_declare(cg, "snprintf", snprintf_ty);
+    let written = cg
+        .builder
+        .build_call(c_snprintf, &args, "format_written")
+        .unwrap()
+        .try_as_basic_value()
+        .left()
+        .unwrap()
+        .into_int_value();
+
+    // result is clamped to the buffer, snprintf returns the untruncated length
+    // and always reserves the last byte for the null terminator
+    let zero = cg.ptr_sized_int_type.const_zero();
+    let one = cg.ptr_sized_int_type.const_int(1, false);
+    let written = cg
+        .builder
+        .build_cast(
+            values::InstructionOpcode::SExt,
+            written,
+            cg.ptr_sized_int_type,
+            "format_written_ext",
+        )
+        .unwrap()
+        .into_int_value();
+    let is_neg = cg
+        .builder
+        .build_int_compare(inkwell::IntPredicate::SLT, written, zero, "format_is_neg")
+        .unwrap();
+    let written = cg
+        .builder
+        .build_select(is_neg, zero, written, "format_written_pos")
+        .unwrap()
+        .into_int_value();
+    let is_empty = cg
+        .builder
+        .build_int_compare(inkwell::IntPredicate::EQ, len, zero, "format_is_empty")
+        .unwrap();
+    let capacity = cg
+        .builder
+        .build_int_sub(len, one, "format_capacity")
+        .unwrap();
+    let capacity = cg
+        .builder
+        .build_select(is_empty, zero, capacity, "format_capacity")
+        .unwrap()
+        .into_int_value();
+    let is_truncated = cg
+        .builder
+        .build_int_compare(
+            inkwell::IntPredicate::UGT,
+            written,
+            capacity,
+            "format_is_trunc",
+        )
+        .unwrap();
+    let result_len = cg
+        .builder
+        .build_select(is_truncated, capacity, written, "format_len")
+        .unwrap();
+
+    let slice = cg.slice_type.get_undef();
+    let slice = cg
+        .builder
+        .build_insert_value(slice, ptr, 0, "format_slice")
+        .unwrap();
+    let slice = cg
+        .builder
+        .build_insert_value(slice, result_len, 1, "format_slice")
+        .unwrap();
+    Some(slice.into_struct_value().into())
+}
+
+pub fn c_function_get_or_declare<'ctx>(
+    cg: &Codegen<'ctx>,
+    name: &str,
+    function_ty: inkwell::types::FunctionType<'ctx>,
+) -> values::FunctionValue<'ctx> {
+    match cg.module.get_function(name) {
+        Some(function) => function,
+        None => {
+            let linkage = Some(inkwell::module::Linkage::External);
+            cg.module.add_function(name, function_ty, linkage)
+        }
+    }
+}
+
 fn codegen_struct_init<'ctx>(
     cg: &Codegen<'ctx>,
     proc_cg: &mut ProcCodegen<'ctx>,
@@ -1009,6 +1401,53 @@ fn codegen_struct_init<'ctx>(
     }
 }
 
+fn codegen_variant_init<'ctx>(
+    cg: &Codegen<'ctx>,
+    proc_cg: &mut ProcCodegen<'ctx>,
+    variant_init: &'ctx hir::VariantInit<'ctx>,
+    expect_ptr: bool,
+    kind: BlockKind<'ctx>,
+) -> Option<values::BasicValueEnum<'ctx>> {
+    let enum_id = variant_init.enum_id;
+    let variant_id = variant_init.variant_id;
+    let enum_ty = cg.enums[enum_id.index()].expect("tagged enum type");
+    let variant_ty = cg.variant_type(enum_id, variant_id);
+    let (enum_ptr, elided) = if let BlockKind::TailStore(target_ptr) = kind {
+        (target_ptr, true)
+    } else {
+        (
+            cg.entry_insert_alloca(proc_cg, enum_ty.into(), "variant_init"),
+            false,
+        )
+    };
+
+    for (idx, expr) in variant_init.input.iter().enumerate() {
+        let field_ptr = cg
+            .builder
+            .build_struct_gep(variant_ty, enum_ptr, idx as u32 + 1, "variant_field_ptr")
+            .unwrap();
+        if let Some(value) = codegen_expr(cg, proc_cg, false, expr, BlockKind::TailStore(field_ptr))
+        {
+            cg.builder.build_store(field_ptr, value).unwrap();
+        }
+    }
+    // tag is stored last, field values can still read the previous target value
+    let tag = codegen_variant_tag(cg, enum_id, variant_id);
+    cg.builder.build_store(enum_ptr, tag).unwrap();
+
+    if expect_ptr {
+        Some(enum_ptr.into())
+    } else if elided {
+        None
+    } else {
+        Some(
+            cg.builder
+                .build_load(enum_ty, enum_ptr, "variant_val")
+                .unwrap(),
+        )
+    }
+}
+
 #[allow(unsafe_code)]
 fn codegen_array_init<'ctx>(
     cg: &Codegen<'ctx>,
@@ -1059,12 +1498,81 @@ fn codegen_array_init<'ctx>(
     }
 }
 
+//@array repeat is always generated as a loop, small arrays could be unrolled 16.10.26
+#[allow(unsafe_code)]
 fn codegen_array_repeat<'ctx>(
     cg: &Codegen<'ctx>,
     proc_cg: &mut ProcCodegen<'ctx>,
     array_repeat: &'ctx hir::ArrayRepeat<'ctx>,
-) -> values::BasicValueEnum<'ctx> {
-    todo!("codegen `array repeat` not supported")
+    expect_ptr: bool,
+    kind: BlockKind<'ctx>,
+) -> Option<values::BasicValueEnum<'ctx>> {
+    let elem_ty = cg.type_into_basic(array_repeat.elem_ty);
+    let array_ty = elem_ty.array_type(array_repeat.len as u32);
+    let (array_ptr, elided) = if let BlockKind::TailStore(target_ptr) = kind {
+        (target_ptr, true)
+    } else {
+        (
+            cg.entry_insert_alloca(proc_cg, array_ty.into(), "array_repeat"),
+            false,
+        )
+    };
+
+    let value = codegen_expr_value(cg, proc_cg, array_repeat.expr);
+    let index_ptr = cg.entry_insert_alloca(proc_cg, cg.ptr_sized_int_type.into(), "repeat_idx");
+    cg.builder
+        .build_store(index_ptr, cg.ptr_sized_int_type.const_zero())
+        .unwrap();
+
+    let entry_bb = cg.append_bb(proc_cg, "repeat_entry");
+    let body_bb = cg.append_bb(proc_cg, "repeat_body");
+    let exit_bb = cg.append_bb(proc_cg, "repeat_exit");
+    cg.build_br(entry_bb);
+
+    cg.position_at_end(entry_bb);
+    let index = cg
+        .builder
+        .build_load(cg.ptr_sized_int_type, index_ptr, "repeat_idx")
+        .unwrap()
+        .into_int_value();
+    let len = cg.ptr_sized_int_type.const_int(array_repeat.len, false);
+    let cond = cg
+        .builder
+        .build_int_compare(inkwell::IntPredicate::ULT, index, len, "repeat_cond")
+        .unwrap();
+    cg.build_cond_br(cond.into(), body_bb, exit_bb);
+
+    cg.position_at_end(body_bb);
+    let elem_ptr = unsafe {
+        cg.builder
+            .build_in_bounds_gep(
+                array_ty,
+                array_ptr,
+                &[cg.ptr_sized_int_type.const_zero(), index],
+                "elem_ptr",
+            )
+            .unwrap()
+    };
+    cg.builder.build_store(elem_ptr, value).unwrap();
+    let index_next = cg
+        .builder
+        .build_int_add(index, cg.ptr_sized_int_type.const_int(1, false), "repeat_idx_next")
+        .unwrap();
+    cg.builder.build_store(index_ptr, index_next).unwrap();
+    cg.build_br(entry_bb);
+
+    cg.position_at_end(exit_bb);
+    if expect_ptr {
+        Some(array_ptr.into())
+    } else if elided {
+        None
+    } else {
+        Some(
+            cg.builder
+                .build_load(array_ty, array_ptr, "array_val")
+                .unwrap(),
+        )
+    }
 }
 
 fn codegen_deref<'ctx>(
@@ -1431,8 +1939,5 @@ pub fn codegen_bin_op<'ctx>(
             .build_or(lhs.into_int_value(), rhs.into_int_value(), "bin_temp")
             .unwrap()
             .into(),
-        ast::BinOp::Range | ast::BinOp::RangeInc => {
-            panic!("codegen: range binary operators are not implemented");
-        }
     }
 }
@@ -1,29 +1,38 @@
 use super::context::{Codegen, ProcCodegen};
-use super::emit_expr::{codegen_block_value_optional, codegen_const_value};
+use super::emit_expr::{
+    c_function_get_or_declare, codegen_block_value_optional, codegen_const_value,
+};
 use crate::ast;
 use crate::hir;
+use crate::target::TargetTriple;
+use inkwell::attributes;
 use inkwell::module;
 use inkwell::targets;
 use inkwell::types::BasicType;
+use inkwell::values;
 
 pub fn codegen_module<'ctx>(
     hir: hir::Hir<'ctx>,
     context_llvm: &'ctx inkwell::context::Context,
+    opt_level: inkwell::OptimizationLevel,
+    target: TargetTriple,
 ) -> (module::Module<'ctx>, targets::TargetMachine) {
-    let mut cg = Codegen::new(hir, &context_llvm);
+    let mut cg = Codegen::new(hir, &context_llvm, opt_level, target);
     codegen_string_literals(&mut cg);
+    codegen_enum_types(&mut cg);
     codegen_struct_types(&mut cg);
     codegen_consts(&mut cg);
     codegen_globals(&mut cg);
     codegen_function_values(&mut cg);
     codegen_function_bodies(&cg);
+    codegen_test_harness(&cg);
     cg.finish()
 }
 
 fn codegen_string_literals(cg: &mut Codegen) {
-    for (idx, &string) in cg.hir.intern_string.get_all_strings().iter().enumerate() {
-        let c_string = cg.hir.string_is_cstr[idx];
-        let array_value = cg.context.const_string(string.as_bytes(), c_string);
+    for lit in cg.hir.string_lits.lits() {
+        let string = cg.hir.intern_string.get_str(lit.id);
+        let array_value = cg.context.const_string(string.as_bytes(), lit.c_string);
         let array_ty = array_value.get_type();
 
         let global = cg.module.add_global(array_ty, None, "rock_string_lit");
@@ -35,6 +44,25 @@ fn codegen_string_literals(cg: &mut Codegen) {
     }
 }
 
+// payload only reserves the size and alignment of the largest variant,
+// tag is stored first and variant fields are accessed through `variant_type`
+fn codegen_enum_types(cg: &mut Codegen) {
+    for data in cg.hir.enums.iter() {
+        if !data.has_fields() {
+            cg.enums.push(None);
+            continue;
+        }
+        let size = data.size_eval.get_size().expect("resolved enum size");
+        let tag_ty = cg.basic_type_into_int(data.basic);
+        let payload_elem_ty = cg.context.custom_width_int_type(size.align() as u32 * 8);
+        let payload_ty = payload_elem_ty.array_type((size.size() / size.align() - 1) as u32);
+
+        let enum_ty = cg.context.opaque_struct_type("rock_enum");
+        enum_ty.set_body(&[tag_ty.into(), payload_ty.into()], false);
+        cg.enums.push(Some(enum_ty));
+    }
+}
+
 fn codegen_struct_types(cg: &mut Codegen) {
     for _ in 0..cg.hir.structs.len() {
         let opaque = cg.context.opaque_struct_type("rock_struct");
@@ -64,44 +92,60 @@ fn codegen_consts(cg: &mut Codegen) {
 fn codegen_globals(cg: &mut Codegen) {
     for data in cg.hir.globals.iter() {
         let global_ty = cg.type_into_basic(data.ty);
-        let value = codegen_const_value(cg, cg.hir.const_eval_value(data.value));
-
         let global = cg.module.add_global(global_ty, None, "rock_global");
         global.set_linkage(module::Linkage::Internal);
         global.set_constant(data.mutt == ast::Mut::Immutable);
-        global.set_thread_local(data.thread_local);
-        global.set_initializer(&value);
+        global.set_thread_local(data.attr_set.contains(hir::GlobalFlag::ThreadLocal));
         cg.globals.push(global);
     }
+
+    for &global_id in cg.hir.global_init_order.iter() {
+        let data = &cg.hir.globals[global_id.index()];
+        let value = codegen_const_value(cg, cg.hir.const_eval_value(data.value));
+        cg.globals[global_id.index()].set_initializer(&value);
+    }
 }
 
 fn codegen_function_values(cg: &mut Codegen) {
     let mut param_types = Vec::new();
-    for proc_data in cg.hir.procs.iter() {
+    for (idx, proc_data) in cg.hir.procs.iter().enumerate() {
         param_types.clear();
 
+        if proc_data.poly_params.is_some() {
+            cg.function_values.push(None);
+            continue;
+        }
+
         for param in proc_data.params {
             param_types.push(cg.type_into_basic_metadata(param.ty));
         }
 
         //@repeated in Codegen ProcType generation 29.05.24
+        let is_variadic = proc_data.attr_set.contains(hir::ProcFlag::Variadic);
+        let is_entry = proc_data.attr_set.contains(hir::ProcFlag::Main);
         let function_ty = match cg.type_into_basic_option(proc_data.return_ty) {
-            Some(ty) => ty.fn_type(&param_types, proc_data.is_variadic),
+            Some(ty) => ty.fn_type(&param_types, is_variadic),
+            // `void` main returns exit code 0
+            None if is_entry => cg.context.i32_type().fn_type(&param_types, false),
             None => cg
                 .context
                 .void_type()
-                .fn_type(&param_types, proc_data.is_variadic),
+                .fn_type(&param_types, is_variadic),
         };
 
-        let name = cg.hir.intern_name.get_str(proc_data.name.id);
-
-        //@switch to explicit main flag on proc_data or store ProcID of the entry point in hir instead 29.05.24
-        // module of main being 0 is not stable, might put core library as the first Package / Module thats processed
-        let is_main = proc_data.origin_id == hir::ModuleID::new(0) && name == "main";
-        let is_c_call = proc_data.block.is_none();
+        // test harness defines its own `main` entry point
+        let is_main = is_entry && cg.hir.tests.is_none();
+        let is_c_call = proc_data.attr_set.contains(hir::ProcFlag::External);
+        let external = cg
+            .hir
+            .externals
+            .iter()
+            .find(|external| external.proc_id.index() == idx);
 
-        let name = if is_main || is_c_call {
-            name
+        let name = if let Some(external) = external {
+            cg.hir.intern_name.get_str(external.link_name)
+        } else if is_main {
+            cg.hir.intern_name.get_str(proc_data.name.id)
         } else {
             "rock_proc"
         };
@@ -112,22 +156,51 @@ fn codegen_function_values(cg: &mut Codegen) {
         };
 
         let function = cg.module.add_function(name, function_ty, Some(linkage));
-        if proc_data.block.is_none() {
-            cg.c_functions.insert(proc_data.name.id, function);
+        if let Some(external) = external {
+            function.set_call_conventions(llvm_call_conv(external.call_conv));
+            cg.c_functions.insert(external.link_name, function);
+        }
+        if proc_data.attr_set.contains(hir::ProcFlag::Inline) {
+            add_function_attribute(cg, function, "inlinehint");
+        }
+        if proc_data.attr_set.contains(hir::ProcFlag::NoReturn) {
+            add_function_attribute(cg, function, "noreturn");
         }
-        cg.function_values.push(function);
+        cg.function_values.push(Some(function));
+    }
+}
+
+fn add_function_attribute<'ctx>(
+    cg: &Codegen<'ctx>,
+    function: values::FunctionValue<'ctx>,
+    name: &str,
+) {
+    let kind_id = attributes::Attribute::get_named_enum_kind_id(name);
+    let attribute = cg.context.create_enum_attribute(kind_id, 0);
+    function.add_attribute(attributes::AttributeLoc::Function, attribute);
+}
+
+// llvm calling convention ids
+fn llvm_call_conv(call_conv: hir::CallConv) -> u32 {
+    match call_conv {
+        hir::CallConv::C => 0,
+        hir::CallConv::Stdcall => 64,
+        hir::CallConv::Fastcall => 65,
     }
 }
 
 fn codegen_function_bodies(cg: &Codegen) {
     for (idx, proc_data) in cg.hir.procs.iter().enumerate() {
+        if proc_data.poly_params.is_some() {
+            continue;
+        }
         let block = if let Some(block) = proc_data.block {
             block
         } else {
             continue;
         };
 
-        let function = cg.function_values[idx];
+        let function = cg.function_value(hir::ProcID::new(idx));
 
         let entry_block = cg.context.append_basic_block(function, "entry");
         cg.builder.position_at_end(entry_block);
@@ -156,7 +229,6 @@ fn codegen_function_bodies(cg: &Codegen) {
             param_vars,
             local_vars,
             block_info: Vec::new(),
-            defer_blocks: Vec::new(),
             next_loop_info: None,
             tail_alloca: Vec::with_capacity(64),
         };
@@ -167,3 +239,84 @@ fn codegen_function_bodies(cg: &Codegen) {
         }
     }
 }
+
+// `main(argc, argv)` runs a single test selected by its index in `argv[1]`
+// each test runs in a separate process, panics exit it and are reported as failures
+#[allow(unsafe_code)]
+fn codegen_test_harness(cg: &Codegen) {
+    let tests = match &cg.hir.tests {
+        Some(tests) => tests,
+        None => return,
+    };
+
+    let i32_type = cg.context.i32_type();
+    let main_ty = i32_type.fn_type(&[i32_type.into(), cg.ptr_type.into()], false);
+    let main = cg
+        .module
+        .add_function("main", main_ty, Some(module::Linkage::External));
+    let atoi_ty = i32_type.fn_type(&[cg.ptr_type.into()], false);
+    let c_atoi = c_function_get_or_declare(cg, "atoi", atoi_ty);
+
+    let entry_bb = cg.context.append_basic_block(main, "entry");
+    let select_bb = cg.context.append_basic_block(main, "test_select");
+    let invalid_bb = cg.context.append_basic_block(main, "test_invalid");
+
+    cg.builder.position_at_end(entry_bb);
+    let argc = main.get_nth_param(0).expect("argc").into_int_value();
+    let has_arg = cg
+        .builder
+        .build_int_compare(
+            inkwell::IntPredicate::SGE,
+            argc,
+            i32_type.const_int(2, false),
+            "has_arg",
+        )
+        .unwrap();
+    cg.builder
+        .build_conditional_branch(has_arg, select_bb, invalid_bb)
+        .unwrap();
+
+    cg.builder.position_at_end(select_bb);
+    let argv = main.get_nth_param(1).expect("argv").into_pointer_value();
+    let arg_ptr = unsafe {
+        cg.builder
+            .build_gep(
+                cg.ptr_type,
+                argv,
+                &[i32_type.const_int(1, false)],
+                "arg_ptr",
+            )
+            .unwrap()
+    };
+    let arg = cg.builder.build_load(cg.ptr_type, arg_ptr, "arg").unwrap();
+    let test_idx = cg
+        .builder
+        .build_call(c_atoi, &[arg.into()], "test_idx")
+        .unwrap()
+        .try_as_basic_value()
+        .left()
+        .expect("atoi value")
+        .into_int_value();
+
+    let mut cases = Vec::with_capacity(tests.len());
+    for (idx, proc_id) in tests.iter().enumerate() {
+        let test_bb = cg.context.append_basic_block(main, "test_run");
+        cg.builder.position_at_end(test_bb);
+        let function = cg.function_value(*proc_id);
+        cg.builder.build_call(function, &[], "").unwrap();
+        cg.builder
+            .build_return(Some(&i32_type.const_int(0, false)))
+            .unwrap();
+        cases.push((i32_type.const_int(idx as u64, false), test_bb));
+    }
+
+    cg.builder.position_at_end(select_bb);
+    cg.builder
+        .build_switch(test_idx, invalid_bb, &cases)
+        .unwrap();
+
+    cg.builder.position_at_end(invalid_bb);
+    cg.builder
+        .build_return(Some(&i32_type.const_int(2, false)))
+        .unwrap();
+}
@@ -21,7 +21,7 @@ pub fn codegen_block<'ctx>(
             hir::Stmt::Break => codegen_break(cg, proc_cg),
             hir::Stmt::Continue => codegen_continue(cg, proc_cg),
             hir::Stmt::Return(expr) => codegen_return(cg, proc_cg, expr),
-            hir::Stmt::Defer(block) => proc_cg.push_defer_block(*block),
+            hir::Stmt::Defer(block) => codegen_defer(cg, proc_cg, *block),
             hir::Stmt::Loop(loop_) => codegen_loop(cg, proc_cg, loop_),
             hir::Stmt::Local(local_id) => codegen_local(cg, proc_cg, local_id),
             hir::Stmt::Assign(assign) => codegen_assign(cg, proc_cg, assign),
@@ -29,22 +29,16 @@ pub fn codegen_block<'ctx>(
             hir::Stmt::ExprTail(expr) => codegen_expr_tail(cg, proc_cg, expr, kind),
         }
     }
-
-    if !cg.insert_bb_has_term() {
-        codegen_defer_blocks(cg, proc_cg, &proc_cg.last_defer_blocks());
-    }
     proc_cg.exit_block();
 }
 
 fn codegen_break<'ctx>(cg: &Codegen<'ctx>, proc_cg: &mut ProcCodegen<'ctx>) {
-    let (loop_info, defer_blocks) = proc_cg.last_loop_info();
-    codegen_defer_blocks(cg, proc_cg, &defer_blocks);
+    let loop_info = proc_cg.last_loop_info();
     cg.build_br(loop_info.break_bb);
 }
 
 fn codegen_continue<'ctx>(cg: &Codegen<'ctx>, proc_cg: &mut ProcCodegen<'ctx>) {
-    let (loop_info, defer_blocks) = proc_cg.last_loop_info();
-    codegen_defer_blocks(cg, proc_cg, &defer_blocks);
+    let loop_info = proc_cg.last_loop_info();
     cg.build_br(loop_info.continue_bb);
 }
 
@@ -53,7 +47,6 @@ fn codegen_return<'ctx>(
     proc_cg: &mut ProcCodegen<'ctx>,
     expr: Option<&'ctx hir::Expr<'ctx>>,
 ) {
-    codegen_defer_blocks(cg, proc_cg, &proc_cg.all_defer_blocks());
     if let Some(expr) = expr {
         let value = codegen_expr_value_optional(cg, proc_cg, expr);
         cg.build_ret(value);
@@ -62,20 +55,19 @@ fn codegen_return<'ctx>(
     }
 }
 
-fn codegen_defer_blocks<'ctx>(
+// fallthrough defers are lowered even if block end is not reachable
+fn codegen_defer<'ctx>(
     cg: &Codegen<'ctx>,
     proc_cg: &mut ProcCodegen<'ctx>,
-    defer_blocks: &[hir::Block<'ctx>],
+    block: hir::Block<'ctx>,
 ) {
-    if defer_blocks.is_empty() {
+    if cg.insert_bb_has_term() {
         return;
     }
-    for block in defer_blocks.iter().copied().rev() {
-        let defer_bb = cg.append_bb(proc_cg, "defer_block");
-        cg.build_br(defer_bb);
-        cg.position_at_end(defer_bb);
-        codegen_block(cg, proc_cg, block, BlockKind::TailIgnore);
-    }
+    let defer_bb = cg.append_bb(proc_cg, "defer_block");
+    cg.build_br(defer_bb);
+    cg.position_at_end(defer_bb);
+    codegen_block(cg, proc_cg, block, BlockKind::TailIgnore);
     let exit_bb = cg.append_bb(proc_cg, "defer_exit");
     cg.build_br(exit_bb);
     cg.position_at_end(exit_bb);
@@ -89,10 +81,10 @@ fn codegen_loop<'ctx>(
     let entry_bb = cg.append_bb(proc_cg, "loop_entry");
     let body_bb = cg.append_bb(proc_cg, "loop_body");
     let exit_bb = cg.append_bb(proc_cg, "loop_exit");
-    proc_cg.set_next_loop_info(exit_bb, entry_bb);
 
     match loop_.kind {
         hir::LoopKind::Loop => {
+            proc_cg.set_next_loop_info(exit_bb, entry_bb);
             cg.build_br(entry_bb);
             cg.position_at_end(entry_bb);
             cg.build_br(body_bb);
@@ -102,6 +94,7 @@ fn codegen_loop<'ctx>(
             cg.build_br_no_term(entry_bb);
         }
         hir::LoopKind::While { cond } => {
+            proc_cg.set_next_loop_info(exit_bb, entry_bb);
             cg.build_br(entry_bb);
             cg.position_at_end(entry_bb);
             let cond = codegen_expr_value(cg, proc_cg, cond);
@@ -116,6 +109,9 @@ fn codegen_loop<'ctx>(
             cond,
             assign,
         } => {
+            // `continue` jumps to the assign step, not directly to the condition
+            let step_bb = cg.append_bb(proc_cg, "loop_step");
+            proc_cg.set_next_loop_info(exit_bb, step_bb);
             codegen_local(cg, proc_cg, local_id);
 
             cg.build_br(entry_bb);
@@ -125,10 +121,11 @@ fn codegen_loop<'ctx>(
 
             cg.position_at_end(body_bb);
             codegen_block(cg, proc_cg, loop_.block, BlockKind::TailIgnore);
-            if !cg.insert_bb_has_term() {
-                codegen_assign(cg, proc_cg, assign);
-            }
-            cg.build_br_no_term(entry_bb);
+            cg.build_br_no_term(step_bb);
+
+            cg.position_at_end(step_bb);
+            codegen_assign(cg, proc_cg, assign);
+            cg.build_br(entry_bb);
         }
     }
 
@@ -3,9 +3,12 @@ mod emit_expr;
 mod emit_mod;
 mod emit_stmt;
 
+use crate::build_layout::{BuildKind, BuildLayout, BuildMetadata};
 use crate::error::ErrorComp;
 use crate::fs_env;
 use crate::hir;
+use crate::linker::{LinkInput, Linker, Subsystem};
+use crate::package::manifest::OptLevel;
 use crate::session::Session;
 use inkwell::module;
 use inkwell::targets;
@@ -13,69 +16,95 @@ use std::path::PathBuf;
 
 struct BuildContext {
     bin_name: String,
-    build_kind: BuildKind,
-    build_dir: PathBuf,
+    layout: BuildLayout,
+    object_path: PathBuf,
     executable_path: PathBuf,
 }
 
-#[derive(Copy, Clone)]
-pub enum BuildKind {
-    Debug,
-    Release,
-}
-
-impl BuildKind {
-    pub fn as_str(self) -> &'static str {
-        match self {
-            BuildKind::Debug => "debug",
-            BuildKind::Release => "release",
-        }
-    }
-}
-
 pub fn codegen(
     hir: hir::Hir,
     session: &Session,
     build_kind: BuildKind,
     emit_llvm: bool,
-    args: Option<Vec<String>>,
-) -> Result<(), ErrorComp> {
+) -> Result<PathBuf, ErrorComp> {
+    let test_harness = hir.tests.is_some();
+    let context = create_build_context(session, build_kind, test_harness)?;
+    let metadata = BuildMetadata::new(
+        session,
+        build_kind,
+        context.executable_path.clone(),
+        vec![context.object_path.clone()],
+    );
+
+    // nothing changed since the last build, keep the executable
+    let last_metadata = context.layout.read_metadata();
+    if let Some(last) = &last_metadata {
+        if !emit_llvm && metadata.is_up_to_date(last) {
+            return Ok(context.executable_path);
+        }
+    }
+
+    let link_libs: Vec<String> = hir
+        .link_libs
+        .iter()
+        .map(|&name_id| hir.intern_name.get_str(name_id).to_string())
+        .collect();
     let context_llvm = inkwell::context::Context::create();
-    let (module, machine) = emit_mod::codegen_module(hir, &context_llvm);
-    let context = create_build_context(session, build_kind)?;
+    let opt_level = build_opt_level(session, build_kind);
+    let (module, machine) =
+        emit_mod::codegen_module(hir, &context_llvm, opt_level, session.target());
     module_verify(&context, &module, emit_llvm)?;
-    build_executable(&context, module, machine, session)?;
-    run_executable(&context, args)?;
-    Ok(())
+    build_executable(&context, module, machine, session, link_libs)?;
+
+    if let Some(last) = &last_metadata {
+        context.layout.remove_stale(last, &metadata)?;
+    }
+    context.layout.write_metadata(&metadata)?;
+    Ok(context.executable_path)
+}
+
+// [build] `opt_level` from the root manifest overrides the build kind default
+fn build_opt_level(session: &Session, build_kind: BuildKind) -> inkwell::OptimizationLevel {
+    let root_manifest = session.package(Session::ROOT_ID).manifest();
+    let opt_level = root_manifest
+        .build
+        .opt_level
+        .unwrap_or(build_kind.default_opt_level());
+
+    match opt_level {
+        OptLevel::None => inkwell::OptimizationLevel::None,
+        OptLevel::Less => inkwell::OptimizationLevel::Less,
+        OptLevel::Default => inkwell::OptimizationLevel::Default,
+        OptLevel::Aggressive => inkwell::OptimizationLevel::Aggressive,
+    }
 }
 
 fn create_build_context(
     session: &Session,
     build_kind: BuildKind,
+    test_harness: bool,
 ) -> Result<BuildContext, ErrorComp> {
-    let mut build_dir = fs_env::dir_get_current_working()?;
-    build_dir.push("build");
-
-    fs_env::dir_create(&build_dir, false)?;
-    build_dir.push(build_kind.as_str());
-    fs_env::dir_create(&build_dir, false)?;
+    let layout = BuildLayout::new(session.cwd(), build_kind, session.target());
+    layout.create_dirs(session)?;
 
     let root_package = session.package(Session::ROOT_ID);
     let root_manifest = root_package.manifest();
-    let bin_name = if let Some(bin_name) = &root_manifest.build.bin_name {
+    let mut bin_name = if let Some(bin_name) = &root_manifest.build.bin_name {
         bin_name.clone()
     } else {
         root_manifest.package.name.clone()
     };
+    if test_harness {
+        bin_name.push_str("_test");
+    }
 
-    let mut executable_path = build_dir.clone();
-    executable_path.push(&bin_name);
-    executable_path.set_extension("exe"); //@assuming windows
+    let object_path = layout.object_path(&root_manifest.package.name, &bin_name);
+    let executable_path = layout.executable_path(&bin_name);
 
     let context = BuildContext {
         bin_name,
-        build_kind,
-        build_dir,
+        layout,
+        object_path,
         executable_path,
     };
     Ok(context)
@@ -86,7 +115,7 @@ fn module_verify<'ctx>(
     module: &module::Module<'ctx>,
     emit_llvm: bool,
 ) -> Result<(), ErrorComp> {
-    let emit_path = context.build_dir.join(format!("{}.ll", context.bin_name));
+    let emit_path = context.layout.llvm_ir_path(&context.bin_name);
 
     if emit_llvm {
         module.print_to_file(&emit_path).unwrap();
@@ -108,10 +137,11 @@ fn build_executable<'ctx>(
     module: module::Module<'ctx>,
     machine: targets::TargetMachine,
     session: &Session,
+    link_libs: Vec<String>,
 ) -> Result<(), ErrorComp> {
-    let object_path = context.build_dir.join(format!("{}.o", context.bin_name));
+    let object_path = &context.object_path;
     machine
-        .write_to_file(&module, targets::FileType::Object, &object_path)
+        .write_to_file(&module, targets::FileType::Object, object_path)
         .map_err(|error| {
             ErrorComp::message(format!(
                 "failed to write llvm module as object file\nreason: {}",
@@ -119,96 +149,76 @@ fn build_executable<'ctx>(
             ))
         })?;
 
-    let arg_obj = object_path.to_string_lossy().to_string();
-    let arg_out = format!("/out:{}", context.executable_path.to_string_lossy());
-    let mut args = vec![arg_obj, arg_out];
-
-    //@check if they need to be comma separated instead of being separate
-    match context.build_kind {
-        BuildKind::Debug => {
-            args.push("/opt:ref".into());
-            args.push("/opt:noicf".into());
-            args.push("/opt:nolbr".into());
-        }
-        BuildKind::Release => {
-            args.push("/opt:ref".into());
-            args.push("/opt:icf".into());
-            args.push("/opt:lbr".into());
-        }
-    }
-
-    //@windows only
-    if true {
-        //sybsystem needs to be specified on windows (console, windows)
-        //@only console with `main` entry point is supported, support WinMain when such feature is required 29.05.24
-        args.push("/subsystem:console".into());
-        // link with C runtime library: libcmt.lib (static), msvcrt.lib (dynamic)
-        //@always linking with static C runtime library, support attributes or toml configs 29.05.24
-        // to change this if needed, this might be a problem when trying to link C libraries (eg: raylib.lib)
-    } else {
-        panic!("only windows targets are supported");
-    }
-
-    let mut nodefaultlib = false;
+    let mut input = LinkInput {
+        kind: context.layout.kind(),
+        objects: vec![object_path.clone()],
+        output: context.executable_path.clone(),
+        subsystem: Subsystem::Console,
+        lib_paths: Vec::new(),
+        libs: link_libs,
+        nodefaultlib: false,
+        target: session.target(),
+    };
 
     for package_id in session.package_ids() {
         let package = session.package(package_id);
         let manifest = package.manifest();
 
-        match manifest.build.nodefaultlib {
-            Some(true) => nodefaultlib = true,
-            _ => {}
+        if manifest.build.nodefaultlib == Some(true) {
+            input.nodefaultlib = true;
         }
         if let Some(lib_paths) = &manifest.build.lib_paths {
             for path in lib_paths {
-                let lib_path = package.root_dir().join(path);
-                args.push(format!("/libpath:{}", lib_path.to_string_lossy()));
+                input.lib_paths.push(package.root_dir.join(path));
             }
         }
         if let Some(links) = &manifest.build.links {
             for link in links {
-                args.push(format!("{}", link));
+                if !input.libs.contains(link) {
+                    input.libs.push(link.clone());
+                }
             }
         }
     }
 
-    if !nodefaultlib {
-        //@windows only
-        args.push("/defaultlib:libcmt.lib".into());
-    }
+    let linker = Linker::find(session.target())?;
+    linker.link(&input)
+}
 
-    // lld-link is called system wide, and requires llvm being installed @29.05.24
-    // test and use bundled lld-link relative to install path instead
-    let _ = std::process::Command::new("lld-link")
+/// runs the executable with inherited stdio and returns its exit code
+pub fn run_executable(executable_path: &PathBuf, args: Vec<String>) -> Result<i32, ErrorComp> {
+    let status = std::process::Command::new(executable_path.as_os_str())
         .args(args)
         .status()
         .map_err(|io_error| {
             ErrorComp::message(format!(
-                "failed to link object file `{}`\nreason: {}",
-                object_path.to_string_lossy(),
+                "failed to run executable `{}`\nreason: {}",
+                executable_path.to_string_lossy(),
                 io_error
             ))
         })?;
-    fs_env::file_remove(&object_path, false)?;
-    Ok(())
-}
 
-fn run_executable(context: &BuildContext, args: Option<Vec<String>>) -> Result<(), ErrorComp> {
-    let args = match args {
-        Some(args) => args,
-        None => return Ok(()),
-    };
+    //@no exit code when process was terminated by a signal (unix), treating it as failure
+    Ok(status.code().unwrap_or(1))
+}
 
-    std::process::Command::new(context.executable_path.as_os_str())
+/// runs the executable with captured output, returns its exit code and output
+pub fn run_executable_captured(
+    executable_path: &PathBuf,
+    args: Vec<String>,
+) -> Result<(i32, String), ErrorComp> {
+    let output = std::process::Command::new(executable_path.as_os_str())
         .args(args)
-        .status()
+        .output()
         .map_err(|io_error| {
             ErrorComp::message(format!(
                 "failed to run executable `{}`\nreason: {}",
-                context.executable_path.to_string_lossy(),
+                executable_path.to_string_lossy(),
                 io_error
             ))
         })?;
 
-    Ok(())
+    let mut text = String::from_utf8_lossy(&output.stdout).to_string();
+    text.push_str(&String::from_utf8_lossy(&output.stderr));
+    Ok((output.status.code().unwrap_or(1), text))
 }
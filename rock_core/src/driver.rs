@@ -0,0 +1,259 @@
+use crate::ast;
+use crate::ast_parse;
+use crate::bitset::BitSet;
+use crate::build_layout::BuildKind;
+#[cfg(not(target_os = "linux"))]
+#[cfg(feature = "codegen_llvm")]
+use crate::codegen;
+use crate::error::ice;
+use crate::error::{DiagnosticCollection, LintConfig, ResultComp, WarningComp};
+use crate::hir;
+use crate::hir_lower::{self, InlayHints};
+use crate::index::FileIndex;
+use crate::intern::InternPool;
+use crate::session::Session;
+use crate::target::TargetTriple;
+#[cfg(not(target_os = "linux"))]
+#[cfg(feature = "codegen_llvm")]
+use crate::timer::Timer;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+pub use crate::timer::{Timing, Timings};
+
+/// options shared by `rock_cli` and `rock_ls`,
+/// each front-end fills them from its own arguments or settings
+pub struct CompileOptions {
+    pub stage: Stage,
+    pub build_kind: BuildKind,
+    pub target: TargetTriple,
+    pub lint_config: LintConfig,
+    pub error_limit: Option<u32>,
+    /// depth of nested expressions, types and blocks accepted by the parser
+    pub nesting_limit: u32,
+    pub emit: EmitSet,
+    pub emit_llvm: bool,
+    pub tests: bool,
+    pub inlay_hints: bool,
+    /// collect definitions and references for `index::SymbolIndex`
+    pub symbol_index: bool,
+    pub cancel: CancelToken,
+}
+
+/// set from another thread to stop a running compilation,
+/// checked between stages and checker passes, output of a cancelled compilation is incomplete
+#[derive(Clone, Default)]
+pub struct CancelToken(Arc<AtomicBool>);
+
+/// last stage to run, later stages are skipped
+#[derive(Copy, Clone, PartialEq)]
+pub enum Stage {
+    Parse,
+    Check,
+    Build,
+}
+
+#[derive(Copy, Clone, PartialEq)]
+pub enum EmitKind {
+    Ast,
+    Hir,
+}
+
+/// printed program representations, collected after each stage completes without errors
+#[derive(Copy, Clone)]
+pub struct EmitSet(BitSet);
+
+pub struct Compilation<'ast, 'hir, 'intern> {
+    /// outputs of emitted stages, kept even when a later stage fails
+    pub emitted: Vec<(EmitKind, String)>,
+    /// stages that completed, recorded for `--timings` and slow operation logs
+    pub timings: Timings,
+    /// output of the last stage that ran, partial when `diagnostics` contain errors,
+    /// `None` after an internal compiler error or a codegen failure
+    pub compiled: Option<Compiled<'ast, 'hir, 'intern>>,
+    pub diagnostics: DiagnosticCollection,
+}
+
+pub enum Compiled<'ast, 'hir, 'intern> {
+    Parsed(Box<ast::Ast<'ast, 'intern>>),
+    /// index entries are `None` unless `symbol_index` was requested
+    Checked(Box<hir::Hir<'hir>>, InlayHints, Option<Vec<FileIndex>>),
+    Built(Built),
+}
+
+pub struct Built {
+    /// `None` when codegen is not available on this host
+    pub executable_path: Option<PathBuf>,
+    /// `#[test]` procedure names, indexed same as test harness arguments
+    pub test_names: Vec<String>,
+}
+
+impl<'ast, 'hir, 'intern> Compilation<'ast, 'hir, 'intern> {
+    pub fn has_errors(&self) -> bool {
+        self.diagnostics.has_errors()
+    }
+
+    /// partial output is discarded when any errors were reported
+    pub fn into_result(
+        self,
+    ) -> Result<(Compiled<'ast, 'hir, 'intern>, Vec<WarningComp>), DiagnosticCollection> {
+        match self.compiled {
+            Some(compiled) => ResultComp::new(compiled, self.diagnostics).into_result(vec![]),
+            None => Err(self.diagnostics),
+        }
+    }
+}
+
+impl CompileOptions {
+    pub fn new(stage: Stage, target: TargetTriple) -> CompileOptions {
+        CompileOptions {
+            stage,
+            build_kind: BuildKind::Debug,
+            target,
+            lint_config: LintConfig::new(),
+            error_limit: None,
+            nesting_limit: ast_parse::NESTING_LIMIT,
+            emit: EmitSet::EMPTY,
+            emit_llvm: false,
+            tests: false,
+            inlay_hints: false,
+            symbol_index: false,
+            cancel: CancelToken::new(),
+        }
+    }
+
+    /// `Session` must be created with `building` set for the build stage
+    pub fn building(&self) -> bool {
+        self.stage == Stage::Build
+    }
+}
+
+impl EmitSet {
+    pub const EMPTY: EmitSet = EmitSet(BitSet::EMPTY);
+
+    pub fn set(&mut self, kind: EmitKind) {
+        self.0.set(kind);
+    }
+    pub fn contains(self, kind: EmitKind) -> bool {
+        self.0.contains(kind)
+    }
+}
+
+impl CancelToken {
+    pub fn new() -> CancelToken {
+        CancelToken::default()
+    }
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+impl From<EmitKind> for u32 {
+    fn from(kind: EmitKind) -> u32 {
+        kind as u32
+    }
+}
+
+/// run all stages up to `options.stage` on a created session,
+/// internal compiler errors are reported as `ErrorCode::Internal` instead of a panic
+///
+/// stages run on partial results of the previous stage, except:
+/// - check is skipped after syntax errors, items that failed to parse would be reported as not found
+/// - codegen is skipped after any errors
+pub fn compile<'ast, 'hir, 'intern: 'hir>(
+    session: &Session,
+    intern_name: InternPool<'intern>,
+    options: &CompileOptions,
+) -> Compilation<'ast, 'hir, 'intern> {
+    let mut emitted = Vec::new();
+    let mut timings = Timings::new();
+    let result =
+        ice::catch(|| compile_impl(session, intern_name, options, &mut emitted, &mut timings));
+    let (compiled, diagnostics) = match result {
+        Ok(result) => result,
+        Err(ice) => (
+            None,
+            DiagnosticCollection::new().join_errors(vec![ice.into_error()]),
+        ),
+    };
+    Compilation {
+        emitted,
+        timings,
+        compiled,
+        diagnostics,
+    }
+}
+
+fn compile_impl<'ast, 'hir, 'intern: 'hir>(
+    session: &Session,
+    intern_name: InternPool<'intern>,
+    options: &CompileOptions,
+    emitted: &mut Vec<(EmitKind, String)>,
+    timings: &mut Timings,
+) -> (Option<Compiled<'ast, 'hir, 'intern>>, DiagnosticCollection) {
+    let parsed = ast_parse::parse(session, intern_name, options.nesting_limit, timings);
+    let ast = parsed.value;
+    let diagnostics = parsed.diagnostics;
+    if options.emit.contains(EmitKind::Ast) && !diagnostics.has_errors() {
+        emitted.push((EmitKind::Ast, ast_parse::ast_print(&ast, session)));
+    }
+    if options.stage == Stage::Parse || diagnostics.has_errors() || options.cancel.is_cancelled() {
+        return (Some(Compiled::Parsed(Box::new(ast))), diagnostics);
+    }
+
+    let (checked, hints, index) = hir_lower::check_impl(
+        ast,
+        session,
+        options.error_limit,
+        options.tests,
+        options.inlay_hints,
+        options.symbol_index,
+        &options.cancel,
+        timings,
+    );
+    let checked = options.lint_config.apply(checked);
+    let hir = checked.value;
+    let diagnostics = diagnostics.join_collection(checked.diagnostics);
+    if options.emit.contains(EmitKind::Hir) && !diagnostics.has_errors() {
+        emitted.push((EmitKind::Hir, hir_lower::hir_print(&hir)));
+    }
+    if options.stage == Stage::Check || diagnostics.has_errors() || options.cancel.is_cancelled() {
+        let checked = Compiled::Checked(Box::new(hir), hints.unwrap_or_default(), index);
+        return (Some(checked), diagnostics);
+    }
+
+    let test_names = test_names(&hir);
+    #[cfg(any(target_os = "linux", not(feature = "codegen_llvm")))]
+    let executable_path = None;
+    #[cfg(not(target_os = "linux"))]
+    #[cfg(feature = "codegen_llvm")]
+    let executable_path = {
+        let mut timer = Timer::new();
+        let result = codegen::codegen(hir, session, options.build_kind, options.emit_llvm);
+        timer.lap(timings, "codegen", None);
+        match result {
+            Ok(executable_path) => Some(executable_path),
+            Err(error) => return (None, diagnostics.join_errors(vec![error])),
+        }
+    };
+    let built = Built {
+        executable_path,
+        test_names,
+    };
+    (Some(Compiled::Built(built)), diagnostics)
+}
+
+fn test_names(hir: &hir::Hir) -> Vec<String> {
+    let test_procs = hir.tests.as_deref().unwrap_or(&[]);
+    test_procs
+        .iter()
+        .map(|proc_id| {
+            let name = hir.procs[proc_id.index()].name.id;
+            hir.intern_name.get_str(name).to_string()
+        })
+        .collect()
+}
@@ -0,0 +1,178 @@
+use crate::driver::{self, CompileOptions, EmitKind};
+use crate::error::{
+    Diagnostic, DiagnosticCode, DiagnosticCollection, DiagnosticContext, DiagnosticKind,
+    DiagnosticSeverity, ErrorComp,
+};
+use crate::session::Session;
+use crate::text;
+use std::path::PathBuf;
+
+/// output of `compile_str`, diagnostics are resolved to plain text and source locations
+pub struct CompileResult {
+    /// warnings followed by errors, without terminal formatting
+    pub diagnostics: Vec<Message>,
+    /// printed ast or hir of each stage in `options.emit` that completed without errors
+    pub emitted: Vec<(EmitKind, String)>,
+}
+
+pub struct Message {
+    pub severity: DiagnosticSeverity,
+    pub code: Option<&'static str>,
+    pub message: String,
+    /// first context is the main one, others are related info
+    pub contexts: Vec<MessageContext>,
+}
+
+pub struct MessageContext {
+    pub severity: DiagnosticSeverity,
+    pub message: String,
+    /// module path relative to the package, `src/<name>`
+    pub path: PathBuf,
+    pub start: u32,
+    pub end: u32,
+    /// 1-based line and char column of `start`
+    pub line: u32,
+    pub col: u32,
+}
+
+/// compile in-memory modules of a single library package, used by embedders
+/// `files` are `(name, source)` pairs, names are module paths relative to `src`
+///
+/// current directory and file system are not accessed, each call creates its own session,
+/// `Stage::Build` is rejected since executables are written to the package directory
+pub fn compile_str(files: &[(&str, &str)], options: &CompileOptions) -> CompileResult {
+    if options.building() {
+        let error = ErrorComp::message("build stage is not supported when compiling from strings");
+        let diagnostics = DiagnosticCollection::new().join_errors(vec![error]);
+        return CompileResult::new(None, diagnostics, Vec::new());
+    }
+    let files: Vec<(PathBuf, String)> = files
+        .iter()
+        .map(|&(name, source)| (PathBuf::from(name), source.to_string()))
+        .collect();
+
+    match Session::new_virtual(PathBuf::new(), options.target, &files) {
+        Ok((session, intern_name)) => {
+            let compilation = driver::compile(&session, intern_name, options);
+            CompileResult::new(Some(&session), compilation.diagnostics, compilation.emitted)
+        }
+        Err(error) => {
+            let diagnostics = DiagnosticCollection::new().join_errors(vec![error]);
+            CompileResult::new(None, diagnostics, Vec::new())
+        }
+    }
+}
+
+impl CompileResult {
+    fn new(
+        session: Option<&Session>,
+        diagnostics: DiagnosticCollection,
+        emitted: Vec<(EmitKind, String)>,
+    ) -> CompileResult {
+        let warnings = diagnostics.warnings().iter().map(|warning| {
+            Message::new(session, warning.diagnostic(), DiagnosticSeverity::Warning)
+        });
+        let errors = diagnostics
+            .errors()
+            .iter()
+            .map(|error| Message::new(session, error.diagnostic(), DiagnosticSeverity::Error));
+        CompileResult {
+            diagnostics: warnings.chain(errors).collect(),
+            emitted,
+        }
+    }
+
+    pub fn has_errors(&self) -> bool {
+        self.diagnostics
+            .iter()
+            .any(|message| matches!(message.severity, DiagnosticSeverity::Error))
+    }
+}
+
+impl Message {
+    // session errors are reported before any module exists, they have no contexts
+    fn new(
+        session: Option<&Session>,
+        diagnostic: &Diagnostic,
+        severity: DiagnosticSeverity,
+    ) -> Message {
+        let mut contexts = Vec::new();
+        if let Some(session) = session {
+            match diagnostic.kind() {
+                DiagnosticKind::Message => {}
+                DiagnosticKind::Context { main, info } => {
+                    contexts.push(MessageContext::new(session, main, severity));
+                    if let Some(info) = info {
+                        let info = MessageContext::new(session, info, DiagnosticSeverity::Info);
+                        contexts.push(info);
+                    }
+                }
+                DiagnosticKind::ContextVec { main, info_vec } => {
+                    contexts.push(MessageContext::new(session, main, severity));
+                    for info in info_vec {
+                        let info = MessageContext::new(session, info, DiagnosticSeverity::Info);
+                        contexts.push(info);
+                    }
+                }
+            }
+        }
+        Message {
+            severity,
+            code: diagnostic.code().map(DiagnosticCode::as_str),
+            message: diagnostic.message().as_str().to_string(),
+            contexts,
+        }
+    }
+}
+
+impl MessageContext {
+    fn new(
+        session: &Session,
+        context: &DiagnosticContext,
+        severity: DiagnosticSeverity,
+    ) -> MessageContext {
+        let module = session.module(context.source().module_id());
+        let range = context.source().range();
+        let location = text::find_text_location(&module.source, range.start(), &module.line_ranges);
+        MessageContext {
+            severity,
+            message: context.message().to_string(),
+            path: module.path.clone(),
+            start: range.start().into(),
+            end: range.end().into(),
+            line: location.line(),
+            col: location.col(),
+        }
+    }
+}
+
+#[test]
+fn test_compile_str() {
+    use crate::driver::Stage;
+    use crate::target::TargetTriple;
+
+    let files = [
+        (
+            "main.rock",
+            "import shapes;\n\nproc sum() -> s32 {\n    return true;\n}\n",
+        ),
+        ("shapes.rock", "pub proc area() -> s32 { return 1; }\n"),
+    ];
+    let options = CompileOptions::new(Stage::Check, TargetTriple::host());
+    let result = compile_str(&files, &options);
+    assert!(result.has_errors());
+
+    let mismatch = result
+        .diagnostics
+        .iter()
+        .find(|message| message.code == Some("E0038"))
+        .expect("type mismatch is reported");
+    let main = &mismatch.contexts[0];
+    assert!(main.path == std::path::Path::new("src/main.rock"));
+    assert!(main.line == 4 && main.col == 12);
+    assert!(main.end - main.start == 4);
+
+    let options = CompileOptions::new(Stage::Build, TargetTriple::host());
+    let result = compile_str(&files, &options);
+    assert!(result.has_errors() && result.diagnostics[0].contexts.is_empty());
+}
@@ -0,0 +1,925 @@
+use super::ErrorCode;
+
+// each explanation starts with a short summary line,
+// examples are indented by 4 spaces and must produce the described error
+pub(super) fn explain(code: ErrorCode) -> &'static str {
+    match code {
+        ErrorCode::SyntaxError => {
+            r#"source code does not match the language grammar.
+
+the parser expected a different token at the reported location,
+the error message describes what was expected instead.
+
+example:
+    proc main() -> s32 {
+        return 0
+    }
+
+statements must be terminated with `;`: `return 0;`"#
+        }
+        ErrorCode::UnknownSymbol => {
+            r#"source code contains a character which is not a valid token.
+
+only ascii characters are allowed outside of comments,
+character literals and string literals.
+
+example:
+    proc main() -> s32 {
+        return 0 @ 1;
+    }
+
+`@` is not an operator, remove it or replace it with a valid one."#
+        }
+        ErrorCode::UnterminatedComment => {
+            r#"block comment is missing its closing `*/`.
+
+block comments can be nested, each `/*` requires a matching `*/`.
+
+example:
+    /* outer /* inner */
+    proc main() -> s32 {
+        return 0;
+    }
+
+add the missing `*/` after the comment text."#
+        }
+        ErrorCode::UnterminatedString => {
+            r#"string literal is missing its closing quote.
+
+regular string literals are closed with `"`,
+raw string literals are closed with a backtick.
+
+example:
+    proc main() -> s32 {
+        let s = "hello;
+        return 0;
+    }
+
+add the closing quote: `"hello"`."#
+        }
+        ErrorCode::InvalidCharLiteral => {
+            r#"character literal is malformed.
+
+character literal must contain exactly one character,
+tab and `'` characters must be escaped.
+
+example:
+    proc main() -> s32 {
+        let c = '';
+        return 0;
+    }
+
+put a single character between the quotes: `'a'`."#
+        }
+        ErrorCode::InvalidEscape => {
+            r#"escape sequence is incomplete or not supported.
+
+supported escape sequences are:
+`\t`, `\n`, `\r`, `\0`, `\'`, `\"` and `\\`
+unicode scalar values use 1 to 6 hex digits: `\u{1F600}`
+
+example:
+    proc main() -> s32 {
+        let s = "path\q";
+        return 0;
+    }
+
+use `\\` to write a backslash or use a raw string literal."#
+        }
+        ErrorCode::CStringNullByte => {
+            r#"c string literal contains a null terminator `\0`.
+
+c strings are null terminated automatically,
+an extra `\0` inside would cut the string short.
+
+example:
+    proc main() -> s32 {
+        let s = c"text\0";
+        return 0;
+    }
+
+remove the `\0`: `c"text"`."#
+        }
+        ErrorCode::InvalidNumberLiteral => {
+            r#"number literal is malformed or has an invalid type suffix.
+
+integer literals must fit into `u64` before their type is known,
+type suffix must be a basic integer or float type.
+
+example:
+    proc main() -> s32 {
+        let x = 10_s99;
+        return 0;
+    }
+
+use a valid suffix like `10_s32` or remove the suffix."#
+        }
+        ErrorCode::NameNotFound => {
+            r#"name is not defined in the current scope or in the referenced module.
+
+names must be declared before use as locals or parameters,
+or defined as items in the module or imported into it.
+
+example:
+    proc main() -> s32 {
+        return value;
+    }
+
+check the spelling or declare the missing name."#
+        }
+        ErrorCode::DuplicateDefinition => {
+            r#"name is defined multiple times in the same scope.
+
+applies to items, parameters, type parameters, fields, variants and methods,
+methods also cannot have the same name as a field of their type.
+glob imports bringing different items with the same name also conflict.
+
+example:
+    proc value() -> s32 { return 1; }
+    proc value() -> s32 { return 2; }
+
+rename or remove one of the definitions."#
+        }
+        ErrorCode::PrivateItem => {
+            r#"item, field or method is private to its module.
+
+items, fields and methods are private by default,
+mark them with `pub` to use them from other modules.
+
+example:
+    // module `math`
+    proc square(x: s32) -> s32 { return x * x; }
+
+    // module `main`
+    import math;
+    proc main() -> s32 { return math.square(2); }
+
+mark the definition as public: `pub proc square(..)`."#
+        }
+        ErrorCode::ImportNotDefinedHere => {
+            r#"name is imported into the referenced module, not defined in it.
+
+imported names cannot be accessed through another module,
+import them from the module where they are defined.
+
+example:
+    // module `a`
+    import b.{ value };
+
+    // module `main`
+    import a;
+    proc main() -> s32 { return a.value(); }
+
+import `value` from module `b` instead."#
+        }
+        ErrorCode::PackageNotFound => {
+            r#"imported package is not a dependency of the current package.
+
+packages must be listed in `[dependencies]` of `Rock.toml`
+before they can be imported.
+
+example:
+    import json:parser;
+
+add the package to the manifest: `json = "0.1.0"`."#
+        }
+        ErrorCode::ModuleNotFound => {
+            r#"import path does not refer to an existing module.
+
+each import path segment is a directory in `src`,
+the last segment is a module file without the `.rock` extension.
+
+example:
+    import utils/strings;
+
+check that `src/utils/strings.rock` exists."#
+        }
+        ErrorCode::SelfImport => {
+            r#"module imports itself.
+
+all items of the current module are already in scope.
+
+example:
+    // module `main`
+    import main;
+
+remove the import."#
+        }
+        ErrorCode::AlreadyImported => {
+            r#"same module or item is imported multiple times.
+
+each module or item only needs to be imported once,
+importing different items under the same name is a duplicate definition.
+
+example:
+    import a.{ value };
+    import a.{ value };
+
+remove the duplicate import."#
+        }
+        ErrorCode::UnexpectedPathSegment => {
+            r#"path has more segments than the referenced item allows.
+
+only modules can be followed by a path segment,
+and enums can only be followed by a variant name.
+
+example:
+    struct Vec2 { x: f32, y: f32 }
+    proc main() -> s32 {
+        let x: Vec2.x = 0.0;
+        return 0;
+    }
+
+remove the extra path segments."#
+        }
+        ErrorCode::ExpectedType => {
+            r#"path was used as a type but refers to something else.
+
+only structs, enums and type parameters can be used as types.
+
+example:
+    proc value() -> s32 { return 0; }
+    proc main() -> s32 {
+        let x: value = 0;
+        return 0;
+    }
+
+use a type: `let x: s32 = 0;`."#
+        }
+        ErrorCode::ExpectedValue => {
+            r#"path was used as a value but refers to something else.
+
+modules, structs and enums are not values.
+
+example:
+    struct Vec2 { x: f32, y: f32 }
+    proc main() -> s32 {
+        let x = Vec2;
+        return 0;
+    }
+
+use a value, for example a struct initializer: `Vec2.{ x: 0.0, y: 0.0 }`."#
+        }
+        ErrorCode::UnknownAttribute => {
+            r#"attribute name is not recognized.
+
+this error is no longer emitted, unknown attributes are ignored
+and reported with the `unknown_attribute` warning instead.
+
+supported attributes are: `test`, `builtin`, `inline`, `no_return`,
+`deprecated`, `thread_local`, `link`, `link_name`, `call_conv` and `allow`.
+
+example:
+    #[fast]
+    proc main() -> s32 { return 0; }
+
+remove the attribute or check its spelling."#
+        }
+        ErrorCode::InvalidAttributeArgument => {
+            r#"attribute arguments are missing, unexpected or invalid.
+
+each attribute expects a specific set of arguments,
+for example `#[call_conv]` expects a single calling convention.
+
+example:
+    #[call_conv(pascal)]
+    proc draw();
+
+use `c`, `stdcall` or `fastcall` calling convention."#
+        }
+        ErrorCode::AttributeNotApplicable => {
+            r#"attribute cannot be applied to this kind of item.
+
+for example `#[test]` can only be applied to procedures,
+and `#[thread_local]` can only be applied to globals.
+
+example:
+    #[test]
+    struct Vec2 { x: f32, y: f32 }
+
+remove the attribute or move it to a supported item."#
+        }
+        ErrorCode::UnstableGenerics => {
+            r#"generic procedures are used without enabling them.
+
+generic procedures are unstable and must be enabled
+in the `[build]` section of `Rock.toml`.
+
+example:
+    proc id[T](x: T) -> T { return x; }
+
+add `unstable_generics = true` to the `[build]` section."#
+        }
+        ErrorCode::GenericNotAllowed => {
+            r#"type parameters are used where they are not supported.
+
+external procedures and methods cannot be generic.
+
+example:
+    proc malloc[T](size: u64) -> rawptr;
+
+remove the type parameters."#
+        }
+        ErrorCode::InvalidVariadic => {
+            r#"variadic procedure is declared incorrectly.
+
+only `external` procedures can be variadic,
+and they must have at least one named parameter before `..`.
+
+example:
+    proc printf(..) -> s32;
+
+add a named parameter: `proc printf(fmt: &u8, ..) -> s32;`."#
+        }
+        ErrorCode::InvalidImpl => {
+            r#"`impl` block or its method is invalid.
+
+`impl` blocks can only be defined for structs and enums,
+and methods cannot be `external`.
+
+example:
+    struct Vec2 { x: f32, y: f32 }
+    impl Vec2 {
+        proc len(self: &Vec2) -> f32;
+    }
+
+methods must have a body."#
+        }
+        ErrorCode::InvalidSelfParam => {
+            r#"method is missing `self` parameter or it has an invalid type.
+
+first parameter of a method must be named `self`,
+its type must be the owner type or a reference to it.
+
+example:
+    struct Vec2 { x: f32, y: f32 }
+    impl Vec2 {
+        proc len(v: &Vec2) -> f32 { return 0.0; }
+    }
+
+rename the parameter: `proc len(self: &Vec2) -> f32`."#
+        }
+        ErrorCode::InvalidTestProc => {
+            r#"procedure with `#[test]` attribute has an invalid signature.
+
+test procedures are called by the test harness,
+they cannot be generic, take parameters or return a value.
+
+example:
+    #[test]
+    proc test_add(x: s32) { }
+
+remove the parameters: `proc test_add() { }`."#
+        }
+        ErrorCode::InvalidMainProc => {
+            r#"`main` procedure has an invalid signature.
+
+`main` cannot be generic or have any parameters,
+it must return `s32` or `void`.
+
+example:
+    proc main() -> bool { return true; }
+
+return an exit code instead: `proc main() -> s32 { return 0; }`."#
+        }
+        ErrorCode::NotFfiSafe => {
+            r#"type cannot be passed to or returned from an external procedure.
+
+external procedures follow the C ABI, types without
+a defined C layout like slices cannot be used in their signatures.
+
+example:
+    proc puts(s: []u8) -> s32;
+
+pass a pointer instead: `proc puts(s: &u8) -> s32;`."#
+        }
+        ErrorCode::ConstDependencyCycle => {
+            r#"constants or type sizes depend on themselves.
+
+constant values and type sizes are computed at compile time,
+a cycle makes them impossible to compute.
+
+example:
+    const A: s32 = B;
+    const B: s32 = A;
+
+break the cycle, use a reference for recursive types: `next: &Node`."#
+        }
+        ErrorCode::NotConstant => {
+            r#"expression cannot be evaluated at compile time.
+
+constants, globals, enum variant values and array lengths
+must be constant expressions.
+
+example:
+    proc value() -> s32 { return 1; }
+    const A: s32 = value();
+
+use a constant expression: `const A: s32 = 1;`."#
+        }
+        ErrorCode::ConstOverflow => {
+            r#"constant expression overflows its type.
+
+constant arithmetic, shifts and casts are checked at compile time,
+the result must fit into the expected type.
+
+example:
+    const A: u8 = 200;
+    const B: u8 = A + 100;
+
+use a larger type: `const B: u16 = 300;`."#
+        }
+        ErrorCode::ConstDivisionByZero => {
+            r#"constant expression divides by zero.
+
+division and remainder by zero are checked at compile time.
+
+example:
+    const A: s32 = 10 / 0;
+
+change the divisor."#
+        }
+        ErrorCode::ConstIndexOutOfBounds => {
+            r#"constant index is out of bounds of a constant array.
+
+indexing is checked at compile time when both
+the array and the index are constants.
+
+example:
+    const ARR: [3]s32 = [1, 2, 3];
+    const A: s32 = ARR[3];
+
+index must be less than the array length."#
+        }
+        ErrorCode::TypeSizeOverflow => {
+            r#"type size does not fit into `u64`.
+
+struct, enum and array sizes are computed at compile time.
+
+example:
+    struct Big {
+        a: [1152921504606846976]u64,
+        b: [1152921504606846976]u64,
+    }
+
+use smaller array lengths."#
+        }
+        ErrorCode::LiteralOutOfRange => {
+            r#"integer literal does not fit into its type.
+
+the range of the expected type is reported with the error.
+
+example:
+    proc main() -> s32 {
+        let x: u8 = 256;
+        return 0;
+    }
+
+use a larger type or a smaller value."#
+        }
+        ErrorCode::TypeMismatch => {
+            r#"expression has a different type than expected.
+
+there are no implicit conversions between types,
+use `as` to perform explicit primitive casts.
+
+example:
+    proc main() -> s32 {
+        let x: s32 = true;
+        return x;
+    }
+
+use a value of the expected type or cast it."#
+        }
+        ErrorCode::MissingElse => {
+            r#"`if` expression is used as a value without an `else` block.
+
+without `else` the `if` evaluates to `void` when the condition is false.
+
+example:
+    proc main() -> s32 {
+        let x: s32 = if true { -> 1; };
+        return x;
+    }
+
+add an `else` block: `if true { -> 1; } else { -> 0; }`."#
+        }
+        ErrorCode::ExpectedValueType => {
+            r#"type cannot be used for values.
+
+`void` and `never` types cannot be used
+for locals, parameters, fields or array elements.
+
+example:
+    struct Empty { value: void }
+
+use a value type like `s32`."#
+        }
+        ErrorCode::UnknownField => {
+            r#"field does not exist on the type.
+
+slices only have `ptr` and `len` fields,
+struct fields are listed in the struct definition.
+
+example:
+    struct Vec2 { x: f32, y: f32 }
+    proc main() -> s32 {
+        let v = Vec2.{ x: 0.0, y: 0.0 };
+        let z = v.z;
+        return 0;
+    }
+
+check the field name in the struct definition."#
+        }
+        ErrorCode::MissingFields => {
+            r#"struct initializer does not initialize all fields.
+
+every field must be given a value.
+
+example:
+    struct Vec2 { x: f32, y: f32 }
+    proc main() -> s32 {
+        let v = Vec2.{ x: 0.0 };
+        return 0;
+    }
+
+initialize the missing fields: `Vec2.{ x: 0.0, y: 0.0 }`."#
+        }
+        ErrorCode::FieldAlreadyInitialized => {
+            r#"struct field is initialized multiple times.
+
+example:
+    struct Vec2 { x: f32, y: f32 }
+    proc main() -> s32 {
+        let v = Vec2.{ x: 0.0, x: 1.0, y: 0.0 };
+        return 0;
+    }
+
+remove the duplicate field initializer."#
+        }
+        ErrorCode::UnknownVariant => {
+            r#"enum variant does not exist.
+
+example:
+    enum Color { Red = 0, Green = 1 }
+    proc main() -> s32 {
+        let c = Color.Blue;
+        return 0;
+    }
+
+check the variant name in the enum definition."#
+        }
+        ErrorCode::CannotInfer => {
+            r#"type cannot be inferred from the surrounding context.
+
+struct initializers, enum variants and empty arrays without a type
+path need an expected type, type parameters must appear in arguments.
+
+example:
+    proc main() -> s32 {
+        let x = .{ a: 0 };
+        return 0;
+    }
+
+add a type annotation or a type path: `let x: Vec2 = .{ .. };`."#
+        }
+        ErrorCode::ArgumentCount => {
+            r#"wrong number of arguments or variant fields.
+
+procedure calls must provide exactly one argument per parameter,
+variadic procedures can take more arguments.
+
+example:
+    proc add(a: s32, b: s32) -> s32 { return a + b; }
+    proc main() -> s32 {
+        return add(1);
+    }
+
+provide all arguments: `add(1, 2)`."#
+        }
+        ErrorCode::InvalidPattern => {
+            r#"match pattern is invalid.
+
+enum variant patterns can only be used when matching on enums,
+variant fields can only be bound to names or ignored with `_`.
+range patterns `0..<10` and `'a'..='z'` require integer or `char` values,
+fields cannot be bound in or-patterns `.A(x) | .B(x)`.
+
+example:
+    enum Shape { Circle(f32) = 0 }
+    proc main() -> s32 {
+        let s = Shape.Circle(1.0);
+        match s {
+            .Circle(1.0) -> {},
+        }
+        return 0;
+    }
+
+bind the field to a name: `.Circle(radius) -> {}`."#
+        }
+        ErrorCode::NonExhaustiveMatch => {
+            r#"match does not cover all possible values.
+
+every enum variant must be covered, or `_` must be used
+as a fallback arm for the remaining values.
+
+example:
+    enum Color { Red = 0, Green = 1 }
+    proc main() -> s32 {
+        let c = Color.Red;
+        return match c {
+            .Red -> 0,
+        };
+    }
+
+add the missing arms or a fallback: `_ -> 1,`."#
+        }
+        ErrorCode::InvalidMatchType => {
+            r#"value of this type cannot be matched on.
+
+only integers, booleans, characters, strings and enums can be matched.
+
+example:
+    struct Vec2 { x: f32, y: f32 }
+    proc main() -> s32 {
+        let v = Vec2.{ x: 0.0, y: 0.0 };
+        match v { _ -> {}, }
+        return 0;
+    }
+
+match on a field instead."#
+        }
+        ErrorCode::InvalidOperator => {
+            r#"operator cannot be applied to values of this type.
+
+for example arithmetic operators require numbers,
+and logical operators require booleans.
+
+example:
+    proc main() -> s32 {
+        let x = true + false;
+        return 0;
+    }
+
+use an operator supported by the operand type."#
+        }
+        ErrorCode::CannotIndex => {
+            r#"value cannot be indexed or sliced.
+
+only arrays and slices, or references to them support `[]`.
+
+example:
+    proc main() -> s32 {
+        let x: s32 = 10;
+        return x[0];
+    }
+
+index into an array or a slice."#
+        }
+        ErrorCode::CannotDereference => {
+            r#"value is not a reference and cannot be dereferenced.
+
+example:
+    proc main() -> s32 {
+        let x: s32 = 10;
+        return *x;
+    }
+
+remove the `*` or dereference a reference value."#
+        }
+        ErrorCode::NotCallable => {
+            r#"value is not a procedure and cannot be called.
+
+example:
+    proc main() -> s32 {
+        let x: s32 = 10;
+        return x();
+    }
+
+call a procedure or a procedure pointer."#
+        }
+        ErrorCode::GenericProcedureValue => {
+            r#"generic procedure is used as a value.
+
+generic procedures are instantiated at the call site,
+they cannot be stored or passed as procedure pointers.
+
+example:
+    proc id[T](x: T) -> T { return x; }
+    proc main() -> s32 {
+        let f = id;
+        return 0;
+    }
+
+call the procedure directly: `id(10)`."#
+        }
+        ErrorCode::InvalidVariadicArgument => {
+            r#"value cannot be passed as a variadic argument.
+
+variadic arguments follow the C ABI, `void`
+and types without a defined C layout cannot be passed.
+
+example:
+    proc printf(fmt: &u8, ..) -> s32;
+    proc nothing() { }
+    proc main() -> s32 {
+        printf(c"%d", nothing());
+        return 0;
+    }
+
+pass a value with a C compatible type."#
+        }
+        ErrorCode::InvalidCast => {
+            r#"cast between these types is not allowed.
+
+`as` only performs primitive casts between numbers,
+booleans, characters, enums and pointers.
+
+example:
+    struct Vec2 { x: f32, y: f32 }
+    proc main() -> s32 {
+        let v = Vec2.{ x: 0.0, y: 0.0 };
+        return v as s32;
+    }
+
+cast a primitive value instead: `v.x as s32`."#
+        }
+        ErrorCode::SizeofTypeParameter => {
+            r#"`sizeof` is used on a type parameter.
+
+size of a type parameter is not known until the procedure is instantiated.
+
+example:
+    proc size[T]() -> u64 { return sizeof(T); }
+
+use `sizeof` on a concrete type."#
+        }
+        ErrorCode::InvalidFormat => {
+            r#"`#print` or `#format` directive is invalid.
+
+first argument must be a string literal with `{}` placeholders,
+each placeholder takes one of the remaining arguments.
+
+example:
+    proc main() -> s32 {
+        #print("{} {}", 1);
+        return 0;
+    }
+
+pass one argument per placeholder."#
+        }
+        ErrorCode::InvalidReference => {
+            r#"reference cannot be taken to this expression.
+
+constants don't have a memory address, temporary values only
+allow immutable `&`, slice fields cannot be referenced.
+
+example:
+    const A: s32 = 10;
+    proc main() -> s32 {
+        let r = &A;
+        return 0;
+    }
+
+use a `global` which has a memory address."#
+        }
+        ErrorCode::MutableAccessToImmutable => {
+            r#"mutable access is requested through an immutable value.
+
+`&mut`, mutable slices and `&mut self` methods require
+a mutable variable or a mutable reference.
+
+example:
+    proc main() -> s32 {
+        let x: s32 = 10;
+        let r = &mut x;
+        return 0;
+    }
+
+declare the variable with `mut`: `mut x: s32 = 10;`."#
+        }
+        ErrorCode::InvalidAssignment => {
+            r#"expression cannot be assigned to.
+
+constants, temporary values and slice fields are not assignable.
+
+example:
+    const A: s32 = 10;
+    proc main() -> s32 {
+        A = 20;
+        return 0;
+    }
+
+assign to a mutable variable or a `global mut`."#
+        }
+        ErrorCode::AssignToImmutable => {
+            r#"value is assigned through an immutable binding.
+
+variables declared with `let`, immutable references and
+immutable slices cannot be used to modify values.
+
+example:
+    proc main() -> s32 {
+        let x: s32 = 10;
+        x = 20;
+        return x;
+    }
+
+declare the variable with `mut`: `mut x: s32 = 10;`."#
+        }
+        ErrorCode::DanglingReference => {
+            r#"reference to a local variable outlives the variable.
+
+locals are destroyed at the end of their block,
+references to them cannot escape that block.
+
+example:
+    proc main() -> s32 {
+        mut r: &s32 = &0;
+        {
+            let x: s32 = 10;
+            r = &x;
+        }
+        return *r;
+    }
+
+declare the variable in the same or an outer block."#
+        }
+        ErrorCode::ControlFlowOutsideLoop => {
+            r#"`break` or `continue` is used outside of a loop.
+
+example:
+    proc main() -> s32 {
+        break;
+        return 0;
+    }
+
+move the statement into a `for` loop."#
+        }
+        ErrorCode::ControlFlowInDefer => {
+            r#"control flow leaves a `defer` block.
+
+`defer` blocks cannot `return`, `break` or `continue` out of
+the block, and `defer` statements cannot be nested.
+
+example:
+    proc main() -> s32 {
+        defer { return 1; }
+        return 0;
+    }
+
+remove the control flow statement from the `defer` block."#
+        }
+        ErrorCode::GenericInstanceLimit => {
+            r#"too many generic procedure instances were created,
+or an instance type is nested too deeply.
+
+this usually means a generic procedure instantiates
+itself with a new, growing type on each call.
+
+example:
+    proc nest[T](x: T) { nest(&x); }
+
+break the recursion or make it non generic."#
+        }
+        ErrorCode::Internal => {
+            r#"internal compiler error.
+
+this is a bug in the compiler, please report it
+together with the source code that caused it."#
+        }
+        ErrorCode::DuplicateVariantValue => {
+            r#"multiple enum variants have the same value.
+
+variant values are compared by `match` and casts,
+variants with the same value could not be told apart.
+
+example:
+    enum Color { Red = 0, Green = 1, Blue = 1 }
+
+give each variant a unique value."#
+        }
+        ErrorCode::SizeofNoValues => {
+            r#"`sizeof` is used on a type without values.
+
+`void` and `never` types have no values to store,
+their size is meaningless.
+
+example:
+    const SIZE: usize = sizeof(void);
+
+use `sizeof` on a type that can be stored."#
+        }
+        ErrorCode::ReservedName => {
+            r#"name is reserved by the language.
+
+`self` names the method receiver, `super` and `package` are reserved for paths,
+they cannot be used as item names, import aliases or path segments.
+
+example:
+    proc super() {}
+
+choose a different name."#
+        }
+    }
+}
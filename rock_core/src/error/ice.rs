@@ -0,0 +1,119 @@
+use super::{
+    Diagnostic, DiagnosticCode, DiagnosticContext, DiagnosticKind, ErrorCode, ErrorComp,
+    SourceRange,
+};
+use std::panic::{self, AssertUnwindSafe, Location, PanicHookInfo};
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::{Mutex, Once};
+
+/// internal compiler error, a bug in the compiler and not in the compiled program
+/// converted to `ErrorComp` at the driver boundary, instead of aborting the process
+pub struct Ice {
+    message: String,
+    location: String,
+    source: Option<SourceRange>,
+}
+
+impl Ice {
+    #[track_caller]
+    pub fn new(message: impl Into<String>) -> Ice {
+        Ice {
+            message: message.into(),
+            location: Location::caller().to_string(),
+            source: None,
+        }
+    }
+
+    /// program source that was being compiled when the error occurred
+    pub fn with_source(mut self, source: SourceRange) -> Ice {
+        self.source = Some(source);
+        self
+    }
+
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+    pub fn location(&self) -> &str {
+        &self.location
+    }
+
+    pub fn into_error(self) -> ErrorComp {
+        let message = format!(
+            "internal compiler error: {}\ncompiler location: {}",
+            self.message, self.location
+        );
+        let kind = match self.source {
+            Some(source) => DiagnosticKind::Context {
+                main: DiagnosticContext::new("".into(), source),
+                info: None,
+            },
+            None => DiagnosticKind::Message,
+        };
+        let code = DiagnosticCode::Error(ErrorCode::Internal);
+        ErrorComp(Box::new(Diagnostic::new_coded(message.into(), kind, code)))
+    }
+}
+
+// panics inside `catch` are recorded here instead of being printed,
+// worker threads spawned inside `catch` record their panic as well
+static CATCH_DEPTH: AtomicU32 = AtomicU32::new(0);
+static CAUGHT: Mutex<Option<Ice>> = Mutex::new(None);
+static HOOK: Once = Once::new();
+
+/// run `work` and turn a panic into `Ice`
+/// state mutated by `work` may be left partially updated after a panic
+pub fn catch<R>(work: impl FnOnce() -> R) -> Result<R, Ice> {
+    HOOK.call_once(install_hook);
+    CATCH_DEPTH.fetch_add(1, Ordering::SeqCst);
+    let result = panic::catch_unwind(AssertUnwindSafe(work));
+    CATCH_DEPTH.fetch_sub(1, Ordering::SeqCst);
+
+    match result {
+        Ok(value) => Ok(value),
+        Err(payload) => {
+            let caught = CAUGHT.lock().map(|mut caught| caught.take());
+            match caught {
+                Ok(Some(ice)) => Err(ice),
+                _ => Err(Ice {
+                    message: payload_message(payload.as_ref()),
+                    location: "unknown".to_string(),
+                    source: None,
+                }),
+            }
+        }
+    }
+}
+
+fn install_hook() {
+    let default_hook = panic::take_hook();
+    panic::set_hook(Box::new(move |info: &PanicHookInfo| {
+        if CATCH_DEPTH.load(Ordering::SeqCst) == 0 {
+            default_hook(info);
+            return;
+        }
+        let location = match info.location() {
+            Some(location) => location.to_string(),
+            None => "unknown".to_string(),
+        };
+        // first panic is kept, scoped threads re-panic in their parent
+        if let Ok(mut caught) = CAUGHT.lock() {
+            if caught.is_none() {
+                *caught = Some(Ice {
+                    message: payload_message(info.payload()),
+                    location,
+                    source: None,
+                });
+            }
+        }
+    }));
+}
+
+fn payload_message(payload: &(dyn std::any::Any + Send)) -> String {
+    if let Some(message) = payload.downcast_ref::<&str>() {
+        message.to_string()
+    } else if let Some(message) = payload.downcast_ref::<String>() {
+        message.clone()
+    } else {
+        "panic without a message".to_string()
+    }
+}
@@ -0,0 +1,888 @@
+mod explain;
+pub mod ice;
+
+use crate::session::ModuleID;
+use crate::text::{TextOffset, TextRange};
+
+pub enum ResultComp<T> {
+    Ok((T, Vec<WarningComp>)),
+    Err(DiagnosticCollection),
+}
+
+/// value produced even when errors were reported,
+/// callers decide if a partial value can still be used
+pub struct PartialComp<T> {
+    pub value: T,
+    pub diagnostics: DiagnosticCollection,
+}
+
+pub struct DiagnosticCollection {
+    errors: Vec<ErrorComp>,
+    warnings: Vec<WarningComp>,
+}
+
+// diagnostics are boxed to keep `Result<_, ErrorComp>` small
+pub struct ErrorComp(Box<Diagnostic>);
+pub struct WarningComp(Box<Diagnostic>);
+pub struct Info;
+
+pub struct Diagnostic {
+    message: StringOrStr,
+    kind: DiagnosticKind,
+    code: Option<DiagnosticCode>,
+    fixes: Vec<DiagnosticFix>,
+}
+
+pub enum DiagnosticKind {
+    Message,
+    Context {
+        main: DiagnosticContext,
+        info: Option<DiagnosticContext>,
+    },
+    ContextVec {
+        main: DiagnosticContext,
+        info_vec: Vec<DiagnosticContext>,
+    },
+}
+
+pub struct DiagnosticContext {
+    message: StringOrStr,
+    source: SourceRange,
+}
+
+/// suggested fix, applied by replacing each edit range with its text
+pub struct DiagnosticFix {
+    message: StringOrStr,
+    edits: Vec<DiagnosticEdit>,
+}
+
+pub struct DiagnosticEdit {
+    source: SourceRange,
+    text: String,
+}
+
+#[derive(Copy, Clone)]
+pub enum DiagnosticSeverity {
+    Info,
+    Error,
+    Warning,
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, Hash)]
+pub struct SourceRange {
+    range: TextRange,
+    module_id: ModuleID,
+}
+
+#[derive(Copy, Clone, PartialEq)]
+pub enum DiagnosticCode {
+    Error(ErrorCode),
+    Warning(WarningCode),
+}
+
+#[derive(Copy, Clone, PartialEq)]
+pub enum ErrorCode {
+    SyntaxError,
+    UnknownSymbol,
+    UnterminatedComment,
+    UnterminatedString,
+    InvalidCharLiteral,
+    InvalidEscape,
+    CStringNullByte,
+    InvalidNumberLiteral,
+    NameNotFound,
+    DuplicateDefinition,
+    PrivateItem,
+    ImportNotDefinedHere,
+    PackageNotFound,
+    ModuleNotFound,
+    SelfImport,
+    AlreadyImported,
+    UnexpectedPathSegment,
+    ExpectedType,
+    ExpectedValue,
+    UnknownAttribute,
+    InvalidAttributeArgument,
+    AttributeNotApplicable,
+    UnstableGenerics,
+    GenericNotAllowed,
+    InvalidVariadic,
+    InvalidImpl,
+    InvalidSelfParam,
+    InvalidTestProc,
+    InvalidMainProc,
+    NotFfiSafe,
+    ConstDependencyCycle,
+    NotConstant,
+    ConstOverflow,
+    ConstDivisionByZero,
+    ConstIndexOutOfBounds,
+    TypeSizeOverflow,
+    LiteralOutOfRange,
+    TypeMismatch,
+    MissingElse,
+    ExpectedValueType,
+    UnknownField,
+    MissingFields,
+    FieldAlreadyInitialized,
+    UnknownVariant,
+    CannotInfer,
+    ArgumentCount,
+    InvalidPattern,
+    NonExhaustiveMatch,
+    InvalidMatchType,
+    InvalidOperator,
+    CannotIndex,
+    CannotDereference,
+    NotCallable,
+    GenericProcedureValue,
+    InvalidVariadicArgument,
+    InvalidCast,
+    SizeofTypeParameter,
+    InvalidFormat,
+    InvalidReference,
+    MutableAccessToImmutable,
+    InvalidAssignment,
+    AssignToImmutable,
+    DanglingReference,
+    ControlFlowOutsideLoop,
+    ControlFlowInDefer,
+    GenericInstanceLimit,
+    Internal,
+    DuplicateVariantValue,
+    SizeofNoValues,
+    ReservedName,
+}
+
+#[derive(Copy, Clone, PartialEq)]
+pub enum WarningCode {
+    UnknownLint,
+    DuplicateAttribute,
+    RedundantAlias,
+    RedundantCast,
+    UnreachableCode,
+    UnreachablePattern,
+    UnusedVariable,
+    UnusedItem,
+    SliceOutOfBounds,
+    ImportCycle,
+    UnknownAttribute,
+    Deprecated,
+}
+
+#[derive(Copy, Clone, PartialEq)]
+pub enum LintLevel {
+    Allow,
+    Warn,
+    Deny,
+}
+
+#[derive(Clone)]
+pub struct LintConfig {
+    levels: [LintLevel; WarningCode::ALL.len()],
+}
+
+pub enum StringOrStr {
+    String(String),
+    Str(&'static str),
+}
+
+impl<T> ResultComp<T> {
+    pub fn new(value: T, diagnostics: DiagnosticCollection) -> ResultComp<T> {
+        if diagnostics.errors.is_empty() {
+            ResultComp::Ok((value, diagnostics.warnings))
+        } else {
+            ResultComp::Err(diagnostics)
+        }
+    }
+
+    pub fn from_error(result: Result<T, ErrorComp>) -> ResultComp<T> {
+        match result {
+            Ok(value) => ResultComp::Ok((value, vec![])),
+            Err(error) => ResultComp::Err(DiagnosticCollection::new().join_errors(vec![error])),
+        }
+    }
+
+    pub fn from_errors(result: Result<T, Vec<ErrorComp>>) -> ResultComp<T> {
+        match result {
+            Ok(value) => ResultComp::Ok((value, vec![])),
+            Err(errors) => ResultComp::Err(DiagnosticCollection::new().join_errors(errors)),
+        }
+    }
+
+    pub fn is_ok(&self) -> bool {
+        matches!(self, ResultComp::Ok(..))
+    }
+
+    pub fn is_err(&self) -> bool {
+        matches!(self, ResultComp::Err(..))
+    }
+
+    pub fn into_result(
+        self,
+        mut warnings_prev: Vec<WarningComp>,
+    ) -> Result<(T, Vec<WarningComp>), DiagnosticCollection> {
+        match self {
+            ResultComp::Ok((value, warnings)) => {
+                if warnings_prev.is_empty() {
+                    Ok((value, warnings))
+                } else {
+                    warnings_prev.extend(warnings);
+                    Ok((value, warnings_prev))
+                }
+            }
+            ResultComp::Err(mut diagnostics) => {
+                if warnings_prev.is_empty() {
+                    Err(diagnostics)
+                } else {
+                    warnings_prev.extend(diagnostics.warnings);
+                    diagnostics.warnings = warnings_prev;
+                    Err(diagnostics)
+                }
+            }
+        }
+    }
+}
+
+impl<T> PartialComp<T> {
+    pub fn new(value: T, diagnostics: DiagnosticCollection) -> PartialComp<T> {
+        PartialComp { value, diagnostics }
+    }
+
+    pub fn has_errors(&self) -> bool {
+        self.diagnostics.has_errors()
+    }
+
+    /// partial value is discarded when any errors were reported
+    pub fn into_result(
+        self,
+        warnings_prev: Vec<WarningComp>,
+    ) -> Result<(T, Vec<WarningComp>), DiagnosticCollection> {
+        ResultComp::new(self.value, self.diagnostics).into_result(warnings_prev)
+    }
+}
+
+impl DiagnosticCollection {
+    pub fn new() -> DiagnosticCollection {
+        DiagnosticCollection {
+            errors: Vec::new(),
+            warnings: Vec::new(),
+        }
+    }
+
+    pub fn from_result(
+        result: Result<Vec<WarningComp>, DiagnosticCollection>,
+    ) -> DiagnosticCollection {
+        match result {
+            Ok(warnings) => DiagnosticCollection::new().join_warnings(warnings),
+            Err(diagnostics) => diagnostics,
+        }
+    }
+
+    pub fn has_errors(&self) -> bool {
+        !self.errors.is_empty()
+    }
+    pub fn errors(&self) -> &[ErrorComp] {
+        &self.errors
+    }
+    pub fn last_error_mut(&mut self) -> Option<&mut ErrorComp> {
+        self.errors.last_mut()
+    }
+    pub fn warnings(&self) -> &[WarningComp] {
+        &self.warnings
+    }
+    pub fn warnings_moveout(self) -> Vec<WarningComp> {
+        self.warnings
+    }
+
+    pub fn error(&mut self, error: ErrorComp) {
+        self.errors.push(error);
+    }
+    pub fn warning(&mut self, warning: WarningComp) {
+        self.warnings.push(warning);
+    }
+
+    #[must_use]
+    pub fn join_errors(mut self, errors: Vec<ErrorComp>) -> DiagnosticCollection {
+        if self.errors.is_empty() {
+            self.errors = errors;
+        } else {
+            self.errors.extend(errors);
+        }
+        self
+    }
+    #[must_use]
+    pub fn join_warnings(mut self, warnings: Vec<WarningComp>) -> DiagnosticCollection {
+        if self.warnings.is_empty() {
+            self.warnings = warnings
+        } else {
+            self.warnings.extend(warnings);
+        }
+        self
+    }
+    #[must_use]
+    pub fn join_collection(mut self, other: DiagnosticCollection) -> DiagnosticCollection {
+        self.errors.extend(other.errors);
+        self.warnings.extend(other.warnings);
+        self
+    }
+}
+
+impl ErrorComp {
+    pub fn diagnostic(&self) -> &Diagnostic {
+        &self.0
+    }
+
+    pub fn message(msg: impl Into<StringOrStr>) -> ErrorComp {
+        ErrorComp(Box::new(Diagnostic::new(
+            msg.into(),
+            DiagnosticKind::Message,
+        )))
+    }
+
+    pub fn new(
+        code: ErrorCode,
+        msg: impl Into<StringOrStr>,
+        src: SourceRange,
+        info: Option<DiagnosticContext>,
+    ) -> ErrorComp {
+        ErrorComp(Box::new(Diagnostic::new_coded(
+            msg.into(),
+            DiagnosticKind::Context {
+                main: DiagnosticContext::new("".into(), src),
+                info,
+            },
+            DiagnosticCode::Error(code),
+        )))
+    }
+
+    pub fn new_detailed(
+        code: ErrorCode,
+        msg: impl Into<StringOrStr>,
+        ctx_msg: impl Into<StringOrStr>,
+        src: SourceRange,
+        info: Option<DiagnosticContext>,
+    ) -> ErrorComp {
+        ErrorComp(Box::new(Diagnostic::new_coded(
+            msg.into(),
+            DiagnosticKind::Context {
+                main: DiagnosticContext::new(ctx_msg.into(), src),
+                info,
+            },
+            DiagnosticCode::Error(code),
+        )))
+    }
+
+    pub fn new_detailed_info_vec(
+        code: ErrorCode,
+        msg: impl Into<StringOrStr>,
+        ctx_msg: impl Into<StringOrStr>,
+        src: SourceRange,
+        info_vec: Vec<DiagnosticContext>,
+    ) -> ErrorComp {
+        ErrorComp(Box::new(Diagnostic::new_coded(
+            msg.into(),
+            DiagnosticKind::ContextVec {
+                main: DiagnosticContext::new(ctx_msg.into(), src),
+                info_vec,
+            },
+            DiagnosticCode::Error(code),
+        )))
+    }
+
+    pub fn with_fix(mut self, fix: DiagnosticFix) -> ErrorComp {
+        self.add_fix(fix);
+        self
+    }
+
+    pub fn add_fix(&mut self, fix: DiagnosticFix) {
+        self.0.fixes.push(fix);
+    }
+}
+
+impl WarningComp {
+    pub fn diagnostic(&self) -> &Diagnostic {
+        &self.0
+    }
+
+    pub fn message(msg: impl Into<StringOrStr>) -> WarningComp {
+        WarningComp(Box::new(Diagnostic::new(
+            msg.into(),
+            DiagnosticKind::Message,
+        )))
+    }
+
+    pub fn new(
+        code: WarningCode,
+        msg: impl Into<StringOrStr>,
+        src: SourceRange,
+        info: Option<DiagnosticContext>,
+    ) -> WarningComp {
+        WarningComp(Box::new(Diagnostic::new_coded(
+            msg.into(),
+            DiagnosticKind::Context {
+                main: DiagnosticContext::new("".into(), src),
+                info,
+            },
+            DiagnosticCode::Warning(code),
+        )))
+    }
+
+    pub fn new_detailed(
+        code: WarningCode,
+        msg: impl Into<StringOrStr>,
+        ctx_msg: impl Into<StringOrStr>,
+        src: SourceRange,
+        info: Option<DiagnosticContext>,
+    ) -> WarningComp {
+        WarningComp(Box::new(Diagnostic::new_coded(
+            msg.into(),
+            DiagnosticKind::Context {
+                main: DiagnosticContext::new(ctx_msg.into(), src),
+                info,
+            },
+            DiagnosticCode::Warning(code),
+        )))
+    }
+
+    pub fn new_detailed_info_vec(
+        code: WarningCode,
+        msg: impl Into<StringOrStr>,
+        ctx_msg: impl Into<StringOrStr>,
+        src: SourceRange,
+        info_vec: Vec<DiagnosticContext>,
+    ) -> WarningComp {
+        WarningComp(Box::new(Diagnostic::new_coded(
+            msg.into(),
+            DiagnosticKind::ContextVec {
+                main: DiagnosticContext::new(ctx_msg.into(), src),
+                info_vec,
+            },
+            DiagnosticCode::Warning(code),
+        )))
+    }
+
+    pub fn with_fix(mut self, fix: DiagnosticFix) -> WarningComp {
+        self.0.fixes.push(fix);
+        self
+    }
+
+    pub fn code(&self) -> Option<WarningCode> {
+        match self.0.code {
+            Some(DiagnosticCode::Warning(code)) => Some(code),
+            _ => None,
+        }
+    }
+}
+
+impl Info {
+    pub fn new(msg: impl Into<StringOrStr>, source: SourceRange) -> Option<DiagnosticContext> {
+        Some(DiagnosticContext::new(msg.into(), source))
+    }
+    pub fn new_value(msg: impl Into<StringOrStr>, source: SourceRange) -> DiagnosticContext {
+        DiagnosticContext::new(msg.into(), source)
+    }
+}
+
+impl Diagnostic {
+    fn new(message: StringOrStr, kind: DiagnosticKind) -> Diagnostic {
+        Diagnostic {
+            message,
+            kind,
+            code: None,
+            fixes: Vec::new(),
+        }
+    }
+    fn new_coded(message: StringOrStr, kind: DiagnosticKind, code: DiagnosticCode) -> Diagnostic {
+        Diagnostic {
+            message,
+            kind,
+            code: Some(code),
+            fixes: Vec::new(),
+        }
+    }
+    pub fn code(&self) -> Option<DiagnosticCode> {
+        self.code
+    }
+    pub fn message(&self) -> &StringOrStr {
+        &self.message
+    }
+    pub fn kind(&self) -> &DiagnosticKind {
+        &self.kind
+    }
+    pub fn fixes(&self) -> &[DiagnosticFix] {
+        &self.fixes
+    }
+    pub fn main_source(&self) -> Option<SourceRange> {
+        match &self.kind {
+            DiagnosticKind::Message => None,
+            DiagnosticKind::Context { main, .. } => Some(main.source),
+            DiagnosticKind::ContextVec { main, .. } => Some(main.source),
+        }
+    }
+}
+
+impl DiagnosticContext {
+    fn new(message: StringOrStr, source: SourceRange) -> DiagnosticContext {
+        DiagnosticContext { message, source }
+    }
+    pub fn message(&self) -> &str {
+        self.message.as_str()
+    }
+    pub fn source(&self) -> SourceRange {
+        self.source
+    }
+}
+
+impl DiagnosticFix {
+    pub fn new(message: impl Into<StringOrStr>, edits: Vec<DiagnosticEdit>) -> DiagnosticFix {
+        DiagnosticFix {
+            message: message.into(),
+            edits,
+        }
+    }
+    pub fn message(&self) -> &str {
+        self.message.as_str()
+    }
+    pub fn edits(&self) -> &[DiagnosticEdit] {
+        &self.edits
+    }
+}
+
+impl DiagnosticEdit {
+    pub fn replace(source: SourceRange, text: impl Into<String>) -> DiagnosticEdit {
+        DiagnosticEdit {
+            source,
+            text: text.into(),
+        }
+    }
+    pub fn insert(
+        module_id: ModuleID,
+        offset: TextOffset,
+        text: impl Into<String>,
+    ) -> DiagnosticEdit {
+        let range = TextRange::empty_at(offset);
+        DiagnosticEdit::replace(SourceRange::new(module_id, range), text)
+    }
+    pub fn source(&self) -> SourceRange {
+        self.source
+    }
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+}
+
+impl SourceRange {
+    pub fn new(module_id: ModuleID, range: TextRange) -> SourceRange {
+        SourceRange { range, module_id }
+    }
+    pub fn range(&self) -> TextRange {
+        self.range
+    }
+    pub fn module_id(&self) -> ModuleID {
+        self.module_id
+    }
+}
+
+impl DiagnosticCode {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            DiagnosticCode::Error(code) => code.as_str(),
+            DiagnosticCode::Warning(code) => code.as_str(),
+        }
+    }
+}
+
+impl ErrorCode {
+    pub const ALL: [ErrorCode; 70] = [
+        ErrorCode::SyntaxError,
+        ErrorCode::UnknownSymbol,
+        ErrorCode::UnterminatedComment,
+        ErrorCode::UnterminatedString,
+        ErrorCode::InvalidCharLiteral,
+        ErrorCode::InvalidEscape,
+        ErrorCode::CStringNullByte,
+        ErrorCode::InvalidNumberLiteral,
+        ErrorCode::NameNotFound,
+        ErrorCode::DuplicateDefinition,
+        ErrorCode::PrivateItem,
+        ErrorCode::ImportNotDefinedHere,
+        ErrorCode::PackageNotFound,
+        ErrorCode::ModuleNotFound,
+        ErrorCode::SelfImport,
+        ErrorCode::AlreadyImported,
+        ErrorCode::UnexpectedPathSegment,
+        ErrorCode::ExpectedType,
+        ErrorCode::ExpectedValue,
+        ErrorCode::UnknownAttribute,
+        ErrorCode::InvalidAttributeArgument,
+        ErrorCode::AttributeNotApplicable,
+        ErrorCode::UnstableGenerics,
+        ErrorCode::GenericNotAllowed,
+        ErrorCode::InvalidVariadic,
+        ErrorCode::InvalidImpl,
+        ErrorCode::InvalidSelfParam,
+        ErrorCode::InvalidTestProc,
+        ErrorCode::InvalidMainProc,
+        ErrorCode::NotFfiSafe,
+        ErrorCode::ConstDependencyCycle,
+        ErrorCode::NotConstant,
+        ErrorCode::ConstOverflow,
+        ErrorCode::ConstDivisionByZero,
+        ErrorCode::ConstIndexOutOfBounds,
+        ErrorCode::TypeSizeOverflow,
+        ErrorCode::LiteralOutOfRange,
+        ErrorCode::TypeMismatch,
+        ErrorCode::MissingElse,
+        ErrorCode::ExpectedValueType,
+        ErrorCode::UnknownField,
+        ErrorCode::MissingFields,
+        ErrorCode::FieldAlreadyInitialized,
+        ErrorCode::UnknownVariant,
+        ErrorCode::CannotInfer,
+        ErrorCode::ArgumentCount,
+        ErrorCode::InvalidPattern,
+        ErrorCode::NonExhaustiveMatch,
+        ErrorCode::InvalidMatchType,
+        ErrorCode::InvalidOperator,
+        ErrorCode::CannotIndex,
+        ErrorCode::CannotDereference,
+        ErrorCode::NotCallable,
+        ErrorCode::GenericProcedureValue,
+        ErrorCode::InvalidVariadicArgument,
+        ErrorCode::InvalidCast,
+        ErrorCode::SizeofTypeParameter,
+        ErrorCode::InvalidFormat,
+        ErrorCode::InvalidReference,
+        ErrorCode::MutableAccessToImmutable,
+        ErrorCode::InvalidAssignment,
+        ErrorCode::AssignToImmutable,
+        ErrorCode::DanglingReference,
+        ErrorCode::ControlFlowOutsideLoop,
+        ErrorCode::ControlFlowInDefer,
+        ErrorCode::GenericInstanceLimit,
+        ErrorCode::Internal,
+        ErrorCode::DuplicateVariantValue,
+        ErrorCode::SizeofNoValues,
+        ErrorCode::ReservedName,
+    ];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ErrorCode::SyntaxError => "E0001",
+            ErrorCode::UnknownSymbol => "E0002",
+            ErrorCode::UnterminatedComment => "E0003",
+            ErrorCode::UnterminatedString => "E0004",
+            ErrorCode::InvalidCharLiteral => "E0005",
+            ErrorCode::InvalidEscape => "E0006",
+            ErrorCode::CStringNullByte => "E0007",
+            ErrorCode::InvalidNumberLiteral => "E0008",
+            ErrorCode::NameNotFound => "E0009",
+            ErrorCode::DuplicateDefinition => "E0010",
+            ErrorCode::PrivateItem => "E0011",
+            ErrorCode::ImportNotDefinedHere => "E0012",
+            ErrorCode::PackageNotFound => "E0013",
+            ErrorCode::ModuleNotFound => "E0014",
+            ErrorCode::SelfImport => "E0015",
+            ErrorCode::AlreadyImported => "E0016",
+            ErrorCode::UnexpectedPathSegment => "E0017",
+            ErrorCode::ExpectedType => "E0018",
+            ErrorCode::ExpectedValue => "E0019",
+            ErrorCode::UnknownAttribute => "E0020",
+            ErrorCode::InvalidAttributeArgument => "E0021",
+            ErrorCode::AttributeNotApplicable => "E0022",
+            ErrorCode::UnstableGenerics => "E0023",
+            ErrorCode::GenericNotAllowed => "E0024",
+            ErrorCode::InvalidVariadic => "E0025",
+            ErrorCode::InvalidImpl => "E0026",
+            ErrorCode::InvalidSelfParam => "E0027",
+            ErrorCode::InvalidTestProc => "E0028",
+            ErrorCode::InvalidMainProc => "E0029",
+            ErrorCode::NotFfiSafe => "E0030",
+            ErrorCode::ConstDependencyCycle => "E0031",
+            ErrorCode::NotConstant => "E0032",
+            ErrorCode::ConstOverflow => "E0033",
+            ErrorCode::ConstDivisionByZero => "E0034",
+            ErrorCode::ConstIndexOutOfBounds => "E0035",
+            ErrorCode::TypeSizeOverflow => "E0036",
+            ErrorCode::LiteralOutOfRange => "E0037",
+            ErrorCode::TypeMismatch => "E0038",
+            ErrorCode::MissingElse => "E0039",
+            ErrorCode::ExpectedValueType => "E0040",
+            ErrorCode::UnknownField => "E0041",
+            ErrorCode::MissingFields => "E0042",
+            ErrorCode::FieldAlreadyInitialized => "E0043",
+            ErrorCode::UnknownVariant => "E0044",
+            ErrorCode::CannotInfer => "E0045",
+            ErrorCode::ArgumentCount => "E0046",
+            ErrorCode::InvalidPattern => "E0047",
+            ErrorCode::NonExhaustiveMatch => "E0048",
+            ErrorCode::InvalidMatchType => "E0049",
+            ErrorCode::InvalidOperator => "E0050",
+            ErrorCode::CannotIndex => "E0051",
+            ErrorCode::CannotDereference => "E0052",
+            ErrorCode::NotCallable => "E0053",
+            ErrorCode::GenericProcedureValue => "E0054",
+            ErrorCode::InvalidVariadicArgument => "E0055",
+            ErrorCode::InvalidCast => "E0056",
+            ErrorCode::SizeofTypeParameter => "E0057",
+            ErrorCode::InvalidFormat => "E0058",
+            ErrorCode::InvalidReference => "E0059",
+            ErrorCode::MutableAccessToImmutable => "E0060",
+            ErrorCode::InvalidAssignment => "E0061",
+            ErrorCode::AssignToImmutable => "E0062",
+            ErrorCode::DanglingReference => "E0063",
+            ErrorCode::ControlFlowOutsideLoop => "E0064",
+            ErrorCode::ControlFlowInDefer => "E0065",
+            ErrorCode::GenericInstanceLimit => "E0066",
+            ErrorCode::Internal => "E0067",
+            ErrorCode::DuplicateVariantValue => "E0068",
+            ErrorCode::SizeofNoValues => "E0069",
+            ErrorCode::ReservedName => "E0070",
+        }
+    }
+
+    pub fn from_code(string: &str) -> Option<ErrorCode> {
+        ErrorCode::ALL
+            .iter()
+            .copied()
+            .find(|code| code.as_str().eq_ignore_ascii_case(string))
+    }
+
+    /// longer description of the error with examples, used by `rock explain`
+    pub fn explain(self) -> &'static str {
+        explain::explain(self)
+    }
+}
+
+impl WarningCode {
+    pub const ALL: [WarningCode; 12] = [
+        WarningCode::UnknownLint,
+        WarningCode::DuplicateAttribute,
+        WarningCode::RedundantAlias,
+        WarningCode::RedundantCast,
+        WarningCode::UnreachableCode,
+        WarningCode::UnreachablePattern,
+        WarningCode::UnusedVariable,
+        WarningCode::UnusedItem,
+        WarningCode::SliceOutOfBounds,
+        WarningCode::ImportCycle,
+        WarningCode::UnknownAttribute,
+        WarningCode::Deprecated,
+    ];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            WarningCode::UnknownLint => "unknown_lint",
+            WarningCode::DuplicateAttribute => "duplicate_attribute",
+            WarningCode::RedundantAlias => "redundant_alias",
+            WarningCode::RedundantCast => "redundant_cast",
+            WarningCode::UnreachableCode => "unreachable_code",
+            WarningCode::UnreachablePattern => "unreachable_pattern",
+            WarningCode::UnusedVariable => "unused_variable",
+            WarningCode::UnusedItem => "unused_item",
+            WarningCode::SliceOutOfBounds => "slice_out_of_bounds",
+            WarningCode::ImportCycle => "import_cycle",
+            WarningCode::UnknownAttribute => "unknown_attribute",
+            WarningCode::Deprecated => "deprecated",
+        }
+    }
+
+    pub fn from_name(string: &str) -> Option<WarningCode> {
+        WarningCode::ALL
+            .iter()
+            .copied()
+            .find(|code| code.as_str() == string)
+    }
+}
+
+impl From<WarningCode> for u32 {
+    fn from(code: WarningCode) -> u32 {
+        code as u32
+    }
+}
+
+impl LintConfig {
+    pub fn new() -> LintConfig {
+        LintConfig {
+            levels: [LintLevel::Warn; WarningCode::ALL.len()],
+        }
+    }
+
+    pub fn set(&mut self, code: WarningCode, level: LintLevel) {
+        self.levels[code as usize] = level;
+    }
+
+    pub fn set_all(&mut self, level: LintLevel) {
+        self.levels = [level; WarningCode::ALL.len()];
+    }
+
+    pub fn level(&self, code: WarningCode) -> LintLevel {
+        self.levels[code as usize]
+    }
+
+    /// removes allowed warnings and turns denied warnings into errors
+    pub fn apply<T>(&self, result: PartialComp<T>) -> PartialComp<T> {
+        let mut diagnostics = result.diagnostics;
+        let warnings = std::mem::take(&mut diagnostics.warnings);
+        PartialComp::new(result.value, self.apply_warnings(diagnostics, warnings))
+    }
+
+    fn apply_warnings(
+        &self,
+        mut diagnostics: DiagnosticCollection,
+        warnings: Vec<WarningComp>,
+    ) -> DiagnosticCollection {
+        for warning in warnings {
+            let level = match warning.code() {
+                Some(code) => self.level(code),
+                None => LintLevel::Warn,
+            };
+            match level {
+                LintLevel::Allow => {}
+                LintLevel::Warn => diagnostics.warning(warning),
+                LintLevel::Deny => diagnostics.error(ErrorComp(warning.0)),
+            }
+        }
+        diagnostics
+    }
+}
+
+impl Default for LintConfig {
+    fn default() -> LintConfig {
+        LintConfig::new()
+    }
+}
+
+impl StringOrStr {
+    pub fn as_str(&self) -> &str {
+        match self {
+            StringOrStr::Str(string) => string,
+            StringOrStr::String(string) => string,
+        }
+    }
+}
+
+impl From<&'static str> for StringOrStr {
+    fn from(value: &'static str) -> StringOrStr {
+        StringOrStr::Str(value)
+    }
+}
+
+impl From<String> for StringOrStr {
+    fn from(value: String) -> StringOrStr {
+        StringOrStr::String(value)
+    }
+}
+
+#[test]
+fn test_error_code_registry() {
+    for (idx, code) in ErrorCode::ALL.iter().copied().enumerate() {
+        assert!(code as usize == idx);
+        assert_eq!(code.as_str(), format!("E{:04}", idx + 1));
+        assert!(ErrorCode::from_code(code.as_str()) == Some(code));
+        assert!(!code.explain().lines().next().unwrap_or("").is_empty());
+    }
+    assert!(ErrorCode::from_code("e0001") == Some(ErrorCode::SyntaxError));
+    assert!(ErrorCode::from_code("E0000").is_none());
+}
@@ -1,53 +1,29 @@
-use crate::ast::{AssignOp, BinOp};
 use crate::error::ErrorComp;
 use crate::session::ModuleID;
 use crate::syntax;
-use crate::syntax::ast_layer as ast;
+use crate::syntax::syntax_kind::SyntaxKind;
 use crate::syntax::syntax_tree::{Node, NodeID, NodeOrToken, SyntaxTree};
 use crate::text::TextRange;
+use crate::token::{Token, Trivia, T};
+
+const MAX_WIDTH: usize = 100;
+const TAB: &str = "    ";
 
-//@conform to ast_build style:
-// _cst postfix in cst nodes with name conflits,
-// remove _fmt postfix from functions
 //@use session?
 pub fn format(source: &str, module_id: ModuleID) -> Result<String, Vec<ErrorComp>> {
-    return format_experiment(source, module_id);
-
-    let (tree, errors) = syntax::parse(source, module_id, true);
-    if !errors.is_empty() {
-        return Err(errors);
-    }
-
-    let mut fmt = Formatter::new(&tree, source);
-    source_file(&mut fmt, tree.source_file());
-    Ok(fmt.finish())
-}
-
-pub fn format_experiment(source: &str, module_id: ModuleID) -> Result<String, Vec<ErrorComp>> {
     let (tree, errors) = syntax::parse(source, module_id, true);
     if !errors.is_empty() {
         return Err(errors);
     }
 
     let mut fmt = Formatter::new(&tree, source);
-    node_format(&mut fmt, tree.node(NodeID::new(0)));
-    Ok(fmt.finish())
-}
+    fmt.collect(tree.node(NodeID::new(0)));
+    fmt.expand_groups();
 
-fn node_format(fmt: &mut Formatter, node: &Node) {
-    for node_or_token in node.content.iter().copied() {
-        match node_or_token {
-            NodeOrToken::Node(node_id) => {
-                node_format(fmt, fmt.tree.node(node_id));
-            }
-            NodeOrToken::Token(token_id) => {
-                let range = fmt.tree.tokens().token_range(token_id.index());
-                fmt.write_range(range);
-            }
-            NodeOrToken::Trivia(trivia_id) => {
-                let range = fmt.tree.tokens().trivia_range(trivia_id.index());
-                fmt.write_range(range);
-            }
+    loop {
+        let (output, line_widths) = fmt.print();
+        if !fmt.expand_overflow(&line_widths) {
+            return Ok(output);
         }
     }
 }
@@ -121,804 +97,514 @@ fn format_test() {
     }
 }
 
-struct Formatter<'syn> {
-    tree: &'syn SyntaxTree<'syn>,
-    source: &'syn str,
-    tab_depth: u32,
-    buffer: String,
-}
-
-impl<'syn> Formatter<'syn> {
-    fn new(tree: &'syn SyntaxTree<'syn>, source: &'syn str) -> Formatter<'syn> {
-        Formatter {
-            tree,
-            source,
-            tab_depth: 0,
-            buffer: String::with_capacity(source.len()),
-        }
-    }
-
-    fn finish(self) -> String {
-        self.buffer
-    }
-
-    fn write(&mut self, string: &str) {
-        self.buffer.push_str(string);
-    }
-
-    fn write_c(&mut self, c: char) {
-        self.buffer.push(c);
-    }
-
-    fn write_range(&mut self, range: TextRange) {
-        let string = &self.source[range.as_usize()];
-        self.write(string);
-    }
-
-    fn space(&mut self) {
-        self.buffer.push(' ');
-    }
-
-    fn new_line(&mut self) {
-        self.buffer.push('\n');
-    }
-
-    fn tab(&mut self) {
-        self.write("    ");
-    }
-
-    fn tab_depth(&mut self) {
-        for _ in 0..self.tab_depth {
-            self.tab();
-        }
-    }
-
-    fn depth_increment(&mut self) {
-        self.tab_depth += 1;
-    }
-
-    fn depth_decrement(&mut self) {
-        self.tab_depth -= 1;
-    }
-}
-
-fn source_file(fmt: &mut Formatter, source_file: ast::SourceFile) {
-    for item in source_file.items(fmt.tree) {
-        item_fmt(fmt, item);
-    }
-}
-
-fn item_fmt(fmt: &mut Formatter, item: ast::Item) {
-    fmt.new_line();
-    match item {
-        ast::Item::Proc(item) => proc_item(fmt, item),
-        ast::Item::Enum(item) => enum_item(fmt, item),
-        ast::Item::Struct(item) => struct_item(fmt, item),
-        ast::Item::Const(item) => const_item(fmt, item),
-        ast::Item::Global(item) => global_item(fmt, item),
-        ast::Item::Import(item) => import_item(fmt, item),
-    }
-}
+#[test]
+fn test_format_idempotent() {
+    let source = r#"
+// leading comment
+import core/io.{ print, read, };
 
-fn attribute_list(fmt: &mut Formatter, attr_list: ast::AttributeList) {
-    for attr in attr_list.attrs(fmt.tree) {
-        attribute(fmt, attr);
-        fmt.new_line();
-    }
+pub struct Point {
+    x: s32, // line comment after a field
+    y: s32
 }
 
-fn attribute(fmt: &mut Formatter, attr: ast::Attribute) {
-    fmt.write("#[");
-    name_fmt(fmt, attr.name(fmt.tree).unwrap());
-    fmt.write_c(']');
-}
+/* block comment */ pub proc add(a: s32, b: s32,) -> s32 { return a + b; }
 
-fn visibility(fmt: &mut Formatter, vis: ast::Visibility) {
-    if vis.is_pub(fmt.tree) {
-        fmt.write("pub");
-        fmt.space();
-    }
+pub proc long_call(first_argument: s32, second_argument: s32, third_argument: s32) -> s32 {
+    let total = add(first_argument, add(second_argument, add(third_argument, first_argument + second_argument)));
+    // comment between statements
+    return total;
 }
-
-macro_rules! item_attr_vis_fmt {
-    ($fmt:ident, $item:expr) => {
-        if let Some(attr_list) = $item.attr_list($fmt.tree) {
-            attribute_list($fmt, attr_list);
-        }
-        if let Some(vis) = $item.visiblity($fmt.tree) {
-            visibility($fmt, vis);
-        }
+"#;
+    let format_valid = |source: &str| match format(source, ModuleID::dummy()) {
+        Ok(formatted) => formatted,
+        Err(_) => panic!("incorrect source syntax"),
     };
+    let formatted = format_valid(source);
+    let reformatted = format_valid(&formatted);
+    assert!(formatted == reformatted, "not idempotent:\n{reformatted}");
+
+    let max_width = formatted.lines().map(|line| line.chars().count()).max();
+    assert!(max_width.unwrap_or(0) <= MAX_WIDTH);
+    assert!(formatted.contains("// leading comment"));
+    assert!(formatted.contains("// line comment after a field"));
+    assert!(formatted.contains("/* block comment */"));
+    assert!(formatted.contains("// comment between statements"));
+    assert!(formatted.contains("import core/io.{ print, read };"));
+    assert!(formatted.contains("proc add(a: s32, b: s32) -> s32"));
+    assert!(formatted.contains("    y: s32,\n}"));
+    assert!(formatted.contains("let total = add(\n        first_argument,\n"));
+    assert!(formatted.contains("second_argument)),\n    );"));
 }
 
-fn proc_item(fmt: &mut Formatter, item: ast::ProcItem) {
-    item_attr_vis_fmt!(fmt, item);
-    fmt.write("proc");
-    fmt.space();
-    name_fmt(fmt, item.name(fmt.tree).unwrap());
-    param_list(fmt, item.param_list(fmt.tree).unwrap());
-    if let Some(ty) = item.return_ty(fmt.tree) {
-        fmt.space();
-        fmt.write("->");
-        fmt.space();
-        type_fmt(fmt, ty);
-    }
-    if let Some(block) = item.block(fmt.tree) {
-        fmt.space();
-        block_fmt(fmt, block);
-    } else {
-        fmt.write_c(';');
-    }
-    fmt.new_line();
-}
-
-fn param_list(fmt: &mut Formatter, param_list: ast::ParamList) {
-    fmt.write_c('(');
-    let mut first = true;
-    for param in param_list.params(fmt.tree) {
-        if !first {
-            fmt.write_c(',');
-            fmt.space();
-        }
-        first = false;
-        param_fmt(fmt, param);
-    }
-    if param_list.is_variadic(fmt.tree) {
-        if !first {
-            fmt.write_c(',');
-            fmt.space();
-        }
-        fmt.write("..");
-    }
-    fmt.write_c(')');
-}
-
-fn param_fmt(fmt: &mut Formatter, param: ast::Param) {
-    if param.is_mut(fmt.tree) {
-        fmt.write("mut");
-        fmt.space();
-    }
-    name_fmt(fmt, param.name(fmt.tree).unwrap());
-    fmt.write_c(':');
-    fmt.space();
-    type_fmt(fmt, param.ty(fmt.tree).unwrap());
-}
-
-fn enum_item(fmt: &mut Formatter, item: ast::EnumItem) {
-    item_attr_vis_fmt!(fmt, item);
-    fmt.write("enum");
-    fmt.space();
-    name_fmt(fmt, item.name(fmt.tree).unwrap());
-    fmt.space();
-    if let Some(ty) = item.type_basic(fmt.tree) {
-        type_basic(fmt, ty);
-        fmt.space();
-    }
-    variant_list(fmt, item.variant_list(fmt.tree).unwrap());
-    fmt.new_line();
-}
-
-fn variant_list(fmt: &mut Formatter, variant_list: ast::VariantList) {
-    fmt.write_c('{');
-    let mut empty = true;
-    for variant in variant_list.variants(fmt.tree) {
-        if empty {
-            fmt.new_line();
-            empty = false;
-        }
-        fmt.tab();
-        variant_fmt(fmt, variant);
-        fmt.write_c(',');
-        fmt.new_line();
-    }
-    fmt.write_c('}');
-}
-
-fn variant_fmt(fmt: &mut Formatter, variant: ast::Variant) {
-    name_fmt(fmt, variant.name(fmt.tree).unwrap());
-    if let Some(expr) = variant.value(fmt.tree) {
-        fmt.space();
-        fmt.write_c('=');
-        fmt.space();
-        expr_fmt(fmt, expr);
-    }
-}
-
-fn struct_item(fmt: &mut Formatter, item: ast::StructItem) {
-    item_attr_vis_fmt!(fmt, item);
-    fmt.write("struct");
-    fmt.space();
-    name_fmt(fmt, item.name(fmt.tree).unwrap());
-    fmt.space();
-    field_list(fmt, item.field_list(fmt.tree).unwrap());
-    fmt.new_line();
-}
-
-fn field_list(fmt: &mut Formatter, field_list: ast::FieldList) {
-    fmt.write_c('{');
-    let mut empty = true;
-    for field in field_list.fields(fmt.tree) {
-        if empty {
-            fmt.new_line();
-            empty = false;
-        }
-        fmt.tab();
-        field_fmt(fmt, field);
-        fmt.write_c(',');
-        fmt.new_line();
-    }
-    fmt.write_c('}');
-}
-
-fn field_fmt(fmt: &mut Formatter, field: ast::Field) {
-    name_fmt(fmt, field.name(fmt.tree).unwrap());
-    fmt.write_c(':');
-    fmt.space();
-    type_fmt(fmt, field.ty(fmt.tree).unwrap());
-}
-
-fn const_item(fmt: &mut Formatter, item: ast::ConstItem) {
-    item_attr_vis_fmt!(fmt, item);
-    fmt.write("const");
-    fmt.space();
-    name_fmt(fmt, item.name(fmt.tree).unwrap());
-    fmt.write_c(':');
-    fmt.space();
-    type_fmt(fmt, item.ty(fmt.tree).unwrap());
-    fmt.space();
-    fmt.write_c('=');
-    fmt.space();
-    expr_fmt(fmt, item.value(fmt.tree).unwrap());
-    fmt.write_c(';');
-    fmt.new_line();
-}
-
-fn global_item(fmt: &mut Formatter, item: ast::GlobalItem) {
-    item_attr_vis_fmt!(fmt, item);
-    fmt.write("global");
-    fmt.space();
-    if item.is_mut(fmt.tree) {
-        fmt.write("mut");
-        fmt.space();
-    }
-    name_fmt(fmt, item.name(fmt.tree).unwrap());
-    fmt.write_c(':');
-    fmt.space();
-    type_fmt(fmt, item.ty(fmt.tree).unwrap());
-    fmt.space();
-    fmt.write_c('=');
-    fmt.space();
-    expr_fmt(fmt, item.value(fmt.tree).unwrap());
-    fmt.write_c(';');
-    fmt.new_line();
-}
-
-fn import_item(fmt: &mut Formatter, item: ast::ImportItem) {
-    //@vis will still be added here
-    item_attr_vis_fmt!(fmt, item);
-    fmt.write("import");
-    fmt.space();
-    if let Some(name) = item.package(fmt.tree) {
-        name_fmt(fmt, name);
-        fmt.write_c(':');
-    }
-    import_path(fmt, item.import_path(fmt.tree).unwrap());
-    if let Some(name_alias) = item.name_alias(fmt.tree) {
-        name_alias_fmt(fmt, name_alias);
-    }
-    if let Some(symbol_list) = item.import_symbol_list(fmt.tree) {
-        fmt.write_c('.');
-        import_symbol_list(fmt, symbol_list);
-    } else {
-        fmt.write_c(';');
-    }
-    fmt.new_line();
-}
-
-fn import_path(fmt: &mut Formatter, import_path: ast::ImportPath) {
-    let mut first = true;
-    for name in import_path.names(fmt.tree) {
-        if !first {
-            fmt.write_c('/');
-        }
-        first = false;
-        name_fmt(fmt, name);
-    }
+struct Formatter<'syn> {
+    tree: &'syn SyntaxTree<'syn>,
+    source: &'syn str,
+    elems: Vec<Elem>,
+    groups: Vec<Group>,
+    group_stack: Vec<u32>,
+    newlines: u32,
+    line_start: bool,
+}
+
+#[derive(Copy, Clone)]
+struct Elem {
+    kind: ElemKind,
+    range: TextRange,
+    parent: SyntaxKind,
+    group: u32,
+    newlines: u32,
+    line_start: bool,
+}
+
+#[derive(Copy, Clone, PartialEq)]
+enum ElemKind {
+    Token(Token),
+    Open(u32),
+    Close(u32),
+    LineComment,
+    BlockComment,
+}
+
+// group is a delimited `() [] {}` region,
+// expanded groups place each element on a separate line
+struct Group {
+    kind: SyntaxKind,
+    open: usize,
+    close: usize,
+    line: u32,
+    indent: u32,
+    brace: bool,
+    expand: bool,
 }
 
-fn import_symbol_list(fmt: &mut Formatter, import_symbol_list: ast::ImportSymbolList) {
-    fmt.write_c('{');
-    let mut first = true;
-    for import_symbol in import_symbol_list.import_symbols(fmt.tree) {
-        if !first {
-            fmt.write_c(',');
-            fmt.space();
+impl<'syn> Formatter<'syn> {
+    fn new(tree: &'syn SyntaxTree<'syn>, source: &'syn str) -> Formatter<'syn> {
+        let root = Group {
+            kind: SyntaxKind::SOURCE_FILE,
+            open: usize::MAX,
+            close: usize::MAX,
+            line: 0,
+            indent: 0,
+            brace: false,
+            expand: true,
+        };
+        Formatter {
+            tree,
+            source,
+            elems: Vec::with_capacity(source.len() / 4),
+            groups: vec![root],
+            group_stack: vec![0],
+            newlines: 0,
+            line_start: false,
+        }
+    }
+
+    fn collect(&mut self, node: &Node) {
+        let element_list = element_list(node.kind);
+
+        for node_or_token in node.content.iter().copied() {
+            match node_or_token {
+                NodeOrToken::Node(node_id) => {
+                    let child = self.tree.node(node_id);
+                    if element_list {
+                        self.line_start = true;
+                    }
+                    self.collect(child);
+                    if child.kind == SyntaxKind::ATTRIBUTE_LIST {
+                        self.line_start = true;
+                    }
+                }
+                NodeOrToken::Token(token_id) => {
+                    let token = self.tree.token(token_id);
+                    let range = self.tree.token_range(token_id);
+                    if element_list && !is_delimiter(token) {
+                        self.line_start = true;
+                    }
+                    self.push_token(token, range, node.kind);
+                }
+                NodeOrToken::Trivia(trivia_id) => {
+                    let tokens = self.tree.tokens();
+                    let trivia = tokens.trivia(trivia_id.index());
+                    let range = tokens.trivia_range(trivia_id.index());
+                    let newline_count = self.source[range.as_usize()].matches('\n').count() as u32;
+
+                    let kind = match trivia {
+                        Trivia::Whitespace => {
+                            self.newlines += newline_count;
+                            continue;
+                        }
+                        Trivia::LineComment | Trivia::DocComment => ElemKind::LineComment,
+                        Trivia::BlockComment => ElemKind::BlockComment,
+                        Trivia::Error => unreachable!("format with lexer errors"),
+                    };
+                    self.push_elem(kind, range, node.kind);
+                    self.newlines = if kind == ElemKind::LineComment {
+                        newline_count
+                    } else {
+                        0
+                    };
+                }
+            }
         }
-        first = false;
-        import_symbol_fmt(fmt, import_symbol);
-    }
-    fmt.write_c('}');
-}
-
-fn import_symbol_fmt(fmt: &mut Formatter, import_symbol: ast::ImportSymbol) {
-    name_fmt(fmt, import_symbol.name(fmt.tree).unwrap());
-    if let Some(name_alias) = import_symbol.name_alias(fmt.tree) {
-        name_alias_fmt(fmt, name_alias);
     }
-}
-
-fn name_alias_fmt(fmt: &mut Formatter, name_alias: ast::NameAlias) {
-    fmt.space();
-    fmt.write("as");
-    fmt.space();
-    name_fmt(fmt, name_alias.name(fmt.tree).unwrap());
-}
-
-fn name_fmt(fmt: &mut Formatter, name: ast::Name) {
-    fmt.write_range(name.range(fmt.tree));
-}
 
-fn path_fmt(fmt: &mut Formatter, path: ast::Path) {
-    let mut first = true;
-    for name in path.names(fmt.tree) {
-        if !first {
-            fmt.write_c('.');
+    fn push_token(&mut self, token: Token, range: TextRange, parent: SyntaxKind) {
+        match token {
+            T!['('] | T!['['] | T!['{'] => {
+                let group_id = self.groups.len() as u32;
+                self.groups.push(Group {
+                    kind: parent,
+                    open: self.elems.len(),
+                    close: usize::MAX,
+                    line: 0,
+                    indent: 0,
+                    brace: token == T!['{'],
+                    expand: false,
+                });
+                self.push_elem(ElemKind::Open(group_id), range, parent);
+                self.group_stack.push(group_id);
+            }
+            T![')'] | T![']'] | T!['}'] => {
+                let group_id = *self.group_stack.last().unwrap();
+                self.groups[group_id as usize].close = self.elems.len();
+                self.push_elem(ElemKind::Close(group_id), range, parent);
+                self.group_stack.pop();
+            }
+            _ => self.push_elem(ElemKind::Token(token), range, parent),
+        }
+    }
+
+    fn push_elem(&mut self, kind: ElemKind, range: TextRange, parent: SyntaxKind) {
+        let is_token = !matches!(kind, ElemKind::LineComment | ElemKind::BlockComment);
+        let elem = Elem {
+            kind,
+            range,
+            parent,
+            group: *self.group_stack.last().unwrap(),
+            newlines: std::mem::take(&mut self.newlines),
+            line_start: is_token && std::mem::take(&mut self.line_start),
+        };
+        self.elems.push(elem);
+    }
+
+    // list groups expand when source had line breaks in them,
+    // blocks also expand when they contain multiple statements
+    fn expand_groups(&mut self) {
+        let mut has_newline = vec![false; self.groups.len()];
+        let mut has_comment = vec![false; self.groups.len()];
+        let mut stmt_count = vec![0_u32; self.groups.len()];
+
+        for elem in self.elems.iter() {
+            let group_id = elem.group as usize;
+            if elem.newlines > 0 {
+                has_newline[group_id] = true;
+            }
+            if elem.kind == ElemKind::LineComment {
+                has_comment[group_id] = true;
+            }
+            if elem.line_start {
+                stmt_count[group_id] += 1;
+            }
         }
-        first = false;
-        name_fmt(fmt, name);
-    }
-}
-
-fn type_fmt(fmt: &mut Formatter, ty: ast::Type) {
-    match ty {
-        ast::Type::Basic(ty) => type_basic(fmt, ty),
-        ast::Type::Custom(ty) => type_custom(fmt, ty),
-        ast::Type::Reference(ty) => type_reference(fmt, ty),
-        ast::Type::Procedure(ty) => type_procedure(fmt, ty),
-        ast::Type::ArraySlice(ty) => type_array_slice(fmt, ty),
-        ast::Type::ArrayStatic(ty) => type_array_static(fmt, ty),
-    }
-}
-
-fn type_basic(fmt: &mut Formatter, ty: ast::TypeBasic) {
-    let basic = ty.basic(fmt.tree);
-    fmt.write(basic.as_str());
-}
-
-fn type_custom(fmt: &mut Formatter, ty: ast::TypeCustom) {
-    path_fmt(fmt, ty.path(fmt.tree).unwrap());
-}
 
-fn type_reference(fmt: &mut Formatter, ty: ast::TypeReference) {
-    fmt.write_c('&');
-    if ty.is_mut(fmt.tree) {
-        fmt.write("mut");
-        fmt.space();
-    }
-    type_fmt(fmt, ty.ref_ty(fmt.tree).unwrap());
-}
-
-fn type_procedure(fmt: &mut Formatter, ty: ast::TypeProcedure) {
-    fmt.write("proc");
-    param_type_list(fmt, ty.param_type_list(fmt.tree).unwrap());
-    if let Some(ty) = ty.return_ty(fmt.tree) {
-        fmt.space();
-        fmt.write("->");
-        fmt.space();
-        type_fmt(fmt, ty);
-    }
-}
-
-fn param_type_list(fmt: &mut Formatter, param_type_list: ast::ParamTypeList) {
-    fmt.write_c('(');
-    let mut first = true;
-    for ty in param_type_list.param_types(fmt.tree) {
-        if !first {
-            fmt.write_c(',');
-            fmt.space();
-        }
-        first = false;
-        type_fmt(fmt, ty);
-    }
-    if param_type_list.is_variadic(fmt.tree) {
-        if !first {
-            fmt.write_c(',');
-            fmt.space();
+        for (group_id, group) in self.groups.iter_mut().enumerate().skip(1) {
+            if !element_list(group.kind) || group.close == group.open + 1 {
+                continue;
+            }
+            let is_block = matches!(group.kind, SyntaxKind::BLOCK | SyntaxKind::EXPR_BLOCK);
+            group.expand = has_newline[group_id]
+                || has_comment[group_id]
+                || (is_block && stmt_count[group_id] > 1)
+                || group.kind == SyntaxKind::IMPL_PROC_LIST;
         }
-        fmt.write("..");
     }
-    fmt.write_c(')');
-}
 
-fn type_array_slice(fmt: &mut Formatter, ty: ast::TypeArraySlice) {
-    fmt.write_c('[');
-    if ty.is_mut(fmt.tree) {
-        fmt.write("mut");
-    }
-    fmt.write_c(']');
-    type_fmt(fmt, ty.elem_ty(fmt.tree).unwrap());
-}
-
-fn type_array_static(fmt: &mut Formatter, ty: ast::TypeArrayStatic) {
-    fmt.write_c('[');
-    expr_fmt(fmt, ty.len(fmt.tree).unwrap());
-    fmt.write_c(']');
-    type_fmt(fmt, ty.elem_ty(fmt.tree).unwrap());
-}
+    // expands the first inline group on each line that exceeds max width
+    fn expand_overflow(&mut self, line_widths: &[usize]) -> bool {
+        let mut expanded = false;
+        let mut last_line = u32::MAX;
 
-fn stmt_fmt(fmt: &mut Formatter, stmt: ast::Stmt) {
-    match stmt {
-        ast::Stmt::Break(_) => {
-            fmt.write("break");
-            fmt.write_c(';');
-        }
-        ast::Stmt::Continue(_) => {
-            fmt.write("continue");
-            fmt.write_c(';');
-        }
-        ast::Stmt::Return(stmt_return) => {
-            fmt.write("return");
-            if let Some(expr) = stmt_return.expr(fmt.tree) {
-                fmt.space();
-                expr_fmt(fmt, expr);
+        for group in self.groups.iter_mut().skip(1) {
+            if group.expand || group.line == last_line {
+                continue;
             }
-            fmt.write_c(';');
-        }
-        ast::Stmt::Defer(defer) => {
-            fmt.write("defer");
-            fmt.space();
-            if let Some(short_block) = defer.short_block(fmt.tree) {
-                stmt_fmt(fmt, short_block.stmt(fmt.tree).unwrap());
-            } else {
-                block_fmt(fmt, defer.block(fmt.tree).unwrap());
+            if !element_list(group.kind) || group.close == group.open + 1 {
+                continue;
             }
-        }
-        ast::Stmt::Loop(loop_) => stmt_loop(fmt, loop_),
-        ast::Stmt::Local(local) => stmt_local(fmt, local),
-        ast::Stmt::Assign(assign) => stmt_assign(fmt, assign, true),
-        ast::Stmt::ExprSemi(expr_semi) => {
-            let expr = expr_semi.expr(fmt.tree).unwrap();
-            let semi = !matches!(
-                expr,
-                ast::Expr::If(_) | ast::Expr::Block(_) | ast::Expr::Match(_)
-            );
-            expr_fmt(fmt, expr);
-            if semi {
-                fmt.write_c(';');
+            if line_widths[group.line as usize] > MAX_WIDTH {
+                group.expand = true;
+                expanded = true;
+                last_line = group.line;
             }
         }
-        ast::Stmt::ExprTail(expr_tail) => {
-            fmt.write("->");
-            fmt.space();
-            expr_fmt(fmt, expr_tail.expr(fmt.tree).unwrap());
-            fmt.write_c(';');
-        }
+        expanded
     }
-}
 
-fn stmt_loop(fmt: &mut Formatter, loop_: ast::StmtLoop) {
-    fmt.write("for");
-    fmt.space();
-
-    if let Some(while_header) = loop_.while_header(fmt.tree) {
-        expr_fmt(fmt, while_header.cond(fmt.tree).unwrap());
-        fmt.space();
-    } else if let Some(clike_header) = loop_.clike_header(fmt.tree) {
-        stmt_local(fmt, clike_header.local(fmt.tree).unwrap());
-        fmt.space();
-        expr_fmt(fmt, clike_header.cond(fmt.tree).unwrap());
-        fmt.write_c(';');
-        fmt.space();
-        stmt_assign(fmt, clike_header.assign(fmt.tree).unwrap(), false);
-        fmt.space();
-    }
+    fn print(&mut self) -> (String, Vec<usize>) {
+        let mut output = String::with_capacity(self.source.len());
+        let mut line_widths = Vec::with_capacity(64);
+        let mut line_start = 0;
+        let mut line_indent = 0;
+        let mut prev = None;
+        self.group_stack.clear();
+        self.group_stack.push(0);
 
-    block_fmt(fmt, loop_.block(fmt.tree).unwrap());
-}
-
-fn stmt_local(fmt: &mut Formatter, local: ast::StmtLocal) {
-    if local.is_mut(fmt.tree) {
-        fmt.write("mut");
-    } else {
-        fmt.write("let");
-    }
-    fmt.space();
-    name_fmt(fmt, local.name(fmt.tree).unwrap());
-
-    if let Some(ty) = local.ty(fmt.tree) {
-        fmt.write_c(':');
-        fmt.space();
-        type_fmt(fmt, ty);
-        if let Some(expr) = local.expr(fmt.tree) {
-            fmt.space();
-            fmt.write_c('=');
-            fmt.space();
-            expr_fmt(fmt, expr);
-        }
-    } else {
-        fmt.space();
-        fmt.write_c('=');
-        fmt.space();
-        expr_fmt(fmt, local.expr(fmt.tree).unwrap());
-    }
-    fmt.write_c(';');
-}
-
-fn stmt_assign(fmt: &mut Formatter, assign: ast::StmtAssign, semi: bool) {
-    let mut lhs_rhs = assign.lhs_rhs_iter(fmt.tree);
-    expr_fmt(fmt, lhs_rhs.next().unwrap());
-
-    fmt.space();
-    let assign_op = assign.assign_op(fmt.tree).unwrap();
-    match assign_op {
-        AssignOp::Assign => {
-            fmt.write_c('=');
-        }
-        AssignOp::Bin(op) => {
-            fmt.write(op.as_str());
-            fmt.write_c('=');
-        }
-    }
-    fmt.space();
+        for idx in 0..self.elems.len() {
+            let elem = self.elems[idx];
+            if self.skip_trailing_comma(idx) {
+                continue;
+            }
 
-    expr_fmt(fmt, lhs_rhs.next().unwrap());
-    if semi {
-        fmt.write_c(';');
-    }
-}
+            let breaks = match prev {
+                Some(prev) => self.breaks_between(prev, idx),
+                None => 0,
+            };
+            if breaks > 0 {
+                for _ in 0..breaks {
+                    line_widths.push(output[line_start..].chars().count());
+                    output.push('\n');
+                    line_start = output.len();
+                }
+                line_indent = self.line_indent(idx);
+                for _ in 0..line_indent {
+                    output.push_str(TAB);
+                }
+            } else if let Some(prev) = prev {
+                if self.space_between(prev, idx) {
+                    output.push(' ');
+                }
+            }
 
-fn expr_fmt(fmt: &mut Formatter, expr: ast::Expr) {
-    match expr {
-        ast::Expr::Paren(expr_paren) => {
-            fmt.write_c('(');
-            expr_fmt(fmt, expr_paren.expr(fmt.tree).unwrap());
-            fmt.write_c(')');
-        }
-        ast::Expr::LitNull(_) => fmt.write("null"),
-        //@assuming that lit.rage is single lit token
-        ast::Expr::LitBool(lit) => fmt.write_range(lit.range(fmt.tree)),
-        ast::Expr::LitInt(lit) => fmt.write_range(lit.range(fmt.tree)),
-        ast::Expr::LitFloat(lit) => fmt.write_range(lit.range(fmt.tree)),
-        ast::Expr::LitChar(lit) => fmt.write_range(lit.range(fmt.tree)),
-        ast::Expr::LitString(lit) => fmt.write_range(lit.range(fmt.tree)),
-        ast::Expr::If(if_) => expr_if(fmt, if_),
-        ast::Expr::Block(block) => block_fmt(fmt, block.into_block()),
-        ast::Expr::Match(match_) => expr_match(fmt, match_),
-        ast::Expr::Field(field) => {
-            expr_fmt(fmt, field.target(fmt.tree).unwrap());
-            fmt.write_c('.');
-            name_fmt(fmt, field.name(fmt.tree).unwrap());
-        }
-        ast::Expr::Index(index) => expr_index(fmt, index),
-        ast::Expr::Call(call) => expr_call(fmt, call),
-        ast::Expr::Cast(cast) => {
-            expr_fmt(fmt, cast.target(fmt.tree).unwrap());
-            fmt.space();
-            fmt.write("as");
-            fmt.space();
-            type_fmt(fmt, cast.into_ty(fmt.tree).unwrap());
-        }
-        ast::Expr::Sizeof(sizeof) => {
-            fmt.write("sizeof");
-            fmt.write_c('(');
-            type_fmt(fmt, sizeof.ty(fmt.tree).unwrap());
-            fmt.write_c(')');
-        }
-        ast::Expr::Item(item) => {
-            path_fmt(fmt, item.path(fmt.tree).unwrap());
-        }
-        ast::Expr::Variant(variant) => {
-            fmt.write_c('.');
-            name_fmt(fmt, variant.name(fmt.tree).unwrap());
-        }
-        ast::Expr::StructInit(struct_init) => expr_struct_init(fmt, struct_init),
-        ast::Expr::ArrayInit(array_init) => expr_array_init(fmt, array_init),
-        ast::Expr::ArrayRepeat(array_repeat) => expr_array_repeat(fmt, array_repeat),
-        ast::Expr::Deref(deref) => {
-            fmt.write_c('*');
-            expr_fmt(fmt, deref.expr(fmt.tree).unwrap());
-        }
-        ast::Expr::Address(address) => {
-            fmt.write_c('&');
-            if address.is_mut(fmt.tree) {
-                fmt.write("mut");
-                fmt.space();
+            let text = &self.source[elem.range.as_usize()];
+            match elem.kind {
+                ElemKind::LineComment => output.push_str(text.trim_end()),
+                _ => output.push_str(text),
             }
-            expr_fmt(fmt, address.expr(fmt.tree).unwrap());
-        }
-        ast::Expr::Unary(unary) => {
-            let op = unary.un_op(fmt.tree);
-            fmt.write(op.as_str());
-            expr_fmt(fmt, unary.rhs(fmt.tree).unwrap());
-        }
-        ast::Expr::Binary(binary) => {
-            let mut lhs_rhs = binary.lhs_rhs_iter(fmt.tree);
-            expr_fmt(fmt, lhs_rhs.next().unwrap());
-
-            let op = binary.bin_op(fmt.tree);
-            if matches!(op, BinOp::Range | BinOp::RangeInc) {
-                fmt.write(op.as_str());
-            } else {
-                fmt.space();
-                fmt.write(op.as_str());
-                fmt.space();
+            match elem.kind {
+                ElemKind::Open(group_id) => {
+                    let group = &mut self.groups[group_id as usize];
+                    group.line = line_widths.len() as u32;
+                    group.indent = line_indent + 1;
+                    self.group_stack.push(group_id);
+                }
+                ElemKind::Close(_) => {
+                    self.group_stack.pop();
+                }
+                _ => {}
             }
-
-            expr_fmt(fmt, lhs_rhs.next().unwrap());
+            if self.insert_trailing_comma(idx) {
+                output.push(',');
+            }
+            prev = Some(idx);
         }
-    }
-}
-
-fn expr_if(fmt: &mut Formatter, if_: ast::ExprIf) {
-    let entry = if_.entry_branch(fmt.tree).unwrap();
-    fmt.write("if");
-    fmt.space();
-    expr_fmt(fmt, entry.cond(fmt.tree).unwrap());
-    fmt.space();
-    block_fmt(fmt, entry.block(fmt.tree).unwrap());
-
-    for branch in if_.else_if_branches(fmt.tree) {
-        fmt.space();
-        fmt.write("else");
-        fmt.space();
-        fmt.write("if");
-        fmt.space();
-        expr_fmt(fmt, branch.cond(fmt.tree).unwrap());
-        fmt.space();
-        block_fmt(fmt, branch.block(fmt.tree).unwrap());
-    }
-
-    if let Some(block) = if_.else_block(fmt.tree) {
-        fmt.space();
-        fmt.write("else");
-        fmt.space();
-        block_fmt(fmt, block);
-    }
-}
 
-fn block_fmt(fmt: &mut Formatter, block: ast::Block) {
-    fmt.write_c('{');
-    fmt.depth_increment();
-
-    let mut empty = true;
-    for stmt in block.stmts(fmt.tree) {
-        if empty {
-            fmt.new_line();
-            empty = false;
+        if prev.is_some() {
+            line_widths.push(output[line_start..].chars().count());
+            output.push('\n');
         }
-        fmt.tab_depth();
-        stmt_fmt(fmt, stmt);
-        fmt.new_line();
+        (output, line_widths)
     }
 
-    fmt.depth_decrement();
-    if !empty {
-        fmt.tab_depth();
-    }
-    fmt.write_c('}');
-}
+    fn breaks_between(&self, prev_idx: usize, idx: usize) -> u32 {
+        let prev = self.elems[prev_idx];
+        let elem = self.elems[idx];
+        let group = &self.groups[elem.group as usize];
+        let after_open = matches!(prev.kind, ElemKind::Open(group_id) if group_id == elem.group);
+        let max_breaks = if after_open { 1 } else { 2 };
 
-fn expr_match(fmt: &mut Formatter, match_: ast::ExprMatch) {
-    fmt.write("match");
-    fmt.space();
-    expr_fmt(fmt, match_.on_expr(fmt.tree).unwrap());
-    fmt.space();
-    fmt.write_c('{');
-    fmt.depth_increment();
-
-    let mut empty = true;
-    let match_arm_list = match_.match_arm_list(fmt.tree).unwrap();
-    for match_arm in match_arm_list.match_arms(fmt.tree) {
-        if empty {
-            fmt.new_line();
-            empty = false;
+        if prev.kind == ElemKind::LineComment {
+            return elem.newlines.clamp(1, max_breaks);
         }
-        let mut pat_expr_iter = match_arm.pat_expr_iter(fmt.tree);
-        fmt.tab_depth();
-        expr_fmt(fmt, pat_expr_iter.next().unwrap());
-        fmt.space();
-        fmt.write("->");
-        fmt.space();
-        expr_fmt(fmt, pat_expr_iter.next().unwrap());
-        fmt.write_c(',');
-        fmt.new_line();
-    }
-
-    if let Some(fallback) = match_arm_list.fallback(fmt.tree) {
-        if empty {
-            fmt.new_line();
-            empty = false;
+        if prev.kind == ElemKind::BlockComment && elem.newlines == 0 {
+            return 0;
         }
-        fmt.tab_depth();
-        fmt.write_c('_');
-        fmt.space();
-        fmt.write("->");
-        fmt.space();
-        expr_fmt(fmt, fallback.expr(fmt.tree).unwrap());
-        fmt.write_c(',');
-        fmt.new_line();
-    }
-
-    fmt.depth_decrement();
-    if !empty {
-        fmt.tab_depth();
-    }
-    fmt.write_c('}');
-}
-
-fn expr_index(fmt: &mut Formatter, index: ast::ExprIndex) {
-    expr_fmt(fmt, index.target(fmt.tree).unwrap());
-    fmt.write_c('[');
-    if index.is_mut(fmt.tree) {
-        fmt.write("mut");
-        fmt.space();
-    }
-    //@todo no slicing / index expr api
-    fmt.write("<@index_or_slice>");
-    fmt.write_c(']');
-}
-
-fn expr_call(fmt: &mut Formatter, call: ast::ExprCall) {
-    expr_fmt(fmt, call.target(fmt.tree).unwrap());
-    fmt.write_c('(');
-    let mut first = true;
-    let call_argument_list = call.call_argument_list(fmt.tree).unwrap();
-    for expr in call_argument_list.inputs(fmt.tree) {
-        if !first {
-            fmt.write_c(',');
-            fmt.space();
+        match elem.kind {
+            ElemKind::Close(_) => return group.expand as u32,
+            ElemKind::Token(T![else]) | ElemKind::Token(T![,]) | ElemKind::Token(T![;]) => {
+                return 0
+            }
+            ElemKind::LineComment | ElemKind::BlockComment => return elem.newlines.min(max_breaks),
+            _ => {}
         }
-        first = false;
-        expr_fmt(fmt, expr);
-    }
-    fmt.write_c(')');
-}
 
-fn expr_struct_init(fmt: &mut Formatter, struct_init: ast::ExprStructInit) {
-    if let Some(path) = struct_init.path(fmt.tree) {
-        path_fmt(fmt, path);
-    }
-    fmt.write_c('.');
-    fmt.write_c('{');
-
-    let mut first = true;
-    let field_init_list = struct_init.field_init_list(fmt.tree).unwrap();
-    for field_init in field_init_list.field_inits(fmt.tree) {
-        if !first {
-            fmt.write_c(',');
-            fmt.space();
-        }
-        first = false;
-        if let Some(name) = field_init.name(fmt.tree) {
-            name_fmt(fmt, name);
-            fmt.write_c(':');
-            fmt.space();
+        if !group.expand {
+            0
+        } else if elem.line_start {
+            elem.newlines.clamp(1, max_breaks)
+        } else if group.kind == SyntaxKind::SOURCE_FILE {
+            0
+        } else {
+            elem.newlines.min(1)
         }
-        expr_fmt(fmt, field_init.expr(fmt.tree).unwrap());
     }
-    fmt.write_c('}');
-}
 
-fn expr_array_init(fmt: &mut Formatter, array_init: ast::ExprArrayInit) {
-    fmt.write_c('[');
-    let mut first = true;
-    for expr in array_init.inputs(fmt.tree) {
-        if !first {
-            fmt.write_c(',');
-            fmt.space();
+    fn line_indent(&self, idx: usize) -> u32 {
+        let elem = self.elems[idx];
+        if let ElemKind::Close(group_id) = elem.kind {
+            let group = &self.groups[group_id as usize];
+            if group.expand {
+                return group.indent - 1;
+            }
         }
-        first = false;
-        expr_fmt(fmt, expr);
-    }
-    fmt.write_c(']');
-}
 
-fn expr_array_repeat(fmt: &mut Formatter, array_repeat: ast::ExprArrayRepeat) {
-    let mut expr_len = array_repeat.expr_len_iter(fmt.tree);
-    fmt.write_c('[');
-    expr_fmt(fmt, expr_len.next().unwrap());
-    fmt.write_c(';');
-    fmt.space();
-    expr_fmt(fmt, expr_len.next().unwrap());
-    fmt.write_c(']');
+        let expanded_id = self
+            .group_stack
+            .iter()
+            .rev()
+            .copied()
+            .find(|&group_id| self.groups[group_id as usize].expand)
+            .unwrap_or(0);
+        let indent = self.groups[expanded_id as usize].indent;
+
+        let continuation = match elem.kind {
+            ElemKind::LineComment | ElemKind::BlockComment => elem.group != expanded_id,
+            ElemKind::Token(T![else]) => false,
+            _ => !elem.line_start || elem.group != expanded_id,
+        };
+        indent + continuation as u32
+    }
+
+    fn space_between(&self, prev_idx: usize, idx: usize) -> bool {
+        let prev = self.elems[prev_idx];
+        let elem = self.elems[idx];
+
+        match elem.kind {
+            ElemKind::LineComment | ElemKind::BlockComment => return true,
+            ElemKind::Close(group_id) => {
+                return prev.kind != ElemKind::Open(group_id)
+                    && self.groups[group_id as usize].brace;
+            }
+            ElemKind::Open(group_id) => match self.groups[group_id as usize].kind {
+                SyntaxKind::POLY_PARAMS
+                | SyntaxKind::PARAM_LIST
+                | SyntaxKind::PARAM_TYPE_LIST
+                | SyntaxKind::VARIANT_FIELD_LIST
+                | SyntaxKind::CALL_ARGUMENT_LIST
+                | SyntaxKind::ATTRIBUTE_ARGS
+                | SyntaxKind::EXPR_SIZEOF
+                | SyntaxKind::EXPR_INDEX
+                | SyntaxKind::ATTRIBUTE => return false,
+                _ => {}
+            },
+            ElemKind::Token(T![,]) | ElemKind::Token(T![;]) | ElemKind::Token(T![:]) => {
+                return false
+            }
+            ElemKind::Token(T![.])
+                if !matches!(
+                    elem.parent,
+                    SyntaxKind::EXPR_VARIANT | SyntaxKind::EXPR_STRUCT_INIT
+                ) =>
+            {
+                return false
+            }
+            ElemKind::Token(T![..]) if elem.parent == SyntaxKind::EXPR_RANGE => return false,
+            ElemKind::Token(T![/]) if elem.parent == SyntaxKind::IMPORT_PATH => return false,
+            _ => {}
+        }
+
+        match prev.kind {
+            ElemKind::LineComment | ElemKind::BlockComment => true,
+            ElemKind::Open(group_id) => self.groups[group_id as usize].brace,
+            ElemKind::Close(group_id) => !matches!(
+                self.groups[group_id as usize].kind,
+                SyntaxKind::TYPE_ARRAY_SLICE | SyntaxKind::TYPE_ARRAY_STATIC
+            ),
+            ElemKind::Token(token) => match token {
+                T![.] | T![#] => false,
+                T![..] => prev.parent != SyntaxKind::EXPR_RANGE,
+                T![/] => prev.parent != SyntaxKind::IMPORT_PATH,
+                T![:] => prev.parent != SyntaxKind::IMPORT_ITEM,
+                T![&] => !matches!(
+                    prev.parent,
+                    SyntaxKind::EXPR_ADDRESS | SyntaxKind::TYPE_REFERENCE
+                ),
+                T![*] => prev.parent != SyntaxKind::EXPR_DEREF,
+                _ => prev.parent != SyntaxKind::EXPR_UNARY,
+            },
+        }
+    }
+
+    // trailing commas are removed from inline lists
+    fn skip_trailing_comma(&self, idx: usize) -> bool {
+        if self.elems[idx].kind != ElemKind::Token(T![,]) {
+            return false;
+        }
+        match self.next_token(idx) {
+            Some(next_idx) => match self.elems[next_idx].kind {
+                ElemKind::Close(group_id) => !self.groups[group_id as usize].expand,
+                _ => false,
+            },
+            None => false,
+        }
+    }
+
+    // trailing commas are inserted into expanded lists
+    fn insert_trailing_comma(&self, idx: usize) -> bool {
+        let elem = self.elems[idx];
+        let group_id = match self
+            .next_token(idx)
+            .map(|next_idx| self.elems[next_idx].kind)
+        {
+            Some(ElemKind::Close(group_id)) => group_id,
+            _ => return false,
+        };
+        let group = &self.groups[group_id as usize];
+        if !group.expand || !comma_list(group.kind) {
+            return false;
+        }
+        match elem.kind {
+            ElemKind::Token(T![,]) | ElemKind::Token(T![..]) => false,
+            ElemKind::Open(open_id) if open_id == group_id => false,
+            _ => !self.elems[group.open..idx].iter().rev().any(|elem| {
+                elem.group == group_id
+                    && elem.line_start
+                    && elem.parent == SyntaxKind::MATCH_FALLBACK
+            }),
+        }
+    }
+
+    fn next_token(&self, idx: usize) -> Option<usize> {
+        (idx + 1..self.elems.len()).find(|&next_idx| {
+            !matches!(
+                self.elems[next_idx].kind,
+                ElemKind::LineComment | ElemKind::BlockComment
+            )
+        })
+    }
+}
+
+fn element_list(kind: SyntaxKind) -> bool {
+    matches!(
+        kind,
+        SyntaxKind::SOURCE_FILE
+            | SyntaxKind::BLOCK
+            | SyntaxKind::EXPR_BLOCK
+            | SyntaxKind::IMPL_PROC_LIST
+    ) || comma_list(kind)
+}
+
+fn comma_list(kind: SyntaxKind) -> bool {
+    matches!(
+        kind,
+        SyntaxKind::ATTRIBUTE_ARGS
+            | SyntaxKind::POLY_PARAMS
+            | SyntaxKind::PARAM_LIST
+            | SyntaxKind::VARIANT_LIST
+            | SyntaxKind::VARIANT_FIELD_LIST
+            | SyntaxKind::FIELD_LIST
+            | SyntaxKind::IMPORT_SYMBOL_LIST
+            | SyntaxKind::PARAM_TYPE_LIST
+            | SyntaxKind::MATCH_ARM_LIST
+            | SyntaxKind::CALL_ARGUMENT_LIST
+            | SyntaxKind::FIELD_INIT_LIST
+            | SyntaxKind::EXPR_ARRAY_INIT
+    )
+}
+
+fn is_delimiter(token: Token) -> bool {
+    matches!(
+        token,
+        T!['('] | T![')'] | T!['['] | T![']'] | T!['{'] | T!['}'] | T![,]
+    )
 }
@@ -42,6 +42,19 @@ pub fn dir_create(path: &PathBuf, force: bool) -> Result<(), ErrorComp> {
     })
 }
 
+pub fn dir_remove_all(path: &PathBuf, force: bool) -> Result<(), ErrorComp> {
+    if !force && !path.exists() {
+        return Ok(());
+    }
+    std::fs::remove_dir_all(path).map_err(|io_error| {
+        ErrorComp::message(format!(
+            "failed to remove directory: `{}`\nreason: {}",
+            path.to_string_lossy(),
+            io_error
+        ))
+    })
+}
+
 pub fn dir_read(path: &PathBuf) -> Result<std::fs::ReadDir, ErrorComp> {
     std::fs::read_dir(path).map_err(|io_error| {
         ErrorComp::message(format!(
@@ -1,5 +1,6 @@
-use super::{ConstArray, ConstStruct, ConstValue, ConstValueID};
+use super::{ConstArray, ConstStruct, ConstValue, ConstValueID, StringLit, StringLitID};
 use crate::arena::Arena;
+use crate::intern::InternID;
 use std::collections::HashMap;
 use std::hash::{Hash, Hasher};
 
@@ -9,6 +10,13 @@ pub struct ConstInternPool<'hir> {
     intern_map: HashMap<ConstValue<'hir>, ConstValueID>,
 }
 
+/// program-wide table of string literals, collected while typechecking,
+/// literals of all modules with same contents share one entry and id
+pub struct StringLitTable {
+    lits: Vec<StringLit>,
+    lit_ids: HashMap<InternID, StringLitID>,
+}
+
 impl<'hir> ConstInternPool<'hir> {
     pub fn new() -> ConstInternPool<'hir> {
         ConstInternPool {
@@ -36,6 +44,41 @@ impl<'hir> ConstInternPool<'hir> {
     }
 }
 
+impl StringLitTable {
+    pub fn new() -> StringLitTable {
+        StringLitTable {
+            lits: Vec::with_capacity(256),
+            lit_ids: HashMap::with_capacity(256),
+        }
+    }
+
+    /// `c_string` is kept when any use of the string requires a null terminator
+    pub fn add(&mut self, id: InternID, string: &str, c_string: bool) -> StringLitID {
+        if let Some(&lit_id) = self.lit_ids.get(&id) {
+            self.lits[lit_id.index()].c_string |= c_string;
+            return lit_id;
+        }
+        let lit_id = StringLitID::new(self.lits.len());
+        self.lits.push(StringLit {
+            id,
+            len: string.len() as u64,
+            c_string,
+        });
+        self.lit_ids.insert(id, lit_id);
+        lit_id
+    }
+
+    pub fn lit_id(&self, id: InternID) -> Option<StringLitID> {
+        self.lit_ids.get(&id).copied()
+    }
+    pub fn get(&self, lit_id: StringLitID) -> StringLit {
+        self.lits[lit_id.index()]
+    }
+    pub fn lits(&self) -> &[StringLit] {
+        &self.lits
+    }
+}
+
 impl<'hir> Eq for ConstValue<'hir> {}
 
 //@perf: test for hash collision rates and how well this performs in terms of speed 09.05.24
@@ -50,7 +93,7 @@ impl<'hir> Hash for ConstValue<'hir> {
             ConstValue::IntU(val) => val.hash(state),
             ConstValue::Float { val, ty } => (val.to_bits(), ty).hash(state),
             ConstValue::Char { val } => val.hash(state),
-            ConstValue::String { id, c_string } => (id, c_string).hash(state),
+            ConstValue::String { id, kind } => (id, kind).hash(state),
             ConstValue::Procedure { proc_id } => proc_id.0.hash(state),
             ConstValue::EnumVariant {
                 enum_id,
@@ -74,3 +117,22 @@ impl<'hir> Hash for ConstArray<'hir> {
         (self.len, self.values).hash(state);
     }
 }
+
+#[test]
+fn test_string_lit_table() {
+    let mut table = StringLitTable::new();
+    let hello = InternID::new(0);
+    let world = InternID::new(1);
+
+    let hello_id = table.add(hello, "hello", false);
+    let world_id = table.add(world, "world!", false);
+    assert!(hello_id != world_id);
+    assert!(table.add(hello, "hello", true) == hello_id);
+    assert!(table.lits().len() == 2);
+
+    let lit = table.get(hello_id);
+    assert!(lit.id == hello && lit.len == 5 && lit.c_string);
+    assert!(!table.get(world_id).c_string);
+    assert!(table.lit_id(world) == Some(world_id));
+    assert!(table.lit_id(InternID::new(2)).is_none());
+}
@@ -1,4 +1,6 @@
 pub mod intern;
+pub mod names;
+pub mod print;
 
 use crate::arena::Arena;
 use crate::ast;
@@ -6,20 +8,30 @@ use crate::bitset::BitSet;
 use crate::id_impl;
 use crate::intern::{InternID, InternPool};
 use crate::session::ModuleID;
-use intern::ConstInternPool;
+use intern::{ConstInternPool, StringLitTable};
+use names::NameTable;
 
 pub struct Hir<'hir> {
     pub arena: Arena<'hir>,
     pub intern_name: InternPool<'hir>,
     pub intern_string: InternPool<'hir>,
-    pub string_is_cstr: Vec<bool>,
+    pub string_lits: StringLitTable,
     pub const_intern: ConstInternPool<'hir>,
     pub procs: Vec<ProcData<'hir>>,
     pub enums: Vec<EnumData<'hir>>,
     pub structs: Vec<StructData<'hir>>,
     pub consts: Vec<ConstData<'hir>>,
     pub globals: Vec<GlobalData<'hir>>,
+    /// globals in initialization order, dependencies come first
+    pub global_init_order: Vec<GlobalID>,
     pub const_values: Vec<ConstValueID>,
+    pub externals: Vec<ExternalProc>,
+    /// libraries requested by `#[link]` attributes, without duplicates
+    pub link_libs: Vec<InternID>,
+    /// root package `#[test]` procedures, only collected for `rock test`
+    pub tests: Option<Vec<ProcID>>,
+    /// resolved names and path segments of each module
+    pub names: NameTable,
 }
 
 id_impl!(ProcID);
@@ -28,6 +40,7 @@ pub struct ProcData<'hir> {
     pub attr_set: BitSet,
     pub vis: ast::Vis,
     pub name: ast::Name,
+    pub poly_params: Option<&'hir [ast::Name]>,
     pub params: &'hir [ProcParam<'hir>],
     pub return_ty: Type<'hir>,
     pub block: Option<Block<'hir>>,
@@ -51,6 +64,23 @@ pub enum ProcFlag {
     Test,
     Builtin,
     Inline,
+    NoReturn,
+    Deprecated,
+}
+
+/// `external` procedure resolved by the linker
+#[derive(Copy, Clone)]
+pub struct ExternalProc {
+    pub proc_id: ProcID,
+    pub link_name: InternID,
+    pub call_conv: CallConv,
+}
+
+#[derive(Copy, Clone, PartialEq)]
+pub enum CallConv {
+    C,
+    Stdcall,
+    Fastcall,
 }
 
 id_impl!(EnumID);
@@ -59,14 +89,16 @@ pub struct EnumData<'hir> {
     pub vis: ast::Vis,
     pub name: ast::Name,
     pub basic: ast::BasicType,
-    pub variants: &'hir [EnumVariant],
+    pub variants: &'hir [EnumVariant<'hir>],
+    pub size_eval: SizeEval,
 }
 
 id_impl!(EnumVariantID);
 #[derive(Copy, Clone)]
-pub struct EnumVariant {
+pub struct EnumVariant<'hir> {
     pub name: ast::Name,
     pub value: ConstEvalID,
+    pub fields: &'hir [Type<'hir>],
 }
 
 id_impl!(StructID);
@@ -84,6 +116,7 @@ pub struct StructField<'hir> {
     pub vis: ast::Vis,
     pub name: ast::Name,
     pub ty: Type<'hir>,
+    pub default: Option<ConstEvalID>,
 }
 
 id_impl!(ConstID);
@@ -143,6 +176,7 @@ pub enum Type<'hir> {
     Procedure(&'hir ProcType<'hir>),
     ArraySlice(&'hir ArraySlice<'hir>),
     ArrayStatic(&'hir ArrayStatic<'hir>),
+    PolyProc(ProcID, u32),
 }
 
 #[derive(Copy, Clone)]
@@ -164,9 +198,11 @@ pub struct ArrayStatic<'hir> {
     pub elem_ty: Type<'hir>,
 }
 
+/// length is always a `usize` constant value,
+/// declaration types refer to a const eval which is resolved later
 #[derive(Copy, Clone)]
 pub enum ArrayStaticLen {
-    Immediate(Option<u64>),
+    Value(ConstValueID),
     ConstEval(ConstEvalID),
 }
 
@@ -180,6 +216,7 @@ pub enum Stmt<'hir> {
     Break,
     Continue,
     Return(Option<&'hir Expr<'hir>>),
+    /// deferred block executed in place, inserted before each exit of its scope
     Defer(&'hir Block<'hir>),
     Loop(&'hir Loop<'hir>),
     Local(LocalID),
@@ -236,7 +273,7 @@ pub enum ConstValue<'hir> {
     IntU (u64),
     Float       { val: f64, ty: Option<ast::BasicType> },
     Char        { val: char },
-    String      { id: InternID, c_string: bool },
+    String      { id: InternID, kind: ast::StringKind },
     Procedure   { proc_id: ProcID },
     EnumVariant { enum_id: EnumID, variant_id: EnumVariantID },
     Struct      { struct_: &'hir ConstStruct<'hir> },
@@ -256,6 +293,17 @@ pub struct ConstArray<'hir> {
     pub values: &'hir [ConstValueID],
 }
 
+id_impl!(StringLitID);
+/// unique string used by regular or c string literals, emitted once by codegen
+#[derive(Copy, Clone)]
+pub struct StringLit {
+    pub id: InternID,
+    /// length in bytes, without the null terminator
+    pub len: u64,
+    /// used by any `c"..."` literal, stored with a null terminator
+    pub c_string: bool,
+}
+
 #[rustfmt::skip]
 #[derive(Copy, Clone)]
 pub enum Expr<'hir> {
@@ -274,8 +322,11 @@ pub enum Expr<'hir> {
     ConstVar     { const_id: ConstID },
     GlobalVar    { global_id: GlobalID },
     CallDirect   { proc_id: ProcID, input: &'hir [&'hir Expr<'hir>] },
+    CallPoly     { proc_id: ProcID, poly_call: &'hir PolyCall<'hir> },
     CallIndirect { target: &'hir Expr<'hir>, indirect: &'hir CallIndirect<'hir> },
+    Format       { format: &'hir Format<'hir> },
     StructInit   { struct_id: StructID, input: &'hir [StructFieldInit<'hir>] },
+    VariantInit  { variant_init: &'hir VariantInit<'hir> },
     ArrayInit    { array_init: &'hir ArrayInit<'hir> },
     ArrayRepeat  { array_repeat: &'hir ArrayRepeat<'hir> },
     Deref        { rhs: &'hir Expr<'hir>, ptr_ty: &'hir Type<'hir> },
@@ -295,6 +346,8 @@ pub struct If<'hir> {
 pub struct Branch<'hir> {
     pub cond: &'hir Expr<'hir>,
     pub block: Block<'hir>,
+    /// condition value if known at compile time
+    pub cond_value: Option<bool>,
 }
 
 #[derive(Copy, Clone)]
@@ -302,21 +355,40 @@ pub struct Match<'hir> {
     pub on_expr: &'hir Expr<'hir>,
     pub arms: &'hir [MatchArm<'hir>],
     pub fallback: Option<Block<'hir>>,
+    /// reachable arm values form a contiguous range, can be lowered to a jump table
+    pub jump_table: bool,
+    /// arms cover every possible value without a fallback
+    pub exhaustive: bool,
 }
 
 #[derive(Copy, Clone)]
 pub struct MatchArm<'hir> {
-    pub pat: ConstValueID,
+    pub pats: &'hir [MatchPat],
+    /// variant field bindings, `None` for `_` fields
+    pub binds: &'hir [Option<LocalID>],
     pub block: Block<'hir>,
     pub unreachable: bool,
 }
 
+#[derive(Copy, Clone)]
+pub enum MatchPat {
+    Value(ConstValueID),
+    /// integer or `char` range, `end` is excluded unless `inclusive`
+    Range {
+        start: ConstValueID,
+        end: ConstValueID,
+        inclusive: bool,
+    },
+}
+
 #[derive(Copy, Clone)]
 pub struct IndexAccess<'hir> {
     pub deref: bool,
     pub elem_ty: Type<'hir>,
     pub kind: IndexKind<'hir>,
     pub index: &'hir Expr<'hir>,
+    /// `false` when a constant index is in bounds of a static array
+    pub bounds_check: bool,
 }
 
 #[derive(Copy, Clone)]
@@ -356,6 +428,7 @@ pub enum SliceRangeEnd<'hir> {
 pub enum CastKind {
     Error,
     NoOp,
+    Integer_NoOp,
     Integer_Trunc,
     Sint_Sign_Extend,
     Uint_Zero_Extend,
@@ -365,6 +438,8 @@ pub enum CastKind {
     Uint_to_Float,
     Float_Trunc,
     Float_Extend,
+    Pointer_to_Int,
+    Int_to_Pointer,
 }
 
 #[derive(Copy, Clone)]
@@ -373,12 +448,52 @@ pub struct CallIndirect<'hir> {
     pub input: &'hir [&'hir Expr<'hir>],
 }
 
+/// `#print` or `#format` lowered to `printf` or `snprintf` runtime calls
+#[derive(Copy, Clone)]
+pub struct Format<'hir> {
+    pub buffer: Option<&'hir Expr<'hir>>,
+    pub c_format: &'hir str,
+    pub args: &'hir [FormatArg<'hir>],
+}
+
+#[derive(Copy, Clone)]
+pub struct FormatArg<'hir> {
+    pub kind: FormatArgKind,
+    pub expr: &'hir Expr<'hir>,
+}
+
+#[derive(Copy, Clone)]
+pub enum FormatArgKind {
+    Sint,
+    Uint,
+    Float,
+    Bool,
+    Slice,
+    CString,
+}
+
+/// call to generic procedure, `instance` is not assigned inside generic procedures
+#[derive(Copy, Clone)]
+pub struct PolyCall<'hir> {
+    pub instance: ProcID,
+    pub poly_types: &'hir [Type<'hir>],
+    pub input: &'hir [&'hir Expr<'hir>],
+    pub return_ty: Type<'hir>,
+}
+
 #[derive(Copy, Clone)]
 pub struct StructFieldInit<'hir> {
     pub field_id: StructFieldID,
     pub expr: &'hir Expr<'hir>,
 }
 
+#[derive(Copy, Clone)]
+pub struct VariantInit<'hir> {
+    pub enum_id: EnumID,
+    pub variant_id: EnumVariantID,
+    pub input: &'hir [&'hir Expr<'hir>],
+}
+
 #[derive(Copy, Clone)]
 pub struct ArrayInit<'hir> {
     pub elem_ty: Type<'hir>,
@@ -422,6 +537,12 @@ impl<'hir> Hir<'hir> {
         let value_id = self.const_values[id.index()];
         self.const_intern.get(value_id)
     }
+    pub fn array_static_len(&self, len: ArrayStaticLen) -> ConstValue<'hir> {
+        match len {
+            ArrayStaticLen::Value(value_id) => self.const_value(value_id),
+            ArrayStaticLen::ConstEval(eval_id) => self.const_eval_value(eval_id),
+        }
+    }
 }
 
 impl<'hir> ProcData<'hir> {
@@ -441,11 +562,30 @@ impl<'hir> ProcData<'hir> {
     }
 }
 
+impl CallConv {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            CallConv::C => "c",
+            CallConv::Stdcall => "stdcall",
+            CallConv::Fastcall => "fastcall",
+        }
+    }
+
+    pub fn from_str(string: &str) -> Option<CallConv> {
+        match string {
+            "c" => Some(CallConv::C),
+            "stdcall" => Some(CallConv::Stdcall),
+            "fastcall" => Some(CallConv::Fastcall),
+            _ => None,
+        }
+    }
+}
+
 impl<'hir> EnumData<'hir> {
-    pub fn variant(&self, id: EnumVariantID) -> &'hir EnumVariant {
+    pub fn variant(&self, id: EnumVariantID) -> &'hir EnumVariant<'hir> {
         &self.variants[id.index()]
     }
-    pub fn find_variant(&self, id: InternID) -> Option<(EnumVariantID, &'hir EnumVariant)> {
+    pub fn find_variant(&self, id: InternID) -> Option<(EnumVariantID, &'hir EnumVariant<'hir>)> {
         for (idx, variant) in self.variants.iter().enumerate() {
             if variant.name.id == id {
                 return Some((EnumVariantID::new(idx), variant));
@@ -453,6 +593,11 @@ impl<'hir> EnumData<'hir> {
         }
         None
     }
+    pub fn has_fields(&self) -> bool {
+        self.variants
+            .iter()
+            .any(|variant| !variant.fields.is_empty())
+    }
 }
 
 impl<'hir> StructData<'hir> {
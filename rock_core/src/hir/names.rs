@@ -0,0 +1,103 @@
+use super::{
+    ConstID, EnumID, EnumVariantID, GlobalID, LocalID, ProcID, ProcParamID, StructFieldID, StructID,
+};
+use crate::session::ModuleID;
+use crate::text::{TextOffset, TextRange};
+
+/// names and path segments resolved while lowering, stored per module,
+/// ide queries look up resolutions here instead of resolving paths again
+#[derive(Default)]
+pub struct NameTable {
+    modules: Vec<Vec<NameRef>>,
+}
+
+#[derive(Copy, Clone)]
+pub struct NameRef {
+    pub range: TextRange,
+    pub kind: NameRefKind,
+}
+
+#[derive(Copy, Clone, PartialEq)]
+pub enum NameRefKind {
+    Module(ModuleID),
+    Proc(ProcID),
+    Enum(EnumID),
+    Variant(EnumID, EnumVariantID),
+    Struct(StructID),
+    Field(StructID, StructFieldID),
+    Const(ConstID),
+    Global(GlobalID),
+    /// `def` is the name range of the local, in the same module
+    Local {
+        id: LocalID,
+        def: TextRange,
+    },
+    /// `def` is the name range of the parameter, in the same module
+    Param {
+        id: ProcParamID,
+        def: TextRange,
+    },
+}
+
+impl NameTable {
+    pub fn new() -> NameTable {
+        NameTable::default()
+    }
+
+    pub fn add(&mut self, module_id: ModuleID, range: TextRange, kind: NameRefKind) {
+        if module_id.index() >= self.modules.len() {
+            self.modules.resize_with(module_id.index() + 1, Vec::new);
+        }
+        self.modules[module_id.index()].push(NameRef { range, kind });
+    }
+
+    // generic procedures are lowered again for each instance,
+    // their names resolve the same way and are only kept once
+    pub fn finish(mut self) -> NameTable {
+        for names in self.modules.iter_mut() {
+            names.sort_by_key(|name| name.range.start());
+            names.dedup_by(|a, b| a.range == b.range);
+        }
+        self
+    }
+
+    /// resolved names of the module, sorted by range
+    pub fn module_names(&self, module_id: ModuleID) -> &[NameRef] {
+        match self.modules.get(module_id.index()) {
+            Some(names) => names,
+            None => &[],
+        }
+    }
+
+    /// resolved name with `offset` inside of its range
+    pub fn name_at(&self, module_id: ModuleID, offset: TextOffset) -> Option<NameRef> {
+        let names = self.module_names(module_id);
+        let idx = names.partition_point(|name| name.range.end() < offset);
+        names
+            .get(idx)
+            .copied()
+            .filter(|name| name.range.contains_inclusive(offset))
+    }
+}
+
+#[test]
+fn test_name_table() {
+    let module_id = ModuleID::new(0);
+    let range = |start: u32, end: u32| TextRange::new(start.into(), end.into());
+
+    let mut names = NameTable::new();
+    let local = NameRefKind::Local {
+        id: LocalID::new(0),
+        def: range(4, 5),
+    };
+    names.add(module_id, range(20, 25), NameRefKind::Proc(ProcID::new(1)));
+    names.add(module_id, range(10, 11), local);
+    names.add(module_id, range(20, 25), NameRefKind::Proc(ProcID::new(1)));
+    let names = names.finish();
+
+    assert!(names.module_names(module_id).len() == 2);
+    assert!(names.module_names(ModuleID::new(1)).is_empty());
+    assert!(names.name_at(module_id, 10.into()).unwrap().kind == local);
+    assert!(names.name_at(module_id, 25.into()).unwrap().range == range(20, 25));
+    assert!(names.name_at(module_id, 15.into()).is_none());
+}
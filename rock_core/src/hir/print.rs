@@ -0,0 +1,660 @@
+use super::*;
+use std::fmt::Write;
+
+// hir is printed as rock-like pseudo source:
+// ids are resolved back to names, types are fully resolved,
+// local types are explicit and binary expressions are parenthesized
+
+/// readable text form of lowered items, used by `--emit hir`
+pub fn hir_print(hir: &Hir) -> String {
+    let mut p = Printer {
+        hir,
+        proc: None,
+        buf: String::with_capacity(4096),
+        indent: 0,
+    };
+
+    for data in hir.structs.iter() {
+        p.struct_item(data);
+    }
+    for data in hir.enums.iter() {
+        p.enum_item(data);
+    }
+    for data in hir.consts.iter() {
+        p.const_item(data);
+    }
+    for &global_id in hir.global_init_order.iter() {
+        p.global_item(hir.global_data(global_id));
+    }
+    for (idx, data) in hir.procs.iter().enumerate() {
+        p.proc_item(ProcID::new(idx), data);
+    }
+    p.buf
+}
+
+struct Printer<'p, 'hir> {
+    hir: &'p Hir<'hir>,
+    proc: Option<&'p ProcData<'hir>>,
+    buf: String,
+    indent: u32,
+}
+
+impl<'p, 'hir> Printer<'p, 'hir> {
+    fn struct_item(&mut self, data: &StructData) {
+        self.vis(data.vis);
+        self.buf.push_str("struct ");
+        self.name(data.name.id);
+        self.size_comment(data.size_eval);
+        self.buf.push_str("{\n");
+        for field in data.fields {
+            self.tab(1);
+            self.vis(field.vis);
+            self.name(field.name.id);
+            self.buf.push_str(": ");
+            self.ty(field.ty);
+            if let Some(default) = field.default {
+                self.buf.push_str(" = ");
+                self.const_eval(default);
+            }
+            self.buf.push_str(",\n");
+        }
+        self.buf.push_str("}\n\n");
+    }
+
+    fn enum_item(&mut self, data: &EnumData) {
+        self.vis(data.vis);
+        self.buf.push_str("enum ");
+        self.name(data.name.id);
+        self.buf.push(' ');
+        self.buf.push_str(data.basic.as_str());
+        self.size_comment(data.size_eval);
+        self.buf.push_str("{\n");
+        for variant in data.variants {
+            self.tab(1);
+            self.name(variant.name.id);
+            if !variant.fields.is_empty() {
+                self.buf.push('(');
+                self.list(variant.fields, |p, &ty| p.ty(ty));
+                self.buf.push(')');
+            }
+            self.buf.push_str(" = ");
+            self.const_eval(variant.value);
+            self.buf.push_str(",\n");
+        }
+        self.buf.push_str("}\n\n");
+    }
+
+    fn const_item(&mut self, data: &ConstData) {
+        self.vis(data.vis);
+        self.buf.push_str("const ");
+        self.name(data.name.id);
+        self.buf.push_str(": ");
+        self.ty(data.ty);
+        self.buf.push_str(" = ");
+        self.const_eval(data.value);
+        self.buf.push_str(";\n\n");
+    }
+
+    fn global_item(&mut self, data: &GlobalData) {
+        if data.attr_set.contains(GlobalFlag::ThreadLocal) {
+            self.buf.push_str("#[thread_local]\n");
+        }
+        self.vis(data.vis);
+        self.buf.push_str("global ");
+        self.mutt(data.mutt);
+        self.name(data.name.id);
+        self.buf.push_str(": ");
+        self.ty(data.ty);
+        self.buf.push_str(" = ");
+        self.const_eval(data.value);
+        self.buf.push_str(";\n\n");
+    }
+
+    fn proc_item(&mut self, proc_id: ProcID, data: &'p ProcData<'hir>) {
+        if let Some(external) = self.hir.externals.iter().find(|e| e.proc_id == proc_id) {
+            let link_name = self.hir.intern_name.get_str(external.link_name);
+            let _ = writeln!(
+                &mut self.buf,
+                "#[link_name({link_name}), call_conv({})]",
+                external.call_conv.as_str()
+            );
+        }
+        if data.attr_set.contains(ProcFlag::Test) {
+            self.buf.push_str("#[test]\n");
+        }
+        if data.attr_set.contains(ProcFlag::Inline) {
+            self.buf.push_str("#[inline]\n");
+        }
+        if data.attr_set.contains(ProcFlag::NoReturn) {
+            self.buf.push_str("#[no_return]\n");
+        }
+        if data.attr_set.contains(ProcFlag::Deprecated) {
+            self.buf.push_str("#[deprecated]\n");
+        }
+        self.vis(data.vis);
+        self.buf.push_str("proc ");
+        self.name(data.name.id);
+        if let Some(poly_params) = data.poly_params {
+            self.buf.push('(');
+            self.list(poly_params, |p, name| p.name(name.id));
+            self.buf.push(')');
+        }
+
+        self.proc = Some(data);
+        self.buf.push('(');
+        self.list(data.params, |p, param| {
+            p.mutt(param.mutt);
+            p.name(param.name.id);
+            p.buf.push_str(": ");
+            p.ty(param.ty);
+        });
+        if data.attr_set.contains(ProcFlag::Variadic) {
+            if !data.params.is_empty() {
+                self.buf.push_str(", ");
+            }
+            self.buf.push_str("..");
+        }
+        self.buf.push_str(") -> ");
+        self.ty(data.return_ty);
+
+        match data.block {
+            Some(block) => {
+                self.buf.push(' ');
+                self.block(block);
+                self.buf.push_str("\n\n");
+            }
+            None => self.buf.push_str(";\n\n"),
+        }
+        self.proc = None;
+    }
+
+    fn block(&mut self, block: Block) {
+        if block.stmts.is_empty() {
+            self.buf.push_str("{}");
+            return;
+        }
+        self.buf.push_str("{\n");
+        self.indent += 1;
+        for stmt in block.stmts {
+            self.tab(self.indent);
+            self.stmt(*stmt);
+            self.buf.push('\n');
+        }
+        self.indent -= 1;
+        self.tab(self.indent);
+        self.buf.push('}');
+    }
+
+    fn stmt(&mut self, stmt: Stmt) {
+        match stmt {
+            Stmt::Break => self.buf.push_str("break;"),
+            Stmt::Continue => self.buf.push_str("continue;"),
+            Stmt::Return(expr) => {
+                self.buf.push_str("return");
+                if let Some(expr) = expr {
+                    self.buf.push(' ');
+                    self.expr(expr);
+                }
+                self.buf.push(';');
+            }
+            Stmt::Defer(block) => {
+                self.buf.push_str("defer ");
+                self.block(*block);
+            }
+            Stmt::Loop(loop_) => {
+                self.buf.push_str("for ");
+                match loop_.kind {
+                    LoopKind::Loop => {}
+                    LoopKind::While { cond } => {
+                        self.expr(cond);
+                        self.buf.push(' ');
+                    }
+                    LoopKind::ForLoop {
+                        local_id,
+                        cond,
+                        assign,
+                    } => {
+                        self.local(local_id);
+                        self.buf.push(' ');
+                        self.expr(cond);
+                        self.buf.push_str("; ");
+                        self.assign(assign);
+                        self.buf.push(' ');
+                    }
+                }
+                self.block(loop_.block);
+            }
+            Stmt::Local(local_id) => self.local(local_id),
+            Stmt::Assign(assign) => {
+                self.assign(assign);
+                self.buf.push(';');
+            }
+            Stmt::ExprSemi(expr) => {
+                self.expr(expr);
+                self.buf.push(';');
+            }
+            Stmt::ExprTail(expr) => {
+                self.buf.push_str("-> ");
+                self.expr(expr);
+                self.buf.push(';');
+            }
+        }
+    }
+
+    fn local(&mut self, local_id: LocalID) {
+        let local = self.proc_data().local(local_id);
+        match local.mutt {
+            ast::Mut::Mutable => self.buf.push_str("mut "),
+            ast::Mut::Immutable => self.buf.push_str("let "),
+        }
+        self.name(local.name.id);
+        self.buf.push_str(": ");
+        self.ty(local.ty);
+        if let Some(value) = local.value {
+            self.buf.push_str(" = ");
+            self.expr(value);
+        }
+        self.buf.push(';');
+    }
+
+    fn assign(&mut self, assign: &Assign) {
+        self.expr(assign.lhs);
+        match assign.op {
+            ast::AssignOp::Assign => self.buf.push_str(" = "),
+            ast::AssignOp::Bin(op) => {
+                let _ = write!(&mut self.buf, " {}= ", op.as_str());
+            }
+        }
+        self.expr(assign.rhs);
+    }
+
+    fn expr(&mut self, expr: &Expr) {
+        match *expr {
+            Expr::Error => self.buf.push_str("<error>"),
+            Expr::Const { value } => self.const_value(value),
+            Expr::If { if_ } => {
+                self.buf.push_str("if ");
+                self.branch(if_.entry);
+                for branch in if_.branches {
+                    self.buf.push_str(" else if ");
+                    self.branch(*branch);
+                }
+                if let Some(block) = if_.else_block {
+                    self.buf.push_str(" else ");
+                    self.block(block);
+                }
+            }
+            Expr::Block { block } => self.block(block),
+            Expr::Match { match_ } => {
+                self.buf.push_str("match ");
+                self.expr(match_.on_expr);
+                if match_.jump_table {
+                    self.buf.push_str(" /* jump table */");
+                }
+                self.buf.push_str(" {\n");
+                self.indent += 1;
+                for arm in match_.arms {
+                    self.tab(self.indent);
+                    if arm.unreachable {
+                        self.buf.push_str("/* unreachable */ ");
+                    }
+                    for (idx, pat) in arm.pats.iter().enumerate() {
+                        if idx != 0 {
+                            self.buf.push_str(" | ");
+                        }
+                        match *pat {
+                            MatchPat::Value(value) => self.const_value(self.hir.const_value(value)),
+                            MatchPat::Range {
+                                start,
+                                end,
+                                inclusive,
+                            } => {
+                                self.const_value(self.hir.const_value(start));
+                                self.buf.push_str(if inclusive { "..=" } else { "..<" });
+                                self.const_value(self.hir.const_value(end));
+                            }
+                        }
+                    }
+                    if !arm.binds.is_empty() {
+                        self.buf.push('(');
+                        self.list(arm.binds, |p, &local_id| match local_id {
+                            Some(local_id) => p.name(p.proc_data().local(local_id).name.id),
+                            None => p.buf.push('_'),
+                        });
+                        self.buf.push(')');
+                    }
+                    self.buf.push_str(" -> ");
+                    self.block(arm.block);
+                    self.buf.push_str(",\n");
+                }
+                if let Some(block) = match_.fallback {
+                    self.tab(self.indent);
+                    self.buf.push_str("_ -> ");
+                    self.block(block);
+                    self.buf.push_str(",\n");
+                }
+                self.indent -= 1;
+                self.tab(self.indent);
+                self.buf.push('}');
+            }
+            Expr::StructField {
+                target,
+                struct_id,
+                field_id,
+                ..
+            } => {
+                self.expr(target);
+                self.buf.push('.');
+                let field = self.hir.struct_data(struct_id).field(field_id);
+                self.name(field.name.id);
+            }
+            Expr::SliceField {
+                target, first_ptr, ..
+            } => {
+                self.expr(target);
+                self.buf.push_str(if first_ptr { ".ptr" } else { ".len" });
+            }
+            Expr::Index { target, access } => {
+                self.expr(target);
+                self.buf.push('[');
+                self.expr(access.index);
+                self.buf.push(']');
+            }
+            Expr::Slice { target, access } => {
+                self.expr(target);
+                self.buf.push('[');
+                if let Some(lower) = access.range.lower {
+                    self.expr(lower);
+                }
+                match access.range.upper {
+                    SliceRangeEnd::Unbounded => self.buf.push_str(".."),
+                    SliceRangeEnd::Exclusive(upper) => {
+                        self.buf.push_str("..<");
+                        self.expr(upper);
+                    }
+                    SliceRangeEnd::Inclusive(upper) => {
+                        self.buf.push_str("..=");
+                        self.expr(upper);
+                    }
+                }
+                self.buf.push(']');
+            }
+            Expr::Cast { target, into, .. } => {
+                self.buf.push('(');
+                self.expr(target);
+                self.buf.push_str(" as ");
+                self.ty(*into);
+                self.buf.push(')');
+            }
+            Expr::LocalVar { local_id } => self.name(self.proc_data().local(local_id).name.id),
+            Expr::ParamVar { param_id } => self.name(self.proc_data().param(param_id).name.id),
+            Expr::ConstVar { const_id } => self.name(self.hir.const_data(const_id).name.id),
+            Expr::GlobalVar { global_id } => self.name(self.hir.global_data(global_id).name.id),
+            Expr::CallDirect { proc_id, input } => {
+                self.name(self.hir.proc_data(proc_id).name.id);
+                self.input(input);
+            }
+            Expr::CallPoly { proc_id, poly_call } => {
+                self.name(self.hir.proc_data(proc_id).name.id);
+                self.buf.push('(');
+                self.list(poly_call.poly_types, |p, &ty| p.ty(ty));
+                self.buf.push(')');
+                self.input(poly_call.input);
+            }
+            Expr::CallIndirect { target, indirect } => {
+                self.buf.push('(');
+                self.expr(target);
+                self.buf.push(')');
+                self.input(indirect.input);
+            }
+            Expr::Format { format } => {
+                match format.buffer {
+                    Some(buffer) => {
+                        self.buf.push_str("#format(");
+                        self.expr(buffer);
+                        self.buf.push_str(", ");
+                    }
+                    None => self.buf.push_str("#print("),
+                }
+                let _ = write!(&mut self.buf, "\"{}\"", format.c_format.escape_default());
+                for arg in format.args {
+                    self.buf.push_str(", ");
+                    self.expr(arg.expr);
+                }
+                self.buf.push(')');
+            }
+            Expr::StructInit { struct_id, input } => {
+                let data = self.hir.struct_data(struct_id);
+                self.name(data.name.id);
+                self.buf.push_str(".{ ");
+                self.list(input, |p, init| {
+                    p.name(data.field(init.field_id).name.id);
+                    p.buf.push_str(": ");
+                    p.expr(init.expr);
+                });
+                self.buf.push_str(" }");
+            }
+            Expr::VariantInit { variant_init } => {
+                self.variant(variant_init.enum_id, variant_init.variant_id);
+                self.input(variant_init.input);
+            }
+            Expr::ArrayInit { array_init } => {
+                self.buf.push('[');
+                self.list(array_init.input, |p, &expr| p.expr(expr));
+                self.buf.push(']');
+            }
+            Expr::ArrayRepeat { array_repeat } => {
+                self.buf.push('[');
+                self.expr(array_repeat.expr);
+                let _ = write!(&mut self.buf, "; {}]", array_repeat.len);
+            }
+            Expr::Deref { rhs, .. } => {
+                self.buf.push('*');
+                self.expr(rhs);
+            }
+            Expr::Address { rhs } => {
+                self.buf.push('&');
+                self.expr(rhs);
+            }
+            Expr::Unary { op, rhs } => {
+                self.buf.push_str(op.as_str());
+                self.expr(rhs);
+            }
+            Expr::Binary { op, lhs, rhs, .. } => {
+                self.buf.push('(');
+                self.expr(lhs);
+                let _ = write!(&mut self.buf, " {} ", op.as_str());
+                self.expr(rhs);
+                self.buf.push(')');
+            }
+        }
+    }
+
+    fn branch(&mut self, branch: Branch) {
+        self.expr(branch.cond);
+        match branch.cond_value {
+            Some(true) => self.buf.push_str(" /* always true */ "),
+            Some(false) => self.buf.push_str(" /* always false */ "),
+            None => self.buf.push(' '),
+        }
+        self.block(branch.block);
+    }
+
+    fn input(&mut self, input: &[&Expr]) {
+        self.buf.push('(');
+        self.list(input, |p, &expr| p.expr(expr));
+        self.buf.push(')');
+    }
+
+    fn const_eval(&mut self, eval_id: ConstEvalID) {
+        match self.hir.const_values.get(eval_id.index()) {
+            Some(&value_id) => self.const_value(self.hir.const_value(value_id)),
+            None => self.buf.push_str("<error>"),
+        }
+    }
+
+    fn const_value(&mut self, value: ConstValue) {
+        match value {
+            ConstValue::Error => self.buf.push_str("<error>"),
+            ConstValue::Null => self.buf.push_str("null"),
+            ConstValue::Bool { val } => {
+                let _ = write!(&mut self.buf, "{val}");
+            }
+            ConstValue::Int { val, neg, .. } => {
+                let sign = if neg { "-" } else { "" };
+                let _ = write!(&mut self.buf, "{sign}{val}");
+            }
+            ConstValue::IntS(val) => {
+                let _ = write!(&mut self.buf, "{val}");
+            }
+            ConstValue::IntU(val) => {
+                let _ = write!(&mut self.buf, "{val}");
+            }
+            ConstValue::Float { val, .. } => {
+                let _ = write!(&mut self.buf, "{val:?}");
+            }
+            ConstValue::Char { val } => {
+                let _ = write!(&mut self.buf, "`{}`", val.escape_default());
+            }
+            ConstValue::String { id, kind } => {
+                let string = self.hir.intern_string.get_str(id);
+                let prefix = kind.prefix();
+                let _ = write!(&mut self.buf, "{prefix}\"{}\"", string.escape_default());
+            }
+            ConstValue::Procedure { proc_id } => self.name(self.hir.proc_data(proc_id).name.id),
+            ConstValue::EnumVariant {
+                enum_id,
+                variant_id,
+            } => self.variant(enum_id, variant_id),
+            ConstValue::Struct { struct_ } => {
+                let data = self.hir.struct_data(struct_.struct_id);
+                self.name(data.name.id);
+                self.buf.push_str(".{ ");
+                for (idx, &value_id) in struct_.fields.iter().enumerate() {
+                    if idx != 0 {
+                        self.buf.push_str(", ");
+                    }
+                    self.name(data.fields[idx].name.id);
+                    self.buf.push_str(": ");
+                    self.const_value(self.hir.const_value(value_id));
+                }
+                self.buf.push_str(" }");
+            }
+            ConstValue::Array { array } => {
+                self.buf.push('[');
+                self.list(array.values, |p, &value_id| {
+                    p.const_value(p.hir.const_value(value_id))
+                });
+                self.buf.push(']');
+            }
+            ConstValue::ArrayRepeat { value, len } => {
+                self.buf.push('[');
+                self.const_value(self.hir.const_value(value));
+                let _ = write!(&mut self.buf, "; {len}]");
+            }
+        }
+    }
+
+    fn ty(&mut self, ty: Type) {
+        match ty {
+            Type::Error => self.buf.push_str("<error>"),
+            Type::Basic(basic) => self.buf.push_str(basic.as_str()),
+            Type::Enum(enum_id) => self.name(self.hir.enum_data(enum_id).name.id),
+            Type::Struct(struct_id) => self.name(self.hir.struct_data(struct_id).name.id),
+            Type::Reference(ref_ty, mutt) => {
+                self.buf.push('&');
+                self.mutt(mutt);
+                self.ty(*ref_ty);
+            }
+            Type::Procedure(proc_ty) => {
+                self.buf.push_str("proc(");
+                self.list(proc_ty.params, |p, &ty| p.ty(ty));
+                if proc_ty.is_variadic {
+                    if !proc_ty.params.is_empty() {
+                        self.buf.push_str(", ");
+                    }
+                    self.buf.push_str("..");
+                }
+                self.buf.push_str(") -> ");
+                self.ty(proc_ty.return_ty);
+            }
+            Type::ArraySlice(slice) => {
+                self.buf.push('[');
+                if slice.mutt == ast::Mut::Mutable {
+                    self.buf.push_str("mut");
+                }
+                self.buf.push(']');
+                self.ty(slice.elem_ty);
+            }
+            Type::ArrayStatic(array) => {
+                self.buf.push('[');
+                match array.len {
+                    ArrayStaticLen::Value(value_id) => {
+                        self.const_value(self.hir.const_value(value_id))
+                    }
+                    ArrayStaticLen::ConstEval(eval_id) => self.const_eval(eval_id),
+                }
+                self.buf.push(']');
+                self.ty(array.elem_ty);
+            }
+            Type::PolyProc(proc_id, poly_idx) => {
+                let poly_params = self.hir.proc_data(proc_id).poly_params;
+                match poly_params.and_then(|params| params.get(poly_idx as usize)) {
+                    Some(name) => self.name(name.id),
+                    None => self.buf.push_str("<error>"),
+                }
+            }
+        }
+    }
+
+    fn variant(&mut self, enum_id: EnumID, variant_id: EnumVariantID) {
+        let data = self.hir.enum_data(enum_id);
+        self.name(data.name.id);
+        self.buf.push('.');
+        self.name(data.variant(variant_id).name.id);
+    }
+
+    fn size_comment(&mut self, size_eval: SizeEval) {
+        if let Some(size) = size_eval.get_size() {
+            let _ = write!(
+                &mut self.buf,
+                " /* size: {}, align: {} */ ",
+                size.size(),
+                size.align()
+            );
+        } else {
+            self.buf.push(' ');
+        }
+    }
+
+    fn list<T>(&mut self, items: &[T], mut print: impl FnMut(&mut Self, &T)) {
+        for (idx, item) in items.iter().enumerate() {
+            if idx != 0 {
+                self.buf.push_str(", ");
+            }
+            print(self, item);
+        }
+    }
+
+    fn proc_data(&self) -> &'p ProcData<'hir> {
+        self.proc.expect("printing inside of a procedure")
+    }
+    fn name(&mut self, id: InternID) {
+        self.buf.push_str(self.hir.intern_name.get_str(id));
+    }
+    fn vis(&mut self, vis: ast::Vis) {
+        if vis == ast::Vis::Public {
+            self.buf.push_str("pub ");
+        }
+    }
+    fn mutt(&mut self, mutt: ast::Mut) {
+        if mutt == ast::Mut::Mutable {
+            self.buf.push_str("mut ");
+        }
+    }
+    fn tab(&mut self, indent: u32) {
+        for _ in 0..indent {
+            self.buf.push_str("    ");
+        }
+    }
+}
@@ -0,0 +1,145 @@
+use super::hir_build::{HirData, HirEmit};
+use super::pass_4;
+use crate::ast;
+use crate::hir;
+use crate::target::TargetTriple;
+
+// lightweight folding of already typechecked expressions,
+// unlike `pass_4::fold_const_expr` it never reports errors:
+// anything that cannot be folded is left for runtime
+
+/// compile-time known value of the expression
+pub fn fold_expr<'hir>(
+    hir: &HirData<'hir, '_, '_>,
+    emit: &HirEmit<'hir>,
+    expr: &hir::Expr<'hir>,
+) -> Option<hir::ConstValue<'hir>> {
+    match *expr {
+        hir::Expr::Const { value } => match value {
+            hir::ConstValue::Error => None,
+            _ => Some(value),
+        },
+        hir::Expr::ConstVar { const_id } => {
+            let data = hir.registry().const_data(const_id);
+            let (eval, _) = hir.registry().const_eval(data.value);
+            match *eval {
+                hir::ConstEval::ResolvedValue(value_id) => match emit.const_intern.get(value_id) {
+                    hir::ConstValue::Error => None,
+                    value => Some(value),
+                },
+                _ => None,
+            }
+        }
+        hir::Expr::Unary { op, rhs } => fold_unary(hir.target(), op, fold_expr(hir, emit, rhs)?),
+        hir::Expr::Binary { op, lhs, rhs, .. } => {
+            let lhs = fold_expr(hir, emit, lhs)?;
+            // `false && ..` and `true || ..` are known without the rhs
+            match (op, lhs) {
+                (ast::BinOp::LogicAnd, hir::ConstValue::Bool { val: false }) => return Some(lhs),
+                (ast::BinOp::LogicOr, hir::ConstValue::Bool { val: true }) => return Some(lhs),
+                _ => {}
+            }
+            fold_binary(hir.target(), op, lhs, fold_expr(hir, emit, rhs)?)
+        }
+        _ => None,
+    }
+}
+
+/// compile-time known value of `bool` condition
+pub fn fold_bool<'hir>(
+    hir: &HirData<'hir, '_, '_>,
+    emit: &HirEmit<'hir>,
+    expr: &hir::Expr<'hir>,
+) -> Option<bool> {
+    match fold_expr(hir, emit, expr)? {
+        hir::ConstValue::Bool { val } => Some(val),
+        _ => None,
+    }
+}
+
+/// compile-time known non negative integer, used for array bounds
+pub fn fold_usize<'hir>(
+    hir: &HirData<'hir, '_, '_>,
+    emit: &HirEmit<'hir>,
+    expr: &hir::Expr<'hir>,
+) -> Option<u64> {
+    match fold_expr(hir, emit, expr)? {
+        hir::ConstValue::Int {
+            val, neg: false, ..
+        } => Some(val),
+        _ => None,
+    }
+}
+
+fn fold_unary<'hir>(
+    target: TargetTriple,
+    op: ast::UnOp,
+    rhs: hir::ConstValue<'hir>,
+) -> Option<hir::ConstValue<'hir>> {
+    match (op, rhs) {
+        (ast::UnOp::Neg, hir::ConstValue::Int { val, neg, ty }) => {
+            pass_4::int_from_i128(target, -pass_4::int_to_i128(val, neg), ty).ok()
+        }
+        (ast::UnOp::Neg, hir::ConstValue::Float { val, ty }) => {
+            Some(hir::ConstValue::Float { val: -val, ty })
+        }
+        (ast::UnOp::BitNot, hir::ConstValue::Int { val, neg, ty }) => {
+            let value = pass_4::int_wrap(target, !pass_4::int_to_i128(val, neg), ty);
+            pass_4::int_from_i128(target, value, ty).ok()
+        }
+        (ast::UnOp::LogicNot, hir::ConstValue::Bool { val }) => {
+            Some(hir::ConstValue::Bool { val: !val })
+        }
+        _ => None,
+    }
+}
+
+fn fold_binary<'hir>(
+    target: TargetTriple,
+    op: ast::BinOp,
+    lhs: hir::ConstValue<'hir>,
+    rhs: hir::ConstValue<'hir>,
+) -> Option<hir::ConstValue<'hir>> {
+    let value = match (lhs, rhs) {
+        (
+            hir::ConstValue::Int { val, neg, ty },
+            hir::ConstValue::Int {
+                val: val2,
+                neg: neg2,
+                ..
+            },
+        ) => {
+            let lhs = pass_4::int_to_i128(val, neg);
+            let rhs = pass_4::int_to_i128(val2, neg2);
+            pass_4::fold_binary_int(target, op, lhs, rhs, ty).ok()?
+        }
+        (hir::ConstValue::Float { val, ty }, hir::ConstValue::Float { val: val2, .. }) => {
+            pass_4::fold_binary_float(op, val, val2, ty).ok()?
+        }
+        (hir::ConstValue::Bool { val }, hir::ConstValue::Bool { val: val2 }) => match op {
+            ast::BinOp::LogicAnd => hir::ConstValue::Bool { val: val && val2 },
+            ast::BinOp::LogicOr => hir::ConstValue::Bool { val: val || val2 },
+            _ => pass_4::fold_compare(op, val, val2)?,
+        },
+        (hir::ConstValue::Char { val }, hir::ConstValue::Char { val: val2 }) => {
+            pass_4::fold_compare(op, val, val2)?
+        }
+        (
+            hir::ConstValue::EnumVariant { variant_id, .. },
+            hir::ConstValue::EnumVariant {
+                variant_id: variant_id2,
+                ..
+            },
+        ) => match op {
+            ast::BinOp::IsEq | ast::BinOp::NotEq => {
+                pass_4::fold_compare(op, variant_id.index(), variant_id2.index())?
+            }
+            _ => return None,
+        },
+        _ => return None,
+    };
+    match value {
+        hir::ConstValue::Error => None,
+        _ => Some(value),
+    }
+}
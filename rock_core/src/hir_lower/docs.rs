@@ -0,0 +1,447 @@
+use super::hir_build::{HirData, HirEmit, MethodOwner};
+use super::module_graph::module_name;
+use super::pass_5;
+use crate::ast;
+use crate::hir;
+use crate::session::{ModuleID, Session};
+use crate::text::TextRange;
+
+/// public items of the root package, collected for `rock doc`
+pub struct PackageDocs {
+    pub name: String,
+    pub modules: Vec<ModuleDocs>,
+}
+
+/// `path` is the module path inside of the package: `dir/module`
+pub struct ModuleDocs {
+    pub module_id: ModuleID,
+    pub path: String,
+    pub items: Vec<ItemDocs>,
+}
+
+pub struct ItemDocs {
+    pub kind: DocItemKind,
+    pub name: String,
+    /// doc comment lines without the leading `///`
+    pub docs: String,
+    pub signature: Vec<DocSpan>,
+    /// public methods, only set for enums and structs
+    pub methods: Vec<ItemDocs>,
+}
+
+#[derive(Copy, Clone, PartialEq)]
+pub enum DocItemKind {
+    Proc,
+    Enum,
+    Struct,
+    Const,
+    Global,
+}
+
+/// part of the signature text, types defined in the package link to their item
+pub struct DocSpan {
+    pub text: String,
+    pub link: Option<DocLink>,
+}
+
+pub struct DocLink {
+    pub module_id: ModuleID,
+    pub name: String,
+}
+
+impl DocItemKind {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            DocItemKind::Proc => "proc",
+            DocItemKind::Enum => "enum",
+            DocItemKind::Struct => "struct",
+            DocItemKind::Const => "const",
+            DocItemKind::Global => "global",
+        }
+    }
+}
+
+pub fn package_docs<'hir>(
+    hir: &HirData<'hir, '_, '_>,
+    emit: &HirEmit<'hir>,
+    session: &Session,
+) -> PackageDocs {
+    let mut modules: Vec<ModuleDocs> = session
+        .module_ids()
+        .filter(|&module_id| session.module(module_id).package_id == Session::ROOT_ID)
+        .map(|module_id| {
+            let name = module_name(session, module_id);
+            let path = match name.split_once(':') {
+                Some((_, path)) => path.to_string(),
+                None => name,
+            };
+            ModuleDocs {
+                module_id,
+                path,
+                items: Vec::new(),
+            }
+        })
+        .collect();
+    let d = DocBuilder { hir, emit, session };
+
+    let mut methods: Vec<(MethodOwner, hir::ProcID)> = hir
+        .methods()
+        .map(|(owner, method)| (owner, method.proc_id))
+        .collect();
+    methods.sort_by_key(|&(_, proc_id)| proc_id.index());
+
+    for proc_id in hir.registry().proc_ids() {
+        let data = hir.registry().proc_data(proc_id);
+        let is_method = methods.iter().any(|&(_, method_id)| method_id == proc_id);
+        if data.vis == ast::Vis::Public && !is_method {
+            add_item(&mut modules, data.origin_id, d.proc_item(proc_id));
+        }
+    }
+    for enum_id in hir.registry().enum_ids() {
+        let data = hir.registry().enum_data(enum_id);
+        if data.vis == ast::Vis::Public {
+            let mut item = d.enum_item(enum_id);
+            item.methods = d.methods(&methods, MethodOwner::Enum(enum_id));
+            add_item(&mut modules, data.origin_id, item);
+        }
+    }
+    for struct_id in hir.registry().struct_ids() {
+        let data = hir.registry().struct_data(struct_id);
+        if data.vis == ast::Vis::Public {
+            let mut item = d.struct_item(struct_id);
+            item.methods = d.methods(&methods, MethodOwner::Struct(struct_id));
+            add_item(&mut modules, data.origin_id, item);
+        }
+    }
+    for const_id in hir.registry().const_ids() {
+        let data = hir.registry().const_data(const_id);
+        if data.vis == ast::Vis::Public {
+            add_item(&mut modules, data.origin_id, d.const_item(const_id));
+        }
+    }
+    for global_id in hir.registry().global_ids() {
+        let data = hir.registry().global_data(global_id);
+        if data.vis == ast::Vis::Public {
+            add_item(&mut modules, data.origin_id, d.global_item(global_id));
+        }
+    }
+
+    for module in modules.iter_mut() {
+        module.items.sort_by(|a, b| a.name.cmp(&b.name));
+    }
+    modules.sort_by(|a, b| a.path.cmp(&b.path));
+    PackageDocs {
+        name: session
+            .package(Session::ROOT_ID)
+            .manifest()
+            .package
+            .name
+            .clone(),
+        modules,
+    }
+}
+
+// items of dependency packages are not documented
+fn add_item(modules: &mut [ModuleDocs], origin_id: ModuleID, item: ItemDocs) {
+    if let Some(module) = modules
+        .iter_mut()
+        .find(|module| module.module_id == origin_id)
+    {
+        module.items.push(item);
+    }
+}
+
+struct DocBuilder<'d, 'hir, 'ast, 'intern> {
+    hir: &'d HirData<'hir, 'ast, 'intern>,
+    emit: &'d HirEmit<'hir>,
+    session: &'d Session,
+}
+
+impl<'hir> DocBuilder<'_, 'hir, '_, '_> {
+    fn proc_item(&self, proc_id: hir::ProcID) -> ItemDocs {
+        let data = self.hir.registry().proc_data(proc_id);
+        let item = self.hir.registry().proc_item(proc_id);
+        let mut spans = Vec::new();
+
+        push_text(&mut spans, "proc ");
+        push_text(&mut spans, self.hir.name_str(data.name.id));
+        if let Some(poly_params) = data.poly_params {
+            push_text(&mut spans, "[");
+            for (idx, param) in poly_params.iter().enumerate() {
+                if idx != 0 {
+                    push_text(&mut spans, ", ");
+                }
+                push_text(&mut spans, self.hir.name_str(param.id));
+            }
+            push_text(&mut spans, "]");
+        }
+        push_text(&mut spans, "(");
+        for (idx, param) in data.params.iter().enumerate() {
+            if idx != 0 {
+                push_text(&mut spans, ", ");
+            }
+            if param.mutt == ast::Mut::Mutable {
+                push_text(&mut spans, "mut ");
+            }
+            push_text(&mut spans, self.hir.name_str(param.name.id));
+            push_text(&mut spans, ": ");
+            self.type_spans(&mut spans, param.ty);
+        }
+        if item.is_variadic {
+            if !data.params.is_empty() {
+                push_text(&mut spans, ", ");
+            }
+            push_text(&mut spans, "..");
+        }
+        push_text(&mut spans, ")");
+        if item.return_ty.is_some() {
+            push_text(&mut spans, " -> ");
+            self.type_spans(&mut spans, data.return_ty);
+        }
+
+        self.item(
+            DocItemKind::Proc,
+            data.name,
+            data.origin_id,
+            item.docs,
+            spans,
+        )
+    }
+
+    fn enum_item(&self, enum_id: hir::EnumID) -> ItemDocs {
+        let data = self.hir.registry().enum_data(enum_id);
+        let item = self.hir.registry().enum_item(enum_id);
+        let mut spans = Vec::new();
+
+        push_text(&mut spans, "enum ");
+        push_text(&mut spans, self.hir.name_str(data.name.id));
+        if let Some(basic) = item.basic {
+            push_text(&mut spans, " ");
+            push_text(&mut spans, basic.as_str());
+        }
+        push_text(&mut spans, " {\n");
+        for (variant, variant_item) in data.variants.iter().zip(item.variants.iter()) {
+            push_text(&mut spans, "    ");
+            push_text(&mut spans, self.hir.name_str(variant.name.id));
+            if !variant.fields.is_empty() {
+                push_text(&mut spans, "(");
+                for (idx, field_ty) in variant.fields.iter().enumerate() {
+                    if idx != 0 {
+                        push_text(&mut spans, ", ");
+                    }
+                    self.type_spans(&mut spans, *field_ty);
+                }
+                push_text(&mut spans, ")");
+            }
+            push_text(&mut spans, " = ");
+            let value = self.source(data.origin_id, variant_item.value.0.range);
+            push_text(&mut spans, value);
+            push_text(&mut spans, ",\n");
+        }
+        push_text(&mut spans, "}");
+
+        self.item(
+            DocItemKind::Enum,
+            data.name,
+            data.origin_id,
+            item.docs,
+            spans,
+        )
+    }
+
+    // private fields are not shown
+    fn struct_item(&self, struct_id: hir::StructID) -> ItemDocs {
+        let data = self.hir.registry().struct_data(struct_id);
+        let item = self.hir.registry().struct_item(struct_id);
+        let mut spans = Vec::new();
+
+        push_text(&mut spans, "struct ");
+        push_text(&mut spans, self.hir.name_str(data.name.id));
+        push_text(&mut spans, " {\n");
+        for field in data
+            .fields
+            .iter()
+            .filter(|field| field.vis == ast::Vis::Public)
+        {
+            push_text(&mut spans, "    ");
+            push_text(&mut spans, self.hir.name_str(field.name.id));
+            push_text(&mut spans, ": ");
+            self.type_spans(&mut spans, field.ty);
+            push_text(&mut spans, ",\n");
+        }
+        push_text(&mut spans, "}");
+
+        self.item(
+            DocItemKind::Struct,
+            data.name,
+            data.origin_id,
+            item.docs,
+            spans,
+        )
+    }
+
+    fn const_item(&self, const_id: hir::ConstID) -> ItemDocs {
+        let data = self.hir.registry().const_data(const_id);
+        let item = self.hir.registry().const_item(const_id);
+        let mut spans = Vec::new();
+
+        push_text(&mut spans, "const ");
+        push_text(&mut spans, self.hir.name_str(data.name.id));
+        push_text(&mut spans, ": ");
+        self.type_spans(&mut spans, data.ty);
+        push_text(&mut spans, " = ");
+        push_text(&mut spans, self.source(data.origin_id, item.value.0.range));
+
+        self.item(
+            DocItemKind::Const,
+            data.name,
+            data.origin_id,
+            item.docs,
+            spans,
+        )
+    }
+
+    fn global_item(&self, global_id: hir::GlobalID) -> ItemDocs {
+        let data = self.hir.registry().global_data(global_id);
+        let item = self.hir.registry().global_item(global_id);
+        let mut spans = Vec::new();
+
+        push_text(&mut spans, "global ");
+        if data.mutt == ast::Mut::Mutable {
+            push_text(&mut spans, "mut ");
+        }
+        push_text(&mut spans, self.hir.name_str(data.name.id));
+        push_text(&mut spans, ": ");
+        self.type_spans(&mut spans, data.ty);
+
+        self.item(
+            DocItemKind::Global,
+            data.name,
+            data.origin_id,
+            item.docs,
+            spans,
+        )
+    }
+
+    fn methods(&self, methods: &[(MethodOwner, hir::ProcID)], owner: MethodOwner) -> Vec<ItemDocs> {
+        methods
+            .iter()
+            .filter(|&&(method_owner, proc_id)| {
+                method_owner == owner
+                    && self.hir.registry().proc_data(proc_id).vis == ast::Vis::Public
+            })
+            .map(|&(_, proc_id)| self.proc_item(proc_id))
+            .collect()
+    }
+
+    fn item(
+        &self,
+        kind: DocItemKind,
+        name: ast::Name,
+        origin_id: ModuleID,
+        docs: &[TextRange],
+        signature: Vec<DocSpan>,
+    ) -> ItemDocs {
+        let mut text = String::new();
+        for (idx, range) in docs.iter().enumerate() {
+            if idx != 0 {
+                text.push('\n');
+            }
+            let line = self.source(origin_id, *range).trim_end();
+            let line = line.strip_prefix("///").unwrap_or(line);
+            text.push_str(line.strip_prefix(' ').unwrap_or(line));
+        }
+        ItemDocs {
+            kind,
+            name: self.hir.name_str(name.id).to_string(),
+            docs: text,
+            signature,
+            methods: Vec::new(),
+        }
+    }
+
+    fn source(&self, module_id: ModuleID, range: TextRange) -> &str {
+        &self.session.module(module_id).source[range.as_usize()]
+    }
+
+    // same output as `type_format`, with enum and struct names split into linked spans
+    fn type_spans(&self, spans: &mut Vec<DocSpan>, ty: hir::Type<'hir>) {
+        match ty {
+            hir::Type::Enum(id) => {
+                let data = self.hir.registry().enum_data(id);
+                self.push_link(spans, data.name, data.origin_id, data.vis);
+            }
+            hir::Type::Struct(id) => {
+                let data = self.hir.registry().struct_data(id);
+                self.push_link(spans, data.name, data.origin_id, data.vis);
+            }
+            hir::Type::Reference(ref_ty, mutt) => {
+                push_text(spans, "&");
+                if mutt == ast::Mut::Mutable {
+                    push_text(spans, "mut ");
+                }
+                self.type_spans(spans, *ref_ty);
+            }
+            hir::Type::Procedure(proc_ty) => {
+                push_text(spans, "proc(");
+                for (idx, param) in proc_ty.params.iter().enumerate() {
+                    if idx != 0 {
+                        push_text(spans, ", ");
+                    }
+                    self.type_spans(spans, *param);
+                }
+                if proc_ty.is_variadic {
+                    push_text(spans, ", ..");
+                }
+                push_text(spans, ") -> ");
+                self.type_spans(spans, proc_ty.return_ty);
+            }
+            hir::Type::ArraySlice(slice) => {
+                push_text(spans, "[");
+                if slice.mutt == ast::Mut::Mutable {
+                    push_text(spans, "mut");
+                }
+                push_text(spans, "]");
+                self.type_spans(spans, slice.elem_ty);
+            }
+            hir::Type::ArrayStatic(array) => {
+                match pass_5::array_static_get_len(self.hir, self.emit, array.len) {
+                    Some(len) => push_text(spans, &format!("[{len}]")),
+                    None => push_text(spans, "[<unknown>]"),
+                }
+                self.type_spans(spans, array.elem_ty);
+            }
+            hir::Type::Error | hir::Type::Basic(_) | hir::Type::PolyProc(..) => {
+                push_text(spans, &pass_5::type_format(self.hir, self.emit, ty));
+            }
+        }
+    }
+
+    fn push_link(
+        &self,
+        spans: &mut Vec<DocSpan>,
+        name: ast::Name,
+        origin_id: ModuleID,
+        vis: ast::Vis,
+    ) {
+        let text = self.hir.name_str(name.id).to_string();
+        let documented = vis == ast::Vis::Public
+            && self.session.module(origin_id).package_id == Session::ROOT_ID;
+        let link = documented.then(|| DocLink {
+            module_id: origin_id,
+            name: text.clone(),
+        });
+        spans.push(DocSpan { text, link });
+    }
+}
+
+// plain text is merged into the previous span
+fn push_text(spans: &mut Vec<DocSpan>, text: &str) {
+    match spans.last_mut() {
+        Some(span) if span.link.is_none() => span.text.push_str(text),
+        _ => spans.push(DocSpan {
+            text: text.to_string(),
+            link: None,
+        }),
+    }
+}
@@ -1,12 +1,18 @@
 use crate::arena::Arena;
 use crate::ast;
-use crate::error::{DiagnosticCollection, ErrorComp, Info, ResultComp, SourceRange, WarningComp};
+use crate::bitset::BitSet;
+use crate::error::{
+    DiagnosticCollection, DiagnosticFix, ErrorCode, ErrorComp, Info, PartialComp, ResultComp,
+    SourceRange, WarningComp,
+};
 use crate::hir;
-use crate::hir::intern::ConstInternPool;
+use crate::hir::intern::{ConstInternPool, StringLitTable};
+use crate::hir::names::{NameRefKind, NameTable};
 use crate::intern::{InternID, InternPool};
 use crate::session::ModuleID;
+use crate::target::TargetTriple;
 use crate::text::TextRange;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
 /// prevents allocation of `hir::Expr::Error` during typechecking
 pub const EXPR_ERROR: &hir::Expr = &hir::Expr::Error;
@@ -15,9 +21,17 @@ pub struct HirData<'hir, 'ast, 'intern> {
     modules: Vec<Module>,
     registry: Registry<'hir, 'ast>,
     ast: ast::Ast<'ast, 'intern>,
+    impls: Vec<ImplBlock<'ast>>,
+    methods: HashMap<(MethodOwner, InternID), Method>,
+    externals: Vec<hir::ExternalProc>,
+    link_libs: Vec<InternID>,
+    target: TargetTriple,
+    tests: Option<Vec<hir::ProcID>>,
+    imports: Vec<super::ModuleImport>,
 }
 
 pub struct Module {
+    parent: Option<ModuleID>,
     symbols: HashMap<InternID, Symbol>,
 }
 
@@ -28,7 +42,34 @@ pub enum Symbol {
     Imported { kind: SymbolKind, import_range: TextRange },
 }
 
+/// procedures of the `impl` block are registered in order starting from `first_proc`
 #[derive(Copy, Clone)]
+pub struct ImplBlock<'ast> {
+    pub origin_id: ModuleID,
+    pub item: &'ast ast::ImplItem<'ast>,
+    pub first_proc: hir::ProcID,
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, Hash)]
+pub enum MethodOwner {
+    Enum(hir::EnumID),
+    Struct(hir::StructID),
+}
+
+#[derive(Copy, Clone)]
+pub struct Method {
+    pub proc_id: hir::ProcID,
+    pub receiver: MethodReceiver,
+}
+
+/// type of the first `self` parameter
+#[derive(Copy, Clone, PartialEq)]
+pub enum MethodReceiver {
+    Value,
+    Reference(ast::Mut),
+}
+
+#[derive(Copy, Clone, PartialEq)]
 pub enum SymbolKind {
     Module(ModuleID),
     Proc(hir::ProcID),
@@ -50,20 +91,77 @@ pub struct Registry<'hir, 'ast> {
     hir_consts: Vec<hir::ConstData<'hir>>,
     hir_globals: Vec<hir::GlobalData<'hir>>,
     const_evals: Vec<(hir::ConstEval<'ast>, ModuleID)>,
+    global_init_order: Vec<hir::GlobalID>,
 }
 
 pub struct HirEmit<'hir> {
     pub arena: Arena<'hir>,
     pub const_intern: ConstInternPool<'hir>,
+    pub string_lits: StringLitTable,
+    pub names: NameTable,
     diagnostics: DiagnosticCollection,
+    error_keys: HashSet<(SourceRange, String)>,
+    error_limit: Option<u32>,
+    lint_allow: BitSet,
+    poly_scope: Option<PolyScope<'hir>>,
+    pub usage: Usage,
+    pub poly_instances: Vec<PolyInstance<'hir>>,
+    pub signature_query: Option<SignatureQuery>,
+    pub inlay_hints: Option<super::InlayHints>,
+}
+
+/// call expression which signature is requested,
+/// signature is filled in when the call is typechecked
+pub struct SignatureQuery {
+    pub origin_id: ModuleID,
+    pub call_range: TextRange,
+    pub active_input: usize,
+    pub signature: Option<super::Signature>,
+}
+
+/// type parameters of the generic procedure being lowered,
+/// `poly_types` are set when lowering an instance
+#[derive(Copy, Clone)]
+pub struct PolyScope<'hir> {
+    pub proc_id: hir::ProcID,
+    pub poly_params: &'hir [ast::Name],
+    pub poly_types: Option<&'hir [hir::Type<'hir>]>,
+}
+
+/// generic procedure instantiated with concrete types, created by monomorphization
+#[derive(Copy, Clone)]
+pub struct PolyInstance<'hir> {
+    pub proc_id: hir::ProcID,
+    pub instance_id: hir::ProcID,
+    pub poly_types: &'hir [hir::Type<'hir>],
+    pub source: SourceRange,
+}
+
+/// usage flags collected during typechecking, reported by unused lints
+#[derive(Default)]
+pub struct Usage {
+    procs: Vec<bool>,
+    enums: Vec<bool>,
+    structs: Vec<bool>,
+    consts: Vec<bool>,
+    globals: Vec<bool>,
+    proc_variables: Vec<ProcVariableUsage>,
+    references: Option<Vec<(SymbolKind, SourceRange)>>,
+}
+
+pub struct ProcVariableUsage {
+    pub proc_id: hir::ProcID,
+    pub params_used: Vec<bool>,
+    pub locals_used: Vec<bool>,
 }
 
 impl<'hir, 'ast, 'intern> HirData<'hir, 'ast, 'intern> {
-    pub fn new(ast: ast::Ast<'ast, 'intern>) -> Self {
+    pub fn new(ast: ast::Ast<'ast, 'intern>, target: TargetTriple) -> Self {
         let mut modules = Vec::with_capacity(ast.modules.len());
 
         for _ in ast.modules.iter() {
             modules.push(Module {
+                parent: None,
                 symbols: HashMap::with_capacity(64),
             });
         }
@@ -72,6 +170,13 @@ impl<'hir, 'ast, 'intern> HirData<'hir, 'ast, 'intern> {
             modules,
             registry: Registry::new(ast::ItemCount::default()),
             ast,
+            impls: Vec::new(),
+            methods: HashMap::new(),
+            externals: Vec::new(),
+            link_libs: Vec::new(),
+            target,
+            tests: None,
+            imports: Vec::new(),
         }
     }
 
@@ -82,6 +187,9 @@ impl<'hir, 'ast, 'intern> HirData<'hir, 'ast, 'intern> {
         &mut self.modules[id.index()]
     }
 
+    pub fn target(&self) -> TargetTriple {
+        self.target
+    }
     pub fn registry(&self) -> &Registry<'hir, 'ast> {
         &self.registry
     }
@@ -92,9 +200,6 @@ impl<'hir, 'ast, 'intern> HirData<'hir, 'ast, 'intern> {
     pub fn name_str(&self, id: InternID) -> &str {
         self.ast.intern_name.get_str(id)
     }
-    pub fn intern_name(&mut self) -> &mut InternPool<'intern> {
-        &mut self.ast.intern_name
-    }
     pub fn intern_string(&self) -> &InternPool<'intern> {
         &self.ast.intern_string
     }
@@ -102,6 +207,63 @@ impl<'hir, 'ast, 'intern> HirData<'hir, 'ast, 'intern> {
         self.ast.modules[module_id.index()]
     }
 
+    pub fn add_impl(&mut self, impl_block: ImplBlock<'ast>) {
+        self.impls.push(impl_block);
+    }
+    pub fn impl_count(&self) -> usize {
+        self.impls.len()
+    }
+    pub fn impl_block(&self, idx: usize) -> ImplBlock<'ast> {
+        self.impls[idx]
+    }
+    pub fn add_method(&mut self, owner: MethodOwner, name_id: InternID, method: Method) {
+        self.methods.insert((owner, name_id), method);
+    }
+    pub fn method_find(&self, owner: MethodOwner, name_id: InternID) -> Option<Method> {
+        self.methods.get(&(owner, name_id)).copied()
+    }
+    /// in unspecified order
+    pub fn methods(&self) -> impl Iterator<Item = (MethodOwner, Method)> + '_ {
+        self.methods
+            .iter()
+            .map(|(&(owner, _), &method)| (owner, method))
+    }
+    pub fn add_external(&mut self, external: hir::ExternalProc) {
+        self.externals.push(external);
+    }
+    pub fn add_link_lib(&mut self, name_id: InternID) {
+        if !self.link_libs.contains(&name_id) {
+            self.link_libs.push(name_id);
+        }
+    }
+    pub fn set_tests(&mut self, tests: Vec<hir::ProcID>) {
+        self.tests = Some(tests);
+    }
+    pub fn add_import(&mut self, import: super::ModuleImport) {
+        self.imports.push(import);
+    }
+    pub fn imports(&self) -> &[super::ModuleImport] {
+        &self.imports
+    }
+    pub fn take_imports(&mut self) -> Vec<super::ModuleImport> {
+        std::mem::take(&mut self.imports)
+    }
+
+    pub fn set_module_parent(&mut self, origin_id: ModuleID, parent_id: ModuleID) {
+        self.module_mut(origin_id).parent = Some(parent_id);
+    }
+    /// private items are visible in the module where they are defined and its submodules
+    pub fn module_in_subtree(&self, origin_id: ModuleID, target_id: ModuleID) -> bool {
+        let mut module_id = Some(origin_id);
+        while let Some(id) = module_id {
+            if id == target_id {
+                return true;
+            }
+            module_id = self.module(id).parent;
+        }
+        false
+    }
+
     pub fn add_symbol(&mut self, origin_id: ModuleID, id: InternID, symbol: Symbol) {
         let origin = self.module_mut(origin_id);
         origin.symbols.insert(id, symbol);
@@ -116,6 +278,15 @@ impl<'hir, 'ast, 'intern> HirData<'hir, 'ast, 'intern> {
         }
     }
 
+    pub fn symbol_imported(&self, origin_id: ModuleID, id: InternID) -> Option<SymbolKind> {
+        let origin = self.module(origin_id);
+
+        match origin.symbols.get(&id).cloned() {
+            Some(Symbol::Imported { kind, .. }) => Some(kind),
+            _ => None,
+        }
+    }
+
     pub fn symbol_in_scope_source(&self, origin_id: ModuleID, id: InternID) -> Option<SourceRange> {
         let origin = self.module(origin_id);
         let symbol = origin.symbols.get(&id).cloned()?;
@@ -142,15 +313,10 @@ impl<'hir, 'ast, 'intern> HirData<'hir, 'ast, 'intern> {
             Some(Symbol::Defined { kind }) => {
                 let source = SourceRange::new(target_id, kind.name_range(&self.registry));
 
-                let vis = if origin_id == target_id {
-                    ast::Vis::Public
-                } else {
-                    kind.vis(&self.registry)
-                };
-
-                return match vis {
+                return match self.symbol_vis(origin_id, target_id, kind) {
                     ast::Vis::Public => Ok((kind, source)),
                     ast::Vis::Private => Err(ErrorComp::new(
+                        ErrorCode::PrivateItem,
                         format!(
                             "{} `{}` is private",
                             kind.kind_name(),
@@ -167,16 +333,59 @@ impl<'hir, 'ast, 'intern> HirData<'hir, 'ast, 'intern> {
                 if origin_id == target_id {
                     return Ok((kind, source));
                 }
+                return Err(ErrorComp::new(
+                    ErrorCode::ImportNotDefinedHere,
+                    format!(
+                        "{} `{}` is imported into this module, import it from where it is defined",
+                        kind.kind_name(),
+                        self.name_str(name.id)
+                    ),
+                    SourceRange::new(origin_id, name.range),
+                    Info::new("imported here", source),
+                ));
             }
             None => {}
         }
 
         Err(ErrorComp::new(
+            ErrorCode::NameNotFound,
             format!("name `{}` is not found in module", self.name_str(name.id)),
             SourceRange::new(origin_id, name.range),
             None,
         ))
     }
+
+    /// symbols defined in `target_id` that `origin_id` can import, in definition order
+    pub fn symbols_visible(
+        &self,
+        origin_id: ModuleID,
+        target_id: ModuleID,
+    ) -> Vec<(InternID, SymbolKind)> {
+        let target = self.module(target_id);
+        let mut symbols: Vec<(InternID, SymbolKind)> = target
+            .symbols
+            .iter()
+            .filter_map(|(&id, &symbol)| match symbol {
+                Symbol::Defined { kind }
+                    if self.symbol_vis(origin_id, target_id, kind) == ast::Vis::Public =>
+                {
+                    Some((id, kind))
+                }
+                _ => None,
+            })
+            .collect();
+        symbols.sort_by_key(|(_, kind)| kind.name_range(&self.registry).start());
+        symbols
+    }
+
+    // items are public within their own module subtree
+    fn symbol_vis(&self, origin_id: ModuleID, target_id: ModuleID, kind: SymbolKind) -> ast::Vis {
+        if self.module_in_subtree(origin_id, target_id) {
+            ast::Vis::Public
+        } else {
+            kind.vis(&self.registry)
+        }
+    }
 }
 
 impl SymbolKind {
@@ -191,6 +400,17 @@ impl SymbolKind {
         }
     }
 
+    pub fn name_ref_kind(self) -> NameRefKind {
+        match self {
+            SymbolKind::Module(id) => NameRefKind::Module(id),
+            SymbolKind::Proc(id) => NameRefKind::Proc(id),
+            SymbolKind::Enum(id) => NameRefKind::Enum(id),
+            SymbolKind::Struct(id) => NameRefKind::Struct(id),
+            SymbolKind::Const(id) => NameRefKind::Const(id),
+            SymbolKind::Global(id) => NameRefKind::Global(id),
+        }
+    }
+
     fn vis(self, registry: &Registry) -> ast::Vis {
         match self {
             SymbolKind::Module(..) => unreachable!(),
@@ -229,6 +449,7 @@ impl<'hir, 'ast> Registry<'hir, 'ast> {
             hir_consts: Vec::with_capacity(total.consts as usize),
             hir_globals: Vec::with_capacity(total.globals as usize),
             const_evals: Vec::with_capacity(const_eval_estimate as usize),
+            global_init_order: Vec::with_capacity(total.globals as usize),
         }
     }
 
@@ -373,25 +594,149 @@ impl<'hir, 'ast> Registry<'hir, 'ast> {
     ) -> &mut (hir::ConstEval<'ast>, ModuleID) {
         &mut self.const_evals[id.index()]
     }
+    pub fn add_global_init(&mut self, id: hir::GlobalID) {
+        self.global_init_order.push(id);
+    }
+}
+
+impl Usage {
+    pub fn mark_symbol(&mut self, kind: SymbolKind) {
+        match kind {
+            SymbolKind::Module(_) => {}
+            SymbolKind::Proc(id) => Usage::mark(&mut self.procs, id.index()),
+            SymbolKind::Enum(id) => Usage::mark(&mut self.enums, id.index()),
+            SymbolKind::Struct(id) => Usage::mark(&mut self.structs, id.index()),
+            SymbolKind::Const(id) => Usage::mark(&mut self.consts, id.index()),
+            SymbolKind::Global(id) => Usage::mark(&mut self.globals, id.index()),
+        }
+    }
+
+    pub fn symbol_used(&self, kind: SymbolKind) -> bool {
+        let (flags, index) = match kind {
+            SymbolKind::Module(_) => return true,
+            SymbolKind::Proc(id) => (&self.procs, id.index()),
+            SymbolKind::Enum(id) => (&self.enums, id.index()),
+            SymbolKind::Struct(id) => (&self.structs, id.index()),
+            SymbolKind::Const(id) => (&self.consts, id.index()),
+            SymbolKind::Global(id) => (&self.globals, id.index()),
+        };
+        flags.get(index).copied().unwrap_or(false)
+    }
+
+    /// references are only recorded for the symbol index
+    pub fn record_references(&mut self) {
+        self.references = Some(Vec::new());
+    }
+    pub fn add_reference(&mut self, kind: SymbolKind, source: SourceRange) {
+        if let Some(references) = &mut self.references {
+            references.push((kind, source));
+        }
+    }
+    pub fn take_references(&mut self) -> Option<Vec<(SymbolKind, SourceRange)>> {
+        self.references.take()
+    }
+
+    pub fn add_proc_variables(&mut self, variables: ProcVariableUsage) {
+        self.proc_variables.push(variables);
+    }
+    pub fn take_proc_variables(&mut self) -> Vec<ProcVariableUsage> {
+        std::mem::take(&mut self.proc_variables)
+    }
+
+    fn mark(flags: &mut Vec<bool>, index: usize) {
+        if index >= flags.len() {
+            flags.resize(index + 1, false);
+        }
+        flags[index] = true;
+    }
 }
 
 impl<'hir> HirEmit<'hir> {
-    pub fn new() -> HirEmit<'hir> {
+    pub fn new(error_limit: Option<u32>) -> HirEmit<'hir> {
         HirEmit {
             arena: Arena::new(),
             const_intern: ConstInternPool::new(),
+            string_lits: StringLitTable::new(),
+            names: NameTable::new(),
             diagnostics: DiagnosticCollection::new(),
+            error_keys: HashSet::new(),
+            error_limit,
+            lint_allow: BitSet::EMPTY,
+            poly_scope: None,
+            usage: Usage::default(),
+            poly_instances: Vec::new(),
+            signature_query: None,
+            inlay_hints: None,
         }
     }
 
-    #[inline]
+    /// identical errors at the same source range are reported once,
+    /// errors past the error limit are dropped
     pub fn error(&mut self, error: ErrorComp) {
+        if self.error_limit_reached() {
+            return;
+        }
+        let diagnostic = error.diagnostic();
+        if let Some(source) = diagnostic.main_source() {
+            let key = (source, diagnostic.message().as_str().to_string());
+            if !self.error_keys.insert(key) {
+                return;
+            }
+        }
+
         self.diagnostics.error(error);
+        if let Some(limit) = self.error_limit {
+            if self.error_limit_reached() {
+                self.diagnostics.warning(WarningComp::message(format!(
+                    "error limit of {limit} reached, further errors are not reported"
+                )));
+            }
+        }
+    }
+    pub fn signature_requested(&self, origin_id: ModuleID, call_range: TextRange) -> bool {
+        match &self.signature_query {
+            Some(query) => query.origin_id == origin_id && query.call_range == call_range,
+            None => false,
+        }
+    }
+    /// attaches a fix to the last error, if it was reported at `source`
+    pub fn error_fix(&mut self, source: SourceRange, fix: DiagnosticFix) {
+        if let Some(error) = self.diagnostics.last_error_mut() {
+            if error.diagnostic().main_source() == Some(source) {
+                error.add_fix(fix);
+            }
+        }
+    }
+    #[inline]
+    pub fn error_limit_reached(&self) -> bool {
+        match self.error_limit {
+            Some(limit) => self.diagnostics.errors().len() >= limit as usize,
+            None => false,
+        }
     }
     #[inline]
     pub fn warning(&mut self, warning: WarningComp) {
+        if let Some(code) = warning.code() {
+            if self.lint_allow.contains(code) {
+                return;
+            }
+        }
         self.diagnostics.warning(warning);
     }
+    /// warnings with codes from `#[allow(..)]` of the current item are not emitted
+    #[inline]
+    pub fn set_lint_allow(&mut self, lint_allow: BitSet) {
+        self.lint_allow = lint_allow;
+    }
+    /// type parameter names resolve to `hir::Type::PolyProc` while scope is set
+    #[inline]
+    pub fn set_poly_scope(&mut self, poly_scope: Option<PolyScope<'hir>>) {
+        self.poly_scope = poly_scope;
+    }
+    #[inline]
+    pub fn poly_scope(&self) -> Option<PolyScope<'hir>> {
+        self.poly_scope
+    }
     #[inline]
     pub fn error_count(&self) -> usize {
         self.diagnostics.errors().len()
@@ -401,52 +746,63 @@ impl<'hir> HirEmit<'hir> {
         self.error_count() > error_count
     }
 
+    /// diagnostics of passes that ran so far, without emitting the hir
+    pub fn emit_value<T>(self, value: T) -> ResultComp<T> {
+        ResultComp::new(value, self.diagnostics)
+    }
+
+    /// hir is always produced, unresolved constants are replaced by `ConstValue::Error`
     pub fn emit<'ast, 'intern: 'hir>(
-        self,
+        mut self,
         hir: HirData<'hir, 'ast, 'intern>,
-    ) -> ResultComp<hir::Hir<'hir>> {
-        if !self.diagnostics.errors().is_empty() {
-            return ResultComp::Err(self.diagnostics);
-        }
-
+    ) -> PartialComp<hir::Hir<'hir>> {
+        // unresolved constants are expected after errors, otherwise it's a compiler bug
+        let report_unresolved = !self.diagnostics.has_errors();
         let mut const_values = Vec::with_capacity(hir.registry.const_evals.len());
-        let mut errors = Vec::new();
 
         for (eval, origin_id) in hir.registry.const_evals.iter() {
             match *eval {
                 hir::ConstEval::Unresolved(expr) => {
-                    errors.push(ErrorComp::new(
-                        "internal: trying to emit hir with ConstEval::Unresolved expression",
-                        SourceRange::new(*origin_id, expr.0.range),
-                        None,
-                    ));
+                    if report_unresolved {
+                        self.diagnostics.error(ErrorComp::new(
+                            ErrorCode::Internal,
+                            "internal: trying to emit hir with ConstEval::Unresolved expression",
+                            SourceRange::new(*origin_id, expr.0.range),
+                            None,
+                        ));
+                    }
+                    const_values.push(self.const_intern.intern(hir::ConstValue::Error));
                 }
                 hir::ConstEval::ResolvedError => {
-                    errors.push(ErrorComp::message(
-                        "internal: trying to emit hir with ConstEval::ResolvedError expression",
-                    ));
+                    if report_unresolved {
+                        self.diagnostics.error(ErrorComp::message(
+                            "internal: trying to emit hir with ConstEval::ResolvedError expression",
+                        ));
+                    }
+                    const_values.push(self.const_intern.intern(hir::ConstValue::Error));
                 }
                 hir::ConstEval::ResolvedValue(value_id) => const_values.push(value_id),
             }
         }
 
-        if errors.is_empty() {
-            let hir = hir::Hir {
-                arena: self.arena,
-                intern_name: hir.ast.intern_name,
-                intern_string: hir.ast.intern_string,
-                string_is_cstr: hir.ast.string_is_cstr,
-                const_intern: self.const_intern,
-                procs: hir.registry.hir_procs,
-                enums: hir.registry.hir_enums,
-                structs: hir.registry.hir_structs,
-                consts: hir.registry.hir_consts,
-                globals: hir.registry.hir_globals,
-                const_values,
-            };
-            ResultComp::Ok((hir, self.diagnostics.warnings_moveout()))
-        } else {
-            ResultComp::Err(self.diagnostics.join_errors(errors))
-        }
+        let hir = hir::Hir {
+            arena: self.arena,
+            intern_name: hir.ast.intern_name,
+            intern_string: hir.ast.intern_string,
+            string_lits: self.string_lits,
+            const_intern: self.const_intern,
+            procs: hir.registry.hir_procs,
+            enums: hir.registry.hir_enums,
+            structs: hir.registry.hir_structs,
+            consts: hir.registry.hir_consts,
+            globals: hir.registry.hir_globals,
+            global_init_order: hir.registry.global_init_order,
+            const_values,
+            externals: hir.externals,
+            link_libs: hir.link_libs,
+            tests: hir.tests,
+            names: self.names.finish(),
+        };
+        PartialComp::new(hir, self.diagnostics)
     }
 }
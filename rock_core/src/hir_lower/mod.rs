@@ -1,29 +1,245 @@
+mod const_fold;
+mod docs;
 mod hir_build;
+mod module_graph;
 mod pass_1;
 mod pass_2;
 mod pass_3;
 mod pass_4;
 mod pass_5;
 mod pass_6;
+mod pass_7;
+mod pass_8;
+mod place;
 mod proc_scope;
+mod symbol_index;
 
 use crate::ast;
-use crate::error::ResultComp;
+use crate::ast_parse;
+use crate::driver::CancelToken;
+use crate::error::{DiagnosticCollection, ErrorComp, PartialComp, ResultComp};
 use crate::hir;
-use crate::session::Session;
-use hir_build::{HirData, HirEmit};
+use crate::index::FileIndex;
+use crate::intern::InternPool;
+use crate::session::{ModuleID, Session};
+use crate::target::TargetTriple;
+use crate::text::{TextOffset, TextRange};
+use crate::timer::{Timer, Timings};
+use hir_build::{HirData, HirEmit, SignatureQuery};
+use std::path::PathBuf;
 
+pub use crate::hir::print::hir_print;
+pub use docs::{DocItemKind, DocLink, DocSpan, ItemDocs, ModuleDocs, PackageDocs};
+pub use module_graph::{module_name, ModuleGraph, ModuleImport};
+
+/// inlay hints of each module, sorted by offset
+#[derive(Default)]
+pub struct InlayHints {
+    modules: Vec<Vec<InlayHint>>,
+}
+
+pub struct InlayHint {
+    pub offset: TextOffset,
+    pub kind: InlayHintKind,
+    pub label: String,
+}
+
+#[derive(Copy, Clone, PartialEq)]
+pub enum InlayHintKind {
+    Type,
+    Parameter,
+}
+
+/// formatted procedure signature, `params` are ranges in the `label`
+pub struct Signature {
+    pub label: String,
+    pub params: Vec<TextRange>,
+    pub active_param: Option<usize>,
+}
+
+/// hir is returned even when errors were reported, it must not be used for codegen
 pub fn check<'hir, 'ast, 'intern: 'hir>(
     ast: ast::Ast<'ast, 'intern>,
     session: &Session,
-) -> ResultComp<hir::Hir<'hir>> {
-    let mut hir = HirData::new(ast);
-    let mut emit = HirEmit::new();
+    error_limit: Option<u32>,
+) -> PartialComp<hir::Hir<'hir>> {
+    let cancel = CancelToken::new();
+    let mut timings = Timings::new();
+    check_impl(
+        ast,
+        session,
+        error_limit,
+        false,
+        false,
+        false,
+        &cancel,
+        &mut timings,
+    )
+    .0
+}
+
+/// parse and check in-memory modules of a single library package,
+/// used by diagnostic tests, current directory and file system are not accessed
+pub fn check_virtual(
+    files: &[(PathBuf, String)],
+    target: TargetTriple,
+) -> Result<(Session, DiagnosticCollection), ErrorComp> {
+    let (session, intern_name) = Session::new_virtual(PathBuf::new(), target, files)?;
+    let diagnostics = check_virtual_impl(&session, intern_name);
+    Ok((session, diagnostics))
+}
+
+fn check_virtual_impl(session: &Session, intern_name: InternPool) -> DiagnosticCollection {
+    let mut timings = Timings::new();
+    let parsed = ast_parse::parse(session, intern_name, ast_parse::NESTING_LIMIT, &mut timings);
+    if parsed.has_errors() {
+        return parsed.diagnostics;
+    }
+    let checked = check(parsed.value, session, None);
+    parsed.diagnostics.join_collection(checked.diagnostics)
+}
+
+pub(crate) fn check_impl<'hir, 'ast, 'intern: 'hir>(
+    ast: ast::Ast<'ast, 'intern>,
+    session: &Session,
+    error_limit: Option<u32>,
+    tests: bool,
+    hints: bool,
+    index: bool,
+    cancel: &CancelToken,
+    timings: &mut Timings,
+) -> (
+    PartialComp<hir::Hir<'hir>>,
+    Option<InlayHints>,
+    Option<Vec<FileIndex>>,
+) {
+    //@passes are serial, procedure typechecking could run in parallel
+    // once `HirEmit` arena and usage tracking are split per thread 16.10.26
+    let mut hir = HirData::new(ast, session.target());
+    let mut emit = HirEmit::new(error_limit);
+    if hints {
+        emit.inlay_hints = Some(InlayHints::default());
+    }
+    if index {
+        emit.usage.record_references();
+    }
+    let mut timer = Timer::new();
     pass_1::populate_scopes(&mut hir, &mut emit, session);
+    timer.lap(timings, "populate scopes", Some(emit.arena.mem_usage()));
     pass_2::resolve_imports(&mut hir, &mut emit, session);
+    timer.lap(timings, "resolve imports", Some(emit.arena.mem_usage()));
     pass_3::process_items(&mut hir, &mut emit);
+    timer.lap(timings, "process items", Some(emit.arena.mem_usage()));
     pass_4::resolve_const_dependencies(&mut hir, &mut emit);
+    timer.lap(timings, "resolve constants", Some(emit.arena.mem_usage()));
+    if emit.error_limit_reached() || cancel.is_cancelled() {
+        return (emit.emit(hir), None, None);
+    }
     pass_5::typecheck_procedures(&mut hir, &mut emit);
+    timer.lap(timings, "typecheck", Some(emit.arena.mem_usage()));
+    // generic instances are typechecked again in pass_8, hints and references are taken before that
+    let hints = emit.inlay_hints.take().map(InlayHints::finish);
+    let index = emit
+        .usage
+        .take_references()
+        .map(|references| symbol_index::collect(&hir, session, references));
+    if emit.error_limit_reached() || cancel.is_cancelled() {
+        return (emit.emit(hir), hints, index);
+    }
     pass_6::check_entry_point(&mut hir, &mut emit, session);
-    emit.emit(hir)
+    if tests {
+        pass_6::collect_tests(&mut hir, session);
+    }
+    timer.lap(timings, "entry point", None);
+    pass_7::check_unused(&hir, &mut emit, session);
+    timer.lap(timings, "check unused", None);
+    pass_8::monomorphize_procedures(&mut hir, &mut emit);
+    timer.lap(timings, "monomorphize", Some(emit.arena.mem_usage()));
+    let result = emit.emit(hir);
+    timer.lap(timings, "emit hir", None);
+    (result, hints, index)
+}
+
+/// module import graph of the whole session, only scopes and imports are resolved
+pub fn module_graph<'hir, 'ast, 'intern: 'hir>(
+    ast: ast::Ast<'ast, 'intern>,
+    session: &Session,
+) -> ResultComp<ModuleGraph> {
+    let mut hir = HirData::new(ast, session.target());
+    let mut emit: HirEmit<'hir> = HirEmit::new(None);
+    pass_1::populate_scopes(&mut hir, &mut emit, session);
+    pass_2::resolve_imports(&mut hir, &mut emit, session);
+
+    let graph = ModuleGraph::new(session, hir.take_imports());
+    emit.emit_value(graph)
+}
+
+/// public items of the root package, procedure bodies are not typechecked
+pub fn package_docs<'hir, 'ast, 'intern: 'hir>(
+    ast: ast::Ast<'ast, 'intern>,
+    session: &Session,
+) -> ResultComp<PackageDocs> {
+    let mut hir = HirData::new(ast, session.target());
+    let mut emit: HirEmit<'hir> = HirEmit::new(None);
+    pass_1::populate_scopes(&mut hir, &mut emit, session);
+    pass_2::resolve_imports(&mut hir, &mut emit, session);
+    pass_3::process_items(&mut hir, &mut emit);
+    pass_4::resolve_const_dependencies(&mut hir, &mut emit);
+
+    let docs = docs::package_docs(&hir, &emit, session);
+    emit.emit_value(docs)
+}
+
+/// signature of the innermost call around `offset`, procedures are typechecked to resolve the callee
+pub fn signature_help<'hir, 'ast, 'intern: 'hir>(
+    ast: ast::Ast<'ast, 'intern>,
+    session: &Session,
+    module_id: ModuleID,
+    offset: TextOffset,
+) -> Option<Signature> {
+    let call = ast::query::call_at(ast.modules[module_id.index()], offset)?;
+
+    let mut hir = HirData::new(ast, session.target());
+    let mut emit = HirEmit::new(None);
+    emit.signature_query = Some(SignatureQuery {
+        origin_id: module_id,
+        call_range: call.range,
+        active_input: call.active_input,
+        signature: None,
+    });
+    pass_1::populate_scopes(&mut hir, &mut emit, session);
+    pass_2::resolve_imports(&mut hir, &mut emit, session);
+    pass_3::process_items(&mut hir, &mut emit);
+    pass_4::resolve_const_dependencies(&mut hir, &mut emit);
+    pass_5::typecheck_procedures(&mut hir, &mut emit);
+
+    emit.signature_query.take()?.signature
+}
+
+impl InlayHints {
+    fn add(&mut self, module_id: ModuleID, hint: InlayHint) {
+        if module_id.index() >= self.modules.len() {
+            self.modules.resize_with(module_id.index() + 1, Vec::new);
+        }
+        self.modules[module_id.index()].push(hint);
+    }
+
+    // hints are added in typecheck order, values are checked before their locals
+    fn finish(mut self) -> InlayHints {
+        for hints in self.modules.iter_mut() {
+            hints.sort_by_key(|hint| hint.offset);
+        }
+        self
+    }
+
+    /// hints with offset inside of the `range`
+    pub fn in_range(&self, module_id: ModuleID, range: TextRange) -> &[InlayHint] {
+        let hints = match self.modules.get(module_id.index()) {
+            Some(hints) => hints,
+            None => return &[],
+        };
+        let start = hints.partition_point(|hint| hint.offset < range.start());
+        let end = hints.partition_point(|hint| hint.offset <= range.end());
+        &hints[start..end]
+    }
 }
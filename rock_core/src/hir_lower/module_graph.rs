@@ -0,0 +1,181 @@
+use crate::session::{ModuleID, Session};
+use crate::text::TextRange;
+
+/// modules connected by resolved imports, collected in pass_2
+pub struct ModuleGraph {
+    pub imports: Vec<ModuleImport>,
+    /// each cycle is listed once, starting from its lowest `ModuleID`
+    pub cycles: Vec<Vec<ModuleID>>,
+}
+
+/// `range` covers the import path in the `origin_id` module
+#[derive(Copy, Clone)]
+pub struct ModuleImport {
+    pub origin_id: ModuleID,
+    pub target_id: ModuleID,
+    pub range: TextRange,
+}
+
+impl ModuleGraph {
+    pub fn new(session: &Session, imports: Vec<ModuleImport>) -> ModuleGraph {
+        let cycles = find_import_cycles(session, &imports);
+        ModuleGraph { imports, cycles }
+    }
+
+    /// imports from `origin_id` to `target_id`, same module can be imported more than once
+    pub fn imports_between(
+        &self,
+        origin_id: ModuleID,
+        target_id: ModuleID,
+    ) -> impl Iterator<Item = &ModuleImport> {
+        self.imports
+            .iter()
+            .filter(move |import| import.origin_id == origin_id && import.target_id == target_id)
+    }
+
+    pub fn in_cycle(&self, origin_id: ModuleID, target_id: ModuleID) -> bool {
+        self.cycles.iter().any(|cycle| {
+            let len = cycle.len();
+            (0..len).any(|idx| cycle[idx] == origin_id && cycle[(idx + 1) % len] == target_id)
+        })
+    }
+}
+
+/// module name as written in imports: `package:dir/module`
+pub fn module_name(session: &Session, module_id: ModuleID) -> String {
+    let module = session.module(module_id);
+    let package = session.package(module.package_id);
+    let path = module.path.with_extension("");
+    let path = path.strip_prefix(&package.src.path).unwrap_or(&path);
+
+    let mut name = package.manifest().package.name.clone();
+    name.push(':');
+    for (idx, component) in path.components().enumerate() {
+        if idx != 0 {
+            name.push('/');
+        }
+        name.push_str(&component.as_os_str().to_string_lossy());
+    }
+    name
+}
+
+/// each cycle is listed once, starting from its lowest `ModuleID`
+pub fn find_import_cycles(session: &Session, imports: &[ModuleImport]) -> Vec<Vec<ModuleID>> {
+    let module_count = session.module_ids().count();
+    let mut edges = vec![Vec::new(); module_count];
+    for import in imports.iter() {
+        let targets = &mut edges[import.origin_id.index()];
+        if !targets.contains(&import.target_id) {
+            targets.push(import.target_id);
+        }
+    }
+    find_cycles(&edges)
+}
+
+// strongly connected components are found first (tarjan),
+// then one cycle is traced inside of each component with more than one module
+fn find_cycles(edges: &[Vec<ModuleID>]) -> Vec<Vec<ModuleID>> {
+    let mut tarjan = Tarjan {
+        edges,
+        index: vec![None; edges.len()],
+        low_link: vec![0; edges.len()],
+        on_stack: vec![false; edges.len()],
+        stack: Vec::new(),
+        next_index: 0,
+        components: Vec::new(),
+    };
+    for module_idx in 0..edges.len() {
+        if tarjan.index[module_idx].is_none() {
+            tarjan.connect(module_idx);
+        }
+    }
+
+    let mut cycles = Vec::new();
+    for component in tarjan.components {
+        if component.len() > 1 {
+            cycles.push(trace_cycle(edges, &component));
+        }
+    }
+    cycles.sort_by_key(|cycle| cycle[0].index());
+    cycles
+}
+
+struct Tarjan<'edges> {
+    edges: &'edges [Vec<ModuleID>],
+    index: Vec<Option<u32>>,
+    low_link: Vec<u32>,
+    on_stack: Vec<bool>,
+    stack: Vec<usize>,
+    next_index: u32,
+    components: Vec<Vec<usize>>,
+}
+
+impl Tarjan<'_> {
+    //@recursive, deep import chains could overflow the stack
+    fn connect(&mut self, module_idx: usize) {
+        self.index[module_idx] = Some(self.next_index);
+        self.low_link[module_idx] = self.next_index;
+        self.next_index += 1;
+        self.stack.push(module_idx);
+        self.on_stack[module_idx] = true;
+
+        let edges = self.edges;
+        for target_id in edges[module_idx].iter() {
+            let target_idx = target_id.index();
+            match self.index[target_idx] {
+                None => {
+                    self.connect(target_idx);
+                    self.low_link[module_idx] =
+                        self.low_link[module_idx].min(self.low_link[target_idx]);
+                }
+                Some(target_index) if self.on_stack[target_idx] => {
+                    self.low_link[module_idx] = self.low_link[module_idx].min(target_index);
+                }
+                Some(_) => {}
+            }
+        }
+
+        if Some(self.low_link[module_idx]) == self.index[module_idx] {
+            let mut component = Vec::new();
+            while let Some(member_idx) = self.stack.pop() {
+                self.on_stack[member_idx] = false;
+                component.push(member_idx);
+                if member_idx == module_idx {
+                    break;
+                }
+            }
+            self.components.push(component);
+        }
+    }
+}
+
+// shortest path from the lowest module back to itself, staying inside the component
+fn trace_cycle(edges: &[Vec<ModuleID>], component: &[usize]) -> Vec<ModuleID> {
+    let start = *component.iter().min().unwrap();
+    let mut prev: Vec<Option<usize>> = vec![None; edges.len()];
+    let mut queue = std::collections::VecDeque::from([start]);
+
+    'search: while let Some(module_idx) = queue.pop_front() {
+        for target_id in edges[module_idx].iter() {
+            let target_idx = target_id.index();
+            if !component.contains(&target_idx) || prev[target_idx].is_some() {
+                continue;
+            }
+            prev[target_idx] = Some(module_idx);
+            if target_idx == start {
+                break 'search;
+            }
+            queue.push_back(target_idx);
+        }
+    }
+
+    let mut cycle = Vec::new();
+    let mut module_idx = prev[start].unwrap();
+    while module_idx != start {
+        cycle.push(ModuleID::new(module_idx));
+        module_idx = prev[module_idx].unwrap();
+    }
+    cycle.push(ModuleID::new(start));
+    cycle.reverse();
+    cycle
+}
@@ -1,32 +1,83 @@
-use super::hir_build::{HirData, HirEmit, Symbol, SymbolKind};
+use super::hir_build::{HirData, HirEmit, ImplBlock, Symbol, SymbolKind};
 use crate::ast;
 use crate::bitset::BitSet;
-use crate::error::{ErrorComp, Info, SourceRange, WarningComp};
+use crate::error::{ErrorCode, ErrorComp, Info, SourceRange, WarningCode, WarningComp};
+use crate::intern::{self, InternID};
 use crate::hir;
 use crate::hir::{GlobalFlag, ProcFlag};
 use crate::session::{ModuleID, Session};
+use std::collections::HashMap;
 
 pub fn populate_scopes<'hir>(
     hir: &mut HirData<'hir, '_, '_>,
     emit: &mut HirEmit<'hir>,
     session: &Session,
 ) {
+    set_module_parents(hir, session);
     for origin_id in session.module_ids() {
-        add_module_items(hir, emit, origin_id);
+        add_module_items(hir, emit, session, origin_id);
+    }
+}
+
+// submodules of `foo.rock` are located in `foo/` directory next to it
+fn set_module_parents(hir: &mut HirData, session: &Session) {
+    let mut module_paths = HashMap::new();
+    for module_id in session.module_ids() {
+        module_paths.insert(session.module(module_id).path.as_path(), module_id);
+    }
+
+    for origin_id in session.module_ids() {
+        let module = session.module(origin_id);
+        let parent_path = match module.path.parent() {
+            Some(dir) => dir.with_extension("rock"),
+            None => continue,
+        };
+        if let Some(parent_id) = module_paths.get(parent_path.as_path()).copied() {
+            hir.set_module_parent(origin_id, parent_id);
+        }
     }
 }
 
 fn add_module_items<'hir>(
     hir: &mut HirData<'hir, '_, '_>,
     emit: &mut HirEmit<'hir>,
+    session: &Session,
     origin_id: ModuleID,
 ) {
+    let package_id = session.module(origin_id).package_id;
+    let manifest = session.package(package_id).manifest();
+    let allow_generics = manifest.build.unstable_generics == Some(true);
+
     let module_ast = hir.ast_module(origin_id);
     for item in module_ast.items.iter().copied() {
+        let attrs = match item {
+            ast::Item::Proc(item) => item.attrs,
+            ast::Item::Enum(item) => item.attrs,
+            ast::Item::Struct(item) => item.attrs,
+            ast::Item::Const(item) => item.attrs,
+            ast::Item::Global(item) => item.attrs,
+            ast::Item::Import(item) => item.attrs,
+            ast::Item::Impl(item) => item.attrs,
+        };
+        check_attribute_args(hir, emit, origin_id, attrs);
+        emit.set_lint_allow(lint_allow_set(hir, attrs));
+
+        let name = match item {
+            ast::Item::Proc(item) => Some(item.name),
+            ast::Item::Enum(item) => Some(item.name),
+            ast::Item::Struct(item) => Some(item.name),
+            ast::Item::Const(item) => Some(item.name),
+            ast::Item::Global(item) => Some(item.name),
+            ast::Item::Import(_) | ast::Item::Impl(_) => None,
+        };
+        if let Some(name) = name {
+            check_reserved_name(hir, emit, origin_id, name);
+        }
+
         match item {
             ast::Item::Proc(item) => match hir.symbol_in_scope_source(origin_id, item.name.id) {
                 Some(src) => error_name_already_defined(hir, emit, origin_id, item.name, src),
-                None => add_proc_item(hir, emit, origin_id, item),
+                None => add_proc_item(hir, emit, origin_id, item, allow_generics),
             },
             ast::Item::Enum(item) => match hir.symbol_in_scope_source(origin_id, item.name.id) {
                 Some(src) => error_name_already_defined(hir, emit, origin_id, item.name, src),
@@ -45,8 +96,10 @@ fn add_module_items<'hir>(
                 None => add_global_item(hir, emit, origin_id, item),
             },
             ast::Item::Import(item) => check_import_item(emit, origin_id, item),
+            ast::Item::Impl(item) => add_impl_item(hir, emit, origin_id, item, allow_generics),
         }
     }
+    emit.set_lint_allow(BitSet::EMPTY);
 }
 
 fn add_proc_item<'hir, 'ast>(
@@ -54,7 +107,56 @@ fn add_proc_item<'hir, 'ast>(
     emit: &mut HirEmit<'hir>,
     origin_id: ModuleID,
     item: &'ast ast::ProcItem<'ast>,
+    allow_generics: bool,
 ) {
+    let id = add_proc_data(hir, emit, origin_id, item, allow_generics);
+    hir.add_symbol(
+        origin_id,
+        item.name.id,
+        Symbol::Defined {
+            kind: SymbolKind::Proc(id),
+        },
+    );
+}
+
+// methods are not added to module scope, they are bound to their type in pass_3
+fn add_impl_item<'hir, 'ast>(
+    hir: &mut HirData<'hir, 'ast, '_>,
+    emit: &mut HirEmit<'hir>,
+    origin_id: ModuleID,
+    item: &'ast ast::ImplItem<'ast>,
+    allow_generics: bool,
+) {
+    for attr in item.attrs {
+        match attr.kind {
+            ast::AttributeKind::Unknown => warning_attribute_unknown(emit, origin_id, attr),
+            ast::AttributeKind::Allow => {}
+            _ => error_attribute_cannot_apply(emit, origin_id, attr, "impl blocks"),
+        }
+    }
+
+    let first_proc = hir::ProcID::new(hir.registry().proc_ids().count());
+    for proc_item in item.procs.iter().copied() {
+        check_attribute_args(hir, emit, origin_id, proc_item.attrs);
+        emit.set_lint_allow(lint_allow_set(hir, proc_item.attrs));
+        check_reserved_name(hir, emit, origin_id, proc_item.name);
+        add_proc_data(hir, emit, origin_id, proc_item, allow_generics);
+    }
+
+    hir.add_impl(ImplBlock {
+        origin_id,
+        item,
+        first_proc,
+    });
+}
+
+fn add_proc_data<'hir, 'ast>(
+    hir: &mut HirData<'hir, 'ast, '_>,
+    emit: &mut HirEmit<'hir>,
+    origin_id: ModuleID,
+    item: &'ast ast::ProcItem<'ast>,
+    allow_generics: bool,
+) -> hir::ProcID {
     let mut attr_set = BitSet::EMPTY;
 
     if item.block.is_none() {
@@ -66,6 +168,7 @@ fn add_proc_item<'hir, 'ast>(
             attr_set.set(ProcFlag::Variadic);
         } else {
             emit.error(ErrorComp::new(
+                ErrorCode::InvalidVariadic,
                 "`variadic` procedures must be `external`",
                 SourceRange::new(origin_id, item.name.range),
                 None,
@@ -78,12 +181,18 @@ fn add_proc_item<'hir, 'ast>(
             ast::AttributeKind::Test => Some(ProcFlag::Test),
             ast::AttributeKind::Builtin => Some(ProcFlag::Builtin),
             ast::AttributeKind::Inline => Some(ProcFlag::Inline),
+            ast::AttributeKind::No_Return => Some(ProcFlag::NoReturn),
+            ast::AttributeKind::Deprecated => Some(ProcFlag::Deprecated),
             ast::AttributeKind::Thread_Local => {
                 error_attribute_cannot_apply(emit, origin_id, attr, "procedures");
                 None
             }
+            ast::AttributeKind::Link_Name
+            | ast::AttributeKind::Link
+            | ast::AttributeKind::Call_Conv => None,
+            ast::AttributeKind::Allow => None,
             ast::AttributeKind::Unknown => {
-                error_attribute_unknown(emit, origin_id, attr);
+                warning_attribute_unknown(emit, origin_id, attr);
                 None
             }
         };
@@ -102,26 +211,123 @@ fn add_proc_item<'hir, 'ast>(
         }
     }
 
+    let poly_params = item.poly_params.map(|poly_params| {
+        check_poly_params(hir, emit, origin_id, item, poly_params, allow_generics)
+    });
+
     let data = hir::ProcData {
         origin_id,
         attr_set,
         vis: item.vis,
         name: item.name,
+        poly_params,
         params: &[],
         return_ty: hir::Type::Error,
         block: None,
         locals: &[],
     };
 
-    let id = hir.registry_mut().add_proc(item, data);
+    let external = attr_set.contains(ProcFlag::External);
+    let proc_id = hir.registry_mut().add_proc(item, data);
+    add_external_proc(hir, emit, origin_id, item, proc_id, external);
+    proc_id
+}
 
-    hir.add_symbol(
-        origin_id,
-        item.name.id,
-        Symbol::Defined {
-            kind: SymbolKind::Proc(id),
-        },
-    );
+fn add_external_proc(
+    hir: &mut HirData,
+    emit: &mut HirEmit,
+    origin_id: ModuleID,
+    item: &ast::ProcItem,
+    proc_id: hir::ProcID,
+    external: bool,
+) {
+    let mut link_name = item.name.id;
+    let mut call_conv = hir.target().default_call_conv();
+
+    for attr in item.attrs {
+        match attr.kind {
+            ast::AttributeKind::Link_Name
+            | ast::AttributeKind::Link
+            | ast::AttributeKind::Call_Conv
+                if !external =>
+            {
+                error_attribute_cannot_apply(emit, origin_id, attr, "non-external procedures");
+            }
+            ast::AttributeKind::Link => {
+                for arg in attr.args {
+                    hir.add_link_lib(arg.id);
+                }
+            }
+            ast::AttributeKind::Link_Name => {
+                if let Some(arg) = attr.args.first() {
+                    link_name = arg.id;
+                }
+            }
+            ast::AttributeKind::Call_Conv => {
+                if let Some(arg) = attr.args.first() {
+                    if let Some(conv) = hir::CallConv::from_str(hir.name_str(arg.id)) {
+                        call_conv = conv;
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    if external {
+        hir.add_external(hir::ExternalProc {
+            proc_id,
+            link_name,
+            call_conv,
+        });
+    }
+}
+
+fn check_poly_params<'hir>(
+    hir: &HirData<'hir, '_, '_>,
+    emit: &mut HirEmit<'hir>,
+    origin_id: ModuleID,
+    item: &ast::ProcItem,
+    poly_params: &[ast::Name],
+    allow_generics: bool,
+) -> &'hir [ast::Name] {
+    if !allow_generics {
+        emit.error(ErrorComp::new(
+            ErrorCode::UnstableGenerics,
+            "generic procedures are unstable\nenable them with `unstable_generics = true` in the `[build]` section of `Rock.toml`",
+            SourceRange::new(origin_id, item.name.range),
+            None,
+        ));
+    }
+    if item.block.is_none() {
+        emit.error(ErrorComp::new(
+            ErrorCode::GenericNotAllowed,
+            "`external` procedures cannot be generic",
+            SourceRange::new(origin_id, item.name.range),
+            None,
+        ));
+    }
+
+    let mut unique = Vec::<ast::Name>::with_capacity(poly_params.len());
+    for name in poly_params.iter().copied() {
+        if let Some(existing) = unique.iter().find(|&it| it.id == name.id) {
+            emit.error(ErrorComp::new(
+                ErrorCode::DuplicateDefinition,
+                format!(
+                    "type parameter `{}` is defined multiple times",
+                    hir.name_str(name.id)
+                ),
+                SourceRange::new(origin_id, name.range),
+                Info::new(
+                    "existing type parameter",
+                    SourceRange::new(origin_id, existing.range),
+                ),
+            ));
+        } else {
+            unique.push(name);
+        }
+    }
+    emit.arena.alloc_slice(&unique)
 }
 
 fn add_enum_item<'hir, 'ast>(
@@ -132,7 +338,8 @@ fn add_enum_item<'hir, 'ast>(
 ) {
     for attr in item.attrs {
         match attr.kind {
-            ast::AttributeKind::Unknown => error_attribute_unknown(emit, origin_id, attr),
+            ast::AttributeKind::Unknown => warning_attribute_unknown(emit, origin_id, attr),
+            ast::AttributeKind::Allow => {}
             _ => error_attribute_cannot_apply(emit, origin_id, attr, "enums"),
         }
     }
@@ -143,6 +350,7 @@ fn add_enum_item<'hir, 'ast>(
         name: item.name,
         basic: item.basic.unwrap_or(ast::BasicType::S32),
         variants: &[],
+        size_eval: hir::SizeEval::Unresolved,
     };
 
     let id = hir.registry_mut().add_enum(item, data);
@@ -164,7 +372,8 @@ fn add_struct_item<'hir, 'ast>(
 ) {
     for attr in item.attrs {
         match attr.kind {
-            ast::AttributeKind::Unknown => error_attribute_unknown(emit, origin_id, attr),
+            ast::AttributeKind::Unknown => warning_attribute_unknown(emit, origin_id, attr),
+            ast::AttributeKind::Allow => {}
             _ => error_attribute_cannot_apply(emit, origin_id, attr, "structs"),
         }
     }
@@ -196,7 +405,8 @@ fn add_const_item<'hir, 'ast>(
 ) {
     for attr in item.attrs {
         match attr.kind {
-            ast::AttributeKind::Unknown => error_attribute_unknown(emit, origin_id, attr),
+            ast::AttributeKind::Unknown => warning_attribute_unknown(emit, origin_id, attr),
+            ast::AttributeKind::Allow => {}
             _ => error_attribute_cannot_apply(emit, origin_id, attr, "constants"),
         }
     }
@@ -230,13 +440,21 @@ fn add_global_item<'hir, 'ast>(
 
     for attr in item.attrs {
         let flag = match attr.kind {
-            ast::AttributeKind::Test | ast::AttributeKind::Builtin | ast::AttributeKind::Inline => {
+            ast::AttributeKind::Test
+            | ast::AttributeKind::Builtin
+            | ast::AttributeKind::Inline
+            | ast::AttributeKind::No_Return
+            | ast::AttributeKind::Deprecated
+            | ast::AttributeKind::Link_Name
+            | ast::AttributeKind::Link
+            | ast::AttributeKind::Call_Conv => {
                 error_attribute_cannot_apply(emit, origin_id, attr, "globals");
                 None
             }
             ast::AttributeKind::Thread_Local => Some(GlobalFlag::ThreadLocal),
+            ast::AttributeKind::Allow => None,
             ast::AttributeKind::Unknown => {
-                error_attribute_unknown(emit, origin_id, attr);
+                warning_attribute_unknown(emit, origin_id, attr);
                 None
             }
         };
@@ -283,7 +501,8 @@ fn check_import_item<'hir, 'ast>(
 ) {
     for attr in item.attrs {
         match attr.kind {
-            ast::AttributeKind::Unknown => error_attribute_unknown(emit, origin_id, attr),
+            ast::AttributeKind::Unknown => warning_attribute_unknown(emit, origin_id, attr),
+            ast::AttributeKind::Allow => {}
             _ => error_attribute_cannot_apply(emit, origin_id, attr, "constants"),
         }
     }
@@ -297,15 +516,164 @@ pub fn error_name_already_defined(
     existing: SourceRange,
 ) {
     emit.error(ErrorComp::new(
+        ErrorCode::DuplicateDefinition,
         format!("name `{}` is defined multiple times", hir.name_str(name.id)),
         SourceRange::new(origin_id, name.range),
         Info::new("existing definition", existing),
     ));
 }
 
-fn error_attribute_unknown(emit: &mut HirEmit, origin_id: ModuleID, attr: &ast::Attribute) {
-    emit.error(ErrorComp::new(
-        format!("attribute is unknown"),
+// reserved items are still added to scope to avoid follow-up errors
+pub fn check_reserved_name(
+    hir: &HirData,
+    emit: &mut HirEmit,
+    origin_id: ModuleID,
+    name: ast::Name,
+) {
+    if intern::RESERVED_NAMES.contains(&name.id) {
+        emit.error(ErrorComp::new(
+            ErrorCode::ReservedName,
+            format!("name `{}` is reserved", hir.name_str(name.id)),
+            SourceRange::new(origin_id, name.range),
+            None,
+        ));
+    }
+}
+
+fn check_attribute_args(
+    hir: &HirData,
+    emit: &mut HirEmit,
+    origin_id: ModuleID,
+    attrs: &[ast::Attribute],
+) {
+    for attr in attrs {
+        if attr.string.is_some()
+            && !matches!(
+                attr.kind,
+                ast::AttributeKind::Deprecated | ast::AttributeKind::Unknown
+            )
+        {
+            emit.error(ErrorComp::new(
+                ErrorCode::InvalidAttributeArgument,
+                format!(
+                    "attribute #[{}] does not take a string argument",
+                    attr.kind.as_str()
+                ),
+                SourceRange::new(origin_id, attr.range),
+                None,
+            ));
+        }
+
+        match attr.kind {
+            ast::AttributeKind::Allow => {
+                if attr.args.is_empty() {
+                    emit.error(ErrorComp::new(
+                        ErrorCode::InvalidAttributeArgument,
+                        "attribute #[allow] expects a list of warning codes",
+                        SourceRange::new(origin_id, attr.range),
+                        None,
+                    ));
+                }
+                for arg in attr.args {
+                    if lint_code(hir, arg.id).is_none() {
+                        emit.warning(WarningComp::new(
+                            WarningCode::UnknownLint,
+                            format!("unknown warning code `{}`", hir.name_str(arg.id)),
+                            SourceRange::new(origin_id, arg.range),
+                            None,
+                        ));
+                    }
+                }
+            }
+            ast::AttributeKind::Link => {
+                if attr.args.is_empty() {
+                    emit.error(ErrorComp::new(
+                        ErrorCode::InvalidAttributeArgument,
+                        "attribute #[link] expects a list of library names",
+                        SourceRange::new(origin_id, attr.range),
+                        None,
+                    ));
+                }
+            }
+            ast::AttributeKind::Link_Name | ast::AttributeKind::Call_Conv => {
+                if attr.args.len() != 1 {
+                    emit.error(ErrorComp::new(
+                        ErrorCode::InvalidAttributeArgument,
+                        format!(
+                            "attribute #[{}] expects a single argument",
+                            attr.kind.as_str()
+                        ),
+                        SourceRange::new(origin_id, attr.range),
+                        None,
+                    ));
+                }
+                if attr.kind == ast::AttributeKind::Call_Conv {
+                    for arg in attr.args {
+                        if hir::CallConv::from_str(hir.name_str(arg.id)).is_none() {
+                            emit.error(ErrorComp::new(
+                                ErrorCode::InvalidAttributeArgument,
+                                format!(
+                                    "unknown calling convention `{}`\nexpected `c`, `stdcall` or `fastcall`",
+                                    hir.name_str(arg.id)
+                                ),
+                                SourceRange::new(origin_id, arg.range),
+                                None,
+                            ));
+                        }
+                    }
+                }
+            }
+            ast::AttributeKind::Deprecated => {
+                if !attr.args.is_empty() {
+                    emit.error(ErrorComp::new(
+                        ErrorCode::InvalidAttributeArgument,
+                        "attribute #[deprecated] expects an optional message: `#[deprecated(\"message\")]`",
+                        SourceRange::new(origin_id, attr.range),
+                        None,
+                    ));
+                }
+            }
+            ast::AttributeKind::Unknown => {}
+            _ => {
+                if !attr.args.is_empty() {
+                    emit.error(ErrorComp::new(
+                        ErrorCode::InvalidAttributeArgument,
+                        format!(
+                            "attribute #[{}] does not take any arguments",
+                            attr.kind.as_str()
+                        ),
+                        SourceRange::new(origin_id, attr.range),
+                        None,
+                    ));
+                }
+            }
+        }
+    }
+}
+
+pub fn lint_allow_set(hir: &HirData, attrs: &[ast::Attribute]) -> BitSet {
+    let mut lint_allow = BitSet::EMPTY;
+    for attr in attrs {
+        if attr.kind == ast::AttributeKind::Allow {
+            for arg in attr.args {
+                if let Some(code) = lint_code(hir, arg.id) {
+                    lint_allow.set(code);
+                }
+            }
+        }
+    }
+    lint_allow
+}
+
+fn lint_code(hir: &HirData, name_id: InternID) -> Option<WarningCode> {
+    WarningCode::from_name(hir.name_str(name_id))
+}
+
+// unknown attributes are ignored, allowing attributes from newer compiler versions
+fn warning_attribute_unknown(emit: &mut HirEmit, origin_id: ModuleID, attr: &ast::Attribute) {
+    emit.warning(WarningComp::new(
+        WarningCode::UnknownAttribute,
+        "attribute is unknown",
         SourceRange::new(origin_id, attr.range),
         None,
     ));
@@ -318,6 +686,7 @@ fn error_attribute_cannot_apply(
     item_kind: &'static str,
 ) {
     emit.error(ErrorComp::new(
+        ErrorCode::AttributeNotApplicable,
         format!(
             "attribute #[{}] cannot be applied to {item_kind}",
             attr.kind.as_str()
@@ -340,6 +709,7 @@ pub fn check_attribute_flag<FlagT: AttributeFlag + Copy + Clone>(
     if attr_set.contains(new_flag.into_u32()) {
         if let Some(attr) = attr {
             emit.warning(WarningComp::new(
+                WarningCode::DuplicateAttribute,
                 format!("duplicate attribute #[`{}`]", attr.kind.as_str()),
                 SourceRange::new(origin_id, attr.range),
                 None,
@@ -359,6 +729,7 @@ pub fn check_attribute_flag<FlagT: AttributeFlag + Copy + Clone>(
             if !compat_set.contains(flag.into_u32()) {
                 if let Some(attr) = attr {
                     emit.error(ErrorComp::new(
+                        ErrorCode::AttributeNotApplicable,
                         format!(
                             "attribute #[{}] cannot be applied to `{}` {item_kind}",
                             new_flag.as_str(),
@@ -369,6 +740,7 @@ pub fn check_attribute_flag<FlagT: AttributeFlag + Copy + Clone>(
                     ));
                 } else {
                     emit.error(ErrorComp::new(
+                        ErrorCode::AttributeNotApplicable,
                         format!(
                             "`{}` {item_kind} cannot be `{}`",
                             new_flag.as_str(),
@@ -408,6 +780,8 @@ impl AttributeFlag for ProcFlag {
             ProcFlag::Test => "test",
             ProcFlag::Builtin => "builtin",
             ProcFlag::Inline => "inline",
+            ProcFlag::NoReturn => "no_return",
+            ProcFlag::Deprecated => "deprecated",
         }
     }
 
@@ -419,6 +793,8 @@ impl AttributeFlag for ProcFlag {
             ProcFlag::Test => PROC_FLAG_COMPAT_TEST,
             ProcFlag::Builtin => PROC_FLAG_COMPAT_BUILTIN,
             ProcFlag::Inline => PROC_FLAG_COMPAT_INLINE,
+            ProcFlag::NoReturn => PROC_FLAG_COMPAT_NO_RETURN,
+            ProcFlag::Deprecated => PROC_FLAG_COMPAT_DEPRECATED,
         }
     }
 }
@@ -453,27 +829,57 @@ impl Into<u32> for GlobalFlag {
     }
 }
 
-pub const PROC_FLAG_ALL: [ProcFlag; 6] = [
+pub const PROC_FLAG_ALL: [ProcFlag; 8] = [
     ProcFlag::External,
     ProcFlag::Variadic,
     ProcFlag::Main,
     ProcFlag::Test,
     ProcFlag::Builtin,
     ProcFlag::Inline,
+    ProcFlag::NoReturn,
+    ProcFlag::Deprecated,
 ];
 
-const PROC_FLAG_COMPAT_EXTERNAL: BitSet =
-    BitSet::new(&[ProcFlag::Variadic as u32, ProcFlag::Inline as u32]);
-const PROC_FLAG_COMPAT_VARIADIC: BitSet =
-    BitSet::new(&[ProcFlag::External as u32, ProcFlag::Inline as u32]);
+const PROC_FLAG_COMPAT_EXTERNAL: BitSet = BitSet::new(&[
+    ProcFlag::Variadic as u32,
+    ProcFlag::Inline as u32,
+    ProcFlag::NoReturn as u32,
+    ProcFlag::Deprecated as u32,
+]);
+const PROC_FLAG_COMPAT_VARIADIC: BitSet = BitSet::new(&[
+    ProcFlag::External as u32,
+    ProcFlag::Inline as u32,
+    ProcFlag::NoReturn as u32,
+    ProcFlag::Deprecated as u32,
+]);
 const PROC_FLAG_COMPAT_MAIN: BitSet = BitSet::new(&[]);
 const PROC_FLAG_COMPAT_TEST: BitSet = BitSet::new(&[ProcFlag::Inline as u32]);
-const PROC_FLAG_COMPAT_BUILTIN: BitSet = BitSet::new(&[ProcFlag::Inline as u32]);
+const PROC_FLAG_COMPAT_BUILTIN: BitSet = BitSet::new(&[
+    ProcFlag::Inline as u32,
+    ProcFlag::NoReturn as u32,
+    ProcFlag::Deprecated as u32,
+]);
 const PROC_FLAG_COMPAT_INLINE: BitSet = BitSet::new(&[
     ProcFlag::External as u32,
     ProcFlag::Variadic as u32,
     ProcFlag::Test as u32,
     ProcFlag::Builtin as u32,
+    ProcFlag::NoReturn as u32,
+    ProcFlag::Deprecated as u32,
+]);
+const PROC_FLAG_COMPAT_NO_RETURN: BitSet = BitSet::new(&[
+    ProcFlag::External as u32,
+    ProcFlag::Variadic as u32,
+    ProcFlag::Builtin as u32,
+    ProcFlag::Inline as u32,
+    ProcFlag::Deprecated as u32,
+]);
+const PROC_FLAG_COMPAT_DEPRECATED: BitSet = BitSet::new(&[
+    ProcFlag::External as u32,
+    ProcFlag::Variadic as u32,
+    ProcFlag::Builtin as u32,
+    ProcFlag::Inline as u32,
+    ProcFlag::NoReturn as u32,
 ]);
 
 const GLOBAL_FLAG_ALL: [GlobalFlag; 1] = [GlobalFlag::ThreadLocal];
@@ -1,30 +1,128 @@
 use super::hir_build::{HirData, HirEmit, Symbol, SymbolKind};
+use super::module_graph;
+use super::pass_1;
+use super::ModuleImport;
 use crate::ast;
-use crate::error::{ErrorComp, SourceRange, WarningComp};
+use crate::bitset::BitSet;
+use crate::error::{ErrorCode, ErrorComp, Info, SourceRange, WarningCode, WarningComp};
 use crate::session::{ModuleID, ModuleOrDirectory, Session};
+use crate::text::TextRange;
+use std::collections::HashSet;
 
 pub fn resolve_imports<'hir>(
     hir: &mut HirData<'hir, '_, '_>,
     emit: &mut HirEmit<'hir>,
     session: &Session,
 ) {
+    let mut globs = Vec::new();
     for origin_id in session.module_ids() {
         let module_ast = hir.ast_module(origin_id);
         for item in module_ast.items.iter().copied() {
             if let ast::Item::Import(import) = item {
-                resolve_import(hir, emit, session, origin_id, import);
+                emit.set_lint_allow(pass_1::lint_allow_set(hir, import.attrs));
+                let target_id = resolve_import(hir, emit, session, origin_id, import);
+                if let (Some(target_id), Some(glob)) = (target_id, import.glob) {
+                    globs.push((origin_id, target_id, glob));
+                }
+            }
+        }
+    }
+    emit.set_lint_allow(BitSet::EMPTY);
+    resolve_glob_imports(hir, emit, globs);
+    check_import_cycles(hir, emit, session);
+}
+
+// globs are resolved after other imports, names already in scope take priority,
+// same name imported by different globs must refer to the same symbol
+fn resolve_glob_imports(
+    hir: &mut HirData,
+    emit: &mut HirEmit,
+    globs: Vec<(ModuleID, ModuleID, TextRange)>,
+) {
+    let mut glob_imported = HashSet::new();
+
+    for (origin_id, target_id, glob) in globs {
+        for (id, kind) in hir.symbols_visible(origin_id, target_id) {
+            let existing = match hir.symbol_in_scope_source(origin_id, id) {
+                Some(existing) => existing,
+                None => {
+                    glob_imported.insert((origin_id, id));
+                    let symbol = Symbol::Imported {
+                        kind,
+                        import_range: glob,
+                    };
+                    hir.add_symbol(origin_id, id, symbol);
+                    continue;
+                }
+            };
+            if glob_imported.contains(&(origin_id, id))
+                && hir.symbol_imported(origin_id, id) != Some(kind)
+            {
+                let name = ast::Name { range: glob, id };
+                pass_1::error_name_already_defined(hir, emit, origin_id, name, existing);
             }
         }
     }
 }
 
+// cycles are allowed, all modules are in scope before any item is resolved,
+// reported since they usually mean that module responsibilities are mixed up
+fn check_import_cycles(hir: &HirData, emit: &mut HirEmit, session: &Session) {
+    for cycle in module_graph::find_import_cycles(session, hir.imports()) {
+        let mut path = String::new();
+        for module_id in cycle.iter().chain(cycle.first()) {
+            if !path.is_empty() {
+                path.push_str(" -> ");
+            }
+            let module_name = module_graph::module_name(session, *module_id);
+            path.push_str(&format!("`{module_name}`"));
+        }
+
+        let mut main = None;
+        let mut info_vec = Vec::with_capacity(cycle.len() - 1);
+        for (idx, &origin_id) in cycle.iter().enumerate() {
+            let target_id = cycle[(idx + 1) % cycle.len()];
+            let import = hir
+                .imports()
+                .iter()
+                .find(|import| import.origin_id == origin_id && import.target_id == target_id)
+                .unwrap();
+
+            let prefix = if idx == 0 { "" } else { "which " };
+            let postfix = if idx + 1 == cycle.len() {
+                ", completing the cycle..."
+            } else {
+                ""
+            };
+            let target_name = module_graph::module_name(session, target_id);
+            let msg = format!("{prefix}imports `{target_name}`{postfix}");
+            let src = SourceRange::new(origin_id, import.range);
+
+            if idx == 0 {
+                main = Some((msg, src));
+            } else {
+                info_vec.push(Info::new_value(msg, src));
+            }
+        }
+
+        let (ctx_msg, src) = main.unwrap();
+        emit.warning(WarningComp::new_detailed_info_vec(
+            WarningCode::ImportCycle,
+            format!("import cycle found: {path}"),
+            ctx_msg,
+            src,
+            info_vec,
+        ));
+    }
+}
+
 fn resolve_import<'hir, 'ast>(
     hir: &mut HirData<'hir, 'ast, '_>,
     emit: &mut HirEmit<'hir>,
     session: &Session,
     origin_id: ModuleID,
     import: &'ast ast::ImportItem<'ast>,
-) {
+) -> Option<ModuleID> {
     let mut source_package = session.package(session.module(origin_id).package_id);
 
     if let Some(package_name) = import.package {
@@ -32,6 +130,7 @@ fn resolve_import<'hir, 'ast>(
             source_package = session.package(dependency_id);
         } else {
             emit.error(ErrorComp::new(
+                ErrorCode::PackageNotFound,
                 format!(
                     "package `{}` is not found in dependencies of `{}`",
                     hir.name_str(package_name.id),
@@ -40,7 +139,7 @@ fn resolve_import<'hir, 'ast>(
                 SourceRange::new(origin_id, package_name.range),
                 None,
             ));
-            return;
+            return None;
         }
     }
 
@@ -51,9 +150,15 @@ fn resolve_import<'hir, 'ast>(
     let mut target_dir = &source_package.src;
 
     for name in directory_names {
+        // submodules of `foo.rock` are in `foo/`, directory takes priority
+        if let Some(directory) = target_dir.find_dir(name.id) {
+            target_dir = directory;
+            continue;
+        }
         match target_dir.find(session, name.id) {
             ModuleOrDirectory::None => {
                 emit.error(ErrorComp::new(
+                    ErrorCode::ModuleNotFound,
                     format!(
                         "expected directory `{}` is not found in `{}`",
                         hir.name_str(name.id),
@@ -62,10 +167,11 @@ fn resolve_import<'hir, 'ast>(
                     SourceRange::new(origin_id, name.range),
                     None,
                 ));
-                return;
+                return None;
             }
             ModuleOrDirectory::Module(_) => {
                 emit.error(ErrorComp::new(
+                    ErrorCode::ModuleNotFound,
                     format!(
                         "expected directory, found module `{}`",
                         hir.name_str(name.id),
@@ -73,17 +179,16 @@ fn resolve_import<'hir, 'ast>(
                     SourceRange::new(origin_id, name.range),
                     None,
                 ));
-                return;
-            }
-            ModuleOrDirectory::Directory(directory) => {
-                target_dir = directory;
+                return None;
             }
+            ModuleOrDirectory::Directory(_) => unreachable!(),
         }
     }
 
     let target_id = match target_dir.find(session, last_name.id) {
         ModuleOrDirectory::None => {
             emit.error(ErrorComp::new(
+                ErrorCode::ModuleNotFound,
                 format!(
                     "expected module `{}` is not found in `{}`",
                     hir.name_str(last_name.id),
@@ -92,11 +197,12 @@ fn resolve_import<'hir, 'ast>(
                 SourceRange::new(origin_id, last_name.range),
                 None,
             ));
-            return;
+            return None;
         }
         ModuleOrDirectory::Module(module_id) => module_id,
         ModuleOrDirectory::Directory(_) => {
             emit.error(ErrorComp::new(
+                ErrorCode::ModuleNotFound,
                 format!(
                     "expected module, found directory `{}`",
                     hir.name_str(last_name.id),
@@ -104,12 +210,13 @@ fn resolve_import<'hir, 'ast>(
                 SourceRange::new(origin_id, last_name.range),
                 None,
             ));
-            return;
+            return None;
         }
     };
 
     if target_id == origin_id {
         emit.error(ErrorComp::new(
+            ErrorCode::SelfImport,
             format!(
                 "importing module `{}` into itself is redundant, remove this import",
                 hir.name_str(last_name.id)
@@ -117,20 +224,33 @@ fn resolve_import<'hir, 'ast>(
             SourceRange::new(origin_id, last_name.range),
             None,
         ));
-        return;
+        return None;
     }
 
+    let first_name = import.package.unwrap_or(import.import_path[0]);
+    hir.add_import(ModuleImport {
+        origin_id,
+        target_id,
+        range: TextRange::new(first_name.range.start(), last_name.range.end()),
+    });
+
     let module_alias = name_alias_check(hir, emit, origin_id, last_name, import.alias);
 
+    let module_kind = SymbolKind::Module(target_id);
+
     match hir.symbol_in_scope_source(origin_id, module_alias.id) {
+        // same module can be imported again to import more symbols
+        Some(_)
+            if (!import.symbols.is_empty() || import.glob.is_some())
+                && hir.symbol_imported(origin_id, module_alias.id) == Some(module_kind) => {}
         Some(existing) => {
-            super::pass_1::error_name_already_defined(hir, emit, origin_id, module_alias, existing);
+            error_import_conflict(hir, emit, origin_id, module_alias, module_kind, existing);
         }
         None => hir.add_symbol(
             origin_id,
             module_alias.id,
             Symbol::Imported {
-                kind: SymbolKind::Module(target_id),
+                kind: module_kind,
                 import_range: module_alias.range,
             },
         ),
@@ -142,27 +262,50 @@ fn resolve_import<'hir, 'ast>(
 
         match found_symbol {
             Err(error) => emit.error(error),
-            Ok((kind, _)) => match hir.symbol_in_scope_source(origin_id, symbol_alias.id) {
-                Some(existing) => {
-                    super::pass_1::error_name_already_defined(
-                        hir,
-                        emit,
+            Ok((kind, _)) => {
+                let source = SourceRange::new(origin_id, symbol.name.range);
+                emit.usage.add_reference(kind, source);
+                match hir.symbol_in_scope_source(origin_id, symbol_alias.id) {
+                    Some(existing) => {
+                        error_import_conflict(hir, emit, origin_id, symbol_alias, kind, existing);
+                    }
+                    None => hir.add_symbol(
                         origin_id,
-                        symbol_alias,
-                        existing,
-                    );
+                        symbol_alias.id,
+                        Symbol::Imported {
+                            kind,
+                            import_range: symbol_alias.range,
+                        },
+                    ),
                 }
-                None => hir.add_symbol(
-                    origin_id,
-                    symbol_alias.id,
-                    Symbol::Imported {
-                        kind,
-                        import_range: symbol_alias.range,
-                    },
-                ),
-            },
+            }
         }
     }
+    Some(target_id)
+}
+
+fn error_import_conflict(
+    hir: &HirData,
+    emit: &mut HirEmit,
+    origin_id: ModuleID,
+    name: ast::Name,
+    kind: SymbolKind,
+    existing: SourceRange,
+) {
+    if hir.symbol_imported(origin_id, name.id) == Some(kind) {
+        emit.error(ErrorComp::new(
+            ErrorCode::AlreadyImported,
+            format!(
+                "{} `{}` is already imported",
+                kind.kind_name(),
+                hir.name_str(name.id)
+            ),
+            SourceRange::new(origin_id, name.range),
+            Info::new("existing import", existing),
+        ));
+    } else {
+        pass_1::error_name_already_defined(hir, emit, origin_id, name, existing);
+    }
 }
 
 fn name_alias_check(
@@ -172,9 +315,11 @@ fn name_alias_check(
     name: ast::Name,
     name_alias: Option<ast::Name>,
 ) -> ast::Name {
+    pass_1::check_reserved_name(hir, emit, origin_id, name_alias.unwrap_or(name));
     if let Some(alias) = name_alias {
         if alias.id == name.id {
             emit.warning(WarningComp::new(
+                WarningCode::RedundantAlias,
                 format!(
                     "name alias `{}` is redundant, remove it",
                     hir.name_str(alias.id)
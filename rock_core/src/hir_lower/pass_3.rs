@@ -1,10 +1,12 @@
-use super::hir_build::{HirData, HirEmit};
+use super::hir_build::{HirData, HirEmit, Method, MethodOwner, MethodReceiver, PolyScope};
 use super::pass_4;
 use super::pass_5::{self, Expectation};
 use crate::ast;
-use crate::error::{ErrorComp, Info, SourceRange};
+use crate::error::{ErrorCode, ErrorComp, Info, SourceRange};
 use crate::hir;
+use crate::intern;
 use crate::session::ModuleID;
+use crate::text::TextRange;
 
 pub fn process_items<'hir>(hir: &mut HirData<'hir, '_, '_>, emit: &mut HirEmit<'hir>) {
     for id in hir.registry().proc_ids() {
@@ -16,6 +18,9 @@ pub fn process_items<'hir>(hir: &mut HirData<'hir, '_, '_>, emit: &mut HirEmit<'
     for id in hir.registry().struct_ids() {
         process_struct_data(hir, emit, id)
     }
+    for idx in 0..hir.impl_count() {
+        process_impl_block(hir, emit, idx)
+    }
     for id in hir.registry().const_ids() {
         process_const_data(hir, emit, id)
     }
@@ -72,23 +77,11 @@ pub fn type_resolve<'hir>(
             hir::Type::ArraySlice(emit.arena.alloc(slice))
         }
         ast::TypeKind::ArrayStatic(array) => {
-            let expect = Expectation::HasType(hir::Type::USIZE, None);
-            let value = pass_4::resolve_const_expr(hir, emit, origin_id, expect, array.len);
-
-            let len = match value {
-                hir::ConstValue::Int { val, ty, neg } => {
-                    if neg {
-                        None
-                    } else {
-                        Some(val)
-                    }
-                }
-                _ => None,
-            };
+            let len = resolve_array_len(hir, emit, origin_id, array.len);
             let elem_ty = type_resolve(hir, emit, origin_id, array.elem_ty);
 
             let array = hir::ArrayStatic {
-                len: hir::ArrayStaticLen::Immediate(len),
+                len: hir::ArrayStaticLen::Value(emit.const_intern.intern(len)),
                 elem_ty,
             };
             hir::Type::ArrayStatic(emit.arena.alloc(array))
@@ -96,6 +89,22 @@ pub fn type_resolve<'hir>(
     }
 }
 
+/// shared by array types and array repeat expressions,
+/// `ConstValue::Error` if length is not a valid `usize` constant, errors are already reported
+pub fn resolve_array_len<'hir>(
+    hir: &HirData<'hir, '_, '_>,
+    emit: &mut HirEmit<'hir>,
+    origin_id: ModuleID,
+    len: ast::ConstExpr,
+) -> hir::ConstValue<'hir> {
+    let expect = Expectation::HasType(hir::Type::USIZE, None);
+    let value = pass_4::resolve_const_expr(hir, emit, origin_id, expect, len);
+    match value {
+        hir::ConstValue::Int { neg, .. } if !neg => value,
+        _ => hir::ConstValue::Error,
+    }
+}
+
 #[must_use]
 pub fn type_resolve_delayed<'hir, 'ast>(
     hir: &mut HirData<'hir, 'ast, '_>,
@@ -161,12 +170,20 @@ pub fn process_proc_data<'hir>(
     id: hir::ProcID,
 ) {
     let item = hir.registry().proc_item(id);
-    let origin_id = hir.registry().proc_data(id).origin_id;
+    let data = hir.registry().proc_data(id);
+    let origin_id = data.origin_id;
+    let poly_scope = data.poly_params.map(|poly_params| PolyScope {
+        proc_id: id,
+        poly_params,
+        poly_types: None,
+    });
+    emit.set_poly_scope(poly_scope);
     let mut unique = Vec::<hir::ProcParam>::new();
 
     for param in item.params.iter() {
         if let Some(existing) = unique.iter().find(|&it| it.name.id == param.name.id) {
             emit.error(ErrorComp::new(
+                ErrorCode::DuplicateDefinition,
                 format!(
                     "parameter `{}` is defined multiple times",
                     hir.name_str(param.name.id)
@@ -190,11 +207,30 @@ pub fn process_proc_data<'hir>(
     }
 
     hir.registry_mut().proc_data_mut(id).params = emit.arena.alloc_slice(&unique);
-    hir.registry_mut().proc_data_mut(id).return_ty = if let Some(ret_ty) = item.return_ty {
-        type_resolve_delayed(hir, emit, origin_id, ret_ty)
+    // `#[no_return]` procedures return `never`, their calls diverge
+    let no_return = hir
+        .registry()
+        .proc_data(id)
+        .attr_set
+        .contains(hir::ProcFlag::NoReturn);
+    let return_ty = if let Some(ret_ty) = item.return_ty {
+        let ty = type_resolve_delayed(hir, emit, origin_id, ret_ty);
+        if no_return && !ty.is_never() && !ty.is_error() {
+            emit.error(ErrorComp::new(
+                ErrorCode::AttributeNotApplicable,
+                "procedures with #[no_return] attribute can only return `never`",
+                SourceRange::new(origin_id, ret_ty.range),
+                None,
+            ));
+        }
+        ty
+    } else if no_return {
+        hir::Type::Basic(ast::BasicType::Never)
     } else {
         hir::Type::Basic(ast::BasicType::Void)
-    }
+    };
+    hir.registry_mut().proc_data_mut(id).return_ty = return_ty;
+    emit.set_poly_scope(None);
 }
 
 fn process_enum_data<'hir>(
@@ -209,6 +245,7 @@ fn process_enum_data<'hir>(
     for variant in item.variants.iter() {
         if let Some(existing) = unique.iter().find(|&it| it.name.id == variant.name.id) {
             emit.error(ErrorComp::new(
+                ErrorCode::DuplicateDefinition,
                 format!(
                     "variant `{}` is defined multiple times",
                     hir.name_str(variant.name.id)
@@ -220,9 +257,22 @@ fn process_enum_data<'hir>(
                 ),
             ));
         } else {
+            let mut fields = Vec::with_capacity(variant.fields.len());
+            for field_ty in variant.fields {
+                let ty = type_resolve_delayed(hir, emit, origin_id, *field_ty);
+                pass_5::require_value_type(
+                    hir,
+                    emit,
+                    ty,
+                    SourceRange::new(origin_id, field_ty.range),
+                );
+                fields.push(ty);
+            }
+
             unique.push(hir::EnumVariant {
                 name: variant.name,
                 value: hir.registry_mut().add_const_eval(variant.value, origin_id),
+                fields: emit.arena.alloc_slice(&fields),
             });
         }
     }
@@ -242,6 +292,7 @@ fn process_struct_data<'hir>(
     for field in item.fields.iter() {
         if let Some(existing) = unique.iter().find(|&it| it.name.id == field.name.id) {
             emit.error(ErrorComp::new(
+                ErrorCode::DuplicateDefinition,
                 format!(
                     "field `{}` is defined multiple times",
                     hir.name_str(field.name.id)
@@ -256,10 +307,15 @@ fn process_struct_data<'hir>(
             let ty = type_resolve_delayed(hir, emit, origin_id, field.ty);
             pass_5::require_value_type(hir, emit, ty, SourceRange::new(origin_id, field.ty.range));
 
+            let default = field
+                .default
+                .map(|default| hir.registry_mut().add_const_eval(default, origin_id));
+
             unique.push(hir::StructField {
                 vis: field.vis,
                 name: field.name,
                 ty,
+                default,
             });
         }
     }
@@ -292,3 +348,132 @@ fn process_global_data<'hir>(
     pass_5::require_value_type(hir, emit, ty, SourceRange::new(origin_id, item.ty.range));
     hir.registry_mut().global_data_mut(id).ty = ty;
 }
+
+fn process_impl_block<'hir>(hir: &mut HirData<'hir, '_, '_>, emit: &mut HirEmit<'hir>, idx: usize) {
+    let impl_block = hir.impl_block(idx);
+    let origin_id = impl_block.origin_id;
+    let path = impl_block.item.path;
+    let path_range = TextRange::new(
+        path.names.first().unwrap().range.start(),
+        path.names.last().unwrap().range.end(),
+    );
+
+    let owner = match pass_5::path_resolve_type(hir, emit, None, origin_id, path) {
+        hir::Type::Error => return,
+        ty => match pass_5::type_method_owner(ty) {
+            Some(owner) => owner,
+            None => {
+                emit.error(ErrorComp::new(
+                    ErrorCode::InvalidImpl,
+                    "`impl` blocks can only be defined for structs and enums",
+                    SourceRange::new(origin_id, path_range),
+                    None,
+                ));
+                return;
+            }
+        },
+    };
+    let owner_name = match owner {
+        MethodOwner::Enum(id) => hir.registry().enum_data(id).name,
+        MethodOwner::Struct(id) => hir.registry().struct_data(id).name,
+    };
+    let self_id = intern::NAME_SELF;
+
+    for proc_idx in 0..impl_block.item.procs.len() {
+        let proc_id = hir::ProcID::new(impl_block.first_proc.index() + proc_idx);
+        let data = hir.registry().proc_data(proc_id);
+        let name = data.name;
+        let name_src = SourceRange::new(origin_id, name.range);
+
+        if data.poly_params.is_some() {
+            emit.error(ErrorComp::new(
+                ErrorCode::GenericNotAllowed,
+                "methods cannot be generic",
+                name_src,
+                None,
+            ));
+            continue;
+        }
+        if data.attr_set.contains(hir::ProcFlag::External) {
+            emit.error(ErrorComp::new(
+                ErrorCode::InvalidImpl,
+                "methods cannot be `external`",
+                name_src,
+                None,
+            ));
+            continue;
+        }
+
+        let receiver = match data.params.first() {
+            Some(param) if param.name.id == self_id => match param.ty {
+                hir::Type::Error => continue,
+                hir::Type::Reference(ref_ty, mutt)
+                    if pass_5::type_method_owner(*ref_ty) == Some(owner) =>
+                {
+                    MethodReceiver::Reference(mutt)
+                }
+                ty if pass_5::type_method_owner(ty) == Some(owner) => MethodReceiver::Value,
+                _ => {
+                    let owner = hir.name_str(owner_name.id);
+                    emit.error(ErrorComp::new(
+                        ErrorCode::InvalidSelfParam,
+                        format!("`self` must have type `{owner}`, `&{owner}` or `&mut {owner}`"),
+                        SourceRange::new(origin_id, param.name.range),
+                        None,
+                    ));
+                    continue;
+                }
+            },
+            _ => {
+                emit.error(ErrorComp::new(
+                    ErrorCode::InvalidSelfParam,
+                    format!(
+                        "method `{}` must have `self` as the first parameter",
+                        hir.name_str(name.id)
+                    ),
+                    name_src,
+                    None,
+                ));
+                continue;
+            }
+        };
+
+        if let MethodOwner::Struct(id) = owner {
+            let data = hir.registry().struct_data(id);
+            if let Some((_, field)) = data.find_field(name.id) {
+                emit.error(ErrorComp::new(
+                    ErrorCode::DuplicateDefinition,
+                    format!(
+                        "method `{}` has the same name as a field of `{}`",
+                        hir.name_str(name.id),
+                        hir.name_str(owner_name.id)
+                    ),
+                    name_src,
+                    Info::new(
+                        "field defined here",
+                        SourceRange::new(data.origin_id, field.name.range),
+                    ),
+                ));
+                continue;
+            }
+        }
+        if let Some(existing) = hir.method_find(owner, name.id) {
+            let existing = hir.registry().proc_data(existing.proc_id);
+            emit.error(ErrorComp::new(
+                ErrorCode::DuplicateDefinition,
+                format!(
+                    "method `{}` is already defined for `{}`",
+                    hir.name_str(name.id),
+                    hir.name_str(owner_name.id)
+                ),
+                name_src,
+                Info::new(
+                    "existing method",
+                    SourceRange::new(existing.origin_id, existing.name.range),
+                ),
+            ));
+            continue;
+        }
+        hir.add_method(owner, name.id, Method { proc_id, receiver });
+    }
+}
@@ -2,18 +2,21 @@ use super::hir_build::{HirData, HirEmit};
 use super::pass_3;
 use super::pass_5::{self, Expectation};
 use super::proc_scope;
-use crate::ast;
+use crate::ast::{self, BasicType};
 use crate::bitset::BitSet;
-use crate::error::{ErrorComp, Info, SourceRange, StringOrStr};
+use crate::error::{ErrorCode, ErrorComp, Info, SourceRange, StringOrStr};
 use crate::intern::InternID;
 use crate::session::ModuleID;
+use crate::target::TargetTriple;
 use crate::text::TextRange;
 use crate::{hir, id_impl};
 
 #[derive(Copy, Clone, PartialEq)]
 enum ConstDependency {
     EnumVariant(hir::EnumID, hir::EnumVariantID),
+    EnumSize(hir::EnumID),
     StructSize(hir::StructID),
+    FieldDefault(hir::StructID, hir::StructFieldID),
     Const(hir::ConstID),
     Global(hir::GlobalID),
     ArrayLen(hir::ConstEvalID),
@@ -44,6 +47,34 @@ pub fn resolve_const_dependencies<'hir>(hir: &mut HirData<'hir, '_, '_>, emit: &
                 hir::ConstEval::ResolvedValue(_) => {}
             }
         }
+        check_duplicate_variant_values(hir, emit, id);
+    }
+
+    for id in hir.registry().enum_ids() {
+        let data = hir.registry().enum_data(id);
+
+        if matches!(data.size_eval, hir::SizeEval::Unresolved) {
+            let (mut tree, root_id) = Tree::new_rooted(ConstDependency::EnumSize(id));
+            let mut is_ok = true;
+
+            for variant in data.variants {
+                for field_ty in variant.fields {
+                    if let Err(from_id) =
+                        add_type_size_const_dependencies(hir, emit, &mut tree, root_id, *field_ty)
+                    {
+                        const_dependencies_mark_error_up_to_root(hir, &tree, from_id);
+                        is_ok = false;
+                        break;
+                    }
+                }
+                if !is_ok {
+                    break;
+                }
+            }
+            if is_ok {
+                resolve_const_dependency_tree(hir, emit, &tree);
+            }
+        }
     }
 
     for id in hir.registry().struct_ids() {
@@ -68,6 +99,40 @@ pub fn resolve_const_dependencies<'hir>(hir: &mut HirData<'hir, '_, '_>, emit: &
         }
     }
 
+    for id in hir.registry().struct_ids() {
+        let data = hir.registry().struct_data(id);
+
+        for (idx, field) in data.fields.iter().enumerate() {
+            let eval_id = match field.default {
+                Some(eval_id) => eval_id,
+                None => continue,
+            };
+            let (eval, origin_id) = *hir.registry().const_eval(eval_id);
+            let field_id = hir::StructFieldID::new(idx);
+
+            match eval {
+                hir::ConstEval::Unresolved(expr) => {
+                    let (mut tree, root_id) =
+                        Tree::new_rooted(ConstDependency::FieldDefault(id, field_id));
+
+                    if let Err(from_id) =
+                        add_type_usage_const_dependencies(hir, emit, &mut tree, root_id, field.ty)
+                    {
+                        const_dependencies_mark_error_up_to_root(hir, &tree, from_id);
+                    } else if let Err(from_id) = add_expr_const_dependencies(
+                        hir, emit, &mut tree, root_id, origin_id, expr.0,
+                    ) {
+                        const_dependencies_mark_error_up_to_root(hir, &tree, from_id);
+                    } else {
+                        resolve_const_dependency_tree(hir, emit, &tree);
+                    }
+                }
+                hir::ConstEval::ResolvedError => {}
+                hir::ConstEval::ResolvedValue(_) => {}
+            }
+        }
+    }
+
     for id in hir.registry().const_ids() {
         let data = hir.registry().const_data(id);
         let (eval, origin_id) = *hir.registry().const_eval(data.value);
@@ -133,6 +198,41 @@ pub fn resolve_const_dependencies<'hir>(hir: &mut HirData<'hir, '_, '_>, emit: &
     }
 }
 
+// variant values are resolved at this point, errors were already reported
+fn check_duplicate_variant_values(hir: &HirData, emit: &mut HirEmit, enum_id: hir::EnumID) {
+    let data = hir.registry().enum_data(enum_id);
+    let mut unique = Vec::<(i128, &hir::EnumVariant)>::with_capacity(data.variants.len());
+
+    for variant in data.variants {
+        let value_id = match hir.registry().const_eval(variant.value).0 {
+            hir::ConstEval::ResolvedValue(value_id) => value_id,
+            _ => continue,
+        };
+        let value = match emit.const_intern.get(value_id) {
+            hir::ConstValue::Int { val, neg, .. } => int_to_i128(val, neg),
+            _ => continue,
+        };
+
+        if let Some((_, existing)) = unique.iter().find(|(existing, _)| *existing == value) {
+            emit.error(ErrorComp::new(
+                ErrorCode::DuplicateVariantValue,
+                format!(
+                    "variant `{}` has the same value `{value}` as variant `{}`",
+                    hir.name_str(variant.name.id),
+                    hir.name_str(existing.name.id)
+                ),
+                SourceRange::new(data.origin_id, variant.name.range),
+                Info::new(
+                    "existing variant",
+                    SourceRange::new(data.origin_id, existing.name.range),
+                ),
+            ));
+        } else {
+            unique.push((value, variant));
+        }
+    }
+}
+
 struct Tree<T: PartialEq + Copy + Clone> {
     nodes: Vec<TreeNode<T>>,
 }
@@ -231,10 +331,19 @@ fn check_const_dependency_cycle(
             let variant = data.variant(variant_id);
             SourceRange::new(data.origin_id, variant.name.range)
         }
+        ConstDependency::EnumSize(id) => {
+            let data = hir.registry().enum_data(id);
+            SourceRange::new(data.origin_id, data.name.range)
+        }
         ConstDependency::StructSize(id) => {
             let data = hir.registry().struct_data(id);
             SourceRange::new(data.origin_id, data.name.range)
         }
+        ConstDependency::FieldDefault(id, field_id) => {
+            let data = hir.registry().struct_data(id);
+            let field = data.field(field_id);
+            SourceRange::new(data.origin_id, field.name.range)
+        }
         ConstDependency::Const(id) => {
             let data = hir.registry().const_data(id);
             SourceRange::new(data.origin_id, data.name.range)
@@ -283,6 +392,15 @@ fn check_const_dependency_cycle(
                 let src = SourceRange::new(data.origin_id, variant.name.range);
                 (msg, src)
             }
+            ConstDependency::EnumSize(id) => {
+                let data = hir.registry().enum_data(id);
+                let msg = format!(
+                    "{prefix}depends on size of `{}`{postfix}",
+                    hir.name_str(data.name.id)
+                );
+                let src = SourceRange::new(data.origin_id, data.name.range);
+                (msg, src)
+            }
             ConstDependency::StructSize(id) => {
                 let data = hir.registry().struct_data(id);
                 let msg = format!(
@@ -292,6 +410,17 @@ fn check_const_dependency_cycle(
                 let src = SourceRange::new(data.origin_id, data.name.range);
                 (msg, src)
             }
+            ConstDependency::FieldDefault(id, field_id) => {
+                let data = hir.registry().struct_data(id);
+                let field = data.field(field_id);
+                let msg = format!(
+                    "{prefix}depends on `{}.{}` default value{postfix}",
+                    hir.name_str(data.name.id),
+                    hir.name_str(field.name.id)
+                );
+                let src = SourceRange::new(data.origin_id, field.name.range);
+                (msg, src)
+            }
             ConstDependency::Const(id) => {
                 let data = hir.registry().const_data(id);
                 let msg = format!(
@@ -333,8 +462,22 @@ fn check_const_dependency_cycle(
         info_src = src;
     }
 
+    // structs and enums containing themselves by value have no finite layout
+    let infinite_size = cycle_deps.iter().all(|const_dep| {
+        matches!(
+            const_dep,
+            ConstDependency::EnumSize(_) | ConstDependency::StructSize(_)
+        )
+    });
+    let msg = if infinite_size {
+        "infinitely sized type found, use a reference to break the cycle:"
+    } else {
+        "constant dependency cycle found:"
+    };
+
     emit.error(ErrorComp::new_detailed_info_vec(
-        "constant dependency cycle found:",
+        ErrorCode::ConstDependencyCycle,
+        msg,
         ctx_msg,
         src,
         info_vec,
@@ -356,10 +499,21 @@ fn const_dependencies_mark_error_up_to_root(
                 let (eval, _) = hir.registry_mut().const_eval_mut(eval_id);
                 *eval = hir::ConstEval::ResolvedError;
             }
+            ConstDependency::EnumSize(id) => {
+                let data = hir.registry_mut().enum_data_mut(id);
+                data.size_eval = hir::SizeEval::ResolvedError;
+            }
             ConstDependency::StructSize(id) => {
                 let data = hir.registry_mut().struct_data_mut(id);
                 data.size_eval = hir::SizeEval::ResolvedError;
             }
+            ConstDependency::FieldDefault(id, field_id) => {
+                let data = hir.registry().struct_data(id);
+                if let Some(eval_id) = data.field(field_id).default {
+                    let (eval, _) = hir.registry_mut().const_eval_mut(eval_id);
+                    *eval = hir::ConstEval::ResolvedError;
+                }
+            }
             ConstDependency::Const(id) => {
                 let data = hir.registry().const_data(id);
                 let eval_id = data.value;
@@ -406,6 +560,32 @@ fn add_enum_variant_const_dependency<'hir>(
     }
 }
 
+fn add_enum_size_const_dependency<'hir>(
+    hir: &mut HirData<'hir, '_, '_>,
+    emit: &mut HirEmit<'hir>,
+    tree: &mut Tree<ConstDependency>,
+    parent_id: TreeNodeID,
+    enum_id: hir::EnumID,
+) -> Result<(), TreeNodeID> {
+    let data = hir.registry().enum_data(enum_id);
+
+    match data.size_eval {
+        hir::SizeEval::Unresolved => {
+            let node_id = tree.add_child(parent_id, ConstDependency::EnumSize(enum_id));
+            check_const_dependency_cycle(hir, emit, tree, parent_id, node_id)?;
+
+            for variant in data.variants {
+                for field_ty in variant.fields {
+                    add_type_size_const_dependencies(hir, emit, tree, node_id, *field_ty)?;
+                }
+            }
+            Ok(())
+        }
+        hir::SizeEval::ResolvedError => Err(parent_id),
+        hir::SizeEval::Resolved(_) => Ok(()),
+    }
+}
+
 fn add_struct_size_const_dependency<'hir>(
     hir: &mut HirData<'hir, '_, '_>,
     emit: &mut HirEmit<'hir>,
@@ -430,6 +610,63 @@ fn add_struct_size_const_dependency<'hir>(
     }
 }
 
+fn add_global_const_dependency<'hir>(
+    hir: &mut HirData<'hir, '_, '_>,
+    emit: &mut HirEmit<'hir>,
+    tree: &mut Tree<ConstDependency>,
+    parent_id: TreeNodeID,
+    global_id: hir::GlobalID,
+) -> Result<(), TreeNodeID> {
+    let data = hir.registry().global_data(global_id);
+    let global_ty = data.ty;
+    let eval_id = data.value;
+    let (eval, origin_id) = *hir.registry().const_eval(eval_id);
+
+    match eval {
+        hir::ConstEval::Unresolved(expr) => {
+            let node_id = tree.add_child(parent_id, ConstDependency::Global(global_id));
+            check_const_dependency_cycle(hir, emit, tree, parent_id, node_id)?;
+
+            add_type_usage_const_dependencies(hir, emit, tree, node_id, global_ty)?;
+            add_expr_const_dependencies(hir, emit, tree, node_id, origin_id, expr.0)?;
+            Ok(())
+        }
+        hir::ConstEval::ResolvedError => Err(parent_id),
+        hir::ConstEval::ResolvedValue(_) => Ok(()),
+    }
+}
+
+fn add_field_default_const_dependency<'hir>(
+    hir: &mut HirData<'hir, '_, '_>,
+    emit: &mut HirEmit<'hir>,
+    tree: &mut Tree<ConstDependency>,
+    parent_id: TreeNodeID,
+    struct_id: hir::StructID,
+    field_id: hir::StructFieldID,
+) -> Result<(), TreeNodeID> {
+    let field = hir.registry().struct_data(struct_id).field(field_id);
+    let field_ty = field.ty;
+    let eval_id = match field.default {
+        Some(eval_id) => eval_id,
+        None => return Ok(()),
+    };
+    let (eval, origin_id) = *hir.registry().const_eval(eval_id);
+
+    match eval {
+        hir::ConstEval::Unresolved(expr) => {
+            let dependency = ConstDependency::FieldDefault(struct_id, field_id);
+            let node_id = tree.add_child(parent_id, dependency);
+            check_const_dependency_cycle(hir, emit, tree, parent_id, node_id)?;
+
+            add_type_usage_const_dependencies(hir, emit, tree, node_id, field_ty)?;
+            add_expr_const_dependencies(hir, emit, tree, node_id, origin_id, expr.0)?;
+            Ok(())
+        }
+        hir::ConstEval::ResolvedError => Err(parent_id),
+        hir::ConstEval::ResolvedValue(_) => Ok(()),
+    }
+}
+
 fn add_const_var_const_dependency<'hir>(
     hir: &mut HirData<'hir, '_, '_>,
     emit: &mut HirEmit<'hir>,
@@ -489,12 +726,15 @@ fn add_type_size_const_dependencies<'hir>(
     match ty {
         hir::Type::Error => {}
         hir::Type::Basic(_) => {}
-        hir::Type::Enum(_) => {}
+        hir::Type::Enum(id) => {
+            add_enum_size_const_dependency(hir, emit, tree, parent_id, id)?;
+        }
         hir::Type::Struct(id) => {
             add_struct_size_const_dependency(hir, emit, tree, parent_id, id)?;
         }
         hir::Type::Reference(_, _) => {}
         hir::Type::Procedure(_) => {}
+        hir::Type::PolyProc(_, _) => {}
         hir::Type::ArraySlice(_) => {}
         hir::Type::ArrayStatic(array) => {
             if let hir::ArrayStaticLen::ConstEval(eval_id) = array.len {
@@ -516,7 +756,14 @@ fn add_type_usage_const_dependencies<'hir>(
     match ty {
         hir::Type::Error => {}
         hir::Type::Basic(_) => {}
-        hir::Type::Enum(_) => {}
+        hir::Type::Enum(id) => {
+            let data = hir.registry().enum_data(id);
+            for variant in data.variants {
+                for field_ty in variant.fields {
+                    add_type_usage_const_dependencies(hir, emit, tree, parent_id, *field_ty)?
+                }
+            }
+        }
         hir::Type::Struct(id) => {
             let data = hir.registry().struct_data(id);
             for field in data.fields {
@@ -532,6 +779,7 @@ fn add_type_usage_const_dependencies<'hir>(
             }
             add_type_usage_const_dependencies(hir, emit, tree, parent_id, proc_ty.return_ty)?
         }
+        hir::Type::PolyProc(_, _) => {}
         hir::Type::ArraySlice(slice) => {
             add_type_usage_const_dependencies(hir, emit, tree, parent_id, slice.elem_ty)?;
         }
@@ -593,6 +841,10 @@ fn add_expr_const_dependencies<'hir, 'ast>(
             add_expr_const_dependencies(hir, emit, tree, parent_id, origin_id, target)?;
             Ok(())
         }
+        ast::ExprKind::Format { .. } => {
+            error_cannot_use_in_constants(hir, emit, origin_id, expr.range, "format");
+            Err(parent_id)
+        }
         ast::ExprKind::Sizeof { ty } => {
             let ty = pass_3::type_resolve_delayed(hir, emit, origin_id, *ty);
             add_type_size_const_dependencies(hir, emit, tree, parent_id, ty)?;
@@ -622,9 +874,32 @@ fn add_expr_const_dependencies<'hir, 'ast>(
                     add_const_var_const_dependency(hir, emit, tree, parent_id, const_id)?;
                     Ok(())
                 }
-                pass_5::ValueID::Global(_) => {
-                    error_cannot_refer_to_in_constants(hir, emit, origin_id, expr.range, "globals");
-                    Err(parent_id)
+                pass_5::ValueID::Global(global_id) => {
+                    // only global initializers can read values of other immutable globals
+                    let in_global =
+                        matches!(tree.get_node(parent_id).value, ConstDependency::Global(_));
+                    let data = hir.registry().global_data(global_id);
+
+                    if !in_global {
+                        error_cannot_refer_to_in_constants(
+                            hir, emit, origin_id, expr.range, "globals",
+                        );
+                        Err(parent_id)
+                    } else if data.mutt == ast::Mut::Mutable {
+                        emit.error(ErrorComp::new(
+                            ErrorCode::NotConstant,
+                            "cannot refer to mutable globals in global initializers",
+                            SourceRange::new(origin_id, expr.range),
+                            Info::new(
+                                "global defined here",
+                                SourceRange::new(data.origin_id, data.name.range),
+                            ),
+                        ));
+                        Err(parent_id)
+                    } else {
+                        add_global_const_dependency(hir, emit, tree, parent_id, global_id)?;
+                        Ok(())
+                    }
                 }
                 pass_5::ValueID::Local(_) => {
                     error_cannot_refer_to_in_constants(hir, emit, origin_id, expr.range, "locals");
@@ -647,6 +922,10 @@ fn add_expr_const_dependencies<'hir, 'ast>(
             error_cannot_use_in_constants(hir, emit, origin_id, expr.range, "variant selector");
             Err(parent_id)
         }
+        ast::ExprKind::Wildcard => {
+            pass_5::error_wildcard_expr(emit, SourceRange::new(origin_id, expr.range));
+            Err(parent_id)
+        }
         ast::ExprKind::StructInit { struct_init } => match struct_init.path {
             //@cannot infer struct / enum variant type in constants
             Some(path) => {
@@ -660,6 +939,18 @@ fn add_expr_const_dependencies<'hir, 'ast>(
                             hir, emit, tree, parent_id, origin_id, init.expr,
                         )?;
                     }
+                    // omitted fields are initialized with their default values
+                    let field_count = hir.registry().struct_data(struct_id).fields.len();
+                    for idx in 0..field_count {
+                        let field_id = hir::StructFieldID::new(idx);
+                        let field = hir.registry().struct_data(struct_id).field(field_id);
+                        let name_id = field.name.id;
+                        if !struct_init.input.iter().any(|init| init.name.id == name_id) {
+                            add_field_default_const_dependency(
+                                hir, emit, tree, parent_id, struct_id, field_id,
+                            )?;
+                        }
+                    }
                     Ok(())
                 } else {
                     Err(parent_id)
@@ -701,6 +992,10 @@ fn add_expr_const_dependencies<'hir, 'ast>(
             add_expr_const_dependencies(hir, emit, tree, parent_id, origin_id, bin.rhs)?;
             Ok(())
         }
+        ast::ExprKind::Range { .. } => {
+            error_cannot_use_in_constants(hir, emit, origin_id, expr.range, "range");
+            Err(parent_id)
+        }
     }
 }
 
@@ -712,6 +1007,7 @@ fn error_cannot_use_in_constants(
     name: &str,
 ) {
     emit.error(ErrorComp::new(
+        ErrorCode::NotConstant,
         format!("cannot use `{name}` expression in constants"),
         SourceRange::new(origin_id, range),
         None,
@@ -726,6 +1022,7 @@ fn error_cannot_refer_to_in_constants(
     name: &str,
 ) {
     emit.error(ErrorComp::new(
+        ErrorCode::NotConstant,
         format!("cannot refer to `{name}` in constants"),
         SourceRange::new(origin_id, range),
         None,
@@ -747,10 +1044,29 @@ fn resolve_const_dependency_tree<'hir>(
                 let expect = Expectation::HasType(hir::Type::Basic(data.basic), None); //@add range for basic type on enum
                 resolve_and_update_const_eval(hir, emit, variant.value, expect);
             }
+            ConstDependency::EnumSize(id) => {
+                let size_eval = resolve_enum_size(hir, emit, id);
+                hir.registry_mut().enum_data_mut(id).size_eval = size_eval;
+            }
             ConstDependency::StructSize(id) => {
                 let size_eval = resolve_struct_size(hir, emit, id);
                 hir.registry_mut().struct_data_mut(id).size_eval = size_eval;
             }
+            ConstDependency::FieldDefault(id, field_id) => {
+                let data = hir.registry().struct_data(id);
+                let item = hir.registry().struct_item(id);
+                let field = data.field(field_id);
+
+                let expect_src = item
+                    .fields
+                    .iter()
+                    .find(|item_field| item_field.name.range == field.name.range)
+                    .map(|item_field| SourceRange::new(data.origin_id, item_field.ty.range));
+                let expect = Expectation::HasType(field.ty, expect_src);
+                if let Some(eval_id) = field.default {
+                    resolve_and_update_const_eval(hir, emit, eval_id, expect);
+                }
+            }
             ConstDependency::Const(id) => {
                 let data = hir.registry().const_data(id);
                 let item = hir.registry().const_item(id);
@@ -765,7 +1081,9 @@ fn resolve_const_dependency_tree<'hir>(
 
                 let expect_src = SourceRange::new(data.origin_id, item.ty.range);
                 let expect = Expectation::HasType(data.ty, Some(expect_src));
-                resolve_and_update_const_eval(hir, emit, data.value, expect);
+                if resolve_and_update_const_eval(hir, emit, data.value, expect) {
+                    hir.registry_mut().add_global_init(id);
+                }
             }
             ConstDependency::ArrayLen(eval_id) => {
                 let expect = Expectation::HasType(hir::Type::USIZE, None);
@@ -775,21 +1093,24 @@ fn resolve_const_dependency_tree<'hir>(
     }
 }
 
+/// returns `false` if the eval was already resolved
 fn resolve_and_update_const_eval<'hir>(
     hir: &mut HirData<'hir, '_, '_>,
     emit: &mut HirEmit<'hir>,
     eval_id: hir::ConstEvalID,
     expect: Expectation<'hir>,
-) {
+) -> bool {
     let (eval, origin_id) = *hir.registry().const_eval(eval_id);
 
+    // same dependency can be added to the tree multiple times
     let value = match eval {
         hir::ConstEval::Unresolved(expr) => resolve_const_expr(hir, emit, origin_id, expect, expr),
-        _ => panic!("calling `resolve_const_expr` on already resolved expr"),
+        _ => return false,
     };
 
     let (eval, _) = hir.registry_mut().const_eval_mut(eval_id);
     *eval = hir::ConstEval::ResolvedValue(emit.const_intern.intern(value));
+    true
 }
 
 #[must_use]
@@ -808,6 +1129,7 @@ pub fn resolve_const_expr<'hir>(
             id: InternID::dummy(),
             range: TextRange::empty_at(0.into()),
         },
+        poly_params: None,
         params: &[],
         return_ty: hir::Type::VOID,
         block: None,
@@ -821,7 +1143,8 @@ pub fn resolve_const_expr<'hir>(
     if emit.did_error(error_count) {
         hir::ConstValue::Error
     } else {
-        fold_const_expr(hir, emit, origin_id, hir_expr.expr)
+        let src = SourceRange::new(origin_id, expr.0.range);
+        fold_const_expr(hir, emit, src, hir_expr.expr)
     }
 }
 
@@ -849,6 +1172,7 @@ fn resolve_struct_size(
             new_size
         } else {
             emit.error(ErrorComp::new(
+                ErrorCode::TypeSizeOverflow,
                 format!(
                     "struct size overflow: `{}` + `{}` (when computing: total_size + field_size)",
                     size, field_size
@@ -865,6 +1189,50 @@ fn resolve_struct_size(
     hir::SizeEval::Resolved(hir::Size::new(size, align))
 }
 
+// tag is stored first, each variant payload is laid out after it like struct fields
+fn resolve_enum_size(hir: &HirData, emit: &mut HirEmit, enum_id: hir::EnumID) -> hir::SizeEval {
+    let data = hir.registry().enum_data(enum_id);
+    let tag_size = pass_5::basic_type_size(hir.target(), data.basic);
+    let mut size: u64 = tag_size.size();
+    let mut align: u64 = tag_size.align();
+
+    for variant in data.variants {
+        let mut variant_size: u64 = tag_size.size();
+
+        for field_ty in variant.fields {
+            let (field_size, field_align) = match pass_5::type_size(
+                hir,
+                emit,
+                *field_ty,
+                SourceRange::new(data.origin_id, variant.name.range),
+            ) {
+                Some(size) => (size.size(), size.align()),
+                None => return hir::SizeEval::ResolvedError,
+            };
+            variant_size = aligned_size(variant_size, field_align);
+            variant_size = if let Some(new_size) = variant_size.checked_add(field_size) {
+                new_size
+            } else {
+                emit.error(ErrorComp::new(
+                    ErrorCode::TypeSizeOverflow,
+                    format!(
+                        "enum size overflow: `{}` + `{}` (when computing: variant_size + field_size)",
+                        variant_size, field_size
+                    ),
+                    SourceRange::new(data.origin_id, variant.name.range),
+                    None,
+                ));
+                return hir::SizeEval::ResolvedError;
+            };
+            align = align.max(field_align);
+        }
+        size = size.max(variant_size);
+    }
+
+    size = aligned_size(size, align);
+    hir::SizeEval::Resolved(hir::Size::new(size, align))
+}
+
 //@remove asserts later on when compiler is stable? 02.05.24
 fn aligned_size(size: u64, align: u64) -> u64 {
     assert!(align != 0);
@@ -872,7 +1240,7 @@ fn aligned_size(size: u64, align: u64) -> u64 {
     size.wrapping_add(align).wrapping_sub(1) & !align.wrapping_sub(1)
 }
 
-//@check int, float value range constraints 14.05.24
+//@check int literal value range constraints 14.05.24
 // same for typecheck_int_lit etc, regular expressions checking
 // should later be merged with this constant resolution / folding flow
 
@@ -880,13 +1248,12 @@ fn aligned_size(size: u64, align: u64) -> u64 {
 // also ajust const dependencies since globals wont be a dependency anymore they are not allowed
 //@more refined message for each incompatible expression type
 
-//@assume invalid expressions to be already checked for?
-// if `force` return ConstValue::Error
-// else panic!("unexpected `name` in constant");
+//@hir expressions dont store their ranges, `src` is the range of entire constant expression
+// errors reported during folding point to it instead of exact sub expression 10.06.24
 pub fn fold_const_expr<'hir>(
     hir: &HirData<'hir, '_, '_>,
     emit: &mut HirEmit<'hir>,
-    origin_id: ModuleID,
+    src: SourceRange,
     expr: &'hir hir::Expr<'hir>,
 ) -> hir::ConstValue<'hir> {
     let result = match *expr {
@@ -900,57 +1267,50 @@ pub fn fold_const_expr<'hir>(
             field_id,
             deref,
             ..
-        } => Ok(fold_struct_field(
-            hir, emit, origin_id, target, field_id, deref,
-        )),
+        } => Ok(fold_struct_field(hir, emit, src, target, field_id, deref)),
         hir::Expr::SliceField {
             target,
             first_ptr,
             deref,
-        } => Ok(fold_slice_field(
-            hir, emit, origin_id, target, first_ptr, deref,
-        )),
-        hir::Expr::Index { target, access } => Ok(fold_index(hir, emit, origin_id, target, access)),
+        } => Ok(fold_slice_field(hir, emit, src, target, first_ptr, deref)),
+        hir::Expr::Index { target, access } => Ok(fold_index(hir, emit, src, target, access)),
         hir::Expr::Slice { .. } => Err("slice"),
-        hir::Expr::Cast { target, into, kind } => Err("cast"), //@todo cast 10.06.24
+        hir::Expr::Cast { target, into, kind } => {
+            Ok(fold_cast(hir, emit, src, target, *into, kind))
+        }
         hir::Expr::LocalVar { .. } => Err("local var"),
         hir::Expr::ParamVar { .. } => Err("param var"),
         hir::Expr::ConstVar { const_id } => Ok(fold_const_var(hir, emit, const_id)),
-        hir::Expr::GlobalVar { .. } => Err("global vall"), //@custom message
+        hir::Expr::GlobalVar { global_id } => Ok(fold_global_var(hir, emit, global_id)),
         hir::Expr::CallDirect { .. } => Err("call direct"),
+        hir::Expr::CallPoly { .. } => Err("call poly"),
         hir::Expr::CallIndirect { .. } => Err("call indirect"),
+        hir::Expr::Format { .. } => Err("format"),
         hir::Expr::StructInit { struct_id, input } => {
-            Ok(fold_struct_init(hir, emit, origin_id, struct_id, input))
-        }
-        hir::Expr::ArrayInit { array_init } => {
-            Ok(fold_array_init(hir, emit, origin_id, array_init))
+            Ok(fold_struct_init(hir, emit, src, struct_id, input))
         }
+        hir::Expr::VariantInit { .. } => Err("variant init"),
+        hir::Expr::ArrayInit { array_init } => Ok(fold_array_init(hir, emit, src, array_init)),
         hir::Expr::ArrayRepeat { array_repeat } => {
-            Ok(fold_array_repeat(hir, emit, origin_id, array_repeat))
+            Ok(fold_array_repeat(hir, emit, src, array_repeat))
         }
         hir::Expr::Deref { .. } => Err("deref"),
         hir::Expr::Address { .. } => Err("address"),
-        hir::Expr::Unary { op, rhs } => Ok(fold_unary_expr(hir, emit, origin_id, op, rhs)),
-        hir::Expr::Binary {
-            op,
-            lhs,
-            rhs,
-            lhs_signed_int,
-        } => Err("binary"), //@todo binary 10.06.24
+        hir::Expr::Unary { op, rhs } => Ok(fold_unary_expr(hir, emit, src, op, rhs)),
+        hir::Expr::Binary { op, lhs, rhs, .. } => {
+            Ok(fold_binary_expr(hir, emit, src, op, lhs, rhs))
+        }
     };
 
     match result {
         Ok(value) => value,
         Err(expr_name) => {
-            //@range not available
-            //emit.error(ErrorComp::new(
-            //    format!("cannot use `{expr_name}` expression in constants"),
-            //    SourceRange::new(origin_id, expr.0.range),
-            //    None,
-            //));
-            emit.error(ErrorComp::message(format!(
-                "cannot use `{expr_name}` expression in constants"
-            )));
+            emit.error(ErrorComp::new(
+                ErrorCode::NotConstant,
+                format!("cannot use `{expr_name}` expression in constants"),
+                src,
+                None,
+            ));
             hir::ConstValue::Error
         }
     }
@@ -959,20 +1319,22 @@ pub fn fold_const_expr<'hir>(
 fn fold_struct_field<'hir>(
     hir: &HirData<'hir, '_, '_>,
     emit: &mut HirEmit<'hir>,
-    origin_id: ModuleID,
+    src: SourceRange,
     target: &'hir hir::Expr<'hir>,
     field_id: hir::StructFieldID,
     deref: bool,
 ) -> hir::ConstValue<'hir> {
     if deref {
-        //@expr range required
-        emit.error(ErrorComp::message(
+        emit.error(ErrorComp::new(
+            ErrorCode::NotConstant,
             "cannot perform implicit dereference in constant expression",
+            src,
+            None,
         ));
         return hir::ConstValue::Error;
     }
 
-    let target = fold_const_expr(hir, emit, origin_id, target);
+    let target = fold_const_expr(hir, emit, src, target);
     match target {
         hir::ConstValue::Struct { struct_ } => {
             let value_id = struct_.fields[field_id.index()];
@@ -985,29 +1347,31 @@ fn fold_struct_field<'hir>(
 fn fold_slice_field<'hir>(
     hir: &HirData<'hir, '_, '_>,
     emit: &mut HirEmit<'hir>,
-    origin_id: ModuleID,
+    src: SourceRange,
     target: &'hir hir::Expr<'hir>,
     first_ptr: bool,
     deref: bool,
 ) -> hir::ConstValue<'hir> {
     if deref {
-        //@expr range required
-        emit.error(ErrorComp::message(
+        emit.error(ErrorComp::new(
+            ErrorCode::NotConstant,
             "cannot perform implicit dereference in constant expression",
+            src,
+            None,
         ));
         return hir::ConstValue::Error;
     }
 
-    let target = fold_const_expr(hir, emit, origin_id, target);
+    let target = fold_const_expr(hir, emit, src, target);
     match target {
-        hir::ConstValue::String { id, c_string } => {
-            if !first_ptr && !c_string {
+        hir::ConstValue::String { id, kind } => {
+            if !first_ptr && kind == ast::StringKind::Regular {
                 let string = hir.intern_string().get_str(id);
                 let len = string.len();
                 hir::ConstValue::Int {
                     val: len as u64,
                     neg: false,
-                    ty: ast::BasicType::Usize,
+                    ty: BasicType::Usize,
                 }
             } else {
                 hir::ConstValue::Error
@@ -1020,46 +1384,52 @@ fn fold_slice_field<'hir>(
 fn fold_index<'hir>(
     hir: &HirData<'hir, '_, '_>,
     emit: &mut HirEmit<'hir>,
-    origin_id: ModuleID,
+    src: SourceRange,
     target: &'hir hir::Expr<'hir>,
     access: &'hir hir::IndexAccess<'hir>,
 ) -> hir::ConstValue<'hir> {
-    let target_value = fold_const_expr(hir, emit, origin_id, target);
-    let index_value = fold_const_expr(hir, emit, origin_id, access.index);
+    let target_value = fold_const_expr(hir, emit, src, target);
+    let index_value = fold_const_expr(hir, emit, src, access.index);
 
     let index = match index_value {
-        hir::ConstValue::Int { val, neg, ty } => {
-            if !neg {
-                Some(val)
-            } else {
-                None
+        hir::ConstValue::Int { val, neg, .. } if !neg => val,
+        _ => return hir::ConstValue::Error,
+    };
+
+    let (len, value_id) = match target_value {
+        hir::ConstValue::Array { array } => {
+            let value_id = array.values.get(index as usize).copied();
+            (array.len, value_id)
+        }
+        hir::ConstValue::ArrayRepeat { len, value } => (len, Some(value)),
+        hir::ConstValue::String {
+            id,
+            kind: ast::StringKind::Bytes,
+        } => {
+            let bytes = hir.intern_string().get_str(id).as_bytes();
+            if let Some(&byte) = bytes.get(index as usize) {
+                return hir::ConstValue::Int {
+                    val: byte as u64,
+                    neg: false,
+                    ty: BasicType::U8,
+                };
             }
+            (bytes.len() as u64, None)
         }
-        _ => None,
+        _ => return hir::ConstValue::Error,
     };
-    if let Some(index) = index {
-        //@bounds check, same with normal array
-        match target_value {
-            hir::ConstValue::Array { array } => {
-                if index >= array.len {
-                    //@no source range available
-                    hir::ConstValue::Error
-                } else {
-                    emit.const_intern.get(array.values[index as usize])
-                }
-            }
-            hir::ConstValue::ArrayRepeat { len, value } => {
-                if index >= len {
-                    //@no source range available
-                    hir::ConstValue::Error
-                } else {
-                    emit.const_intern.get(value)
-                }
-            }
-            _ => hir::ConstValue::Error,
+
+    match value_id {
+        Some(value_id) if index < len => emit.const_intern.get(value_id),
+        _ => {
+            emit.error(ErrorComp::new(
+                ErrorCode::ConstIndexOutOfBounds,
+                format!("index out of bounds in constant expression\nindex is `{index}` but length is `{len}`"),
+                src,
+                None,
+            ));
+            hir::ConstValue::Error
         }
-    } else {
-        hir::ConstValue::Error
     }
 }
 
@@ -1076,10 +1446,23 @@ fn fold_const_var<'hir>(
     }
 }
 
+fn fold_global_var<'hir>(
+    hir: &HirData<'hir, '_, '_>,
+    emit: &mut HirEmit<'hir>,
+    global_id: hir::GlobalID,
+) -> hir::ConstValue<'hir> {
+    let data = hir.registry().global_data(global_id);
+    let (eval, _) = hir.registry().const_eval(data.value);
+    match *eval {
+        hir::ConstEval::ResolvedValue(value_id) => emit.const_intern.get(value_id),
+        _ => panic!("unresolved global"),
+    }
+}
+
 fn fold_struct_init<'hir>(
     hir: &HirData<'hir, '_, '_>,
     emit: &mut HirEmit<'hir>,
-    origin_id: ModuleID,
+    src: SourceRange,
     struct_id: hir::StructID,
     input: &'hir [hir::StructFieldInit<'hir>],
 ) -> hir::ConstValue<'hir> {
@@ -1088,7 +1471,7 @@ fn fold_struct_init<'hir>(
     value_ids.resize(input.len(), error_id);
 
     for init in input {
-        let value = fold_const_expr(hir, emit, origin_id, init.expr);
+        let value = fold_const_expr(hir, emit, src, init.expr);
         value_ids[init.field_id.index()] = emit.const_intern.intern(value);
     }
 
@@ -1101,13 +1484,13 @@ fn fold_struct_init<'hir>(
 fn fold_array_init<'hir>(
     hir: &HirData<'hir, '_, '_>,
     emit: &mut HirEmit<'hir>,
-    origin_id: ModuleID,
+    src: SourceRange,
     array_init: &'hir hir::ArrayInit<'hir>,
 ) -> hir::ConstValue<'hir> {
     let mut value_ids = Vec::with_capacity(array_init.input.len());
 
     for &init in array_init.input {
-        let value = fold_const_expr(hir, emit, origin_id, init);
+        let value = fold_const_expr(hir, emit, src, init);
         value_ids.push(emit.const_intern.intern(value));
     }
 
@@ -1123,10 +1506,10 @@ fn fold_array_init<'hir>(
 fn fold_array_repeat<'hir>(
     hir: &HirData<'hir, '_, '_>,
     emit: &mut HirEmit<'hir>,
-    origin_id: ModuleID,
+    src: SourceRange,
     array_repeat: &'hir hir::ArrayRepeat<'hir>,
 ) -> hir::ConstValue<'hir> {
-    let value = fold_const_expr(hir, emit, origin_id, array_repeat.expr);
+    let value = fold_const_expr(hir, emit, src, array_repeat.expr);
 
     hir::ConstValue::ArrayRepeat {
         value: emit.const_intern.intern(value),
@@ -1134,21 +1517,161 @@ fn fold_array_repeat<'hir>(
     }
 }
 
+fn fold_cast<'hir>(
+    hir: &HirData<'hir, '_, '_>,
+    emit: &mut HirEmit<'hir>,
+    src: SourceRange,
+    target: &'hir hir::Expr<'hir>,
+    into: hir::Type<'hir>,
+    kind: hir::CastKind,
+) -> hir::ConstValue<'hir> {
+    let target_value = fold_const_expr(hir, emit, src, target);
+    if let hir::CastKind::NoOp = kind {
+        return target_value;
+    }
+    let into = match into {
+        hir::Type::Basic(basic) => basic,
+        _ => return hir::ConstValue::Error,
+    };
+
+    // `bool`, `char` and enum values are cast by their integer value
+    let target_value = match target_value {
+        hir::ConstValue::Bool { val } => hir::ConstValue::Int {
+            val: val as u64,
+            neg: false,
+            ty: BasicType::U8,
+        },
+        hir::ConstValue::Char { val } => hir::ConstValue::Int {
+            val: val as u64,
+            neg: false,
+            ty: BasicType::U32,
+        },
+        hir::ConstValue::EnumVariant {
+            enum_id,
+            variant_id,
+        } => {
+            let variant = hir.registry().enum_data(enum_id).variant(variant_id);
+            match hir.registry().const_eval(variant.value).0 {
+                hir::ConstEval::ResolvedValue(value_id) => emit.const_intern.get(value_id),
+                _ => return hir::ConstValue::Error,
+            }
+        }
+        _ => target_value,
+    };
+
+    match kind {
+        hir::CastKind::Error | hir::CastKind::NoOp => hir::ConstValue::Error,
+        hir::CastKind::Pointer_to_Int | hir::CastKind::Int_to_Pointer => {
+            emit.error(ErrorComp::new(
+                ErrorCode::NotConstant,
+                "cannot cast between pointers and integers in constant expression",
+                src,
+                None,
+            ));
+            hir::ConstValue::Error
+        }
+        hir::CastKind::Uint_Zero_Extend if into == BasicType::Char => match target_value {
+            hir::ConstValue::Int { val, .. } => hir::ConstValue::Char {
+                val: char::from(val as u8),
+            },
+            _ => hir::ConstValue::Error,
+        },
+        hir::CastKind::Integer_NoOp if into == BasicType::Char => match target_value {
+            hir::ConstValue::Int { val, .. } => fold_cast_char(emit, src, val),
+            _ => hir::ConstValue::Error,
+        },
+        hir::CastKind::Integer_NoOp
+        | hir::CastKind::Integer_Trunc
+        | hir::CastKind::Sint_Sign_Extend
+        | hir::CastKind::Uint_Zero_Extend => match target_value {
+            hir::ConstValue::Int { val, neg, .. } => {
+                let value = int_wrap(hir.target(), int_to_i128(val, neg), into);
+                fold_result(emit, src, int_from_i128(hir.target(), value, into))
+            }
+            _ => hir::ConstValue::Error,
+        },
+        hir::CastKind::Float_to_Sint | hir::CastKind::Float_to_Uint => match target_value {
+            hir::ConstValue::Float { val, .. } => {
+                let value = val.trunc();
+                let (min, max) = int_range(hir.target(), into);
+
+                if value.is_finite() && value >= min as f64 && value <= max as f64 {
+                    fold_result(emit, src, int_from_i128(hir.target(), value as i128, into))
+                } else {
+                    emit.error(ErrorComp::new(
+                        ErrorCode::ConstOverflow,
+                        format!(
+                            "cannot cast float constant `{val}` into `{}`, value is out of range",
+                            into.as_str()
+                        ),
+                        src,
+                        None,
+                    ));
+                    hir::ConstValue::Error
+                }
+            }
+            _ => hir::ConstValue::Error,
+        },
+        hir::CastKind::Sint_to_Float | hir::CastKind::Uint_to_Float => match target_value {
+            hir::ConstValue::Int { val, neg, .. } => {
+                let value = int_to_i128(val, neg) as f64;
+                fold_result(emit, src, float_from_f64(value, Some(into)))
+            }
+            _ => hir::ConstValue::Error,
+        },
+        hir::CastKind::Float_Trunc | hir::CastKind::Float_Extend => match target_value {
+            hir::ConstValue::Float { val, .. } => {
+                fold_result(emit, src, float_from_f64(val, Some(into)))
+            }
+            _ => hir::ConstValue::Error,
+        },
+    }
+}
+
+fn fold_cast_char<'hir>(
+    emit: &mut HirEmit<'hir>,
+    src: SourceRange,
+    val: u64,
+) -> hir::ConstValue<'hir> {
+    match char::from_u32(val as u32) {
+        Some(val) => hir::ConstValue::Char { val },
+        None => {
+            emit.error(ErrorComp::new(
+                ErrorCode::ConstOverflow,
+                format!("cannot cast `{val}` into `char`, value is not a unicode scalar value"),
+                src,
+                None,
+            ));
+            hir::ConstValue::Error
+        }
+    }
+}
+
 fn fold_unary_expr<'hir>(
     hir: &HirData<'hir, '_, '_>,
     emit: &mut HirEmit<'hir>,
-    origin_id: ModuleID,
+    src: SourceRange,
     op: ast::UnOp,
     rhs: &'hir hir::Expr<'hir>,
 ) -> hir::ConstValue<'hir> {
-    let rhs_value = fold_const_expr(hir, emit, origin_id, rhs);
+    let rhs_value = fold_const_expr(hir, emit, src, rhs);
     match op {
         ast::UnOp::Neg => match rhs_value {
-            hir::ConstValue::Int { val, neg, ty } => hir::ConstValue::Int { val, neg: !neg, ty },
+            hir::ConstValue::Int { val, neg, ty } => fold_result(
+                emit,
+                src,
+                int_from_i128(hir.target(), -int_to_i128(val, neg), ty),
+            ),
             hir::ConstValue::Float { val, ty } => hir::ConstValue::Float { val: -val, ty },
             _ => hir::ConstValue::Error,
         },
-        ast::UnOp::BitNot => hir::ConstValue::Error,
+        ast::UnOp::BitNot => match rhs_value {
+            hir::ConstValue::Int { val, neg, ty } => {
+                let value = int_wrap(hir.target(), !int_to_i128(val, neg), ty);
+                fold_result(emit, src, int_from_i128(hir.target(), value, ty))
+            }
+            _ => hir::ConstValue::Error,
+        },
         ast::UnOp::LogicNot => match rhs_value {
             hir::ConstValue::Bool { val } => hir::ConstValue::Bool { val: !val },
             _ => hir::ConstValue::Error,
@@ -1156,93 +1679,267 @@ fn fold_unary_expr<'hir>(
     }
 }
 
-impl<'hir> hir::ConstValue<'hir> {
-    fn test() {
-        //const shift_test: i32 = i32::MAX + i32::MAX;
-
-        let lhs: i128 = 10;
-        let rhs: i128 = -10;
-
-        let add = lhs.checked_add(rhs);
-        let sub = lhs.checked_sub(rhs);
-        let mul = lhs.checked_mul(rhs);
-        let div = lhs.checked_div(rhs); //@check / 0 separately
-        let rem = lhs.checked_rem(rhs); //@check / 0 separately
-
-        let shl = lhs.checked_shl(rhs as u32); //@check for positive
-        let shr = lhs.checked_shr(rhs as u32); //@check for positive
-        let is_eq = lhs == rhs;
-        let not_eq = lhs != rhs;
-        let less = lhs < rhs;
-        let less_eq = lhs <= rhs;
-        let greater = lhs > rhs;
-        let greater_eq = lhs >= rhs;
+fn fold_binary_expr<'hir>(
+    hir: &HirData<'hir, '_, '_>,
+    emit: &mut HirEmit<'hir>,
+    src: SourceRange,
+    op: ast::BinOp,
+    lhs: &'hir hir::Expr<'hir>,
+    rhs: &'hir hir::Expr<'hir>,
+) -> hir::ConstValue<'hir> {
+    let lhs_value = fold_const_expr(hir, emit, src, lhs);
+    let rhs_value = fold_const_expr(hir, emit, src, rhs);
+
+    match (lhs_value, rhs_value) {
+        (
+            hir::ConstValue::Int { val, neg, ty },
+            hir::ConstValue::Int {
+                val: val2,
+                neg: neg2,
+                ..
+            },
+        ) => {
+            let lhs = int_to_i128(val, neg);
+            let rhs = int_to_i128(val2, neg2);
+            fold_result(emit, src, fold_binary_int(hir.target(), op, lhs, rhs, ty))
+        }
+        (hir::ConstValue::Float { val, ty }, hir::ConstValue::Float { val: val2, .. }) => {
+            fold_result(emit, src, fold_binary_float(op, val, val2, ty))
+        }
+        (hir::ConstValue::Bool { val }, hir::ConstValue::Bool { val: val2 }) => match op {
+            ast::BinOp::LogicAnd => hir::ConstValue::Bool { val: val && val2 },
+            ast::BinOp::LogicOr => hir::ConstValue::Bool { val: val || val2 },
+            _ => fold_compare(op, val, val2).unwrap_or(hir::ConstValue::Error),
+        },
+        (hir::ConstValue::Char { val }, hir::ConstValue::Char { val: val2 }) => {
+            fold_compare(op, val, val2).unwrap_or(hir::ConstValue::Error)
+        }
+        (
+            hir::ConstValue::Procedure { proc_id },
+            hir::ConstValue::Procedure { proc_id: proc_id2 },
+        ) => fold_compare(op, proc_id.index(), proc_id2.index()).unwrap_or(hir::ConstValue::Error),
+        _ => hir::ConstValue::Error,
     }
+}
 
-    fn add(self, other: hir::ConstValue<'hir>) -> hir::ConstValue<'hir> {
-        match (self, other) {
-            (hir::ConstValue::IntS(lhs), hir::ConstValue::IntS(rhs)) => {
-                Self::from_i64_opt(lhs.checked_add(rhs))
+/// constant folding failure, reported at the folded expression
+pub enum FoldError {
+    Overflow(String),
+    DivisionByZero(String),
+}
+
+fn fold_result<'hir>(
+    emit: &mut HirEmit<'hir>,
+    src: SourceRange,
+    result: Result<hir::ConstValue<'hir>, FoldError>,
+) -> hir::ConstValue<'hir> {
+    let (code, msg) = match result {
+        Ok(value) => return value,
+        Err(FoldError::Overflow(msg)) => (ErrorCode::ConstOverflow, msg),
+        Err(FoldError::DivisionByZero(msg)) => (ErrorCode::ConstDivisionByZero, msg),
+    };
+    emit.error(ErrorComp::new(code, msg, src, None));
+    hir::ConstValue::Error
+}
+
+/// unsupported operators fold into `ConstValue::Error` without an error
+pub fn fold_binary_int<'hir>(
+    target: TargetTriple,
+    op: ast::BinOp,
+    lhs: i128,
+    rhs: i128,
+    ty: BasicType,
+) -> Result<hir::ConstValue<'hir>, FoldError> {
+    if let Some(value) = fold_compare(op, lhs, rhs) {
+        return Ok(value);
+    }
+
+    let value = match op {
+        ast::BinOp::Add => lhs.checked_add(rhs),
+        ast::BinOp::Sub => lhs.checked_sub(rhs),
+        ast::BinOp::Mul => lhs.checked_mul(rhs),
+        ast::BinOp::Div | ast::BinOp::Rem => {
+            if rhs == 0 {
+                return Err(error_division_by_zero(op, lhs.to_string()));
             }
-            (hir::ConstValue::IntS(lhs), hir::ConstValue::IntU(rhs)) => {
-                Self::from_i64_opt(lhs.checked_add_unsigned(rhs))
+            if let ast::BinOp::Div = op {
+                lhs.checked_div(rhs)
+            } else {
+                lhs.checked_rem(rhs)
             }
-            (hir::ConstValue::IntU(lhs), hir::ConstValue::IntU(rhs)) => {
-                Self::from_u64_opt(lhs.checked_add(rhs))
+        }
+        ast::BinOp::BitAnd => Some(lhs & rhs),
+        ast::BinOp::BitOr => Some(lhs | rhs),
+        ast::BinOp::BitXor => Some(lhs ^ rhs),
+        ast::BinOp::BitShl | ast::BinOp::BitShr => {
+            let bit_width = int_bit_width(target, ty);
+            if rhs < 0 || rhs >= bit_width as i128 {
+                return Err(FoldError::Overflow(format!(
+                    "shift amount `{rhs}` is out of range for `{}`, expected range 0..<{bit_width}",
+                    ty.as_str()
+                )));
             }
-            (hir::ConstValue::IntU(lhs), hir::ConstValue::IntS(rhs)) => {
-                Self::from_u64_opt(lhs.checked_add_signed(rhs))
+            if let ast::BinOp::BitShl = op {
+                Some(int_wrap(target, lhs << rhs, ty))
+            } else {
+                Some(lhs >> rhs)
             }
-            _ => hir::ConstValue::Error,
         }
+        _ => return Ok(hir::ConstValue::Error),
+    };
+
+    match value {
+        Some(value) => int_from_i128(target, value, ty),
+        None => Err(FoldError::Overflow(format!(
+            "constant integer overflow: `{lhs} {} {rhs}` does not fit into `{}`",
+            op.as_str(),
+            ty.as_str()
+        ))),
+    }
+}
+
+/// unsupported operators fold into `ConstValue::Error` without an error
+pub fn fold_binary_float<'hir>(
+    op: ast::BinOp,
+    lhs: f64,
+    rhs: f64,
+    ty: Option<BasicType>,
+) -> Result<hir::ConstValue<'hir>, FoldError> {
+    if let Some(value) = fold_compare(op, lhs, rhs) {
+        return Ok(value);
     }
 
-    fn sub(self, other: hir::ConstValue<'hir>) -> hir::ConstValue<'hir> {
-        match (self, other) {
-            (hir::ConstValue::IntS(lhs), hir::ConstValue::IntS(rhs)) => {
-                Self::from_i64_opt(lhs.checked_sub(rhs))
-            }
-            (hir::ConstValue::IntS(lhs), hir::ConstValue::IntU(rhs)) => {
-                Self::from_i64_opt(lhs.checked_sub_unsigned(rhs))
+    let value = match op {
+        ast::BinOp::Add => lhs + rhs,
+        ast::BinOp::Sub => lhs - rhs,
+        ast::BinOp::Mul => lhs * rhs,
+        ast::BinOp::Div => {
+            if rhs == 0.0 {
+                return Err(error_division_by_zero(op, lhs.to_string()));
             }
-            (hir::ConstValue::IntU(lhs), hir::ConstValue::IntU(rhs)) => {
-                Self::from_u64_opt(lhs.checked_sub(rhs))
-            }
-            (hir::ConstValue::IntU(lhs), hir::ConstValue::IntS(rhs)) => {
-                if let Some(rhs) = Self::i64_to_unsigned(rhs) {
-                    Self::from_u64_opt(lhs.checked_sub(rhs))
-                } else {
-                    eprintln!("error in const sub u - s int operation");
-                    hir::ConstValue::Error
-                }
-            }
-            _ => hir::ConstValue::Error,
+            lhs / rhs
         }
+        _ => return Ok(hir::ConstValue::Error),
+    };
+    float_from_f64(value, ty)
+}
+
+pub fn fold_compare<'hir, T: PartialOrd>(
+    op: ast::BinOp,
+    lhs: T,
+    rhs: T,
+) -> Option<hir::ConstValue<'hir>> {
+    let val = match op {
+        ast::BinOp::IsEq => lhs == rhs,
+        ast::BinOp::NotEq => lhs != rhs,
+        ast::BinOp::Less => lhs < rhs,
+        ast::BinOp::LessEq => lhs <= rhs,
+        ast::BinOp::Greater => lhs > rhs,
+        ast::BinOp::GreaterEq => lhs >= rhs,
+        _ => return None,
+    };
+    Some(hir::ConstValue::Bool { val })
+}
+
+fn error_division_by_zero(op: ast::BinOp, lhs: String) -> FoldError {
+    FoldError::DivisionByZero(format!(
+        "division by zero in constant expression: `{lhs} {} 0`",
+        op.as_str()
+    ))
+}
+
+pub fn int_to_i128(val: u64, neg: bool) -> i128 {
+    if neg {
+        -(val as i128)
+    } else {
+        val as i128
     }
+}
 
-    fn i64_to_unsigned(val: i64) -> Option<u64> {
-        if val >= 0 {
-            Some(val as u64)
-        } else {
-            None
-        }
+pub fn int_from_i128<'hir>(
+    target: TargetTriple,
+    value: i128,
+    ty: BasicType,
+) -> Result<hir::ConstValue<'hir>, FoldError> {
+    let (min, max) = int_range(target, ty);
+
+    if value < min || value > max {
+        return Err(FoldError::Overflow(format!(
+            "constant integer overflow: value `{value}` does not fit into `{}`\nexpected range is {min}..={max}",
+            ty.as_str()
+        )));
     }
 
-    fn from_i64_opt(val: Option<i64>) -> hir::ConstValue<'hir> {
-        if let Some(val) = val {
-            hir::ConstValue::IntS(val)
-        } else {
-            eprintln!("error in const signed int operation");
-            hir::ConstValue::Error
-        }
+    Ok(hir::ConstValue::Int {
+        val: value.unsigned_abs() as u64,
+        neg: value < 0,
+        ty,
+    })
+}
+
+pub fn int_range(target: TargetTriple, ty: BasicType) -> (i128, i128) {
+    match ty {
+        BasicType::S8 => (i8::MIN as i128, i8::MAX as i128),
+        BasicType::S16 => (i16::MIN as i128, i16::MAX as i128),
+        BasicType::S32 => (i32::MIN as i128, i32::MAX as i128),
+        BasicType::S64 => (i64::MIN as i128, i64::MAX as i128),
+        BasicType::Ssize => match target.pointer_width() {
+            4 => (i32::MIN as i128, i32::MAX as i128),
+            _ => (i64::MIN as i128, i64::MAX as i128),
+        },
+        BasicType::U8 => (0, u8::MAX as i128),
+        BasicType::U16 => (0, u16::MAX as i128),
+        BasicType::U32 => (0, u32::MAX as i128),
+        BasicType::U64 => (0, u64::MAX as i128),
+        BasicType::Usize => match target.pointer_width() {
+            4 => (0, u32::MAX as i128),
+            _ => (0, u64::MAX as i128),
+        },
+        _ => panic!("int_range on non integer type"),
     }
+}
 
-    fn from_u64_opt(val: Option<u64>) -> hir::ConstValue<'hir> {
-        if let Some(val) = val {
-            hir::ConstValue::IntU(val)
-        } else {
-            eprintln!("error in const unsigned int operation");
-            hir::ConstValue::Error
+pub fn int_bit_width(target: TargetTriple, ty: BasicType) -> u32 {
+    match ty {
+        BasicType::S8 | BasicType::U8 => 8,
+        BasicType::S16 | BasicType::U16 => 16,
+        BasicType::S32 | BasicType::U32 => 32,
+        BasicType::S64 | BasicType::U64 => 64,
+        BasicType::Ssize | BasicType::Usize => target.pointer_width() as u32 * 8,
+        _ => panic!("int_bit_width on non integer type"),
+    }
+}
+
+/// wraps value into two's complement range of the integer type
+pub fn int_wrap(target: TargetTriple, value: i128, ty: BasicType) -> i128 {
+    let bit_width = int_bit_width(target, ty);
+    let wrapped = value & ((1_i128 << bit_width) - 1);
+    let (min, _) = int_range(target, ty);
+
+    if min < 0 && wrapped >= (1_i128 << (bit_width - 1)) {
+        wrapped - (1_i128 << bit_width)
+    } else {
+        wrapped
+    }
+}
+
+fn float_from_f64<'hir>(
+    value: f64,
+    ty: Option<BasicType>,
+) -> Result<hir::ConstValue<'hir>, FoldError> {
+    let (val, finite) = match ty {
+        Some(BasicType::F32) => {
+            let value = value as f32;
+            (value as f64, value.is_finite())
         }
+        _ => (value, value.is_finite()),
+    };
+
+    if !finite {
+        return Err(FoldError::Overflow(format!(
+            "constant float overflow: value does not fit into `{}`",
+            ty.unwrap_or(BasicType::F64).as_str()
+        )));
     }
+    Ok(hir::ConstValue::Float { val, ty })
 }
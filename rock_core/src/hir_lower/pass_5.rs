@@ -1,19 +1,44 @@
-use super::hir_build::{self, HirData, HirEmit, SymbolKind};
-use super::proc_scope::{BlockEnter, DeferStatus, LoopStatus, ProcScope, VariableID};
+use super::const_fold;
+use super::hir_build::{self, HirData, HirEmit, Method, MethodOwner, MethodReceiver};
+use super::hir_build::{PolyInstance, PolyScope, SymbolKind};
+use super::pass_1;
+use super::place::{self, Addressability, PlaceBase, PlaceUse};
+use super::proc_scope::{BlockEnter, DeferStatus, LiteralValue, LoopStatus, ProcScope, VariableID};
+use super::{InlayHint, InlayHintKind};
 use crate::ast::{self, BasicType};
-use crate::error::{ErrorComp, Info, SourceRange, WarningComp};
+use crate::bitset::BitSet;
+use crate::error::{
+    DiagnosticEdit, DiagnosticFix, ErrorCode, ErrorComp, Info, SourceRange, WarningCode,
+    WarningComp,
+};
 use crate::hir;
-use crate::intern::InternID;
+use crate::hir::names::NameRefKind;
+use crate::intern::{self, InternID};
 use crate::session::ModuleID;
-use crate::text::{TextOffset, TextRange};
+use crate::target::TargetTriple;
+use crate::text::{self, TextOffset, TextRange};
 
 pub fn typecheck_procedures<'hir>(hir: &mut HirData<'hir, '_, '_>, emit: &mut HirEmit<'hir>) {
     for proc_id in hir.registry().proc_ids() {
+        if emit.error_limit_reached() {
+            break;
+        }
+        let item = hir.registry().proc_item(proc_id);
+        let data = hir.registry().proc_data(proc_id);
+        let poly_scope = data.poly_params.map(|poly_params| PolyScope {
+            proc_id,
+            poly_params,
+            poly_types: None,
+        });
+        emit.set_lint_allow(pass_1::lint_allow_set(hir, item.attrs));
+        emit.set_poly_scope(poly_scope);
         typecheck_proc(hir, emit, proc_id)
     }
+    emit.set_lint_allow(BitSet::EMPTY);
+    emit.set_poly_scope(None);
 }
 
-fn typecheck_proc<'hir>(
+pub fn typecheck_proc<'hir>(
     hir: &mut HirData<'hir, '_, '_>,
     emit: &mut HirEmit<'hir>,
     proc_id: hir::ProcID,
@@ -24,6 +49,7 @@ fn typecheck_proc<'hir>(
     if data.attr_set.contains(hir::ProcFlag::Variadic) {
         if data.params.is_empty() {
             emit.error(ErrorComp::new(
+                ErrorCode::InvalidVariadic,
                 "variadic procedures must have at least one named parameter",
                 SourceRange::new(data.origin_id, data.name.range),
                 None,
@@ -31,9 +57,28 @@ fn typecheck_proc<'hir>(
         }
     }
 
+    if data.attr_set.contains(hir::ProcFlag::External) {
+        for (idx, param) in data.params.iter().enumerate() {
+            let range = item.params[idx].ty.range;
+            check_ffi_type(hir, emit, data.origin_id, param.ty, range);
+        }
+        if let Some(return_ty) = item.return_ty {
+            check_ffi_type(hir, emit, data.origin_id, data.return_ty, return_ty.range);
+        }
+    }
+
     if data.attr_set.contains(hir::ProcFlag::Test) {
+        if data.poly_params.is_some() {
+            emit.error(ErrorComp::new(
+                ErrorCode::InvalidTestProc,
+                "procedures with #[test] attribute cannot be generic",
+                SourceRange::new(data.origin_id, data.name.range),
+                None,
+            ));
+        }
         if !data.params.is_empty() {
             emit.error(ErrorComp::new(
+                ErrorCode::InvalidTestProc,
                 "procedures with #[test] attribute cannot have any input parameters",
                 SourceRange::new(data.origin_id, data.name.range),
                 None,
@@ -42,6 +87,7 @@ fn typecheck_proc<'hir>(
         if !data.return_ty.is_void() {
             if let Some(return_ty) = item.return_ty {
                 emit.error(ErrorComp::new(
+                    ErrorCode::InvalidTestProc,
                     "procedures with #[test] attribute can only return `void`",
                     SourceRange::new(data.origin_id, return_ty.range),
                     None,
@@ -59,7 +105,12 @@ fn typecheck_proc<'hir>(
 
         let mut proc = ProcScope::new(data, expect);
         let block_res = typecheck_block(hir, emit, &mut proc, expect, block, BlockEnter::None);
+        for local_id in proc.undecided_literal_locals() {
+            let local = proc.get_local(local_id);
+            record_type_hint(hir, emit, data.origin_id, local.name.range.end(), local.ty);
+        }
         let locals = emit.arena.alloc_slice(proc.finish_locals());
+        emit.usage.add_proc_variables(proc.finish_usage(proc_id));
 
         let data = hir.registry_mut().proc_data_mut(proc_id);
         data.block = Some(block_res.block);
@@ -67,6 +118,61 @@ fn typecheck_proc<'hir>(
     }
 }
 
+fn check_ffi_type<'hir>(
+    hir: &HirData<'hir, '_, '_>,
+    emit: &mut HirEmit<'hir>,
+    origin_id: ModuleID,
+    ty: hir::Type<'hir>,
+    range: TextRange,
+) {
+    if let Some(reason) = type_ffi_unsafe(hir, ty, false) {
+        emit.error(ErrorComp::new(
+            ErrorCode::NotFfiSafe,
+            format!(
+                "type `{}` is not FFI-safe\n{reason}",
+                type_format(hir, emit, ty)
+            ),
+            SourceRange::new(origin_id, range),
+            None,
+        ));
+    }
+}
+
+/// returns the reason why type cannot be passed to or from `external` procedures
+fn type_ffi_unsafe(hir: &HirData, ty: hir::Type, in_struct: bool) -> Option<&'static str> {
+    match ty {
+        hir::Type::Error => None,
+        hir::Type::Basic(BasicType::F16) => Some("`f16` has no C equivalent, use `f32` instead"),
+        hir::Type::Basic(_) => None,
+        hir::Type::Enum(enum_id) => {
+            if hir.registry().enum_data(enum_id).has_fields() {
+                Some("enums with variant fields have no C equivalent")
+            } else {
+                None
+            }
+        }
+        hir::Type::Struct(struct_id) => {
+            let data = hir.registry().struct_data(struct_id);
+            data.fields
+                .iter()
+                .find_map(|field| type_ffi_unsafe(hir, field.ty, true))
+        }
+        hir::Type::Reference(..) => None,
+        hir::Type::Procedure(..) => None,
+        hir::Type::PolyProc(..) => None,
+        hir::Type::ArraySlice(_) => {
+            Some("slices have no C equivalent, pass `ptr` and `len` separately")
+        }
+        hir::Type::ArrayStatic(array) => {
+            if in_struct {
+                type_ffi_unsafe(hir, array.elem_ty, true)
+            } else {
+                Some("arrays cannot be passed by value, pass a reference instead")
+            }
+        }
+    }
+}
+
 pub fn type_matches<'hir>(
     hir: &HirData<'hir, '_, '_>,
     emit: &HirEmit<'hir>,
@@ -101,18 +207,129 @@ pub fn type_matches<'hir>(
             }
         }
         (hir::Type::ArrayStatic(array), hir::Type::ArrayStatic(array2)) => {
-            if let Some(len) = array_static_get_len(hir, emit, array.len) {
-                if let Some(len2) = array_static_get_len(hir, emit, array2.len) {
-                    return (len == len2) && type_matches(hir, emit, array.elem_ty, array2.elem_ty);
-                }
+            let len = array_static_get_len(hir, emit, array.len);
+            let len2 = array_static_get_len(hir, emit, array2.len);
+            // invalid length was already reported, only element types are compared
+            let len_matches = match (len, len2) {
+                (Some(len), Some(len2)) => len == len2,
+                _ => true,
+            };
+            len_matches && type_matches(hir, emit, array.elem_ty, array2.elem_ty)
+        }
+        (hir::Type::PolyProc(id, idx), hir::Type::PolyProc(id2, idx2)) => id == id2 && idx == idx2,
+        _ => false,
+    }
+}
+
+pub fn type_has_poly(ty: hir::Type) -> bool {
+    match ty {
+        hir::Type::Error => false,
+        hir::Type::Basic(_) => false,
+        hir::Type::Enum(_) => false,
+        hir::Type::Struct(_) => false,
+        hir::Type::Reference(ref_ty, _) => type_has_poly(*ref_ty),
+        hir::Type::Procedure(proc_ty) => {
+            proc_ty.params.iter().any(|param| type_has_poly(*param))
+                || type_has_poly(proc_ty.return_ty)
+        }
+        hir::Type::ArraySlice(slice) => type_has_poly(slice.elem_ty),
+        hir::Type::ArrayStatic(array) => type_has_poly(array.elem_ty),
+        hir::Type::PolyProc(_, _) => true,
+    }
+}
+
+fn type_depth(ty: hir::Type) -> u32 {
+    match ty {
+        hir::Type::Error
+        | hir::Type::Basic(_)
+        | hir::Type::Enum(_)
+        | hir::Type::Struct(_)
+        | hir::Type::PolyProc(_, _) => 0,
+        hir::Type::Reference(ref_ty, _) => 1 + type_depth(*ref_ty),
+        hir::Type::Procedure(proc_ty) => {
+            let params = proc_ty.params.iter().map(|param| type_depth(*param));
+            1 + params.max().unwrap_or(0).max(type_depth(proc_ty.return_ty))
+        }
+        hir::Type::ArraySlice(slice) => 1 + type_depth(slice.elem_ty),
+        hir::Type::ArrayStatic(array) => 1 + type_depth(array.elem_ty),
+    }
+}
+
+/// replaces type parameters of `proc_id` with `poly_types`
+pub fn type_substitute<'hir>(
+    emit: &mut HirEmit<'hir>,
+    proc_id: hir::ProcID,
+    poly_types: &[hir::Type<'hir>],
+    ty: hir::Type<'hir>,
+) -> hir::Type<'hir> {
+    if !type_has_poly(ty) {
+        return ty;
+    }
+    match ty {
+        hir::Type::Reference(ref_ty, mutt) => {
+            let ref_ty = type_substitute(emit, proc_id, poly_types, *ref_ty);
+            hir::Type::Reference(emit.arena.alloc(ref_ty), mutt)
+        }
+        hir::Type::Procedure(proc_ty) => {
+            let mut params = Vec::with_capacity(proc_ty.params.len());
+            for param in proc_ty.params {
+                params.push(type_substitute(emit, proc_id, poly_types, *param));
             }
-            true
+            let proc_ty = hir::ProcType {
+                params: emit.arena.alloc_slice(&params),
+                return_ty: type_substitute(emit, proc_id, poly_types, proc_ty.return_ty),
+                is_variadic: proc_ty.is_variadic,
+            };
+            hir::Type::Procedure(emit.arena.alloc(proc_ty))
+        }
+        hir::Type::ArraySlice(slice) => {
+            let slice = hir::ArraySlice {
+                mutt: slice.mutt,
+                elem_ty: type_substitute(emit, proc_id, poly_types, slice.elem_ty),
+            };
+            hir::Type::ArraySlice(emit.arena.alloc(slice))
         }
-        (hir::Type::ArrayStatic(array), ..) => array_static_get_len(hir, emit, array.len).is_none(),
-        (.., hir::Type::ArrayStatic(array2)) => {
-            array_static_get_len(hir, emit, array2.len).is_none()
+        hir::Type::ArrayStatic(array) => {
+            let array = hir::ArrayStatic {
+                len: array.len,
+                elem_ty: type_substitute(emit, proc_id, poly_types, array.elem_ty),
+            };
+            hir::Type::ArrayStatic(emit.arena.alloc(array))
         }
-        _ => false,
+        hir::Type::PolyProc(id, idx) if id == proc_id => poly_types[idx as usize],
+        _ => ty,
+    }
+}
+
+/// infers unknown type parameters of `proc_id` by matching `param_ty` against `input_ty`
+fn type_infer_poly<'hir>(
+    proc_id: hir::ProcID,
+    poly_types: &mut [hir::Type<'hir>],
+    param_ty: hir::Type<'hir>,
+    input_ty: hir::Type<'hir>,
+) {
+    match (param_ty, input_ty) {
+        (hir::Type::PolyProc(id, idx), _) if id == proc_id => {
+            if matches!(poly_types[idx as usize], hir::Type::Error) {
+                poly_types[idx as usize] = input_ty;
+            }
+        }
+        (hir::Type::Reference(ref_ty, _), hir::Type::Reference(ref_ty2, _)) => {
+            type_infer_poly(proc_id, poly_types, *ref_ty, *ref_ty2);
+        }
+        (hir::Type::Procedure(proc_ty), hir::Type::Procedure(proc_ty2)) => {
+            for (param, param2) in proc_ty.params.iter().zip(proc_ty2.params) {
+                type_infer_poly(proc_id, poly_types, *param, *param2);
+            }
+            type_infer_poly(proc_id, poly_types, proc_ty.return_ty, proc_ty2.return_ty);
+        }
+        (hir::Type::ArraySlice(slice), hir::Type::ArraySlice(slice2)) => {
+            type_infer_poly(proc_id, poly_types, slice.elem_ty, slice2.elem_ty);
+        }
+        (hir::Type::ArrayStatic(array), hir::Type::ArrayStatic(array2)) => {
+            type_infer_poly(proc_id, poly_types, array.elem_ty, array2.elem_ty);
+        }
+        _ => {}
     }
 }
 
@@ -127,6 +344,10 @@ pub fn type_format<'hir>(
         hir::Type::Basic(basic) => basic.as_str().to_string(),
         hir::Type::Enum(id) => hir.name_str(hir.registry().enum_data(id).name.id).into(),
         hir::Type::Struct(id) => hir.name_str(hir.registry().struct_data(id).name.id).into(),
+        hir::Type::PolyProc(id, idx) => {
+            let poly_params = hir.registry().proc_data(id).poly_params.unwrap();
+            hir.name_str(poly_params[idx as usize].id).into()
+        }
         hir::Type::Reference(ref_ty, mutt) => {
             let mut_str = match mutt {
                 ast::Mut::Mutable => "mut ",
@@ -167,32 +388,32 @@ pub fn type_format<'hir>(
     }
 }
 
-fn array_static_get_len<'hir>(
+/// length of array types produced by expressions, known during typecheck
+fn array_static_len<'hir>(emit: &mut HirEmit<'hir>, len: u64) -> hir::ArrayStaticLen {
+    let value = hir::ConstValue::Int {
+        val: len,
+        neg: false,
+        ty: BasicType::Usize,
+    };
+    hir::ArrayStaticLen::Value(emit.const_intern.intern(value))
+}
+
+/// `None` if length is unresolved or invalid, errors are already reported
+pub fn array_static_get_len<'hir>(
     hir: &HirData<'hir, '_, '_>,
     emit: &HirEmit<'hir>,
     len: hir::ArrayStaticLen,
 ) -> Option<u64> {
-    match len {
-        hir::ArrayStaticLen::Immediate(len) => len,
-        hir::ArrayStaticLen::ConstEval(eval_id) => {
-            let (eval, _) = *hir.registry().const_eval(eval_id);
-            match eval {
-                hir::ConstEval::ResolvedValue(value_id) => {
-                    let value = emit.const_intern.get(value_id);
-                    match value {
-                        hir::ConstValue::Int { val, neg, ty } => {
-                            if neg {
-                                None
-                            } else {
-                                Some(val)
-                            }
-                        }
-                        _ => None,
-                    }
-                }
-                _ => None,
-            }
-        }
+    let value_id = match len {
+        hir::ArrayStaticLen::Value(value_id) => value_id,
+        hir::ArrayStaticLen::ConstEval(eval_id) => match hir.registry().const_eval(eval_id).0 {
+            hir::ConstEval::ResolvedValue(value_id) => value_id,
+            _ => return None,
+        },
+    };
+    match emit.const_intern.get(value_id) {
+        hir::ConstValue::Int { val, neg, .. } if !neg => Some(val),
+        _ => None,
     }
 }
 
@@ -222,12 +443,27 @@ pub fn check_type_expectation<'hir>(
             None
         };
 
+        let mut message = format!(
+            "type mismatch: expected `{}`, found `{}`",
+            type_format(hir, emit, expect_ty),
+            type_format(hir, emit, found_ty)
+        );
+        if let Some((into, from)) = numeric_mismatch(expect_ty, found_ty) {
+            let into_str = into.as_str();
+            message.push_str(&format!(
+                "\nnumeric types are not converted implicitly, add `as {into_str}`"
+            ));
+            if !numeric_cast_lossless(hir.target(), from, into) {
+                let from_str = from.as_str();
+                message.push_str(&format!(
+                    "\nconversion from `{from_str}` to `{into_str}` may lose information"
+                ));
+            }
+        }
+
         emit.error(ErrorComp::new(
-            format!(
-                "type mismatch: expected `{}`, found `{}`",
-                type_format(hir, emit, expect_ty),
-                type_format(hir, emit, found_ty)
-            ),
+            ErrorCode::TypeMismatch,
+            message,
             SourceRange::new(origin_id, from_range),
             info,
         ));
@@ -237,6 +473,40 @@ pub fn check_type_expectation<'hir>(
     }
 }
 
+/// expected and found types, when both are different numeric types
+fn numeric_mismatch(expect_ty: hir::Type, found_ty: hir::Type) -> Option<(BasicType, BasicType)> {
+    match (expect_ty, found_ty) {
+        (hir::Type::Basic(into), hir::Type::Basic(from)) => {
+            let numeric =
+                BasicTypeKind::new(into).is_number() && BasicTypeKind::new(from).is_number();
+            (numeric && into != from).then_some((into, from))
+        }
+        _ => None,
+    }
+}
+
+/// every value of `from` is exactly representable in `into`
+fn numeric_cast_lossless(target: TargetTriple, from: BasicType, into: BasicType) -> bool {
+    let from_kind = BasicTypeKind::new(from);
+    let into_kind = BasicTypeKind::new(into);
+
+    match (from_kind, into_kind) {
+        (BasicTypeKind::Float, BasicTypeKind::Float) => {
+            basic_type_size(target, from).size() < basic_type_size(target, into).size()
+        }
+        (BasicTypeKind::Float, _) | (_, BasicTypeKind::Float) => false,
+        _ => {
+            let from_bits = super::pass_4::int_bit_width(target, from);
+            let into_bits = super::pass_4::int_bit_width(target, into);
+            match (from_kind.is_signed_integer(), into_kind.is_signed_integer()) {
+                (false, true) => from_bits < into_bits,
+                (true, false) => false,
+                _ => from_bits <= into_bits,
+            }
+        }
+    }
+}
+
 pub struct TypeResult<'hir> {
     ty: hir::Type<'hir>,
     pub expr: &'hir hir::Expr<'hir>,
@@ -339,10 +609,12 @@ pub fn typecheck_expr<'hir>(
     let mut expr_res = match expr.kind {
         ast::ExprKind::LitNull => typecheck_lit_null(emit),
         ast::ExprKind::LitBool { val } => typecheck_lit_bool(emit, val),
-        ast::ExprKind::LitInt { val } => typecheck_lit_int(emit, expect, val),
-        ast::ExprKind::LitFloat { val } => typecheck_lit_float(emit, expect, val),
+        ast::ExprKind::LitInt { val, ty } => {
+            typecheck_lit_int(hir, emit, proc, expect, val, false, ty, expr.range)
+        }
+        ast::ExprKind::LitFloat { val, ty } => typecheck_lit_float(emit, expect, val, ty),
         ast::ExprKind::LitChar { val } => typecheck_lit_char(emit, val),
-        ast::ExprKind::LitString { id, c_string } => typecheck_lit_string(emit, id, c_string),
+        ast::ExprKind::LitString { id, kind } => typecheck_lit_string(hir, emit, id, kind),
         ast::ExprKind::If { if_ } => typecheck_if(hir, emit, proc, expect, if_, expr.range),
         ast::ExprKind::Block { block } => {
             typecheck_block(hir, emit, proc, expect, *block, BlockEnter::None)
@@ -358,18 +630,28 @@ pub fn typecheck_expr<'hir>(
         ast::ExprKind::Slice {
             target,
             mutt,
-            slice_range,
-        } => typecheck_slice(hir, emit, proc, target, mutt, slice_range, expr.range),
+            range,
+        } => typecheck_slice(hir, emit, proc, target, mutt, range, expr.range),
         ast::ExprKind::Call { target, input } => {
-            typecheck_call(hir, emit, proc, target, input, expr.range)
+            typecheck_call(hir, emit, proc, expect, target, input, expr.range)
         }
         ast::ExprKind::Cast { target, into } => {
             typecheck_cast(hir, emit, proc, target, into, expr.range)
         }
         ast::ExprKind::Sizeof { ty } => typecheck_sizeof(hir, emit, proc, *ty, expr.range),
-        ast::ExprKind::Item { path } => typecheck_item(hir, emit, proc, path),
+        ast::ExprKind::Format { format } => typecheck_format(hir, emit, proc, format, expr.range),
+        ast::ExprKind::Item { path } => {
+            let item_res = typecheck_item(hir, emit, proc, expect, path);
+            let item_res = check_variant_fields_missing(hir, emit, proc, item_res, expr.range);
+            check_poly_proc_value(hir, emit, proc, item_res, expr.range)
+        }
         ast::ExprKind::Variant { name } => {
-            typecheck_variant(hir, emit, proc, expect, name, expr.range)
+            let variant_res = typecheck_variant(hir, emit, proc, expect, name, expr.range);
+            check_variant_fields_missing(hir, emit, proc, variant_res, expr.range)
+        }
+        ast::ExprKind::Wildcard => {
+            error_wildcard_expr(emit, SourceRange::new(proc.origin(), expr.range));
+            TypeResult::new(hir::Type::Error, hir_build::EXPR_ERROR)
         }
         ast::ExprKind::StructInit { struct_init } => {
             typecheck_struct_init(hir, emit, proc, expect, struct_init, expr.range)
@@ -388,17 +670,53 @@ pub fn typecheck_expr<'hir>(
         ast::ExprKind::Binary { op, op_range, bin } => {
             typecheck_binary(hir, emit, proc, expect, op, op_range, bin)
         }
+        ast::ExprKind::Range { range } => typecheck_range(hir, emit, proc, range, expr.range),
     };
 
     //@if `errored` is usefull it can be done via emit error count api
     if !expr_res.ignore {
         expr_res.errored =
             check_type_expectation(hir, emit, proc.origin(), expr.range, expect, expr_res.ty);
+
+        if expr_res.errored && !is_literal(expr) {
+            if let Expectation::HasType(expect_ty, _) = expect {
+                if let Some((into, _)) = numeric_mismatch(expect_ty, expr_res.ty) {
+                    let fix = numeric_cast_fix(proc.origin(), expr, into);
+                    emit.error_fix(SourceRange::new(proc.origin(), expr.range), fix);
+                }
+            }
+        }
     }
 
     expr_res
 }
 
+fn numeric_cast_fix(origin_id: ModuleID, expr: &ast::Expr, into: BasicType) -> DiagnosticFix {
+    let message = format!("add `as {}`", into.as_str());
+    let cast = format!(" as {}", into.as_str());
+
+    // `as` is postfix and binds tighter than prefix and binary operators
+    let needs_parens = matches!(
+        expr.kind,
+        ast::ExprKind::If { .. }
+            | ast::ExprKind::Block { .. }
+            | ast::ExprKind::Match { .. }
+            | ast::ExprKind::Deref { .. }
+            | ast::ExprKind::Address { .. }
+            | ast::ExprKind::Unary { .. }
+            | ast::ExprKind::Binary { .. }
+    );
+    let edits = if needs_parens {
+        vec![
+            DiagnosticEdit::insert(origin_id, expr.range.start(), "("),
+            DiagnosticEdit::insert(origin_id, expr.range.end(), format!("){cast}")),
+        ]
+    } else {
+        vec![DiagnosticEdit::insert(origin_id, expr.range.end(), cast)]
+    };
+    DiagnosticFix::new(message, edits)
+}
+
 fn typecheck_lit_null<'hir>(emit: &mut HirEmit<'hir>) -> TypeResult<'hir> {
     let value = hir::ConstValue::Null;
 
@@ -416,14 +734,36 @@ fn typecheck_lit_bool<'hir>(emit: &mut HirEmit<'hir>, val: bool) -> TypeResult<'
 }
 
 fn typecheck_lit_int<'hir>(
+    hir: &HirData<'hir, '_, '_>,
     emit: &mut HirEmit<'hir>,
+    proc: &ProcScope<'hir, '_>,
     expect: Expectation<'hir>,
     val: u64,
+    neg: bool,
+    suffix: Option<BasicType>,
+    range: TextRange,
 ) -> TypeResult<'hir> {
-    let lit_type = coerce_int_type(expect);
+    let lit_type = suffix.unwrap_or_else(|| coerce_int_type(expect));
+    let neg = neg && val != 0;
+
+    let (min, max) = super::pass_4::int_range(hir.target(), lit_type);
+    let value = if neg { -(val as i128) } else { val as i128 };
+    if value < min || value > max {
+        emit.error(ErrorComp::new(
+            ErrorCode::LiteralOutOfRange,
+            format!(
+                "integer literal `{value}` does not fit into `{}`\nexpected range is {min}..={max}",
+                lit_type.as_str()
+            ),
+            SourceRange::new(proc.origin(), range),
+            None,
+        ));
+        return TypeResult::new(hir::Type::Basic(lit_type), hir_build::EXPR_ERROR);
+    }
+
     let value = hir::ConstValue::Int {
         val,
-        neg: false,
+        neg,
         ty: lit_type,
     };
 
@@ -460,8 +800,9 @@ fn typecheck_lit_float<'hir>(
     emit: &mut HirEmit<'hir>,
     expect: Expectation<'hir>,
     val: f64,
+    suffix: Option<BasicType>,
 ) -> TypeResult<'hir> {
-    let lit_type = coerce_float_type(expect);
+    let lit_type = suffix.unwrap_or_else(|| coerce_float_type(expect));
     let value = hir::ConstValue::Float {
         val,
         ty: Some(lit_type),
@@ -496,13 +837,34 @@ fn typecheck_lit_char<'hir>(emit: &mut HirEmit<'hir>, val: char) -> TypeResult<'
 }
 
 fn typecheck_lit_string<'hir>(
+    hir: &HirData<'hir, '_, '_>,
     emit: &mut HirEmit<'hir>,
     id: InternID,
-    c_string: bool,
+    kind: ast::StringKind,
 ) -> TypeResult<'hir> {
-    let value = hir::ConstValue::String { id, c_string };
+    let value = hir::ConstValue::String { id, kind };
 
-    let string_ty = alloc_string_lit_type(emit, c_string);
+    let string_ty = match kind {
+        ast::StringKind::Regular => {
+            let string = hir.intern_string().get_str(id);
+            emit.string_lits.add(id, string, false);
+            alloc_string_lit_type(emit, false)
+        }
+        ast::StringKind::CString => {
+            let string = hir.intern_string().get_str(id);
+            emit.string_lits.add(id, string, true);
+            alloc_string_lit_type(emit, true)
+        }
+        ast::StringKind::Bytes => {
+            let len = hir.intern_string().get_str(id).len();
+            let len = array_static_len(emit, len as u64);
+            let array = emit.arena.alloc(hir::ArrayStatic {
+                len,
+                elem_ty: hir::Type::Basic(BasicType::U8),
+            });
+            hir::Type::ArrayStatic(array)
+        }
+    };
     let expr = hir::Expr::Const { value };
     let expr = emit.arena.alloc(expr);
     TypeResult::new(string_ty, expr)
@@ -529,6 +891,16 @@ fn typecheck_if<'hir>(
     if_: &ast::If<'_>,
     expr_range: TextRange,
 ) -> TypeResult<'hir> {
+    // open `if` evaluates to `void`, outer expectation only
+    // applies when all branches including `else` are present
+    let mut value_expect = None;
+    if if_.else_block.is_none() {
+        if let Expectation::HasType(expect_ty, expect_src) = expect {
+            value_expect = Some((expect_ty, expect_src));
+        }
+        expect = Expectation::None;
+    }
+
     let mut if_type = hir::Type::Basic(BasicType::Never);
     let entry = typecheck_branch(hir, emit, proc, &mut expect, &mut if_type, &if_.entry);
 
@@ -558,8 +930,33 @@ fn typecheck_if<'hir>(
         if_type = hir::Type::VOID;
     }
 
-    if else_block.is_none() && !if_type.is_error() && !if_type.is_void() && !if_type.is_never() {
+    // value expected from an open `if` is reported at the keyword,
+    // instead of a type mismatch against `void`
+    let mut missing_else = false;
+    if let Some((expect_ty, expect_src)) = value_expect {
+        if !expect_ty.is_void() && !expect_ty.is_error() {
+            missing_else = true;
+            let if_range = TextRange::new(expr_range.start(), expr_range.start() + 2.into());
+            emit.error(ErrorComp::new(
+                ErrorCode::MissingElse,
+                format!(
+                    "`if` expression used as a value must have an `else` branch\nexpected type is `{}`",
+                    type_format(hir, emit, expect_ty)
+                ),
+                SourceRange::new(proc.origin(), if_range),
+                expect_src.and_then(|source| Info::new("expected due to this", source)),
+            ));
+        }
+    }
+
+    if !missing_else
+        && else_block.is_none()
+        && !if_type.is_error()
+        && !if_type.is_void()
+        && !if_type.is_never()
+    {
         emit.error(ErrorComp::new(
+            ErrorCode::MissingElse,
             "`if` expression is missing an `else` block\n`if` without `else` evaluates to `void` and cannot return a value",
             SourceRange::new(proc.origin(), expr_range),
             None,
@@ -575,8 +972,11 @@ fn typecheck_if<'hir>(
     let if_expr = hir::Expr::If { if_ };
     let if_expr = emit.arena.alloc(if_expr);
     //@diverges bool is not needed `never` type serves that role
-    //@cannot tell when to ignore typecheck or not (in cases where never is in the blocks)
-    TypeResult::new_div(if_type, if_expr, if_type.is_never())
+    let mut if_res = TypeResult::new_div(if_type, if_expr, if_type.is_never());
+    // with `else` all branches were checked against the expectation,
+    // checking the `if` itself would report the same mismatch again
+    if_res.ignore = else_block.is_some() || missing_else;
+    if_res
 }
 
 fn typecheck_branch<'hir>(
@@ -609,6 +1009,7 @@ fn typecheck_branch<'hir>(
     hir::Branch {
         cond: cond_res.expr,
         block: block_res.block,
+        cond_value: const_fold::fold_bool(hir, emit, cond_res.expr),
     }
 }
 
@@ -631,9 +1032,78 @@ fn typecheck_match<'hir>(
 
     let mut arms = Vec::with_capacity(match_.arms.len());
     for arm in match_.arms {
-        let value =
-            super::pass_4::resolve_const_expr(hir, emit, proc.origin(), pat_expect, arm.pat);
+        let bind_pat = arm
+            .pats
+            .iter()
+            .any(|pat| variant_bind_pat(on_res.ty, pat).is_some());
+        if bind_pat {
+            proc.push_block(BlockEnter::None);
+        }
+
+        let mut pats = Vec::with_capacity(arm.pats.len());
+        let mut binds: &[Option<hir::LocalID>] = &[];
+        for pat in arm.pats {
+            let pat = match *pat {
+                ast::MatchPat::Value(value) => {
+                    let value = if let Some((enum_id, target, input)) =
+                        variant_bind_pat(on_res.ty, pat)
+                    {
+                        let (value, pat_binds) = typecheck_variant_pat(
+                            hir,
+                            emit,
+                            proc,
+                            pat_expect,
+                            enum_id,
+                            on_res.expr,
+                            target,
+                            input,
+                        );
+                        if arm.pats.len() == 1 {
+                            binds = pat_binds;
+                        } else if pat_binds.iter().any(Option::is_some) {
+                            emit.error(ErrorComp::new(
+                                ErrorCode::InvalidPattern,
+                                "variant fields cannot be bound in or-patterns",
+                                SourceRange::new(proc.origin(), pat.range()),
+                                None,
+                            ));
+                        }
+                        value
+                    } else {
+                        let origin_id = proc.origin();
+                        super::pass_4::resolve_const_expr(hir, emit, origin_id, pat_expect, value)
+                    };
+                    if value == hir::ConstValue::Error {
+                        check_exaust = false;
+                    }
+                    hir::MatchPat::Value(emit.const_intern.intern(value))
+                }
+                ast::MatchPat::Range {
+                    start,
+                    end,
+                    inclusive,
+                } => {
+                    let range = pat.range();
+                    let range_pat = typecheck_range_pat(
+                        hir, emit, proc, pat_expect, on_res.ty, start, end, inclusive, range,
+                    );
+                    match range_pat {
+                        Some(range_pat) => range_pat,
+                        None => {
+                            check_exaust = false;
+                            let value = hir::ConstValue::Error;
+                            hir::MatchPat::Value(emit.const_intern.intern(value))
+                        }
+                    }
+                }
+            };
+            pats.push(pat);
+        }
+
         let value_res = typecheck_expr(hir, emit, proc, expect, arm.expr);
+        if bind_pat {
+            proc.pop_block();
+        }
 
         // never -> anything
         // error -> anything except never
@@ -648,16 +1118,12 @@ fn typecheck_match<'hir>(
             }
         }
 
-        if value == hir::ConstValue::Error {
-            check_exaust = false;
-        }
-
-        let pat_value_id = emit.const_intern.intern(value);
         let tail_stmt = hir::Stmt::ExprTail(value_res.expr);
         let stmts = emit.arena.alloc_slice(&[tail_stmt]);
 
         let arm = hir::MatchArm {
-            pat: pat_value_id,
+            pats: emit.arena.alloc_slice(&pats),
+            binds,
             block: hir::Block { stmts },
             unreachable: false,
         };
@@ -680,12 +1146,17 @@ fn typecheck_match<'hir>(
         None
     };
 
+    let mut covered = false;
+    let mut pats_unreachable: Vec<Vec<bool>> =
+        arms.iter().map(|arm| vec![false; arm.pats.len()]).collect();
+
     if check_exaust {
-        check_match_exhaust(
+        covered = check_match_exhaust(
             hir,
             emit,
             proc,
-            &mut arms,
+            &arms,
+            &mut pats_unreachable,
             &mut fallback,
             match_,
             match_range,
@@ -693,90 +1164,498 @@ fn typecheck_match<'hir>(
         );
     }
 
+    if check_exaust {
+        check_match_duplicates(hir, emit, proc, &arms, &mut pats_unreachable, match_);
+    }
+    remove_unreachable_pats(emit, &mut arms, &pats_unreachable);
+    if let Some(on_value) = const_fold::fold_expr(hir, emit, on_res.expr) {
+        mark_dead_match_arms(hir, emit, &mut arms, on_value);
+    }
+
+    let jump_table = check_exaust && match_is_dense(hir, emit, &arms);
+    let exhaustive = check_exaust && fallback.is_none() && covered;
+
     let arms = emit.arena.alloc_slice(&arms);
     let match_ = hir::Match {
         on_expr: on_res.expr,
         arms,
         fallback,
+        jump_table,
+        exhaustive,
     };
     let match_ = emit.arena.alloc(match_);
     let match_expr = hir::Expr::Match { match_ };
     let match_expr = emit.arena.alloc(match_expr);
-    //@cannot tell when to ignore typecheck or not
-    TypeResult::new(match_type, match_expr)
+    // arms were checked against the expectation, same as `if` with `else`
+    TypeResult::new_ignore_typecheck(match_type, match_expr)
 }
 
-//@different enum variants 01.06.24
-// could have same value and result in
-// error in llvm ir generation, not checked currently
-fn check_match_exhaust<'hir>(
+/// integer value of the arm pattern, enum variants use their tag value
+fn match_case_value<'hir>(
+    hir: &HirData<'hir, '_, '_>,
+    emit: &HirEmit<'hir>,
+    value: hir::ConstValue<'hir>,
+) -> Option<i128> {
+    match value {
+        hir::ConstValue::Bool { val } => Some(val as i128),
+        hir::ConstValue::Int { val, neg, .. } => {
+            Some(if neg { -(val as i128) } else { val as i128 })
+        }
+        hir::ConstValue::Char { val } => Some(val as i128),
+        hir::ConstValue::EnumVariant {
+            enum_id,
+            variant_id,
+        } => {
+            let variant = hir.registry().enum_data(enum_id).variant(variant_id);
+            match hir.registry().const_eval(variant.value).0 {
+                hir::ConstEval::ResolvedValue(value_id) => {
+                    match_case_value(hir, emit, emit.const_intern.get(value_id))
+                }
+                _ => None,
+            }
+        }
+        _ => None,
+    }
+}
+
+/// inclusive bounds of values matched by the pattern
+fn match_pat_bounds<'hir>(
+    hir: &HirData<'hir, '_, '_>,
+    emit: &HirEmit<'hir>,
+    pat: hir::MatchPat,
+) -> Option<(i128, i128)> {
+    match pat {
+        hir::MatchPat::Value(value_id) => {
+            let value = match_case_value(hir, emit, emit.const_intern.get(value_id))?;
+            Some((value, value))
+        }
+        hir::MatchPat::Range {
+            start,
+            end,
+            inclusive,
+        } => {
+            let start = match_case_value(hir, emit, emit.const_intern.get(start))?;
+            let end = match_case_value(hir, emit, emit.const_intern.get(end))?;
+            Some((start, if inclusive { end } else { end - 1 }))
+        }
+    }
+}
+
+/// every value in `start..=end` is matched by some of the `bounds`
+fn bounds_cover(bounds: &[(i128, i128)], start: i128, end: i128) -> bool {
+    let mut bounds = bounds.to_vec();
+    bounds.sort_unstable();
+    let mut next = start;
+    for (bound_start, bound_end) in bounds {
+        if bound_start > next {
+            break;
+        }
+        if bound_end >= next {
+            next = bound_end + 1;
+        }
+        if next > end {
+            return true;
+        }
+    }
+    false
+}
+
+/// values already matched by previous patterns, including distinct enum variants with equal tags
+/// `bool` and repeated enum variants are already reported by `check_match_exhaust`
+fn check_match_duplicates<'hir>(
     hir: &HirData<'hir, '_, '_>,
     emit: &mut HirEmit<'hir>,
-    proc: &mut ProcScope<'hir, '_>,
-    arms: &mut Vec<hir::MatchArm<'hir>>,
-    fallback: &mut Option<hir::Block<'hir>>,
+    proc: &ProcScope<'hir, '_>,
+    arms: &[hir::MatchArm<'hir>],
+    pats_unreachable: &mut [Vec<bool>],
     match_ast: &ast::Match<'_>,
-    match_range: TextRange,
-    on_ty: hir::Type<'hir>,
 ) {
-    //@todo int
-    match on_ty {
-        hir::Type::Basic(BasicType::Bool) => {
-            let mut cover_true = false;
-            let mut cover_false = false;
+    let mut matched: Vec<(i128, i128)> = Vec::with_capacity(arms.len());
+    let mut matched_ranges: Vec<TextRange> = Vec::with_capacity(arms.len());
 
-            for (idx, arm) in arms.iter_mut().enumerate() {
-                let value = emit.const_intern.get(arm.pat);
-                match value {
-                    hir::ConstValue::Bool { val } => {
-                        if val {
-                            if cover_true {
-                                let ast_arm = match_ast.arms[idx];
-                                arm.unreachable = true;
-                                emit.warning(WarningComp::new(
-                                    "unreachable pattern",
-                                    SourceRange::new(proc.origin(), ast_arm.pat.0.range),
-                                    None,
-                                ));
-                            } else {
-                                cover_true = true;
-                            }
-                        } else {
-                            if cover_false {
-                                let ast_arm = match_ast.arms[idx];
-                                arm.unreachable = true;
-                                emit.warning(WarningComp::new(
-                                    "unreachable pattern",
-                                    SourceRange::new(proc.origin(), ast_arm.pat.0.range),
-                                    None,
-                                ));
-                            } else {
-                                cover_false = true;
-                            }
-                        }
-                    }
-                    _ => {}
-                }
+    for (idx, arm) in arms.iter().enumerate() {
+        for (pat_idx, &pat) in arm.pats.iter().enumerate() {
+            if pats_unreachable[idx][pat_idx] {
+                continue;
             }
-
+            let (start, end) = match match_pat_bounds(hir, emit, pat) {
+                Some(bounds) => bounds,
+                None => continue,
+            };
+            let pat_range = match_ast.arms[idx].pats[pat_idx].range();
+            if !bounds_cover(&matched, start, end) {
+                matched.push((start, end));
+                matched_ranges.push(pat_range);
+                continue;
+            }
+
+            let first_idx = matched
+                .iter()
+                .position(|&(first_start, first_end)| first_start <= start && start <= first_end)
+                .unwrap_or(0);
+            pats_unreachable[idx][pat_idx] = true;
+            emit.warning(WarningComp::new(
+                WarningCode::UnreachablePattern,
+                "unreachable pattern",
+                SourceRange::new(proc.origin(), pat_range),
+                Info::new(
+                    "same value is already matched here",
+                    SourceRange::new(proc.origin(), matched_ranges[first_idx]),
+                ),
+            ));
+        }
+    }
+}
+
+/// unreachable patterns are not lowered, arm is unreachable when all of its patterns are
+fn remove_unreachable_pats<'hir>(
+    emit: &mut HirEmit<'hir>,
+    arms: &mut [hir::MatchArm<'hir>],
+    pats_unreachable: &[Vec<bool>],
+) {
+    for (arm, unreachable) in arms.iter_mut().zip(pats_unreachable) {
+        if unreachable.iter().all(|&unreachable| unreachable) {
+            arm.unreachable = true;
+        } else if unreachable.contains(&true) {
+            let pats: Vec<hir::MatchPat> = arm
+                .pats
+                .iter()
+                .zip(unreachable)
+                .filter(|(_, &unreachable)| !unreachable)
+                .map(|(&pat, _)| pat)
+                .collect();
+            arm.pats = emit.arena.alloc_slice(&pats);
+        }
+    }
+}
+
+// llvm default minimum of cases for jump table lowering
+const JUMP_TABLE_MIN_CASES: usize = 4;
+
+/// reachable arms cover a contiguous range of values, range patterns are checked before the jump
+fn match_is_dense<'hir>(
+    hir: &HirData<'hir, '_, '_>,
+    emit: &HirEmit<'hir>,
+    arms: &[hir::MatchArm<'hir>],
+) -> bool {
+    let mut values = Vec::with_capacity(arms.len());
+    for arm in arms.iter().filter(|arm| !arm.unreachable) {
+        for &pat in arm.pats {
+            let value_id = match pat {
+                hir::MatchPat::Value(value_id) => value_id,
+                hir::MatchPat::Range { .. } => return false,
+            };
+            match match_case_value(hir, emit, emit.const_intern.get(value_id)) {
+                Some(value) => values.push(value),
+                None => return false,
+            }
+        }
+    }
+    if values.len() < JUMP_TABLE_MIN_CASES {
+        return false;
+    }
+    values.sort_unstable();
+    let min = values[0];
+    let max = values[values.len() - 1];
+    max - min + 1 == values.len() as i128
+}
+
+/// with compile-time known `match` value only the first matching arm is reachable
+fn mark_dead_match_arms<'hir>(
+    hir: &HirData<'hir, '_, '_>,
+    emit: &HirEmit<'hir>,
+    arms: &mut [hir::MatchArm<'hir>],
+    on_value: hir::ConstValue<'hir>,
+) {
+    let on_case = match_case_value(hir, emit, on_value);
+    let mut matched = false;
+    for arm in arms.iter_mut() {
+        if arm.unreachable {
+            continue;
+        }
+        let arm_matches = arm.pats.iter().any(|&pat| match pat {
+            hir::MatchPat::Value(value_id) => emit.const_intern.get(value_id) == on_value,
+            hir::MatchPat::Range { .. } => match (on_case, match_pat_bounds(hir, emit, pat)) {
+                (Some(value), Some((start, end))) => start <= value && value <= end,
+                _ => false,
+            },
+        });
+        let binds = arm.binds.iter().any(Option::is_some);
+        if !matched && !binds && arm_matches {
+            matched = true;
+        } else {
+            arm.unreachable = true;
+        }
+    }
+}
+
+/// variant pattern with field bindings: `.Variant(a, _)`
+fn variant_bind_pat<'ast>(
+    on_ty: hir::Type,
+    pat: &ast::MatchPat<'ast>,
+) -> Option<(
+    hir::EnumID,
+    &'ast ast::Expr<'ast>,
+    &'ast [&'ast ast::Expr<'ast>],
+)> {
+    match (on_ty, *pat) {
+        (hir::Type::Enum(enum_id), ast::MatchPat::Value(value)) => match value.0.kind {
+            ast::ExprKind::Call { target, input } => Some((enum_id, target, *input)),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// range pattern: `start..<end` or `start..=end`, integer and `char` only
+fn typecheck_range_pat<'hir>(
+    hir: &HirData<'hir, '_, '_>,
+    emit: &mut HirEmit<'hir>,
+    proc: &ProcScope<'hir, '_>,
+    pat_expect: Expectation<'hir>,
+    on_ty: hir::Type<'hir>,
+    start: ast::ConstExpr,
+    end: ast::ConstExpr,
+    inclusive: bool,
+    pat_range: TextRange,
+) -> Option<hir::MatchPat> {
+    let range_ty = match on_ty {
+        hir::Type::Error => return None,
+        hir::Type::Basic(basic) => matches!(
+            BasicTypeKind::new(basic),
+            BasicTypeKind::SignedInt | BasicTypeKind::UnsignedInt | BasicTypeKind::Char
+        ),
+        _ => false,
+    };
+    if !range_ty {
+        emit.error(ErrorComp::new(
+            ErrorCode::InvalidPattern,
+            format!(
+                "range patterns cannot be used to match on `{}`",
+                type_format(hir, emit, on_ty)
+            ),
+            SourceRange::new(proc.origin(), pat_range),
+            None,
+        ));
+        return None;
+    }
+
+    let origin_id = proc.origin();
+    let start = super::pass_4::resolve_const_expr(hir, emit, origin_id, pat_expect, start);
+    let end = super::pass_4::resolve_const_expr(hir, emit, origin_id, pat_expect, end);
+    let start_value = match_case_value(hir, emit, start)?;
+    let end_value = match_case_value(hir, emit, end)?;
+
+    let empty = if inclusive {
+        start_value > end_value
+    } else {
+        start_value >= end_value
+    };
+    if empty {
+        let note = if inclusive {
+            "lower bound must be less than or equal to the upper bound"
+        } else {
+            "lower bound must be less than the upper bound"
+        };
+        emit.error(ErrorComp::new(
+            ErrorCode::InvalidPattern,
+            format!("range pattern matches no values\n{note}"),
+            SourceRange::new(origin_id, pat_range),
+            None,
+        ));
+        return None;
+    }
+
+    Some(hir::MatchPat::Range {
+        start: emit.const_intern.intern(start),
+        end: emit.const_intern.intern(end),
+        inclusive,
+    })
+}
+
+/// variant pattern with payload bindings: `.Variant(a, b)`, `_` fields are not bound
+fn typecheck_variant_pat<'hir>(
+    hir: &HirData<'hir, '_, '_>,
+    emit: &mut HirEmit<'hir>,
+    proc: &mut ProcScope<'hir, '_>,
+    pat_expect: Expectation<'hir>,
+    enum_id: hir::EnumID,
+    on_expr: &'hir hir::Expr<'hir>,
+    target: &ast::Expr<'_>,
+    input: &[&ast::Expr<'_>],
+) -> (hir::ConstValue<'hir>, &'hir [Option<hir::LocalID>]) {
+    let target_res = match target.kind {
+        ast::ExprKind::Item { path } => typecheck_item(hir, emit, proc, Expectation::None, path),
+        ast::ExprKind::Variant { name } => {
+            typecheck_variant(hir, emit, proc, pat_expect, name, target.range)
+        }
+        _ => {
+            emit.error(ErrorComp::new(
+                ErrorCode::InvalidPattern,
+                "expected enum variant pattern",
+                SourceRange::new(proc.origin(), target.range),
+                None,
+            ));
+            return (hir::ConstValue::Error, &[]);
+        }
+    };
+    if check_type_expectation(
+        hir,
+        emit,
+        proc.origin(),
+        target.range,
+        pat_expect,
+        target_res.ty,
+    ) {
+        return (hir::ConstValue::Error, &[]);
+    }
+
+    let variant_id = match *target_res.expr {
+        hir::Expr::Const {
+            value: hir::ConstValue::EnumVariant { variant_id, .. },
+        } => variant_id,
+        _ => return (hir::ConstValue::Error, &[]),
+    };
+    let data = hir.registry().enum_data(enum_id);
+    let variant = data.variant(variant_id);
+
+    if input.len() != variant.fields.len() {
+        let plural = if variant.fields.len() == 1 { "" } else { "s" };
+        emit.error(ErrorComp::new(
+            ErrorCode::ArgumentCount,
+            format!(
+                "expected {} variant field{plural}, found {}",
+                variant.fields.len(),
+                input.len()
+            ),
+            SourceRange::new(proc.origin(), target.range),
+            Info::new(
+                "variant defined here",
+                SourceRange::new(data.origin_id, variant.name.range),
+            ),
+        ));
+    }
+
+    let mut binds = Vec::with_capacity(input.len());
+    for (idx, &expr) in input.iter().enumerate() {
+        let name = match expr.kind {
+            ast::ExprKind::Item { path } if path.names.len() == 1 => path.names[0],
+            ast::ExprKind::Wildcard => {
+                binds.push(None);
+                continue;
+            }
+            _ => {
+                emit.error(ErrorComp::new(
+                    ErrorCode::InvalidPattern,
+                    "expected binding name",
+                    SourceRange::new(proc.origin(), expr.range),
+                    None,
+                ));
+                continue;
+            }
+        };
+
+        if let Some(existing_var) = proc.find_variable_in_block(name.id) {
+            let existing = match existing_var {
+                VariableID::Local(id) => {
+                    SourceRange::new(proc.origin(), proc.get_local(id).name.range)
+                }
+                VariableID::Param(id) => {
+                    SourceRange::new(proc.origin(), proc.get_param(id).name.range)
+                }
+            };
+            super::pass_1::error_name_already_defined(hir, emit, proc.origin(), name, existing);
+            continue;
+        }
+
+        let ty = variant.fields.get(idx).copied().unwrap_or(hir::Type::Error);
+        let local = emit.arena.alloc(hir::Local {
+            mutt: ast::Mut::Immutable,
+            name,
+            ty,
+            value: None,
+        });
+        let local_id = proc.push_local(local);
+        proc.set_local_reference(local_id, get_expr_variable_reference(proc, on_expr));
+        binds.push(Some(local_id));
+    }
+
+    let value = hir::ConstValue::EnumVariant {
+        enum_id,
+        variant_id,
+    };
+    (value, emit.arena.alloc_slice(&binds))
+}
+
+/// returns `true` when arms cover every possible value
+fn check_match_exhaust<'hir>(
+    hir: &HirData<'hir, '_, '_>,
+    emit: &mut HirEmit<'hir>,
+    proc: &mut ProcScope<'hir, '_>,
+    arms: &[hir::MatchArm<'hir>],
+    pats_unreachable: &mut [Vec<bool>],
+    fallback: &mut Option<hir::Block<'hir>>,
+    match_ast: &ast::Match<'_>,
+    match_range: TextRange,
+    on_ty: hir::Type<'hir>,
+) -> bool {
+    match on_ty {
+        hir::Type::Basic(BasicType::Bool) => {
+            let mut cover_true = false;
+            let mut cover_false = false;
+
+            for (idx, arm) in arms.iter().enumerate() {
+                for (pat_idx, &pat) in arm.pats.iter().enumerate() {
+                    let val = match pat {
+                        hir::MatchPat::Value(value_id) => match emit.const_intern.get(value_id) {
+                            hir::ConstValue::Bool { val } => val,
+                            _ => continue,
+                        },
+                        hir::MatchPat::Range { .. } => continue,
+                    };
+                    let covered = if val {
+                        &mut cover_true
+                    } else {
+                        &mut cover_false
+                    };
+                    if *covered {
+                        pats_unreachable[idx][pat_idx] = true;
+                        emit.warning(WarningComp::new(
+                            WarningCode::UnreachablePattern,
+                            "unreachable pattern",
+                            SourceRange::new(
+                                proc.origin(),
+                                match_ast.arms[idx].pats[pat_idx].range(),
+                            ),
+                            None,
+                        ));
+                    } else {
+                        *covered = true;
+                    }
+                }
+            }
+
             if fallback.is_some() {
                 let all_covered = cover_true && cover_false;
                 if all_covered {
                     *fallback = None;
                     emit.warning(WarningComp::new(
+                        WarningCode::UnreachablePattern,
                         "unreachable pattern",
                         SourceRange::new(proc.origin(), match_ast.fallback_range),
                         None,
                     ));
                 }
+                all_covered
             } else {
                 let missing = match (cover_true, cover_false) {
-                    (true, true) => return,
+                    (true, true) => return true,
                     (true, false) => "`false`",
                     (false, true) => "`true`",
                     (false, false) => "`true`, `false`",
                 };
                 emit.error(ErrorComp::new(
+                    ErrorCode::NonExhaustiveMatch,
                     format!("non-exhaustive match patterns\nmissing: {}", missing),
                     SourceRange::new(
                         proc.origin(),
@@ -784,10 +1663,38 @@ fn check_match_exhaust<'hir>(
                     ),
                     None,
                 ));
+                false
             }
         }
-        hir::Type::Basic(BasicType::Char) => {
-            //
+        hir::Type::Basic(basic)
+            if matches!(
+                BasicTypeKind::new(basic),
+                BasicTypeKind::SignedInt | BasicTypeKind::UnsignedInt
+            ) =>
+        {
+            let mut bounds = Vec::with_capacity(arms.len());
+            for arm in arms.iter() {
+                for &pat in arm.pats {
+                    if let Some(pat_bounds) = match_pat_bounds(hir, emit, pat) {
+                        bounds.push(pat_bounds);
+                    }
+                }
+            }
+
+            let (min, max) = super::pass_4::int_range(hir.target(), basic);
+            if !bounds_cover(&bounds, min, max) {
+                return false;
+            }
+            if fallback.is_some() {
+                *fallback = None;
+                emit.warning(WarningComp::new(
+                    WarningCode::UnreachablePattern,
+                    "unreachable pattern",
+                    SourceRange::new(proc.origin(), match_ast.fallback_range),
+                    None,
+                ));
+            }
+            true
         }
         hir::Type::Enum(enum_id) => {
             let data = hir.registry().enum_data(enum_id);
@@ -796,33 +1703,40 @@ fn check_match_exhaust<'hir>(
             let mut variants_covered = Vec::new();
             variants_covered.resize(variant_count, false);
 
-            for (idx, arm) in arms.iter_mut().enumerate() {
-                let value = emit.const_intern.get(arm.pat);
-                match value {
+            for (idx, arm) in arms.iter().enumerate() {
+                for (pat_idx, &pat) in arm.pats.iter().enumerate() {
                     //@consider typecheck result of patterns to make sure this is same type 01.06.24
                     // (dont check when any error were raised or value is Error)
-                    hir::ConstValue::EnumVariant { variant_id, .. } => {
-                        if !variants_covered[variant_id.index()] {
-                            variants_covered[variant_id.index()] = true;
-                        } else {
-                            let ast_arm = match_ast.arms[idx];
-                            arm.unreachable = true;
-                            emit.warning(WarningComp::new(
-                                "unreachable pattern",
-                                SourceRange::new(proc.origin(), ast_arm.pat.0.range),
-                                None,
-                            ));
-                        }
+                    let variant_id = match pat {
+                        hir::MatchPat::Value(value_id) => match emit.const_intern.get(value_id) {
+                            hir::ConstValue::EnumVariant { variant_id, .. } => variant_id,
+                            _ => continue,
+                        },
+                        hir::MatchPat::Range { .. } => continue,
+                    };
+                    if !variants_covered[variant_id.index()] {
+                        variants_covered[variant_id.index()] = true;
+                    } else {
+                        pats_unreachable[idx][pat_idx] = true;
+                        emit.warning(WarningComp::new(
+                            WarningCode::UnreachablePattern,
+                            "unreachable pattern",
+                            SourceRange::new(
+                                proc.origin(),
+                                match_ast.arms[idx].pats[pat_idx].range(),
+                            ),
+                            None,
+                        ));
                     }
-                    _ => {}
                 }
             }
 
+            let all_covered = variants_covered.iter().copied().all(|v| v);
             if fallback.is_some() {
-                let all_covered = variants_covered.iter().copied().all(|v| v);
                 if all_covered {
                     *fallback = None;
                     emit.warning(WarningComp::new(
+                        WarningCode::UnreachablePattern,
                         "unreachable pattern",
                         SourceRange::new(proc.origin(), match_ast.fallback_range),
                         None,
@@ -846,6 +1760,7 @@ fn check_match_exhaust<'hir>(
 
                 if missing_count > 0 {
                     emit.error(ErrorComp::new(
+                        ErrorCode::NonExhaustiveMatch,
                         format!(
                             "non-exhaustive match patterns\nmissing variants: {}",
                             missing
@@ -858,8 +1773,9 @@ fn check_match_exhaust<'hir>(
                     ));
                 }
             }
+            all_covered
         }
-        _ => {}
+        _ => false,
     }
 }
 
@@ -871,19 +1787,33 @@ fn typecheck_field<'hir>(
     name: ast::Name,
 ) -> TypeResult<'hir> {
     let target_res = typecheck_expr(hir, emit, proc, Expectation::None, target);
+    typecheck_field_access(hir, emit, proc, target_res, name)
+}
+
+fn typecheck_field_access<'hir>(
+    hir: &HirData<'hir, '_, '_>,
+    emit: &mut HirEmit<'hir>,
+    proc: &mut ProcScope<'hir, '_>,
+    target_res: TypeResult<'hir>,
+    name: ast::Name,
+) -> TypeResult<'hir> {
+    let target_res = auto_deref(emit, target_res);
     let (field_ty, kind, deref) = check_type_field(hir, emit, proc, target_res.ty, name);
 
     match kind {
         FieldKind::Error => TypeResult::new(hir::Type::Error, hir_build::EXPR_ERROR),
-        FieldKind::Field(struct_id, field_id) => TypeResult::new(
-            field_ty,
-            emit.arena.alloc(hir::Expr::StructField {
-                target: target_res.expr,
-                struct_id,
-                field_id,
-                deref,
-            }),
-        ),
+        FieldKind::Field(struct_id, field_id) => {
+            check_field_vis(hir, emit, proc, struct_id, field_id, name.range);
+            TypeResult::new(
+                field_ty,
+                emit.arena.alloc(hir::Expr::StructField {
+                    target: target_res.expr,
+                    struct_id,
+                    field_id,
+                    deref,
+                }),
+            )
+        }
         FieldKind::Slice { first_ptr } => TypeResult::new(
             field_ty,
             emit.arena.alloc(hir::Expr::SliceField {
@@ -892,6 +1822,68 @@ fn typecheck_field<'hir>(
                 deref,
             }),
         ),
+        // length is known from the type, target is not evaluated
+        FieldKind::ArrayLen(len) => TypeResult::new(
+            field_ty,
+            emit.arena.alloc(hir::Expr::Const {
+                value: hir::ConstValue::Int {
+                    val: len,
+                    neg: false,
+                    ty: BasicType::Usize,
+                },
+            }),
+        ),
+    }
+}
+
+fn check_field_vis(
+    hir: &HirData,
+    emit: &mut HirEmit,
+    proc: &ProcScope,
+    struct_id: hir::StructID,
+    field_id: hir::StructFieldID,
+    range: TextRange,
+) {
+    let data = hir.registry().struct_data(struct_id);
+    let field = data.field(field_id);
+
+    if field.vis == ast::Vis::Private && !hir.module_in_subtree(proc.origin(), data.origin_id) {
+        emit.error(ErrorComp::new(
+            ErrorCode::PrivateItem,
+            format!("field `{}` is private", hir.name_str(field.name.id)),
+            SourceRange::new(proc.origin(), range),
+            Info::new(
+                "defined here",
+                SourceRange::new(data.origin_id, field.name.range),
+            ),
+        ));
+    }
+}
+
+fn method_call_hint(hir: &HirData, ty: hir::Type, name: ast::Name) -> String {
+    let method = type_method_owner(ty).and_then(|owner| hir.method_find(owner, name.id));
+    match method {
+        Some(_) => format!(
+            "\nmethod `{0}` exists, call it with `.{0}(..)`",
+            hir.name_str(name.id)
+        ),
+        None => String::new(),
+    }
+}
+
+// methods take priority, fields with the same name are not allowed
+fn method_call_find(hir: &HirData, mut ty: hir::Type, name: ast::Name) -> Option<Method> {
+    while let hir::Type::Reference(ref_ty, _) = ty {
+        ty = *ref_ty;
+    }
+    type_method_owner(ty).and_then(|owner| hir.method_find(owner, name.id))
+}
+
+pub fn type_method_owner(ty: hir::Type) -> Option<MethodOwner> {
+    match ty {
+        hir::Type::Enum(id) => Some(MethodOwner::Enum(id)),
+        hir::Type::Struct(id) => Some(MethodOwner::Struct(id)),
+        _ => None,
     }
 }
 
@@ -899,6 +1891,7 @@ enum FieldKind {
     Error,
     Field(hir::StructID, hir::StructFieldID),
     Slice { first_ptr: bool },
+    ArrayLen(u64),
 }
 
 fn check_type_field<'hir>(
@@ -929,13 +1922,17 @@ fn type_get_field<'hir>(
         hir::Type::Struct(id) => {
             let data = hir.registry().struct_data(id);
             if let Some((field_id, field)) = data.find_field(name.id) {
+                let kind = NameRefKind::Field(id, field_id);
+                emit.names.add(proc.origin(), name.range, kind);
                 (field.ty, FieldKind::Field(id, field_id))
             } else {
                 emit.error(ErrorComp::new(
+                    ErrorCode::UnknownField,
                     format!(
-                        "no field `{}` exists on struct type `{}`",
+                        "no field `{}` exists on struct type `{}`{}",
                         hir.name_str(name.id),
                         hir.name_str(data.name.id),
+                        method_call_hint(hir, ty, name),
                     ),
                     SourceRange::new(proc.origin(), name.range),
                     None,
@@ -957,6 +1954,7 @@ fn type_get_field<'hir>(
                 _ => {
                     let ty_format = type_format(hir, emit, ty);
                     emit.error(ErrorComp::new(
+                        ErrorCode::UnknownField,
                         format!(
                             "no field `{}` exists on slice type `{}`\ndid you mean `len` or `ptr`?",
                             hir.name_str(name.id),
@@ -969,13 +1967,21 @@ fn type_get_field<'hir>(
                 }
             }
         }
+        hir::Type::ArrayStatic(array) if hir.name_str(name.id) == "len" => {
+            match array_static_get_len(hir, emit, array.len) {
+                Some(len) => (hir::Type::Basic(BasicType::Usize), FieldKind::ArrayLen(len)),
+                None => (hir::Type::Error, FieldKind::Error),
+            }
+        }
         _ => {
             let ty_format = type_format(hir, emit, ty);
             emit.error(ErrorComp::new(
+                ErrorCode::UnknownField,
                 format!(
-                    "no field `{}` exists on value of type `{}`",
+                    "no field `{}` exists on value of type `{}`{}",
                     hir.name_str(name.id),
                     ty_format,
+                    method_call_hint(hir, ty, name),
                 ),
                 SourceRange::new(proc.origin(), name.range),
                 None,
@@ -985,6 +1991,22 @@ fn type_get_field<'hir>(
     }
 }
 
+/// peels nested references, leaving at most one level
+/// which is then dereferenced by the field or index access itself
+fn auto_deref<'hir>(emit: &mut HirEmit<'hir>, mut res: TypeResult<'hir>) -> TypeResult<'hir> {
+    while let hir::Type::Reference(ref_ty, _) = res.ty {
+        if !matches!(ref_ty, hir::Type::Reference(..)) {
+            break;
+        }
+        let deref_expr = hir::Expr::Deref {
+            rhs: res.expr,
+            ptr_ty: ref_ty,
+        };
+        res = TypeResult::new(*ref_ty, emit.arena.alloc(deref_expr));
+    }
+    res
+}
+
 struct CollectionType<'hir> {
     deref: bool,
     elem_ty: hir::Type<'hir>,
@@ -1034,11 +2056,20 @@ fn typecheck_index<'hir>(
     expr_range: TextRange, //@use range of brackets? `[]` 08.05.24
 ) -> TypeResult<'hir> {
     let target_res = typecheck_expr(hir, emit, proc, Expectation::None, target);
+    let target_res = auto_deref(emit, target_res);
     let expect_usize = Expectation::HasType(hir::Type::USIZE, None);
     let index_res = typecheck_expr(hir, emit, proc, expect_usize, index);
 
     match CollectionType::from(target_res.ty) {
         Ok(Some(collection)) => {
+            let bounds_check = match collection.kind {
+                SliceOrArray::Slice(_) => true,
+                SliceOrArray::Array(array) => {
+                    let len = array_static_get_len(hir, emit, array.len);
+                    let index = const_fold::fold_usize(hir, emit, index_res.expr);
+                    !matches!(len.zip(index), Some((len, index)) if index < len)
+                }
+            };
             let access = hir::IndexAccess {
                 deref: collection.deref,
                 elem_ty: collection.elem_ty,
@@ -1056,6 +2087,7 @@ fn typecheck_index<'hir>(
                     SliceOrArray::Array(array) => hir::IndexKind::Array { array },
                 },
                 index: index_res.expr,
+                bounds_check,
             };
 
             let index_expr = hir::Expr::Index {
@@ -1067,6 +2099,7 @@ fn typecheck_index<'hir>(
         Ok(None) => TypeResult::new(hir::Type::Error, hir_build::EXPR_ERROR),
         Err(()) => {
             emit.error(ErrorComp::new(
+                ErrorCode::CannotIndex,
                 format!(
                     "cannot index value of type `{}`",
                     type_format(hir, emit, target_res.ty)
@@ -1082,16 +2115,55 @@ fn typecheck_index<'hir>(
     }
 }
 
+fn check_slice_mutability<'hir>(
+    hir: &HirData<'hir, '_, '_>,
+    emit: &mut HirEmit<'hir>,
+    proc: &ProcScope<'hir, '_>,
+    collection: &CollectionType<'hir>,
+    target: &'hir hir::Expr<'hir>,
+    expr_range: TextRange,
+) {
+    let (immutable, src) = match collection.kind {
+        SliceOrArray::Slice(slice) => (
+            slice.mutt == ast::Mut::Immutable,
+            place::binding_source(hir, proc, target),
+        ),
+        SliceOrArray::Array(_) => {
+            let adressability = if collection.deref {
+                place::classify_deref_place(hir, proc, target)
+            } else {
+                place::classify_place(hir, proc, target)
+            };
+            match adressability {
+                Addressability::Addressable(mutt, src) => (mutt == ast::Mut::Immutable, Some(src)),
+                Addressability::ReferenceTarget(mutt, src)
+                | Addressability::SliceElement(mutt, src) => (mutt == ast::Mut::Immutable, src),
+                _ => (false, None),
+            }
+        }
+    };
+
+    if immutable {
+        emit.error(ErrorComp::new(
+            ErrorCode::MutableAccessToImmutable,
+            "cannot create mutable slice from immutable value",
+            SourceRange::new(proc.origin(), expr_range),
+            src.and_then(|src| Info::new("variable defined here", src)),
+        ));
+    }
+}
+
 fn typecheck_slice<'hir>(
     hir: &HirData<'hir, '_, '_>,
     emit: &mut HirEmit<'hir>,
     proc: &mut ProcScope<'hir, '_>,
     target: &ast::Expr<'_>,
     mutt: ast::Mut,
-    slice: &ast::SliceRange<'_>,
+    slice: &ast::RangeExpr<'_>,
     expr_range: TextRange, //@use range of brackets? `[]` 08.05.24
 ) -> TypeResult<'hir> {
     let target_res = typecheck_expr(hir, emit, proc, Expectation::None, target);
+    let target_res = auto_deref(emit, target_res);
     let expect_usize = Expectation::HasType(hir::Type::USIZE, None);
 
     let lower = slice.lower.map(|lower| {
@@ -1099,12 +2171,12 @@ fn typecheck_slice<'hir>(
         lower_res.expr
     });
     let upper = match slice.upper {
-        ast::SliceRangeEnd::Unbounded => hir::SliceRangeEnd::Unbounded,
-        ast::SliceRangeEnd::Exclusive(upper) => {
+        ast::RangeEnd::Unbounded => hir::SliceRangeEnd::Unbounded,
+        ast::RangeEnd::Exclusive(upper) => {
             let upper_res = typecheck_expr(hir, emit, proc, expect_usize, upper);
             hir::SliceRangeEnd::Exclusive(upper_res.expr)
         }
-        ast::SliceRangeEnd::Inclusive(upper) => {
+        ast::RangeEnd::Inclusive(upper) => {
             let upper_res = typecheck_expr(hir, emit, proc, expect_usize, upper);
             hir::SliceRangeEnd::Inclusive(upper_res.expr)
         }
@@ -1130,8 +2202,13 @@ fn typecheck_slice<'hir>(
                 range: hir::SliceRange { lower, upper },
             };
 
-            //@mutability not checked, use addressability? 08.05.24
-            // or only base type? check different cases (eg:  &slice_var[mut ..] // invalid? )
+            if mutt == ast::Mut::Mutable {
+                check_slice_mutability(hir, emit, proc, &collection, target_res.expr, expr_range);
+            }
+            if let SliceOrArray::Array(array) = collection.kind {
+                let len = array_static_get_len(hir, emit, array.len);
+                check_slice_bounds(hir, emit, proc, len, slice, access.range);
+            }
             let slice_ty = emit.arena.alloc(hir::ArraySlice {
                 mutt,
                 elem_ty: collection.elem_ty,
@@ -1149,6 +2226,7 @@ fn typecheck_slice<'hir>(
         Ok(None) => TypeResult::new(hir::Type::Error, hir_build::EXPR_ERROR),
         Err(()) => {
             emit.error(ErrorComp::new(
+                ErrorCode::CannotIndex,
                 format!(
                     "cannot slice value of type `{}`",
                     type_format(hir, emit, target_res.ty)
@@ -1164,18 +2242,125 @@ fn typecheck_slice<'hir>(
     }
 }
 
+/// constant bounds are checked against each other and static array length
+fn check_slice_bounds<'hir>(
+    hir: &HirData<'hir, '_, '_>,
+    emit: &mut HirEmit<'hir>,
+    proc: &ProcScope<'hir, '_>,
+    len: Option<u64>,
+    slice: &ast::RangeExpr,
+    range: hir::SliceRange<'hir>,
+) {
+    let const_usize = |expr| const_fold::fold_usize(hir, emit, expr);
+    let lower = match range.lower {
+        Some(lower) => const_usize(lower),
+        None => Some(0),
+    };
+    // value as written and its exclusive end
+    let upper = match range.upper {
+        hir::SliceRangeEnd::Unbounded => len.map(|len| (len, len)),
+        hir::SliceRangeEnd::Exclusive(upper) => const_usize(upper).map(|val| (val, val)),
+        hir::SliceRangeEnd::Inclusive(upper) => {
+            const_usize(upper).and_then(|val| Some((val, val.checked_add(1)?)))
+        }
+    };
+    let lower_range = slice.lower.map(|expr| expr.range);
+    let upper_range = match slice.upper {
+        ast::RangeEnd::Unbounded => None,
+        ast::RangeEnd::Exclusive(expr) | ast::RangeEnd::Inclusive(expr) => Some(expr.range),
+    };
+
+    let (message, range) = if let Some((lower, len)) =
+        lower.zip(len).filter(|(lower, len)| lower > len)
+    {
+        let msg =
+            format!("slice range start `{lower}` is out of bounds for array of length `{len}`");
+        (msg, lower_range)
+    } else if let Some(((upper, _), len)) = upper.zip(len).filter(|((_, end), len)| end > len) {
+        let msg = format!("slice range end `{upper}` is out of bounds for array of length `{len}`");
+        (msg, upper_range)
+    } else if let Some((lower, (upper, _))) =
+        lower.zip(upper).filter(|(lower, (_, end))| lower > end)
+    {
+        let msg = format!("slice range start `{lower}` is greater than range end `{upper}`");
+        (msg, lower_range)
+    } else {
+        return;
+    };
+
+    if let Some(range) = range {
+        emit.warning(WarningComp::new(
+            WarningCode::SliceOutOfBounds,
+            message,
+            SourceRange::new(proc.origin(), range),
+            None,
+        ));
+    }
+}
+
 fn typecheck_call<'hir>(
     hir: &HirData<'hir, '_, '_>,
     emit: &mut HirEmit<'hir>,
     proc: &mut ProcScope<'hir, '_>,
+    expect: Expectation<'hir>,
     target: &ast::Expr<'_>,
     input: &&[&ast::Expr<'_>],
     expr_range: TextRange,
 ) -> TypeResult<'hir> {
-    let target_res = typecheck_expr(hir, emit, proc, Expectation::None, target);
+    // variants are checked directly, calling them is the only valid use of payload variants
+    let target_res = match target.kind {
+        // `a.b.c()` is parsed as a path, last segment might name a method
+        ast::ExprKind::Item { path } => {
+            let (item_res, field_names) =
+                typecheck_item_value(hir, emit, proc, Expectation::None, path);
+            match field_names.split_last() {
+                Some((&name, field_names)) => {
+                    let target_res = typecheck_item_fields(hir, emit, proc, item_res, field_names);
+                    if let Some(method) = method_call_find(hir, target_res.ty, name) {
+                        let prev_name = path.names[path.names.len() - 2];
+                        let receiver = MethodCallReceiver {
+                            res: target_res,
+                            range: TextRange::new(target.range.start(), prev_name.range.end()),
+                            name_range: name.range,
+                        };
+                        return typecheck_method_call(
+                            hir, emit, proc, method, receiver, input, expr_range,
+                        );
+                    }
+                    typecheck_field_access(hir, emit, proc, target_res, name)
+                }
+                None => item_res,
+            }
+        }
+        ast::ExprKind::Variant { name } => {
+            typecheck_variant(hir, emit, proc, expect, name, target.range)
+        }
+        ast::ExprKind::Field { target, name } => {
+            let target_res = typecheck_expr(hir, emit, proc, Expectation::None, target);
+            if let Some(method) = method_call_find(hir, target_res.ty, name) {
+                let receiver = MethodCallReceiver {
+                    res: target_res,
+                    range: target.range,
+                    name_range: name.range,
+                };
+                return typecheck_method_call(hir, emit, proc, method, receiver, input, expr_range);
+            }
+            typecheck_field_access(hir, emit, proc, target_res, name)
+        }
+        _ => typecheck_expr(hir, emit, proc, Expectation::None, target),
+    };
 
     match target_res.ty {
         hir::Type::Error => {}
+        hir::Type::Enum(enum_id) if expr_variant_has_fields(hir, target_res.expr) => {
+            let variant_id = match *target_res.expr {
+                hir::Expr::Const {
+                    value: hir::ConstValue::EnumVariant { variant_id, .. },
+                } => variant_id,
+                _ => unreachable!(),
+            };
+            return typecheck_variant_init(hir, emit, proc, enum_id, variant_id, input, expr_range);
+        }
         hir::Type::Procedure(proc_ty) => {
             // both direct and indirect return proc_ty
             // it can be used for input checks
@@ -1206,10 +2391,11 @@ fn typecheck_call<'hir>(
                     None
                 };
 
-                //@plular form for argument`s` only needed if != 1
+                let plural = if expected_count == 1 { "" } else { "s" };
                 emit.error(ErrorComp::new(
+                    ErrorCode::ArgumentCount,
                     format!(
-                        "expected{at_least} {} input arguments, found {}",
+                        "expected{at_least} {} input argument{plural}, found {}",
                         expected_count, input_count
                     ),
                     SourceRange::new(proc.origin(), expr_range),
@@ -1217,15 +2403,41 @@ fn typecheck_call<'hir>(
                 ));
             }
 
+            if emit.signature_requested(proc.origin(), expr_range) {
+                record_signature(hir, emit, direct_id, *proc_ty, 0);
+            }
+            if let Some(proc_id) = direct_id {
+                record_param_hints(hir, emit, proc.origin(), proc_id, input, 0);
+                check_deprecated_call(hir, emit, proc.origin(), proc_id, target.range);
+            }
+            if let Some(proc_id) = direct_id {
+                if hir.registry().proc_data(proc_id).poly_params.is_some() {
+                    return typecheck_call_poly(hir, emit, proc, proc_id, input, expr_range);
+                }
+            }
+
             let mut hir_input = Vec::with_capacity(input.len());
             for (idx, &expr) in input.iter().enumerate() {
-                //@expectation could have source?
                 let expect = match proc_ty.params.get(idx) {
-                    Some(param) => Expectation::HasType(*param, None),
+                    Some(param) => {
+                        // direct calls point to the parameter type in declaration
+                        let expect_src = direct_id.map(|proc_id| {
+                            let data = hir.registry().proc_data(proc_id);
+                            let item = hir.registry().proc_item(proc_id);
+                            SourceRange::new(data.origin_id, item.params[idx].ty.range)
+                        });
+                        Expectation::HasType(*param, expect_src)
+                    }
                     None => Expectation::None,
                 };
                 let input_res = typecheck_expr(hir, emit, proc, expect, expr);
-                hir_input.push(input_res.expr);
+
+                if proc_ty.is_variadic && idx >= expected_count {
+                    let input_expr = check_variadic_arg(hir, emit, proc, input_res, expr.range);
+                    hir_input.push(input_expr);
+                } else {
+                    hir_input.push(input_res.expr);
+                }
             }
             let hir_input = emit.arena.alloc_slice(&hir_input);
 
@@ -1249,22 +2461,659 @@ fn typecheck_call<'hir>(
                 proc_ty.return_ty.is_never(),
             );
         }
-        _ => {
-            emit.error(ErrorComp::new(
-                format!(
-                    "cannot call value of type `{}`",
-                    type_format(hir, emit, target_res.ty)
-                ),
-                SourceRange::new(proc.origin(), target.range),
-                None,
-            ));
+        _ => {
+            emit.error(ErrorComp::new(
+                ErrorCode::NotCallable,
+                format!(
+                    "cannot call value of type `{}`",
+                    type_format(hir, emit, target_res.ty)
+                ),
+                SourceRange::new(proc.origin(), target.range),
+                None,
+            ));
+        }
+    }
+
+    for &expr in input.iter() {
+        let _ = typecheck_expr(hir, emit, proc, Expectation::None, expr);
+    }
+    TypeResult::new(hir::Type::Error, hir_build::EXPR_ERROR)
+}
+
+// C default argument promotions are applied to variadic arguments
+fn check_variadic_arg<'hir>(
+    hir: &HirData<'hir, '_, '_>,
+    emit: &mut HirEmit<'hir>,
+    proc: &ProcScope<'hir, '_>,
+    arg_res: TypeResult<'hir>,
+    arg_range: TextRange,
+) -> &'hir hir::Expr<'hir> {
+    let (into, kind) = match arg_res.ty {
+        hir::Type::Basic(BasicType::S8 | BasicType::S16) => {
+            (BasicType::S32, hir::CastKind::Sint_Sign_Extend)
+        }
+        hir::Type::Basic(BasicType::U8 | BasicType::U16 | BasicType::Bool) => {
+            (BasicType::S32, hir::CastKind::Uint_Zero_Extend)
+        }
+        hir::Type::Basic(BasicType::F16 | BasicType::F32) => {
+            (BasicType::F64, hir::CastKind::Float_Extend)
+        }
+        hir::Type::Basic(BasicType::Void) => {
+            emit.error(ErrorComp::new(
+                ErrorCode::InvalidVariadicArgument,
+                "cannot pass `void` value as variadic argument",
+                SourceRange::new(proc.origin(), arg_range),
+                None,
+            ));
+            return arg_res.expr;
+        }
+        ty => {
+            if let Some(reason) = type_ffi_unsafe(hir, ty, false) {
+                emit.error(ErrorComp::new(
+                    ErrorCode::InvalidVariadicArgument,
+                    format!(
+                        "cannot pass value of type `{}` as variadic argument\n{reason}",
+                        type_format(hir, emit, ty)
+                    ),
+                    SourceRange::new(proc.origin(), arg_range),
+                    None,
+                ));
+            }
+            return arg_res.expr;
+        }
+    };
+
+    let cast_expr = hir::Expr::Cast {
+        target: arg_res.expr,
+        into: emit.arena.alloc(hir::Type::Basic(into)),
+        kind,
+    };
+    emit.arena.alloc(cast_expr)
+}
+
+struct MethodCallReceiver<'hir> {
+    res: TypeResult<'hir>,
+    range: TextRange,
+    name_range: TextRange,
+}
+
+// receiver is passed as the first argument,
+// automatically referenced or dereferenced to match `self`
+fn typecheck_method_call<'hir>(
+    hir: &HirData<'hir, '_, '_>,
+    emit: &mut HirEmit<'hir>,
+    proc: &mut ProcScope<'hir, '_>,
+    method: Method,
+    receiver: MethodCallReceiver<'hir>,
+    input: &[&ast::Expr<'_>],
+    expr_range: TextRange,
+) -> TypeResult<'hir> {
+    let method_kind = SymbolKind::Proc(method.proc_id);
+    let reference = SourceRange::new(proc.origin(), receiver.name_range);
+    let name_kind = method_kind.name_ref_kind();
+    emit.names.add(proc.origin(), reference.range(), name_kind);
+    emit.usage.mark_symbol(method_kind);
+    emit.usage.add_reference(method_kind, reference);
+    let data = hir.registry().proc_data(method.proc_id);
+    let item = hir.registry().proc_item(method.proc_id);
+
+    if data.vis == ast::Vis::Private && !hir.module_in_subtree(proc.origin(), data.origin_id) {
+        emit.error(ErrorComp::new(
+            ErrorCode::PrivateItem,
+            format!("method `{}` is private", hir.name_str(data.name.id)),
+            SourceRange::new(proc.origin(), receiver.name_range),
+            Info::new(
+                "defined here",
+                SourceRange::new(data.origin_id, data.name.range),
+            ),
+        ));
+    }
+    check_deprecated_call(
+        hir,
+        emit,
+        proc.origin(),
+        method.proc_id,
+        receiver.name_range,
+    );
+
+    let receiver_res = auto_deref(emit, receiver.res);
+    let self_expr = match (method.receiver, receiver_res.ty) {
+        (MethodReceiver::Value, hir::Type::Reference(ref_ty, _)) => {
+            let deref_expr = hir::Expr::Deref {
+                rhs: receiver_res.expr,
+                ptr_ty: ref_ty,
+            };
+            emit.arena.alloc(deref_expr)
+        }
+        (MethodReceiver::Value, _) => receiver_res.expr,
+        (MethodReceiver::Reference(mutt), hir::Type::Reference(_, ref_mutt)) => {
+            let ref_mutt = match place::classify_deref_place(hir, proc, receiver_res.expr) {
+                Addressability::ReferenceTarget(ref_mutt, _) => ref_mutt,
+                _ => ref_mutt,
+            };
+            if mutt == ast::Mut::Mutable && ref_mutt == ast::Mut::Immutable {
+                emit.error(ErrorComp::new(
+                    ErrorCode::MutableAccessToImmutable,
+                    format!(
+                        "cannot call method `{}` which takes `&mut self` through an immutable reference",
+                        hir.name_str(data.name.id)
+                    ),
+                    SourceRange::new(proc.origin(), receiver.range),
+                    Info::new(
+                        "method defined here",
+                        SourceRange::new(data.origin_id, data.name.range),
+                    ),
+                ));
+            }
+            receiver_res.expr
+        }
+        (MethodReceiver::Reference(mutt), _) => {
+            place::check_place(
+                hir,
+                emit,
+                proc,
+                PlaceUse::Reference(mutt),
+                receiver_res.expr,
+                receiver.range,
+            );
+            let address_expr = hir::Expr::Address {
+                rhs: receiver_res.expr,
+            };
+            emit.arena.alloc(address_expr)
+        }
+    };
+
+    if emit.signature_requested(proc.origin(), expr_range) {
+        let proc_ty = hir::ProcType {
+            params: &[],
+            return_ty: data.return_ty,
+            is_variadic: item.is_variadic,
+        };
+        record_signature(hir, emit, Some(method.proc_id), proc_ty, 1);
+    }
+    record_param_hints(hir, emit, proc.origin(), method.proc_id, input, 1);
+
+    let input_count = input.len();
+    let expected_count = data.params.len() - 1;
+    if input_count != expected_count {
+        let plural = if expected_count == 1 { "" } else { "s" };
+        emit.error(ErrorComp::new(
+            ErrorCode::ArgumentCount,
+            format!("expected {expected_count} input argument{plural}, found {input_count}"),
+            SourceRange::new(proc.origin(), expr_range),
+            Info::new(
+                "calling this method",
+                SourceRange::new(data.origin_id, data.name.range),
+            ),
+        ));
+    }
+
+    let mut hir_input = Vec::with_capacity(input.len() + 1);
+    hir_input.push(self_expr);
+    for (idx, &expr) in input.iter().enumerate() {
+        let expect = match data.params.get(idx + 1) {
+            Some(param) => {
+                let expect_src = SourceRange::new(data.origin_id, item.params[idx + 1].ty.range);
+                Expectation::HasType(param.ty, Some(expect_src))
+            }
+            None => Expectation::None,
+        };
+        let input_res = typecheck_expr(hir, emit, proc, expect, expr);
+        hir_input.push(input_res.expr);
+    }
+
+    let call_expr = hir::Expr::CallDirect {
+        proc_id: method.proc_id,
+        input: emit.arena.alloc_slice(&hir_input),
+    };
+    TypeResult::new_div(
+        data.return_ty,
+        emit.arena.alloc(call_expr),
+        data.return_ty.is_never(),
+    )
+}
+
+// direct calls show parameter names, params of `proc_ty` are used for indirect calls,
+// `skip_inputs` accounts for the method receiver which is not part of the input list
+fn record_signature<'hir>(
+    hir: &HirData<'hir, '_, '_>,
+    emit: &mut HirEmit<'hir>,
+    direct_id: Option<hir::ProcID>,
+    proc_ty: hir::ProcType<'hir>,
+    skip_inputs: usize,
+) {
+    let mut label = String::from("proc");
+    let mut params = Vec::new();
+
+    match direct_id {
+        Some(proc_id) => {
+            let data = hir.registry().proc_data(proc_id);
+            label.push(' ');
+            label.push_str(hir.name_str(data.name.id));
+            label.push('(');
+            for (idx, param) in data.params.iter().enumerate() {
+                if idx != 0 {
+                    label.push_str(", ");
+                }
+                let mutt = match param.mutt {
+                    ast::Mut::Mutable => "mut ",
+                    ast::Mut::Immutable => "",
+                };
+                let name = hir.name_str(param.name.id);
+                let ty = type_format(hir, emit, param.ty);
+                signature_param(&mut label, &mut params, &format!("{mutt}{name}: {ty}"));
+            }
+        }
+        None => {
+            label.push('(');
+            for (idx, param) in proc_ty.params.iter().enumerate() {
+                if idx != 0 {
+                    label.push_str(", ");
+                }
+                let ty = type_format(hir, emit, *param);
+                signature_param(&mut label, &mut params, &ty);
+            }
+        }
+    }
+    if proc_ty.is_variadic {
+        if !params.is_empty() {
+            label.push_str(", ");
+        }
+        signature_param(&mut label, &mut params, "..");
+    }
+    label.push(')');
+    if !proc_ty.return_ty.is_void() {
+        label.push_str(" -> ");
+        label.push_str(&type_format(hir, emit, proc_ty.return_ty));
+    }
+
+    let query = emit.signature_query.as_mut().unwrap();
+    let mut active_param = query.active_input + skip_inputs;
+    if proc_ty.is_variadic {
+        active_param = active_param.min(params.len() - 1);
+    }
+    query.signature = Some(super::Signature {
+        label,
+        active_param: (active_param < params.len()).then_some(active_param),
+        params,
+    });
+}
+
+fn record_type_hint<'hir>(
+    hir: &HirData<'hir, '_, '_>,
+    emit: &mut HirEmit<'hir>,
+    origin_id: ModuleID,
+    offset: TextOffset,
+    ty: hir::Type<'hir>,
+) {
+    if emit.inlay_hints.is_none() || ty.is_error() {
+        return;
+    }
+    let hint = InlayHint {
+        offset,
+        kind: InlayHintKind::Type,
+        label: type_format(hir, emit, ty),
+    };
+    emit.inlay_hints.as_mut().unwrap().add(origin_id, hint);
+}
+
+fn check_deprecated_call(
+    hir: &HirData,
+    emit: &mut HirEmit,
+    origin_id: ModuleID,
+    proc_id: hir::ProcID,
+    range: TextRange,
+) {
+    let data = hir.registry().proc_data(proc_id);
+    if !data.attr_set.contains(hir::ProcFlag::Deprecated) {
+        return;
+    }
+    let mut message = format!("procedure `{}` is deprecated", hir.name_str(data.name.id));
+    let item = hir.registry().proc_item(proc_id);
+    for attr in item.attrs {
+        if let (ast::AttributeKind::Deprecated, Some(id)) = (attr.kind, attr.string) {
+            message.push('\n');
+            message.push_str(hir.intern_string().get_str(id));
+        }
+    }
+    emit.warning(WarningComp::new(
+        WarningCode::Deprecated,
+        message,
+        SourceRange::new(origin_id, range),
+        Info::new(
+            "deprecated here",
+            SourceRange::new(data.origin_id, data.name.range),
+        ),
+    ));
+}
+
+// inputs which are named same as the parameter dont get a hint
+fn record_param_hints<'hir>(
+    hir: &HirData<'hir, '_, '_>,
+    emit: &mut HirEmit<'hir>,
+    origin_id: ModuleID,
+    proc_id: hir::ProcID,
+    input: &[&ast::Expr<'_>],
+    skip_params: usize,
+) {
+    let hints = match emit.inlay_hints.as_mut() {
+        Some(hints) => hints,
+        None => return,
+    };
+    let data = hir.registry().proc_data(proc_id);
+    let params = data.params.iter().skip(skip_params);
+
+    for (expr, param) in input.iter().zip(params) {
+        if let ast::ExprKind::Item { path } = expr.kind {
+            if let [name] = path.names {
+                if name.id == param.name.id {
+                    continue;
+                }
+            }
+        }
+        let hint = InlayHint {
+            offset: expr.range.start(),
+            kind: InlayHintKind::Parameter,
+            label: hir.name_str(param.name.id).to_string(),
+        };
+        hints.add(origin_id, hint);
+    }
+}
+
+fn signature_param(label: &mut String, params: &mut Vec<TextRange>, param: &str) {
+    let start = label.len() as u32;
+    label.push_str(param);
+    params.push(TextRange::new(start.into(), (label.len() as u32).into()));
+}
+
+fn typecheck_call_poly<'hir>(
+    hir: &HirData<'hir, '_, '_>,
+    emit: &mut HirEmit<'hir>,
+    proc: &mut ProcScope<'hir, '_>,
+    proc_id: hir::ProcID,
+    input: &[&ast::Expr<'_>],
+    expr_range: TextRange,
+) -> TypeResult<'hir> {
+    let error_count = emit.error_count();
+    let data = hir.registry().proc_data(proc_id);
+    let item = hir.registry().proc_item(proc_id);
+    let poly_params = data.poly_params.unwrap();
+
+    // type parameters are inferred from input in order,
+    // unknown ones are `Error` which matches any type
+    let mut poly_types = vec![hir::Type::Error; poly_params.len()];
+    let mut hir_input = Vec::with_capacity(input.len());
+    let mut input_checks = Vec::with_capacity(input.len());
+
+    for (idx, &expr) in input.iter().enumerate() {
+        let expect = match data.params.get(idx) {
+            Some(param) => {
+                let expect_ty = type_substitute(emit, proc_id, &poly_types, param.ty);
+                let expect_src = SourceRange::new(data.origin_id, item.params[idx].ty.range);
+                Expectation::HasType(expect_ty, Some(expect_src))
+            }
+            None => Expectation::None,
+        };
+        let input_res = typecheck_expr(hir, emit, proc, expect, expr);
+
+        if let Some(param) = data.params.get(idx) {
+            type_infer_poly(proc_id, &mut poly_types, param.ty, input_res.ty);
+        }
+        if let Expectation::HasType(expect_ty, expect_src) = expect {
+            input_checks.push((expr.range, expect_ty, expect_src, input_res.ty));
+        }
+        hir_input.push(input_res.expr);
+    }
+
+    let mut inferred = true;
+    for (idx, poly_ty) in poly_types.iter().enumerate() {
+        if matches!(poly_ty, hir::Type::Error) {
+            inferred = false;
+            if !emit.did_error(error_count) {
+                let name = poly_params[idx];
+                emit.error(ErrorComp::new(
+                    ErrorCode::CannotInfer,
+                    format!("cannot infer type parameter `{}`", hir.name_str(name.id)),
+                    SourceRange::new(proc.origin(), expr_range),
+                    Info::new(
+                        "type parameter declared here",
+                        SourceRange::new(data.origin_id, name.range),
+                    ),
+                ));
+            }
+        }
+    }
+    if !inferred {
+        return TypeResult::new(hir::Type::Error, hir_build::EXPR_ERROR);
+    }
+
+    // input checked against partially inferred types is checked again
+    for (idx, (range, expect_ty, expect_src, input_ty)) in input_checks.into_iter().enumerate() {
+        let param_ty = type_substitute(emit, proc_id, &poly_types, data.params[idx].ty);
+        if type_matches(hir, emit, expect_ty, input_ty) {
+            let expect = Expectation::HasType(param_ty, expect_src);
+            check_type_expectation(hir, emit, proc.origin(), range, expect, input_ty);
+        }
+    }
+
+    let return_ty = type_substitute(emit, proc_id, &poly_types, data.return_ty);
+    let poly_types = emit.arena.alloc_slice(&poly_types);
+    let instance = if poly_types.iter().any(|ty| type_has_poly(*ty)) {
+        hir::ProcID::dummy()
+    } else {
+        let source = SourceRange::new(proc.origin(), expr_range);
+        poly_instance_request(hir, emit, proc_id, poly_types, source)
+    };
+
+    let poly_call = hir::PolyCall {
+        instance,
+        poly_types,
+        input: emit.arena.alloc_slice(&hir_input),
+        return_ty,
+    };
+    let call_expr = hir::Expr::CallPoly {
+        proc_id,
+        poly_call: emit.arena.alloc(poly_call),
+    };
+    TypeResult::new_div(return_ty, emit.arena.alloc(call_expr), return_ty.is_never())
+}
+
+const POLY_TYPE_DEPTH_LIMIT: u32 = 64;
+
+/// returns id of existing or newly requested instance,
+/// instances are assigned ids after all user defined procedures
+pub fn poly_instance_request<'hir>(
+    hir: &HirData<'hir, '_, '_>,
+    emit: &mut HirEmit<'hir>,
+    proc_id: hir::ProcID,
+    poly_types: &'hir [hir::Type<'hir>],
+    source: SourceRange,
+) -> hir::ProcID {
+    // recursive instances with growing types would never stop being requested
+    if poly_types
+        .iter()
+        .any(|ty| type_depth(*ty) > POLY_TYPE_DEPTH_LIMIT)
+    {
+        let name = hir.name_str(hir.registry().proc_data(proc_id).name.id);
+        emit.error(ErrorComp::new(
+            ErrorCode::GenericInstanceLimit,
+            format!("generic procedure `{name}` is instantiated with a type nested too deeply\ntype nesting limit is {POLY_TYPE_DEPTH_LIMIT}"),
+            source,
+            None,
+        ));
+        return hir::ProcID::dummy();
+    }
+
+    for instance in emit.poly_instances.iter() {
+        if instance.proc_id == proc_id
+            && (0..poly_types.len()).all(|idx| {
+                let ty = instance.poly_types[idx];
+                let ty2 = poly_types[idx];
+                type_matches(hir, emit, ty, ty2) && type_matches(hir, emit, ty2, ty)
+            })
+        {
+            return instance.instance_id;
+        }
+    }
+
+    let base_idx = match emit.poly_instances.first() {
+        Some(instance) => instance.instance_id.index(),
+        None => hir.registry().proc_ids().count(),
+    };
+    let instance_id = hir::ProcID::new(base_idx + emit.poly_instances.len());
+    emit.poly_instances.push(PolyInstance {
+        proc_id,
+        instance_id,
+        poly_types,
+        source,
+    });
+    instance_id
+}
+
+fn typecheck_variant_init<'hir>(
+    hir: &HirData<'hir, '_, '_>,
+    emit: &mut HirEmit<'hir>,
+    proc: &mut ProcScope<'hir, '_>,
+    enum_id: hir::EnumID,
+    variant_id: hir::EnumVariantID,
+    input: &[&ast::Expr<'_>],
+    expr_range: TextRange,
+) -> TypeResult<'hir> {
+    let data = hir.registry().enum_data(enum_id);
+    let variant = data.variant(variant_id);
+    let input_count = input.len();
+    let expected_count = variant.fields.len();
+
+    if input_count != expected_count {
+        let plural = if expected_count == 1 { "" } else { "s" };
+        emit.error(ErrorComp::new(
+            ErrorCode::ArgumentCount,
+            format!(
+                "expected {} variant field{plural}, found {}",
+                expected_count, input_count
+            ),
+            SourceRange::new(proc.origin(), expr_range),
+            Info::new(
+                "variant defined here",
+                SourceRange::new(data.origin_id, variant.name.range),
+            ),
+        ));
+    }
+
+    let item = hir.registry().enum_item(enum_id);
+    let variant_item = item
+        .variants
+        .iter()
+        .find(|it| it.name.id == variant.name.id);
+    let mut hir_input = Vec::with_capacity(input.len());
+
+    for (idx, &expr) in input.iter().enumerate() {
+        let expect = match variant.fields.get(idx) {
+            Some(field_ty) => {
+                let expect_src =
+                    variant_item.map(|it| SourceRange::new(data.origin_id, it.fields[idx].range));
+                Expectation::HasType(*field_ty, expect_src)
+            }
+            None => Expectation::None,
+        };
+        let input_res = typecheck_expr(hir, emit, proc, expect, expr);
+        hir_input.push(input_res.expr);
+    }
+
+    let variant_init = hir::VariantInit {
+        enum_id,
+        variant_id,
+        input: emit.arena.alloc_slice(&hir_input),
+    };
+    let variant_init = emit.arena.alloc(variant_init);
+    let variant_expr = hir::Expr::VariantInit { variant_init };
+    TypeResult::new(hir::Type::Enum(enum_id), emit.arena.alloc(variant_expr))
+}
+
+fn expr_variant_has_fields(hir: &HirData, expr: &hir::Expr) -> bool {
+    match *expr {
+        hir::Expr::Const {
+            value:
+                hir::ConstValue::EnumVariant {
+                    enum_id,
+                    variant_id,
+                },
+        } => {
+            let data = hir.registry().enum_data(enum_id);
+            !data.variant(variant_id).fields.is_empty()
+        }
+        _ => false,
+    }
+}
+
+fn check_variant_fields_missing<'hir>(
+    hir: &HirData<'hir, '_, '_>,
+    emit: &mut HirEmit<'hir>,
+    proc: &ProcScope<'hir, '_>,
+    expr_res: TypeResult<'hir>,
+    expr_range: TextRange,
+) -> TypeResult<'hir> {
+    if let hir::Expr::Const {
+        value: hir::ConstValue::EnumVariant {
+            enum_id,
+            variant_id,
+        },
+    } = *expr_res.expr
+    {
+        let data = hir.registry().enum_data(enum_id);
+        let variant = data.variant(variant_id);
+        if variant.fields.is_empty() {
+            return expr_res;
         }
+        emit.error(ErrorComp::new(
+            ErrorCode::ArgumentCount,
+            format!(
+                "variant `{}` requires {} field value{}",
+                hir.name_str(variant.name.id),
+                variant.fields.len(),
+                if variant.fields.len() == 1 { "" } else { "s" }
+            ),
+            SourceRange::new(proc.origin(), expr_range),
+            Info::new(
+                "variant defined here",
+                SourceRange::new(data.origin_id, variant.name.range),
+            ),
+        ));
+        return TypeResult::new(hir::Type::Error, hir_build::EXPR_ERROR);
     }
+    expr_res
+}
 
-    for &expr in input.iter() {
-        let _ = typecheck_expr(hir, emit, proc, Expectation::None, expr);
+fn check_poly_proc_value<'hir>(
+    hir: &HirData<'hir, '_, '_>,
+    emit: &mut HirEmit<'hir>,
+    proc: &ProcScope<'hir, '_>,
+    expr_res: TypeResult<'hir>,
+    expr_range: TextRange,
+) -> TypeResult<'hir> {
+    if let hir::Expr::Const {
+        value: hir::ConstValue::Procedure { proc_id },
+    } = *expr_res.expr
+    {
+        let data = hir.registry().proc_data(proc_id);
+        if data.poly_params.is_none() {
+            return expr_res;
+        }
+        emit.error(ErrorComp::new(
+            ErrorCode::GenericProcedureValue,
+            format!(
+                "generic procedure `{}` can only be called directly",
+                hir.name_str(data.name.id)
+            ),
+            SourceRange::new(proc.origin(), expr_range),
+            Info::new(
+                "procedure defined here",
+                SourceRange::new(data.origin_id, data.name.range),
+            ),
+        ));
+        return TypeResult::new(hir::Type::Error, hir_build::EXPR_ERROR);
     }
-    TypeResult::new(hir::Type::Error, hir_build::EXPR_ERROR)
+    expr_res
 }
 
 pub fn type_size(
@@ -1275,12 +3124,17 @@ pub fn type_size(
 ) -> Option<hir::Size> {
     match ty {
         hir::Type::Error => None,
-        hir::Type::Basic(basic) => Some(basic_type_size(basic)),
-        hir::Type::Enum(id) => Some(basic_type_size(hir.registry().enum_data(id).basic)),
+        hir::Type::Basic(basic) => Some(basic_type_size(hir.target(), basic)),
+        hir::Type::Enum(id) => hir.registry().enum_data(id).size_eval.get_size(),
         hir::Type::Struct(id) => hir.registry().struct_data(id).size_eval.get_size(),
-        hir::Type::Reference(_, _) => Some(hir::Size::new_equal(8)), //@assume 64bit target
-        hir::Type::Procedure(_) => Some(hir::Size::new_equal(8)),    //@assume 64bit target
-        hir::Type::ArraySlice(_) => Some(hir::Size::new(16, 8)),     //@assume 64bit target
+        hir::Type::Reference(_, _) | hir::Type::Procedure(_) => {
+            let ptr_size = hir.target().pointer_width();
+            Some(hir::Size::new_equal(ptr_size))
+        }
+        hir::Type::ArraySlice(_) => {
+            let ptr_size = hir.target().pointer_width();
+            Some(hir::Size::new(2 * ptr_size, ptr_size))
+        }
         hir::Type::ArrayStatic(array) => {
             if let (Some(elem_size), Some(len)) = (
                 type_size(hir, emit, array.elem_ty, source),
@@ -1290,6 +3144,7 @@ pub fn type_size(
                     Some(hir::Size::new(array_size, elem_size.align()))
                 } else {
                     emit.error(ErrorComp::new(
+                        ErrorCode::TypeSizeOverflow,
                         format!(
                             "array size overflow: `{}` * `{}` (elem_size * array_len)",
                             elem_size.size(),
@@ -1304,27 +3159,28 @@ pub fn type_size(
                 None
             }
         }
+        hir::Type::PolyProc(_, _) => None,
     }
 }
 
-fn basic_type_size(basic: BasicType) -> hir::Size {
+pub fn basic_type_size(target: TargetTriple, basic: BasicType) -> hir::Size {
     match basic {
         BasicType::S8 => hir::Size::new_equal(1),
         BasicType::S16 => hir::Size::new_equal(2),
         BasicType::S32 => hir::Size::new_equal(4),
         BasicType::S64 => hir::Size::new_equal(8),
-        BasicType::Ssize => hir::Size::new_equal(8), //@assume 64bit target
+        BasicType::Ssize => hir::Size::new_equal(target.pointer_width()),
         BasicType::U8 => hir::Size::new_equal(1),
         BasicType::U16 => hir::Size::new_equal(2),
         BasicType::U32 => hir::Size::new_equal(4),
         BasicType::U64 => hir::Size::new_equal(8),
-        BasicType::Usize => hir::Size::new_equal(8), //@assume 64bit target
+        BasicType::Usize => hir::Size::new_equal(target.pointer_width()),
         BasicType::F16 => hir::Size::new_equal(2),
         BasicType::F32 => hir::Size::new_equal(4),
         BasicType::F64 => hir::Size::new_equal(8),
         BasicType::Bool => hir::Size::new_equal(1),
         BasicType::Char => hir::Size::new_equal(4),
-        BasicType::Rawptr => hir::Size::new_equal(8), //@assume 64bit target
+        BasicType::Rawptr => hir::Size::new_equal(target.pointer_width()),
         BasicType::Void => hir::Size::new(0, 1),
         BasicType::Never => hir::Size::new(0, 1),
     }
@@ -1408,6 +3264,7 @@ fn typecheck_cast<'hir>(
     // ensured by early return above
     if type_matches(hir, emit, target_res.ty, into) {
         emit.warning(WarningComp::new(
+            WarningCode::RedundantCast,
             format!(
                 "redundant cast from `{}` into `{}`",
                 type_format(hir, emit, target_res.ty),
@@ -1425,75 +3282,23 @@ fn typecheck_cast<'hir>(
         return TypeResult::new(into, emit.arena.alloc(cast_expr));
     }
 
-    // invariant: from_size != into_size
-    // ensured by cast redundancy warning above
-    let cast_kind = match (target_res.ty, into) {
-        (hir::Type::Basic(from), hir::Type::Basic(into)) => {
-            let from_kind = BasicTypeKind::new(from);
-            let into_kind = BasicTypeKind::new(into);
-            let from_size = basic_type_size(from).size();
-            let into_size = basic_type_size(into).size();
-
-            match from_kind {
-                BasicTypeKind::SignedInt => match into_kind {
-                    BasicTypeKind::SignedInt | BasicTypeKind::UnsignedInt => {
-                        if from_size < into_size {
-                            hir::CastKind::Sint_Sign_Extend
-                        } else {
-                            hir::CastKind::Integer_Trunc
-                        }
-                    }
-                    BasicTypeKind::Float => hir::CastKind::Sint_to_Float,
-                    _ => hir::CastKind::Error,
-                },
-                BasicTypeKind::UnsignedInt => match into_kind {
-                    BasicTypeKind::SignedInt | BasicTypeKind::UnsignedInt => {
-                        if from_size < into_size {
-                            hir::CastKind::Uint_Zero_Extend
-                        } else {
-                            hir::CastKind::Integer_Trunc
-                        }
-                    }
-                    BasicTypeKind::Float => hir::CastKind::Uint_to_Float,
-                    _ => hir::CastKind::Error,
-                },
-                BasicTypeKind::Float => match into_kind {
-                    BasicTypeKind::SignedInt => hir::CastKind::Float_to_Sint,
-                    BasicTypeKind::UnsignedInt => hir::CastKind::Float_to_Uint,
-                    BasicTypeKind::Float => {
-                        if from_size < into_size {
-                            hir::CastKind::Float_Extend
-                        } else {
-                            hir::CastKind::Float_Trunc
-                        }
-                    }
-                    _ => hir::CastKind::Error,
-                },
-                BasicTypeKind::Bool => hir::CastKind::Error,
-                BasicTypeKind::Char => hir::CastKind::Error,
-                BasicTypeKind::Rawptr => hir::CastKind::Error,
-                BasicTypeKind::Void => hir::CastKind::Error,
-                BasicTypeKind::Never => hir::CastKind::Error,
-            }
+    let cast_kind = match cast_kind(hir, target_res.ty, into) {
+        Ok(cast_kind) => cast_kind,
+        Err(note) => {
+            emit.error(ErrorComp::new(
+                ErrorCode::InvalidCast,
+                format!(
+                    "invalid cast from `{}` into `{}`\n{note}",
+                    type_format(hir, emit, target_res.ty),
+                    type_format(hir, emit, into)
+                ),
+                SourceRange::new(proc.origin(), range),
+                None,
+            ));
+            hir::CastKind::Error
         }
-        _ => hir::CastKind::Error,
     };
 
-    if let hir::CastKind::Error = cast_kind {
-        //@cast could be primitive but still invalid
-        // wording might be improved
-        // or have 2 error types for this
-        emit.error(ErrorComp::new(
-            format!(
-                "non primitive cast from `{}` into `{}`",
-                type_format(hir, emit, target_res.ty),
-                type_format(hir, emit, into)
-            ),
-            SourceRange::new(proc.origin(), range),
-            None,
-        ));
-    }
-
     let cast_expr = hir::Expr::Cast {
         target: target_res.expr,
         into: emit.arena.alloc(into),
@@ -1502,6 +3307,100 @@ fn typecheck_cast<'hir>(
     TypeResult::new(into, emit.arena.alloc(cast_expr))
 }
 
+/// cast operation for `from as into`, types are known to be different
+/// `Err` contains a note on why the cast is not allowed
+fn cast_kind<'hir>(
+    hir: &HirData<'hir, '_, '_>,
+    from_ty: hir::Type<'hir>,
+    into_ty: hir::Type<'hir>,
+) -> Result<hir::CastKind, &'static str> {
+    let from = match from_ty {
+        hir::Type::Basic(basic) => basic,
+        hir::Type::Enum(enum_id) => {
+            let data = hir.registry().enum_data(enum_id);
+            if data.variants.iter().any(|v| !v.fields.is_empty()) {
+                return Err("enums with variant fields cannot be cast");
+            }
+            if !matches!(into_ty, hir::Type::Basic(basic) if BasicTypeKind::new(basic).is_integer())
+            {
+                return Err("enums can only be cast into integer types");
+            }
+            data.basic
+        }
+        hir::Type::Reference(_, _) => BasicType::Rawptr,
+        _ => return Err("only primitive types can be cast"),
+    };
+    let into = match into_ty {
+        hir::Type::Basic(basic) => basic,
+        hir::Type::Enum(_) => return Err("values cannot be cast into enums"),
+        _ => return Err("only primitive types can be cast"),
+    };
+
+    let from_size = basic_type_size(hir.target(), from).size();
+    let into_size = basic_type_size(hir.target(), into).size();
+    let int_cast = |extend: hir::CastKind| {
+        if from_size < into_size {
+            extend
+        } else if from_size > into_size {
+            hir::CastKind::Integer_Trunc
+        } else {
+            hir::CastKind::Integer_NoOp
+        }
+    };
+
+    let cast_kind = match (BasicTypeKind::new(from), BasicTypeKind::new(into)) {
+        (BasicTypeKind::SignedInt, BasicTypeKind::SignedInt | BasicTypeKind::UnsignedInt) => {
+            int_cast(hir::CastKind::Sint_Sign_Extend)
+        }
+        (
+            BasicTypeKind::UnsignedInt | BasicTypeKind::Char,
+            BasicTypeKind::SignedInt | BasicTypeKind::UnsignedInt,
+        ) => int_cast(hir::CastKind::Uint_Zero_Extend),
+        // `bool` is a single bit, always extended
+        (BasicTypeKind::Bool, BasicTypeKind::SignedInt | BasicTypeKind::UnsignedInt) => {
+            hir::CastKind::Uint_Zero_Extend
+        }
+        (BasicTypeKind::SignedInt, BasicTypeKind::Float) => hir::CastKind::Sint_to_Float,
+        (BasicTypeKind::UnsignedInt, BasicTypeKind::Float) => hir::CastKind::Uint_to_Float,
+        (BasicTypeKind::Float, BasicTypeKind::SignedInt) => hir::CastKind::Float_to_Sint,
+        (BasicTypeKind::Float, BasicTypeKind::UnsignedInt) => hir::CastKind::Float_to_Uint,
+        (BasicTypeKind::Float, BasicTypeKind::Float) => {
+            if from_size < into_size {
+                hir::CastKind::Float_Extend
+            } else {
+                hir::CastKind::Float_Trunc
+            }
+        }
+        (BasicTypeKind::UnsignedInt, BasicTypeKind::Char) if from == BasicType::U8 => {
+            hir::CastKind::Uint_Zero_Extend
+        }
+        // constants are checked to be unicode scalar values, runtime values are not
+        (BasicTypeKind::UnsignedInt, BasicTypeKind::Char) if from == BasicType::U32 => {
+            hir::CastKind::Integer_NoOp
+        }
+        (BasicTypeKind::Rawptr, BasicTypeKind::Rawptr) => hir::CastKind::NoOp,
+        (BasicTypeKind::Rawptr, BasicTypeKind::UnsignedInt) if into == BasicType::Usize => {
+            hir::CastKind::Pointer_to_Int
+        }
+        (BasicTypeKind::UnsignedInt, BasicTypeKind::Rawptr) if from == BasicType::Usize => {
+            hir::CastKind::Int_to_Pointer
+        }
+        (BasicTypeKind::Rawptr, _) | (_, BasicTypeKind::Rawptr) => {
+            return Err("pointers can only be cast from and into `usize`")
+        }
+        (_, BasicTypeKind::Bool) => return Err("compare the value to produce a `bool`"),
+        (_, BasicTypeKind::Char) => return Err("only `u8` and `u32` can be cast into `char`"),
+        (BasicTypeKind::Bool | BasicTypeKind::Char, BasicTypeKind::Float) => {
+            return Err("cast into an integer type first")
+        }
+        (BasicTypeKind::Void | BasicTypeKind::Never, _)
+        | (_, BasicTypeKind::Void | BasicTypeKind::Never) => {
+            return Err("`void` and `never` have no values to cast")
+        }
+    };
+    Ok(cast_kind)
+}
+
 fn typecheck_sizeof<'hir>(
     hir: &HirData<'hir, '_, '_>,
     emit: &mut HirEmit<'hir>,
@@ -1511,6 +3410,29 @@ fn typecheck_sizeof<'hir>(
 ) -> TypeResult<'hir> {
     let ty = super::pass_3::type_resolve(hir, emit, proc.origin(), ty);
 
+    //@sizeof is folded before monomorphization 16.10.26
+    if type_has_poly(ty) {
+        emit.error(ErrorComp::new(
+            ErrorCode::SizeofTypeParameter,
+            "cannot use `sizeof` on type parameters",
+            SourceRange::new(proc.origin(), expr_range),
+            None,
+        ));
+        return TypeResult::new(hir::Type::Basic(BasicType::Usize), hir_build::EXPR_ERROR);
+    }
+    if let hir::Type::Basic(basic @ (BasicType::Void | BasicType::Never)) = ty {
+        emit.error(ErrorComp::new(
+            ErrorCode::SizeofNoValues,
+            format!(
+                "cannot use `sizeof` on `{}`, type has no values",
+                basic.as_str()
+            ),
+            SourceRange::new(proc.origin(), expr_range),
+            None,
+        ));
+        return TypeResult::new(hir::Type::Basic(BasicType::Usize), hir_build::EXPR_ERROR);
+    }
+
     //@usize semantics not finalized yet
     // assigning usize type to constant int, since it represents size
     //@review source range for this type_size error 10.05.24
@@ -1529,17 +3451,213 @@ fn typecheck_sizeof<'hir>(
     TypeResult::new(hir::Type::Basic(BasicType::Usize), sizeof_expr)
 }
 
+fn typecheck_format<'hir>(
+    hir: &HirData<'hir, '_, '_>,
+    emit: &mut HirEmit<'hir>,
+    proc: &mut ProcScope<'hir, '_>,
+    format: &ast::Format<'_>,
+    expr_range: TextRange,
+) -> TypeResult<'hir> {
+    let string_ty = alloc_string_lit_type(emit, false);
+    let result_ty = match format.kind {
+        ast::FormatKind::Print => hir::Type::Basic(BasicType::Void),
+        ast::FormatKind::Format => string_ty,
+    };
+
+    let mut input = format.input.iter();
+    let buffer = if format.kind == ast::FormatKind::Format {
+        let buffer_ty = emit.arena.alloc(hir::ArraySlice {
+            mutt: ast::Mut::Mutable,
+            elem_ty: hir::Type::Basic(BasicType::U8),
+        });
+        let expect = Expectation::HasType(hir::Type::ArraySlice(buffer_ty), None);
+        input
+            .next()
+            .map(|buffer| typecheck_expr(hir, emit, proc, expect, buffer).expr)
+    } else {
+        None
+    };
+
+    let string_lit = match input.next() {
+        Some(expr) => match expr.kind {
+            ast::ExprKind::LitString {
+                id,
+                kind: ast::StringKind::Regular,
+            } => Some((id, expr.range)),
+            _ => {
+                emit.error(ErrorComp::new(
+                    ErrorCode::InvalidFormat,
+                    "format string must be a string literal",
+                    SourceRange::new(proc.origin(), expr.range),
+                    None,
+                ));
+                None
+            }
+        },
+        None => {
+            emit.error(ErrorComp::new(
+                ErrorCode::InvalidFormat,
+                format!("`#{}` expects a format string", format.kind.as_str()),
+                SourceRange::new(proc.origin(), expr_range),
+                None,
+            ));
+            None
+        }
+    };
+
+    let arg_count = input.len();
+    let mut args = Vec::with_capacity(arg_count);
+    for &arg in input {
+        let arg_res = typecheck_expr(hir, emit, proc, Expectation::None, arg);
+        let kind = match arg_res.ty {
+            hir::Type::Error => continue,
+            hir::Type::Basic(basic) => match BasicTypeKind::new(basic) {
+                BasicTypeKind::SignedInt => Some(hir::FormatArgKind::Sint),
+                BasicTypeKind::UnsignedInt => Some(hir::FormatArgKind::Uint),
+                BasicTypeKind::Float => Some(hir::FormatArgKind::Float),
+                BasicTypeKind::Bool => Some(hir::FormatArgKind::Bool),
+                _ => None,
+            },
+            hir::Type::ArraySlice(slice)
+                if matches!(slice.elem_ty, hir::Type::Basic(BasicType::U8)) =>
+            {
+                Some(hir::FormatArgKind::Slice)
+            }
+            hir::Type::Reference(hir::Type::Basic(BasicType::U8), _) => {
+                Some(hir::FormatArgKind::CString)
+            }
+            _ => None,
+        };
+
+        if let Some(kind) = kind {
+            let expr = arg_res.expr;
+            args.push(hir::FormatArg { kind, expr });
+        } else {
+            emit.error(ErrorComp::new(
+                ErrorCode::InvalidFormat,
+                format!(
+                    "cannot format value of type `{}`",
+                    type_format(hir, emit, arg_res.ty)
+                ),
+                SourceRange::new(proc.origin(), arg.range),
+                None,
+            ));
+        }
+    }
+
+    let (string_id, string_range) = match string_lit {
+        Some(string_lit) => string_lit,
+        None => return TypeResult::new(result_ty, hir_build::EXPR_ERROR),
+    };
+    let segments = match format_string_segments(hir.intern_string().get_str(string_id)) {
+        Ok(segments) => segments,
+        Err(message) => {
+            emit.error(ErrorComp::new(
+                ErrorCode::InvalidFormat,
+                message,
+                SourceRange::new(proc.origin(), string_range),
+                None,
+            ));
+            return TypeResult::new(result_ty, hir_build::EXPR_ERROR);
+        }
+    };
+
+    let expected_count = segments.len() - 1;
+    if expected_count != arg_count {
+        emit.error(ErrorComp::new(
+            ErrorCode::InvalidFormat,
+            format!(
+                "format string expects {} argument{}, found {}",
+                expected_count,
+                if expected_count == 1 { "" } else { "s" },
+                arg_count
+            ),
+            SourceRange::new(proc.origin(), expr_range),
+            None,
+        ));
+        return TypeResult::new(result_ty, hir_build::EXPR_ERROR);
+    }
+    if args.len() != arg_count {
+        return TypeResult::new(result_ty, hir_build::EXPR_ERROR);
+    }
+
+    let mut c_format = String::new();
+    for (idx, segment) in segments.iter().enumerate() {
+        c_format.push_str(segment);
+        if let Some(arg) = args.get(idx) {
+            c_format.push_str(match arg.kind {
+                hir::FormatArgKind::Sint => "%lld",
+                hir::FormatArgKind::Uint => "%llu",
+                hir::FormatArgKind::Float => "%g",
+                hir::FormatArgKind::Bool => "%s",
+                hir::FormatArgKind::Slice => "%.*s",
+                hir::FormatArgKind::CString => "%s",
+            });
+        }
+    }
+
+    let format = hir::Format {
+        buffer,
+        c_format: emit.arena.alloc_str(&c_format),
+        args: emit.arena.alloc_slice(&args),
+    };
+    let format_expr = hir::Expr::Format {
+        format: emit.arena.alloc(format),
+    };
+    TypeResult::new(result_ty, emit.arena.alloc(format_expr))
+}
+
+// splits format string by `{}` placeholders,
+// `{{` and `}}` are escapes, `%` is escaped for printf
+fn format_string_segments(string: &str) -> Result<Vec<String>, &'static str> {
+    let mut segments = Vec::new();
+    let mut segment = String::new();
+    let mut chars = string.chars();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '{' => match chars.next() {
+                Some('{') => segment.push('{'),
+                Some('}') => segments.push(std::mem::take(&mut segment)),
+                _ => return Err("invalid format string, expected `{}` or `{{`"),
+            },
+            '}' => match chars.next() {
+                Some('}') => segment.push('}'),
+                _ => return Err("invalid format string, unmatched `}`, use `}}` to escape it"),
+            },
+            '%' => segment.push_str("%%"),
+            _ => segment.push(c),
+        }
+    }
+
+    segments.push(segment);
+    Ok(segments)
+}
+
 fn typecheck_item<'hir>(
     hir: &HirData<'hir, '_, '_>,
     emit: &mut HirEmit<'hir>,
     proc: &mut ProcScope<'hir, '_>,
+    expect: Expectation<'hir>,
     path: &ast::Path,
 ) -> TypeResult<'hir> {
+    let (item_res, field_names) = typecheck_item_value(hir, emit, proc, expect, path);
+    typecheck_item_fields(hir, emit, proc, item_res, field_names)
+}
+
+fn typecheck_item_value<'hir, 'ast>(
+    hir: &HirData<'hir, 'ast, '_>,
+    emit: &mut HirEmit<'hir>,
+    proc: &mut ProcScope<'hir, '_>,
+    expect: Expectation<'hir>,
+    path: &'ast ast::Path<'ast>,
+) -> (TypeResult<'hir>, &'ast [ast::Name]) {
     let (value_id, field_names) = path_resolve_value(hir, emit, Some(proc), proc.origin(), path);
 
     let item_res = match value_id {
         ValueID::None => {
-            return TypeResult::new(hir::Type::Error, hir_build::EXPR_ERROR);
+            let error_res = TypeResult::new(hir::Type::Error, hir_build::EXPR_ERROR);
+            return (error_res, &[]);
         }
         ValueID::Proc(proc_id) => {
             let data = hir.registry().proc_data(proc_id);
@@ -1555,22 +3673,24 @@ fn typecheck_item<'hir>(
                 is_variadic: data.attr_set.contains(hir::ProcFlag::Variadic),
             };
 
-            return TypeResult::new(
+            let proc_res = TypeResult::new(
                 hir::Type::Procedure(emit.arena.alloc(proc_ty)),
                 emit.arena.alloc(hir::Expr::Const {
                     value: hir::ConstValue::Procedure { proc_id },
                 }),
             );
+            return (proc_res, &[]);
         }
         ValueID::Enum(enum_id, variant_id) => {
             let value = hir::ConstValue::EnumVariant {
                 enum_id,
                 variant_id,
             };
-            return TypeResult::new(
+            let enum_res = TypeResult::new(
                 hir::Type::Enum(enum_id),
                 emit.arena.alloc(hir::Expr::Const { value }),
             );
+            return (enum_res, &[]);
         }
         ValueID::Const(id) => TypeResult::new(
             hir.registry().const_data(id).ty,
@@ -1580,64 +3700,97 @@ fn typecheck_item<'hir>(
             hir.registry().global_data(id).ty,
             emit.arena.alloc(hir::Expr::GlobalVar { global_id: id }),
         ),
-        ValueID::Local(id) => TypeResult::new(
-            proc.get_local(id).ty, //@type of local var might not be known
-            emit.arena.alloc(hir::Expr::LocalVar { local_id: id }),
-        ),
-        ValueID::Param(id) => TypeResult::new(
-            proc.get_param(id).ty,
-            emit.arena.alloc(hir::Expr::ParamVar { param_id: id }),
-        ),
+        ValueID::Local(id) => {
+            proc.mark_used(VariableID::Local(id));
+            // expectation applies to the local itself, not to its fields
+            if field_names.is_empty() {
+                infer_literal_local(hir, emit, proc, expect, id);
+            } else {
+                infer_literal_local(hir, emit, proc, Expectation::None, id);
+            }
+            TypeResult::new(
+                proc.get_local(id).ty,
+                emit.arena.alloc(hir::Expr::LocalVar { local_id: id }),
+            )
+        }
+        ValueID::Param(id) => {
+            proc.mark_used(VariableID::Param(id));
+            TypeResult::new(
+                proc.get_param(id).ty,
+                emit.arena.alloc(hir::Expr::ParamVar { param_id: id }),
+            )
+        }
     };
 
-    //@everything below is copy-paste from regular typecheck field access 16.05.24
-    // de-duplicate later
-    let mut target = item_res.expr;
-    let mut target_ty = item_res.ty;
-
-    for &name in field_names {
-        let (field_ty, kind, deref) = check_type_field(hir, emit, proc, target_ty, name);
-
-        match kind {
-            FieldKind::Error => return TypeResult::new(hir::Type::Error, target),
-            FieldKind::Field(struct_id, field_id) => {
-                //@duplicated field vis check, other one is in typecheck_struct_init
-                let data = hir.registry().struct_data(struct_id);
-                let field = data.field(field_id);
+    (item_res, field_names)
+}
 
-                if proc.origin() != data.origin_id {
-                    if field.vis == ast::Vis::Private {
-                        emit.error(ErrorComp::new(
-                            format!("field `{}` is private", hir.name_str(field.name.id),),
-                            SourceRange::new(proc.origin(), name.range),
-                            Info::new(
-                                "defined here",
-                                SourceRange::new(data.origin_id, field.name.range),
-                            ),
-                        ));
-                    }
-                }
+/// decide the type of a local initialized by an unsuffixed literal on its first use,
+/// expected type is used if the literal fits into it, otherwise default type is kept
+fn infer_literal_local<'hir>(
+    hir: &HirData<'hir, '_, '_>,
+    emit: &mut HirEmit<'hir>,
+    proc: &mut ProcScope<'hir, '_>,
+    expect: Expectation<'hir>,
+    local_id: hir::LocalID,
+) {
+    let literal = match proc.take_local_literal(local_id) {
+        Some(literal) => literal,
+        None => return,
+    };
+    let expect_basic = match expect {
+        Expectation::HasType(hir::Type::Basic(basic), _) => Some(basic),
+        _ => None,
+    };
 
-                target_ty = field_ty;
-                target = emit.arena.alloc(hir::Expr::StructField {
-                    target,
-                    struct_id,
-                    field_id,
-                    deref,
-                });
-            }
-            FieldKind::Slice { first_ptr } => {
-                target_ty = field_ty;
-                target = emit.arena.alloc(hir::Expr::SliceField {
-                    target,
-                    first_ptr,
-                    deref,
-                });
-            }
+    let decided = match (literal, expect_basic) {
+        (LiteralValue::Int { val, neg }, Some(basic)) if coerce_int_type(expect) == basic => {
+            let (min, max) = super::pass_4::int_range(hir.target(), basic);
+            let int = if neg { -(val as i128) } else { val as i128 };
+            let value = hir::ConstValue::Int {
+                val,
+                neg,
+                ty: basic,
+            };
+            (int >= min && int <= max).then_some((value, basic))
         }
+        (LiteralValue::Float { val }, Some(basic)) if coerce_float_type(expect) == basic => {
+            let value = hir::ConstValue::Float {
+                val,
+                ty: Some(basic),
+            };
+            Some((value, basic))
+        }
+        _ => None,
+    };
+
+    let local = proc.get_local(local_id);
+    if let Some((value, basic)) = decided {
+        let value = emit.arena.alloc(hir::Expr::Const { value });
+        let local = emit.arena.alloc(hir::Local {
+            mutt: local.mutt,
+            name: local.name,
+            ty: hir::Type::Basic(basic),
+            value: Some(value),
+        });
+        proc.replace_local(local_id, local);
     }
+    let local = proc.get_local(local_id);
+    record_type_hint(hir, emit, proc.origin(), local.name.range.end(), local.ty);
+}
 
-    TypeResult::new(target_ty, target)
+fn typecheck_item_fields<'hir>(
+    hir: &HirData<'hir, '_, '_>,
+    emit: &mut HirEmit<'hir>,
+    proc: &mut ProcScope<'hir, '_>,
+    item_res: TypeResult<'hir>,
+    field_names: &[ast::Name],
+) -> TypeResult<'hir> {
+    let mut target_res = item_res;
+    for &name in field_names {
+        target_res = typecheck_field_access(hir, emit, proc, target_res, name);
+    }
+    target_res
 }
 
 fn typecheck_variant<'hir>(
@@ -1661,6 +3814,7 @@ fn typecheck_variant<'hir>(
         Some(enum_id) => enum_id,
         None => {
             emit.error(ErrorComp::new(
+                ErrorCode::CannotInfer,
                 format!(
                     "cannot infer enum type of variant `{}`",
                     hir.name_str(name.id)
@@ -1674,17 +3828,13 @@ fn typecheck_variant<'hir>(
 
     let data = hir.registry().enum_data(enum_id);
     let variant_id = match data.find_variant(name.id) {
-        Some((variant_id, _)) => variant_id,
+        Some((variant_id, _)) => {
+            let kind = NameRefKind::Variant(enum_id, variant_id);
+            emit.names.add(proc.origin(), name.range, kind);
+            variant_id
+        }
         None => {
-            //@duplicate error, same as path resolve 1.07.24
-            emit.error(ErrorComp::new(
-                format!("enum variant `{}` is not found", hir.name_str(name.id)),
-                SourceRange::new(proc.origin(), name.range),
-                Info::new(
-                    "enum defined here",
-                    SourceRange::new(data.origin_id, data.name.range),
-                ),
-            ));
+            error_unknown_variant(hir, emit, proc.origin(), name, enum_id);
             return TypeResult::new(hir::Type::Error, hir_build::EXPR_ERROR);
         }
     };
@@ -1698,8 +3848,22 @@ fn typecheck_variant<'hir>(
     TypeResult::new(hir::Type::Enum(enum_id), expr)
 }
 
+pub fn error_wildcard_expr(emit: &mut HirEmit, src: SourceRange) {
+    emit.error(ErrorComp::new(
+        ErrorCode::InvalidPattern,
+        "`_` can only be used as match fallback or variant field pattern",
+        src,
+        None,
+    ))
+}
+
 pub fn error_cannot_infer_struct_type(emit: &mut HirEmit, src: SourceRange) {
-    emit.error(ErrorComp::new("cannot infer struct type", src, None))
+    emit.error(ErrorComp::new(
+        ErrorCode::CannotInfer,
+        "cannot infer struct type",
+        src,
+        None,
+    ))
 }
 
 //@support struct type inference
@@ -1759,12 +3923,15 @@ fn typecheck_struct_init<'hir>(
 
     for input in struct_init.input {
         if let Some((field_id, field)) = data.find_field(input.name.id) {
+            let kind = NameRefKind::Field(struct_id, field_id);
+            emit.names.add(proc.origin(), input.name.range, kind);
             //@get expect source?
             let expect = Expectation::HasType(field.ty, None);
             let input_res = typecheck_expr(hir, emit, proc, expect, input.expr);
 
             if let FieldStatus::Init(range) = field_status[field_id.index()] {
                 emit.error(ErrorComp::new(
+                    ErrorCode::FieldAlreadyInitialized,
                     format!(
                         "field `{}` was already initialized",
                         hir.name_str(input.name.id),
@@ -1773,18 +3940,7 @@ fn typecheck_struct_init<'hir>(
                     Info::new("initialized here", SourceRange::new(data.origin_id, range)),
                 ));
             } else {
-                if proc.origin() != data.origin_id {
-                    if field.vis == ast::Vis::Private {
-                        emit.error(ErrorComp::new(
-                            format!("field `{}` is private", hir.name_str(field.name.id),),
-                            SourceRange::new(proc.origin(), input.name.range),
-                            Info::new(
-                                "defined here",
-                                SourceRange::new(data.origin_id, field.name.range),
-                            ),
-                        ));
-                    }
-                }
+                check_field_vis(hir, emit, proc, struct_id, field_id, input.name.range);
 
                 let field_init = hir::StructFieldInit {
                     field_id,
@@ -1796,6 +3952,7 @@ fn typecheck_struct_init<'hir>(
             }
         } else {
             emit.error(ErrorComp::new(
+                ErrorCode::UnknownField,
                 format!(
                     "field `{}` is not found in `{}`",
                     hir.name_str(input.name.id),
@@ -1812,30 +3969,58 @@ fn typecheck_struct_init<'hir>(
     }
 
     if init_count < field_count {
-        //@change message to list limited number of fields based on their name len()
-        let mut message = "missing field initializers: ".to_string();
+        let mut missing = Vec::new();
 
         for (idx, status) in field_status.iter().enumerate() {
             if let FieldStatus::None = status {
-                let field = data.field(hir::StructFieldID::new(idx));
-                message.push('`');
-                message.push_str(hir.name_str(field.name.id));
-                if idx + 1 != field_count {
-                    message.push_str("`, ");
+                let field_id = hir::StructFieldID::new(idx);
+                let field = data.field(field_id);
+
+                // omitted fields with default values are initialized with them
+                if let Some(eval_id) = field.default {
+                    let expr = match *hir.registry().const_eval(eval_id) {
+                        (hir::ConstEval::ResolvedValue(value_id), _) => {
+                            let value = emit.const_intern.get(value_id);
+                            emit.arena.alloc(hir::Expr::Const { value })
+                        }
+                        _ => hir_build::EXPR_ERROR,
+                    };
+                    field_inits.push(hir::StructFieldInit { field_id, expr });
                 } else {
-                    message.push('`');
+                    missing.push(hir.name_str(field.name.id));
                 }
             }
         }
 
-        emit.error(ErrorComp::new(
-            message,
-            SourceRange::new(proc.origin(), expr_range),
-            Info::new(
-                "struct defined here",
-                SourceRange::new(data.origin_id, data.name.range),
-            ),
-        ));
+        if !missing.is_empty() {
+            //@change message to list limited number of fields based on their name len()
+            let message = format!("missing field initializers: `{}`", missing.join("`, `"));
+            let mut fix_text = missing.join(", ");
+            if !struct_init.input.is_empty() {
+                fix_text.insert_str(0, ", ");
+            }
+
+            // missing fields are added in shorthand form, after the last field or before `}`
+            let fix_offset = match struct_init.input.last() {
+                Some(input) => input.expr.range.end(),
+                None => expr_range.end() - 1.into(),
+            };
+            let edit = DiagnosticEdit::insert(proc.origin(), fix_offset, fix_text);
+            let fix = DiagnosticFix::new("add missing fields", vec![edit]);
+
+            emit.error(
+                ErrorComp::new(
+                    ErrorCode::MissingFields,
+                    message,
+                    SourceRange::new(proc.origin(), expr_range),
+                    Info::new(
+                        "struct defined here",
+                        SourceRange::new(data.origin_id, data.name.range),
+                    ),
+                )
+                .with_fix(fix),
+            );
+        }
     }
 
     let input = emit.arena.alloc_slice(&field_inits);
@@ -1851,64 +4036,57 @@ fn typecheck_array_init<'hir>(
     input: &[&ast::Expr<'_>],
     array_range: TextRange,
 ) -> TypeResult<'hir> {
-    let mut expect_array_ty = None;
-
+    // element type comes from `[N]T` or `[]T` expectation,
+    // otherwise the first element is the expectation for the rest
+    let mut expect_elem_ty = None;
     expect = match expect {
         Expectation::None => Expectation::None,
         Expectation::HasType(expect_ty, expect_src) => match expect_ty {
             hir::Type::ArrayStatic(array) => {
-                expect_array_ty = Some(array);
+                expect_elem_ty = Some(array.elem_ty);
                 Expectation::HasType(array.elem_ty, expect_src)
             }
+            hir::Type::ArraySlice(slice) => {
+                expect_elem_ty = Some(slice.elem_ty);
+                Expectation::HasType(slice.elem_ty, expect_src)
+            }
             _ => Expectation::None,
         },
     };
 
-    //@fix elem_ty inference same as `if`, `match`
-    let mut elem_ty = hir::Type::Error;
-
+    let mut elem_ty = expect_elem_ty;
     let input = {
         let mut input_res = Vec::with_capacity(input.len());
         for &expr in input.iter() {
             let expr_res = typecheck_expr(hir, emit, proc, expect, expr);
             input_res.push(expr_res.expr);
 
-            //@temp old version
-            if elem_ty.is_error() {
-                elem_ty = expr_res.ty;
-            }
-            //@restore expect update
-            /*
-            if expect.ty.is_error() {
-                expect = TypeExpectation::new(
-                    expr_res.ty,
-                    Some(SourceRange::new(proc.origin(), expr.range)),
-                )
+            if elem_ty.is_none() && !expr_res.ty.is_error() {
+                elem_ty = Some(expr_res.ty);
+                let expect_src = SourceRange::new(proc.origin(), expr.range);
+                expect = Expectation::HasType(expr_res.ty, Some(expect_src));
             }
-            */
         }
         emit.arena.alloc_slice(&input_res)
     };
 
-    let elem_ty = if input.is_empty() {
-        if let Some(array_ty) = expect_array_ty {
-            array_ty.elem_ty
-        } else {
-            emit.error(ErrorComp::new(
-                "cannot infer type of empty array",
+    let elem_ty = match elem_ty {
+        Some(elem_ty) => elem_ty,
+        None if input.is_empty() => {
+            emit.error(ErrorComp::new_detailed(
+                ErrorCode::CannotInfer,
+                "cannot infer element type of empty array",
+                "add a type annotation, for example `[0]s32`",
                 SourceRange::new(proc.origin(), array_range),
                 None,
             ));
             hir::Type::Error
         }
-    } else {
-        elem_ty
+        None => hir::Type::Error,
     };
 
-    let array_type: &hir::ArrayStatic = emit.arena.alloc(hir::ArrayStatic {
-        len: hir::ArrayStaticLen::Immediate(Some(input.len() as u64)),
-        elem_ty,
-    });
+    let len = array_static_len(emit, input.len() as u64);
+    let array_type: &hir::ArrayStatic = emit.arena.alloc(hir::ArrayStatic { len, elem_ty });
     let array_init = emit.arena.alloc(hir::ArrayInit { elem_ty, input });
     let array_expr = emit.arena.alloc(hir::Expr::ArrayInit { array_init });
     TypeResult::new(hir::Type::ArrayStatic(array_type), array_expr)
@@ -1932,28 +4110,13 @@ fn typecheck_array_repeat<'hir>(
 
     let expr_res = typecheck_expr(hir, emit, proc, expect, expr);
 
-    //@this is duplicated here and in pass_3::type_resolve 09.05.24
-    let value = super::pass_4::resolve_const_expr(
-        hir,
-        emit,
-        proc.origin(),
-        Expectation::HasType(hir::Type::USIZE, None),
-        len,
-    );
-    let len = match value {
-        hir::ConstValue::Int { val, ty, neg } => {
-            if neg {
-                None
-            } else {
-                Some(val)
-            }
-        }
-        _ => None,
-    };
+    let len = super::pass_3::resolve_array_len(hir, emit, proc.origin(), len);
 
-    if let Some(len) = len {
+    // length is known at this point, type and node both carry the evaluated value
+    if let hir::ConstValue::Int { val: len, .. } = len {
+        let array_len = array_static_len(emit, len);
         let array_type = emit.arena.alloc(hir::ArrayStatic {
-            len: hir::ArrayStaticLen::Immediate(Some(len)), //@move to always specified size? else error 09.06.24
+            len: array_len,
             elem_ty: expr_res.ty,
         });
         let array_repeat = emit.arena.alloc(hir::ArrayRepeat {
@@ -1983,6 +4146,7 @@ fn typecheck_deref<'hir>(
         hir::Type::Reference(ref_ty, _) => *ref_ty,
         _ => {
             emit.error(ErrorComp::new(
+                ErrorCode::CannotDereference,
                 format!(
                     "cannot dereference value of type `{}`",
                     type_format(hir, emit, rhs_res.ty)
@@ -2009,124 +4173,119 @@ fn typecheck_address<'hir>(
     rhs: &ast::Expr,
 ) -> TypeResult<'hir> {
     let rhs_res = typecheck_expr(hir, emit, proc, Expectation::None, rhs);
-    let adressability = get_expr_addressability(hir, proc, rhs_res.expr);
-
-    match adressability {
-        Addressability::Unknown => {} //@ & to error should be also Error? 16.05.24
-        Addressability::Constant => {
-            emit.error(ErrorComp::new(
-                "cannot get reference to a constant, you can use `global` instead",
-                SourceRange::new(proc.origin(), rhs.range),
-                None,
-            ));
-        }
-        Addressability::SliceField => {
-            emit.error(ErrorComp::new(
-                "cannot get reference to a slice field, slice itself cannot be modified",
-                SourceRange::new(proc.origin(), rhs.range),
-                None,
-            ));
-        }
-        Addressability::Temporary => {
-            emit.error(ErrorComp::new(
-                "cannot get reference to a temporary value",
-                SourceRange::new(proc.origin(), rhs.range),
-                None,
-            ));
-        }
-        Addressability::TemporaryImmutable => {
-            if mutt == ast::Mut::Mutable {
-                emit.error(ErrorComp::new(
-                    "cannot get mutable reference to this temporary value, only immutable `&` is allowed",
-                    SourceRange::new(proc.origin(), rhs.range),
-                    None,
-                ));
-            }
-        }
-        Addressability::Addressable(rhs_mutt, src) => {
-            if mutt == ast::Mut::Mutable && rhs_mutt == ast::Mut::Immutable {
-                emit.error(ErrorComp::new(
-                    "cannot get mutable reference to an immutable variable",
-                    SourceRange::new(proc.origin(), rhs.range),
-                    Info::new("variable defined here", src),
-                ));
-            }
-        }
-        Addressability::NotImplemented => {
-            emit.error(ErrorComp::new(
-                "addressability not implemented for this expression",
-                SourceRange::new(proc.origin(), rhs.range),
-                None,
-            ));
-        }
-    }
+    place::check_place(
+        hir,
+        emit,
+        proc,
+        PlaceUse::Reference(mutt),
+        rhs_res.expr,
+        rhs.range,
+    );
 
     let ref_ty = hir::Type::Reference(emit.arena.alloc(rhs_res.ty), mutt);
     let address_expr = hir::Expr::Address { rhs: rhs_res.expr };
     TypeResult::new(ref_ty, emit.arena.alloc(address_expr))
 }
 
-enum Addressability {
-    Unknown,
-    Constant,
-    SliceField,
-    Temporary,
-    TemporaryImmutable,
-    Addressable(ast::Mut, SourceRange),
-    NotImplemented, //@temporary non crashing error 05.05.24
+#[derive(Copy, Clone)]
+enum ReferenceEscape {
+    Return,
+    BlockTail,
+    Global,
+    Variable(u32),
+    Outliving,
 }
 
-fn get_expr_addressability<'hir>(
-    hir: &HirData<'hir, '_, '_>,
+/// variable which memory is referenced by the value of the expression
+//@references returned from calls or stored through local references are not tracked 16.10.26
+fn get_expr_variable_reference<'hir>(
     proc: &ProcScope<'hir, '_>,
     expr: &'hir hir::Expr<'hir>,
-) -> Addressability {
+) -> Option<VariableID> {
     match *expr {
-        hir::Expr::Error => Addressability::Unknown,
-        hir::Expr::Const { .. } => Addressability::Temporary, //@TemporaryImmutable for struct / array? and alloca them
-        hir::Expr::If { .. } => Addressability::Temporary,
-        hir::Expr::Block { .. } => Addressability::Temporary,
-        hir::Expr::Match { .. } => Addressability::Temporary,
-        hir::Expr::StructField { target, .. } => get_expr_addressability(hir, proc, target),
-        hir::Expr::SliceField { .. } => Addressability::SliceField,
-        hir::Expr::Index { target, .. } => get_expr_addressability(hir, proc, target),
-        hir::Expr::Slice { target, .. } => get_expr_addressability(hir, proc, target),
-        hir::Expr::Cast { .. } => Addressability::Temporary,
-        hir::Expr::LocalVar { local_id } => {
-            let local = proc.get_local(local_id);
-            Addressability::Addressable(
-                local.mutt,
-                SourceRange::new(proc.origin(), local.name.range),
-            )
+        hir::Expr::Address { rhs } => match place::place_base(rhs) {
+            PlaceBase::Variable(var_id) => Some(var_id),
+            _ => None,
+        },
+        hir::Expr::Slice { target, access } => match access.kind {
+            hir::SliceKind::Array { .. } if !access.deref => match place::place_base(target) {
+                PlaceBase::Variable(var_id) => Some(var_id),
+                _ => None,
+            },
+            _ => None,
+        },
+        hir::Expr::Cast { target, .. } => get_expr_variable_reference(proc, target),
+        hir::Expr::LocalVar { local_id } => proc.local_reference(local_id),
+        hir::Expr::StructInit { input, .. } => input
+            .iter()
+            .find_map(|field| get_expr_variable_reference(proc, field.expr)),
+        hir::Expr::VariantInit { variant_init } => variant_init
+            .input
+            .iter()
+            .find_map(|&expr| get_expr_variable_reference(proc, expr)),
+        hir::Expr::ArrayInit { array_init } => array_init
+            .input
+            .iter()
+            .find_map(|&expr| get_expr_variable_reference(proc, expr)),
+        _ => None,
+    }
+}
+
+fn check_reference_escape<'hir>(
+    hir: &HirData<'hir, '_, '_>,
+    emit: &mut HirEmit<'hir>,
+    proc: &ProcScope<'hir, '_>,
+    expr: &'hir hir::Expr<'hir>,
+    range: TextRange,
+    escape: ReferenceEscape,
+) {
+    let var_id = match get_expr_variable_reference(proc, expr) {
+        Some(var_id) => var_id,
+        None => return,
+    };
+    let var_depth = proc.variable_depth(var_id);
+    let (var_kind, name) = match var_id {
+        VariableID::Local(id) => ("local variable", proc.get_local(id).name),
+        VariableID::Param(id) => ("parameter", proc.get_param(id).name),
+    };
+    let name_str = hir.name_str(name.id);
+
+    let msg = match escape {
+        ReferenceEscape::Return => {
+            format!("cannot return a reference to {var_kind} `{name_str}`")
         }
-        hir::Expr::ParamVar { param_id } => {
-            let param = proc.get_param(param_id);
-            Addressability::Addressable(
-                param.mutt,
-                SourceRange::new(proc.origin(), param.name.range),
-            )
+        ReferenceEscape::BlockTail => {
+            if var_depth < proc.block_depth() {
+                return;
+            }
+            format!("reference to {var_kind} `{name_str}` escapes the block it was defined in")
         }
-        hir::Expr::ConstVar { .. } => Addressability::Constant,
-        hir::Expr::GlobalVar { global_id } => {
-            let data = hir.registry().global_data(global_id);
-            Addressability::Addressable(
-                data.mutt,
-                SourceRange::new(data.origin_id, data.name.range),
+        ReferenceEscape::Global => {
+            format!("cannot store a reference to {var_kind} `{name_str}` in a global")
+        }
+        ReferenceEscape::Outliving => {
+            format!("reference to {var_kind} `{name_str}` is stored behind a reference that outlives it")
+        }
+        ReferenceEscape::Variable(depth) => {
+            if depth >= var_depth {
+                return;
+            }
+            // deferred assignment runs as the scope of the variable is popped
+            let stored = match proc.defer_status() {
+                DeferStatus::None => "stored",
+                DeferStatus::Inside(_) => "stored by `defer`",
+            };
+            format!(
+                "reference to {var_kind} `{name_str}` is {stored} in a variable that outlives it"
             )
         }
-        hir::Expr::CallDirect { .. } => Addressability::Temporary,
-        hir::Expr::CallIndirect { .. } => Addressability::Temporary,
-        hir::Expr::StructInit { .. } => Addressability::TemporaryImmutable,
-        hir::Expr::ArrayInit { .. } => Addressability::TemporaryImmutable,
-        hir::Expr::ArrayRepeat { .. } => Addressability::TemporaryImmutable,
-        hir::Expr::Deref { rhs, .. } => get_expr_addressability(hir, proc, rhs),
-        hir::Expr::Address { .. } => Addressability::Temporary,
-        hir::Expr::Unary { op, rhs } => Addressability::Temporary,
-        hir::Expr::Binary { op, .. } => match op {
-            ast::BinOp::Range | ast::BinOp::RangeInc => Addressability::TemporaryImmutable,
-            _ => Addressability::Temporary,
-        },
-    }
+    };
+    emit.error(ErrorComp::new(
+        ErrorCode::DanglingReference,
+        msg,
+        SourceRange::new(proc.origin(), range),
+        Info::new("variable defined here", SourceRange::new(proc.origin(), name.range)),
+    ));
 }
 
 fn typecheck_unary<'hir>(
@@ -2138,6 +4297,12 @@ fn typecheck_unary<'hir>(
     op_range: TextRange,
     rhs: &ast::Expr,
 ) -> TypeResult<'hir> {
+    // negative int literals are folded, to allow the minimum value of signed types
+    if let (ast::UnOp::Neg, ast::ExprKind::LitInt { val, ty }) = (op, rhs.kind) {
+        let range = TextRange::new(op_range.start(), rhs.range.end());
+        return typecheck_lit_int(hir, emit, proc, expect, val, true, ty, range);
+    }
+
     let rhs_expect = match op {
         ast::UnOp::Neg => expect,
         ast::UnOp::BitNot => expect,
@@ -2183,7 +4348,6 @@ fn typecheck_binary<'hir>(
         | ast::BinOp::Greater
         | ast::BinOp::GreaterEq => Expectation::None,
         ast::BinOp::LogicAnd | ast::BinOp::LogicOr => Expectation::HasType(hir::Type::BOOL, None),
-        ast::BinOp::Range | ast::BinOp::RangeInc => Expectation::HasType(hir::Type::USIZE, None),
         _ => expect,
     };
     let lhs_res = typecheck_expr(hir, emit, proc, lhs_expect, bin.lhs);
@@ -2192,7 +4356,6 @@ fn typecheck_binary<'hir>(
 
     let rhs_expect = match op {
         ast::BinOp::LogicAnd | ast::BinOp::LogicOr => Expectation::HasType(hir::Type::BOOL, None),
-        ast::BinOp::Range | ast::BinOp::RangeInc => Expectation::HasType(hir::Type::USIZE, None),
         _ => {
             let rhs_expect_src = SourceRange::new(proc.origin(), bin.lhs.range);
             Expectation::HasType(lhs_res.ty, Some(rhs_expect_src))
@@ -2210,9 +4373,6 @@ fn typecheck_binary<'hir>(
             | ast::BinOp::GreaterEq
             | ast::BinOp::LogicAnd
             | ast::BinOp::LogicOr => hir::Type::BOOL,
-            ast::BinOp::Range | ast::BinOp::RangeInc => {
-                panic!("pass5 bin_op range doesnt produce Range struct type yet")
-            }
             _ => lhs_res.ty,
         }
     } else {
@@ -2232,6 +4392,34 @@ fn typecheck_binary<'hir>(
     TypeResult::new(binary_ty, emit.arena.alloc(binary_expr))
 }
 
+/// ranges have no value type yet, they are only valid as slice `[]` operands
+fn typecheck_range<'hir>(
+    hir: &HirData<'hir, '_, '_>,
+    emit: &mut HirEmit<'hir>,
+    proc: &mut ProcScope<'hir, '_>,
+    range: &ast::RangeExpr<'_>,
+    expr_range: TextRange,
+) -> TypeResult<'hir> {
+    let expect_usize = Expectation::HasType(hir::Type::USIZE, None);
+    if let Some(lower) = range.lower {
+        let _ = typecheck_expr(hir, emit, proc, expect_usize, lower);
+    }
+    match range.upper {
+        ast::RangeEnd::Unbounded => {}
+        ast::RangeEnd::Exclusive(upper) | ast::RangeEnd::Inclusive(upper) => {
+            let _ = typecheck_expr(hir, emit, proc, expect_usize, upper);
+        }
+    }
+
+    emit.error(ErrorComp::new(
+        ErrorCode::InvalidOperator,
+        "range expression can only be used in slice expressions",
+        SourceRange::new(proc.origin(), expr_range),
+        None,
+    ));
+    TypeResult::new(hir::Type::Error, hir_build::EXPR_ERROR)
+}
+
 fn check_match_compatibility<'hir>(
     hir: &HirData<'hir, '_, '_>,
     emit: &mut HirEmit<'hir>,
@@ -2254,6 +4442,7 @@ fn check_match_compatibility<'hir>(
 
     if !compatible {
         emit.error(ErrorComp::new(
+            ErrorCode::InvalidMatchType,
             format!(
                 "cannot match on value of type `{}`",
                 type_format(hir, emit, ty)
@@ -2293,6 +4482,7 @@ fn check_un_op_compatibility<'hir>(
 
     if !compatible {
         emit.error(ErrorComp::new(
+            ErrorCode::InvalidOperator,
             format!(
                 "cannot apply unary operator `{}` on value of type `{}`",
                 op.as_str(),
@@ -2333,25 +4523,28 @@ fn check_bin_op_compatibility<'hir>(
         },
         ast::BinOp::IsEq | ast::BinOp::NotEq => match lhs_ty {
             hir::Type::Basic(basic) => BasicTypeKind::new(basic).is_any_value_type(),
-            hir::Type::Enum(_) => true,
+            hir::Type::Enum(id) => !hir.registry().enum_data(id).has_fields(),
+            hir::Type::Procedure(_) => true,
             _ => false,
         },
+        // `char` values are ordered by their unicode scalar value
         ast::BinOp::Less | ast::BinOp::LessEq | ast::BinOp::Greater | ast::BinOp::GreaterEq => {
             match lhs_ty {
-                hir::Type::Basic(basic) => BasicTypeKind::new(basic).is_number(),
+                hir::Type::Basic(basic) => {
+                    let kind = BasicTypeKind::new(basic);
+                    kind.is_number() || matches!(kind, BasicTypeKind::Char)
+                }
                 _ => false,
             }
         }
         ast::BinOp::LogicAnd | ast::BinOp::LogicOr => {
             matches!(lhs_ty, hir::Type::Basic(BasicType::Bool))
         }
-        ast::BinOp::Range | ast::BinOp::RangeInc => {
-            matches!(lhs_ty, hir::Type::Basic(BasicType::Usize))
-        }
     };
 
     if !compatible {
         emit.error(ErrorComp::new(
+            ErrorCode::InvalidOperator,
             format!(
                 "cannot apply binary operator `{}` on value of type `{}`",
                 op.as_str(),
@@ -2383,6 +4576,9 @@ fn typecheck_block<'hir>(
             ast::StmtKind::Break => {
                 if let Some(stmt_res) = typecheck_break(hir, emit, proc, stmt.range) {
                     let diverges = proc.check_stmt_diverges(hir, emit, true, stmt.range);
+                    if !diverges {
+                        block_stmts.extend(proc.loop_defers().map(hir::Stmt::Defer));
+                    }
                     (stmt_res, diverges)
                 } else {
                     continue;
@@ -2391,6 +4587,9 @@ fn typecheck_block<'hir>(
             ast::StmtKind::Continue => {
                 if let Some(stmt_res) = typecheck_continue(hir, emit, proc, stmt.range) {
                     let diverges = proc.check_stmt_diverges(hir, emit, true, stmt.range);
+                    if !diverges {
+                        block_stmts.extend(proc.loop_defers().map(hir::Stmt::Defer));
+                    }
                     (stmt_res, diverges)
                 } else {
                     continue;
@@ -2399,6 +4598,9 @@ fn typecheck_block<'hir>(
             ast::StmtKind::Return(expr) => {
                 if let Some(stmt_res) = typecheck_return(hir, emit, proc, stmt.range, expr) {
                     let diverges = proc.check_stmt_diverges(hir, emit, true, stmt.range);
+                    if !diverges {
+                        block_stmts.extend(proc.all_defers().map(hir::Stmt::Defer));
+                    }
                     (stmt_res, diverges)
                 } else {
                     continue;
@@ -2408,8 +4610,10 @@ fn typecheck_block<'hir>(
                 //@defer can behave strangely with diverges checks since it inherits diverges 29.05.24
                 // from currently top block, while defer itself can be triggered multiple times in different locations
                 let diverges = proc.check_stmt_diverges(hir, emit, false, stmt.range);
-                let stmt_res = typecheck_defer(hir, emit, proc, stmt.range.start(), *block);
-                (stmt_res, diverges)
+                if !diverges {
+                    typecheck_defer(hir, emit, proc, stmt.range.start(), *block);
+                }
+                continue;
             }
             ast::StmtKind::Loop(loop_) => {
                 let diverges = proc.check_stmt_diverges(hir, emit, false, stmt.range);
@@ -2461,6 +4665,13 @@ fn typecheck_block<'hir>(
                 // type expectation is delegated to tail expression, instead of the block itself
                 let expr_res = typecheck_expr(hir, emit, proc, expect, expr);
                 let stmt_res = hir::Stmt::ExprTail(expr_res.expr);
+                // tail of the proc body block is its return value
+                let escape = if proc.block_depth() == 1 {
+                    ReferenceEscape::Return
+                } else {
+                    ReferenceEscape::BlockTail
+                };
+                check_reference_escape(hir, emit, proc, expr_res.expr, expr.range, escape);
                 // @seems to fix the problem (still a hack)
                 let diverges = proc.check_stmt_diverges(hir, emit, true, stmt.range);
 
@@ -2478,6 +4689,14 @@ fn typecheck_block<'hir>(
         }
     }
 
+    // fallthrough exit, codegen skips defers when block end is not reachable
+    let exits = matches!(
+        block_stmts.last(),
+        Some(hir::Stmt::Break | hir::Stmt::Continue | hir::Stmt::Return(_))
+    );
+    if !exits {
+        block_stmts.extend(proc.block_defers().map(hir::Stmt::Defer));
+    }
     let stmts = emit.arena.alloc_slice(&block_stmts);
     let hir_block = hir::Block { stmts };
 
@@ -2514,6 +4733,7 @@ fn typecheck_break<'hir>(
     match proc.loop_status() {
         LoopStatus::None => {
             emit.error(ErrorComp::new(
+                ErrorCode::ControlFlowOutsideLoop,
                 "cannot use `break` outside of a loop",
                 SourceRange::new(proc.origin(), range),
                 None,
@@ -2522,6 +4742,7 @@ fn typecheck_break<'hir>(
         }
         LoopStatus::Inside_WithDefer => {
             emit.error(ErrorComp::new(
+                ErrorCode::ControlFlowInDefer,
                 "cannot use `break` in a loop that is outside of `defer`",
                 SourceRange::new(proc.origin(), range),
                 None,
@@ -2542,6 +4763,7 @@ fn typecheck_continue<'hir>(
     match proc.loop_status() {
         LoopStatus::None => {
             emit.error(ErrorComp::new(
+                ErrorCode::ControlFlowOutsideLoop,
                 "cannot use `continue` outside of a loop",
                 SourceRange::new(proc.origin(), range),
                 None,
@@ -2550,6 +4772,7 @@ fn typecheck_continue<'hir>(
         }
         LoopStatus::Inside_WithDefer => {
             emit.error(ErrorComp::new(
+                ErrorCode::ControlFlowInDefer,
                 "cannot use `continue` in a loop thats started outside of `defer`",
                 SourceRange::new(proc.origin(), range),
                 None,
@@ -2572,6 +4795,8 @@ fn typecheck_return<'hir>(
         DeferStatus::None => {
             if let Some(expr) = expr {
                 let expr_res = typecheck_expr(hir, emit, proc, proc.return_expect(), expr);
+                let escape = ReferenceEscape::Return;
+                check_reference_escape(hir, emit, proc, expr_res.expr, expr.range, escape);
                 Some(hir::Stmt::Return(Some(expr_res.expr)))
             } else {
                 check_type_expectation(
@@ -2588,6 +4813,7 @@ fn typecheck_return<'hir>(
         DeferStatus::Inside(prev_defer) => {
             //@still check whats being returned? for coverage 29.05.24
             emit.error(ErrorComp::new(
+                ErrorCode::ControlFlowInDefer,
                 "cannot use `return` inside `defer`",
                 SourceRange::new(proc.origin(), range),
                 Info::new("in this defer", SourceRange::new(proc.origin(), prev_defer)),
@@ -2597,21 +4823,23 @@ fn typecheck_return<'hir>(
     }
 }
 
-//@allow break and continue from loops that originated within defer itself
-// this can probably be done via resetting the in_loop when entering defer block
+/// deferred block is registered in the current block scope, it gets
+/// lowered into every exit of that scope: fallthrough, `break`, `continue` and `return`
+/// `break` and `continue` are allowed in loops that started inside of `defer`
 fn typecheck_defer<'hir>(
     hir: &HirData<'hir, '_, '_>,
     emit: &mut HirEmit<'hir>,
     proc: &mut ProcScope<'hir, '_>,
     start: TextOffset,
     block: ast::Block<'_>,
-) -> hir::Stmt<'hir> {
+) {
     let defer_range = TextRange::new(start, start + 5.into());
 
     match proc.defer_status() {
         DeferStatus::None => {}
         DeferStatus::Inside(prev_defer) => {
             emit.error(ErrorComp::new(
+                ErrorCode::ControlFlowInDefer,
                 "`defer` statements cannot be nested",
                 SourceRange::new(proc.origin(), defer_range),
                 Info::new(
@@ -2632,7 +4860,7 @@ fn typecheck_defer<'hir>(
     );
 
     let block = emit.arena.alloc(block_res.block);
-    hir::Stmt::Defer(block)
+    proc.push_defer(block);
 }
 
 fn typecheck_loop<'hir>(
@@ -2641,6 +4869,12 @@ fn typecheck_loop<'hir>(
     proc: &mut ProcScope<'hir, '_>,
     loop_: &ast::Loop<'_>,
 ) -> &'hir hir::Loop<'hir> {
+    // for loop local is only in scope of the loop itself
+    let local_scope = matches!(loop_.kind, ast::LoopKind::ForLoop { .. });
+    if local_scope {
+        proc.push_block(BlockEnter::None);
+    }
+
     let kind = match loop_.kind {
         ast::LoopKind::Loop => hir::LoopKind::Loop,
         ast::LoopKind::While { cond } => {
@@ -2685,6 +4919,9 @@ fn typecheck_loop<'hir>(
         loop_.block,
         BlockEnter::Loop,
     );
+    if local_scope {
+        proc.pop_block();
+    }
 
     emit.arena.alloc(hir::Loop {
         kind,
@@ -2698,16 +4935,8 @@ fn typecheck_local<'hir>(
     proc: &mut ProcScope<'hir, '_>,
     local: &ast::Local,
 ) -> hir::LocalID {
-    //@theres no `nice` way to find both existing name from global (hir) scope
-    // and proc_scope, those are so far disconnected,
-    // some unified model of symbols might be better in the future
-    // this also applies to SymbolKind which is separate from VariableID (leads to some issues in path resolve) @1.04.24
-    let already_defined = if let Some(existing) =
-        hir.symbol_in_scope_source(proc.origin(), local.name.id)
-    {
-        super::pass_1::error_name_already_defined(hir, emit, proc.origin(), local.name, existing);
-        true
-    } else if let Some(existing_var) = proc.find_variable(local.name.id) {
+    // locals shadow module items and variables of enclosing blocks
+    let already_defined = if let Some(existing_var) = proc.find_variable_in_block(local.name.id) {
         let existing = match existing_var {
             VariableID::Local(id) => SourceRange::new(proc.origin(), proc.get_local(id).name.range),
             VariableID::Param(id) => SourceRange::new(proc.origin(), proc.get_param(id).name.range),
@@ -2718,6 +4947,7 @@ fn typecheck_local<'hir>(
         false
     };
 
+    let mut literal = None;
     let (local_ty, local_value) = match local.kind {
         ast::LocalKind::Decl(ast_ty) => {
             let hir_ty = super::pass_3::type_resolve(hir, emit, proc.origin(), ast_ty);
@@ -2731,15 +4961,35 @@ fn typecheck_local<'hir>(
             } else {
                 Expectation::None
             };
+            if ast_ty.is_none() {
+                literal = literal_value(value);
+            }
 
             let value_res = typecheck_expr(hir, emit, proc, expect, value);
 
+            // literal type is known, suggest changing the annotation instead
+            if let (Some(ast_ty), true) = (ast_ty, value_res.errored) {
+                if is_literal(value) {
+                    let ty_src = SourceRange::new(proc.origin(), ast_ty.range);
+                    let ty_format = type_format(hir, emit, value_res.ty);
+                    let message = format!("change type to `{ty_format}`");
+                    let edit = DiagnosticEdit::replace(ty_src, ty_format.as_str());
+                    let fix = DiagnosticFix::new(message, vec![edit]);
+                    emit.error_fix(SourceRange::new(proc.origin(), value.range), fix);
+                }
+            }
+
             if ast_ty.is_some() {
                 match expect {
                     Expectation::None => unreachable!(),
                     Expectation::HasType(expect_ty, _) => (expect_ty, Some(value_res.expr)),
                 }
             } else {
+                // type hint of literal locals is recorded once their type is decided
+                if literal.is_none() {
+                    let offset = local.name.range.end();
+                    record_type_hint(hir, emit, proc.origin(), offset, value_res.ty);
+                }
                 (value_res.ty, Some(value_res.expr))
             }
         }
@@ -2748,6 +4998,7 @@ fn typecheck_local<'hir>(
     if already_defined {
         hir::LocalID::dummy()
     } else {
+        let mutt_range = local.mutt_range;
         //@check for `never`, `void` to prevent panic during codegen
         let local = emit.arena.alloc(hir::Local {
             mutt: local.mutt,
@@ -2755,10 +5006,49 @@ fn typecheck_local<'hir>(
             ty: local_ty,
             value: local_value,
         });
-        proc.push_local(local)
+        let local_id = proc.push_local(local);
+        proc.set_local_mutt_range(local_id, mutt_range);
+        if let Some(literal) = literal {
+            proc.set_local_literal(local_id, literal);
+        }
+        if let Some(value) = local_value {
+            proc.set_local_reference(local_id, get_expr_variable_reference(proc, value));
+        }
+        local_id
+    }
+}
+
+/// unsuffixed int or float literal, which can take its type from a later use
+fn literal_value(expr: &ast::Expr) -> Option<LiteralValue> {
+    match expr.kind {
+        ast::ExprKind::LitInt { val, ty: None } => Some(LiteralValue::Int { val, neg: false }),
+        ast::ExprKind::LitFloat { val, ty: None } => Some(LiteralValue::Float { val }),
+        ast::ExprKind::Unary {
+            op: ast::UnOp::Neg,
+            rhs,
+            ..
+        } => match rhs.kind {
+            ast::ExprKind::LitInt { val, ty: None } => {
+                Some(LiteralValue::Int { val, neg: val != 0 })
+            }
+            _ => None,
+        },
+        _ => None,
     }
 }
 
+fn is_literal(expr: &ast::Expr) -> bool {
+    matches!(
+        expr.kind,
+        ast::ExprKind::LitNull
+            | ast::ExprKind::LitBool { .. }
+            | ast::ExprKind::LitInt { .. }
+            | ast::ExprKind::LitFloat { .. }
+            | ast::ExprKind::LitChar { .. }
+            | ast::ExprKind::LitString { .. }
+    )
+}
+
 //@not checking bin assignment operators (need a good way to do it same in binary expr typecheck)
 fn typecheck_assign<'hir>(
     hir: &HirData<'hir, '_, '_>,
@@ -2767,54 +5057,47 @@ fn typecheck_assign<'hir>(
     assign: &ast::Assign,
 ) -> &'hir hir::Assign<'hir> {
     let lhs_res = typecheck_expr(hir, emit, proc, Expectation::None, assign.lhs);
-    let adressability = get_expr_addressability(hir, proc, lhs_res.expr);
+    place::check_place(
+        hir,
+        emit,
+        proc,
+        PlaceUse::Assign,
+        lhs_res.expr,
+        assign.lhs.range,
+    );
 
-    match adressability {
-        Addressability::Unknown => {}
-        Addressability::Constant => {
-            emit.error(ErrorComp::new(
-                "cannot assign to a constant",
-                SourceRange::new(proc.origin(), assign.lhs.range),
-                None,
-            ));
-        }
-        Addressability::SliceField => {
-            emit.error(ErrorComp::new(
-                "cannot assign to a slice field, slice itself cannot be modified",
-                SourceRange::new(proc.origin(), assign.lhs.range),
-                None,
-            ));
-        }
-        Addressability::Temporary | Addressability::TemporaryImmutable => {
-            emit.error(ErrorComp::new(
-                "cannot assign to a temporary value",
-                SourceRange::new(proc.origin(), assign.lhs.range),
-                None,
-            ));
-        }
-        Addressability::Addressable(mutt, src) => {
-            if mutt == ast::Mut::Immutable {
-                emit.error(ErrorComp::new(
-                    "cannot assign to an immutable variable",
-                    SourceRange::new(proc.origin(), assign.lhs.range),
-                    Info::new("variable defined here", src),
-                ));
-            }
-        }
-        Addressability::NotImplemented => {
-            emit.error(ErrorComp::new(
-                "addressability not implemented for this expression",
-                SourceRange::new(proc.origin(), assign.lhs.range),
-                None,
-            ));
+    // compound assignment follows the binary operator rules of its lhs type
+    let compatible = match assign.op {
+        ast::AssignOp::Assign => true,
+        ast::AssignOp::Bin(op) => {
+            check_bin_op_compatibility(hir, emit, proc.origin(), lhs_res.ty, op, assign.op_range)
         }
-    }
+    };
 
-    let rhs_expect_src = SourceRange::new(proc.origin(), assign.lhs.range);
-    let rhs_expect = Expectation::HasType(lhs_res.ty, Some(rhs_expect_src));
+    let rhs_expect = if compatible {
+        let rhs_expect_src = SourceRange::new(proc.origin(), assign.lhs.range);
+        Expectation::HasType(lhs_res.ty, Some(rhs_expect_src))
+    } else {
+        Expectation::None
+    };
     let rhs_res = typecheck_expr(hir, emit, proc, rhs_expect, assign.rhs);
 
-    //@binary assignment ops not checked
+    let escape = match place::place_base(lhs_res.expr) {
+        PlaceBase::None => None,
+        PlaceBase::Global => Some(ReferenceEscape::Global),
+        PlaceBase::Variable(var_id) => Some(ReferenceEscape::Variable(proc.variable_depth(var_id))),
+        PlaceBase::Outliving => Some(ReferenceEscape::Outliving),
+    };
+    if let Some(escape) = escape {
+        check_reference_escape(hir, emit, proc, rhs_res.expr, assign.rhs.range, escape);
+    }
+    if let hir::Expr::LocalVar { local_id } = *lhs_res.expr {
+        if matches!(assign.op, ast::AssignOp::Assign) {
+            let reference = get_expr_variable_reference(proc, rhs_res.expr);
+            proc.set_local_reference(local_id, reference);
+        }
+    }
+
     let lhs_signed_int = match lhs_res.ty {
         hir::Type::Basic(basic) => BasicTypeKind::new(basic).is_signed_integer(),
         _ => false,
@@ -2840,6 +5123,7 @@ pub fn require_value_type<'hir>(
 ) {
     if !type_is_value_type(ty) {
         emit.error(ErrorComp::new(
+            ErrorCode::ExpectedValueType,
             format!(
                 "expected value type, found `{}`",
                 type_format(hir, emit, ty)
@@ -2858,6 +5142,7 @@ pub fn type_is_value_type(ty: hir::Type) -> bool {
         hir::Type::Struct(_) => true,
         hir::Type::Reference(ref_ty, _) => type_is_value_type(*ref_ty),
         hir::Type::Procedure(_) => true,
+        hir::Type::PolyProc(_, _) => true,
         hir::Type::ArraySlice(slice) => type_is_value_type(slice.elem_ty),
         hir::Type::ArrayStatic(array) => type_is_value_type(array.elem_ty),
     }
@@ -2893,26 +5178,78 @@ fn path_resolve<'hir>(
 
     if let Some(proc) = proc {
         if let Some(var_id) = proc.find_variable(name.id) {
+            let kind = match var_id {
+                VariableID::Local(id) => NameRefKind::Local {
+                    id,
+                    def: proc.get_local(id).name.range,
+                },
+                VariableID::Param(id) => NameRefKind::Param {
+                    id,
+                    def: proc.get_param(id).name.range,
+                },
+            };
+            emit.names.add(origin_id, name.range, kind);
             return (ResolvedPath::Variable(var_id), 0);
         }
     }
+    // items cannot be named `super` or `package`, reported before lookup at any position
+    let reserved = [intern::NAME_SUPER, intern::NAME_PACKAGE];
+    if let Some(name) = path.names.iter().find(|name| reserved.contains(&name.id)) {
+        emit.error(ErrorComp::new(
+            ErrorCode::ReservedName,
+            format!(
+                "name `{}` is reserved and cannot be used in paths",
+                hir.name_str(name.id)
+            ),
+            SourceRange::new(origin_id, name.range),
+            None,
+        ));
+        return (ResolvedPath::None, 0);
+    }
 
     let (module_id, name) = match hir.symbol_from_scope(origin_id, origin_id, name) {
         Ok((kind, source)) => {
             let next_name = path.names.get(1).cloned();
+            emit.names.add(origin_id, name.range, kind.name_ref_kind());
             match (kind, next_name) {
                 (SymbolKind::Module(module_id), Some(name)) => (module_id, name),
-                _ => return (ResolvedPath::Symbol(kind, source), 0),
+                _ => {
+                    let reference = SourceRange::new(origin_id, name.range);
+                    emit.usage.mark_symbol(kind);
+                    emit.usage.add_reference(kind, reference);
+                    return (ResolvedPath::Symbol(kind, source), 0);
+                }
             }
         }
         Err(error) => {
-            emit.error(error);
+            let exited = proc.and_then(|proc| {
+                let local_id = proc.find_exited_local(name.id)?;
+                Some(proc.get_local(local_id).name.range)
+            });
+            match exited {
+                Some(local_range) => emit.error(ErrorComp::new(
+                    ErrorCode::NameNotFound,
+                    format!("variable `{}` is not in scope", hir.name_str(name.id)),
+                    SourceRange::new(origin_id, name.range),
+                    Info::new(
+                        "defined here, in a block that has ended",
+                        SourceRange::new(origin_id, local_range),
+                    ),
+                )),
+                None => emit.error(error),
+            }
             return (ResolvedPath::None, 0);
         }
     };
 
     match hir.symbol_from_scope(origin_id, module_id, name) {
-        Ok((kind, source)) => (ResolvedPath::Symbol(kind, source), 1),
+        Ok((kind, source)) => {
+            emit.names.add(origin_id, name.range, kind.name_ref_kind());
+            let reference = SourceRange::new(origin_id, name.range);
+            emit.usage.mark_symbol(kind);
+            emit.usage.add_reference(kind, reference);
+            (ResolvedPath::Symbol(kind, source), 1)
+        }
         Err(error) => {
             emit.error(error);
             (ResolvedPath::None, 1)
@@ -2929,6 +5266,18 @@ pub fn path_resolve_type<'hir>(
     origin_id: ModuleID,
     path: &ast::Path,
 ) -> hir::Type<'hir> {
+    if let (Some(poly_scope), [name]) = (emit.poly_scope(), path.names) {
+        if let Some(idx) = poly_scope
+            .poly_params
+            .iter()
+            .position(|it| it.id == name.id)
+        {
+            return match poly_scope.poly_types {
+                Some(poly_types) => poly_types[idx],
+                None => hir::Type::PolyProc(poly_scope.proc_id, idx as u32),
+            };
+        }
+    }
     let (resolved, name_idx) = path_resolve(hir, emit, proc, origin_id, path);
 
     let ty = match resolved {
@@ -2948,6 +5297,7 @@ pub fn path_resolve_type<'hir>(
             //@calling this `local` for both params and locals, validate wording consistency
             // by maybe extracting all error formats to separate module @07.04.24
             emit.error(ErrorComp::new(
+                ErrorCode::ExpectedType,
                 format!("expected type, found local `{}`", hir.name_str(name.id)),
                 SourceRange::new(origin_id, name.range),
                 Info::new("defined here", source),
@@ -2960,6 +5310,7 @@ pub fn path_resolve_type<'hir>(
             _ => {
                 let name = path.names[name_idx];
                 emit.error(ErrorComp::new(
+                    ErrorCode::ExpectedType,
                     format!(
                         "expected type, found {} `{}`",
                         kind.kind_name(),
@@ -2977,6 +5328,7 @@ pub fn path_resolve_type<'hir>(
         if let (Some(first), Some(last)) = (remaining.first(), remaining.last()) {
             let range = TextRange::new(first.range.start(), last.range.end());
             emit.error(ErrorComp::new(
+                ErrorCode::UnexpectedPathSegment,
                 "unexpected path segment",
                 SourceRange::new(origin_id, range),
                 None,
@@ -3015,6 +5367,7 @@ pub fn path_resolve_struct<'hir>(
             //@calling this `local` for both params and locals, validate wording consistency
             // by maybe extracting all error formats to separate module @07.04.24
             emit.error(ErrorComp::new(
+                ErrorCode::ExpectedType,
                 format!(
                     "expected struct type, found local `{}`",
                     hir.name_str(name.id)
@@ -3029,6 +5382,7 @@ pub fn path_resolve_struct<'hir>(
             _ => {
                 let name = path.names[name_idx];
                 emit.error(ErrorComp::new(
+                    ErrorCode::ExpectedType,
                     format!(
                         "expected struct type, found {} `{}`",
                         kind.kind_name(),
@@ -3046,6 +5400,7 @@ pub fn path_resolve_struct<'hir>(
         if let (Some(first), Some(last)) = (remaining.first(), remaining.last()) {
             let range = TextRange::new(first.range.start(), last.range.end());
             emit.error(ErrorComp::new(
+                ErrorCode::UnexpectedPathSegment,
                 "unexpected path segment",
                 SourceRange::new(origin_id, range),
                 None,
@@ -3065,6 +5420,43 @@ pub enum ValueID {
     Param(hir::ProcParamID),
 }
 
+/// shared by value paths and variant patterns,
+/// closest variant name is suggested with a fix that replaces the unknown name
+fn error_unknown_variant<'hir>(
+    hir: &HirData<'hir, '_, '_>,
+    emit: &mut HirEmit<'hir>,
+    origin_id: ModuleID,
+    name: ast::Name,
+    enum_id: hir::EnumID,
+) {
+    let data = hir.registry().enum_data(enum_id);
+    let name_str = hir.name_str(name.id);
+    let variant_names = data.variants.iter().map(|v| hir.name_str(v.name.id));
+    let similar = text::similar_name(name_str, variant_names);
+
+    let message = match similar {
+        Some(similar) => {
+            format!("enum variant `{name_str}` is not found\ndid you mean `{similar}`?")
+        }
+        None => format!("enum variant `{name_str}` is not found"),
+    };
+    let src = SourceRange::new(origin_id, name.range);
+    emit.error(ErrorComp::new(
+        ErrorCode::UnknownVariant,
+        message,
+        src,
+        Info::new(
+            "enum defined here",
+            SourceRange::new(data.origin_id, data.name.range),
+        ),
+    ));
+    if let Some(similar) = similar {
+        let edit = DiagnosticEdit::replace(src, similar);
+        let fix = DiagnosticFix::new(format!("replace with `{similar}`"), vec![edit]);
+        emit.error_fix(src, fix);
+    }
+}
+
 pub fn path_resolve_value<'hir, 'ast>(
     hir: &HirData<'hir, 'ast, '_>,
     emit: &mut HirEmit<'hir>,
@@ -3086,6 +5478,7 @@ pub fn path_resolve_value<'hir, 'ast>(
                     if let (Some(first), Some(last)) = (remaining.first(), remaining.last()) {
                         let range = TextRange::new(first.range.start(), last.range.end());
                         emit.error(ErrorComp::new(
+                            ErrorCode::UnexpectedPathSegment,
                             "unexpected path segment",
                             SourceRange::new(origin_id, range),
                             None,
@@ -3098,11 +5491,14 @@ pub fn path_resolve_value<'hir, 'ast>(
                 if let Some(variant_name) = path.names.get(name_idx + 1) {
                     let enum_data = hir.registry().enum_data(id);
                     if let Some((variant_id, ..)) = enum_data.find_variant(variant_name.id) {
+                        let kind = NameRefKind::Variant(id, variant_id);
+                        emit.names.add(origin_id, variant_name.range, kind);
                         if let Some(remaining) = path.names.get(name_idx + 2..) {
                             if let (Some(first), Some(last)) = (remaining.first(), remaining.last())
                             {
                                 let range = TextRange::new(first.range.start(), last.range.end());
                                 emit.error(ErrorComp::new(
+                                    ErrorCode::UnexpectedPathSegment,
                                     "unexpected path segment",
                                     SourceRange::new(origin_id, range),
                                     None,
@@ -3111,19 +5507,13 @@ pub fn path_resolve_value<'hir, 'ast>(
                         }
                         return (ValueID::Enum(id, variant_id), &[]);
                     } else {
-                        emit.error(ErrorComp::new(
-                            format!(
-                                "enum variant `{}` is not found",
-                                hir.name_str(variant_name.id)
-                            ),
-                            SourceRange::new(origin_id, variant_name.range),
-                            Info::new("enum defined here", source),
-                        ));
+                        error_unknown_variant(hir, emit, origin_id, *variant_name, id);
                         return (ValueID::None, &[]);
                     }
                 } else {
                     let name = path.names[name_idx];
                     emit.error(ErrorComp::new(
+                        ErrorCode::ExpectedValue,
                         format!(
                             "expected value, found {} `{}`",
                             kind.kind_name(),
@@ -3140,6 +5530,7 @@ pub fn path_resolve_value<'hir, 'ast>(
             _ => {
                 let name = path.names[name_idx];
                 emit.error(ErrorComp::new(
+                    ErrorCode::ExpectedValue,
                     format!(
                         "expected value, found {} `{}`",
                         kind.kind_name(),
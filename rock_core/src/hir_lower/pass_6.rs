@@ -1,9 +1,11 @@
 use super::hir_build::{HirData, HirEmit, SymbolKind};
 use crate::ast::BasicType;
-use crate::error::{ErrorComp, SourceRange};
+use crate::error::{ErrorCode, ErrorComp, SourceRange};
 use crate::hir;
+use crate::intern;
 use crate::package::manifest::PackageKind;
 use crate::session::{ModuleOrDirectory, Session};
+use crate::text::TextRange;
 
 pub fn check_entry_point<'hir>(
     hir: &mut HirData<'hir, '_, '_>,
@@ -16,7 +18,7 @@ pub fn check_entry_point<'hir>(
         return;
     }
 
-    let main_id = hir.intern_name().intern("main");
+    let main_id = intern::NAME_MAIN;
     let module_or_directory = root_package.src.find(session, main_id);
     let origin_id = match module_or_directory {
         ModuleOrDirectory::Module(module_id) => module_id,
@@ -61,18 +63,34 @@ pub fn check_main_procedure<'hir>(
     let item = hir.registry().proc_item(proc_id);
     let data = hir.registry().proc_data(proc_id);
 
-    if !data.params.is_empty() {
+    // `external` and `variadic` main are reported by attribute flag checks
+    if let Some(poly_params) = item.poly_params {
+        let range = match (poly_params.first(), poly_params.last()) {
+            (Some(first), Some(last)) => TextRange::new(first.range.start(), last.range.end()),
+            _ => data.name.range,
+        };
+        emit.error(ErrorComp::new(
+            ErrorCode::InvalidMainProc,
+            "`main` procedure cannot be generic",
+            SourceRange::new(data.origin_id, range),
+            None,
+        ));
+    }
+    if let (Some(first), Some(last)) = (item.params.first(), item.params.last()) {
+        let range = TextRange::new(first.name.range.start(), last.ty.range.end());
         emit.error(ErrorComp::new(
+            ErrorCode::InvalidMainProc,
             "`main` procedure cannot have any parameters",
-            SourceRange::new(data.origin_id, data.name.range),
+            SourceRange::new(data.origin_id, range),
             None,
         ));
     }
 
+    // `void` return is lowered to exit code 0
     //@allow `never`?
     if !matches!(
         data.return_ty,
-        hir::Type::Error | hir::Type::Basic(BasicType::S32)
+        hir::Type::Error | hir::Type::Basic(BasicType::S32 | BasicType::Void)
     ) {
         let ty_range = if let Some(ty) = item.return_ty {
             ty.range
@@ -80,9 +98,24 @@ pub fn check_main_procedure<'hir>(
             data.name.range
         };
         emit.error(ErrorComp::new(
-            "`main` procedure must return `s32`",
+            ErrorCode::InvalidMainProc,
+            "`main` procedure must return `s32` or `void`",
             SourceRange::new(data.origin_id, ty_range),
             None,
         ));
     }
 }
+
+pub fn collect_tests(hir: &mut HirData, session: &Session) {
+    let mut tests = Vec::new();
+    for proc_id in hir.registry().proc_ids() {
+        let data = hir.registry().proc_data(proc_id);
+        if !data.attr_set.contains(hir::ProcFlag::Test) {
+            continue;
+        }
+        if session.module(data.origin_id).package_id == Session::ROOT_ID {
+            tests.push(proc_id);
+        }
+    }
+    hir.set_tests(tests);
+}
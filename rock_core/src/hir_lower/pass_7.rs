@@ -0,0 +1,155 @@
+use super::hir_build::{HirData, HirEmit, SymbolKind};
+use super::pass_1;
+use crate::ast;
+use crate::bitset::BitSet;
+use crate::error::{DiagnosticEdit, DiagnosticFix, SourceRange, WarningCode, WarningComp};
+use crate::hir;
+use crate::session::{ModuleID, Session};
+
+pub fn check_unused<'hir>(
+    hir: &HirData<'hir, '_, '_>,
+    emit: &mut HirEmit<'hir>,
+    session: &Session,
+) {
+    check_unused_variables(hir, emit, session);
+    check_unused_items(hir, emit, session);
+    emit.set_lint_allow(BitSet::EMPTY);
+}
+
+fn check_unused_variables<'hir>(
+    hir: &HirData<'hir, '_, '_>,
+    emit: &mut HirEmit<'hir>,
+    session: &Session,
+) {
+    let proc_variables = emit.usage.take_proc_variables();
+
+    for variables in proc_variables.iter() {
+        let item = hir.registry().proc_item(variables.proc_id);
+        let data = hir.registry().proc_data(variables.proc_id);
+        if !is_root_module(session, data.origin_id) {
+            continue;
+        }
+        emit.set_lint_allow(pass_1::lint_allow_set(hir, item.attrs));
+
+        for (param, &used) in data.params.iter().zip(variables.params_used.iter()) {
+            if !used {
+                warning_unused(hir, emit, data.origin_id, param.name, "parameter");
+            }
+        }
+        for (local, &used) in data.locals.iter().zip(variables.locals_used.iter()) {
+            if !used {
+                warning_unused(hir, emit, data.origin_id, local.name, "variable");
+            }
+        }
+    }
+}
+
+fn check_unused_items<'hir>(
+    hir: &HirData<'hir, '_, '_>,
+    emit: &mut HirEmit<'hir>,
+    session: &Session,
+) {
+    for id in hir.registry().proc_ids() {
+        let data = hir.registry().proc_data(id);
+        if data.attr_set.contains(hir::ProcFlag::Main)
+            || data.attr_set.contains(hir::ProcFlag::Test)
+            || data.attr_set.contains(hir::ProcFlag::External)
+        {
+            continue;
+        }
+        check_unused_item(hir, emit, session, SymbolKind::Proc(id));
+    }
+    for id in hir.registry().enum_ids() {
+        check_unused_item(hir, emit, session, SymbolKind::Enum(id));
+    }
+    for id in hir.registry().struct_ids() {
+        check_unused_item(hir, emit, session, SymbolKind::Struct(id));
+    }
+    for id in hir.registry().const_ids() {
+        check_unused_item(hir, emit, session, SymbolKind::Const(id));
+    }
+    for id in hir.registry().global_ids() {
+        check_unused_item(hir, emit, session, SymbolKind::Global(id));
+    }
+}
+
+//@only private items are checked, public items can be used by other packages 16.10.26
+fn check_unused_item<'hir>(
+    hir: &HirData<'hir, '_, '_>,
+    emit: &mut HirEmit<'hir>,
+    session: &Session,
+    kind: SymbolKind,
+) {
+    let registry = hir.registry();
+    #[rustfmt::skip]
+    let (item_kind, origin_id, vis, name, attrs) = match kind {
+        SymbolKind::Module(_) => return,
+        SymbolKind::Proc(id) => {
+            let item = registry.proc_item(id);
+            ("procedure", registry.proc_data(id).origin_id, item.vis, item.name, item.attrs)
+        }
+        SymbolKind::Enum(id) => {
+            let item = registry.enum_item(id);
+            ("enum", registry.enum_data(id).origin_id, item.vis, item.name, item.attrs)
+        }
+        SymbolKind::Struct(id) => {
+            let item = registry.struct_item(id);
+            ("struct", registry.struct_data(id).origin_id, item.vis, item.name, item.attrs)
+        }
+        SymbolKind::Const(id) => {
+            let item = registry.const_item(id);
+            ("constant", registry.const_data(id).origin_id, item.vis, item.name, item.attrs)
+        }
+        SymbolKind::Global(id) => {
+            let item = registry.global_item(id);
+            ("global", registry.global_data(id).origin_id, item.vis, item.name, item.attrs)
+        }
+    };
+
+    if vis == ast::Vis::Public || emit.usage.symbol_used(kind) {
+        return;
+    }
+    if !is_root_module(session, origin_id) || is_silenced(hir, name) {
+        return;
+    }
+    emit.set_lint_allow(pass_1::lint_allow_set(hir, attrs));
+    emit.warning(WarningComp::new(
+        WarningCode::UnusedItem,
+        format!("{item_kind} `{}` is never used", hir.name_str(name.id)),
+        SourceRange::new(origin_id, name.range),
+        None,
+    ));
+}
+
+fn warning_unused<'hir>(
+    hir: &HirData<'hir, '_, '_>,
+    emit: &mut HirEmit<'hir>,
+    origin_id: ModuleID,
+    name: ast::Name,
+    var_kind: &'static str,
+) {
+    if is_silenced(hir, name) {
+        return;
+    }
+    let name_str = hir.name_str(name.id);
+    let edit = DiagnosticEdit::insert(origin_id, name.range.start(), "_");
+    let fix = DiagnosticFix::new(format!("rename to `_{name_str}`"), vec![edit]);
+
+    emit.warning(
+        WarningComp::new(
+            WarningCode::UnusedVariable,
+            format!("unused {var_kind} `{name_str}`, prefix it with `_` if this is intentional"),
+            SourceRange::new(origin_id, name.range),
+            None,
+        )
+        .with_fix(fix),
+    );
+}
+
+fn is_silenced(hir: &HirData, name: ast::Name) -> bool {
+    hir.name_str(name.id).starts_with('_')
+}
+
+fn is_root_module(session: &Session, module_id: ModuleID) -> bool {
+    session.module(module_id).package_id == Session::ROOT_ID
+}
@@ -0,0 +1,70 @@
+use super::hir_build::{HirData, HirEmit, PolyScope};
+use super::pass_5;
+use crate::bitset::BitSet;
+use crate::error::{ErrorCode, ErrorComp};
+use crate::hir;
+
+const POLY_INSTANCE_LIMIT: usize = 256;
+
+pub fn monomorphize_procedures<'hir>(hir: &mut HirData<'hir, '_, '_>, emit: &mut HirEmit<'hir>) {
+    // generic bodies are typechecked with concrete types,
+    // errors and warnings were already reported for generic procedure
+    if emit.error_count() > 0 {
+        return;
+    }
+    emit.set_lint_allow(BitSet::FULL);
+
+    let mut idx = 0;
+    while idx < emit.poly_instances.len() {
+        let instance = emit.poly_instances[idx];
+        if idx == POLY_INSTANCE_LIMIT {
+            emit.error(ErrorComp::new(
+                ErrorCode::GenericInstanceLimit,
+                format!("reached the limit of {POLY_INSTANCE_LIMIT} generic procedure instances"),
+                instance.source,
+                None,
+            ));
+            break;
+        }
+
+        let item = hir.registry().proc_item(instance.proc_id);
+        let data = hir.registry().proc_data(instance.proc_id);
+        let poly_params = data.poly_params.unwrap();
+
+        let mut params = Vec::with_capacity(data.params.len());
+        for param in data.params {
+            params.push(hir::ProcParam {
+                mutt: param.mutt,
+                name: param.name,
+                ty: pass_5::type_substitute(emit, instance.proc_id, instance.poly_types, param.ty),
+            });
+        }
+        let return_ty =
+            pass_5::type_substitute(emit, instance.proc_id, instance.poly_types, data.return_ty);
+
+        let instance_data = hir::ProcData {
+            origin_id: data.origin_id,
+            attr_set: data.attr_set,
+            vis: data.vis,
+            name: data.name,
+            poly_params: None,
+            params: emit.arena.alloc_slice(&params),
+            return_ty,
+            block: None,
+            locals: &[],
+        };
+        let instance_id = hir.registry_mut().add_proc(item, instance_data);
+        assert!(instance_id == instance.instance_id);
+
+        emit.set_poly_scope(Some(PolyScope {
+            proc_id: instance.proc_id,
+            poly_params,
+            poly_types: Some(instance.poly_types),
+        }));
+        pass_5::typecheck_proc(hir, emit, instance_id);
+        idx += 1;
+    }
+
+    emit.set_lint_allow(BitSet::EMPTY);
+    emit.set_poly_scope(None);
+}
@@ -0,0 +1,392 @@
+use super::hir_build::{HirData, HirEmit};
+use super::proc_scope::{ProcScope, VariableID};
+use crate::ast;
+use crate::error::{DiagnosticEdit, DiagnosticFix, ErrorCode, ErrorComp, Info, SourceRange};
+use crate::hir;
+use crate::text::TextRange;
+
+/// value category of an expression, whether it denotes a memory location
+pub enum Addressability {
+    Unknown,
+    Constant,
+    SliceField,
+    Temporary,
+    TemporaryImmutable,
+    Addressable(ast::Mut, SourceRange),
+    ReferenceTarget(ast::Mut, Option<SourceRange>),
+    SliceElement(ast::Mut, Option<SourceRange>),
+}
+
+/// how the place expression is being used
+#[derive(Copy, Clone)]
+pub enum PlaceUse {
+    Assign,
+    Reference(ast::Mut),
+}
+
+pub enum PlaceBase {
+    None,
+    Global,
+    Variable(VariableID),
+    /// behind a reference read from a parameter or global, outlives every local
+    Outliving,
+}
+
+pub fn classify_place<'hir>(
+    hir: &HirData<'hir, '_, '_>,
+    proc: &ProcScope<'hir, '_>,
+    expr: &'hir hir::Expr<'hir>,
+) -> Addressability {
+    match *expr {
+        hir::Expr::Error => Addressability::Unknown,
+        hir::Expr::Const { .. } => Addressability::Temporary, //@TemporaryImmutable for struct / array? and alloca them
+        hir::Expr::If { .. } => Addressability::Temporary,
+        hir::Expr::Block { .. } => Addressability::Temporary,
+        hir::Expr::Match { .. } => Addressability::Temporary,
+        hir::Expr::StructField { target, deref, .. } => {
+            if deref {
+                classify_deref_place(hir, proc, target)
+            } else {
+                classify_place(hir, proc, target)
+            }
+        }
+        hir::Expr::SliceField { .. } => Addressability::SliceField,
+        hir::Expr::Index { target, access } => match access.kind {
+            hir::IndexKind::Slice { .. } => {
+                let slice_ty = if access.deref {
+                    match get_expr_type(hir, proc, target) {
+                        Some(hir::Type::Reference(ref_ty, _)) => Some(*ref_ty),
+                        _ => None,
+                    }
+                } else {
+                    get_expr_type(hir, proc, target)
+                };
+                match slice_ty {
+                    Some(hir::Type::ArraySlice(slice)) => {
+                        Addressability::SliceElement(slice.mutt, binding_source(hir, proc, target))
+                    }
+                    _ => Addressability::Unknown,
+                }
+            }
+            hir::IndexKind::Array { .. } => {
+                if access.deref {
+                    classify_deref_place(hir, proc, target)
+                } else {
+                    classify_place(hir, proc, target)
+                }
+            }
+        },
+        hir::Expr::Slice { target, .. } => classify_place(hir, proc, target),
+        hir::Expr::Cast { .. } => Addressability::Temporary,
+        hir::Expr::LocalVar { local_id } => {
+            let local = proc.get_local(local_id);
+            Addressability::Addressable(
+                local.mutt,
+                SourceRange::new(proc.origin(), local.name.range),
+            )
+        }
+        hir::Expr::ParamVar { param_id } => {
+            let param = proc.get_param(param_id);
+            Addressability::Addressable(
+                param.mutt,
+                SourceRange::new(proc.origin(), param.name.range),
+            )
+        }
+        hir::Expr::ConstVar { .. } => Addressability::Constant,
+        hir::Expr::GlobalVar { global_id } => {
+            let data = hir.registry().global_data(global_id);
+            Addressability::Addressable(
+                data.mutt,
+                SourceRange::new(data.origin_id, data.name.range),
+            )
+        }
+        hir::Expr::CallDirect { .. } => Addressability::Temporary,
+        hir::Expr::CallPoly { .. } => Addressability::Temporary,
+        hir::Expr::CallIndirect { .. } => Addressability::Temporary,
+        hir::Expr::Format { .. } => Addressability::Temporary,
+        hir::Expr::StructInit { .. } => Addressability::TemporaryImmutable,
+        hir::Expr::VariantInit { .. } => Addressability::TemporaryImmutable,
+        hir::Expr::ArrayInit { .. } => Addressability::TemporaryImmutable,
+        hir::Expr::ArrayRepeat { .. } => Addressability::TemporaryImmutable,
+        hir::Expr::Deref { rhs, .. } => classify_deref_place(hir, proc, rhs),
+        hir::Expr::Address { .. } => Addressability::Temporary,
+        hir::Expr::Unary { .. } => Addressability::Temporary,
+        hir::Expr::Binary { .. } => Addressability::Temporary,
+    }
+}
+
+/// classify the target of a reference value
+pub fn classify_deref_place<'hir>(
+    hir: &HirData<'hir, '_, '_>,
+    proc: &ProcScope<'hir, '_>,
+    ref_expr: &'hir hir::Expr<'hir>,
+) -> Addressability {
+    match get_expr_type(hir, proc, ref_expr) {
+        Some(hir::Type::Reference(_, mutt)) => {
+            // mutable access requires every dereferenced level to be mutable
+            if mutt == ast::Mut::Mutable {
+                if let hir::Expr::Deref { rhs, .. } = *ref_expr {
+                    let outer = classify_deref_place(hir, proc, rhs);
+                    if let Addressability::ReferenceTarget(ast::Mut::Immutable, _) = outer {
+                        return outer;
+                    }
+                }
+            }
+            Addressability::ReferenceTarget(mutt, binding_source(hir, proc, ref_expr))
+        }
+        _ => Addressability::Unknown,
+    }
+}
+
+/// reports using a non place or immutable place expression
+pub fn check_place<'hir>(
+    hir: &HirData<'hir, '_, '_>,
+    emit: &mut HirEmit<'hir>,
+    proc: &ProcScope<'hir, '_>,
+    place_use: PlaceUse,
+    expr: &'hir hir::Expr<'hir>,
+    range: TextRange,
+) {
+    let src = SourceRange::new(proc.origin(), range);
+    let invalid_code = match place_use {
+        PlaceUse::Assign => ErrorCode::InvalidAssignment,
+        PlaceUse::Reference(_) => ErrorCode::InvalidReference,
+    };
+    let immutable_code = match place_use {
+        PlaceUse::Assign => ErrorCode::AssignToImmutable,
+        PlaceUse::Reference(_) => ErrorCode::MutableAccessToImmutable,
+    };
+    let requires_mut = match place_use {
+        PlaceUse::Assign => true,
+        PlaceUse::Reference(mutt) => mutt == ast::Mut::Mutable,
+    };
+
+    match classify_place(hir, proc, expr) {
+        Addressability::Unknown => {} //@ & to error should be also Error? 16.05.24
+        Addressability::Constant => {
+            let msg = match place_use {
+                PlaceUse::Assign => "cannot assign to a constant",
+                PlaceUse::Reference(_) => {
+                    "cannot get reference to a constant, you can use `global` instead"
+                }
+            };
+            emit.error(ErrorComp::new(invalid_code, msg, src, None));
+        }
+        Addressability::SliceField => {
+            let msg = match place_use {
+                PlaceUse::Assign => {
+                    "cannot assign to a slice field, slice itself cannot be modified"
+                }
+                PlaceUse::Reference(_) => {
+                    "cannot get reference to a slice field, slice itself cannot be modified"
+                }
+            };
+            emit.error(ErrorComp::new(invalid_code, msg, src, None));
+        }
+        Addressability::Temporary => {
+            let msg = match place_use {
+                PlaceUse::Assign => "cannot assign to this expression, it is a temporary value",
+                PlaceUse::Reference(_) => "cannot get reference to a temporary value",
+            };
+            emit.error(ErrorComp::new(invalid_code, msg, src, None));
+        }
+        Addressability::TemporaryImmutable => {
+            let msg = match place_use {
+                PlaceUse::Assign => "cannot assign to this expression, it is a temporary value",
+                PlaceUse::Reference(ast::Mut::Mutable) => {
+                    "cannot get mutable reference to this temporary value, only immutable `&` is allowed"
+                }
+                PlaceUse::Reference(ast::Mut::Immutable) => return,
+            };
+            emit.error(ErrorComp::new(invalid_code, msg, src, None));
+        }
+        Addressability::Addressable(mutt, var_src) => {
+            if requires_mut && mutt == ast::Mut::Immutable {
+                let msg = match place_use {
+                    PlaceUse::Assign => "cannot assign to an immutable variable",
+                    PlaceUse::Reference(_) => {
+                        "cannot get mutable reference to an immutable variable"
+                    }
+                };
+                let mut error = ErrorComp::new(
+                    immutable_code,
+                    msg,
+                    src,
+                    Info::new("variable defined here", var_src),
+                );
+                if let Some(fix) = make_mutable_fix(hir, proc, expr) {
+                    error = error.with_fix(fix);
+                }
+                emit.error(error);
+            }
+        }
+        Addressability::ReferenceTarget(mutt, var_src) => {
+            if requires_mut && mutt == ast::Mut::Immutable {
+                let msg = match place_use {
+                    PlaceUse::Assign => "cannot assign through an immutable reference",
+                    PlaceUse::Reference(_) => {
+                        "cannot get mutable reference through an immutable reference"
+                    }
+                };
+                emit.error(ErrorComp::new(
+                    immutable_code,
+                    msg,
+                    src,
+                    var_src.and_then(|var_src| Info::new("variable defined here", var_src)),
+                ));
+            }
+        }
+        Addressability::SliceElement(mutt, var_src) => {
+            if requires_mut && mutt == ast::Mut::Immutable {
+                let msg = match place_use {
+                    PlaceUse::Assign => "cannot assign to an element of an immutable slice",
+                    PlaceUse::Reference(_) => {
+                        "cannot get mutable reference to an element of an immutable slice"
+                    }
+                };
+                emit.error(ErrorComp::new(
+                    immutable_code,
+                    msg,
+                    src,
+                    var_src.and_then(|var_src| Info::new("variable defined here", var_src)),
+                ));
+            }
+        }
+    }
+}
+
+// suggests declaring the variable behind `Addressability::Addressable` as `mut`
+fn make_mutable_fix<'hir>(
+    hir: &HirData<'hir, '_, '_>,
+    proc: &ProcScope<'hir, '_>,
+    expr: &'hir hir::Expr<'hir>,
+) -> Option<DiagnosticFix> {
+    let (name, edit) = match *expr {
+        hir::Expr::StructField { target, deref, .. } if !deref => {
+            return make_mutable_fix(hir, proc, target);
+        }
+        hir::Expr::Index { target, access } if !access.deref => match access.kind {
+            hir::IndexKind::Array { .. } => return make_mutable_fix(hir, proc, target),
+            hir::IndexKind::Slice { .. } => return None,
+        },
+        hir::Expr::Slice { target, .. } => return make_mutable_fix(hir, proc, target),
+        hir::Expr::LocalVar { local_id } => {
+            let range = proc.local_mutt_range(local_id)?;
+            let edit = DiagnosticEdit::replace(SourceRange::new(proc.origin(), range), "mut");
+            (proc.get_local(local_id).name, edit)
+        }
+        hir::Expr::ParamVar { param_id } => {
+            let param = proc.get_param(param_id);
+            let edit = DiagnosticEdit::insert(proc.origin(), param.name.range.start(), "mut ");
+            (param.name, edit)
+        }
+        hir::Expr::GlobalVar { global_id } => {
+            let data = hir.registry().global_data(global_id);
+            let edit = DiagnosticEdit::insert(data.origin_id, data.name.range.start(), "mut ");
+            (data.name, edit)
+        }
+        _ => return None,
+    };
+    let message = format!("make `{}` mutable", hir.name_str(name.id));
+    Some(DiagnosticFix::new(message, vec![edit]))
+}
+
+/// type of expressions that can be used as a base of place expressions
+fn get_expr_type<'hir>(
+    hir: &HirData<'hir, '_, '_>,
+    proc: &ProcScope<'hir, '_>,
+    expr: &'hir hir::Expr<'hir>,
+) -> Option<hir::Type<'hir>> {
+    match *expr {
+        hir::Expr::StructField {
+            struct_id,
+            field_id,
+            ..
+        } => {
+            let data = hir.registry().struct_data(struct_id);
+            Some(data.fields[field_id.index()].ty)
+        }
+        hir::Expr::Index { access, .. } => Some(access.elem_ty),
+        hir::Expr::Cast { into, .. } => Some(*into),
+        hir::Expr::LocalVar { local_id } => Some(proc.get_local(local_id).ty),
+        hir::Expr::ParamVar { param_id } => Some(proc.get_param(param_id).ty),
+        hir::Expr::ConstVar { const_id } => Some(hir.registry().const_data(const_id).ty),
+        hir::Expr::GlobalVar { global_id } => Some(hir.registry().global_data(global_id).ty),
+        hir::Expr::CallDirect { proc_id, .. } => Some(hir.registry().proc_data(proc_id).return_ty),
+        hir::Expr::CallPoly { poly_call, .. } => Some(poly_call.return_ty),
+        hir::Expr::CallIndirect { indirect, .. } => Some(indirect.proc_ty.return_ty),
+        hir::Expr::Deref { ptr_ty, .. } => Some(*ptr_ty),
+        _ => None,
+    }
+}
+
+/// declaration of the variable which holds the value, used as related info
+pub fn binding_source<'hir>(
+    hir: &HirData<'hir, '_, '_>,
+    proc: &ProcScope<'hir, '_>,
+    expr: &'hir hir::Expr<'hir>,
+) -> Option<SourceRange> {
+    match *expr {
+        hir::Expr::StructField {
+            target,
+            deref: false,
+            ..
+        } => binding_source(hir, proc, target),
+        hir::Expr::Index { target, access } if !access.deref => binding_source(hir, proc, target),
+        hir::Expr::LocalVar { local_id } => {
+            let local = proc.get_local(local_id);
+            Some(SourceRange::new(proc.origin(), local.name.range))
+        }
+        hir::Expr::ParamVar { param_id } => {
+            let param = proc.get_param(param_id);
+            Some(SourceRange::new(proc.origin(), param.name.range))
+        }
+        hir::Expr::GlobalVar { global_id } => {
+            let data = hir.registry().global_data(global_id);
+            Some(SourceRange::new(data.origin_id, data.name.range))
+        }
+        _ => None,
+    }
+}
+
+/// variable or global which memory holds the place expression
+pub fn place_base<'hir>(expr: &'hir hir::Expr<'hir>) -> PlaceBase {
+    match *expr {
+        hir::Expr::StructField { target, deref, .. } => {
+            if deref {
+                deref_place_base(target)
+            } else {
+                place_base(target)
+            }
+        }
+        hir::Expr::Index { target, access } => match access.kind {
+            hir::IndexKind::Array { .. } if !access.deref => place_base(target),
+            hir::IndexKind::Array { .. } => deref_place_base(target),
+            hir::IndexKind::Slice { .. } => outliving_base(place_base(target)),
+        },
+        hir::Expr::LocalVar { local_id } => PlaceBase::Variable(VariableID::Local(local_id)),
+        hir::Expr::ParamVar { param_id } => PlaceBase::Variable(VariableID::Param(param_id)),
+        hir::Expr::GlobalVar { .. } => PlaceBase::Global,
+        hir::Expr::Deref { rhs, .. } => deref_place_base(rhs),
+        _ => PlaceBase::None,
+    }
+}
+
+/// base of the place behind a reference value
+//@targets of references held by locals are not tracked 16.10.26
+fn deref_place_base<'hir>(ref_expr: &'hir hir::Expr<'hir>) -> PlaceBase {
+    match *ref_expr {
+        hir::Expr::Address { rhs } => place_base(rhs),
+        _ => outliving_base(place_base(ref_expr)),
+    }
+}
+
+// references and slices passed in or stored in globals point to memory outside of the procedure
+fn outliving_base(base: PlaceBase) -> PlaceBase {
+    match base {
+        PlaceBase::Variable(VariableID::Param(_)) | PlaceBase::Global | PlaceBase::Outliving => {
+            PlaceBase::Outliving
+        }
+        PlaceBase::None | PlaceBase::Variable(VariableID::Local(_)) => PlaceBase::None,
+    }
+}
@@ -1,6 +1,6 @@
-use super::hir_build::{HirData, HirEmit};
+use super::hir_build::{HirData, HirEmit, ProcVariableUsage};
 use super::pass_5::Expectation;
-use crate::error::{Info, SourceRange, WarningComp};
+use crate::error::{Info, SourceRange, WarningCode, WarningComp};
 use crate::hir;
 use crate::intern::InternID;
 use crate::session::ModuleID;
@@ -14,12 +14,29 @@ pub struct ProcScope<'hir, 'check> {
     data: &'check hir::ProcData<'hir>,
     return_expect: Expectation<'hir>,
     blocks: Vec<BlockData>,
+    defers: Vec<&'hir hir::Block<'hir>>,
     locals: Vec<&'hir hir::Local<'hir>>,
     locals_in_scope: Vec<hir::LocalID>,
+    params_used: Vec<bool>,
+    locals_used: Vec<bool>,
+    locals_depth: Vec<u32>,
+    locals_reference: Vec<Option<VariableID>>,
+    locals_mutt_range: Vec<Option<TextRange>>,
+    locals_literal: Vec<Option<LiteralValue>>,
+}
+
+/// value of an un-annotated local initialized by a literal without suffix,
+/// local type is decided by its first use, default type is used until then
+#[derive(Copy, Clone)]
+pub enum LiteralValue {
+    Int { val: u64, neg: bool },
+    Float { val: f64 },
 }
 
 pub struct BlockData {
     local_count: u32,
+    defer_count: u32,
+    is_loop: bool,
     diverges: Diverges,
     loop_status: LoopStatus,
     defer_status: DeferStatus,
@@ -59,6 +76,7 @@ impl Diverges {
     }
 }
 
+#[derive(Copy, Clone)]
 pub enum VariableID {
     Local(hir::LocalID),
     Param(hir::ProcParamID),
@@ -70,14 +88,28 @@ impl<'hir, 'check> ProcScope<'hir, 'check> {
             data,
             return_expect,
             blocks: Vec::new(),
+            defers: Vec::new(),
             locals: Vec::new(),
             locals_in_scope: Vec::new(),
+            params_used: vec![false; data.params.len()],
+            locals_used: Vec::new(),
+            locals_depth: Vec::new(),
+            locals_reference: Vec::new(),
+            locals_mutt_range: Vec::new(),
+            locals_literal: Vec::new(),
         }
     }
 
     pub fn finish_locals(&self) -> &[&'hir hir::Local<'hir>] {
         self.locals.as_slice()
     }
+    pub fn finish_usage(self, proc_id: hir::ProcID) -> ProcVariableUsage {
+        ProcVariableUsage {
+            proc_id,
+            params_used: self.params_used,
+            locals_used: self.locals_used,
+        }
+    }
     pub fn origin(&self) -> ModuleID {
         self.data.origin_id
     }
@@ -99,23 +131,71 @@ impl<'hir, 'check> ProcScope<'hir, 'check> {
     pub fn get_param(&self, id: hir::ProcParamID) -> &hir::ProcParam<'hir> {
         self.data.param(id)
     }
+    /// depth of currently entered block, proc body block has depth of 1
+    pub fn block_depth(&self) -> u32 {
+        self.blocks.len() as u32
+    }
+    /// depth of the block variable was defined in, params live in proc body block
+    pub fn variable_depth(&self, var_id: VariableID) -> u32 {
+        match var_id {
+            VariableID::Local(id) => self.locals_depth[id.index()],
+            VariableID::Param(_) => 1,
+        }
+    }
+    /// variable which memory is referenced by the value of the local
+    pub fn local_reference(&self, id: hir::LocalID) -> Option<VariableID> {
+        self.locals_reference[id.index()]
+    }
+    pub fn set_local_reference(&mut self, id: hir::LocalID, reference: Option<VariableID>) {
+        self.locals_reference[id.index()] = reference;
+    }
+    /// range of `let` or `mut` keyword, match bindings dont have one
+    pub fn local_mutt_range(&self, id: hir::LocalID) -> Option<TextRange> {
+        self.locals_mutt_range[id.index()]
+    }
+    pub fn set_local_mutt_range(&mut self, id: hir::LocalID, range: TextRange) {
+        self.locals_mutt_range[id.index()] = Some(range);
+    }
+    pub fn set_local_literal(&mut self, id: hir::LocalID, literal: LiteralValue) {
+        self.locals_literal[id.index()] = Some(literal);
+    }
+    /// literal value of a local whose type is not decided yet, only returned once
+    pub fn take_local_literal(&mut self, id: hir::LocalID) -> Option<LiteralValue> {
+        self.locals_literal[id.index()].take()
+    }
+    /// locals which were never used, they keep the default literal type
+    pub fn undecided_literal_locals(&self) -> impl Iterator<Item = hir::LocalID> + '_ {
+        (0..self.locals.len())
+            .filter(|&idx| self.locals_literal[idx].is_some())
+            .map(hir::LocalID::new)
+    }
+    /// replace local data once its type is decided, same `LocalID` is kept
+    pub fn replace_local(&mut self, id: hir::LocalID, local: &'hir hir::Local<'hir>) {
+        self.locals[id.index()] = local;
+    }
 
     pub fn push_block(&mut self, enter: BlockEnter) {
         let block_data = match enter {
             BlockEnter::None => BlockData {
                 local_count: 0,
+                defer_count: 0,
+                is_loop: false,
                 diverges: self.inherit_diverges(),
                 loop_status: self.inherit_loop_status(false, false),
                 defer_status: self.inherit_defer_status(None),
             },
             BlockEnter::Loop => BlockData {
                 local_count: 0,
+                defer_count: 0,
+                is_loop: true,
                 diverges: self.inherit_diverges(),
                 loop_status: self.inherit_loop_status(true, false),
                 defer_status: self.inherit_defer_status(None),
             },
             BlockEnter::Defer(range) => BlockData {
                 local_count: 0,
+                defer_count: 0,
+                is_loop: false,
                 diverges: self.inherit_diverges(),
                 loop_status: self.inherit_loop_status(false, true),
                 defer_status: self.inherit_defer_status(Some(range)),
@@ -179,25 +259,90 @@ impl<'hir, 'check> ProcScope<'hir, 'check> {
         for _ in 0..block.local_count {
             self.locals_in_scope.pop();
         }
+        let defer_count = self.defers.len() - block.defer_count as usize;
+        self.defers.truncate(defer_count);
+    }
+
+    pub fn push_defer(&mut self, block: &'hir hir::Block<'hir>) {
+        self.blocks.last_mut().expect("block exists").defer_count += 1;
+        self.defers.push(block);
+    }
+    /// defers that run at the end of current block, in execution order
+    pub fn block_defers(&self) -> impl Iterator<Item = &'hir hir::Block<'hir>> + '_ {
+        let defer_count = self.blocks.last().expect("block exists").defer_count;
+        self.defers.iter().rev().take(defer_count as usize).copied()
+    }
+    /// defers that run on `break` or `continue` of the innermost loop
+    pub fn loop_defers(&self) -> impl Iterator<Item = &'hir hir::Block<'hir>> + '_ {
+        let mut defer_count = 0;
+        for block in self.blocks.iter().rev() {
+            defer_count += block.defer_count as usize;
+            if block.is_loop {
+                break;
+            }
+        }
+        self.defers.iter().rev().take(defer_count).copied()
+    }
+    /// defers that run on `return`
+    pub fn all_defers(&self) -> impl Iterator<Item = &'hir hir::Block<'hir>> + '_ {
+        self.defers.iter().rev().copied()
     }
 
     pub fn push_local(&mut self, local: &'hir hir::Local<'hir>) -> hir::LocalID {
         let local_id = hir::LocalID::new(self.locals.len());
         self.locals.push(local);
+        self.locals_used.push(false);
+        self.locals_depth.push(self.block_depth());
+        self.locals_reference.push(None);
+        self.locals_mutt_range.push(None);
+        self.locals_literal.push(None);
         self.locals_in_scope.push(local_id);
         self.blocks.last_mut().expect("block exists").local_count += 1;
         local_id
     }
 
+    pub fn mark_used(&mut self, var_id: VariableID) {
+        match var_id {
+            VariableID::Local(id) => self.locals_used[id.index()] = true,
+            VariableID::Param(id) => self.params_used[id.index()] = true,
+        }
+    }
+
+    /// innermost variable with this name, locals shadow params and outer locals
     pub fn find_variable(&self, id: InternID) -> Option<VariableID> {
+        for local_id in self.locals_in_scope.iter().rev().copied() {
+            if self.get_local(local_id).name.id == id {
+                return Some(VariableID::Local(local_id));
+            }
+        }
         if let Some((param_id, _)) = self.data.find_param(id) {
             return Some(VariableID::Param(param_id));
         }
-        for local_id in self.locals_in_scope.iter().cloned() {
+        None
+    }
+
+    /// local with this name which went out of scope, when name is not found
+    pub fn find_exited_local(&self, id: InternID) -> Option<hir::LocalID> {
+        let idx = self.locals.iter().rposition(|local| local.name.id == id)?;
+        Some(hir::LocalID::new(idx))
+    }
+
+    /// variable with this name defined in the current block,
+    /// names can only be shadowed in enclosed blocks
+    pub fn find_variable_in_block(&self, id: InternID) -> Option<VariableID> {
+        let local_count = self.blocks.last().expect("block exists").local_count as usize;
+        let block_locals = &self.locals_in_scope[self.locals_in_scope.len() - local_count..];
+
+        for local_id in block_locals.iter().rev().copied() {
             if self.get_local(local_id).name.id == id {
                 return Some(VariableID::Local(local_id));
             }
         }
+        if self.block_depth() == 1 {
+            if let Some((param_id, _)) = self.data.find_param(id) {
+                return Some(VariableID::Param(param_id));
+            }
+        }
         None
     }
 
@@ -220,6 +365,7 @@ impl<'hir, 'check> ProcScope<'hir, 'check> {
                 *diverges = Diverges::AlwaysWarned;
 
                 emit.warning(WarningComp::new(
+                    WarningCode::UnreachableCode,
                     "unreachable statement",
                     SourceRange::new(self.origin(), stmt_range),
                     Info::new(
@@ -233,3 +379,46 @@ impl<'hir, 'check> ProcScope<'hir, 'check> {
         }
     }
 }
+
+#[test]
+fn test_defer_order() {
+    use crate::ast;
+    use crate::bitset::BitSet;
+
+    let data = hir::ProcData {
+        origin_id: ModuleID::new(0),
+        attr_set: BitSet::EMPTY,
+        vis: ast::Vis::Private,
+        name: ast::Name {
+            id: crate::intern::NAME_MAIN,
+            range: TextRange::empty_at(0.into()),
+        },
+        poly_params: None,
+        params: &[],
+        return_ty: hir::Type::VOID,
+        block: None,
+        locals: &[],
+    };
+    let blocks = [hir::Block { stmts: &[] }; 3];
+    let [a, b, c] = [&blocks[0], &blocks[1], &blocks[2]];
+    let same = |defers: Vec<&hir::Block>, expect: &[&hir::Block]| {
+        defers.len() == expect.len() && defers.iter().zip(expect).all(|(l, r)| std::ptr::eq(*l, *r))
+    };
+
+    let mut proc = ProcScope::new(&data, Expectation::None);
+    proc.push_block(BlockEnter::None);
+    proc.push_defer(a);
+    proc.push_block(BlockEnter::Loop);
+    proc.push_defer(b);
+    proc.push_block(BlockEnter::None);
+    proc.push_defer(c);
+
+    assert!(same(proc.block_defers().collect(), &[c]));
+    assert!(same(proc.loop_defers().collect(), &[c, b]));
+    assert!(same(proc.all_defers().collect(), &[c, b, a]));
+    proc.pop_block();
+    assert!(same(proc.block_defers().collect(), &[b]));
+    assert!(same(proc.all_defers().collect(), &[b, a]));
+    proc.pop_block();
+    assert!(same(proc.loop_defers().collect(), &[a]));
+}
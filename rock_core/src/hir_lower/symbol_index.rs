@@ -0,0 +1,126 @@
+use super::hir_build::{HirData, MethodOwner, SymbolKind};
+use crate::ast;
+use crate::error::SourceRange;
+use crate::hir;
+use crate::index::{Definition, DefinitionKind, FileIndex, Reference, SymbolID};
+use crate::session::{ModuleID, Session};
+use std::collections::HashMap;
+
+/// index entries of every module in the session,
+/// `references` were recorded while resolving imports and paths
+pub fn collect(
+    hir: &HirData,
+    session: &Session,
+    references: Vec<(SymbolKind, SourceRange)>,
+) -> Vec<FileIndex> {
+    let mut files: Vec<FileIndex> = session
+        .module_ids()
+        .map(|module_id| {
+            let module = session.module(module_id);
+            FileIndex::new(module.path.clone(), &module.source)
+        })
+        .collect();
+    let owners: HashMap<hir::ProcID, MethodOwner> = hir
+        .methods()
+        .map(|(owner, method)| (method.proc_id, owner))
+        .collect();
+
+    let registry = hir.registry();
+    let mut kinds = Vec::new();
+    kinds.extend(registry.proc_ids().map(SymbolKind::Proc));
+    kinds.extend(registry.enum_ids().map(SymbolKind::Enum));
+    kinds.extend(registry.struct_ids().map(SymbolKind::Struct));
+    kinds.extend(registry.const_ids().map(SymbolKind::Const));
+    kinds.extend(registry.global_ids().map(SymbolKind::Global));
+
+    for kind in kinds {
+        let (origin_id, name) = symbol_name(hir, kind);
+        let owner = match kind {
+            SymbolKind::Proc(proc_id) => owners.get(&proc_id).copied(),
+            _ => None,
+        };
+        let def_kind = match kind {
+            SymbolKind::Module(_) => unreachable!(),
+            SymbolKind::Proc(_) if owner.is_some() => DefinitionKind::Method,
+            SymbolKind::Proc(_) => DefinitionKind::Proc,
+            SymbolKind::Enum(_) => DefinitionKind::Enum,
+            SymbolKind::Struct(_) => DefinitionKind::Struct,
+            SymbolKind::Const(_) => DefinitionKind::Const,
+            SymbolKind::Global(_) => DefinitionKind::Global,
+        };
+        let definition = Definition {
+            symbol_id: symbol_id(hir, session, &owners, kind),
+            name: hir.name_str(name.id).to_string(),
+            kind: def_kind,
+            start: name.range.start().into(),
+            end: name.range.end().into(),
+            owner: owner.map(|owner| symbol_id(hir, session, &owners, owner_kind(owner))),
+        };
+        files[origin_id.index()].definitions.push(definition);
+    }
+
+    for (kind, source) in references {
+        if let SymbolKind::Module(_) = kind {
+            continue;
+        }
+        let reference = Reference {
+            symbol_id: symbol_id(hir, session, &owners, kind),
+            start: source.range().start().into(),
+            end: source.range().end().into(),
+        };
+        files[source.module_id().index()].references.push(reference);
+    }
+    files
+}
+
+fn symbol_id(
+    hir: &HirData,
+    session: &Session,
+    owners: &HashMap<hir::ProcID, MethodOwner>,
+    kind: SymbolKind,
+) -> SymbolID {
+    let (origin_id, name) = symbol_name(hir, kind);
+    let owner = match kind {
+        SymbolKind::Proc(proc_id) => owners.get(&proc_id).map(|&owner| {
+            let (_, owner_name) = symbol_name(hir, owner_kind(owner));
+            hir.name_str(owner_name.id)
+        }),
+        _ => None,
+    };
+    let path = &session.module(origin_id).path;
+    SymbolID::new(path, owner, hir.name_str(name.id))
+}
+
+fn symbol_name(hir: &HirData, kind: SymbolKind) -> (ModuleID, ast::Name) {
+    let registry = hir.registry();
+    match kind {
+        SymbolKind::Module(_) => unreachable!(),
+        SymbolKind::Proc(id) => {
+            let data = registry.proc_data(id);
+            (data.origin_id, data.name)
+        }
+        SymbolKind::Enum(id) => {
+            let data = registry.enum_data(id);
+            (data.origin_id, data.name)
+        }
+        SymbolKind::Struct(id) => {
+            let data = registry.struct_data(id);
+            (data.origin_id, data.name)
+        }
+        SymbolKind::Const(id) => {
+            let data = registry.const_data(id);
+            (data.origin_id, data.name)
+        }
+        SymbolKind::Global(id) => {
+            let data = registry.global_data(id);
+            (data.origin_id, data.name)
+        }
+    }
+}
+
+fn owner_kind(owner: MethodOwner) -> SymbolKind {
+    match owner {
+        MethodOwner::Enum(id) => SymbolKind::Enum(id),
+        MethodOwner::Struct(id) => SymbolKind::Struct(id),
+    }
+}
@@ -0,0 +1,282 @@
+use crate::build_layout;
+use crate::error::ErrorComp;
+use crate::fs_env;
+use crate::package::semver::Semver;
+use crate::text::{TextOffset, TextRange};
+use crate::vfs;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+
+/// definitions and references of each module in the session, stored in `build/index.toml`
+/// entries are replaced when file contents change, queries don't require lowering
+#[derive(Clone, Serialize, Deserialize)]
+pub struct SymbolIndex {
+    pub version: Semver,       // toolchain version
+    pub files: Vec<FileIndex>, // indexed files sorted by path
+}
+
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
+pub struct FileIndex {
+    pub path: PathBuf,
+    pub hash: String, // content hash of the indexed source
+    pub definitions: Vec<Definition>,
+    pub references: Vec<Reference>,
+}
+
+/// stable between runs, derived from the defining file path, method owner and name
+/// ids are limited to 63 bits to fit toml integers
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct SymbolID(u64);
+
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
+pub struct Definition {
+    pub symbol_id: SymbolID,
+    pub name: String,
+    pub kind: DefinitionKind,
+    pub start: u32,
+    pub end: u32,
+    /// enum or struct with the `impl` block of the method
+    pub owner: Option<SymbolID>,
+}
+
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
+pub struct Reference {
+    pub symbol_id: SymbolID,
+    pub start: u32,
+    pub end: u32,
+}
+
+#[derive(Copy, Clone, PartialEq, Serialize, Deserialize)]
+pub enum DefinitionKind {
+    #[serde(rename = "proc")]
+    Proc,
+    #[serde(rename = "method")]
+    Method,
+    #[serde(rename = "enum")]
+    Enum,
+    #[serde(rename = "struct")]
+    Struct,
+    #[serde(rename = "const")]
+    Const,
+    #[serde(rename = "global")]
+    Global,
+}
+
+pub struct Location<'index> {
+    pub path: &'index PathBuf,
+    pub range: TextRange,
+}
+
+impl SymbolIndex {
+    pub fn new() -> SymbolIndex {
+        SymbolIndex {
+            version: crate::VERSION,
+            files: Vec::new(),
+        }
+    }
+
+    /// index of the last check, `None` if missing or written by different toolchain
+    pub fn read(root_dir: &PathBuf) -> Option<SymbolIndex> {
+        let text = std::fs::read_to_string(build_layout::index_path(root_dir)).ok()?;
+        let index: SymbolIndex = basic_toml::from_str(&text).ok()?;
+        if index.version != crate::VERSION {
+            return None;
+        }
+        Some(index)
+    }
+
+    pub fn write(&self, root_dir: &PathBuf) -> Result<(), ErrorComp> {
+        let text = basic_toml::to_string(self).map_err(|error| {
+            ErrorComp::message(format!(
+                "failed to serialize symbol index\nreason: {}",
+                error
+            ))
+        })?;
+        let path = build_layout::index_path(root_dir);
+        if let Some(build_dir) = path.parent() {
+            fs_env::dir_create(&build_dir.to_path_buf(), false)?;
+        }
+        fs_env::file_create_or_rewrite(&path, &text)
+    }
+
+    /// replace entries with `files` of the latest check when any file was added, removed or changed,
+    /// returns `true` when the index was changed and should be written
+    pub fn update(&mut self, mut files: Vec<FileIndex>) -> bool {
+        files.sort_by(|a, b| a.path.cmp(&b.path));
+        let mut changed = files.len() != self.files.len();
+
+        for file in files.iter() {
+            match self.file(&file.path) {
+                Some(existing) if existing.hash == file.hash => {}
+                _ => changed = true,
+            }
+        }
+        if changed {
+            self.files = files;
+        }
+        changed
+    }
+
+    pub fn file(&self, path: &PathBuf) -> Option<&FileIndex> {
+        self.files
+            .binary_search_by(|file| file.path.cmp(path))
+            .ok()
+            .map(|idx| &self.files[idx])
+    }
+
+    /// symbol defined or referenced at `offset`, offsets are only valid for the indexed source
+    pub fn symbol_at(&self, path: &PathBuf, offset: TextOffset) -> Option<SymbolID> {
+        let file = self.file(path)?;
+        let definitions = file
+            .definitions
+            .iter()
+            .map(|def| (def.symbol_id, def.range()));
+        let references = file.references.iter().map(|r| (r.symbol_id, r.range()));
+        definitions
+            .chain(references)
+            .find(|(_, range)| range.contains_inclusive(offset))
+            .map(|(symbol_id, _)| symbol_id)
+    }
+
+    pub fn definition(&self, symbol_id: SymbolID) -> Option<(Location<'_>, &Definition)> {
+        self.files.iter().find_map(|file| {
+            let definition = file
+                .definitions
+                .iter()
+                .find(|def| def.symbol_id == symbol_id)?;
+            let location = Location {
+                path: &file.path,
+                range: definition.range(),
+            };
+            Some((location, definition))
+        })
+    }
+
+    pub fn references(&self, symbol_id: SymbolID) -> Vec<Location<'_>> {
+        let mut locations = Vec::new();
+        for file in self.files.iter() {
+            for reference in file.references.iter() {
+                if reference.symbol_id == symbol_id {
+                    locations.push(Location {
+                        path: &file.path,
+                        range: reference.range(),
+                    });
+                }
+            }
+        }
+        locations
+    }
+
+    /// methods implemented for an enum or struct
+    pub fn implementations(&self, symbol_id: SymbolID) -> Vec<Location<'_>> {
+        let mut locations = Vec::new();
+        for file in self.files.iter() {
+            for definition in file.definitions.iter() {
+                if definition.owner == Some(symbol_id) {
+                    locations.push(Location {
+                        path: &file.path,
+                        range: definition.range(),
+                    });
+                }
+            }
+        }
+        locations
+    }
+}
+
+impl Default for SymbolIndex {
+    fn default() -> SymbolIndex {
+        SymbolIndex::new()
+    }
+}
+
+impl FileIndex {
+    pub fn new(path: PathBuf, source: &str) -> FileIndex {
+        FileIndex {
+            path,
+            hash: content_hash(source),
+            definitions: Vec::new(),
+            references: Vec::new(),
+        }
+    }
+
+    /// offsets of the entry can be used with `source`
+    pub fn is_current(&self, source: &str) -> bool {
+        self.hash == content_hash(source)
+    }
+}
+
+impl SymbolID {
+    pub fn new(path: &PathBuf, owner: Option<&str>, name: &str) -> SymbolID {
+        let path = path.to_string_lossy();
+        let key = match owner {
+            Some(owner) => format!("{path}::{owner}::{name}"),
+            None => format!("{path}::{name}"),
+        };
+        SymbolID(vfs::content_hash(&key) & i64::MAX as u64)
+    }
+}
+
+impl Definition {
+    pub fn range(&self) -> TextRange {
+        TextRange::new(self.start.into(), self.end.into())
+    }
+}
+
+impl Reference {
+    pub fn range(&self) -> TextRange {
+        TextRange::new(self.start.into(), self.end.into())
+    }
+}
+
+fn content_hash(source: &str) -> String {
+    format!("{:016x}", vfs::content_hash(source))
+}
+
+#[test]
+fn test_symbol_index_queries() {
+    let path = PathBuf::from("src/main.rock");
+    let source = "proc main() {}";
+    let vec2 = SymbolID::new(&path, None, "Vec2");
+    let length = SymbolID::new(&path, Some("Vec2"), "length");
+
+    let mut file = FileIndex::new(path.clone(), source);
+    file.definitions.push(Definition {
+        symbol_id: vec2,
+        name: "Vec2".to_string(),
+        kind: DefinitionKind::Struct,
+        start: 7,
+        end: 11,
+        owner: None,
+    });
+    file.definitions.push(Definition {
+        symbol_id: length,
+        name: "length".to_string(),
+        kind: DefinitionKind::Method,
+        start: 30,
+        end: 36,
+        owner: Some(vec2),
+    });
+    file.references.push(Reference {
+        symbol_id: vec2,
+        start: 50,
+        end: 54,
+    });
+
+    let mut index = SymbolIndex::new();
+    assert!(index.update(vec![file.clone()]));
+    assert!(!index.update(vec![file]));
+    assert!(index.file(&path).unwrap().is_current(source));
+
+    assert!(index.symbol_at(&path, 52.into()) == Some(vec2));
+    assert!(index.symbol_at(&path, 40.into()).is_none());
+    assert!(index.definition(vec2).unwrap().0.range == TextRange::new(7.into(), 11.into()));
+    assert!(index.references(vec2).len() == 1);
+    assert!(index.implementations(vec2).len() == 1);
+    assert!(index.implementations(length).is_empty());
+
+    let text = basic_toml::to_string(&index).unwrap();
+    let index: SymbolIndex = basic_toml::from_str(&text).unwrap();
+    assert!(index.file(&path).unwrap().definitions[1].owner == Some(vec2));
+}
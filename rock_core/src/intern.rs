@@ -2,8 +2,21 @@ use crate::arena::Arena;
 use crate::id_impl;
 use std::collections::HashMap;
 use std::hash::{BuildHasher, Hasher};
+use std::sync::Mutex;
 
 id_impl!(InternID);
+
+// names interned in this order by `InternPool::with_names`
+// their ids are stable across sessions and can be used without lookup
+pub const NAME_SELF: InternID = InternID::new(0);
+pub const NAME_MAIN: InternID = InternID::new(1);
+pub const NAME_SUPER: InternID = InternID::new(2);
+pub const NAME_PACKAGE: InternID = InternID::new(3);
+const PREDEFINED_NAMES: [&str; 4] = ["self", "main", "super", "package"];
+
+/// names with special meaning in methods and paths, cannot name items or imports
+pub const RESERVED_NAMES: [InternID; 3] = [NAME_SELF, NAME_SUPER, NAME_PACKAGE];
+
 pub struct InternPool<'intern> {
     arena: Arena<'intern>,
     values: Vec<&'intern str>,
@@ -19,6 +32,14 @@ impl<'intern> InternPool<'intern> {
         }
     }
 
+    pub fn with_names() -> InternPool<'intern> {
+        let mut pool = InternPool::new();
+        for name in PREDEFINED_NAMES {
+            pool.intern(name);
+        }
+        pool
+    }
+
     pub fn intern(&mut self, string: &str) -> InternID {
         if let Some(id) = self.intern_map.get(string).cloned() {
             return id;
@@ -39,6 +60,61 @@ impl<'intern> InternPool<'intern> {
     pub fn get_all_strings(&self) -> &[&str] {
         &self.values
     }
+
+    // strings are written in id order, deserialized pool will have the same ids
+    // format: u32 string count, then each string as u32 byte length + utf-8 bytes
+    pub fn serialize(&self, bytes: &mut Vec<u8>) {
+        bytes.extend_from_slice(&(self.values.len() as u32).to_le_bytes());
+        for string in &self.values {
+            bytes.extend_from_slice(&(string.len() as u32).to_le_bytes());
+            bytes.extend_from_slice(string.as_bytes());
+        }
+    }
+
+    pub fn deserialize(bytes: &[u8]) -> Option<InternPool<'intern>> {
+        fn read_u32(bytes: &mut &[u8]) -> Option<usize> {
+            let (value, rest) = bytes.split_first_chunk::<4>()?;
+            *bytes = rest;
+            Some(u32::from_le_bytes(*value) as usize)
+        }
+
+        let mut bytes = bytes;
+        let mut pool = InternPool::new();
+        let count = read_u32(&mut bytes)?;
+
+        for idx in 0..count {
+            let len = read_u32(&mut bytes)?;
+            let (string, rest) = bytes.split_at_checked(len)?;
+            let string = std::str::from_utf8(string).ok()?;
+            bytes = rest;
+            if pool.intern(string).index() != idx {
+                return None;
+            }
+        }
+        Some(pool)
+    }
+}
+
+/// intern pool which can be shared between threads
+pub struct SharedInternPool<'intern> {
+    pool: Mutex<InternPool<'intern>>,
+}
+
+impl<'intern> SharedInternPool<'intern> {
+    pub fn new(pool: InternPool<'intern>) -> SharedInternPool<'intern> {
+        SharedInternPool {
+            pool: Mutex::new(pool),
+        }
+    }
+
+    pub fn intern(&self, string: &str) -> InternID {
+        let mut pool = self.pool.lock().unwrap();
+        pool.intern(string)
+    }
+
+    pub fn into_inner(self) -> InternPool<'intern> {
+        self.pool.into_inner().unwrap()
+    }
 }
 
 const FNV_OFFSET: u32 = 2166136261;
@@ -1,19 +1,34 @@
 use super::lexer::Lexer;
-use crate::error::{ErrorComp, SourceRange};
-use crate::text::TextRange;
+use crate::ast::StringKind;
+use crate::error::{ErrorCode, ErrorComp, SourceRange};
+use crate::text::{self, TextOffset, TextRange};
 use crate::token::{Token, Trivia};
 
 pub fn source_file(lex: &mut Lexer) {
+    // `Session` removes the byte order mark, other sources can still start with it
+    if lex.peek() == Some(text::BOM) {
+        let start = lex.start_range();
+        lex.eat(text::BOM);
+        if lex.with_trivia {
+            let range = lex.make_range(start);
+            lex.tokens().add_trivia(Trivia::Whitespace, range);
+        }
+    }
     while lex.peek().is_some() {
         lex_whitespace(lex);
         if let Some(c) = lex.peek() {
             match c {
                 '\'' => lex_char(lex),
-                '`' => lex_string(lex, false, true),
-                '"' => lex_string(lex, false, false),
+                '`' => lex_string(lex, StringKind::Regular, true),
+                '"' => lex_string(lex, StringKind::Regular, false),
                 'c' => match lex.peek_next() {
-                    Some('`') => lex_string(lex, true, true),
-                    Some('"') => lex_string(lex, true, false),
+                    Some('`') => lex_string(lex, StringKind::CString, true),
+                    Some('"') => lex_string(lex, StringKind::CString, false),
+                    _ => lex_ident(lex, c),
+                },
+                'b' => match lex.peek_next() {
+                    Some('`') => lex_string(lex, StringKind::Bytes, true),
+                    Some('"') => lex_string(lex, StringKind::Bytes, false),
                     _ => lex_ident(lex, c),
                 },
                 _ => {
@@ -49,11 +64,20 @@ fn lex_whitespace(lex: &mut Lexer) {
             let start = lex.start_range();
             lex.eat(c);
             lex.eat('/');
+            let is_doc = matches!(lex.peek(), Some('/')) && !matches!(lex.peek_next(), Some('/'));
             skip_line_comment(lex);
 
+            let range = lex.make_range(start);
+            if is_doc {
+                lex.tokens().add_doc_comment(range);
+            }
             if lex.with_trivia {
-                let range = lex.make_range(start);
-                lex.tokens().add_trivia(Trivia::LineComment, range);
+                let trivia = if is_doc {
+                    Trivia::DocComment
+                } else {
+                    Trivia::LineComment
+                };
+                lex.tokens().add_trivia(trivia, range);
             }
         } else if c == '/' && matches!(lex.peek_next(), Some('*')) {
             let start = lex.start_range();
@@ -64,6 +88,7 @@ fn lex_whitespace(lex: &mut Lexer) {
             if depth != 0 {
                 let range = lex.make_range(start);
                 lex.errors.push(ErrorComp::new(
+                    ErrorCode::UnterminatedComment,
                     format!("missing {} block comment terminators `*/`", depth),
                     SourceRange::new(lex.module_id, range),
                     None,
@@ -125,6 +150,7 @@ fn lex_char(lex: &mut Lexer) {
             if c == '\n' || c == '\r' {
                 let range = lex.make_range(start);
                 lex.errors.push(ErrorComp::new(
+                    ErrorCode::InvalidCharLiteral,
                     "character literal is incomplete",
                     SourceRange::new(lex.module_id, range),
                     None,
@@ -136,6 +162,7 @@ fn lex_char(lex: &mut Lexer) {
         None => {
             let range = lex.make_range(start);
             lex.errors.push(ErrorComp::new(
+                ErrorCode::InvalidCharLiteral,
                 "character literal is incomplete",
                 SourceRange::new(lex.module_id, range),
                 None,
@@ -157,6 +184,7 @@ fn lex_char(lex: &mut Lexer) {
             lex.eat(fc);
             let range = lex.make_range(start);
             lex.errors.push(ErrorComp::new(
+                ErrorCode::InvalidCharLiteral,
                 "character literal tab must be escaped: `\\t`",
                 SourceRange::new(lex.module_id, range),
                 None,
@@ -172,6 +200,23 @@ fn lex_char(lex: &mut Lexer) {
     let terminated = matches!(lex.peek(), Some('\''));
     if terminated {
         lex.eat('\'');
+    } else if !inner_tick && closing_tick_on_line(lex) {
+        // example [ 'ab', '\n\t' ]
+        while let Some(c) = lex.peek() {
+            lex.eat(c);
+            if c == '\'' {
+                break;
+            }
+        }
+        let range = lex.make_range(start);
+        lex.errors.push(ErrorComp::new(
+            ErrorCode::InvalidCharLiteral,
+            "character literal must contain exactly one character\nuse a string literal for more characters",
+            SourceRange::new(lex.module_id, range),
+            None,
+        ));
+        lex.tokens().add_char(char, range);
+        return;
     }
     let range = lex.make_range(start);
 
@@ -179,6 +224,7 @@ fn lex_char(lex: &mut Lexer) {
         (true, false) => {
             // example [ '' ]
             lex.errors.push(ErrorComp::new(
+                ErrorCode::InvalidCharLiteral,
                 "character literal cannot be empty",
                 SourceRange::new(lex.module_id, range),
                 None,
@@ -187,6 +233,7 @@ fn lex_char(lex: &mut Lexer) {
         (true, true) => {
             // example [ ''' ]
             lex.errors.push(ErrorComp::new(
+                ErrorCode::InvalidCharLiteral,
                 "character literal `'` must be escaped: `\\'`",
                 SourceRange::new(lex.module_id, range),
                 None,
@@ -195,6 +242,7 @@ fn lex_char(lex: &mut Lexer) {
         (false, false) => {
             // example [ 'x, '\n ]
             lex.errors.push(ErrorComp::new(
+                ErrorCode::InvalidCharLiteral,
                 "character literal not terminated, missing closing `'`",
                 SourceRange::new(lex.module_id, range),
                 None,
@@ -209,10 +257,19 @@ fn lex_char(lex: &mut Lexer) {
     lex.tokens().add_char(char, range);
 }
 
-fn lex_string(lex: &mut Lexer, c_string: bool, mut raw: bool) {
+fn closing_tick_on_line(lex: &Lexer) -> bool {
+    let offset: usize = lex.start_range().into();
+    let rest = &lex.source[offset..];
+    let line = rest.split(['\r', '\n']).next().unwrap_or(rest);
+    line.contains('\'')
+}
+
+fn lex_string(lex: &mut Lexer, kind: StringKind, mut raw: bool) {
     let start = lex.start_range();
-    if c_string {
-        lex.eat('c');
+    match kind {
+        StringKind::Regular => {}
+        StringKind::CString => lex.eat('c'),
+        StringKind::Bytes => lex.eat('b'),
     }
     lex.eat('\"');
 
@@ -235,7 +292,7 @@ fn lex_string(lex: &mut Lexer, c_string: bool, mut raw: bool) {
                     break;
                 }
                 '\\' if !raw => {
-                    let escaped = lex_escape(lex, c_string);
+                    let escaped = lex_escape(lex, kind == StringKind::CString);
                     string.push(escaped);
                 }
                 _ => {
@@ -263,7 +320,7 @@ fn lex_string(lex: &mut Lexer, c_string: bool, mut raw: bool) {
         terminated = false;
     }
 
-    lex.tokens().add_string(string, c_string, range);
+    lex.tokens().add_string(string, kind, range);
 
     if !terminated {
         let message: &str = if raw {
@@ -272,6 +329,7 @@ fn lex_string(lex: &mut Lexer, c_string: bool, mut raw: bool) {
             "string literal not terminated, missing closing \""
         };
         lex.errors.push(ErrorComp::new(
+            ErrorCode::UnterminatedString,
             message,
             SourceRange::new(lex.module_id, range),
             None,
@@ -291,6 +349,7 @@ fn lex_escape(lex: &mut Lexer, c_string: bool) -> char {
     } else {
         let range = lex.make_range(start);
         lex.errors.push(ErrorComp::new(
+            ErrorCode::InvalidEscape,
             INCOMPLETE_MSG,
             SourceRange::new(lex.module_id, range),
             None,
@@ -298,39 +357,47 @@ fn lex_escape(lex: &mut Lexer, c_string: bool) -> char {
         return '\\';
     };
 
-    let escaped = match c {
-        'n' => '\n',
-        't' => '\t',
-        'r' => '\r',
-        '0' => '\0',
-        '\'' => '\'',
-        '\"' => '\"',
-        '\\' => '\\',
-        _ => {
-            if c.is_ascii_whitespace() {
-                let range = lex.make_range(start);
-                lex.errors.push(ErrorComp::new(
-                    INCOMPLETE_MSG,
-                    SourceRange::new(lex.module_id, range),
-                    None,
-                ));
-            } else {
-                lex.eat(c);
-                let range = lex.make_range(start);
-                lex.errors.push(ErrorComp::new(
-                    format!("escape sequence `\\{}` is not supported", c),
-                    SourceRange::new(lex.module_id, range),
-                    None,
-                ));
+    let escaped = if c == 'u' {
+        lex_unicode_escape(lex, start)
+    } else {
+        let escaped = match c {
+            'n' => '\n',
+            't' => '\t',
+            'r' => '\r',
+            '0' => '\0',
+            '\'' => '\'',
+            '\"' => '\"',
+            '\\' => '\\',
+            _ => {
+                if c.is_ascii_whitespace() {
+                    let range = lex.make_range(start);
+                    lex.errors.push(ErrorComp::new(
+                        ErrorCode::InvalidEscape,
+                        INCOMPLETE_MSG,
+                        SourceRange::new(lex.module_id, range),
+                        None,
+                    ));
+                } else {
+                    lex.eat(c);
+                    let range = lex.make_range(start);
+                    lex.errors.push(ErrorComp::new(
+                        ErrorCode::InvalidEscape,
+                        format!("escape sequence `\\{}` is not supported", c),
+                        SourceRange::new(lex.module_id, range),
+                        None,
+                    ));
+                }
+                return '\\';
             }
-            return '\\';
-        }
+        };
+        lex.eat(c);
+        escaped
     };
 
-    lex.eat(c);
     if c_string && escaped == '\0' {
         let range = lex.make_range(start);
         lex.errors.push(ErrorComp::new(
+            ErrorCode::CStringNullByte,
             "c string literals cannot contain any `\\0`\nnull terminator is automatically included",
             SourceRange::new(lex.module_id, range),
             None,
@@ -339,6 +406,56 @@ fn lex_escape(lex: &mut Lexer, c_string: bool) -> char {
     escaped
 }
 
+// example [ \u{41}, \u{1F600} ]
+fn lex_unicode_escape(lex: &mut Lexer, start: TextOffset) -> char {
+    lex.eat('u');
+    if lex.peek() != Some('{') {
+        let range = lex.make_range(start);
+        lex.errors.push(ErrorComp::new(
+            ErrorCode::InvalidEscape,
+            "unicode escape must be in braces: `\\u{7FFF}`",
+            SourceRange::new(lex.module_id, range),
+            None,
+        ));
+        return '\\';
+    }
+    lex.eat('{');
+
+    let mut value: u32 = 0;
+    let mut digit_count = 0;
+    while let Some(c) = lex.peek() {
+        match c.to_digit(16) {
+            Some(digit) => value = value.saturating_mul(16).saturating_add(digit),
+            None => break,
+        }
+        lex.eat(c);
+        digit_count += 1;
+    }
+    let terminated = lex.peek() == Some('}');
+    if terminated {
+        lex.eat('}');
+    }
+
+    let range = lex.make_range(start);
+    let message = if !terminated {
+        "unicode escape is incomplete, missing closing `}`"
+    } else if digit_count == 0 || digit_count > 6 {
+        "unicode escape must have 1 to 6 hex digits"
+    } else {
+        match char::from_u32(value) {
+            Some(escaped) => return escaped,
+            None => "unicode escape is not a unicode scalar value",
+        }
+    };
+    lex.errors.push(ErrorComp::new(
+        ErrorCode::InvalidEscape,
+        message,
+        SourceRange::new(lex.module_id, range),
+        None,
+    ));
+    '\\'
+}
+
 fn lex_number(lex: &mut Lexer, fc: char) {
     let start = lex.start_range();
     lex.eat(fc);
@@ -363,6 +480,18 @@ fn lex_number(lex: &mut Lexer, fc: char) {
             break;
         }
     }
+    // optional type suffix: `10u64`, `1.5f32`
+    if let Some(c) = lex.peek() {
+        if c == '_' || c.is_ascii_alphabetic() {
+            while let Some(c) = lex.peek() {
+                if c == '_' || c.is_ascii_alphanumeric() {
+                    lex.eat(c);
+                } else {
+                    break;
+                }
+            }
+        }
+    }
 
     let range = lex.make_range(start);
     let token = if is_float {
@@ -417,10 +546,14 @@ fn lex_symbol(lex: &mut Lexer, fc: char) {
                 ""
             };
             lex.errors.push(ErrorComp::new(
+                ErrorCode::UnknownSymbol,
                 format!("unknown symbol token {:?}{}", fc, extra),
                 SourceRange::new(lex.module_id, range),
                 None,
             ));
+            if lex.with_trivia {
+                lex.tokens().add_trivia(Trivia::Error, range);
+            }
             return;
         }
     };
@@ -1,12 +1,62 @@
 mod grammar;
 mod lexer;
 
+use crate::ast::BasicType;
 use crate::error::ErrorComp;
 use crate::session::ModuleID;
 use crate::token::token_list::TokenList;
+use crate::token::Token;
 
 pub fn lex(source: &str, module_id: ModuleID, with_trivia: bool) -> (TokenList, Vec<ErrorComp>) {
     let mut lex = lexer::Lexer::new(source, module_id, with_trivia);
     grammar::source_file(&mut lex);
     lex.finish()
 }
+
+pub enum NumberLit {
+    Int { val: u64, ty: Option<BasicType> },
+    Float { val: f64, ty: Option<BasicType> },
+}
+
+/// parses the text of `int_lit` or `float_lit` token, including optional type suffix
+/// integer literal with a float suffix is parsed as a float: `10f32`
+pub fn parse_number_lit(string: &str, is_float: bool) -> Result<NumberLit, String> {
+    let suffix_start = string
+        .find(|c: char| c == '_' || c.is_ascii_alphabetic())
+        .unwrap_or(string.len());
+    let (digits, suffix) = string.split_at(suffix_start);
+
+    let ty = if suffix.is_empty() {
+        None
+    } else {
+        match Token::as_keyword(suffix).and_then(Token::as_basic_type) {
+            Some(basic) if basic_is_number(basic) => Some(basic),
+            _ => return Err(format!("invalid number literal suffix `{suffix}`")),
+        }
+    };
+
+    let float_suffix = matches!(ty, Some(BasicType::F16 | BasicType::F32 | BasicType::F64));
+    if is_float || float_suffix {
+        if is_float && ty.is_some() && !float_suffix {
+            return Err(format!(
+                "invalid suffix `{suffix}` for float literal\nexpected `f16`, `f32` or `f64`"
+            ));
+        }
+        match digits.parse::<f64>() {
+            Ok(val) => Ok(NumberLit::Float { val, ty }),
+            Err(error) => Err(format!("parse float error: {}", error)),
+        }
+    } else {
+        match digits.parse::<u64>() {
+            Ok(val) => Ok(NumberLit::Int { val, ty }),
+            Err(error) => Err(format!("parse int error: {}", error)),
+        }
+    }
+}
+
+fn basic_is_number(basic: BasicType) -> bool {
+    !matches!(
+        basic,
+        BasicType::Bool | BasicType::Char | BasicType::Rawptr | BasicType::Void | BasicType::Never
+    )
+}
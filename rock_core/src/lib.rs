@@ -5,25 +5,34 @@ mod arena;
 mod ast;
 pub mod ast_parse;
 mod bitset;
+pub mod build_layout;
 #[cfg(not(target_os = "linux"))]
 #[cfg(feature = "codegen_llvm")]
 pub mod codegen;
+pub mod driver;
+pub mod embed;
 pub mod error;
 pub mod format;
 pub mod fs_env;
 mod hir;
 pub mod hir_lower;
+pub mod index;
 pub mod intern;
-mod lexer;
+pub mod lexer;
+pub mod linker;
 mod macros;
 pub mod package;
 pub mod session;
 pub mod syntax;
+pub mod target;
 mod temp_buffer;
 pub mod text;
 mod timer;
-mod token;
+pub mod token;
+pub mod vfs;
 
+pub use driver::compile;
+pub use embed::compile_str;
 use package::semver::Semver;
 
 /// toolchain version used to build both `rock_cli` and `rock_ls`  
@@ -0,0 +1,241 @@
+use crate::build_layout::BuildKind;
+use crate::error::ErrorComp;
+use crate::target::{TargetArch, TargetOs, TargetTriple};
+use std::path::PathBuf;
+
+/// command line syntax accepted by the linker
+#[derive(Copy, Clone, PartialEq)]
+pub enum LinkerFlavor {
+    Msvc, // `lld-link`, `link.exe`
+    Gnu,  // `cc`, `clang`, `gcc` driver
+}
+
+pub struct Linker {
+    flavor: LinkerFlavor,
+    path: PathBuf,
+}
+
+#[derive(Copy, Clone)]
+pub enum Subsystem {
+    Console,
+    Windows,
+}
+
+pub struct LinkInput {
+    pub kind: BuildKind,
+    pub objects: Vec<PathBuf>,
+    pub output: PathBuf,
+    pub subsystem: Subsystem,
+    pub lib_paths: Vec<PathBuf>,
+    pub libs: Vec<String>,
+    pub nodefaultlib: bool,
+    pub target: TargetTriple,
+}
+
+// searched in order, first linker found in `PATH` is used
+const WINDOWS_LINKERS: [(&str, LinkerFlavor); 2] = [
+    ("lld-link", LinkerFlavor::Msvc),
+    ("link", LinkerFlavor::Msvc),
+];
+const UNIX_LINKERS: [(&str, LinkerFlavor); 3] = [
+    ("cc", LinkerFlavor::Gnu),
+    ("clang", LinkerFlavor::Gnu),
+    ("gcc", LinkerFlavor::Gnu),
+];
+
+impl Linker {
+    /// find system linker for the target platform
+    pub fn find(target: TargetTriple) -> Result<Linker, ErrorComp> {
+        let candidates: &[(&str, LinkerFlavor)] = match target.os() {
+            TargetOs::Windows => &WINDOWS_LINKERS,
+            TargetOs::Linux | TargetOs::Macos => &UNIX_LINKERS,
+        };
+
+        for &(name, flavor) in candidates {
+            if let Some(path) = find_in_path(name) {
+                return Ok(Linker { flavor, path });
+            }
+        }
+
+        let names: Vec<&str> = candidates.iter().map(|&(name, _)| name).collect();
+        Err(ErrorComp::message(format!(
+            "failed to find a linker, tried: `{}`\nmake sure one of them is installed and available in `PATH`",
+            names.join("`, `")
+        )))
+    }
+
+    pub fn flavor(&self) -> LinkerFlavor {
+        self.flavor
+    }
+    pub fn path(&self) -> &PathBuf {
+        &self.path
+    }
+
+    pub fn args(&self, input: &LinkInput) -> Vec<String> {
+        match self.flavor {
+            LinkerFlavor::Msvc => msvc_args(input),
+            LinkerFlavor::Gnu => gnu_args(input),
+        }
+    }
+
+    /// run the linker, its output is captured and reported only on failure
+    pub fn link(&self, input: &LinkInput) -> Result<(), ErrorComp> {
+        let args = self.args(input);
+        let command = format_command(&self.path, &args);
+
+        let output = std::process::Command::new(&self.path)
+            .args(&args)
+            .output()
+            .map_err(|io_error| {
+                ErrorComp::message(format!(
+                    "failed to run linker `{}`\nreason: {}\ncommand: {}",
+                    self.path.to_string_lossy(),
+                    io_error,
+                    command
+                ))
+            })?;
+
+        if output.status.success() {
+            return Ok(());
+        }
+        // `link.exe` reports errors to stdout
+        let mut text = String::from_utf8_lossy(&output.stderr).to_string();
+        text.push_str(&String::from_utf8_lossy(&output.stdout));
+        let exit_code = match output.status.code() {
+            Some(code) => code.to_string(),
+            None => "none".to_string(),
+        };
+
+        Err(ErrorComp::message(format!(
+            "linking failed with exit code: {}\ncommand: {}\n{}",
+            exit_code,
+            command,
+            text.trim_end()
+        )))
+    }
+}
+
+fn msvc_args(input: &LinkInput) -> Vec<String> {
+    let mut args = Vec::new();
+    args.push("/nologo".to_string());
+    for object in input.objects.iter() {
+        args.push(object.to_string_lossy().to_string());
+    }
+    args.push(format!("/out:{}", input.output.to_string_lossy()));
+
+    match input.kind {
+        BuildKind::Debug => {
+            args.push("/opt:ref".into());
+            args.push("/opt:noicf".into());
+            args.push("/opt:nolbr".into());
+        }
+        BuildKind::Release => {
+            args.push("/opt:ref".into());
+            args.push("/opt:icf".into());
+            args.push("/opt:lbr".into());
+        }
+    }
+
+    //@only `main` entry point is supported, support WinMain when such feature is required 29.05.24
+    match input.target.arch() {
+        TargetArch::X86 => args.push("/machine:x86".into()),
+        TargetArch::X86_64 => args.push("/machine:x64".into()),
+        TargetArch::Aarch64 => args.push("/machine:arm64".into()),
+    }
+
+    match input.subsystem {
+        Subsystem::Console => args.push("/subsystem:console".into()),
+        Subsystem::Windows => args.push("/subsystem:windows".into()),
+    }
+
+    for path in input.lib_paths.iter() {
+        args.push(format!("/libpath:{}", path.to_string_lossy()));
+    }
+    for lib in input.libs.iter() {
+        if is_lib_path(lib) {
+            args.push(lib.clone());
+        } else {
+            args.push(format!("{lib}.lib"));
+        }
+    }
+
+    // link with C runtime library: libcmt.lib (static), msvcrt.lib (dynamic)
+    //@always linking with static C runtime library, support attributes or toml configs 29.05.24
+    if input.nodefaultlib {
+        args.push("/nodefaultlib".into());
+    } else {
+        args.push("/defaultlib:libcmt.lib".into());
+    }
+    args
+}
+
+fn gnu_args(input: &LinkInput) -> Vec<String> {
+    let mut args = Vec::new();
+    // `cc` and `gcc` only link for the host, cross linking requires `clang`
+    if input.target != TargetTriple::host() {
+        args.push(format!("--target={}", input.target.as_str()));
+    }
+    for object in input.objects.iter() {
+        args.push(object.to_string_lossy().to_string());
+    }
+    args.push("-o".into());
+    args.push(input.output.to_string_lossy().to_string());
+
+    if let BuildKind::Release = input.kind {
+        args.push("-Wl,--gc-sections".into());
+        args.push("-s".into());
+    }
+
+    for path in input.lib_paths.iter() {
+        args.push(format!("-L{}", path.to_string_lossy()));
+    }
+    for lib in input.libs.iter() {
+        if is_lib_path(lib) {
+            args.push(lib.clone());
+        } else {
+            args.push(format!("-l{lib}"));
+        }
+    }
+
+    if input.nodefaultlib {
+        args.push("-nostdlib".into());
+    }
+    args
+}
+
+// library file like `raylib.lib` or `lib/libfoo.a` is passed as is,
+// plain library name like `kernel32` gets the platform specific form
+fn is_lib_path(lib: &str) -> bool {
+    lib.contains('.') || lib.contains('/') || lib.contains('\\')
+}
+
+fn find_in_path(name: &str) -> Option<PathBuf> {
+    let path_var = std::env::var_os("PATH")?;
+    for dir in std::env::split_paths(&path_var) {
+        let mut path = dir.join(name);
+        if cfg!(target_os = "windows") {
+            path.set_extension("exe");
+        }
+        if path.is_file() {
+            return Some(path);
+        }
+    }
+    None
+}
+
+/// full command with arguments containing spaces quoted
+fn format_command(path: &PathBuf, args: &[String]) -> String {
+    let mut command = format!("`{}", path.to_string_lossy());
+    for arg in args {
+        command.push(' ');
+        if arg.contains(' ') {
+            command.push('"');
+            command.push_str(arg);
+            command.push('"');
+        } else {
+            command.push_str(arg);
+        }
+    }
+    command.push('`');
+    command
+}
@@ -1,13 +1,13 @@
-use super::semver::Semver;
+use super::semver::{Semver, SemverReq};
 use serde::{Deserialize, Serialize};
 use std::collections::BTreeMap;
 use std::path::PathBuf;
 
 #[derive(Serialize, Deserialize)]
 pub struct Manifest {
-    pub package: PackageManifest,               // table key [package]
-    pub build: BuildManifest,                   // table key [build]
-    pub dependencies: BTreeMap<String, Semver>, // table key [dependencies]
+    pub package: PackageManifest,                  // table key [package]
+    pub build: BuildManifest,                      // table key [build]
+    pub dependencies: BTreeMap<String, SemverReq>, // table key [dependencies]
 }
 
 #[derive(Serialize, Deserialize)]
@@ -23,9 +23,11 @@ pub struct PackageManifest {
 #[derive(Serialize, Deserialize)]
 pub struct BuildManifest {
     pub bin_name: Option<String>,        // executable name
+    pub opt_level: Option<OptLevel>,     // optimization level, default depends on build kind
     pub nodefaultlib: Option<bool>,      // dont link against default lib
     pub lib_paths: Option<Vec<PathBuf>>, // library search paths
     pub links: Option<Vec<String>>,      // library names or paths to link against
+    pub unstable_generics: Option<bool>, // allow generic procedures (experimental)
 }
 
 #[derive(Copy, Clone, PartialEq, Serialize, Deserialize)]
@@ -36,6 +38,18 @@ pub enum PackageKind {
     Lib,
 }
 
+#[derive(Copy, Clone, PartialEq, Serialize, Deserialize)]
+pub enum OptLevel {
+    #[serde(rename = "none")]
+    None,
+    #[serde(rename = "less")]
+    Less,
+    #[serde(rename = "default")]
+    Default,
+    #[serde(rename = "aggressive")]
+    Aggressive,
+}
+
 impl PackageKind {
     pub fn as_str(self) -> &'static str {
         match self {
@@ -2,6 +2,7 @@ pub mod manifest;
 pub mod semver;
 
 use crate::error::ErrorComp;
+use manifest::{Manifest, PackageKind};
 use std::path::PathBuf;
 
 pub fn manifest_serialize(manifest: &manifest::Manifest) -> Result<String, ErrorComp> {
@@ -25,3 +26,109 @@ pub fn manifest_deserialize(
         ))
     })
 }
+
+pub fn package_name_check(name: &str) -> Result<(), ErrorComp> {
+    match package_name_error(name) {
+        Some(message) => Err(ErrorComp::message(message)),
+        None => Ok(()),
+    }
+}
+
+fn package_name_error(name: &str) -> Option<String> {
+    let mut chars = name.chars();
+    match chars.next() {
+        Some(c) => {
+            if !(c == '_' || c.is_ascii_alphabetic()) {
+                return Some(format!(
+                    "package name must be a valid identifier, first `{}` is not allowed",
+                    c
+                ));
+            }
+        }
+        None => return Some("package name cannot be empty".into()),
+    }
+    for c in chars {
+        if !(c == '_' || c.is_ascii_alphanumeric()) {
+            return Some(format!(
+                "package name must be a valid identifier, inner `{}` is not allowed",
+                c
+            ));
+        }
+    }
+    None
+}
+
+/// validates manifest values which cannot be expressed by its format,
+/// errors include the manifest path and the table key which caused them
+pub fn manifest_validate(manifest: &Manifest, manifest_path: &PathBuf) -> Result<(), ErrorComp> {
+    let location = |key: &str| {
+        format!(
+            "\nmanifest path: `{}`\nmanifest key: {key}",
+            manifest_path.to_string_lossy()
+        )
+    };
+
+    let package = &manifest.package;
+    if let Some(message) = package_name_error(&package.name) {
+        let location = location("[package] `name`");
+        return Err(ErrorComp::message(format!("{message}{location}")));
+    }
+
+    if let Some(bin_name) = &manifest.build.bin_name {
+        if package.kind != PackageKind::Bin {
+            return Err(ErrorComp::message(format!(
+                "executable name can only be set for `bin` packages{}",
+                location("[build] `bin_name`")
+            )));
+        }
+        if bin_name.is_empty() || bin_name.contains(['/', '\\']) {
+            return Err(ErrorComp::message(format!(
+                "executable name `{bin_name}` must be a non-empty file name{}",
+                location("[build] `bin_name`")
+            )));
+        }
+    }
+
+    if let Some(lib_paths) = &manifest.build.lib_paths {
+        let root_dir = manifest_path.parent().unwrap_or(manifest_path);
+        let location = location("[build] `lib_paths`");
+        //@relative path doesnt guarantee that libraries
+        // are located within the same package (eg: ../../dir)
+        for path in lib_paths {
+            if !path.is_relative() {
+                return Err(ErrorComp::message(format!(
+                    "library path `{}` must be relative{location}",
+                    path.to_string_lossy()
+                )));
+            }
+            let lib_path = root_dir.join(path);
+            if !lib_path.exists() {
+                return Err(ErrorComp::message(format!(
+                    "library path `{}` does not exist{location}",
+                    lib_path.to_string_lossy()
+                )));
+            }
+            if !lib_path.is_dir() {
+                return Err(ErrorComp::message(format!(
+                    "library path `{}` must be a directory{location}",
+                    lib_path.to_string_lossy()
+                )));
+            }
+        }
+    }
+
+    for name in manifest.dependencies.keys() {
+        let location = location("[dependencies]");
+        if let Some(message) = package_name_error(name) {
+            return Err(ErrorComp::message(format!(
+                "invalid dependency `{name}`\n{message}{location}"
+            )));
+        }
+        if *name == package.name {
+            return Err(ErrorComp::message(format!(
+                "package `{name}` cannot depend on itself{location}"
+            )));
+        }
+    }
+    Ok(())
+}
@@ -1,6 +1,7 @@
 use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
 
-#[derive(Copy, Clone)]
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord)]
 pub struct Semver {
     major: u32,
     minor: u32,
@@ -78,3 +79,217 @@ impl std::str::FromStr for Semver {
         })
     }
 }
+
+/// version requirement, list of comparators which all must match
+/// `1.2.3` and `^1.2.3` allow semver compatible updates
+/// `=1.2` allows any patch, comparisons can be combined: `>=1.2, <1.5`
+#[derive(Clone, PartialEq)]
+pub struct SemverReq {
+    comparators: Vec<Comparator>,
+}
+
+#[derive(Copy, Clone, PartialEq)]
+struct Comparator {
+    op: ReqOp,
+    major: u32,
+    minor: Option<u32>,
+    patch: Option<u32>,
+}
+
+#[derive(Copy, Clone, PartialEq)]
+enum ReqOp {
+    Caret,
+    Exact,
+    Greater,
+    GreaterEq,
+    Less,
+    LessEq,
+}
+
+impl SemverReq {
+    pub fn caret(version: Semver) -> SemverReq {
+        SemverReq {
+            comparators: vec![Comparator {
+                op: ReqOp::Caret,
+                major: version.major,
+                minor: Some(version.minor),
+                patch: Some(version.patch),
+            }],
+        }
+    }
+
+    pub fn matches(&self, version: Semver) -> bool {
+        self.comparators.iter().all(|comp| comp.matches(version))
+    }
+}
+
+impl Comparator {
+    fn matches(&self, version: Semver) -> bool {
+        match self.op {
+            ReqOp::Caret => {
+                let lower =
+                    Semver::new(self.major, self.minor.unwrap_or(0), self.patch.unwrap_or(0));
+                if version < lower {
+                    return false;
+                }
+                match (self.major, self.minor, self.patch) {
+                    (0, Some(0), Some(patch)) => version == Semver::new(0, 0, patch),
+                    (0, Some(minor), _) => version.major == 0 && version.minor == minor,
+                    (major, _, _) => version.major == major,
+                }
+            }
+            ReqOp::Exact => self.cmp_prefix(version) == Ordering::Equal,
+            ReqOp::Greater => self.cmp_prefix(version) == Ordering::Greater,
+            ReqOp::GreaterEq => self.cmp_prefix(version) != Ordering::Less,
+            ReqOp::Less => self.cmp_prefix(version) == Ordering::Less,
+            ReqOp::LessEq => self.cmp_prefix(version) != Ordering::Greater,
+        }
+    }
+
+    // compares only the parts specified in the comparator: `1.2` ignores patch
+    fn cmp_prefix(&self, version: Semver) -> Ordering {
+        let ord = version.major.cmp(&self.major);
+        let ord = match self.minor {
+            Some(minor) => ord.then(version.minor.cmp(&minor)),
+            None => return ord,
+        };
+        match self.patch {
+            Some(patch) => ord.then(version.patch.cmp(&patch)),
+            None => ord,
+        }
+    }
+}
+
+impl ReqOp {
+    fn as_str(self) -> &'static str {
+        match self {
+            ReqOp::Caret => "",
+            ReqOp::Exact => "=",
+            ReqOp::Greater => ">",
+            ReqOp::GreaterEq => ">=",
+            ReqOp::Less => "<",
+            ReqOp::LessEq => "<=",
+        }
+    }
+}
+
+impl std::fmt::Display for SemverReq {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for (idx, comp) in self.comparators.iter().enumerate() {
+            if idx != 0 {
+                write!(f, ", ")?;
+            }
+            write!(f, "{}{}", comp.op.as_str(), comp.major)?;
+            if let Some(minor) = comp.minor {
+                write!(f, ".{}", minor)?;
+            }
+            if let Some(patch) = comp.patch {
+                write!(f, ".{}", patch)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Serialize for SemverReq {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.serialize_str(&self.to_string())
+    }
+}
+
+impl<'de> Deserialize<'de> for SemverReq {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let s = String::deserialize(deserializer)?;
+        s.parse::<SemverReq>().map_err(serde::de::Error::custom)
+    }
+}
+
+impl std::str::FromStr for SemverReq {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut comparators = Vec::new();
+        for part in s.split(',') {
+            comparators.push(part.trim().parse::<Comparator>()?);
+        }
+        Ok(SemverReq { comparators })
+    }
+}
+
+impl std::str::FromStr for Comparator {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (op, version) = if let Some(rest) = s.strip_prefix(">=") {
+            (ReqOp::GreaterEq, rest)
+        } else if let Some(rest) = s.strip_prefix("<=") {
+            (ReqOp::LessEq, rest)
+        } else if let Some(rest) = s.strip_prefix('>') {
+            (ReqOp::Greater, rest)
+        } else if let Some(rest) = s.strip_prefix('<') {
+            (ReqOp::Less, rest)
+        } else if let Some(rest) = s.strip_prefix('=') {
+            (ReqOp::Exact, rest)
+        } else if let Some(rest) = s.strip_prefix('^') {
+            (ReqOp::Caret, rest)
+        } else {
+            (ReqOp::Caret, s)
+        };
+
+        let version = version.trim();
+        if version.is_empty() {
+            return Err(format!(
+                "invalid version requirement `{s}`, missing version"
+            ));
+        }
+        let parts: Vec<&str> = version.split('.').collect();
+        if parts.len() > 3 {
+            return Err(format!(
+                "invalid version requirement `{s}`, expected at most 3 numbers separated by `.`"
+            ));
+        }
+
+        let mut numbers = [None; 3];
+        for (idx, part) in parts.iter().enumerate() {
+            let number = part.parse::<u32>().map_err(|error| {
+                format!("failed to parse version requirement `{s}`\nreason: {error}")
+            })?;
+            numbers[idx] = Some(number);
+        }
+
+        Ok(Comparator {
+            op,
+            major: numbers[0].unwrap_or(0),
+            minor: numbers[1],
+            patch: numbers[2],
+        })
+    }
+}
+
+#[test]
+fn test_semver_req() {
+    let req = |s: &str| s.parse::<SemverReq>().unwrap();
+    let ver = |s: &str| s.parse::<Semver>().unwrap();
+
+    assert!(req("1.2.3").matches(ver("1.9.0")));
+    assert!(!req("1.2.3").matches(ver("1.2.2")));
+    assert!(!req("^1.2").matches(ver("2.0.0")));
+    assert!(req("0.1.0").matches(ver("0.1.7")));
+    assert!(!req("0.1.0").matches(ver("0.2.0")));
+    assert!(!req("^0.0.3").matches(ver("0.0.4")));
+    assert!(req("=0.3").matches(ver("0.3.9")));
+    assert!(!req("=0.3.1").matches(ver("0.3.2")));
+    assert!(req(">=1.2, <1.5").matches(ver("1.4.9")));
+    assert!(!req(">=1.2, <1.5").matches(ver("1.5.0")));
+    assert!(req("<=1.2").matches(ver("1.2.9")));
+    assert!(!req(">1.2").matches(ver("1.2.9")));
+    assert!("1.2.3.4".parse::<SemverReq>().is_err());
+    assert!(">=".parse::<SemverReq>().is_err());
+    assert_eq!(req(">=1.2,<2").to_string(), ">=1.2, <2");
+}
@@ -1,15 +1,21 @@
+mod resolve;
+
 use crate::error::ErrorComp;
 use crate::fs_env;
 use crate::id_impl;
 use crate::intern::{InternID, InternPool};
 use crate::package;
-use crate::package::manifest::{Manifest, PackageKind};
+use crate::package::manifest::{BuildManifest, Manifest, PackageKind, PackageManifest};
+use crate::package::semver::Semver;
+use crate::target::TargetTriple;
 use crate::text::{self, TextRange};
-use std::collections::HashMap;
+use crate::vfs::{EntryKind, FileProvider, MemoryFs};
+use std::collections::{BTreeMap, HashMap};
 use std::path::PathBuf;
 
 pub struct Session {
     cwd: PathBuf,
+    target: TargetTriple,
     modules: Vec<RockModule>,
     packages: Vec<RockPackage>,
 }
@@ -37,6 +43,8 @@ pub struct RockModule {
     pub source: String,
     pub line_ranges: Vec<TextRange>,
     pub package_id: PackageID,
+    /// byte order mark was removed from `source`, editors exclude it from positions
+    pub bom: bool,
 }
 
 impl Session {
@@ -44,14 +52,82 @@ impl Session {
 
     pub fn new<'intern>(
         building: bool,
-        file_cache: Option<&HashMap<PathBuf, String>>,
+        target: TargetTriple,
+        files: &dyn FileProvider,
+    ) -> Result<(Session, InternPool<'intern>), ErrorComp> {
+        let root_dir = fs_env::dir_get_current_working()?;
+        session_create(root_dir, building, target, files)
+    }
+
+    /// create session for a package rooted at `root_dir`, instead of current directory
+    pub fn new_at<'intern>(
+        root_dir: PathBuf,
+        building: bool,
+        target: TargetTriple,
+        files: &dyn FileProvider,
     ) -> Result<(Session, InternPool<'intern>), ErrorComp> {
-        session_create(building, file_cache)
+        session_create(root_dir, building, target, files)
+    }
+
+    /// create session for a single library package from in-memory modules,
+    /// `files` paths are relative to `src`, file system is not accessed
+    pub fn new_virtual<'intern>(
+        root_dir: PathBuf,
+        target: TargetTriple,
+        files: &[(PathBuf, String)],
+    ) -> Result<(Session, InternPool<'intern>), ErrorComp> {
+        let mut session = Session {
+            cwd: root_dir.clone(),
+            target,
+            modules: Vec::new(),
+            packages: Vec::new(),
+        };
+        let mut intern_name = InternPool::with_names();
+
+        let manifest = Manifest {
+            package: PackageManifest {
+                name: "virtual".to_string(),
+                kind: PackageKind::Lib,
+                version: Semver::new(0, 1, 0),
+                authors: None,
+                repository: None,
+                description: None,
+            },
+            build: BuildManifest {
+                bin_name: None,
+                opt_level: None,
+                nodefaultlib: None,
+                lib_paths: None,
+                links: None,
+                unstable_generics: None,
+            },
+            dependencies: BTreeMap::new(),
+        };
+
+        let mut memory = MemoryFs::new();
+        let src_dir = root_dir.join("src");
+        for (path, source) in files {
+            memory.insert(src_dir.join(path), source.clone());
+        }
+
+        let root_id = process_package(
+            &mut session,
+            &mut intern_name,
+            &memory,
+            &root_dir,
+            manifest,
+            false,
+        )?;
+        assert!(root_id == Session::ROOT_ID);
+        Ok((session, intern_name))
     }
 
     pub fn cwd(&self) -> &PathBuf {
         &self.cwd
     }
+    pub fn target(&self) -> TargetTriple {
+        self.target
+    }
     pub fn module(&self, id: ModuleID) -> &RockModule {
         &self.modules[id.index()]
     }
@@ -73,6 +149,12 @@ impl RockPackage {
     pub fn dependency(&self, name_id: InternID) -> Option<PackageID> {
         self.dependency_map.get(&name_id).copied()
     }
+    /// sorted by `PackageID`
+    pub fn dependency_ids(&self) -> Vec<PackageID> {
+        let mut dependency_ids: Vec<PackageID> = self.dependency_map.values().copied().collect();
+        dependency_ids.sort_by_key(|id| id.index());
+        dependency_ids
+    }
 }
 
 pub enum ModuleOrDirectory<'src> {
@@ -96,23 +178,30 @@ impl RockDirectory {
         }
         ModuleOrDirectory::None
     }
+    pub fn find_dir(&self, name_id: InternID) -> Option<&RockDirectory> {
+        self.sub_dirs
+            .iter()
+            .find(|directory| directory.name_id == name_id)
+    }
 }
 
 //@store file_count to be able to iterate over FileIDs or ModuleIDs of specific package
 fn session_create<'intern>(
+    root_dir: PathBuf,
     building: bool,
-    file_cache: Option<&HashMap<PathBuf, String>>,
+    target: TargetTriple,
+    files: &dyn FileProvider,
 ) -> Result<(Session, InternPool<'intern>), ErrorComp> {
     let mut session = Session {
-        cwd: fs_env::dir_get_current_working()?,
+        cwd: root_dir,
+        target,
         modules: Vec::new(),
         packages: Vec::new(),
     };
-    let mut intern_name = InternPool::new();
+    let mut intern_name = InternPool::with_names();
 
     let root_dir = session.cwd.clone();
-    let root_id = process_package(&mut session, &mut intern_name, file_cache, &root_dir, false)?;
-    let root_manifest = &session.package(root_id).manifest;
+    let root_manifest = manifest_load(files, &root_dir, false)?;
 
     if building && root_manifest.package.kind == PackageKind::Lib {
         return Err(ErrorComp::message(
@@ -127,51 +216,107 @@ or you can change [package] `kind` to `bin` in the Rock.toml manifest"#,
     cache_dir.pop();
     cache_dir.push("packages");
 
-    //@no version resolution or transitive dependencies (only root deps)
-    let root_dependencies: Vec<String> = root_manifest
-        .dependencies
-        .iter()
-        .map(|(name, _)| name.clone())
-        .collect();
-    let mut root_dependency_map = HashMap::new();
-
-    for dependency in root_dependencies.iter() {
-        let package_id = process_package(
+    let resolved = resolve::resolve(&root_manifest, |name| {
+        fetch_candidates(files, &cache_dir, name)
+    })?;
+
+    let root_id = process_package(
+        &mut session,
+        &mut intern_name,
+        files,
+        &root_dir,
+        root_manifest,
+        false,
+    )?;
+    assert!(root_id == Session::ROOT_ID);
+
+    for candidate in resolved {
+        process_package(
             &mut session,
             &mut intern_name,
-            file_cache,
-            &cache_dir.join(dependency),
+            files,
+            &candidate.root_dir,
+            candidate.manifest,
             true,
         )?;
-        let name_id = session.package(package_id).name_id;
-        root_dependency_map.insert(name_id, package_id);
     }
 
-    //@only creating dependency map for root
-    // package resultion process is not done yet
-    session.packages[0].dependency_map = root_dependency_map;
+    // each package name is resolved to a single version
+    let package_map: HashMap<InternID, PackageID> = session
+        .package_ids()
+        .map(|package_id| (session.package(package_id).name_id, package_id))
+        .collect();
+
+    for package_id in session.package_ids() {
+        let package = &mut session.packages[package_id.index()];
+        for name in package.manifest.dependencies.keys() {
+            let name_id = intern_name.intern(name);
+            if let Some(&dependency_id) = package_map.get(&name_id) {
+                package.dependency_map.insert(name_id, dependency_id);
+            }
+        }
+    }
     Ok((session, intern_name))
 }
 
-fn process_package(
-    session: &mut Session,
-    intern_name: &mut InternPool,
-    file_cache: Option<&HashMap<PathBuf, String>>,
-    root_dir: &PathBuf,
-    dependency: bool,
-) -> Result<PackageID, ErrorComp> {
-    let package_name = fs_env::filename_stem(root_dir)?;
-    let name_id = intern_name.intern(package_name);
-
-    if dependency && !root_dir.exists() {
+// `packages/name` contains a single version,
+// or a sub directory for each available version
+fn fetch_candidates(
+    files: &dyn FileProvider,
+    cache_dir: &PathBuf,
+    name: &str,
+) -> Result<Vec<resolve::Candidate>, ErrorComp> {
+    let package_dir = cache_dir.join(name);
+    if !files.exists(&package_dir) {
         return Err(ErrorComp::message(format!(
             "could not find package directory, package fetch is not yet implemented\nexpected path: `{}`",
-            root_dir.to_string_lossy()
+            package_dir.to_string_lossy()
         )));
     }
+    if files.exists(&package_dir.join("Rock.toml")) {
+        let candidate = load_candidate(files, package_dir, name)?;
+        return Ok(vec![candidate]);
+    }
 
+    let mut candidates = Vec::new();
+    for entry in files.read_dir(&package_dir)? {
+        if entry.kind == EntryKind::Dir && files.exists(&entry.path.join("Rock.toml")) {
+            candidates.push(load_candidate(files, entry.path, name)?);
+        }
+    }
+
+    if candidates.is_empty() {
+        return Err(ErrorComp::message(format!(
+            "could not find any version of package `{name}`\nexpected path: `{}`",
+            package_dir.to_string_lossy()
+        )));
+    }
+    Ok(candidates)
+}
+
+fn load_candidate(
+    files: &dyn FileProvider,
+    root_dir: PathBuf,
+    name: &str,
+) -> Result<resolve::Candidate, ErrorComp> {
+    let manifest = manifest_load(files, &root_dir, true)?;
+    if manifest.package.name != name {
+        return Err(ErrorComp::message(format!(
+            "dependency `{name}` has mismatched package name `{}`\nmanifest path: `{}`",
+            manifest.package.name,
+            root_dir.join("Rock.toml").to_string_lossy()
+        )));
+    }
+    Ok(resolve::Candidate { root_dir, manifest })
+}
+
+fn manifest_load(
+    files: &dyn FileProvider,
+    root_dir: &PathBuf,
+    dependency: bool,
+) -> Result<Manifest, ErrorComp> {
     let manifest_path = root_dir.join("Rock.toml");
-    if !manifest_path.exists() {
+    if !files.exists(&manifest_path) {
         let in_kind = if dependency { "dependency" } else { "current" };
         return Err(ErrorComp::message(format!(
             "could not find manifest `Rock.toml` in {in_kind} directory\npath: `{}`",
@@ -179,17 +324,31 @@ fn process_package(
         )));
     }
 
-    let manifest_text = fs_env::file_read_to_string(&manifest_path)?;
+    let manifest_text = files.read_to_string(&manifest_path)?;
     let manifest = package::manifest_deserialize(manifest_text, &manifest_path)?;
+    package::manifest_validate(&manifest, &manifest_path)?;
     if dependency && manifest.package.kind == PackageKind::Bin {
-        //@which dependency and for which package and where? not enough information
-        return Err(ErrorComp::message(
-            "cannot depend on executable package, only library dependencies are allowed",
-        ));
+        return Err(ErrorComp::message(format!(
+            "cannot depend on executable package `{}`, only library dependencies are allowed\nmanifest path: `{}`",
+            manifest.package.name,
+            manifest_path.to_string_lossy()
+        )));
     }
+    Ok(manifest)
+}
+
+fn process_package(
+    session: &mut Session,
+    intern_name: &mut InternPool,
+    files: &dyn FileProvider,
+    root_dir: &PathBuf,
+    manifest: Manifest,
+    dependency: bool,
+) -> Result<PackageID, ErrorComp> {
+    let name_id = intern_name.intern(&manifest.package.name);
 
     let src_dir = root_dir.join("src");
-    if !src_dir.exists() {
+    if files.kind(&src_dir) != Some(EntryKind::Dir) {
         //@duplicate, standardize `in` `kind` directory vs package messaging
         // for package related errors
         let in_kind = if dependency { "dependency" } else { "current" };
@@ -199,44 +358,14 @@ fn process_package(
         )));
     }
     let package_id = PackageID::new(session.packages.len());
-    let src = process_directory(session, intern_name, file_cache, package_id, src_dir)?;
-
-    if let Some(lib_paths) = &manifest.build.lib_paths {
-        let location = format!(
-            "\nmanifest path: `{}`\nmanifest key: [build] `lib_paths`",
-            manifest_path.to_string_lossy()
-        );
-        //@relative path doesnt guarantee that libraries
-        // are located within the same package (eg: ../../dir)
-        for path in lib_paths {
-            if !path.is_relative() {
-                return Err(ErrorComp::message(format!(
-                    "library path `{}` must be relative{location}",
-                    path.to_string_lossy()
-                )));
-            }
-            let lib_path = root_dir.join(path);
-            if !lib_path.exists() {
-                return Err(ErrorComp::message(format!(
-                    "library path `{}` does not exist{location}",
-                    lib_path.to_string_lossy()
-                )));
-            }
-            if !lib_path.is_dir() {
-                return Err(ErrorComp::message(format!(
-                    "library path `{}` must be a directory{location}",
-                    lib_path.to_string_lossy()
-                )));
-            }
-        }
-    }
+    let src = process_directory(session, intern_name, files, package_id, src_dir)?;
 
     let package = RockPackage {
         name_id,
         root_dir: root_dir.clone(),
         src,
         manifest,
-        dependency_map: HashMap::new(), //@no deps are set
+        dependency_map: HashMap::new(),
     };
     session.packages.push(package);
     Ok(package_id)
@@ -245,7 +374,7 @@ fn process_package(
 fn process_directory(
     session: &mut Session,
     intern_name: &mut InternPool,
-    file_cache: Option<&HashMap<PathBuf, String>>,
+    files: &dyn FileProvider,
     package_id: PackageID,
     path: PathBuf,
 ) -> Result<RockDirectory, ErrorComp> {
@@ -254,33 +383,28 @@ fn process_directory(
     let mut modules = Vec::new();
     let mut sub_dirs = Vec::new();
 
-    let read_dir = fs_env::dir_read(&path)?;
-    for entry_result in read_dir {
-        let entry = fs_env::dir_entry_validate(&path, entry_result)?;
-        let entry_path = entry.path();
-        fs_env::symlink_forbid(&entry_path)?;
+    for entry in files.read_dir(&path)? {
+        let entry_path = entry.path;
 
-        if entry_path.is_file() {
+        if entry.kind == EntryKind::File {
             let extension = fs_env::file_extension(&entry_path);
             if matches!(extension, Some("rock")) {
                 modules.push(process_file(
                     session,
                     intern_name,
-                    file_cache,
+                    files,
                     package_id,
                     entry_path,
                 )?);
             }
-        } else if entry_path.is_dir() {
+        } else {
             sub_dirs.push(process_directory(
                 session,
                 intern_name,
-                file_cache,
+                files,
                 package_id,
                 entry_path,
             )?);
-        } else {
-            unreachable!()
         }
     }
 
@@ -296,13 +420,14 @@ fn process_directory(
 fn process_file(
     session: &mut Session,
     intern_name: &mut InternPool,
-    file_cache: Option<&HashMap<PathBuf, String>>,
+    files: &dyn FileProvider,
     package_id: PackageID,
     path: PathBuf,
 ) -> Result<ModuleID, ErrorComp> {
     let filename = fs_env::filename_stem(&path)?;
     let name_id = intern_name.intern(filename);
-    let source = read_file(&path, file_cache)?;
+    let mut source = files.read_to_string(&path)?;
+    let bom = text::strip_bom(&mut source);
     let line_ranges = text::find_line_ranges(&source);
 
     let module = RockModule {
@@ -311,21 +436,10 @@ fn process_file(
         source,
         line_ranges,
         package_id,
+        bom,
     };
 
     let module_id = ModuleID::new(session.modules.len());
     session.modules.push(module);
     Ok(module_id)
 }
-
-fn read_file(
-    path: &PathBuf,
-    file_cache: Option<&HashMap<PathBuf, String>>,
-) -> Result<String, ErrorComp> {
-    if let Some(file_cache) = file_cache {
-        if let Some(source) = file_cache.get(path) {
-            return Ok(source.clone());
-        }
-    }
-    Ok(fs_env::file_read_to_string(&path)?)
-}
@@ -0,0 +1,127 @@
+use crate::error::ErrorComp;
+use crate::package::manifest::Manifest;
+use crate::package::semver::SemverReq;
+use std::collections::{BTreeMap, BTreeSet, VecDeque};
+use std::path::PathBuf;
+
+pub struct Candidate {
+    pub root_dir: PathBuf,
+    pub manifest: Manifest,
+}
+
+struct Requirement {
+    req: SemverReq,
+    chain: Vec<String>,
+}
+
+// selection can change requirements of the next iteration,
+// limit protects against selections that never settle
+const RESOLVE_ITERATION_LIMIT: u32 = 64;
+
+/// selects a single version of each package reachable from the root manifest,
+/// highest version which matches all requirements on that package is picked
+pub fn resolve(
+    root: &Manifest,
+    mut fetch: impl FnMut(&str) -> Result<Vec<Candidate>, ErrorComp>,
+) -> Result<Vec<Candidate>, ErrorComp> {
+    let mut candidates: BTreeMap<String, Vec<Candidate>> = BTreeMap::new();
+    let mut selected: BTreeMap<String, usize> = BTreeMap::new();
+
+    for _ in 0..RESOLVE_ITERATION_LIMIT {
+        let requirements = collect_requirements(root, &candidates, &selected);
+        let mut next_selected = BTreeMap::new();
+
+        for (name, reqs) in requirements.iter() {
+            if !candidates.contains_key(name) {
+                let mut fetched = fetch(name)?;
+                fetched.sort_by_key(|p| std::cmp::Reverse(p.manifest.package.version));
+                candidates.insert(name.clone(), fetched);
+            }
+
+            let available = &candidates[name];
+            let found = available.iter().position(|candidate| {
+                let version = candidate.manifest.package.version;
+                reqs.iter().all(|req| req.req.matches(version))
+            });
+            match found {
+                Some(idx) => next_selected.insert(name.clone(), idx),
+                None => return Err(conflict_error(name, reqs, available)),
+            };
+        }
+
+        if next_selected == selected {
+            let resolved = selected
+                .into_iter()
+                .map(|(name, idx)| {
+                    let available = candidates.get_mut(&name).unwrap();
+                    available.swap_remove(idx)
+                })
+                .collect();
+            return Ok(resolved);
+        }
+        selected = next_selected;
+    }
+
+    Err(ErrorComp::message(format!(
+        "failed to resolve dependencies of package `{}`, selected versions did not settle after {RESOLVE_ITERATION_LIMIT} iterations",
+        root.package.name
+    )))
+}
+
+// requirement chains are collected breadth first, to report the shortest ones
+fn collect_requirements<'m>(
+    root: &'m Manifest,
+    candidates: &'m BTreeMap<String, Vec<Candidate>>,
+    selected: &BTreeMap<String, usize>,
+) -> BTreeMap<String, Vec<Requirement>> {
+    let mut requirements: BTreeMap<String, Vec<Requirement>> = BTreeMap::new();
+    let mut visited = BTreeSet::new();
+    let mut queue = VecDeque::new();
+    queue.push_back((root, vec![package_label(root)]));
+
+    while let Some((manifest, chain)) = queue.pop_front() {
+        for (name, req) in manifest.dependencies.iter() {
+            requirements
+                .entry(name.clone())
+                .or_default()
+                .push(Requirement {
+                    req: req.clone(),
+                    chain: chain.clone(),
+                });
+
+            if let Some(&idx) = selected.get(name) {
+                if visited.insert(name.as_str()) {
+                    let dependency = &candidates[name][idx].manifest;
+                    let mut chain = chain.clone();
+                    chain.push(package_label(dependency));
+                    queue.push_back((dependency, chain));
+                }
+            }
+        }
+    }
+    requirements
+}
+
+fn package_label(manifest: &Manifest) -> String {
+    format!("`{} {}`", manifest.package.name, manifest.package.version)
+}
+
+fn conflict_error(name: &str, reqs: &[Requirement], available: &[Candidate]) -> ErrorComp {
+    let versions: Vec<String> = available
+        .iter()
+        .map(|candidate| candidate.manifest.package.version.to_string())
+        .collect();
+
+    let mut message = format!(
+        "failed to resolve dependency `{name}`, no version matches all requirements\navailable versions: {}\nrequired by:",
+        versions.join(", ")
+    );
+    for req in reqs {
+        message.push_str(&format!(
+            "\n  {} requires `{name} {}`",
+            req.chain.join(" -> "),
+            req.req
+        ));
+    }
+    ErrorComp::message(message)
+}
@@ -2,8 +2,9 @@ use super::ast_layer as cst;
 use super::syntax_tree::SyntaxTree;
 use crate::arena::Arena;
 use crate::ast;
-use crate::error::{DiagnosticCollection, ErrorComp, ResultComp, SourceRange};
+use crate::error::{DiagnosticCollection, ErrorCode, ErrorComp, ResultComp, SourceRange};
 use crate::intern::InternPool;
+use crate::lexer::{self, NumberLit};
 use crate::session::{ModuleID, Session};
 use crate::temp_buffer::TempBuffer;
 use crate::text::TextRange;
@@ -23,15 +24,15 @@ struct AstBuildState<'ast> {
     arena: Arena<'ast>,
     intern_name: InternPool<'ast>,
     intern_string: InternPool<'ast>,
-    string_is_cstr: Vec<bool>,
     modules: Vec<ast::Module<'ast>>,
     errors: Vec<ErrorComp>,
 
     items: TempBuffer<ast::Item<'ast>>,
-    attrs: TempBuffer<ast::Attribute>,
+    attrs: TempBuffer<ast::Attribute<'ast>>,
     params: TempBuffer<ast::ProcParam<'ast>>,
     variants: TempBuffer<ast::EnumVariant<'ast>>,
     fields: TempBuffer<ast::StructField<'ast>>,
+    impl_procs: TempBuffer<&'ast ast::ProcItem<'ast>>,
     import_symbols: TempBuffer<ast::ImportSymbol>,
     names: TempBuffer<ast::Name>,
     types: TempBuffer<ast::Type<'ast>>,
@@ -39,6 +40,7 @@ struct AstBuildState<'ast> {
     exprs: TempBuffer<&'ast ast::Expr<'ast>>,
     branches: TempBuffer<ast::Branch<'ast>>,
     match_arms: TempBuffer<ast::MatchArm<'ast>>,
+    match_pats: TempBuffer<ast::MatchPat<'ast>>,
     field_inits: TempBuffer<ast::FieldInit<'ast>>,
 }
 
@@ -66,7 +68,6 @@ impl<'ast> AstBuildState<'ast> {
             arena: Arena::new(),
             intern_name,
             intern_string: InternPool::new(),
-            string_is_cstr: Vec::with_capacity(128),
             modules: Vec::new(),
             errors: Vec::new(),
 
@@ -75,6 +76,7 @@ impl<'ast> AstBuildState<'ast> {
             params: TempBuffer::new(32),
             variants: TempBuffer::new(32),
             fields: TempBuffer::new(32),
+            impl_procs: TempBuffer::new(32),
             import_symbols: TempBuffer::new(32),
             names: TempBuffer::new(32),
             types: TempBuffer::new(32),
@@ -82,6 +84,7 @@ impl<'ast> AstBuildState<'ast> {
             exprs: TempBuffer::new(32),
             branches: TempBuffer::new(32),
             match_arms: TempBuffer::new(32),
+            match_pats: TempBuffer::new(32),
             field_inits: TempBuffer::new(32),
         }
     }
@@ -93,10 +96,11 @@ pub fn parse<'ast, 'intern: 'ast>(
 ) -> ResultComp<ast::Ast<'ast, 'intern>> {
     let t_total = Timer::new();
     let mut state = AstBuildState::new(intern_name);
+    let mut tree_arena = Arena::new();
 
     for module_id in session.module_ids() {
         let module = session.module(module_id);
-        let (tree, errors) = super::parse(&module.source, module_id, false);
+        let (tree, errors) = super::parse_with_arena(&module.source, module_id, false, tree_arena);
 
         if errors.is_empty() {
             let mut ctx = AstBuild::new(&tree, &module.source, module_id, &mut state);
@@ -105,15 +109,15 @@ pub fn parse<'ast, 'intern: 'ast>(
         } else {
             state.errors.extend(errors);
         }
+        tree_arena = tree.into_arena();
     }
 
     t_total.stop("ast parse (new) total");
     if state.errors.is_empty() {
         let ast = ast::Ast {
-            arena: state.arena,
+            arenas: vec![state.arena],
             intern_name: state.intern_name,
             intern_string: state.intern_string,
-            string_is_cstr: state.string_is_cstr,
             modules: state.modules,
         };
         ResultComp::Ok((ast, vec![]))
@@ -127,20 +131,27 @@ fn source_file<'ast>(
     source_file: cst::SourceFile,
 ) -> &'ast [ast::Item<'ast>] {
     let offset = ctx.s.items.start();
+    let mut prev_end = 0.into();
     for item_cst in source_file.items(ctx.tree) {
-        item(ctx, item_cst);
+        let range = item_cst.range(ctx.tree);
+        let doc_range = TextRange::new(prev_end, range.start());
+        let docs = ctx.tree.tokens().doc_comments_in(doc_range);
+        let docs = ctx.s.arena.alloc_slice(docs);
+        item(ctx, item_cst, docs);
+        prev_end = range.end();
     }
     ctx.s.items.take(offset, &mut ctx.s.arena)
 }
 
-fn item<'ast>(ctx: &mut AstBuild<'ast, '_, '_, '_>, item: cst::Item) {
+fn item<'ast>(ctx: &mut AstBuild<'ast, '_, '_, '_>, item: cst::Item, docs: &'ast [TextRange]) {
     let item = match item {
-        cst::Item::Proc(item) => ast::Item::Proc(proc_item(ctx, item)),
-        cst::Item::Enum(item) => ast::Item::Enum(enum_item(ctx, item)),
-        cst::Item::Struct(item) => ast::Item::Struct(struct_item(ctx, item)),
-        cst::Item::Const(item) => ast::Item::Const(const_item(ctx, item)),
-        cst::Item::Global(item) => ast::Item::Global(global_item(ctx, item)),
+        cst::Item::Proc(item) => ast::Item::Proc(proc_item(ctx, item, docs)),
+        cst::Item::Enum(item) => ast::Item::Enum(enum_item(ctx, item, docs)),
+        cst::Item::Struct(item) => ast::Item::Struct(struct_item(ctx, item, docs)),
+        cst::Item::Const(item) => ast::Item::Const(const_item(ctx, item, docs)),
+        cst::Item::Global(item) => ast::Item::Global(global_item(ctx, item, docs)),
         cst::Item::Import(item) => ast::Item::Import(import_item(ctx, item)),
+        cst::Item::Impl(item) => ast::Item::Impl(impl_item(ctx, item)),
     };
     ctx.s.items.add(item);
 }
@@ -148,7 +159,7 @@ fn item<'ast>(ctx: &mut AstBuild<'ast, '_, '_, '_>, item: cst::Item) {
 fn attribute_list<'ast>(
     ctx: &mut AstBuild<'ast, '_, '_, '_>,
     attr_list: Option<cst::AttributeList>,
-) -> &'ast [ast::Attribute] {
+) -> &'ast [ast::Attribute<'ast>] {
     if let Some(attr_list) = attr_list {
         let offset = ctx.s.attrs.start();
         for attr_cst in attr_list.attrs(ctx.tree) {
@@ -161,15 +172,40 @@ fn attribute_list<'ast>(
     }
 }
 
-fn attribute<'ast>(ctx: &mut AstBuild<'ast, '_, '_, '_>, attr: cst::Attribute) -> ast::Attribute {
+fn attribute<'ast>(
+    ctx: &mut AstBuild<'ast, '_, '_, '_>,
+    attr: cst::Attribute,
+) -> ast::Attribute<'ast> {
     //@assuming range of ident token without any trivia
     let name_cst = attr.name(ctx.tree).unwrap();
     let range = name_cst.range(ctx.tree);
     let string = &ctx.source[range.as_usize()];
 
+    let kind = ast::AttributeKind::from_str(string);
+
+    let mut string = None;
+    let args = if let Some(args_cst) = attr.args(ctx.tree) {
+        if args_cst.has_string(ctx.tree) {
+            let (value, _) = ctx.tree.tokens().string(ctx.string_id as usize);
+            let id = ctx.s.intern_string.intern(value);
+            ctx.string_id += 1;
+            string = Some(id);
+        }
+        let offset = ctx.s.names.start();
+        for name_cst in args_cst.names(ctx.tree) {
+            let name = name(ctx, name_cst);
+            ctx.s.names.add(name);
+        }
+        ctx.s.names.take(offset, &mut ctx.s.arena)
+    } else {
+        &[]
+    };
+
     ast::Attribute {
-        kind: ast::AttributeKind::from_str(string),
+        kind,
         range: attr.range(ctx.tree),
+        args,
+        string,
     }
 }
 
@@ -192,10 +228,12 @@ fn mutt(is_mut: bool) -> ast::Mut {
 fn proc_item<'ast>(
     ctx: &mut AstBuild<'ast, '_, '_, '_>,
     item: cst::ProcItem,
+    docs: &'ast [TextRange],
 ) -> &'ast ast::ProcItem<'ast> {
     let attrs = attribute_list(ctx, item.attr_list(ctx.tree));
     let vis = vis(item.visiblity(ctx.tree).is_some());
     let name = name(ctx, item.name(ctx.tree).unwrap());
+    let poly_params = item.poly_params(ctx.tree).map(|pp| poly_params(ctx, pp));
 
     let offset = ctx.s.params.start();
     let param_list = item.param_list(ctx.tree).unwrap();
@@ -209,9 +247,11 @@ fn proc_item<'ast>(
     let block = item.block(ctx.tree).map(|b| block(ctx, b));
 
     let proc_item = ast::ProcItem {
+        docs,
         attrs,
         vis,
         name,
+        poly_params,
         params,
         is_variadic,
         return_ty,
@@ -220,6 +260,18 @@ fn proc_item<'ast>(
     ctx.s.arena.alloc(proc_item)
 }
 
+fn poly_params<'ast>(
+    ctx: &mut AstBuild<'ast, '_, '_, '_>,
+    poly_params: cst::PolyParams,
+) -> &'ast [ast::Name] {
+    let offset = ctx.s.names.start();
+    for name_cst in poly_params.names(ctx.tree) {
+        let name = name(ctx, name_cst);
+        ctx.s.names.add(name);
+    }
+    ctx.s.names.take(offset, &mut ctx.s.arena)
+}
+
 fn param<'ast>(ctx: &mut AstBuild<'ast, '_, '_, '_>, param: cst::Param) {
     let mutt = mutt(param.is_mut(ctx.tree));
     let name = name(ctx, param.name(ctx.tree).unwrap());
@@ -232,6 +284,7 @@ fn param<'ast>(ctx: &mut AstBuild<'ast, '_, '_, '_>, param: cst::Param) {
 fn enum_item<'ast>(
     ctx: &mut AstBuild<'ast, '_, '_, '_>,
     item: cst::EnumItem,
+    docs: &'ast [TextRange],
 ) -> &'ast ast::EnumItem<'ast> {
     let attrs = attribute_list(ctx, item.attr_list(ctx.tree));
     let vis = vis(item.visiblity(ctx.tree).is_some());
@@ -246,6 +299,7 @@ fn enum_item<'ast>(
     let variants = ctx.s.variants.take(offset, &mut ctx.s.arena);
 
     let enum_item = ast::EnumItem {
+        docs,
         attrs,
         vis,
         name,
@@ -259,15 +313,29 @@ fn variant<'ast>(ctx: &mut AstBuild<'ast, '_, '_, '_>, variant: cst::Variant) {
     //@value is optional in grammar but required in ast due to
     // const expr resolve limitation, will panic for now
     let name = name(ctx, variant.name(ctx.tree).unwrap());
+
+    let offset = ctx.s.types.start();
+    if let Some(field_list) = variant.field_list(ctx.tree) {
+        for ty_cst in field_list.field_types(ctx.tree) {
+            let ty = ty(ctx, ty_cst);
+            ctx.s.types.add(ty);
+        }
+    }
+    let fields = ctx.s.types.take(offset, &mut ctx.s.arena);
     let value = ast::ConstExpr(expr(ctx, variant.value(ctx.tree).unwrap()));
 
-    let variant = ast::EnumVariant { name, value };
+    let variant = ast::EnumVariant {
+        name,
+        fields,
+        value,
+    };
     ctx.s.variants.add(variant);
 }
 
 fn struct_item<'ast>(
     ctx: &mut AstBuild<'ast, '_, '_, '_>,
     item: cst::StructItem,
+    docs: &'ast [TextRange],
 ) -> &'ast ast::StructItem<'ast> {
     let attrs = attribute_list(ctx, item.attr_list(ctx.tree));
     let vis = vis(item.visiblity(ctx.tree).is_some());
@@ -281,6 +349,7 @@ fn struct_item<'ast>(
     let fields = ctx.s.fields.take(offset, &mut ctx.s.arena);
 
     let struct_item = ast::StructItem {
+        docs,
         attrs,
         vis,
         name,
@@ -293,14 +362,23 @@ fn field<'ast>(ctx: &mut AstBuild<'ast, '_, '_, '_>, field: cst::Field) {
     let vis = vis(field.visiblity(ctx.tree).is_some());
     let name = name(ctx, field.name(ctx.tree).unwrap());
     let ty = ty(ctx, field.ty(ctx.tree).unwrap());
+    let default = field
+        .default(ctx.tree)
+        .map(|default| ast::ConstExpr(expr(ctx, default)));
 
-    let field = ast::StructField { vis, name, ty };
+    let field = ast::StructField {
+        vis,
+        name,
+        ty,
+        default,
+    };
     ctx.s.fields.add(field);
 }
 
 fn const_item<'ast>(
     ctx: &mut AstBuild<'ast, '_, '_, '_>,
     item: cst::ConstItem,
+    docs: &'ast [TextRange],
 ) -> &'ast ast::ConstItem<'ast> {
     let attrs = attribute_list(ctx, item.attr_list(ctx.tree));
     let vis = vis(item.visiblity(ctx.tree).is_some());
@@ -309,6 +387,7 @@ fn const_item<'ast>(
     let value = ast::ConstExpr(expr(ctx, item.value(ctx.tree).unwrap()));
 
     let const_item = ast::ConstItem {
+        docs,
         attrs,
         vis,
         name,
@@ -321,6 +400,7 @@ fn const_item<'ast>(
 fn global_item<'ast>(
     ctx: &mut AstBuild<'ast, '_, '_, '_>,
     item: cst::GlobalItem,
+    docs: &'ast [TextRange],
 ) -> &'ast ast::GlobalItem<'ast> {
     let attrs = attribute_list(ctx, item.attr_list(ctx.tree));
     let vis = vis(item.visiblity(ctx.tree).is_some());
@@ -330,6 +410,7 @@ fn global_item<'ast>(
     let value = ast::ConstExpr(expr(ctx, item.value(ctx.tree).unwrap()));
 
     let global_item = ast::GlobalItem {
+        docs,
         attrs,
         vis,
         name,
@@ -372,6 +453,7 @@ fn import_item<'ast>(
         import_path,
         alias, //@rename everywhere to name_alias?
         symbols,
+        glob: item.glob_range(ctx.tree),
     };
     ctx.s.arena.alloc(import_item)
 }
@@ -398,6 +480,31 @@ fn name<'ast>(ctx: &mut AstBuild<'ast, '_, '_, '_>, name: cst::Name) -> ast::Nam
     ast::Name { range, id }
 }
 
+fn impl_item<'ast>(
+    ctx: &mut AstBuild<'ast, '_, '_, '_>,
+    item: cst::ImplItem,
+) -> &'ast ast::ImplItem<'ast> {
+    let attrs = attribute_list(ctx, item.attr_list(ctx.tree));
+    let path = path(ctx, item.path(ctx.tree).unwrap());
+
+    let offset = ctx.s.impl_procs.start();
+    let proc_list = item.proc_list(ctx.tree).unwrap();
+    let mut prev_end = proc_list.range(ctx.tree).start();
+    for proc_cst in proc_list.procs(ctx.tree) {
+        let range = proc_cst.range(ctx.tree);
+        let doc_range = TextRange::new(prev_end, range.start());
+        let docs = ctx.tree.tokens().doc_comments_in(doc_range);
+        let docs = ctx.s.arena.alloc_slice(docs);
+        let proc = proc_item(ctx, proc_cst, docs);
+        ctx.s.impl_procs.add(proc);
+        prev_end = range.end();
+    }
+    let procs = ctx.s.impl_procs.take(offset, &mut ctx.s.arena);
+
+    let impl_item = ast::ImplItem { attrs, path, procs };
+    ctx.s.arena.alloc(impl_item)
+}
+
 fn path<'ast>(ctx: &mut AstBuild<'ast, '_, '_, '_>, path: cst::Path) -> &'ast ast::Path<'ast> {
     let offset = ctx.s.names.start();
     for name_cst in path.names(ctx.tree) {
@@ -545,6 +652,9 @@ fn stmt_local<'ast>(
     local: cst::StmtLocal,
 ) -> &'ast ast::Local<'ast> {
     let mutt = mutt(local.is_mut(ctx.tree));
+    // local starts with `let` or `mut`, both are 3 bytes long
+    let start = local.range(ctx.tree).start();
+    let mutt_range = TextRange::new(start, start + 3.into());
     let name = name(ctx, local.name(ctx.tree).unwrap());
 
     let kind = if let Some(ty_cst) = local.ty(ctx.tree) {
@@ -560,7 +670,12 @@ fn stmt_local<'ast>(
         ast::LocalKind::Init(None, expr)
     };
 
-    let local = ast::Local { mutt, name, kind };
+    let local = ast::Local {
+        mutt,
+        mutt_range,
+        name,
+        kind,
+    };
     ctx.s.arena.alloc(local)
 }
 
@@ -598,44 +713,8 @@ fn expr<'ast>(ctx: &mut AstBuild<'ast, '_, '_, '_>, expr_cst: cst::Expr) -> &'as
 
             ast::ExprKind::LitBool { val }
         }
-        cst::Expr::LitInt(lit) => {
-            //@assuming that range of Node == range of Token
-            let range = lit.range(ctx.tree);
-            let string = &ctx.source[range.as_usize()];
-
-            let val = match string.parse::<u64>() {
-                Ok(value) => value,
-                Err(error) => {
-                    ctx.s.errors.push(ErrorComp::new(
-                        format!("parse integer error: {}", error),
-                        SourceRange::new(ctx.module_id, range),
-                        None,
-                    ));
-                    0
-                }
-            };
-
-            ast::ExprKind::LitInt { val }
-        }
-        cst::Expr::LitFloat(lit) => {
-            //@assuming that range of Node == range of Token
-            let range = lit.range(ctx.tree);
-            let string = &ctx.source[range.as_usize()];
-
-            let val = match string.parse::<f64>() {
-                Ok(value) => value,
-                Err(error) => {
-                    ctx.s.errors.push(ErrorComp::new(
-                        format!("parse float error: {}", error),
-                        SourceRange::new(ctx.module_id, range),
-                        None,
-                    ));
-                    0.0
-                }
-            };
-
-            ast::ExprKind::LitFloat { val }
-        }
+        cst::Expr::LitInt(lit) => number_lit(ctx, lit.range(ctx.tree), false),
+        cst::Expr::LitFloat(lit) => number_lit(ctx, lit.range(ctx.tree), true),
         cst::Expr::LitChar(_) => {
             let val = ctx.tree.tokens().char(ctx.char_id as usize);
             ctx.char_id += 1;
@@ -643,17 +722,11 @@ fn expr<'ast>(ctx: &mut AstBuild<'ast, '_, '_, '_>, expr_cst: cst::Expr) -> &'as
             ast::ExprKind::LitChar { val }
         }
         cst::Expr::LitString(_) => {
-            let (string, c_string) = ctx.tree.tokens().string(ctx.string_id as usize);
+            let (string, kind) = ctx.tree.tokens().string(ctx.string_id as usize);
             let id = ctx.s.intern_string.intern(string);
             ctx.string_id += 1;
 
-            if id.index() >= ctx.s.string_is_cstr.len() {
-                ctx.s.string_is_cstr.push(c_string);
-            } else if c_string {
-                ctx.s.string_is_cstr[id.index()] = true;
-            }
-
-            ast::ExprKind::LitString { id, c_string }
+            ast::ExprKind::LitString { id, kind }
         }
         cst::Expr::If(if_) => {
             let entry = if_.entry_branch(ctx.tree).unwrap();
@@ -693,10 +766,14 @@ fn expr<'ast>(ctx: &mut AstBuild<'ast, '_, '_, '_>, expr_cst: cst::Expr) -> &'as
             let offset = ctx.s.match_arms.start();
             let match_arm_lit = match_.match_arm_list(ctx.tree).unwrap();
             for match_arm_cst in match_arm_lit.match_arms(ctx.tree) {
-                let mut pat_expr_iter = match_arm_cst.pat_expr_iter(ctx.tree);
+                let pat_offset = ctx.s.match_pats.start();
+                for match_pat_cst in match_arm_cst.pats(ctx.tree) {
+                    let match_pat = match_pat(ctx, match_pat_cst);
+                    ctx.s.match_pats.add(match_pat);
+                }
                 let match_arm = ast::MatchArm {
-                    pat: ast::ConstExpr(expr(ctx, pat_expr_iter.next().unwrap())),
-                    expr: expr(ctx, pat_expr_iter.next().unwrap()),
+                    pats: ctx.s.match_pats.take(pat_offset, &mut ctx.s.arena),
+                    expr: expr(ctx, match_arm_cst.expr(ctx.tree).unwrap()),
                 };
                 ctx.s.match_arms.add(match_arm);
             }
@@ -755,6 +832,33 @@ fn expr<'ast>(ctx: &mut AstBuild<'ast, '_, '_, '_>, expr_cst: cst::Expr) -> &'as
             let ty_ref = ctx.s.arena.alloc(ty);
             ast::ExprKind::Sizeof { ty: ty_ref }
         }
+        cst::Expr::Format(format) => {
+            let name = format.name(ctx.tree).unwrap();
+            let range = name.range(ctx.tree);
+            let kind = match ast::FormatKind::from_str(&ctx.source[range.as_usize()]) {
+                Some(kind) => kind,
+                None => {
+                    ctx.s.errors.push(ErrorComp::new(
+                        ErrorCode::SyntaxError,
+                        "expected `print` or `format` directive",
+                        SourceRange::new(ctx.module_id, range),
+                        None,
+                    ));
+                    ast::FormatKind::Print
+                }
+            };
+
+            let offset = ctx.s.exprs.start();
+            let argument_list = format.call_argument_list(ctx.tree).unwrap();
+            for input in argument_list.inputs(ctx.tree) {
+                let expr = expr(ctx, input);
+                ctx.s.exprs.add(expr);
+            }
+            let input = ctx.s.exprs.take(offset, &mut ctx.s.arena);
+
+            let format = ctx.s.arena.alloc(ast::Format { kind, input });
+            ast::ExprKind::Format { format }
+        }
         cst::Expr::Item(item) => {
             let path = path(ctx, item.path(ctx.tree).unwrap());
 
@@ -765,6 +869,7 @@ fn expr<'ast>(ctx: &mut AstBuild<'ast, '_, '_, '_>, expr_cst: cst::Expr) -> &'as
 
             ast::ExprKind::Variant { name }
         }
+        cst::Expr::Wildcard(_) => ast::ExprKind::Wildcard,
         cst::Expr::StructInit(struct_init) => {
             let path = struct_init.path(ctx.tree).map(|p| path(ctx, p));
 
@@ -833,12 +938,70 @@ fn expr<'ast>(ctx: &mut AstBuild<'ast, '_, '_, '_>, expr_cst: cst::Expr) -> &'as
             let bin = ctx.s.arena.alloc(bin);
             ast::ExprKind::Binary { op, op_range, bin }
         }
+        cst::Expr::Range(range_expr) => {
+            // `lower..` and `..<upper` both have a single bound
+            let mut bound_iter = range_expr.bound_iter(ctx.tree);
+            let first = bound_iter.next().map(|bound| expr(ctx, bound));
+            let second = bound_iter.next().map(|bound| expr(ctx, bound));
+            let (lower, upper) = match second {
+                Some(upper) => (first, Some(upper)),
+                None if range_expr.is_unbounded(ctx.tree) => (first, None),
+                None => (None, first),
+            };
+            let upper = match upper {
+                None => ast::RangeEnd::Unbounded,
+                Some(upper) if range_expr.is_inclusive(ctx.tree) => ast::RangeEnd::Inclusive(upper),
+                Some(upper) => ast::RangeEnd::Exclusive(upper),
+            };
+
+            let range = ctx.s.arena.alloc(ast::RangeExpr { lower, upper });
+            ast::ExprKind::Range { range }
+        }
     };
 
     let expr = ast::Expr { kind, range };
     ctx.s.arena.alloc(expr)
 }
 
+//@assuming that range of Node == range of Token
+fn number_lit<'ast>(
+    ctx: &mut AstBuild<'ast, '_, '_, '_>,
+    range: TextRange,
+    is_float: bool,
+) -> ast::ExprKind<'ast> {
+    let string = &ctx.source[range.as_usize()];
+
+    match lexer::parse_number_lit(string, is_float) {
+        Ok(NumberLit::Int { val, ty }) => ast::ExprKind::LitInt { val, ty },
+        Ok(NumberLit::Float { val, ty }) => ast::ExprKind::LitFloat { val, ty },
+        Err(message) => {
+            ctx.s.errors.push(ErrorComp::new(
+                ErrorCode::InvalidNumberLiteral,
+                message,
+                SourceRange::new(ctx.module_id, range),
+                None,
+            ));
+            ast::ExprKind::LitInt { val: 0, ty: None }
+        }
+    }
+}
+
+fn match_pat<'ast>(
+    ctx: &mut AstBuild<'ast, '_, '_, '_>,
+    match_pat: cst::MatchPat,
+) -> ast::MatchPat<'ast> {
+    let mut bound_iter = match_pat.bound_iter(ctx.tree);
+    let start = ast::ConstExpr(expr(ctx, bound_iter.next().unwrap()));
+    match bound_iter.next() {
+        Some(end) => ast::MatchPat::Range {
+            start,
+            end: ast::ConstExpr(expr(ctx, end)),
+            inclusive: match_pat.is_inclusive(ctx.tree),
+        },
+        None => ast::MatchPat::Value(start),
+    }
+}
+
 fn block<'ast>(ctx: &mut AstBuild<'ast, '_, '_, '_>, block: cst::Block) -> ast::Block<'ast> {
     let offset = ctx.s.stmts.start();
     for stmt_cst in block.stmts(ctx.tree) {
@@ -220,13 +220,16 @@ ast_node_impl!(SourceFile, SyntaxKind::SOURCE_FILE);
 
 ast_node_impl!(AttributeList, SyntaxKind::ATTRIBUTE_LIST);
 ast_node_impl!(Attribute, SyntaxKind::ATTRIBUTE);
+ast_node_impl!(AttributeArgs, SyntaxKind::ATTRIBUTE_ARGS);
 ast_node_impl!(Visibility, SyntaxKind::VISIBILITY);
+ast_node_impl!(PolyParams, SyntaxKind::POLY_PARAMS);
 ast_node_impl!(ProcItem, SyntaxKind::PROC_ITEM);
 ast_node_impl!(ParamList, SyntaxKind::PARAM_LIST);
 ast_node_impl!(Param, SyntaxKind::PARAM);
 ast_node_impl!(EnumItem, SyntaxKind::ENUM_ITEM);
 ast_node_impl!(VariantList, SyntaxKind::VARIANT_LIST);
 ast_node_impl!(Variant, SyntaxKind::VARIANT);
+ast_node_impl!(VariantFieldList, SyntaxKind::VARIANT_FIELD_LIST);
 ast_node_impl!(StructItem, SyntaxKind::STRUCT_ITEM);
 ast_node_impl!(FieldList, SyntaxKind::FIELD_LIST);
 ast_node_impl!(Field, SyntaxKind::FIELD);
@@ -237,6 +240,8 @@ ast_node_impl!(ImportPath, SyntaxKind::IMPORT_PATH);
 ast_node_impl!(ImportSymbolList, SyntaxKind::IMPORT_SYMBOL_LIST);
 ast_node_impl!(ImportSymbol, SyntaxKind::IMPORT_SYMBOL);
 ast_node_impl!(NameAlias, SyntaxKind::NAME_ALIAS);
+ast_node_impl!(ImplItem, SyntaxKind::IMPL_ITEM);
+ast_node_impl!(ImplProcList, SyntaxKind::IMPL_PROC_LIST);
 
 ast_node_impl!(Name, SyntaxKind::NAME);
 ast_node_impl!(Path, SyntaxKind::PATH);
@@ -277,6 +282,7 @@ ast_node_impl!(ExprBlock, SyntaxKind::EXPR_BLOCK);
 ast_node_impl!(ExprMatch, SyntaxKind::EXPR_MATCH);
 ast_node_impl!(MatchArmList, SyntaxKind::MATCH_ARM_LIST);
 ast_node_impl!(MatchArm, SyntaxKind::MATCH_ARM);
+ast_node_impl!(MatchPat, SyntaxKind::MATCH_PAT);
 ast_node_impl!(MatchFallback, SyntaxKind::MATCH_FALLBACK);
 ast_node_impl!(ExprField, SyntaxKind::EXPR_FIELD);
 ast_node_impl!(ExprIndex, SyntaxKind::EXPR_INDEX);
@@ -284,8 +290,10 @@ ast_node_impl!(ExprCall, SyntaxKind::EXPR_CALL);
 ast_node_impl!(CallArgumentList, SyntaxKind::CALL_ARGUMENT_LIST);
 ast_node_impl!(ExprCast, SyntaxKind::EXPR_CAST);
 ast_node_impl!(ExprSizeof, SyntaxKind::EXPR_SIZEOF);
+ast_node_impl!(ExprFormat, SyntaxKind::EXPR_FORMAT);
 ast_node_impl!(ExprItem, SyntaxKind::EXPR_ITEM);
 ast_node_impl!(ExprVariant, SyntaxKind::EXPR_VARIANT);
+ast_node_impl!(ExprWildcard, SyntaxKind::EXPR_WILDCARD);
 ast_node_impl!(ExprStructInit, SyntaxKind::EXPR_STRUCT_INIT);
 ast_node_impl!(FieldInitList, SyntaxKind::FIELD_INIT_LIST);
 ast_node_impl!(FieldInit, SyntaxKind::FIELD_INIT);
@@ -295,7 +303,9 @@ ast_node_impl!(ExprDeref, SyntaxKind::EXPR_DEREF);
 ast_node_impl!(ExprAddress, SyntaxKind::EXPR_ADDRESS);
 ast_node_impl!(ExprUnary, SyntaxKind::EXPR_UNARY);
 ast_node_impl!(ExprBinary, SyntaxKind::EXPR_BINARY);
+ast_node_impl!(ExprRange, SyntaxKind::EXPR_RANGE);
 
+#[derive(Copy, Clone)]
 pub enum Item<'syn> {
     Proc(ProcItem<'syn>),
     Enum(EnumItem<'syn>),
@@ -303,6 +313,7 @@ pub enum Item<'syn> {
     Const(ConstItem<'syn>),
     Global(GlobalItem<'syn>),
     Import(ImportItem<'syn>),
+    Impl(ImplItem<'syn>),
 }
 
 impl<'syn> AstNode<'syn> for Item<'syn> {
@@ -317,6 +328,7 @@ impl<'syn> AstNode<'syn> for Item<'syn> {
             SyntaxKind::CONST_ITEM => Some(Item::Const(ConstItem(node))),
             SyntaxKind::GLOBAL_ITEM => Some(Item::Global(GlobalItem(node))),
             SyntaxKind::IMPORT_ITEM => Some(Item::Import(ImportItem(node))),
+            SyntaxKind::IMPL_ITEM => Some(Item::Impl(ImplItem(node))),
             _ => None,
         }
     }
@@ -331,6 +343,7 @@ impl<'syn> Item<'syn> {
             Item::Const(item) => item.range(tree),
             Item::Global(item) => item.range(tree),
             Item::Import(item) => item.range(tree),
+            Item::Impl(item) => item.range(tree),
         }
     }
 }
@@ -441,8 +454,10 @@ pub enum Expr<'syn> {
     Call(ExprCall<'syn>),
     Cast(ExprCast<'syn>),
     Sizeof(ExprSizeof<'syn>),
+    Format(ExprFormat<'syn>),
     Item(ExprItem<'syn>),
     Variant(ExprVariant<'syn>),
+    Wildcard(ExprWildcard<'syn>),
     StructInit(ExprStructInit<'syn>),
     ArrayInit(ExprArrayInit<'syn>),
     ArrayRepeat(ExprArrayRepeat<'syn>),
@@ -450,6 +465,7 @@ pub enum Expr<'syn> {
     Address(ExprAddress<'syn>),
     Unary(ExprUnary<'syn>),
     Binary(ExprBinary<'syn>),
+    Range(ExprRange<'syn>),
 }
 
 impl<'syn> AstNode<'syn> for Expr<'syn> {
@@ -473,8 +489,10 @@ impl<'syn> AstNode<'syn> for Expr<'syn> {
             SyntaxKind::EXPR_CALL => Some(Expr::Call(ExprCall(node))),
             SyntaxKind::EXPR_CAST => Some(Expr::Cast(ExprCast(node))),
             SyntaxKind::EXPR_SIZEOF => Some(Expr::Sizeof(ExprSizeof(node))),
+            SyntaxKind::EXPR_FORMAT => Some(Expr::Format(ExprFormat(node))),
             SyntaxKind::EXPR_ITEM => Some(Expr::Item(ExprItem(node))),
             SyntaxKind::EXPR_VARIANT => Some(Expr::Variant(ExprVariant(node))),
+            SyntaxKind::EXPR_WILDCARD => Some(Expr::Wildcard(ExprWildcard(node))),
             SyntaxKind::EXPR_STRUCT_INIT => Some(Expr::StructInit(ExprStructInit(node))),
             SyntaxKind::EXPR_ARRAY_INIT => Some(Expr::ArrayInit(ExprArrayInit(node))),
             SyntaxKind::EXPR_ARRAY_REPEAT => Some(Expr::ArrayRepeat(ExprArrayRepeat(node))),
@@ -482,6 +500,7 @@ impl<'syn> AstNode<'syn> for Expr<'syn> {
             SyntaxKind::EXPR_ADDRESS => Some(Expr::Address(ExprAddress(node))),
             SyntaxKind::EXPR_UNARY => Some(Expr::Unary(ExprUnary(node))),
             SyntaxKind::EXPR_BINARY => Some(Expr::Binary(ExprBinary(node))),
+            SyntaxKind::EXPR_RANGE => Some(Expr::Range(ExprRange(node))),
             _ => None,
         }
     }
@@ -505,8 +524,10 @@ impl<'syn> Expr<'syn> {
             Expr::Call(expr) => expr.range(tree),
             Expr::Cast(expr) => expr.range(tree),
             Expr::Sizeof(expr) => expr.range(tree),
+            Expr::Format(expr) => expr.range(tree),
             Expr::Item(expr) => expr.range(tree),
             Expr::Variant(expr) => expr.range(tree),
+            Expr::Wildcard(expr) => expr.range(tree),
             Expr::StructInit(expr) => expr.range(tree),
             Expr::ArrayInit(expr) => expr.range(tree),
             Expr::ArrayRepeat(expr) => expr.range(tree),
@@ -514,6 +535,7 @@ impl<'syn> Expr<'syn> {
             Expr::Address(expr) => expr.range(tree),
             Expr::Unary(expr) => expr.range(tree),
             Expr::Binary(expr) => expr.range(tree),
+            Expr::Range(expr) => expr.range(tree),
         }
     }
 }
@@ -528,6 +550,12 @@ impl<'syn> AttributeList<'syn> {
 
 impl<'syn> Attribute<'syn> {
     find_first!(name, Name);
+    find_first!(args, AttributeArgs);
+}
+
+impl<'syn> AttributeArgs<'syn> {
+    node_iter!(names, Name);
+    find_token!(has_string, T![string_lit]);
 }
 
 impl<'syn> Visibility<'syn> {
@@ -538,11 +566,16 @@ impl<'syn> ProcItem<'syn> {
     find_first!(attr_list, AttributeList);
     find_first!(visiblity, Visibility);
     find_first!(name, Name);
+    find_first!(poly_params, PolyParams);
     find_first!(param_list, ParamList);
     find_first!(return_ty, Type);
     find_first!(block, Block);
 }
 
+impl<'syn> PolyParams<'syn> {
+    node_iter!(names, Name);
+}
+
 impl<'syn> ParamList<'syn> {
     node_iter!(params, Param);
     find_token_rev!(is_variadic, T![..]);
@@ -568,9 +601,14 @@ impl<'syn> VariantList<'syn> {
 
 impl<'syn> Variant<'syn> {
     find_first!(name, Name);
+    find_first!(field_list, VariantFieldList);
     find_first!(value, Expr);
 }
 
+impl<'syn> VariantFieldList<'syn> {
+    node_iter!(field_types, Type);
+}
+
 impl<'syn> StructItem<'syn> {
     find_first!(attr_list, AttributeList);
     find_first!(visiblity, Visibility);
@@ -586,6 +624,7 @@ impl<'syn> Field<'syn> {
     find_first!(visiblity, Visibility);
     find_first!(name, Name);
     find_first!(ty, Type);
+    find_first!(default, Expr);
 }
 
 impl<'syn> ConstItem<'syn> {
@@ -612,6 +651,23 @@ impl<'syn> ImportItem<'syn> {
     find_first!(import_path, ImportPath);
     find_first!(name_alias, NameAlias); //@rename ast to name_alias
     find_first!(import_symbol_list, ImportSymbolList);
+    pub fn glob_range(&self, tree: &'syn SyntaxTree<'syn>) -> Option<TextRange> {
+        let glob = self
+            .0
+            .find_by_token_with_range(tree, |token| (token == T![*]).then_some(()));
+        glob.map(|(_, range)| range)
+    }
+}
+
+impl<'syn> ImplItem<'syn> {
+    find_first!(attr_list, AttributeList);
+    find_first!(visiblity, Visibility); //@exists but ignored
+    find_first!(path, Path);
+    find_first!(proc_list, ImplProcList);
+}
+
+impl<'syn> ImplProcList<'syn> {
+    node_iter!(procs, ProcItem);
 }
 
 impl<'syn> ImportPath<'syn> {
@@ -798,8 +854,14 @@ impl<'syn> MatchArmList<'syn> {
 }
 
 impl<'syn> MatchArm<'syn> {
+    node_iter!(pats, MatchPat);
+    find_first!(expr, Expr);
+}
+
+impl<'syn> MatchPat<'syn> {
     //@ambiguity in incomplete tree
-    node_iter!(pat_expr_iter, Expr);
+    node_iter!(bound_iter, Expr);
+    find_token!(is_inclusive, T!["..="]);
 }
 
 impl<'syn> MatchFallback<'syn> {
@@ -835,6 +897,11 @@ impl<'syn> ExprSizeof<'syn> {
     find_first!(ty, Type);
 }
 
+impl<'syn> ExprFormat<'syn> {
+    find_first!(name, Name);
+    find_first!(call_argument_list, CallArgumentList);
+}
+
 impl<'syn> ExprItem<'syn> {
     find_first!(path, Path);
 }
@@ -843,6 +910,8 @@ impl<'syn> ExprVariant<'syn> {
     find_first!(name, Name);
 }
 
+impl<'syn> ExprWildcard<'syn> {}
+
 impl<'syn> ExprStructInit<'syn> {
     find_first!(path, Path);
     find_first!(field_init_list, FieldInitList);
@@ -901,3 +970,10 @@ impl<'syn> ExprBinary<'syn> {
     //@ambiguity in incomplete tree
     node_iter!(lhs_rhs_iter, Expr);
 }
+
+impl<'syn> ExprRange<'syn> {
+    //@ambiguity in incomplete tree
+    node_iter!(bound_iter, Expr);
+    find_token!(is_unbounded, T![..]);
+    find_token!(is_inclusive, T!["..="]);
+}
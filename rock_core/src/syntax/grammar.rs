@@ -1,6 +1,7 @@
 use super::parser::{Marker, MarkerClosed, Parser};
 use super::syntax_kind::SyntaxKind;
 use super::token_set::TokenSet;
+use crate::ast::BinOp;
 use crate::token::{Token, T};
 
 pub fn source_file(p: &mut Parser) {
@@ -18,6 +19,26 @@ pub fn source_file(p: &mut Parser) {
 }
 
 fn item(p: &mut Parser) {
+    let m = item_prefix(p);
+
+    match p.peek() {
+        T![proc] => proc_item(p, m),
+        T![enum] => enum_item(p, m),
+        T![struct] => struct_item(p, m),
+        T![const] => const_item(p, m),
+        T![global] => global_item(p, m),
+        T![import] => import_item(p, m),
+        T![impl] => impl_item(p, m),
+        _ => {
+            p.error("expected item");
+            p.sync_to(FIRST_ITEM);
+            m.complete(p, SyntaxKind::ERROR);
+        }
+    }
+}
+
+/// parses attributes and visibility, returned marker starts before them
+fn item_prefix(p: &mut Parser) -> Marker {
     let mut mc = None;
 
     if p.at(T![#]) {
@@ -28,7 +49,7 @@ fn item(p: &mut Parser) {
         mc = Some(m.complete(p, SyntaxKind::ATTRIBUTE_LIST));
     }
 
-    //@not used in import, ignored without errors
+    //@not used in import and impl, ignored without errors
     if p.at(T![pub]) {
         let mc_vis = visibility(p);
         if mc.is_none() {
@@ -36,24 +57,10 @@ fn item(p: &mut Parser) {
         }
     }
 
-    let m = if let Some(mc) = mc {
+    if let Some(mc) = mc {
         p.start_before(mc)
     } else {
         p.start()
-    };
-
-    match p.peek() {
-        T![proc] => proc_item(p, m),
-        T![enum] => enum_item(p, m),
-        T![struct] => struct_item(p, m),
-        T![const] => const_item(p, m),
-        T![global] => global_item(p, m),
-        T![import] => import_item(p, m),
-        _ => {
-            p.error("expected item");
-            p.sync_to(FIRST_ITEM);
-            m.complete(p, SyntaxKind::ERROR);
-        }
     }
 }
 
@@ -62,6 +69,9 @@ fn attribute(p: &mut Parser) -> MarkerClosed {
     p.bump(T![#]);
     if p.eat(T!['[']) {
         name(p);
+        if p.at(T!['(']) {
+            attribute_args(p);
+        }
         p.expect(T![']']);
     } else {
         p.expect(T!['[']);
@@ -69,6 +79,29 @@ fn attribute(p: &mut Parser) -> MarkerClosed {
     m.complete(p, SyntaxKind::ATTRIBUTE)
 }
 
+fn attribute_args(p: &mut Parser) {
+    let m = p.start();
+    p.bump(T!['(']);
+    if p.eat(T![string_lit]) {
+        p.expect(T![')']);
+        m.complete(p, SyntaxKind::ATTRIBUTE_ARGS);
+        return;
+    }
+    while !p.at(T![')']) && !p.at(T![eof]) {
+        if p.at(T![ident]) {
+            name(p);
+            if !p.at(T![')']) {
+                p.expect(T![,]);
+            }
+        } else {
+            p.error("expected attribute argument");
+            break;
+        }
+    }
+    p.expect(T![')']);
+    m.complete(p, SyntaxKind::ATTRIBUTE_ARGS);
+}
+
 fn visibility(p: &mut Parser) -> MarkerClosed {
     let m = p.start();
     p.bump(T![pub]);
@@ -84,19 +117,25 @@ const FIRST_ITEM: TokenSet = TokenSet::new(&[
     T![const],
     T![global],
     T![import],
+    T![impl],
 ]);
 
 const FIRST_PARAM: TokenSet = TokenSet::new(&[T![mut], T![ident]]);
 
+const RECOVER_POLY_PARAMS: TokenSet = FIRST_ITEM.combine(TokenSet::new(&[T!['('], T!['{']]));
 const RECOVER_PARAM_LIST: TokenSet = FIRST_ITEM.combine(TokenSet::new(&[T![->], T!['{'], T![;]]));
 const RECOVER_VARIANT_LIST: TokenSet = FIRST_ITEM;
 const RECOVER_FIELD_LIST: TokenSet = FIRST_ITEM;
 const RECOVER_IMPORT_PATH: TokenSet = FIRST_ITEM.combine(TokenSet::new(&[T![as], T![.], T![;]]));
 const RECOVER_IMPORT_SYMBOL_LIST: TokenSet = FIRST_ITEM.combine(TokenSet::new(&[T![;]]));
+const RECOVER_IMPL_PATH: TokenSet = FIRST_ITEM.combine(TokenSet::new(&[T!['{']]));
 
 fn proc_item(p: &mut Parser, m: Marker) {
     p.bump(T![proc]);
     name(p);
+    if p.at(T!['[']) {
+        poly_params(p);
+    }
     if p.at(T!['(']) {
         param_list(p);
     } else {
@@ -113,6 +152,24 @@ fn proc_item(p: &mut Parser, m: Marker) {
     m.complete(p, SyntaxKind::PROC_ITEM);
 }
 
+fn poly_params(p: &mut Parser) {
+    let m = p.start();
+    p.bump(T!['[']);
+    while !p.at(T![']']) && !p.at(T![eof]) {
+        if p.at(T![ident]) {
+            name(p);
+            if !p.at(T![']']) {
+                p.expect(T![,]);
+            }
+        } else {
+            p.error_recover("expected type parameter", RECOVER_POLY_PARAMS);
+            break;
+        }
+    }
+    p.expect(T![']']);
+    m.complete(p, SyntaxKind::POLY_PARAMS);
+}
+
 fn param_list(p: &mut Parser) {
     let m = p.start();
     p.bump(T!['(']);
@@ -179,12 +236,33 @@ fn variant_list(p: &mut Parser) {
 fn variant(p: &mut Parser) {
     let m = p.start();
     name(p);
+    if p.at(T!['(']) {
+        variant_field_list(p);
+    }
     if p.eat(T![=]) {
         expr(p);
     }
     m.complete(p, SyntaxKind::VARIANT);
 }
 
+fn variant_field_list(p: &mut Parser) {
+    let m = p.start();
+    p.bump(T!['(']);
+    while !p.at(T![')']) && !p.at(T![eof]) {
+        if p.at_set(FIRST_TYPE_SET) {
+            ty(p);
+            if !p.at(T![')']) {
+                p.expect(T![,]);
+            }
+        } else {
+            p.error_recover("expected variant field type", RECOVER_VARIANT_LIST);
+            break;
+        }
+    }
+    p.expect(T![')']);
+    m.complete(p, SyntaxKind::VARIANT_FIELD_LIST);
+}
+
 fn struct_item(p: &mut Parser, m: Marker) {
     p.bump(T![struct]);
     name(p);
@@ -222,6 +300,9 @@ fn field(p: &mut Parser) {
     name(p);
     p.expect(T![:]);
     ty(p);
+    if p.eat(T![=]) {
+        expr(p);
+    }
     m.complete(p, SyntaxKind::FIELD);
 }
 
@@ -248,6 +329,43 @@ fn global_item(p: &mut Parser, m: Marker) {
     m.complete(p, SyntaxKind::GLOBAL_ITEM);
 }
 
+fn impl_item(p: &mut Parser, m: Marker) {
+    p.bump(T![impl]);
+    if p.at(T![ident]) {
+        path_type(p);
+    } else {
+        p.error_recover("expected type path", RECOVER_IMPL_PATH);
+    }
+    if p.at(T!['{']) {
+        impl_proc_list(p);
+    } else {
+        p.error_recover("expected procedure list", FIRST_ITEM);
+    }
+    m.complete(p, SyntaxKind::IMPL_ITEM);
+}
+
+fn impl_proc_list(p: &mut Parser) {
+    let m = p.start();
+    p.bump(T!['{']);
+    while !p.at(T!['}']) && !p.at(T![eof]) {
+        if p.at(T![#]) || p.at(T![pub]) || p.at(T![proc]) {
+            let m = item_prefix(p);
+            if p.at(T![proc]) {
+                proc_item(p, m);
+            } else {
+                p.error("expected procedure");
+                m.complete(p, SyntaxKind::ERROR);
+                break;
+            }
+        } else {
+            p.error_recover("expected procedure", FIRST_ITEM);
+            break;
+        }
+    }
+    p.expect(T!['}']);
+    m.complete(p, SyntaxKind::IMPL_PROC_LIST);
+}
+
 fn import_item(p: &mut Parser, m: Marker) {
     p.bump(T![import]);
     if p.at(T![ident]) && p.at_next(T![:]) {
@@ -265,6 +383,8 @@ fn import_item(p: &mut Parser, m: Marker) {
     if p.eat(T![.]) {
         if p.at(T!['{']) {
             import_symbol_list(p);
+        } else if p.at(T![*]) {
+            p.bump(T![*]);
         } else {
             p.error_recover("expected import symbol list", RECOVER_IMPORT_SYMBOL_LIST);
         }
@@ -373,6 +493,13 @@ const FIRST_TYPE_SET: TokenSet = TokenSet::new(&[
 ]);
 
 fn ty(p: &mut Parser) {
+    if p.enter_nested("type") {
+        ty_impl(p);
+        p.exit_nested();
+    }
+}
+
+fn ty_impl(p: &mut Parser) {
     if p.peek().as_basic_type().is_some() {
         let m = p.start();
         p.bump(p.peek());
@@ -484,7 +611,10 @@ fn stmt(p: &mut Parser) {
                 block(p, SyntaxKind::BLOCK);
             } else {
                 let m = p.start();
-                stmt(p);
+                if p.enter_nested("block") {
+                    stmt(p);
+                    p.exit_nested();
+                }
                 m.complete(p, SyntaxKind::SHORT_BLOCK);
             }
             m.complete(p, SyntaxKind::STMT_DEFER);
@@ -573,12 +703,40 @@ fn local(p: &mut Parser) {
     m.complete(p, SyntaxKind::STMT_LOCAL);
 }
 
+const RANGE_OP_SET: TokenSet = TokenSet::new(&[T![..], T!["..<"], T!["..="]]);
+
 fn expr(p: &mut Parser) {
-    sub_expr(p, 0);
+    let m = if p.at_set(RANGE_OP_SET) {
+        p.start()
+    } else {
+        let mc = sub_expr(p, 0);
+        if !p.at_set(RANGE_OP_SET) {
+            return;
+        }
+        p.start_before(mc)
+    };
+
+    if !p.eat(T![..]) {
+        p.bump(p.peek());
+        sub_expr(p, 0);
+    }
+    m.complete(p, SyntaxKind::EXPR_RANGE);
+}
+
+fn sub_expr(p: &mut Parser, min_prec: u32) -> MarkerClosed {
+    sub_expr_impl(p, min_prec, false)
 }
 
-fn sub_expr(p: &mut Parser, min_prec: u32) {
+/// match pattern operand, `|` separates patterns
+fn pat_operand(p: &mut Parser) {
+    sub_expr_impl(p, 0, true);
+}
+
+fn sub_expr_impl(p: &mut Parser, min_prec: u32, in_pat: bool) -> MarkerClosed {
     let mut mc_curr = primary_expr(p);
+    // operator chains are folded without recursion,
+    // each fold nests the tree deeper and counts towards the nesting limit
+    let mut chain = 0;
 
     loop {
         let prec: u32;
@@ -587,17 +745,39 @@ fn sub_expr(p: &mut Parser, min_prec: u32) {
             if prec < min_prec {
                 break;
             }
+            if in_pat && bin_op == BinOp::BitOr {
+                break;
+            }
             let m = p.start_before(mc_curr);
+            if !p.enter_nested("expression") {
+                mc_curr = m.complete(p, SyntaxKind::EXPR_BINARY);
+                break;
+            }
+            chain += 1;
             p.bump(p.peek());
-            sub_expr(p, prec + 1);
+            sub_expr_impl(p, prec + 1, in_pat);
             mc_curr = m.complete(p, SyntaxKind::EXPR_BINARY);
         } else {
             break;
         }
     }
+    for _ in 0..chain {
+        p.exit_nested();
+    }
+    mc_curr
 }
 
 fn primary_expr(p: &mut Parser) -> MarkerClosed {
+    if !p.enter_nested("expression") {
+        let m = p.start();
+        return m.complete(p, SyntaxKind::ERROR);
+    }
+    let mc = primary_expr_impl(p);
+    p.exit_nested();
+    mc
+}
+
+fn primary_expr_impl(p: &mut Parser) -> MarkerClosed {
     if p.at(T!['(']) {
         let m = p.start();
         p.bump(T!['(']);
@@ -658,6 +838,17 @@ fn primary_expr(p: &mut Parser) -> MarkerClosed {
             p.expect(T![')']);
             m.complete(p, SyntaxKind::EXPR_SIZEOF)
         }
+        T![#] => {
+            let m = p.start();
+            p.bump(T![#]);
+            name(p);
+            if p.at(T!['(']) {
+                call_argument_list(p);
+            } else {
+                p.error("expected argument list");
+            }
+            m.complete(p, SyntaxKind::EXPR_FORMAT)
+        }
         T![ident] => {
             let m = p.start();
             let field_list = path_expr(p);
@@ -679,6 +870,11 @@ fn primary_expr(p: &mut Parser) -> MarkerClosed {
                 m.complete(p, SyntaxKind::EXPR_VARIANT)
             }
         }
+        T![_] => {
+            let m = p.start();
+            p.bump(T![_]);
+            m.complete(p, SyntaxKind::EXPR_WILDCARD)
+        }
         T!['['] => array_expr(p),
         T![*] => {
             let m = p.start();
@@ -727,14 +923,7 @@ fn tail_expr(p: &mut Parser, mc: MarkerClosed) -> MarkerClosed {
                 let m = p.start_before(mc_curr);
                 p.bump(T!['[']);
                 p.eat(T![mut]);
-                if !p.at(T![..]) {
-                    expr(p);
-                }
-                if p.eat(T![..]) {
-                    if !p.at(T![']']) {
-                        expr(p);
-                    }
-                }
+                expr(p);
                 p.expect(T![']']);
                 mc_curr = m.complete(p, SyntaxKind::EXPR_INDEX);
             }
@@ -788,11 +977,14 @@ fn if_(p: &mut Parser) -> MarkerClosed {
 
 fn block(p: &mut Parser, kind: SyntaxKind) -> MarkerClosed {
     let m = p.start();
-    p.bump(T!['{']);
-    while !p.at(T!['}']) && !p.at(T![eof]) {
-        stmt(p);
+    if p.enter_nested("block") {
+        p.bump(T!['{']);
+        while !p.at(T!['}']) && !p.at(T![eof]) {
+            stmt(p);
+        }
+        p.expect(T!['}']);
+        p.exit_nested();
     }
-    p.expect(T!['}']);
     m.complete(p, kind)
 }
 
@@ -840,7 +1032,10 @@ fn match_arm(p: &mut Parser) -> bool {
         m.complete(p, SyntaxKind::MATCH_FALLBACK);
         true
     } else {
-        expr(p);
+        match_pat(p);
+        while p.eat(T![|]) {
+            match_pat(p);
+        }
         p.expect(T![->]);
         expr(p);
         m.complete(p, SyntaxKind::MATCH_ARM);
@@ -848,6 +1043,16 @@ fn match_arm(p: &mut Parser) -> bool {
     }
 }
 
+fn match_pat(p: &mut Parser) {
+    let m = p.start();
+    pat_operand(p);
+    if p.at(T!["..<"]) || p.at(T!["..="]) {
+        p.bump(p.peek());
+        pat_operand(p);
+    }
+    m.complete(p, SyntaxKind::MATCH_PAT);
+}
+
 fn call_argument_list(p: &mut Parser) {
     let m = p.start();
     p.bump(T!['(']);
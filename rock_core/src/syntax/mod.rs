@@ -2,10 +2,11 @@ pub mod ast_build;
 pub mod ast_layer;
 mod grammar;
 mod parser;
-mod syntax_kind;
+pub mod syntax_kind;
 pub mod syntax_tree;
 mod token_set;
 
+use crate::arena::Arena;
 use crate::error::ErrorComp;
 use crate::lexer;
 use crate::session::ModuleID;
@@ -15,13 +16,23 @@ use syntax_tree::SyntaxTree;
 //@allow early returns for usage where broken tree is not needed (eg: format & ast_build)
 // currently syntax_tree::build always runs producing syntax tree
 pub fn parse(source: &str, module_id: ModuleID, with_trivia: bool) -> (SyntaxTree, Vec<ErrorComp>) {
+    parse_with_arena(source, module_id, with_trivia, Arena::new())
+}
+
+// arena from previous syntax tree can be reused with `SyntaxTree::into_arena`
+pub(crate) fn parse_with_arena<'syn>(
+    source: &str,
+    module_id: ModuleID,
+    with_trivia: bool,
+    arena: Arena<'syn>,
+) -> (SyntaxTree<'syn>, Vec<ErrorComp>) {
     let (tokens, lex_errors) = lexer::lex(source, module_id, with_trivia);
 
-    let mut parser = Parser::new(tokens, module_id);
+    let mut parser = Parser::new(&tokens, module_id);
     grammar::source_file(&mut parser);
+    let (events, errors) = parser.finish();
 
-    let (tree, mut parse_errors) = syntax_tree::build(parser.finish());
-    syntax_tree::tree_print(&tree, source); //@debug only
+    let (tree, mut parse_errors) = syntax_tree::build((tokens, events, errors), arena);
     parse_errors.extend(lex_errors);
     (tree, parse_errors)
 }
@@ -1,17 +1,20 @@
 use super::syntax_kind::SyntaxKind;
 use super::token_set::TokenSet;
-use crate::error::{ErrorComp, SourceRange, StringOrStr};
+use crate::ast_parse::NESTING_LIMIT;
+use crate::error::{ErrorCode, ErrorComp, SourceRange, StringOrStr};
 use crate::session::ModuleID;
+use crate::token::token_cursor::TokenCursor;
 use crate::token::token_list::TokenList;
 use crate::token::Token;
 use std::cell::Cell;
 
-pub struct Parser {
-    cursor: usize,
-    tokens: TokenList,
+pub struct Parser<'t> {
+    tokens: TokenCursor<'t>,
     events: Vec<Event>,
     errors: Vec<ErrorComp>,
     steps: Cell<u32>,
+    depth: u32,
+    nesting_exceeded: bool,
     module_id: ModuleID,
 }
 
@@ -35,20 +38,21 @@ pub struct MarkerClosed {
     event_idx: u32,
 }
 
-impl Parser {
-    pub fn new(tokens: TokenList, module_id: ModuleID) -> Parser {
+impl<'t> Parser<'t> {
+    pub fn new(tokens: &'t TokenList, module_id: ModuleID) -> Parser<'t> {
         Parser {
-            cursor: 0,
-            tokens,
+            tokens: TokenCursor::new(tokens),
             events: Vec::new(),
             errors: Vec::new(),
             steps: Cell::new(0),
+            depth: 0,
+            nesting_exceeded: false,
             module_id,
         }
     }
 
-    pub fn finish(self) -> (TokenList, Vec<Event>, Vec<ErrorComp>) {
-        (self.tokens, self.events, self.errors)
+    pub fn finish(self) -> (Vec<Event>, Vec<ErrorComp>) {
+        (self.events, self.errors)
     }
 
     pub fn at(&self, token: Token) -> bool {
@@ -60,7 +64,7 @@ impl Parser {
     }
 
     pub fn at_prev(&self, token: Token) -> bool {
-        self.tokens.token(self.cursor - 1) == token
+        self.tokens.at_prev(token)
     }
 
     pub fn at_set(&self, token_set: TokenSet) -> bool {
@@ -69,12 +73,12 @@ impl Parser {
 
     pub fn peek(&self) -> Token {
         self.step_bump();
-        self.tokens.token(self.cursor)
+        self.tokens.peek()
     }
 
     pub fn peek_next(&self) -> Token {
         self.step_bump();
-        self.tokens.token(self.cursor + 1)
+        self.tokens.peek_nth(1)
     }
 
     pub fn eat(&mut self, token: Token) -> bool {
@@ -122,10 +126,36 @@ impl Parser {
         m.complete(self, SyntaxKind::ERROR);
     }
 
+    /// expressions, types and blocks are parsed recursively, past the nesting limit
+    /// rest of the source is skipped instead of overflowing the stack
+    pub fn enter_nested(&mut self, what: &str) -> bool {
+        if self.depth >= NESTING_LIMIT {
+            if !self.nesting_exceeded {
+                self.error(format!(
+                    "{what} is nested too deeply\nnesting limit is {NESTING_LIMIT}"
+                ));
+                self.nesting_exceeded = true;
+            }
+            self.sync_to(TokenSet::EMPTY);
+            return false;
+        }
+        self.depth += 1;
+        true
+    }
+
+    pub fn exit_nested(&mut self) {
+        self.depth -= 1;
+    }
+
+    // errors of the enclosing nodes after skipped source are not reported
     pub fn error(&mut self, msg: impl Into<StringOrStr>) {
-        let range = self.tokens.token_range(self.cursor + 1);
+        if self.nesting_exceeded {
+            return;
+        }
+        let range = self.tokens.peek_nth_range(1);
         let src = SourceRange::new(self.module_id, range);
-        self.errors.push(ErrorComp::new(msg, src, None));
+        self.errors
+            .push(ErrorComp::new(ErrorCode::SyntaxError, msg, src, None));
     }
 
     fn bump_any(&mut self) {
@@ -136,7 +166,7 @@ impl Parser {
     }
 
     fn do_bump(&mut self) {
-        self.cursor += 1;
+        self.tokens.bump();
         self.step_reset();
         self.push_event(Event::Token);
     }
@@ -7,13 +7,16 @@ pub enum SyntaxKind {
 
     ATTRIBUTE_LIST,
     ATTRIBUTE,
+    ATTRIBUTE_ARGS,
     VISIBILITY,
+    POLY_PARAMS,
     PROC_ITEM,
     PARAM_LIST,
     PARAM,
     ENUM_ITEM,
     VARIANT_LIST,
     VARIANT,
+    VARIANT_FIELD_LIST,
     STRUCT_ITEM,
     FIELD_LIST,
     FIELD,
@@ -24,6 +27,8 @@ pub enum SyntaxKind {
     IMPORT_SYMBOL_LIST,
     IMPORT_SYMBOL,
     NAME_ALIAS,
+    IMPL_ITEM,
+    IMPL_PROC_LIST,
 
     NAME,
     PATH,
@@ -64,6 +69,7 @@ pub enum SyntaxKind {
     EXPR_MATCH,
     MATCH_ARM_LIST,
     MATCH_ARM,
+    MATCH_PAT,
     MATCH_FALLBACK,
     EXPR_FIELD,
     EXPR_INDEX,
@@ -71,8 +77,10 @@ pub enum SyntaxKind {
     CALL_ARGUMENT_LIST,
     EXPR_CAST,
     EXPR_SIZEOF,
+    EXPR_FORMAT,
     EXPR_ITEM,
     EXPR_VARIANT,
+    EXPR_WILDCARD,
     EXPR_STRUCT_INIT,
     FIELD_INIT_LIST,
     FIELD_INIT,
@@ -82,4 +90,5 @@ pub enum SyntaxKind {
     EXPR_ADDRESS,
     EXPR_UNARY,
     EXPR_BINARY,
+    EXPR_RANGE,
 }
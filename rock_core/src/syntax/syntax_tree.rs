@@ -9,7 +9,6 @@ use crate::token::token_list::TokenList;
 use crate::token::Token;
 
 pub struct SyntaxTree<'syn> {
-    #[allow(unused)]
     arena: Arena<'syn>,
     nodes: Vec<Node<'syn>>,
     tokens: TokenList,
@@ -39,6 +38,10 @@ impl<'syn> SyntaxTree<'syn> {
         }
     }
 
+    pub fn into_arena<'new>(self) -> Arena<'new> {
+        self.arena.reset()
+    }
+
     pub fn node(&self, node_id: NodeID) -> &Node {
         &self.nodes[node_id.index()]
     }
@@ -55,8 +58,8 @@ impl<'syn> SyntaxTree<'syn> {
 
 pub fn build<'syn>(
     input: (TokenList, Vec<Event>, Vec<ErrorComp>),
+    mut arena: Arena<'syn>,
 ) -> (SyntaxTree<'syn>, Vec<ErrorComp>) {
-    let mut arena = Arena::new();
     let mut nodes = Vec::new();
     let (tokens, mut events, errors) = input;
 
@@ -219,3 +222,45 @@ pub fn tree_print(tree: &SyntaxTree, source: &str) {
         }
     }
 }
+
+/// source text reconstructed from tokens and trivia in tree order  
+/// syntax tree built with trivia is lossless, result is equal to the source
+pub fn tree_source(tree: &SyntaxTree, source: &str) -> String {
+    let mut output = String::with_capacity(source.len());
+    node_source(tree, source, tree.node(NodeID::new(0)), &mut output);
+    return output;
+
+    fn node_source(tree: &SyntaxTree, source: &str, node: &Node, output: &mut String) {
+        for node_or_token in node.content {
+            let range = match *node_or_token {
+                NodeOrToken::Node(node_id) => {
+                    node_source(tree, source, tree.node(node_id), output);
+                    continue;
+                }
+                NodeOrToken::Token(token_id) => tree.token_range(token_id),
+                NodeOrToken::Trivia(trivia_id) => tree.tokens().trivia_range(trivia_id.index()),
+            };
+            output.push_str(&source[range.as_usize()]);
+        }
+    }
+}
+
+#[test]
+fn tree_source_lossless() {
+    use crate::session::ModuleID;
+
+    let sources = [
+        "",
+        "  // comment\n",
+        "/// doc\nproc main() -> s32 {\n\treturn 0; /* block */ }\n\n",
+        "import core.{ a, b };\r\nstruct S { x: s32, }\n// trailing",
+        "proc broken( { let x = ; } struct",
+        "const A: s32 = 1 + ;\nenum E { A = 0 ",
+        "proc $ main() { let s = \"unterminated }",
+    ];
+
+    for source in sources {
+        let (tree, _) = super::parse(source, ModuleID::dummy(), true);
+        assert_eq!(tree_source(&tree, source), source);
+    }
+}
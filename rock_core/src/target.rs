@@ -0,0 +1,114 @@
+#[derive(Copy, Clone, PartialEq)]
+pub enum TargetArch {
+    X86,
+    X86_64,
+    Aarch64,
+}
+
+#[derive(Copy, Clone, PartialEq)]
+pub enum TargetOs {
+    Windows,
+    Linux,
+    Macos,
+}
+
+/// compilation target, determines type sizes, object format and the linker
+#[derive(Copy, Clone, PartialEq)]
+pub struct TargetTriple {
+    arch: TargetArch,
+    os: TargetOs,
+}
+
+impl TargetTriple {
+    pub const ALL: [TargetTriple; 8] = [
+        TargetTriple::new(TargetArch::X86, TargetOs::Windows),
+        TargetTriple::new(TargetArch::X86_64, TargetOs::Windows),
+        TargetTriple::new(TargetArch::Aarch64, TargetOs::Windows),
+        TargetTriple::new(TargetArch::X86, TargetOs::Linux),
+        TargetTriple::new(TargetArch::X86_64, TargetOs::Linux),
+        TargetTriple::new(TargetArch::Aarch64, TargetOs::Linux),
+        TargetTriple::new(TargetArch::X86_64, TargetOs::Macos),
+        TargetTriple::new(TargetArch::Aarch64, TargetOs::Macos),
+    ];
+
+    pub const fn new(arch: TargetArch, os: TargetOs) -> TargetTriple {
+        TargetTriple { arch, os }
+    }
+
+    /// target of the running compiler, used when `--target` is not specified
+    pub fn host() -> TargetTriple {
+        let arch = if cfg!(target_arch = "x86") {
+            TargetArch::X86
+        } else if cfg!(target_arch = "aarch64") {
+            TargetArch::Aarch64
+        } else {
+            TargetArch::X86_64
+        };
+        let os = if cfg!(target_os = "windows") {
+            TargetOs::Windows
+        } else if cfg!(target_os = "macos") {
+            TargetOs::Macos
+        } else {
+            TargetOs::Linux
+        };
+        TargetTriple::new(arch, os)
+    }
+
+    pub fn arch(self) -> TargetArch {
+        self.arch
+    }
+    pub fn os(self) -> TargetOs {
+        self.os
+    }
+
+    /// size of `usize`, `ssize`, `rawptr`, references and procedure pointers
+    pub fn pointer_width(self) -> u64 {
+        match self.arch {
+            TargetArch::X86 => 4,
+            TargetArch::X86_64 => 8,
+            TargetArch::Aarch64 => 8,
+        }
+    }
+
+    /// calling convention used by `external` procedures without #[call_conv]
+    // 32-bit windows api uses `stdcall`, but C runtime functions
+    // are declared with `cdecl` so `c` is the default everywhere
+    pub(crate) fn default_call_conv(self) -> crate::hir::CallConv {
+        crate::hir::CallConv::C
+    }
+
+    pub fn exe_extension(self) -> Option<&'static str> {
+        match self.os {
+            TargetOs::Windows => Some("exe"),
+            TargetOs::Linux | TargetOs::Macos => None,
+        }
+    }
+    pub fn obj_extension(self) -> &'static str {
+        match self.os {
+            TargetOs::Windows => "obj",
+            TargetOs::Linux | TargetOs::Macos => "o",
+        }
+    }
+
+    /// llvm target triple
+    pub fn as_str(self) -> &'static str {
+        match (self.arch, self.os) {
+            (TargetArch::X86, TargetOs::Windows) => "i686-pc-windows-msvc",
+            (TargetArch::X86_64, TargetOs::Windows) => "x86_64-pc-windows-msvc",
+            (TargetArch::Aarch64, TargetOs::Windows) => "aarch64-pc-windows-msvc",
+            (TargetArch::X86, TargetOs::Linux) => "i686-unknown-linux-gnu",
+            (TargetArch::X86_64, TargetOs::Linux) => "x86_64-unknown-linux-gnu",
+            (TargetArch::Aarch64, TargetOs::Linux) => "aarch64-unknown-linux-gnu",
+            (TargetArch::X86, TargetOs::Macos) => "i686-apple-darwin",
+            (TargetArch::X86_64, TargetOs::Macos) => "x86_64-apple-darwin",
+            (TargetArch::Aarch64, TargetOs::Macos) => "aarch64-apple-darwin",
+        }
+    }
+
+    pub fn from_name(string: &str) -> Option<TargetTriple> {
+        TargetTriple::ALL
+            .iter()
+            .copied()
+            .find(|target| target.as_str() == string)
+    }
+}
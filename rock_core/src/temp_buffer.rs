@@ -5,6 +5,7 @@ pub struct TempBuffer<T: Copy> {
     buffer: Vec<T>,
 }
 
+#[derive(Copy, Clone)]
 pub struct BufferOffset<T: Copy> {
     idx: usize,
     phantom: PhantomData<T>,
@@ -30,6 +31,12 @@ impl<T: Copy> TempBuffer<T> {
         self.buffer.push(value);
     }
 
+    /// discards values added after the offset
+    #[inline]
+    pub fn reset(&mut self, offset: BufferOffset<T>) {
+        self.buffer.truncate(offset.idx);
+    }
+
     pub fn take<'arena>(
         &mut self,
         offset: BufferOffset<T>,
@@ -2,14 +2,14 @@ use std::fmt;
 
 /// `TextRange` byte range in text  
 /// Invariant: `start <= end`
-#[derive(Copy, Clone, PartialEq)]
+#[derive(Copy, Clone, PartialEq, Eq, Hash)]
 pub struct TextRange {
     start: TextOffset,
     end: TextOffset,
 }
 
 /// `TextOffset` byte offset in text
-#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(Copy, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
 pub struct TextOffset(u32);
 
 /// `TextLocation`  
@@ -21,6 +21,29 @@ pub struct TextLocation {
     col: u32,
 }
 
+/// `LineIndex` start offsets of each line  
+/// used to convert between offsets and utf8 or utf16 positions
+pub struct LineIndex {
+    line_starts: Vec<TextOffset>,
+    text_len: TextOffset,
+}
+
+/// `TextEdit` replacement of `range` with `text`
+#[derive(Clone, PartialEq)]
+pub struct TextEdit {
+    pub range: TextRange,
+    pub text: String,
+}
+
+/// `LineCol`  
+/// `line` 0 based line number  
+/// `col`  0 based column, in utf8 bytes or utf16 code units
+#[derive(Copy, Clone, PartialEq)]
+pub struct LineCol {
+    pub line: u32,
+    pub col: u32,
+}
+
 impl TextRange {
     #[inline]
     pub const fn new(start: TextOffset, end: TextOffset) -> TextRange {
@@ -63,7 +86,7 @@ impl TextRange {
         offset.0 >= self.start.0 && offset.0 < self.end.0
     }
     #[inline]
-    const fn contains_inclusive(self, offset: TextOffset) -> bool {
+    pub const fn contains_inclusive(self, offset: TextOffset) -> bool {
         offset.0 >= self.start.0 && offset.0 <= self.end.0
     }
 }
@@ -87,6 +110,87 @@ impl TextLocation {
     }
 }
 
+impl LineIndex {
+    pub fn new(text: &str) -> LineIndex {
+        let mut line_starts = vec![0.into()];
+        for (idx, byte) in text.bytes().enumerate() {
+            if byte == b'\n' {
+                line_starts.push((idx as u32 + 1).into());
+            }
+        }
+        LineIndex {
+            line_starts,
+            text_len: (text.len() as u32).into(),
+        }
+    }
+
+    pub fn line_count(&self) -> usize {
+        self.line_starts.len()
+    }
+
+    /// line range including the line ending
+    pub fn line_range(&self, line: u32) -> Option<TextRange> {
+        let start = *self.line_starts.get(line as usize)?;
+        let end = match self.line_starts.get(line as usize + 1) {
+            Some(&next_start) => next_start,
+            None => self.text_len,
+        };
+        Some(TextRange::new(start, end))
+    }
+
+    /// `col` is in utf8 bytes
+    pub fn line_col(&self, offset: TextOffset) -> LineCol {
+        let line = self.line_starts.partition_point(|&start| start <= offset) - 1;
+        let col = offset - self.line_starts[line];
+        LineCol::new(line as u32, col.into())
+    }
+
+    /// `col` is in utf16 code units
+    pub fn line_col_utf16(&self, text: &str, offset: TextOffset) -> LineCol {
+        let line_col = self.line_col(offset);
+        let prefix_range = TextRange::new(self.line_starts[line_col.line as usize], offset);
+        let prefix = &text[prefix_range.as_usize()];
+        LineCol::new(line_col.line, prefix.encode_utf16().count() as u32)
+    }
+
+    /// `col` is in utf16 code units, columns past the line end are clamped to it,
+    /// columns inside of a surrogate pair are moved to the start of that char
+    pub fn offset_utf16(&self, text: &str, line_col: LineCol) -> Option<TextOffset> {
+        let range = self.line_range(line_col.line)?;
+        let line = &text[range.as_usize()];
+        let line = line.strip_suffix('\n').unwrap_or(line);
+        let line = line.strip_suffix('\r').unwrap_or(line);
+
+        let mut col = 0;
+        for (idx, c) in line.char_indices() {
+            col += c.len_utf16() as u32;
+            if col > line_col.col {
+                return Some(range.start() + (idx as u32).into());
+            }
+        }
+        Some(range.start() + (line.len() as u32).into())
+    }
+
+    pub fn range_utf16(&self, text: &str, range: TextRange) -> (LineCol, LineCol) {
+        let start = self.line_col_utf16(text, range.start());
+        let end = self.line_col_utf16(text, range.end());
+        (start, end)
+    }
+
+    pub fn range_from_utf16(&self, text: &str, start: LineCol, end: LineCol) -> Option<TextRange> {
+        let start = self.offset_utf16(text, start)?;
+        let end = self.offset_utf16(text, end)?;
+        (start <= end).then(|| TextRange::new(start, end))
+    }
+}
+
+impl LineCol {
+    #[inline]
+    pub const fn new(line: u32, col: u32) -> LineCol {
+        LineCol { line, col }
+    }
+}
+
 impl From<u32> for TextOffset {
     #[inline]
     fn from(value: u32) -> TextOffset {
@@ -143,12 +247,119 @@ impl fmt::Debug for TextOffset {
     }
 }
 
+impl fmt::Debug for LineCol {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}", self.line, self.col)
+    }
+}
+
 impl fmt::Debug for TextLocation {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(f, "{}:{}", self.line, self.col)
     }
 }
 
+// middle lines with more combinations are replaced by a single edit
+const DIFF_TABLE_LIMIT: usize = 4_000_000;
+
+/// line based edits which turn `old` into `new`, ranges are in `old`  
+/// edits are sorted and not overlapping, unchanged lines are not included
+pub fn diff_lines(old: &str, new: &str) -> Vec<TextEdit> {
+    let old_lines: Vec<&str> = old.split_inclusive('\n').collect();
+    let new_lines: Vec<&str> = new.split_inclusive('\n').collect();
+
+    let prefix = old_lines
+        .iter()
+        .zip(new_lines.iter())
+        .take_while(|(old_line, new_line)| old_line == new_line)
+        .count();
+    let suffix = old_lines[prefix..]
+        .iter()
+        .rev()
+        .zip(new_lines[prefix..].iter().rev())
+        .take_while(|(old_line, new_line)| old_line == new_line)
+        .count();
+
+    let old_mid = &old_lines[prefix..old_lines.len() - suffix];
+    let new_mid = &new_lines[prefix..new_lines.len() - suffix];
+    let prefix_len: usize = old_lines[..prefix].iter().map(|line| line.len()).sum();
+    let start: TextOffset = (prefix_len as u32).into();
+
+    if old_mid.is_empty() && new_mid.is_empty() {
+        return Vec::new();
+    }
+    if (old_mid.len() + 1) * (new_mid.len() + 1) > DIFF_TABLE_LIMIT {
+        let old_len: usize = old_mid.iter().map(|line| line.len()).sum();
+        let range = TextRange::new(start, start + (old_len as u32).into());
+        return vec![TextEdit {
+            range,
+            text: new_mid.concat(),
+        }];
+    }
+
+    // `table[i * width + j]` is the longest common subsequence of `old_mid[i..]` and `new_mid[j..]`
+    let width = new_mid.len() + 1;
+    let mut table = vec![0_u32; (old_mid.len() + 1) * width];
+    for i in (0..old_mid.len()).rev() {
+        for j in (0..new_mid.len()).rev() {
+            table[i * width + j] = if old_mid[i] == new_mid[j] {
+                table[(i + 1) * width + j + 1] + 1
+            } else {
+                table[(i + 1) * width + j].max(table[i * width + j + 1])
+            };
+        }
+    }
+
+    let mut edits = Vec::new();
+    let mut edit: Option<TextEdit> = None;
+    let mut offset = start;
+    let (mut i, mut j) = (0, 0);
+
+    while i < old_mid.len() || j < new_mid.len() {
+        if i < old_mid.len() && j < new_mid.len() && old_mid[i] == new_mid[j] {
+            edits.extend(edit.take());
+            offset += (old_mid[i].len() as u32).into();
+            i += 1;
+            j += 1;
+        } else if j < new_mid.len()
+            && (i == old_mid.len() || table[i * width + j + 1] >= table[(i + 1) * width + j])
+        {
+            let edit = edit.get_or_insert_with(|| TextEdit {
+                range: TextRange::empty_at(offset),
+                text: String::new(),
+            });
+            edit.text.push_str(new_mid[j]);
+            j += 1;
+        } else {
+            let size: TextOffset = (old_mid[i].len() as u32).into();
+            let edit = edit.get_or_insert_with(|| TextEdit {
+                range: TextRange::empty_at(offset),
+                text: String::new(),
+            });
+            edit.range.extend_by(size);
+            offset += size;
+            i += 1;
+        }
+    }
+    edits.extend(edit);
+    edits
+}
+
+/// utf-8 byte order mark, removed from module sources when loaded
+pub const BOM: char = '\u{feff}';
+
+/// returns `true` if the byte order mark was removed
+pub fn strip_bom(text: &mut String) -> bool {
+    if text.starts_with(BOM) {
+        text.drain(..BOM.len_utf8());
+        true
+    } else {
+        false
+    }
+}
+
+/// line ranges include their `\n` or `\r\n` line ending,
+/// lone `\r` is not a line break, same as in the lexer
 pub fn find_line_ranges(text: &str) -> Vec<TextRange> {
     let mut ranges = Vec::new();
     let mut range = TextRange::empty_at(0.into());
@@ -209,6 +420,32 @@ pub fn find_text_location(
     panic!("{error}");
 }
 
+/// candidate with the smallest case insensitive edit distance,
+/// at most one edit for each 3 characters of `name` is allowed
+pub fn similar_name<'a>(name: &str, candidates: impl Iterator<Item = &'a str>) -> Option<&'a str> {
+    let name: Vec<char> = name.to_lowercase().chars().collect();
+    let max_distance = (name.len() / 3).max(1);
+    let mut best: Option<(usize, &str)> = None;
+
+    for candidate in candidates {
+        let other: Vec<char> = candidate.to_lowercase().chars().collect();
+        let mut prev_row: Vec<usize> = (0..=other.len()).collect();
+        for (i, &c) in name.iter().enumerate() {
+            let mut row = vec![i + 1; other.len() + 1];
+            for (j, &o) in other.iter().enumerate() {
+                let replace = prev_row[j] + (c != o) as usize;
+                row[j + 1] = replace.min(prev_row[j + 1] + 1).min(row[j] + 1);
+            }
+            prev_row = row;
+        }
+        let distance = prev_row[other.len()];
+        if distance <= max_distance && best.is_none_or(|(best, _)| distance < best) {
+            best = Some((distance, candidate));
+        }
+    }
+    best.map(|(_, candidate)| candidate)
+}
+
 #[test]
 fn test() {
     use crate::lexer;
@@ -234,3 +471,81 @@ fn test() {
     assert_eq!(baz_loc_start, TextLocation::new(2, 1));
     assert_eq!(baz_loc_end, TextLocation::new(2, 4));
 }
+
+#[test]
+fn test_mixed_line_endings() {
+    use crate::lexer;
+    use crate::session::ModuleID;
+
+    let text = "foo\r\nbar\nbaz\r\n";
+    let (tokens, errors) = lexer::lex(text, ModuleID::dummy(), false);
+    let line_ranges = find_line_ranges(text);
+    let index = LineIndex::new(text);
+
+    assert!(errors.is_empty());
+    assert_eq!(line_ranges.len(), 3);
+    assert_eq!(line_ranges[0], TextRange::new(0.into(), 5.into()));
+    assert_eq!(line_ranges[1], TextRange::new(5.into(), 9.into()));
+    assert_eq!(line_ranges[2], TextRange::new(9.into(), 14.into()));
+
+    for line in 0..3 {
+        let range = tokens.token_range(line as usize);
+        let location = find_text_location(text, range.start(), &line_ranges);
+        assert_eq!(location, TextLocation::new(line + 1, 1));
+        assert_eq!(index.line_col(range.start()), LineCol::new(line, 0));
+        let location = find_text_location(text, range.end(), &line_ranges);
+        assert_eq!(location, TextLocation::new(line + 1, 4));
+    }
+}
+
+#[test]
+fn test_strip_bom() {
+    let mut text = "\u{feff}foo".to_string();
+    assert!(strip_bom(&mut text));
+    assert_eq!(text, "foo");
+    assert!(!strip_bom(&mut text));
+    assert_eq!(text, "foo");
+}
+
+#[test]
+fn test_line_index() {
+    let text = "a\u{1F600}b\r\nc";
+    let index = LineIndex::new(text);
+
+    assert_eq!(index.line_count(), 2);
+    assert_eq!(index.line_col(5.into()), LineCol::new(0, 5));
+    assert_eq!(index.line_col_utf16(text, 5.into()), LineCol::new(0, 3));
+    assert_eq!(index.line_col_utf16(text, 8.into()), LineCol::new(1, 0));
+    assert_eq!(index.offset_utf16(text, LineCol::new(0, 2)), Some(1.into()));
+    assert_eq!(index.offset_utf16(text, LineCol::new(0, 3)), Some(5.into()));
+    assert_eq!(
+        index.offset_utf16(text, LineCol::new(0, 99)),
+        Some(6.into())
+    );
+    assert_eq!(index.offset_utf16(text, LineCol::new(1, 1)), Some(9.into()));
+    assert_eq!(index.offset_utf16(text, LineCol::new(2, 0)), None);
+}
+
+#[test]
+fn test_diff_lines() {
+    fn apply(text: &str, edits: &[TextEdit]) -> String {
+        let mut output = text.to_string();
+        for edit in edits.iter().rev() {
+            output.replace_range(edit.range.as_usize(), &edit.text);
+        }
+        output
+    }
+
+    let old = "a\nb\nc\nd\n";
+    let new = "a\nB\nc\nd\ne\n";
+    let edits = diff_lines(old, new);
+    assert_eq!(edits.len(), 2);
+    assert_eq!(edits[0].range, TextRange::new(2.into(), 4.into()));
+    assert_eq!(edits[0].text, "B\n");
+    assert_eq!(edits[1].range, TextRange::empty_at(8.into()));
+    assert_eq!(apply(old, &edits), new);
+
+    assert!(diff_lines(old, old).is_empty());
+    assert_eq!(apply("x\ny", &diff_lines("x\ny", "y\nx\n")), "y\nx\n");
+    assert_eq!(apply("", &diff_lines("", "a\n")), "a\n");
+}
@@ -1,9 +1,26 @@
+use std::time::Duration;
+#[cfg(not(target_arch = "wasm32"))]
 use std::time::Instant;
 
 pub struct Timer {
     start: Instant,
 }
 
+/// wall time and arena memory of each compilation stage,
+/// `files` hold lex and parse time of each module
+#[derive(Default)]
+pub struct Timings {
+    pub stages: Vec<Timing>,
+    pub files: Vec<Timing>,
+}
+
+pub struct Timing {
+    pub name: String,
+    pub duration: Duration,
+    /// arena bytes allocated at the end of the stage, `None` if not tracked
+    pub mem_usage: Option<usize>,
+}
+
 impl Timer {
     pub fn new() -> Timer {
         Timer {
@@ -11,9 +28,71 @@ impl Timer {
         }
     }
 
+    pub fn elapsed(&self) -> Duration {
+        self.start.elapsed()
+    }
+
     pub fn stop(self, msg: &str) {
         let end = Instant::now();
         let ms = end.duration_since(self.start).as_secs_f64() * 1000.0;
         eprintln!("{}: {:.3} ms", msg, ms);
     }
+
+    /// record time since the last lap as a stage, then restart
+    pub fn lap(&mut self, timings: &mut Timings, name: &str, mem_usage: Option<usize>) {
+        let now = Instant::now();
+        timings.stage(name, now.duration_since(self.start), mem_usage);
+        self.start = now;
+    }
+}
+
+impl Timings {
+    pub fn new() -> Timings {
+        Timings::default()
+    }
+
+    pub fn stage(&mut self, name: &str, duration: Duration, mem_usage: Option<usize>) {
+        self.stages.push(Timing {
+            name: name.to_string(),
+            duration,
+            mem_usage,
+        });
+    }
+
+    pub fn file(&mut self, name: String, duration: Duration) {
+        self.files.push(Timing {
+            name,
+            duration,
+            mem_usage: None,
+        });
+    }
+
+    /// append stages and files recorded by a later step
+    pub fn extend(&mut self, other: Timings) {
+        self.stages.extend(other.stages);
+        self.files.extend(other.files);
+    }
+
+    pub fn total(&self) -> Duration {
+        self.stages.iter().map(|timing| timing.duration).sum()
+    }
+}
+
+// clock is not available on `wasm32-unknown-unknown`, `Instant::now` would panic
+// stages are still recorded, their duration is always zero
+#[cfg(target_arch = "wasm32")]
+#[derive(Copy, Clone)]
+struct Instant;
+
+#[cfg(target_arch = "wasm32")]
+impl Instant {
+    fn now() -> Instant {
+        Instant
+    }
+    fn elapsed(&self) -> Duration {
+        Duration::ZERO
+    }
+    fn duration_since(&self, _earlier: Instant) -> Duration {
+        Duration::ZERO
+    }
 }
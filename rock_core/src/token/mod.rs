@@ -1,3 +1,4 @@
+pub mod token_cursor;
 mod token_gen;
 pub mod token_list;
 
@@ -22,6 +23,7 @@ token_gen::token_gen! {
     [const]    | "const"    | KwConst    | KW.
     [global]   | "global"   | KwGlobal   | KW.
     [import]   | "import"   | KwImport   | KW.
+    [impl]     | "impl"     | KwImpl     | KW.
 
     // keyword statements
     [break]    | "break"    | KwBreak    | KW.
@@ -80,6 +82,10 @@ token_gen::token_gen! {
     [..]     | ".."     | DotDot       |
     [->]     | "->"     | ArrowThin    |
 
+    // range tokens
+    ["..<"]  | "..<"    | Range        |
+    ["..="]  | "..="    | RangeInc     |
+
     // un op tokens
     [~]      | "~"      | Tilde        | UN[UnOp::BitNot]
     [!]      | "!"      | Bang         | UN[UnOp::LogicNot]
@@ -103,8 +109,6 @@ token_gen::token_gen! {
     [>=]     | ">="     | GreaterEq    | BIN[BinOp::GreaterEq]
     [&&]     | "&&"     | LogicAnd     | BIN[BinOp::LogicAnd]
     [||]     | "||"     | LogicOr      | BIN[BinOp::LogicOr]
-    ["..<"]  | "..<"    | Range        | BIN[BinOp::Range]
-    ["..="]  | "..="    | RangeInc     | BIN[BinOp::RangeInc]
 
     // assign op tokens
     [=]      | "="      | Equals       | ASSIGN[AssignOp::Assign]
@@ -200,5 +204,8 @@ impl Token {
 pub enum Trivia {
     Whitespace,
     LineComment,
+    DocComment,
     BlockComment,
+    /// unknown symbol, reported by the lexer and kept for lossless syntax tree
+    Error,
 }
@@ -0,0 +1,181 @@
+use super::token_list::TokenList;
+use super::{Token, Trivia};
+use crate::text::TextRange;
+
+/// forward cursor over the `TokenList` with arbitrary lookahead and backtracking,
+/// shared by the parsers and the tooling which works with lexed tokens
+/// trivia is skipped by default, `with_trivia` also yields trivia in source order
+pub struct TokenCursor<'tokens> {
+    tokens: &'tokens TokenList,
+    cursor: usize,
+    trivia_cursor: usize,
+    skip_trivia: bool,
+}
+
+/// position in the token stream, restored with `TokenCursor::reset`
+#[derive(Copy, Clone, PartialEq)]
+pub struct Checkpoint {
+    cursor: usize,
+    trivia_cursor: usize,
+}
+
+#[derive(Copy, Clone, PartialEq)]
+pub enum TokenOrTrivia {
+    Token(Token),
+    Trivia(Trivia),
+}
+
+impl<'tokens> TokenCursor<'tokens> {
+    pub fn new(tokens: &'tokens TokenList) -> TokenCursor<'tokens> {
+        TokenCursor {
+            tokens,
+            cursor: 0,
+            trivia_cursor: 0,
+            skip_trivia: true,
+        }
+    }
+
+    /// trivia is only available when tokens were lexed with trivia
+    pub fn with_trivia(tokens: &'tokens TokenList) -> TokenCursor<'tokens> {
+        TokenCursor {
+            skip_trivia: false,
+            ..TokenCursor::new(tokens)
+        }
+    }
+
+    pub fn tokens(&self) -> &'tokens TokenList {
+        self.tokens
+    }
+    /// index of the current token
+    pub fn position(&self) -> usize {
+        self.cursor
+    }
+
+    pub fn peek(&self) -> Token {
+        self.peek_nth(0)
+    }
+    /// token `n` positions ahead of the current token, `eof` past the end
+    pub fn peek_nth(&self, n: usize) -> Token {
+        self.tokens.token(self.clamp(self.cursor + n))
+    }
+    pub fn peek_range(&self) -> TextRange {
+        self.peek_nth_range(0)
+    }
+    pub fn peek_nth_range(&self, n: usize) -> TextRange {
+        self.tokens.token_range(self.clamp(self.cursor + n))
+    }
+    /// previous token, must not be called at the start
+    pub fn prev(&self) -> Token {
+        self.tokens.token(self.cursor - 1)
+    }
+    pub fn prev_range(&self) -> TextRange {
+        self.tokens.token_range(self.cursor - 1)
+    }
+
+    pub fn at(&self, t: Token) -> bool {
+        self.peek() == t
+    }
+    pub fn at_next(&self, t: Token) -> bool {
+        self.peek_nth(1) == t
+    }
+    pub fn at_prev(&self, t: Token) -> bool {
+        self.prev() == t
+    }
+
+    pub fn eat(&mut self, t: Token) -> bool {
+        if self.at(t) {
+            self.bump();
+            return true;
+        }
+        false
+    }
+
+    /// move to the next token, trivia before it is skipped
+    pub fn bump(&mut self) {
+        self.cursor += 1;
+        if !self.skip_trivia && self.cursor < self.tokens.token_count() {
+            let start = self.tokens.token_range(self.cursor).start();
+            while self.trivia_cursor < self.tokens.trivia_count()
+                && self.tokens.trivia_range(self.trivia_cursor).start() < start
+            {
+                self.trivia_cursor += 1;
+            }
+        }
+    }
+
+    pub fn checkpoint(&self) -> Checkpoint {
+        Checkpoint {
+            cursor: self.cursor,
+            trivia_cursor: self.trivia_cursor,
+        }
+    }
+    pub fn reset(&mut self, checkpoint: Checkpoint) {
+        self.cursor = checkpoint.cursor;
+        self.trivia_cursor = checkpoint.trivia_cursor;
+    }
+
+    /// next token or trivia in source order, `None` after `eof`
+    pub fn next_item(&mut self) -> Option<(TokenOrTrivia, TextRange)> {
+        if let Some(item) = self.next_trivia() {
+            return Some(item);
+        }
+        if self.cursor >= self.tokens.token_count() {
+            return None;
+        }
+        let token = self.peek();
+        let range = self.peek_range();
+        if token == Token::Eof {
+            self.cursor = self.tokens.token_count();
+        } else {
+            self.cursor += 1;
+        }
+        Some((TokenOrTrivia::Token(token), range))
+    }
+
+    fn next_trivia(&mut self) -> Option<(TokenOrTrivia, TextRange)> {
+        if self.skip_trivia || self.trivia_cursor >= self.tokens.trivia_count() {
+            return None;
+        }
+        let range = self.tokens.trivia_range(self.trivia_cursor);
+        // `eof` tokens have a dummy range, remaining trivia comes before them
+        let before_token = self.cursor >= self.tokens.token_count()
+            || self.peek() == Token::Eof
+            || range.start() < self.peek_range().start();
+        if !before_token {
+            return None;
+        }
+        let trivia = self.tokens.trivia(self.trivia_cursor);
+        self.trivia_cursor += 1;
+        Some((TokenOrTrivia::Trivia(trivia), range))
+    }
+
+    fn clamp(&self, index: usize) -> usize {
+        index.min(self.tokens.token_count() - 1)
+    }
+}
+
+#[test]
+fn test_trivia_order() {
+    use crate::lexer;
+    use crate::session::ModuleID;
+
+    let text = "// comment\nproc main() {  /* block */ }\n";
+    let (tokens, _) = lexer::lex(text, ModuleID::dummy(), true);
+    let mut cursor = TokenCursor::with_trivia(&tokens);
+
+    let mut lossless = String::new();
+    while let Some((item, range)) = cursor.next_item() {
+        if item != TokenOrTrivia::Token(Token::Eof) {
+            lossless.push_str(&text[range.as_usize()]);
+        }
+    }
+    assert_eq!(lossless, text);
+
+    let mut cursor = TokenCursor::new(&tokens);
+    let checkpoint = cursor.checkpoint();
+    assert!(cursor.eat(Token::KwProc));
+    assert!(cursor.at(Token::Ident) && cursor.at_next(Token::ParenOpen));
+    cursor.reset(checkpoint);
+    assert!(cursor.at(Token::KwProc));
+    assert!(cursor.peek_nth(100) == Token::Eof);
+}
@@ -1,4 +1,5 @@
 use super::{Token, Trivia};
+use crate::ast::StringKind;
 use crate::text::TextRange;
 
 pub struct TokenList {
@@ -7,7 +8,8 @@ pub struct TokenList {
     trivias: Vec<Trivia>,
     trivia_ranges: Vec<TextRange>,
     chars: Vec<char>,
-    strings: Vec<(String, bool)>,
+    strings: Vec<(String, StringKind)>,
+    doc_comments: Vec<TextRange>,
 }
 
 impl TokenList {
@@ -19,6 +21,7 @@ impl TokenList {
             trivia_ranges: Vec::new(),
             chars: Vec::new(),
             strings: Vec::new(),
+            doc_comments: Vec::new(),
         }
     }
 
@@ -28,6 +31,9 @@ impl TokenList {
     pub fn token_range(&self, index: usize) -> TextRange {
         self.token_ranges[index]
     }
+    pub fn token_count(&self) -> usize {
+        self.tokens.len()
+    }
     pub fn trivia(&self, index: usize) -> Trivia {
         self.trivias[index]
     }
@@ -40,9 +46,19 @@ impl TokenList {
     pub fn char(&self, index: usize) -> char {
         self.chars[index]
     }
-    pub fn string(&self, index: usize) -> (&str, bool) {
-        let (string, c_string) = &self.strings[index];
-        (string, *c_string)
+    pub fn string(&self, index: usize) -> (&str, StringKind) {
+        let (string, kind) = &self.strings[index];
+        (string, *kind)
+    }
+    pub fn string_count(&self) -> usize {
+        self.strings.len()
+    }
+    /// doc comments starting within the range, in source order
+    pub fn doc_comments_in(&self, range: TextRange) -> &[TextRange] {
+        let docs = &self.doc_comments;
+        let start = docs.partition_point(|doc| doc.start() < range.start());
+        let end = docs.partition_point(|doc| doc.start() < range.end());
+        &docs[start..end]
     }
 
     pub fn add_token(&mut self, token: Token, range: TextRange) {
@@ -53,14 +69,17 @@ impl TokenList {
         self.trivias.push(trivia);
         self.trivia_ranges.push(range);
     }
+    pub fn add_doc_comment(&mut self, range: TextRange) {
+        self.doc_comments.push(range);
+    }
     pub fn add_char(&mut self, c: char, range: TextRange) {
         self.tokens.push(Token::CharLit);
         self.token_ranges.push(range);
         self.chars.push(c);
     }
-    pub fn add_string(&mut self, s: String, c_string: bool, range: TextRange) {
+    pub fn add_string(&mut self, s: String, kind: StringKind, range: TextRange) {
         self.tokens.push(Token::StringLit);
         self.token_ranges.push(range);
-        self.strings.push((s, c_string));
+        self.strings.push((s, kind));
     }
 }
@@ -0,0 +1,241 @@
+use crate::error::ErrorComp;
+use crate::fs_env;
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::time::SystemTime;
+
+/// source of files and directories used to create a `Session`
+/// `RealFs` reads from disk, `MemoryFs` from a map of paths,
+/// `OverlayFs` layers one provider above another, like open editor files above disk
+pub trait FileProvider {
+    fn kind(&self, path: &PathBuf) -> Option<EntryKind>;
+    fn read_to_string(&self, path: &PathBuf) -> Result<String, ErrorComp>;
+    /// entries sorted by path
+    fn read_dir(&self, path: &PathBuf) -> Result<Vec<DirEntry>, ErrorComp>;
+    fn metadata(&self, path: &PathBuf) -> Result<FileMetadata, ErrorComp>;
+
+    fn exists(&self, path: &PathBuf) -> bool {
+        self.kind(path).is_some()
+    }
+}
+
+#[derive(Copy, Clone, PartialEq)]
+pub enum EntryKind {
+    File,
+    Dir,
+}
+
+pub struct DirEntry {
+    pub path: PathBuf,
+    pub kind: EntryKind,
+}
+
+/// used to detect changed inputs of incremental builds
+/// `modified` is `None` for in-memory files
+#[derive(Copy, Clone, PartialEq)]
+pub struct FileMetadata {
+    pub modified: Option<SystemTime>,
+    pub hash: u64,
+}
+
+pub struct RealFs;
+
+#[derive(Clone, Default)]
+pub struct MemoryFs {
+    files: HashMap<PathBuf, String>,
+}
+
+pub struct OverlayFs<'fs> {
+    upper: &'fs dyn FileProvider,
+    lower: &'fs dyn FileProvider,
+}
+
+impl FileProvider for RealFs {
+    fn kind(&self, path: &PathBuf) -> Option<EntryKind> {
+        if path.is_file() {
+            Some(EntryKind::File)
+        } else if path.is_dir() {
+            Some(EntryKind::Dir)
+        } else {
+            None
+        }
+    }
+
+    fn read_to_string(&self, path: &PathBuf) -> Result<String, ErrorComp> {
+        fs_env::file_read_to_string(path)
+    }
+
+    fn read_dir(&self, path: &PathBuf) -> Result<Vec<DirEntry>, ErrorComp> {
+        let mut entries = Vec::new();
+        let read_dir = fs_env::dir_read(path)?;
+        for entry_result in read_dir {
+            let entry = fs_env::dir_entry_validate(path, entry_result)?;
+            let entry_path = entry.path();
+            fs_env::symlink_forbid(&entry_path)?;
+
+            if let Some(kind) = self.kind(&entry_path) {
+                entries.push(DirEntry {
+                    path: entry_path,
+                    kind,
+                });
+            }
+        }
+        entries.sort_by(|a, b| a.path.cmp(&b.path));
+        Ok(entries)
+    }
+
+    fn metadata(&self, path: &PathBuf) -> Result<FileMetadata, ErrorComp> {
+        let metadata = std::fs::metadata(path).map_err(|io_error| {
+            ErrorComp::message(format!(
+                "failed to read file metadata: `{}`\nreason: {}",
+                path.to_string_lossy(),
+                io_error
+            ))
+        })?;
+        let source = fs_env::file_read_to_string(path)?;
+        Ok(FileMetadata {
+            modified: metadata.modified().ok(),
+            hash: content_hash(&source),
+        })
+    }
+}
+
+impl MemoryFs {
+    pub fn new() -> MemoryFs {
+        MemoryFs::default()
+    }
+
+    pub fn get(&self, path: &PathBuf) -> Option<&String> {
+        self.files.get(path)
+    }
+    pub fn get_mut(&mut self, path: &PathBuf) -> Option<&mut String> {
+        self.files.get_mut(path)
+    }
+    pub fn insert(&mut self, path: PathBuf, text: String) {
+        self.files.insert(path, text);
+    }
+    pub fn remove(&mut self, path: &PathBuf) -> Option<String> {
+        self.files.remove(path)
+    }
+}
+
+// directories are implicit, any proper prefix of a file path is a directory
+impl FileProvider for MemoryFs {
+    fn kind(&self, path: &PathBuf) -> Option<EntryKind> {
+        if self.files.contains_key(path) {
+            return Some(EntryKind::File);
+        }
+        let is_dir = self
+            .files
+            .keys()
+            .any(|file_path| file_path != path && file_path.starts_with(path));
+        is_dir.then_some(EntryKind::Dir)
+    }
+
+    fn read_to_string(&self, path: &PathBuf) -> Result<String, ErrorComp> {
+        match self.files.get(path) {
+            Some(text) => Ok(text.clone()),
+            None => Err(ErrorComp::message(format!(
+                "failed to read file: `{}`\nreason: file is not found in memory",
+                path.to_string_lossy()
+            ))),
+        }
+    }
+
+    fn read_dir(&self, path: &PathBuf) -> Result<Vec<DirEntry>, ErrorComp> {
+        if self.kind(path) != Some(EntryKind::Dir) {
+            return Err(ErrorComp::message(format!(
+                "failed to read directory: `{}`\nreason: directory is not found in memory",
+                path.to_string_lossy()
+            )));
+        }
+
+        let mut entries: Vec<DirEntry> = Vec::new();
+        for file_path in self.files.keys() {
+            let relative = match file_path.strip_prefix(path) {
+                Ok(relative) => relative,
+                Err(_) => continue,
+            };
+            let mut components = relative.components();
+            let first = match components.next() {
+                Some(first) => first,
+                None => continue,
+            };
+            let kind = if components.next().is_none() {
+                EntryKind::File
+            } else {
+                EntryKind::Dir
+            };
+            let entry_path = path.join(first);
+            if !entries.iter().any(|entry| entry.path == entry_path) {
+                entries.push(DirEntry {
+                    path: entry_path,
+                    kind,
+                });
+            }
+        }
+        entries.sort_by(|a, b| a.path.cmp(&b.path));
+        Ok(entries)
+    }
+
+    fn metadata(&self, path: &PathBuf) -> Result<FileMetadata, ErrorComp> {
+        let source = self.read_to_string(path)?;
+        Ok(FileMetadata {
+            modified: None,
+            hash: content_hash(&source),
+        })
+    }
+}
+
+impl<'fs> OverlayFs<'fs> {
+    pub fn new(upper: &'fs dyn FileProvider, lower: &'fs dyn FileProvider) -> OverlayFs<'fs> {
+        OverlayFs { upper, lower }
+    }
+}
+
+// files of the upper provider shadow the lower, directory entries are merged
+impl<'fs> FileProvider for OverlayFs<'fs> {
+    fn kind(&self, path: &PathBuf) -> Option<EntryKind> {
+        self.upper.kind(path).or_else(|| self.lower.kind(path))
+    }
+
+    fn read_to_string(&self, path: &PathBuf) -> Result<String, ErrorComp> {
+        match self.upper.kind(path) {
+            Some(EntryKind::File) => self.upper.read_to_string(path),
+            _ => self.lower.read_to_string(path),
+        }
+    }
+
+    fn read_dir(&self, path: &PathBuf) -> Result<Vec<DirEntry>, ErrorComp> {
+        if self.upper.kind(path) != Some(EntryKind::Dir) {
+            return self.lower.read_dir(path);
+        }
+        let mut entries = self.upper.read_dir(path)?;
+        if self.lower.kind(path) == Some(EntryKind::Dir) {
+            for entry in self.lower.read_dir(path)? {
+                if !entries.iter().any(|upper| upper.path == entry.path) {
+                    entries.push(entry);
+                }
+            }
+        }
+        entries.sort_by(|a, b| a.path.cmp(&b.path));
+        Ok(entries)
+    }
+
+    fn metadata(&self, path: &PathBuf) -> Result<FileMetadata, ErrorComp> {
+        match self.upper.kind(path) {
+            Some(EntryKind::File) => self.upper.metadata(path),
+            _ => self.lower.metadata(path),
+        }
+    }
+}
+
+// FNV-1a, stable across toolchain builds unlike std `DefaultHasher`
+pub fn content_hash(text: &str) -> u64 {
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+    for byte in text.bytes() {
+        hash ^= byte as u64;
+        hash = hash.wrapping_mul(0x0100_0000_01b3);
+    }
+    hash
+}
@@ -0,0 +1,102 @@
+//! placement of deferred blocks in the printed hir
+//!
+//! each exit of a scope runs its `defer` blocks in place, in reverse order:
+//! `break` and `continue` run the defers of the loop, `return` runs all of them
+//! and falling through the end of a block runs the defers of that block
+
+use rock_core::driver::{CompileOptions, EmitKind, Stage};
+use rock_core::embed;
+use rock_core::target::TargetTriple;
+
+const SOURCE: &str = "\
+proc work(_x: s32) {}
+
+pub proc run(n: s32) -> s32 {
+    defer { work(1); }
+    for {
+        defer { work(2); }
+        if n > 2 { break; }
+        if n > 1 { continue; }
+        if n > 0 { return n; }
+    }
+    defer {
+        for {
+            if n > 3 { break; }
+            work(3);
+            continue;
+        }
+    }
+    return 0;
+}
+
+pub proc fall() {
+    defer { work(4); }
+    work(5);
+}
+";
+
+const EXPECTED: &str = "\
+proc work(_x: s32) -> void {}
+
+pub proc run(n: s32) -> s32 {
+    for {
+        if (n > 2) {
+            defer {
+                work(2);
+            }
+            break;
+        };
+        if (n > 1) {
+            defer {
+                work(2);
+            }
+            continue;
+        };
+        if (n > 0) {
+            defer {
+                work(2);
+            }
+            defer {
+                work(1);
+            }
+            return n;
+        };
+        defer {
+            work(2);
+        }
+    }
+    defer {
+        for {
+            if (n > 3) {
+                break;
+            };
+            work(3);
+            continue;
+        }
+    }
+    defer {
+        work(1);
+    }
+    return 0;
+}
+
+pub proc fall() -> void {
+    work(5);
+    defer {
+        work(4);
+    }
+}";
+
+#[test]
+fn defer_emission() {
+    let mut options = CompileOptions::new(Stage::Check, TargetTriple::host());
+    options.emit.set(EmitKind::Hir);
+    let result = embed::compile_str(&[("lib.rock", SOURCE)], &options);
+    assert!(!result.has_errors());
+
+    let hir = match result.emitted.first() {
+        Some((EmitKind::Hir, hir)) => hir.trim_end(),
+        _ => panic!("hir was not emitted"),
+    };
+    assert!(hir == EXPECTED, "unexpected defer placement:\n{hir}");
+}
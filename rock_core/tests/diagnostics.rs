@@ -0,0 +1,376 @@
+//! golden diagnostic tests
+//!
+//! each `tests/diagnostics/*.rock` fixture is checked as a separate library package,
+//! expected diagnostics are annotated in comments below the line they point to:
+//! ```text
+//! let x: s32 = true;
+//! //           ^^^^ error[E0038]: type mismatch: expected `s32`, found `bool`
+//! ```
+//! caret column and count must match the diagnostic range on the closest code line above,
+//! `error` or `warning` kind, code and first line of the message are compared exactly
+
+use rock_core::error::{Diagnostic, DiagnosticCollection};
+use rock_core::format;
+use rock_core::hir_lower;
+use rock_core::session::{ModuleID, Session};
+use rock_core::target::TargetTriple;
+use rock_core::text::LineIndex;
+use std::path::{Path, PathBuf};
+
+#[derive(PartialEq)]
+struct Expected {
+    line: u32,
+    col: u32,
+    len: u32,
+    kind: &'static str,
+    code: String,
+    message: String,
+}
+
+#[test]
+fn diagnostics() {
+    let fixture_dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/diagnostics");
+    let mut fixtures: Vec<PathBuf> = std::fs::read_dir(&fixture_dir)
+        .expect("failed to read `tests/diagnostics`")
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "rock"))
+        .collect();
+    fixtures.sort();
+    assert!(!fixtures.is_empty(), "no diagnostic fixtures found");
+
+    let mut failures = Vec::new();
+    for path in fixtures.iter() {
+        check_fixture(path, &mut failures);
+    }
+    if !failures.is_empty() {
+        panic!(
+            "{} diagnostic mismatches:\n{}",
+            failures.len(),
+            failures.join("\n")
+        );
+    }
+}
+
+fn check_fixture(path: &Path, failures: &mut Vec<String>) {
+    let source = std::fs::read_to_string(path).unwrap();
+    let filename = path.file_name().unwrap().to_str().unwrap();
+    let mut expected = match parse_annotations(&source) {
+        Ok(expected) => expected,
+        Err((line, message)) => {
+            failures.push(format!("{filename}:{}: {message}", line + 1));
+            return;
+        }
+    };
+
+    let files = [(PathBuf::from(filename), source.clone())];
+    let (session, diagnostics) = match hir_lower::check_virtual(&files, TargetTriple::host()) {
+        Ok(result) => result,
+        Err(error) => {
+            let message = error.diagnostic().message().as_str();
+            failures.push(format!("{filename}: session error: {message}"));
+            return;
+        }
+    };
+    let line_index = LineIndex::new(&source);
+
+    for (kind, diagnostic) in all_diagnostics(&diagnostics) {
+        let found = match found_diagnostic(&session, &source, &line_index, kind, diagnostic) {
+            Some(found) => found,
+            None => {
+                failures.push(format!(
+                    "{filename}: unexpected {kind} without source: {}",
+                    diagnostic.message().as_str()
+                ));
+                continue;
+            }
+        };
+        if let Some(idx) = expected.iter().position(|e| *e == found) {
+            expected.remove(idx);
+        } else {
+            failures.push(format!(
+                "{filename}:{}: unexpected {}",
+                found.line + 1,
+                format_expected(&found)
+            ));
+        }
+    }
+    for missing in expected {
+        failures.push(format!(
+            "{filename}:{}: missing {}",
+            missing.line + 1,
+            format_expected(&missing)
+        ));
+    }
+}
+
+fn all_diagnostics(diagnostics: &DiagnosticCollection) -> Vec<(&'static str, &Diagnostic)> {
+    let errors = diagnostics
+        .errors()
+        .iter()
+        .map(|e| ("error", e.diagnostic()));
+    let warnings = diagnostics
+        .warnings()
+        .iter()
+        .map(|w| ("warning", w.diagnostic()));
+    errors.chain(warnings).collect()
+}
+
+fn found_diagnostic(
+    session: &Session,
+    source: &str,
+    line_index: &LineIndex,
+    kind: &'static str,
+    diagnostic: &Diagnostic,
+) -> Option<Expected> {
+    let main = diagnostic.main_source()?;
+    assert!(session.module(main.module_id()).source == source);
+
+    let range = main.range();
+    let start = line_index.line_col(range.start());
+    let line_range = line_index.line_range(start.line).unwrap();
+    let line_end: u32 = line_range.end().into();
+    let end = u32::from(range.end()).min(line_end);
+    let len = (end - u32::from(range.start())).max(1);
+
+    Some(Expected {
+        line: start.line,
+        col: start.col,
+        len,
+        kind,
+        code: diagnostic
+            .code()
+            .map(|c| c.as_str())
+            .unwrap_or("")
+            .to_string(),
+        message: diagnostic
+            .message()
+            .as_str()
+            .lines()
+            .next()
+            .unwrap_or("")
+            .to_string(),
+    })
+}
+
+fn parse_annotations(source: &str) -> Result<Vec<Expected>, (u32, String)> {
+    let mut expected = Vec::new();
+    let mut code_line = None;
+
+    for (line, text) in source.lines().enumerate() {
+        let line = line as u32;
+        let trimmed = text.trim_start();
+        let annotation = trimmed
+            .strip_prefix("//")
+            .filter(|rest| rest.trim_start().starts_with('^'));
+        let Some(annotation) = annotation else {
+            code_line = Some(line);
+            continue;
+        };
+        let Some(target_line) = code_line else {
+            return Err((line, "annotation without a code line above".to_string()));
+        };
+
+        let col = (text.len() - annotation.trim_start().len()) as u32;
+        let carets = annotation.trim_start();
+        let len = carets.bytes().take_while(|&b| b == b'^').count() as u32;
+        let rest = carets[len as usize..].trim_start();
+
+        let (kind, rest) = if let Some(rest) = rest.strip_prefix("error") {
+            ("error", rest)
+        } else if let Some(rest) = rest.strip_prefix("warning") {
+            ("warning", rest)
+        } else {
+            return Err((
+                line,
+                "expected `error` or `warning` after carets".to_string(),
+            ));
+        };
+        let (code, message) = match rest.strip_prefix('[') {
+            Some(rest) => match rest.split_once("]:") {
+                Some((code, message)) => (code.to_string(), message),
+                None => return Err((line, "expected `[code]:` after kind".to_string())),
+            },
+            None => match rest.strip_prefix(':') {
+                Some(message) => (String::new(), message),
+                None => return Err((line, "expected `:` after kind".to_string())),
+            },
+        };
+
+        expected.push(Expected {
+            line: target_line,
+            col,
+            len,
+            kind,
+            code,
+            message: message.trim().to_string(),
+        });
+    }
+    Ok(expected)
+}
+
+fn format_expected(e: &Expected) -> String {
+    let code = if e.code.is_empty() {
+        String::new()
+    } else {
+        format!("[{}]", e.code)
+    };
+    format!(
+        "{}{code} at {}:{} len {}: {}",
+        e.kind,
+        e.line + 1,
+        e.col + 1,
+        e.len,
+        e.message
+    )
+}
+
+// fixtures are single modules, import cycles need a package with several of them
+#[test]
+fn import_cycle() {
+    let files = [
+        (PathBuf::from("a.rock"), "import b;\n".to_string()),
+        (PathBuf::from("b.rock"), "import c;\n".to_string()),
+        (PathBuf::from("c.rock"), "import a;\n".to_string()),
+    ];
+    let (session, diagnostics) = hir_lower::check_virtual(&files, TargetTriple::host())
+        .unwrap_or_else(|_| panic!("session error"));
+
+    let cycles: Vec<&Diagnostic> = diagnostics
+        .warnings()
+        .iter()
+        .filter(|w| w.code().is_some_and(|c| c.as_str() == "import_cycle"))
+        .map(|w| w.diagnostic())
+        .collect();
+    assert_eq!(cycles.len(), 1);
+    assert_eq!(
+        cycles[0].message().as_str(),
+        "import cycle found: `virtual:a` -> `virtual:b` -> `virtual:c` -> `virtual:a`"
+    );
+
+    let main = cycles[0].main_source().unwrap();
+    let module = session.module(main.module_id());
+    assert_eq!(module.path.file_name().unwrap(), "a.rock");
+    assert_eq!(&module.source[main.range().as_usize()], "b");
+}
+
+// glob imports only bring public items into scope,
+// first glob wins and a different item with the same name is a duplicate definition
+#[test]
+fn glob_and_alias_imports() {
+    let files = [
+        (
+            PathBuf::from("a.rock"),
+            "pub proc value() -> s32 { return 1; }\nproc hidden() -> s32 { return 2; }\n"
+                .to_string(),
+        ),
+        (
+            PathBuf::from("b.rock"),
+            "pub proc value() -> s32 { return 3; }\n".to_string(),
+        ),
+        (
+            PathBuf::from("main.rock"),
+            "import a as m;\nimport a.*;\nimport b.*;\n\
+             pub proc check() -> s32 { return m.value() + value() + hidden(); }\n"
+                .to_string(),
+        ),
+    ];
+    let (session, diagnostics) = hir_lower::check_virtual(&files, TargetTriple::host())
+        .unwrap_or_else(|_| panic!("session error"));
+
+    let errors: Vec<&Diagnostic> = diagnostics
+        .errors()
+        .iter()
+        .map(|e| e.diagnostic())
+        .collect();
+    assert_eq!(errors.len(), 2);
+    assert_eq!(
+        errors[0].message().as_str(),
+        "name `value` is defined multiple times"
+    );
+    assert_eq!(
+        errors[1].message().as_str(),
+        "name `hidden` is not found in module"
+    );
+
+    let main = errors[0].main_source().unwrap();
+    let module = session.module(main.module_id());
+    assert_eq!(module.path.file_name().unwrap(), "main.rock");
+    assert_eq!(main.range().start(), 36.into());
+}
+
+// fixture files are checked out with `\n`, line endings and bom are tested in memory
+#[test]
+fn bom_and_crlf() {
+    let source = "\u{feff}pub proc check() {\r\n    let _x: s32 = true;\n}\r\n";
+    let files = [(PathBuf::from("main.rock"), source.to_string())];
+    let (session, diagnostics) = hir_lower::check_virtual(&files, TargetTriple::host())
+        .unwrap_or_else(|_| panic!("session error"));
+
+    let module = session.module(session.module_ids().next().unwrap());
+    assert!(module.bom);
+    assert_eq!(module.source, &source[3..]);
+    assert_eq!(diagnostics.errors().len(), 1);
+
+    let error = diagnostics.errors()[0].diagnostic();
+    let main = error.main_source().unwrap();
+    let line_index = LineIndex::new(&module.source);
+    let start = line_index.line_col(main.range().start());
+    assert_eq!(&module.source[main.range().as_usize()], "true");
+    assert_eq!((start.line, start.col), (1, 18));
+}
+
+#[test]
+fn nesting_limit() {
+    let depth = rock_core::ast_parse::NESTING_LIMIT as usize + 1;
+    let source = format!(
+        "pub proc nested() -> s32 {{\n    return {}1{};\n}}\n",
+        "(".repeat(depth),
+        ")".repeat(depth)
+    );
+    let files = [(PathBuf::from("nested.rock"), source)];
+    let (_, diagnostics) = hir_lower::check_virtual(&files, TargetTriple::host())
+        .unwrap_or_else(|_| panic!("session error"));
+
+    let error = diagnostics.errors()[0].diagnostic();
+    assert_eq!(
+        error.message().as_str(),
+        "expression is nested too deeply\nnesting limit is 256"
+    );
+}
+
+// operator chains are parsed in a loop, but fold into a tree as deep as the chain
+#[test]
+fn operator_chain_limit() {
+    let terms = vec!["1"; 5000].join(" + ");
+    let source = format!("pub proc chain() -> s32 {{\n    return {terms};\n}}\n");
+    let files = [(PathBuf::from("chain.rock"), source)];
+    let (_, diagnostics) = hir_lower::check_virtual(&files, TargetTriple::host())
+        .unwrap_or_else(|_| panic!("session error"));
+
+    let error = diagnostics.errors()[0].diagnostic();
+    assert_eq!(
+        error.message().as_str(),
+        "expression is nested too deeply\nnesting limit is 256"
+    );
+}
+
+// lossless syntax tree used by `rock fmt` and the language server has the same limit
+#[test]
+fn format_nesting_limit() {
+    let depth = 100_000;
+    let source = format!(
+        "pub proc nested() -> s32 {{\n    return {}1{} + -{}1;\n}}\n",
+        "(".repeat(depth),
+        ")".repeat(depth),
+        "-".repeat(depth)
+    );
+    let errors = match format::format(&source, ModuleID::dummy()) {
+        Ok(_) => panic!("deeply nested source was formatted"),
+        Err(errors) => errors,
+    };
+    assert_eq!(errors.len(), 1);
+    assert_eq!(
+        errors[0].diagnostic().message().as_str(),
+        "expression is nested too deeply\nnesting limit is 256"
+    );
+}
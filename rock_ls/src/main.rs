@@ -1,18 +1,22 @@
 #![forbid(unsafe_code)]
 
 mod message;
+mod worker;
 
 use lsp_server::{Connection, RequestId};
 use lsp_types as lsp;
 use lsp_types::notification::{self, Notification as NotificationTrait};
 use message::{Action, Message, MessageBuffer, Notification, Request};
 use std::collections::HashMap;
+use std::sync::Arc;
+use worker::Worker;
 
 fn main() {
     let (conn, io_threads) = Connection::stdio();
-    let _ = initialize_handshake(&conn);
+    let initialize_params = initialize_handshake(&conn);
+    let workspace_roots = workspace_roots(&initialize_params);
 
-    server_loop(&conn);
+    server_loop(&conn, workspace_roots);
 
     drop(conn);
     io_threads.join().expect("io_threads joined");
@@ -24,7 +28,7 @@ fn initialize_handshake(conn: &Connection) -> lsp::InitializeParams {
         text_document_sync: Some(lsp::TextDocumentSyncCapability::Options(
             lsp::TextDocumentSyncOptions {
                 open_close: Some(true),
-                change: Some(lsp::TextDocumentSyncKind::FULL),
+                change: Some(lsp::TextDocumentSyncKind::INCREMENTAL),
                 will_save: Some(false),
                 will_save_wait_until: Some(false),
                 save: Some(lsp::TextDocumentSyncSaveOptions::SaveOptions(
@@ -47,20 +51,24 @@ fn initialize_handshake(conn: &Connection) -> lsp::InitializeParams {
             },
             completion_item: None,
         }),
-        signature_help_provider: None,
-        definition_provider: None,
-        //@re-enable when supported
-        //definition_provider: Some(lsp::OneOf::Left(true)),
+        signature_help_provider: Some(lsp::SignatureHelpOptions {
+            trigger_characters: Some(vec!["(".into(), ",".into()]),
+            retrigger_characters: None,
+            work_done_progress_options: lsp::WorkDoneProgressOptions {
+                work_done_progress: None,
+            },
+        }),
+        definition_provider: Some(lsp::OneOf::Left(true)),
         type_definition_provider: None,
-        implementation_provider: None,
-        references_provider: None,
+        implementation_provider: Some(lsp::ImplementationProviderCapability::Simple(true)),
+        references_provider: Some(lsp::OneOf::Left(true)),
         document_highlight_provider: None,
         document_symbol_provider: None,
         workspace_symbol_provider: None,
-        code_action_provider: None,
+        code_action_provider: Some(lsp::CodeActionProviderCapability::Simple(true)),
         code_lens_provider: None,
         document_formatting_provider: Some(lsp::OneOf::Left(true)),
-        document_range_formatting_provider: None,
+        document_range_formatting_provider: Some(lsp::OneOf::Left(true)),
         document_on_type_formatting_provider: None,
         rename_provider: None,
         document_link_provider: None,
@@ -68,13 +76,20 @@ fn initialize_handshake(conn: &Connection) -> lsp::InitializeParams {
         folding_range_provider: None,
         declaration_provider: None,
         execute_command_provider: None,
-        workspace: None,
+        workspace: Some(lsp::WorkspaceServerCapabilities {
+            workspace_folders: None,
+            file_operations: Some(lsp::WorkspaceFileOperationsServerCapabilities {
+                did_delete: Some(source_file_operations()),
+                did_rename: Some(source_file_operations()),
+                ..Default::default()
+            }),
+        }),
         call_hierarchy_provider: None,
         semantic_tokens_provider: None,
         moniker_provider: None,
         linked_editing_range_provider: None,
         inline_value_provider: None,
-        inlay_hint_provider: None,
+        inlay_hint_provider: Some(lsp::OneOf::Left(true)),
         diagnostic_provider: None,
         experimental: None,
     };
@@ -84,27 +99,168 @@ fn initialize_handshake(conn: &Connection) -> lsp::InitializeParams {
     serde_json::from_value(initialize_params_json).expect("initialize_params from json")
 }
 
+fn source_file_operations() -> lsp::FileOperationRegistrationOptions {
+    lsp::FileOperationRegistrationOptions {
+        filters: vec![lsp::FileOperationFilter {
+            scheme: Some("file".into()),
+            pattern: lsp::FileOperationPattern {
+                glob: "**/*.rock".into(),
+                matches: Some(lsp::FileOperationPatternKind::File),
+                options: None,
+            },
+        }],
+    }
+}
+
 struct ServerContext {
-    files_in_memory: HashMap<PathBuf, String>,
+    files_in_memory: MemoryFs,
+    /// versions of documents open in the client
+    versions: HashMap<PathBuf, i32>,
+    packages: Vec<PackageContext>,
+    worker: Worker,
+}
+
+/// package found in the workspace, each is compiled with its own session
+struct PackageContext {
+    root_dir: PathBuf,
+    dirty: bool,
+}
+
+/// results of the last completed compilation, used to answer requests
+struct Analysis {
+    session: Session,
+    line_indices: Vec<LineIndex>,
+    inlay_hints: InlayHints,
 }
 
 impl ServerContext {
-    fn new() -> ServerContext {
+    fn new(conn: &Connection, workspace_roots: Vec<PathBuf>) -> ServerContext {
+        let mut package_roots = Vec::new();
+        for root_dir in workspace_roots.iter() {
+            discover_packages(root_dir, &mut package_roots);
+        }
+        package_roots.sort();
+        package_roots.dedup();
+
+        let packages = package_roots
+            .into_iter()
+            .map(|root_dir| PackageContext {
+                root_dir,
+                dirty: true,
+            })
+            .collect();
+
         ServerContext {
-            files_in_memory: HashMap::new(),
+            files_in_memory: MemoryFs::new(),
+            versions: HashMap::new(),
+            packages,
+            worker: Worker::spawn(conn),
+        }
+    }
+
+    /// innermost package that contains the `path`
+    fn package_of(&self, path: &PathBuf) -> Option<&PackageContext> {
+        self.packages
+            .iter()
+            .filter(|package| path.starts_with(&package.root_dir))
+            .max_by_key(|package| package.root_dir.components().count())
+    }
+
+    fn analysis_of(&self, path: &PathBuf) -> Option<Arc<Analysis>> {
+        self.worker.analysis(&self.package_of(path)?.root_dir)
+    }
+
+    fn index_of(&self, path: &PathBuf) -> Option<Arc<SymbolIndex>> {
+        self.worker.index(&self.package_of(path)?.root_dir)
+    }
+
+    /// schedule the owning package for compilation,
+    /// packages created after initialization are discovered here
+    fn mark_dirty(&mut self, path: &PathBuf) {
+        let root_dir = match self.package_of(path) {
+            Some(package) => package.root_dir.clone(),
+            None => match find_package_root(path) {
+                Some(root_dir) => root_dir,
+                None => return,
+            },
+        };
+
+        match self
+            .packages
+            .iter_mut()
+            .find(|package| package.root_dir == root_dir)
+        {
+            Some(package) => package.dirty = true,
+            None => self.packages.push(PackageContext {
+                root_dir,
+                dirty: true,
+            }),
+        }
+    }
+}
+
+fn workspace_roots(params: &lsp::InitializeParams) -> Vec<PathBuf> {
+    if let Some(folders) = &params.workspace_folders {
+        if !folders.is_empty() {
+            return folders
+                .iter()
+                .filter_map(|folder| folder.uri.to_file_path().ok())
+                .collect();
+        }
+    }
+    #[allow(deprecated)]
+    if let Some(root_uri) = &params.root_uri {
+        if let Ok(root_dir) = root_uri.to_file_path() {
+            return vec![root_dir];
+        }
+    }
+    std::env::current_dir().into_iter().collect()
+}
+
+// `build` and hidden directories are skipped, symlinks are not followed
+fn discover_packages(dir: &PathBuf, package_roots: &mut Vec<PathBuf>) {
+    if dir.join("Rock.toml").is_file() {
+        package_roots.push(dir.clone());
+    }
+    let read_dir = match std::fs::read_dir(dir) {
+        Ok(read_dir) => read_dir,
+        Err(_) => return,
+    };
+    for entry in read_dir.flatten() {
+        let is_dir = entry.file_type().map(|ty| ty.is_dir()).unwrap_or(false);
+        let name = entry.file_name();
+        let name = name.to_string_lossy();
+        if is_dir && name != "build" && !name.starts_with('.') {
+            discover_packages(&entry.path(), package_roots);
         }
     }
 }
 
-fn server_loop(conn: &Connection) {
+fn find_package_root(path: &PathBuf) -> Option<PathBuf> {
+    path.ancestors()
+        .skip(1)
+        .find(|dir| dir.join("Rock.toml").is_file())
+        .map(|dir| dir.to_path_buf())
+}
+
+fn server_loop(conn: &Connection, workspace_roots: Vec<PathBuf>) {
     let mut buffer = MessageBuffer::new();
-    let mut context = ServerContext::new();
+    let mut context = ServerContext::new(conn, workspace_roots);
 
     loop {
-        match buffer.receive(&conn) {
-            Action::Stop => break,
-            Action::Collect => continue,
-            Action::Handle(messages) => handle_messages(&conn, &mut context, messages),
+        // compiler bugs are reported to the client, server keeps running
+        let result = ice::catch(|| match buffer.receive(conn) {
+            Action::Stop => false,
+            Action::Collect => true,
+            Action::Handle(messages) => {
+                handle_messages(conn, &mut context, messages);
+                true
+            }
+        });
+        match result {
+            Ok(true) => continue,
+            Ok(false) => break,
+            Err(ice) => show_error_message(conn, ice.into_error().diagnostic().message().as_str()),
         }
     }
 }
@@ -114,7 +270,7 @@ fn handle_messages(conn: &Connection, context: &mut ServerContext, messages: Vec
         match message {
             Message::Request(id, req) => handle_request(conn, context, id.clone(), req),
             Message::Notification(not) => handle_notification(context, not),
-            Message::CompileProject => handle_compile_project(conn, context),
+            Message::CompileProject => handle_compile_project(context),
         }
     }
 }
@@ -122,66 +278,188 @@ fn handle_messages(conn: &Connection, context: &mut ServerContext, messages: Vec
 fn handle_request(conn: &Connection, context: &mut ServerContext, id: RequestId, req: Request) {
     match req {
         Request::Completion(params) => {}
-        Request::GotoDefinition(params) => {}
+        Request::GotoDefinition(params) => {
+            let position = params.text_document_position_params;
+            let locations = match symbol_at_position(context, &position) {
+                Some((index, symbol_id)) => {
+                    let definition = index.definition(symbol_id).map(|(location, _)| location);
+                    index_locations(context, &index, definition.into_iter().collect())
+                }
+                None => Vec::new(),
+            };
+
+            let response = lsp::GotoDefinitionResponse::Array(locations);
+            let json = serde_json::to_value(response).expect("json value");
+            send_response(conn, id, json);
+        }
+        Request::References(params) => {
+            let position = params.text_document_position;
+            let locations = match symbol_at_position(context, &position) {
+                Some((index, symbol_id)) => {
+                    let mut locations = Vec::new();
+                    if params.context.include_declaration {
+                        locations.extend(index.definition(symbol_id).map(|(location, _)| location));
+                    }
+                    locations.extend(index.references(symbol_id));
+                    index_locations(context, &index, locations)
+                }
+                None => Vec::new(),
+            };
+
+            let json = serde_json::to_value(locations).expect("json value");
+            send_response(conn, id, json);
+        }
+        Request::Implementation(params) => {
+            let position = params.text_document_position_params;
+            let locations = match symbol_at_position(context, &position) {
+                Some((index, symbol_id)) => {
+                    index_locations(context, &index, index.implementations(symbol_id))
+                }
+                None => Vec::new(),
+            };
+
+            let response = lsp::GotoDefinitionResponse::Array(locations);
+            let json = serde_json::to_value(response).expect("json value");
+            send_response(conn, id, json);
+        }
         Request::Format(params) => {
-            let uri = params.text_document.uri;
-            let path = uri_to_path(&uri);
-
-            if let Some(source) = context.files_in_memory.get(&path) {
-                //@random ModuleID used
-                if let Ok(formatted) = rock_core::format::format(source, ModuleID::new(0)) {
-                    let line_count = source.lines().count() as u32;
-                    context.files_in_memory.insert(path, formatted.clone());
-
-                    //@send the more presice lsp::TextDocumentEdit with uri?
-                    let text_edit = lsp::TextEdit {
-                        range: lsp::Range::new(
-                            lsp::Position::new(0, 0),
-                            lsp::Position::new(line_count, 0),
-                        ),
-                        new_text: formatted,
-                    };
-
-                    let json = serde_json::to_value(vec![text_edit]).expect("json value");
+            let path = uri_to_path(&params.text_document.uri);
+            match run_format(context, &path, None) {
+                Some(text_edits) => {
+                    let json = serde_json::to_value(text_edits).expect("json value");
                     send_response(conn, id, json);
-                } else {
-                    send_response_error(conn, id, None);
                 }
-            } else {
-                send_response_error(conn, id, None);
+                None => send_response_error(conn, id, None),
+            }
+        }
+        Request::FormatRange(params) => {
+            let path = uri_to_path(&params.text_document.uri);
+            match run_format(context, &path, Some(params.range)) {
+                Some(text_edits) => {
+                    let json = serde_json::to_value(text_edits).expect("json value");
+                    send_response(conn, id, json);
+                }
+                None => send_response_error(conn, id, None),
             }
         }
         Request::Hover(params) => {}
+        Request::SignatureHelp(params) => {
+            let position = params.text_document_position_params;
+            let path = uri_to_path(&position.text_document.uri);
+            let signature_help = run_signature_help(context, &path, position.position);
+
+            let json = serde_json::to_value(signature_help).expect("json value");
+            send_response(conn, id, json);
+        }
+        Request::InlayHint(params) => {
+            let path = uri_to_path(&params.text_document.uri);
+            let inlay_hints = match context.analysis_of(&path) {
+                Some(analysis) => create_inlay_hints(&analysis, &path, params.range),
+                None => Vec::new(),
+            };
+
+            let json = serde_json::to_value(inlay_hints).expect("json value");
+            send_response(conn, id, json);
+        }
+        Request::CodeAction(params) => {
+            // fixes are stored in diagnostic data, client sends back diagnostics in the range
+            let mut actions = Vec::new();
+            for diagnostic in params.context.diagnostics {
+                let data = match diagnostic.data.clone() {
+                    Some(data) => data,
+                    None => continue,
+                };
+                if let Ok(fixes) = serde_json::from_value::<Vec<lsp::CodeAction>>(data) {
+                    for mut action in fixes {
+                        action.diagnostics = Some(vec![diagnostic.clone()]);
+                        actions.push(lsp::CodeActionOrCommand::CodeAction(action));
+                    }
+                }
+            }
+
+            let json = serde_json::to_value(actions).expect("json value");
+            send_response(conn, id, json);
+        }
     }
 }
 
 fn handle_notification(context: &mut ServerContext, not: Notification) {
     match not {
-        Notification::SourceFileChanged { path, text } => {
+        Notification::SourceFileChanged {
+            path,
+            text,
+            version,
+        } => {
+            context.mark_dirty(&path);
+            context.versions.insert(path.clone(), version);
             context.files_in_memory.insert(path, text);
         }
+        Notification::SourceFileEdited {
+            path,
+            changes,
+            version,
+        } => {
+            if let Some(text) = context.files_in_memory.get_mut(&path) {
+                for change in changes {
+                    apply_text_change(text, change);
+                }
+            }
+            context.versions.insert(path.clone(), version);
+            context.mark_dirty(&path);
+        }
         Notification::SourceFileClosed { path } => {
             context.files_in_memory.remove(&path);
+            context.versions.remove(&path);
+            context.mark_dirty(&path);
+        }
+        Notification::SourceFilesDeleted { paths } => {
+            for path in paths {
+                context.mark_dirty(&path);
+            }
+        }
+        Notification::SourceFilesRenamed { paths } => {
+            for (old_path, new_path) in paths {
+                context.mark_dirty(&old_path);
+                context.mark_dirty(&new_path);
+            }
         }
     }
 }
 
-fn handle_compile_project(conn: &Connection, context: &ServerContext) {
-    use std::time::Instant;
-    let start_time = Instant::now();
-    let publish_diagnostics = run_diagnostics(context);
-    let elapsed_time = start_time.elapsed();
-    eprintln!(
-        "run diagnostics: {} ms",
-        elapsed_time.as_secs_f64() * 1000.0
-    );
-
-    for publish in publish_diagnostics.iter() {
-        send(
-            conn,
-            lsp_server::Notification::new(notification::PublishDiagnostics::METHOD.into(), publish),
-        );
+//@changes to files on disk are only tracked for client file operations,
+// only edited, deleted or renamed packages are recompiled
+fn handle_compile_project(context: &mut ServerContext) {
+    let root_dirs: Vec<PathBuf> = context
+        .packages
+        .iter_mut()
+        .filter(|package| package.dirty)
+        .map(|package| {
+            package.dirty = false;
+            package.root_dir.clone()
+        })
+        .collect();
+    if root_dirs.is_empty() {
+        return;
     }
+    let worker = &mut context.worker;
+    worker.analyze(root_dirs, &context.files_in_memory, &context.versions);
+}
+
+fn publish_notification(publish: &PublishDiagnosticsParams) -> lsp_server::Notification {
+    lsp_server::Notification::new(notification::PublishDiagnostics::METHOD.into(), publish)
+}
+
+fn error_message_notification(message: &str) -> lsp_server::Notification {
+    let params = lsp::ShowMessageParams {
+        typ: lsp::MessageType::ERROR,
+        message: message.to_string(),
+    };
+    lsp_server::Notification::new(notification::ShowMessage::METHOD.into(), params)
+}
+
+fn show_error_message(conn: &Connection, message: &str) {
+    eprintln!("{message}");
+    send(conn, error_message_notification(message));
 }
 
 fn send_response(conn: &Connection, id: RequestId, result: serde_json::Value) {
@@ -206,35 +484,256 @@ fn send<Content: Into<lsp_server::Message>>(conn: &Connection, msg: Content) {
     conn.sender.send(msg.into()).expect("send message");
 }
 
-use rock_core::ast_parse;
+use rock_core::driver::{CancelToken, CompileOptions, Compiled, Stage, Timings};
+use rock_core::error::ice;
 use rock_core::error::{
-    Diagnostic, DiagnosticCollection, DiagnosticKind, DiagnosticSeverity, SourceRange, WarningComp,
+    Diagnostic, DiagnosticCollection, DiagnosticFix, DiagnosticKind, DiagnosticSeverity,
+    SourceRange,
 };
-use rock_core::hir_lower;
+use rock_core::hir_lower::{self, InlayHintKind, InlayHints};
+use rock_core::index::{self, FileIndex, SymbolID, SymbolIndex};
 use rock_core::intern::InternPool;
 use rock_core::session::{ModuleID, Session};
-use rock_core::text;
+use rock_core::target::TargetTriple;
+use rock_core::text::{self, LineCol, LineIndex, TextRange};
+use rock_core::vfs::{MemoryFs, OverlayFs, RealFs};
 
 use lsp::{DiagnosticRelatedInformation, Location, Position, PublishDiagnosticsParams, Range};
 use std::path::PathBuf;
+use std::time::Duration;
 
 fn check_impl(
     session: &Session,
     intern_name: InternPool,
-) -> Result<Vec<WarningComp>, DiagnosticCollection> {
-    let (ast, warnings) = ast_parse::parse(session, intern_name).into_result(vec![])?;
-    let (_, warnings) = hir_lower::check(ast, session).into_result(warnings)?;
-    Ok(warnings)
+    cancel: &CancelToken,
+    inlay_hints: &mut InlayHints,
+    index_files: &mut Option<Vec<FileIndex>>,
+) -> DiagnosticCollection {
+    let mut options = CompileOptions::new(Stage::Check, TargetTriple::host());
+    options.inlay_hints = true;
+    options.symbol_index = true;
+    options.cancel = cancel.clone();
+    let compilation = rock_core::compile(session, intern_name, &options);
+    log_slow_stages(&compilation.timings);
+    // hints and index of a partially checked program are still used
+    if let Some(Compiled::Checked(_, hints, index)) = compilation.compiled {
+        *inlay_hints = hints;
+        *index_files = index;
+    }
+    compilation.diagnostics
+}
+
+const SLOW_CHECK: Duration = Duration::from_millis(500);
+
+// logged to stderr, shown in the editor language server output
+fn log_slow_stages(timings: &Timings) {
+    let total = timings.total();
+    if total < SLOW_CHECK {
+        return;
+    }
+    eprintln!("slow check: {:.2} ms", total.as_secs_f64() * 1000.0);
+    for timing in timings.stages.iter() {
+        eprintln!(
+            "  {}: {:.2} ms",
+            timing.name,
+            timing.duration.as_secs_f64() * 1000.0
+        );
+    }
+}
+
+// edits are not applied to the in-memory text, client sends them back as changes
+// whole file is formatted, with `range` only edits touching that range are kept
+fn run_format(
+    context: &ServerContext,
+    path: &PathBuf,
+    range: Option<Range>,
+) -> Option<Vec<lsp::TextEdit>> {
+    let source = context.files_in_memory.get(path)?;
+    //@random ModuleID used
+    let formatted = rock_core::format::format(source, ModuleID::new(0)).ok()?;
+    let line_index = LineIndex::new(source);
+
+    let range = match range {
+        Some(range) => {
+            let start = LineCol::new(range.start.line, range.start.character);
+            let end = LineCol::new(range.end.line, range.end.character);
+            let end = line_index
+                .offset_utf16(source, end)
+                .unwrap_or((source.len() as u32).into());
+            let start = line_index.offset_utf16(source, start)?;
+            (start <= end).then(|| TextRange::new(start, end))?
+        }
+        None => TextRange::new(0.into(), (source.len() as u32).into()),
+    };
+
+    let text_edits = text::diff_lines(source, &formatted)
+        .into_iter()
+        .filter(|edit| edit.range.start() <= range.end() && range.start() <= edit.range.end())
+        .map(|edit| {
+            let (start, end) = line_index.range_utf16(source, edit.range);
+            lsp::TextEdit {
+                range: Range::new(
+                    Position::new(start.line, start.col),
+                    Position::new(end.line, end.col),
+                ),
+                new_text: edit.text,
+            }
+        })
+        .collect();
+    Some(text_edits)
+}
+
+// items with syntax errors are missing from the partial ast
+fn run_signature_help(
+    context: &ServerContext,
+    path: &PathBuf,
+    position: Position,
+) -> Option<lsp::SignatureHelp> {
+    let root_dir = context.package_of(path)?.root_dir.clone();
+    let files = OverlayFs::new(&context.files_in_memory, &RealFs);
+    let (session, intern_name) =
+        Session::new_at(root_dir, false, TargetTriple::host(), &files).ok()?;
+    let module_id = session
+        .module_ids()
+        .find(|&module_id| session.module(module_id).path == *path)?;
+
+    let source = &session.module(module_id).source;
+    let line_col = LineCol::new(position.line, position.character);
+    let offset = LineIndex::new(source).offset_utf16(source, line_col)?;
+
+    let options = CompileOptions::new(Stage::Parse, TargetTriple::host());
+    let ast = match rock_core::compile(&session, intern_name, &options).compiled {
+        Some(Compiled::Parsed(ast)) => *ast,
+        _ => return None,
+    };
+    let signature = hir_lower::signature_help(ast, &session, module_id, offset)?;
+
+    let utf16_len = |text: &str| text.encode_utf16().count() as u32;
+    let parameters = signature
+        .params
+        .iter()
+        .map(|range| {
+            let start = utf16_len(&signature.label[..range.start().into()]);
+            let end = utf16_len(&signature.label[..range.end().into()]);
+            lsp::ParameterInformation {
+                label: lsp::ParameterLabel::LabelOffsets([start, end]),
+                documentation: None,
+            }
+        })
+        .collect();
+    let active_parameter = signature.active_param.map(|idx| idx as u32);
+
+    let information = lsp::SignatureInformation {
+        label: signature.label,
+        documentation: None,
+        parameters: Some(parameters),
+        active_parameter,
+    };
+    Some(lsp::SignatureHelp {
+        signatures: vec![information],
+        active_signature: Some(0),
+        active_parameter,
+    })
+}
+
+// index is used before the first check completes, it is read from `build/index.toml`
+// offsets of the index are only used while the indexed source matches the current text
+fn symbol_at_position(
+    context: &ServerContext,
+    position: &lsp::TextDocumentPositionParams,
+) -> Option<(Arc<SymbolIndex>, SymbolID)> {
+    let path = uri_to_path(&position.text_document.uri);
+    let index = context.index_of(&path)?;
+    let source = source_text(context, &path)?;
+    if !index.file(&path)?.is_current(&source) {
+        return None;
+    }
+    let line_col = LineCol::new(position.position.line, position.position.character);
+    let offset = LineIndex::new(&source).offset_utf16(&source, line_col)?;
+    let symbol_id = index.symbol_at(&path, offset)?;
+    Some((index, symbol_id))
+}
+
+// locations in files changed since the last check are skipped
+fn index_locations(
+    context: &ServerContext,
+    index: &SymbolIndex,
+    locations: Vec<index::Location>,
+) -> Vec<Location> {
+    let mut sources: HashMap<&PathBuf, Option<(String, LineIndex)>> = HashMap::new();
+    let mut lsp_locations = Vec::with_capacity(locations.len());
+
+    for location in locations {
+        let source = sources.entry(location.path).or_insert_with(|| {
+            let source = source_text(context, location.path)?;
+            let current = index.file(location.path)?.is_current(&source);
+            current.then(|| {
+                let line_index = LineIndex::new(&source);
+                (source, line_index)
+            })
+        });
+        let (source, line_index) = match source {
+            Some(source) => source,
+            None => continue,
+        };
+        let url = match url_from_path(location.path) {
+            Some(url) => url,
+            None => continue,
+        };
+        let (start, end) = line_index.range_utf16(source, location.range);
+        let range = Range::new(
+            Position::new(start.line, start.col),
+            Position::new(end.line, end.col),
+        );
+        lsp_locations.push(Location::new(url, range));
+    }
+    lsp_locations
+}
+
+// byte order mark is removed, same as in session modules
+fn source_text(context: &ServerContext, path: &PathBuf) -> Option<String> {
+    let text = match context.files_in_memory.get(path) {
+        Some(text) => text.clone(),
+        None => std::fs::read_to_string(path).ok()?,
+    };
+    match text.strip_prefix('\u{feff}') {
+        Some(text) => Some(text.to_string()),
+        None => Some(text),
+    }
+}
+
+// line index is rebuilt after each change, since ranges refer to the already edited text
+fn apply_text_change(text: &mut String, change: lsp::TextDocumentContentChangeEvent) {
+    let range = match change.range {
+        Some(range) => range,
+        None => {
+            *text = change.text;
+            return;
+        }
+    };
+
+    let line_index = LineIndex::new(text);
+    let start = LineCol::new(range.start.line, range.start.character);
+    let end = LineCol::new(range.end.line, range.end.character);
+
+    match line_index.range_from_utf16(text, start, end) {
+        Some(range) => text.replace_range(range.as_usize(), &change.text),
+        None => eprintln!("invalid text change range: {:?}", range),
+    }
 }
 
 fn uri_to_path(uri: &lsp::Url) -> PathBuf {
     uri.to_file_path().expect("uri to pathbuf")
 }
 
-fn url_from_path(path: &PathBuf) -> lsp::Url {
+// relative paths cannot be converted, those are logged and skipped
+fn url_from_path(path: &PathBuf) -> Option<lsp::Url> {
     match lsp::Url::from_file_path(path) {
-        Ok(url) => url,
-        Err(()) => panic!("failed to convert `{}` to url", path.to_string_lossy()),
+        Ok(url) => Some(url),
+        Err(()) => {
+            eprintln!("failed to convert `{}` to url", path.to_string_lossy());
+            None
+        }
     }
 }
 
@@ -246,37 +745,42 @@ fn severity_convert(severity: DiagnosticSeverity) -> Option<lsp::DiagnosticSever
     }
 }
 
-fn source_to_range_and_path(session: &Session, source: SourceRange) -> (Range, &PathBuf) {
+fn source_to_range_and_path<'src>(
+    session: &'src Session,
+    line_indices: &[LineIndex],
+    source: SourceRange,
+) -> (Range, &'src PathBuf) {
     let module = session.module(source.module_id());
-
-    let start_location =
-        text::find_text_location(&module.source, source.range().start(), &module.line_ranges);
-    let end_location =
-        text::find_text_location(&module.source, source.range().end(), &module.line_ranges);
+    let line_index = &line_indices[source.module_id().index()];
+    let (start, end) = line_index.range_utf16(&module.source, source.range());
 
     let range = Range::new(
-        Position::new(start_location.line() - 1, start_location.col() - 1),
-        Position::new(end_location.line() - 1, end_location.col() - 1),
+        Position::new(start.line, start.col),
+        Position::new(end.line, end.col),
     );
-
     (range, &module.path)
 }
 
 fn create_diagnostic<'src>(
     session: &'src Session,
+    line_indices: &[LineIndex],
     diagnostic: &Diagnostic,
     severity: DiagnosticSeverity,
 ) -> Option<(lsp::Diagnostic, &'src PathBuf)> {
+    let diagnostic_fixes = diagnostic.fixes();
     let (main, related_info) = match diagnostic.kind() {
         DiagnosticKind::Message => return None, //@some diagnostic messages dont have source for example session errors or manifest errors
         DiagnosticKind::Context { main, info } => {
             if let Some(info) = info {
-                let (info_range, info_path) = source_to_range_and_path(session, info.source());
-                let related_info = DiagnosticRelatedInformation {
-                    location: Location::new(url_from_path(info_path), info_range),
-                    message: info.message().to_string(),
-                };
-                (main, Some(vec![related_info]))
+                let (info_range, info_path) =
+                    source_to_range_and_path(session, line_indices, info.source());
+                let related_info = url_from_path(info_path).map(|url| {
+                    vec![DiagnosticRelatedInformation {
+                        location: Location::new(url, info_range),
+                        message: info.message().to_string(),
+                    }]
+                });
+                (main, related_info)
             } else {
                 (main, None)
             }
@@ -284,9 +788,14 @@ fn create_diagnostic<'src>(
         DiagnosticKind::ContextVec { main, info_vec } => {
             let mut related_infos = Vec::with_capacity(info_vec.len());
             for info in info_vec {
-                let (info_range, info_path) = source_to_range_and_path(session, info.source());
+                let (info_range, info_path) =
+                    source_to_range_and_path(session, line_indices, info.source());
+                let url = match url_from_path(info_path) {
+                    Some(url) => url,
+                    None => continue,
+                };
                 let related_info = DiagnosticRelatedInformation {
-                    location: Location::new(url_from_path(info_path), info_range),
+                    location: Location::new(url, info_range),
                     message: info.message().to_string(),
                 };
                 related_infos.push(related_info);
@@ -295,7 +804,7 @@ fn create_diagnostic<'src>(
         }
     };
 
-    let (main_range, main_path) = source_to_range_and_path(session, main.source());
+    let (main_range, main_path) = source_to_range_and_path(session, line_indices, main.source());
 
     let mut message = diagnostic.message().as_str().to_string();
     if !main.message().is_empty() {
@@ -303,28 +812,99 @@ fn create_diagnostic<'src>(
         message += main.message();
     }
 
-    let diagnostic = lsp::Diagnostic::new(
+    let code = diagnostic
+        .code()
+        .map(|code| lsp::NumberOrString::String(code.as_str().to_string()));
+
+    let mut diagnostic = lsp::Diagnostic::new(
         main_range,
         severity_convert(severity),
-        None,
+        code,
         None,
         message,
         related_info,
         None,
     );
+    diagnostic.data = create_code_actions(session, line_indices, diagnostic_fixes);
 
     Some((diagnostic, main_path))
 }
 
-fn run_diagnostics(context: &ServerContext) -> Vec<PublishDiagnosticsParams> {
-    //@session errors ignored, its not a correct way to have context in ls server
+fn create_code_actions(
+    session: &Session,
+    line_indices: &[LineIndex],
+    fixes: &[DiagnosticFix],
+) -> Option<serde_json::Value> {
+    if fixes.is_empty() {
+        return None;
+    }
+
+    let mut actions = Vec::with_capacity(fixes.len());
+    for fix in fixes {
+        let mut changes: HashMap<lsp::Url, Vec<lsp::TextEdit>> = HashMap::new();
+        for edit in fix.edits() {
+            let (range, path) = source_to_range_and_path(session, line_indices, edit.source());
+            let url = match url_from_path(path) {
+                Some(url) => url,
+                None => continue,
+            };
+            let text_edit = lsp::TextEdit::new(range, edit.text().to_string());
+            changes.entry(url).or_default().push(text_edit);
+        }
+
+        actions.push(lsp::CodeAction {
+            title: fix.message().to_string(),
+            kind: Some(lsp::CodeActionKind::QUICKFIX),
+            edit: Some(lsp::WorkspaceEdit::new(changes)),
+            is_preferred: Some(fixes.len() == 1),
+            ..Default::default()
+        });
+    }
+    Some(serde_json::to_value(actions).expect("json value"))
+}
+
+type DiagnosticsRun = (
+    Vec<PublishDiagnosticsParams>,
+    Analysis,
+    Vec<String>,
+    Option<Vec<FileIndex>>,
+);
+
+fn run_diagnostics(
+    root_dir: &PathBuf,
+    files_in_memory: &MemoryFs,
+    versions: &HashMap<PathBuf, i32>,
+    cancel: &CancelToken,
+) -> Option<DiagnosticsRun> {
     // this is a temporary full compilation run
-    //@those can be displayed as regular messages
-    let (session, intern_name) = Session::new(false, Some(&context.files_in_memory))
-        .map_err(|_| Result::<(), ()>::Err(()))
-        .expect("lsp session errors cannot be handled");
-    let check_result = check_impl(&session, intern_name);
-    let diagnostics = DiagnosticCollection::from_result(check_result);
+    //@session errors are only logged, those can be displayed as regular messages
+    let files = OverlayFs::new(files_in_memory, &RealFs);
+    let (session, intern_name) =
+        match Session::new_at(root_dir.clone(), false, TargetTriple::host(), &files) {
+            Ok(session) => session,
+            Err(error) => {
+                eprintln!(
+                    "session error `{}`: {}",
+                    root_dir.to_string_lossy(),
+                    error.diagnostic().message().as_str()
+                );
+                return None;
+            }
+        };
+    let mut inlay_hints = InlayHints::default();
+    let mut index_files = None;
+    let diagnostics = check_impl(
+        &session,
+        intern_name,
+        cancel,
+        &mut inlay_hints,
+        &mut index_files,
+    );
+
+    let line_indices: Vec<LineIndex> = session
+        .module_ids()
+        .map(|module_id| LineIndex::new(&session.module(module_id).source))
+        .collect();
 
     // assign empty diagnostics
     let mut diagnostics_map = HashMap::new();
@@ -335,9 +915,12 @@ fn run_diagnostics(context: &ServerContext) -> Vec<PublishDiagnosticsParams> {
 
     // generate diagnostics
     for warning in diagnostics.warnings() {
-        if let Some((diagnostic, main_path)) =
-            create_diagnostic(&session, warning.diagnostic(), DiagnosticSeverity::Warning)
-        {
+        if let Some((diagnostic, main_path)) = create_diagnostic(
+            &session,
+            &line_indices,
+            warning.diagnostic(),
+            DiagnosticSeverity::Warning,
+        ) {
             match diagnostics_map.get_mut(main_path) {
                 Some(diagnostics) => diagnostics.push(diagnostic),
                 None => {
@@ -347,24 +930,89 @@ fn run_diagnostics(context: &ServerContext) -> Vec<PublishDiagnosticsParams> {
         }
     }
 
+    // errors without source, like internal compiler errors, are shown as messages
+    let mut messages = Vec::new();
     for error in diagnostics.errors() {
-        if let Some((diagnostic, main_path)) =
-            create_diagnostic(&session, error.diagnostic(), DiagnosticSeverity::Error)
-        {
+        if let Some((diagnostic, main_path)) = create_diagnostic(
+            &session,
+            &line_indices,
+            error.diagnostic(),
+            DiagnosticSeverity::Error,
+        ) {
             match diagnostics_map.get_mut(main_path) {
                 Some(diagnostics) => diagnostics.push(diagnostic),
                 None => {
                     diagnostics_map.insert(main_path.clone(), vec![diagnostic]);
                 }
             }
+        } else if let DiagnosticKind::Message = error.diagnostic().kind() {
+            messages.push(error.diagnostic().message().as_str().to_string());
         }
     }
 
-    //@not using any document versioning
-    diagnostics_map
+    // closed documents have no version, their diagnostics are never stale
+    let publish_diagnostics = diagnostics_map
         .into_iter()
-        .map(|(path, diagnostics)| {
-            PublishDiagnosticsParams::new(url_from_path(&path), diagnostics, None)
+        .filter_map(|(path, diagnostics)| {
+            let url = url_from_path(&path)?;
+            let version = versions.get(&path).copied();
+            Some(PublishDiagnosticsParams::new(url, diagnostics, version))
         })
-        .collect()
+        .collect();
+
+    let analysis = Analysis {
+        session,
+        line_indices,
+        inlay_hints,
+    };
+    Some((publish_diagnostics, analysis, messages, index_files))
+}
+
+fn create_inlay_hints(analysis: &Analysis, path: &PathBuf, range: Range) -> Vec<lsp::InlayHint> {
+    let session = &analysis.session;
+    let module_id = match session
+        .module_ids()
+        .find(|&module_id| session.module(module_id).path == *path)
+    {
+        Some(module_id) => module_id,
+        None => return Vec::new(),
+    };
+
+    let source = &session.module(module_id).source;
+    let line_index = &analysis.line_indices[module_id.index()];
+    // requested range can extend past the end of file
+    let start = LineCol::new(range.start.line, range.start.character);
+    let end = LineCol::new(range.end.line, range.end.character);
+    let start = match line_index.offset_utf16(source, start) {
+        Some(offset) => offset,
+        None => return Vec::new(),
+    };
+    let end = line_index
+        .offset_utf16(source, end)
+        .unwrap_or((source.len() as u32).into());
+    if start > end {
+        return Vec::new();
+    }
+    let range = TextRange::new(start, end);
+
+    let hints = analysis.inlay_hints.in_range(module_id, range);
+    let mut inlay_hints = Vec::with_capacity(hints.len());
+    for hint in hints {
+        let position = line_index.line_col_utf16(source, hint.offset);
+        let (label, kind) = match hint.kind {
+            InlayHintKind::Type => (format!(": {}", hint.label), lsp::InlayHintKind::TYPE),
+            InlayHintKind::Parameter => (format!("{}:", hint.label), lsp::InlayHintKind::PARAMETER),
+        };
+        inlay_hints.push(lsp::InlayHint {
+            position: Position::new(position.line, position.col),
+            label: lsp::InlayHintLabel::String(label),
+            kind: Some(kind),
+            text_edits: None,
+            tooltip: None,
+            padding_left: None,
+            padding_right: Some(hint.kind == InlayHintKind::Parameter),
+            data: None,
+        });
+    }
+    inlay_hints
 }
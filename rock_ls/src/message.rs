@@ -24,15 +24,45 @@ pub enum Message {
 pub enum Request {
     Completion(lsp::CompletionParams),
     GotoDefinition(lsp::GotoDefinitionParams),
+    References(lsp::ReferenceParams),
+    Implementation(lsp::GotoDefinitionParams),
     Format(lsp::DocumentFormattingParams),
+    FormatRange(lsp::DocumentRangeFormattingParams),
     Hover(lsp::HoverParams),
+    SignatureHelp(lsp::SignatureHelpParams),
+    InlayHint(lsp::InlayHintParams),
+    CodeAction(lsp::CodeActionParams),
 }
 
+/// `version` is the client document version after the change,
+/// published diagnostics carry it so the client can discard stale results
 pub enum Notification {
-    SourceFileChanged { path: PathBuf, text: String },
-    SourceFileClosed { path: PathBuf },
+    SourceFileChanged {
+        path: PathBuf,
+        text: String,
+        version: i32,
+    },
+    SourceFileEdited {
+        path: PathBuf,
+        changes: Vec<lsp::TextDocumentContentChangeEvent>,
+        version: i32,
+    },
+    SourceFileClosed {
+        path: PathBuf,
+    },
+    SourceFilesDeleted {
+        paths: Vec<PathBuf>,
+    },
+    /// `(old_path, new_path)` pairs
+    SourceFilesRenamed {
+        paths: Vec<(PathBuf, PathBuf)>,
+    },
 }
 
+/// diagnostics are published once no messages arrive for this long,
+/// each received message restarts the timer, requests are not delayed
+const CHANGE_DEBOUNCE: Duration = Duration::from_millis(150);
+
 impl MessageBuffer {
     pub fn new() -> MessageBuffer {
         MessageBuffer {
@@ -43,15 +73,18 @@ impl MessageBuffer {
     pub fn receive(&mut self, conn: &Connection) -> Action {
         let message = if self.messages.is_empty() {
             conn.receiver.recv().ok()
+        } else if self.has_requests() {
+            // messages that already arrived are collected first,
+            // cancelled requests are removed before being handled
+            conn.receiver.try_recv().ok()
         } else {
-            let pause = Duration::from_millis(150);
-            conn.receiver.recv_timeout(pause).ok()
+            conn.receiver.recv_timeout(CHANGE_DEBOUNCE).ok()
         };
 
         match message {
             Some(lsp_server::Message::Request(req)) => self.handle_request(conn, req),
             Some(lsp_server::Message::Response(resp)) => self.handle_response(resp),
-            Some(lsp_server::Message::Notification(not)) => self.handle_notification(not),
+            Some(lsp_server::Message::Notification(not)) => self.handle_notification(conn, not),
             None => self.handle_user_pause(),
         }
     }
@@ -61,37 +94,66 @@ impl MessageBuffer {
             return Action::Stop;
         }
         if let Some(message) = extract_request(req) {
-            self.messages.push(Message::CompileProject);
             self.messages.push(message);
-            Action::Handle(self.take_messages())
-        } else {
-            Action::Collect
         }
+        Action::Collect
     }
 
     fn handle_response(&self, _: lsp_server::Response) -> Action {
         Action::Collect
     }
 
-    fn handle_notification(&mut self, not: lsp_server::Notification) -> Action {
-        if let Some(message) = extract_notification(not) {
+    fn handle_notification(&mut self, conn: &Connection, not: lsp_server::Notification) -> Action {
+        if not.method == notification::Cancel::METHOD {
+            let params = cast_notification::<notification::Cancel>(not);
+            self.cancel_request(conn, params.id);
+        } else if let Some(message) = extract_notification(not) {
             self.messages.push(message);
         }
         Action::Collect
     }
 
+    // requests that were already handled are not affected
+    fn cancel_request(&mut self, conn: &Connection, id: lsp::NumberOrString) {
+        let id: RequestId = match id {
+            lsp::NumberOrString::Number(number) => number.into(),
+            lsp::NumberOrString::String(string) => string.into(),
+        };
+        let position = self.messages.iter().position(|message| match message {
+            Message::Request(request_id, _) => *request_id == id,
+            _ => false,
+        });
+        if let Some(position) = position {
+            self.messages.remove(position);
+            let response = lsp_server::Response::new_err(
+                id,
+                lsp_server::ErrorCode::RequestCanceled as i32,
+                "request cancelled".into(),
+            );
+            conn.sender.send(response.into()).expect("send message");
+        }
+    }
+
     fn handle_user_pause(&mut self) -> Action {
         self.messages.push(Message::CompileProject);
         return Action::Handle(self.take_messages());
     }
 
+    fn has_requests(&self) -> bool {
+        self.messages
+            .iter()
+            .any(|message| matches!(message, Message::Request(..)))
+    }
+
     fn take_messages(&mut self) -> Vec<Message> {
         std::mem::take(&mut self.messages)
     }
 }
 
 fn extract_request(request: lsp_server::Request) -> Option<Message> {
-    use request::{Completion, Formatting, GotoDefinition, HoverRequest};
+    use request::SignatureHelpRequest;
+    use request::{CodeActionRequest, Completion, Formatting, GotoDefinition, GotoImplementation};
+    use request::{HoverRequest, InlayHintRequest, RangeFormatting, References};
 
     let id = request.id.clone();
     let request = match request.method.as_str() {
@@ -103,14 +165,38 @@ fn extract_request(request: lsp_server::Request) -> Option<Message> {
             let params = cast_request::<GotoDefinition>(request);
             Request::GotoDefinition(params)
         }
+        References::METHOD => {
+            let params = cast_request::<References>(request);
+            Request::References(params)
+        }
+        GotoImplementation::METHOD => {
+            let params = cast_request::<GotoImplementation>(request);
+            Request::Implementation(params)
+        }
         Formatting::METHOD => {
             let params = cast_request::<Formatting>(request);
             Request::Format(params)
         }
+        RangeFormatting::METHOD => {
+            let params = cast_request::<RangeFormatting>(request);
+            Request::FormatRange(params)
+        }
         HoverRequest::METHOD => {
             let params = cast_request::<HoverRequest>(request);
             Request::Hover(params)
         }
+        SignatureHelpRequest::METHOD => {
+            let params = cast_request::<SignatureHelpRequest>(request);
+            Request::SignatureHelp(params)
+        }
+        InlayHintRequest::METHOD => {
+            let params = cast_request::<InlayHintRequest>(request);
+            Request::InlayHint(params)
+        }
+        CodeActionRequest::METHOD => {
+            let params = cast_request::<CodeActionRequest>(request);
+            Request::CodeAction(params)
+        }
         _ => return None,
     };
     Some(Message::Request(id, request))
@@ -118,6 +204,7 @@ fn extract_request(request: lsp_server::Request) -> Option<Message> {
 
 fn extract_notification(notification: lsp_server::Notification) -> Option<Message> {
     use notification::{DidChangeTextDocument, DidCloseTextDocument, DidOpenTextDocument};
+    use notification::{DidDeleteFiles, DidRenameFiles};
 
     let notification = match notification.method.as_str() {
         DidOpenTextDocument::METHOD => {
@@ -125,13 +212,15 @@ fn extract_notification(notification: lsp_server::Notification) -> Option<Messag
             Notification::SourceFileChanged {
                 path: super::uri_to_path(&params.text_document.uri),
                 text: params.text_document.text,
+                version: params.text_document.version,
             }
         }
         DidChangeTextDocument::METHOD => {
             let params = cast_notification::<DidChangeTextDocument>(notification);
-            Notification::SourceFileChanged {
+            Notification::SourceFileEdited {
                 path: super::uri_to_path(&params.text_document.uri),
-                text: params.content_changes.into_iter().last()?.text,
+                changes: params.content_changes,
+                version: params.text_document.version,
             }
         }
         DidCloseTextDocument::METHOD => {
@@ -140,11 +229,37 @@ fn extract_notification(notification: lsp_server::Notification) -> Option<Messag
                 path: super::uri_to_path(&params.text_document.uri),
             }
         }
+        DidDeleteFiles::METHOD => {
+            let params = cast_notification::<DidDeleteFiles>(notification);
+            let paths = params.files.iter();
+            Notification::SourceFilesDeleted {
+                paths: paths
+                    .filter_map(|file| uri_str_to_path(&file.uri))
+                    .collect(),
+            }
+        }
+        DidRenameFiles::METHOD => {
+            let params = cast_notification::<DidRenameFiles>(notification);
+            let paths = params.files.iter().filter_map(|file| {
+                Some((
+                    uri_str_to_path(&file.old_uri)?,
+                    uri_str_to_path(&file.new_uri)?,
+                ))
+            });
+            Notification::SourceFilesRenamed {
+                paths: paths.collect(),
+            }
+        }
         _ => return None,
     };
     Some(Message::Notification(notification))
 }
 
+// file operation params store uris as plain strings
+fn uri_str_to_path(uri: &str) -> Option<PathBuf> {
+    lsp::Url::parse(uri).ok()?.to_file_path().ok()
+}
+
 fn cast_request<R>(request: lsp_server::Request) -> R::Params
 where
     R: RequestTrait,
@@ -0,0 +1,218 @@
+use super::Analysis;
+use lsp_server::Connection;
+use lsp_types as lsp;
+use rock_core::driver::CancelToken;
+use rock_core::error::ice;
+use rock_core::index::{FileIndex, SymbolIndex};
+use rock_core::vfs::MemoryFs;
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::mpsc::{self, Receiver, Sender, TryRecvError};
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+
+/// runs package analysis on a background thread,
+/// requests are answered from the latest completed snapshot meanwhile
+pub struct Worker {
+    tasks: Sender<Task>,
+    running: CancelToken,
+    snapshots: Snapshots,
+}
+
+/// latest completed results of each package, keyed by package root directory
+type Snapshots = Arc<Mutex<HashMap<PathBuf, Snapshot>>>;
+
+#[derive(Default)]
+struct Snapshot {
+    analysis: Option<Arc<Analysis>>,
+    /// read from `build/index.toml` until the first check completes
+    index: Option<Arc<SymbolIndex>>,
+}
+
+/// in-memory files and document versions at the time the task was sent
+struct Task {
+    root_dirs: Vec<PathBuf>,
+    files_in_memory: MemoryFs,
+    versions: HashMap<PathBuf, i32>,
+    cancel: CancelToken,
+}
+
+impl Worker {
+    pub fn spawn(conn: &Connection) -> Worker {
+        let (tasks, receiver) = mpsc::channel();
+        let snapshots = Snapshots::default();
+        let sender = conn.sender.clone();
+        let send = move |message: lsp_server::Message| sender.send(message).expect("send message");
+
+        let worker_snapshots = snapshots.clone();
+        std::thread::spawn(move || worker_loop(receiver, worker_snapshots, send));
+        Worker {
+            tasks,
+            running: CancelToken::new(),
+            snapshots,
+        }
+    }
+
+    /// running analysis is superseded and cancelled,
+    /// its unfinished packages are analyzed again with the new files
+    pub fn analyze(
+        &mut self,
+        root_dirs: Vec<PathBuf>,
+        files_in_memory: &MemoryFs,
+        versions: &HashMap<PathBuf, i32>,
+    ) {
+        let superseded = std::mem::replace(&mut self.running, CancelToken::new());
+        let task = Task {
+            root_dirs,
+            files_in_memory: files_in_memory.clone(),
+            versions: versions.clone(),
+            cancel: self.running.clone(),
+        };
+        // task is sent first, cancelled worker always finds a newer task
+        self.tasks.send(task).expect("send task");
+        superseded.cancel();
+    }
+
+    pub fn analysis(&self, root_dir: &PathBuf) -> Option<Arc<Analysis>> {
+        let snapshots = self.snapshots.lock().expect("snapshots lock");
+        snapshots.get(root_dir)?.analysis.clone()
+    }
+
+    pub fn index(&self, root_dir: &PathBuf) -> Option<Arc<SymbolIndex>> {
+        let mut snapshots = self.snapshots.lock().expect("snapshots lock");
+        let snapshot = snapshots.entry(root_dir.clone()).or_default();
+        if snapshot.index.is_none() {
+            snapshot.index = SymbolIndex::read(root_dir).map(Arc::new);
+        }
+        snapshot.index.clone()
+    }
+}
+
+impl Drop for Worker {
+    fn drop(&mut self) {
+        self.running.cancel();
+    }
+}
+
+fn worker_loop(tasks: Receiver<Task>, snapshots: Snapshots, send: impl Fn(lsp_server::Message)) {
+    let mut pending: Vec<PathBuf> = Vec::new();
+    let mut published: HashMap<PathBuf, Vec<lsp::Url>> = HashMap::new();
+    let mut current = None;
+
+    loop {
+        if pending.is_empty() {
+            match tasks.recv() {
+                Ok(task) => receive(task, &mut pending, &mut current),
+                Err(_) => return,
+            }
+        }
+        // only the newest files are analyzed, queued tasks are merged
+        loop {
+            match tasks.try_recv() {
+                Ok(task) => receive(task, &mut pending, &mut current),
+                Err(TryRecvError::Empty) => break,
+                Err(TryRecvError::Disconnected) => return,
+            }
+        }
+        let task: &Task = match &current {
+            Some(task) => task,
+            None => continue,
+        };
+
+        let root_dir = pending[0].clone();
+        let start_time = Instant::now();
+        let result = ice::catch(|| {
+            super::run_diagnostics(
+                &root_dir,
+                &task.files_in_memory,
+                &task.versions,
+                &task.cancel,
+            )
+        });
+        if task.cancel.is_cancelled() {
+            continue;
+        }
+        pending.remove(0);
+
+        let publish_diagnostics = match result {
+            Ok(Some((publish_diagnostics, analysis, messages, index_files))) => {
+                let mut snapshots = snapshots.lock().expect("snapshots lock");
+                let snapshot = snapshots.entry(root_dir.clone()).or_default();
+                snapshot.analysis = Some(Arc::new(analysis));
+                if let Some(files) = index_files {
+                    snapshot.index = update_index(&root_dir, snapshot.index.take(), files);
+                }
+                drop(snapshots);
+                for message in messages.iter() {
+                    show_error_message(&send, message);
+                }
+                publish_diagnostics
+            }
+            Ok(None) => {
+                let mut snapshots = snapshots.lock().expect("snapshots lock");
+                snapshots.remove(&root_dir);
+                Vec::new()
+            }
+            Err(ice) => {
+                let message = ice.into_error().diagnostic().message().as_str().to_string();
+                show_error_message(&send, &message);
+                continue;
+            }
+        };
+        let elapsed_time = start_time.elapsed();
+        eprintln!(
+            "run diagnostics `{}`: {} ms",
+            root_dir.to_string_lossy(),
+            elapsed_time.as_secs_f64() * 1000.0
+        );
+
+        // deleted or renamed files are no longer compiled, clear their stale diagnostics
+        let urls: Vec<lsp::Url> = publish_diagnostics
+            .iter()
+            .map(|publish| publish.uri.clone())
+            .collect();
+        for url in published.remove(&root_dir).unwrap_or_default() {
+            if !urls.contains(&url) {
+                let cleared = lsp::PublishDiagnosticsParams::new(url, Vec::new(), None);
+                send(super::publish_notification(&cleared).into());
+            }
+        }
+        published.insert(root_dir, urls);
+
+        for publish in publish_diagnostics.iter() {
+            send(super::publish_notification(publish).into());
+        }
+    }
+}
+
+// index is written only when files changed since the last check
+fn update_index(
+    root_dir: &PathBuf,
+    index: Option<Arc<SymbolIndex>>,
+    files: Vec<FileIndex>,
+) -> Option<Arc<SymbolIndex>> {
+    let mut index = match index {
+        Some(index) => index,
+        None => Arc::new(SymbolIndex::read(root_dir).unwrap_or_default()),
+    };
+    if Arc::make_mut(&mut index).update(files) {
+        if let Err(error) = index.write(root_dir) {
+            eprintln!("{}", error.diagnostic().message().as_str());
+        }
+    }
+    Some(index)
+}
+
+fn receive(task: Task, pending: &mut Vec<PathBuf>, current: &mut Option<Task>) {
+    for root_dir in task.root_dirs.iter() {
+        if !pending.contains(root_dir) {
+            pending.push(root_dir.clone());
+        }
+    }
+    *current = Some(task);
+}
+
+fn show_error_message(send: &impl Fn(lsp_server::Message), message: &str) {
+    eprintln!("{message}");
+    send(super::error_message_notification(message).into());
+}
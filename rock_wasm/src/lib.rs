@@ -0,0 +1,85 @@
+use rock_core::driver::{CompileOptions, Stage};
+use rock_core::embed::{self, CompileResult};
+use rock_core::error::DiagnosticSeverity;
+use rock_core::target::TargetTriple;
+use serde_json::{json, Value};
+use wasm_bindgen::prelude::*;
+
+/// check `source` as the only module `main.rock` of a library package,
+/// `target` is an llvm target triple, empty string selects the default target
+///
+/// returns a json array of diagnostics, objects are same as `--message-format json`:
+/// {"severity","code","message","contexts":[{"severity","message","path","range":[start,end],"line","col"}]}
+#[wasm_bindgen]
+pub fn check(source: &str, target: &str) -> String {
+    let target = if target.is_empty() {
+        TargetTriple::host()
+    } else {
+        match TargetTriple::from_name(target) {
+            Some(target) => target,
+            None => {
+                let error = json!({
+                    "severity": "error",
+                    "code": null,
+                    "message": format!("unknown target `{target}`"),
+                    "contexts": [],
+                });
+                return Value::Array(vec![error]).to_string();
+            }
+        }
+    };
+    let options = CompileOptions::new(Stage::Check, target);
+    let result = embed::compile_str(&[("main.rock", source)], &options);
+    diagnostics_json(&result)
+}
+
+fn diagnostics_json(result: &CompileResult) -> String {
+    let diagnostics = result
+        .diagnostics
+        .iter()
+        .map(|message| {
+            let contexts: Vec<Value> = message
+                .contexts
+                .iter()
+                .map(|context| {
+                    json!({
+                        "severity": severity_name(context.severity),
+                        "message": context.message,
+                        "path": context.path.to_string_lossy(),
+                        "range": [context.start, context.end],
+                        "line": context.line,
+                        "col": context.col,
+                    })
+                })
+                .collect();
+            json!({
+                "severity": severity_name(message.severity),
+                "code": message.code,
+                "message": message.message,
+                "contexts": contexts,
+            })
+        })
+        .collect();
+    Value::Array(diagnostics).to_string()
+}
+
+const fn severity_name(severity: DiagnosticSeverity) -> &'static str {
+    match severity {
+        DiagnosticSeverity::Info => "info",
+        DiagnosticSeverity::Error => "error",
+        DiagnosticSeverity::Warning => "warning",
+    }
+}
+
+#[test]
+fn test_check_json() {
+    let output = check("pub proc value() -> s32 {\n    return true;\n}\n", "");
+    let diagnostics: Value = serde_json::from_str(&output).unwrap();
+    let error = &diagnostics[0];
+    assert!(error["code"] == "E0038");
+    assert!(error["contexts"][0]["path"] == "src/main.rock");
+    assert!(error["contexts"][0]["line"] == 2 && error["contexts"][0]["col"] == 12);
+
+    let output = check("", "wasm32-unknown-unknown");
+    assert!(output.contains("unknown target `wasm32-unknown-unknown`"));
+}